@@ -0,0 +1,92 @@
+-- 031: 广播频道（订阅号 / 公告频道）持久化
+--
+-- 一对多：只有频道主/管理员能发布，订阅者只读。时间线沿用现有机制——
+-- 发布走 MessageService 写 privchat_messages + commit_log + dispatch outbox，
+-- 所以广播频道必须是 privchat_channels 里的一行（channel_type = 2，对应 wire 3）。
+--
+-- 🔴 成员存 privchat_channel_participants（role 0/1/2 = 主/管理员/订阅者）。
+-- dispatch 快照对 channel_type NOT IN (0, 1) 本来就读这张表，不需要另开投递路径。
+--
+-- 广播频道没有 direct 双方也没有 group_id，001 的 CHECK 只认 0/1 两种形态，需放宽。
+
+ALTER TABLE privchat_channels DROP CONSTRAINT IF EXISTS privchat_channels_check;
+ALTER TABLE privchat_channels
+    ADD CONSTRAINT privchat_channels_check CHECK (
+        ((channel_type = 0) AND (direct_user1_id IS NOT NULL) AND (direct_user2_id IS NOT NULL) AND (group_id IS NULL))
+        OR ((channel_type = 1) AND (group_id IS NOT NULL) AND (direct_user1_id IS NULL) AND (direct_user2_id IS NULL))
+        OR ((channel_type = 2) AND (group_id IS NULL) AND (direct_user1_id IS NULL) AND (direct_user2_id IS NULL))
+    );
+
+-- 频道资料。subscriber_count 与 participants 在同一事务里维护，列表页不必 COUNT(*)。
+CREATE TABLE IF NOT EXISTS privchat_broadcast_channels (
+    channel_id BIGINT PRIMARY KEY
+        REFERENCES privchat_channels(channel_id) ON DELETE CASCADE,
+    owner_id BIGINT NOT NULL,
+    name VARCHAR(128) NOT NULL,
+    description TEXT,
+    avatar_url TEXT,
+    subscriber_count BIGINT NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_privchat_broadcast_channels_owner
+    ON privchat_broadcast_channels (owner_id);
+
+-- 频道消息的广播专属属性（标题 / 紧急 / 过期）。
+-- 正文、类型、metadata、pts 的真源仍是 privchat_messages（message_id 相同），
+-- 这里不再复制一份，撤回/删除也只需改一处。
+CREATE TABLE IF NOT EXISTS privchat_channel_messages (
+    message_id BIGINT PRIMARY KEY,
+    channel_id BIGINT NOT NULL
+        REFERENCES privchat_broadcast_channels(channel_id) ON DELETE CASCADE,
+    publisher_id BIGINT,
+    title VARCHAR(256),
+    is_urgent BOOLEAN NOT NULL DEFAULT FALSE,
+    expires_at BIGINT,
+    published_at BIGINT NOT NULL DEFAULT now_millis(),
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_privchat_channel_messages_channel
+    ON privchat_channel_messages (channel_id, message_id DESC);
+
+-- 订阅者对单条频道消息的已读 / 忽略状态。按用户一行，device_id 记录最后操作的设备。
+CREATE TABLE IF NOT EXISTS privchat_channel_message_status (
+    message_id BIGINT NOT NULL
+        REFERENCES privchat_channel_messages(message_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    device_id VARCHAR(128) NOT NULL DEFAULT '',
+    is_read BOOLEAN NOT NULL DEFAULT FALSE,
+    is_dismissed BOOLEAN NOT NULL DEFAULT FALSE,
+    read_at BIGINT,
+    dismissed_at BIGINT,
+    PRIMARY KEY (message_id, user_id)
+);
+
+-- 与 016 对群成员做的一样：订阅关系变化推进 membership_version，
+-- 并与发布时 FOR UPDATE 锁住的频道行串行化，快照不会读到半途的订阅变更。
+CREATE OR REPLACE FUNCTION privchat_bump_participant_membership_version()
+RETURNS TRIGGER AS $$
+DECLARE
+    target_channel_id BIGINT;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        target_channel_id := OLD.channel_id;
+    ELSE
+        target_channel_id := NEW.channel_id;
+    END IF;
+    UPDATE privchat_channels
+    SET membership_version = membership_version + 1
+    WHERE channel_id = target_channel_id AND channel_type = 2;
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_privchat_participant_membership_version ON privchat_channel_participants;
+CREATE TRIGGER trg_privchat_participant_membership_version
+AFTER INSERT OR DELETE OR UPDATE OF left_at ON privchat_channel_participants
+FOR EACH ROW EXECUTE FUNCTION privchat_bump_participant_membership_version();
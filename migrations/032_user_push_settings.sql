@@ -0,0 +1,12 @@
+-- 032: 用户级推送偏好
+--
+-- 推送文案按接收者渲染：locale 决定模板语言，hide_preview 打开后通知里
+-- 不出现发送者、群名和正文，只显示「你收到一条新消息」。
+-- 没有行 = 默认值（zh-CN，显示预览），planner 不需要为每个用户预先写入。
+
+CREATE TABLE IF NOT EXISTS privchat_user_push_settings (
+    user_id BIGINT PRIMARY KEY,
+    locale VARCHAR(16) NOT NULL DEFAULT 'zh-CN',
+    hide_preview BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
@@ -0,0 +1,26 @@
+-- 033: 推送策略——会话通知级别 + 免打扰时段
+--
+-- notify_level：用户对单个会话的通知级别，0 = 全部，1 = 仅 @我 / @全体成员，2 = 不通知。
+-- 🔴 is_muted 是旧字段，与 notify_level = 2 同步维护（mute_channel / set_channel_notify_level
+-- 都会同时写两列），这里先按 is_muted 回填。
+--
+-- dnd_*：用户级免打扰时段（按用户时区的「一天中的分钟数」，start > end 表示跨零点，
+-- 如 22:00-08:00 = 1320-480）。时段内的通知降级为静默推送（不响铃、不亮屏），
+-- 角标照常更新；会话级 notify_level 的过滤优先于免打扰。
+
+ALTER TABLE privchat_user_channels
+    ADD COLUMN IF NOT EXISTS notify_level SMALLINT NOT NULL DEFAULT 0;
+
+UPDATE privchat_user_channels SET notify_level = 2 WHERE is_muted = TRUE AND notify_level = 0;
+
+ALTER TABLE privchat_user_push_settings
+    ADD COLUMN IF NOT EXISTS dnd_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS dnd_start_minute SMALLINT NOT NULL DEFAULT 1320,
+    ADD COLUMN IF NOT EXISTS dnd_end_minute SMALLINT NOT NULL DEFAULT 480,
+    ADD COLUMN IF NOT EXISTS timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+
+ALTER TABLE privchat_user_push_settings DROP CONSTRAINT IF EXISTS privchat_user_push_settings_dnd_range;
+ALTER TABLE privchat_user_push_settings
+    ADD CONSTRAINT privchat_user_push_settings_dnd_range CHECK (
+        dnd_start_minute BETWEEN 0 AND 1439 AND dnd_end_minute BETWEEN 0 AND 1439
+    );
@@ -0,0 +1,19 @@
+-- 034: 管理员手动封禁（Shadow Ban）持久化
+--
+-- 自动处罚（限流升级、信任分）放在 Redis，按 TTL 自然过期；管理员手动封禁是明确的运营决定，
+-- 不能因为 Redis 重启 / 淘汰而消失，所以另存一份真源，服务启动时回灌到 Redis。
+-- device_id 为具体设备；对用户全部设备封禁时每台设备一行。
+-- expires_at 为 NULL 表示无期限，直到管理员解封。
+
+CREATE TABLE IF NOT EXISTS privchat_security_bans (
+    user_id BIGINT NOT NULL,
+    device_id VARCHAR(128) NOT NULL,
+    violation VARCHAR(32) NOT NULL,
+    note TEXT,
+    expires_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    PRIMARY KEY (user_id, device_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_privchat_security_bans_expires
+    ON privchat_security_bans (expires_at);
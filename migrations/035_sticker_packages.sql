@@ -0,0 +1,59 @@
+-- 035: 表情包库持久化
+--
+-- 表情包目录原先硬编码在 StickerService（cdn.example.com 占位 URL），改为 PostgreSQL 真源，
+-- 由管理 API 维护。图片走 FileService 存储源（privchat_file_uploads，business_type = 'sticker'），
+-- 这里只存 file_id，访问 URL 在读取时按存储源 base_url 拼出，切换 CDN 不必回写目录。
+--
+-- package_id / sticker_id 保持字符串：客户端消息 metadata 里存的就是它们。
+
+CREATE TABLE IF NOT EXISTS privchat_sticker_packages (
+    package_id VARCHAR(64) PRIMARY KEY,
+    name VARCHAR(128) NOT NULL,
+    author VARCHAR(128) NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    thumbnail_file_id BIGINT,
+    -- 下架的库不出现在客户端列表里，已发出的消息也不再允许引用其中的表情
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE TABLE IF NOT EXISTS privchat_stickers (
+    sticker_id VARCHAR(64) PRIMARY KEY,
+    package_id VARCHAR(64) NOT NULL
+        REFERENCES privchat_sticker_packages(package_id) ON DELETE CASCADE,
+    file_id BIGINT NOT NULL,
+    alt_text VARCHAR(64) NOT NULL DEFAULT '',
+    emoji VARCHAR(32),
+    width INTEGER NOT NULL DEFAULT 0,
+    height INTEGER NOT NULL DEFAULT 0,
+    mime_type VARCHAR(128) NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_privchat_stickers_package
+    ON privchat_stickers (package_id, sort_order, sticker_id);
+
+-- 用户收藏的表情包库（「我的表情」）
+CREATE TABLE IF NOT EXISTS privchat_user_sticker_packages (
+    user_id BIGINT NOT NULL,
+    package_id VARCHAR(64) NOT NULL
+        REFERENCES privchat_sticker_packages(package_id) ON DELETE CASCADE,
+    added_at BIGINT NOT NULL DEFAULT now_millis(),
+    PRIMARY KEY (user_id, package_id)
+);
+
+-- 最近使用：发送 sticker 消息成功后记录，每个用户只保留最近 N 条（由服务层裁剪）
+CREATE TABLE IF NOT EXISTS privchat_user_recent_stickers (
+    user_id BIGINT NOT NULL,
+    sticker_id VARCHAR(64) NOT NULL
+        REFERENCES privchat_stickers(sticker_id) ON DELETE CASCADE,
+    use_count INTEGER NOT NULL DEFAULT 1,
+    last_used_at BIGINT NOT NULL DEFAULT now_millis(),
+    PRIMARY KEY (user_id, sticker_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_privchat_user_recent_stickers_user
+    ON privchat_user_recent_stickers (user_id, last_used_at DESC);
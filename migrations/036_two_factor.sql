@@ -0,0 +1,30 @@
+-- 036: 内置账号两步验证（TOTP，RFC 6238）
+--
+-- 仅 account.mode = builtin 使用：密码校验通过后，已启用 2FA 的账号还需提交验证器 App 的
+-- 6 位动态码（或一次性恢复码）才签发 token。
+-- secret 为 base32 文本；setup 时写入 enabled = FALSE，用户用动态码确认后才置 TRUE。
+-- last_used_step 记录最近一次通过校验的时间步，同一个动态码不能重放。
+
+CREATE TABLE IF NOT EXISTS privchat_user_totp (
+    user_id BIGINT PRIMARY KEY
+        REFERENCES privchat_users(user_id) ON DELETE CASCADE,
+    secret VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    last_used_step BIGINT NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    confirmed_at BIGINT
+);
+
+-- 恢复码只存 SHA-256 摘要；used_at 非空即已用过
+CREATE TABLE IF NOT EXISTS privchat_user_recovery_codes (
+    user_id BIGINT NOT NULL
+        REFERENCES privchat_users(user_id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    PRIMARY KEY (user_id, code_hash)
+);
+
+-- 登录日志：本次登录所用 token 是否经过两步验证签发
+ALTER TABLE privchat_login_logs
+    ADD COLUMN IF NOT EXISTS mfa_used BOOLEAN NOT NULL DEFAULT FALSE;
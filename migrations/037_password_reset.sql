@@ -0,0 +1,20 @@
+-- 037: 内置账号找回密码（一次性重置令牌）
+--
+-- 仅 account.mode = builtin 使用：用户提交用户名 / 邮箱 / 手机号后，server 生成一次性令牌，
+-- 经 PasswordResetSender（邮件 / 短信，开发环境为本地日志 / 文件）投递；用户提交令牌与新密码完成重置。
+-- 令牌只存 SHA-256 摘要；used_at 非空即已用过，过期或已用的令牌不能再次使用。
+-- 同一用户再次申请时，旧的未使用令牌全部作废。
+
+CREATE TABLE IF NOT EXISTS privchat_password_reset_tokens (
+    token_hash VARCHAR(64) PRIMARY KEY,
+    user_id BIGINT NOT NULL
+        REFERENCES privchat_users(user_id) ON DELETE CASCADE,
+    -- 投递渠道：email / sms
+    channel VARCHAR(16) NOT NULL,
+    expires_at BIGINT NOT NULL,
+    used_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user
+    ON privchat_password_reset_tokens (user_id, created_at DESC);
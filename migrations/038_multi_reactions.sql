@@ -0,0 +1,25 @@
+-- 038: 一条消息允许多个 Reaction + 自定义表情 Reaction + 群 Reaction 白名单
+--
+-- 原主键 (message_id, user_id) 限定每人每条消息只有一个 Reaction，再次添加会覆盖。
+-- 改为 (message_id, user_id, emoji)：同一用户可对一条消息添加多个不同的 Reaction，
+-- 个数上限由服务层控制（ReactionService::MAX_REACTIONS_PER_USER）。
+--
+-- emoji 列除 Unicode emoji 外还存自定义表情 `sticker:<sticker_id>`（sticker_id 最长 64），
+-- 长度放宽到 80。
+
+ALTER TABLE privchat_message_reactions
+    ALTER COLUMN emoji TYPE VARCHAR(80);
+
+ALTER TABLE privchat_message_reactions
+    DROP CONSTRAINT IF EXISTS privchat_message_reactions_pkey;
+ALTER TABLE privchat_message_reactions
+    ADD CONSTRAINT privchat_message_reactions_pkey PRIMARY KEY (message_id, user_id, emoji);
+
+-- 群 Reaction 白名单（群主 / 管理员设置）。
+-- 无记录 = 不限制；allowed_reactions 为空数组 = 本群关闭 Reaction。
+CREATE TABLE IF NOT EXISTS privchat_channel_reaction_settings (
+    channel_id BIGINT PRIMARY KEY,
+    allowed_reactions TEXT[] NOT NULL DEFAULT '{}',
+    updated_by BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
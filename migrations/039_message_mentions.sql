@@ -0,0 +1,23 @@
+-- 039: @提及持久化
+--
+-- 原 MentionService 把提及记录放在进程内 HashMap，重启即丢、多节点各记各的。
+-- 改为每条「消息 × 被@用户」一行；user_id = 0 表示 @全体成员（不展开成员列表，
+-- 查询时按当前在群成员身份匹配，后入群的人不会看到入群前的 @全体成员）。
+--
+-- 未读判定不另存状态：pts > privchat_channel_read_cursor.last_read_pts 即未读，
+-- 已读推进后自然清零。
+-- message_created_at 是 privchat_messages 的分区键，用于回表过滤已撤回/已删除消息时裁剪分区。
+
+CREATE TABLE IF NOT EXISTS privchat_message_mentions (
+    message_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    sender_id BIGINT NOT NULL,
+    pts BIGINT NOT NULL,
+    message_created_at BIGINT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    PRIMARY KEY (message_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_mentions_user_channel_pts
+    ON privchat_message_mentions (user_id, channel_id, pts DESC);
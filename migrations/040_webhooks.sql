@@ -0,0 +1,52 @@
+-- 040: 出站 Webhook 订阅 + 投递日志
+--
+-- server_event 通道只有一个下游（application_url + 共享 X-Service-Key），失败即丢。
+-- Webhook 面向管理员登记的多个外部端点：每个端点按 event_types 订阅事件，
+-- 投递体用端点自己的 secret 做 HMAC-SHA256 签名。
+--
+-- 每个「事件 × 订阅端点」一行投递记录，body 在入队时定稿（重试 / 重放签的是同一份字节）。
+-- status：pending（待投递 / 重试中）→ succeeded | dead（超过最大尝试次数，死信）。
+-- 领取投递时把 next_attempt_at 推后一个租约期，节点中途崩溃的投递租约到期后自动重新可领。
+
+CREATE TABLE IF NOT EXISTS privchat_webhook_endpoints (
+    endpoint_id BIGSERIAL PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    -- 订阅的事件类型；'*' 表示全部
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    description TEXT NOT NULL DEFAULT '',
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE TABLE IF NOT EXISTS privchat_webhook_deliveries (
+    delivery_id BIGSERIAL PRIMARY KEY,
+    endpoint_id BIGINT NOT NULL
+        REFERENCES privchat_webhook_endpoints (endpoint_id) ON DELETE CASCADE,
+    -- 同一事件投给多个端点时共享 event_id，接收方据此幂等
+    event_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    body TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'succeeded', 'dead')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at BIGINT NOT NULL DEFAULT now_millis(),
+    last_status_code INT,
+    last_error TEXT,
+    delivered_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+-- worker 领取到期投递
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON privchat_webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+
+-- 管理 API：按状态（死信视图）/ 端点倒序翻页
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status
+    ON privchat_webhook_deliveries (status, delivery_id DESC);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_endpoint
+    ON privchat_webhook_deliveries (endpoint_id, delivery_id DESC);
@@ -0,0 +1,46 @@
+-- 041: Bot 平台 API
+--
+-- Bot 仍是 user_type = 2 的普通账号；这里补齐 Bot 对外 API 需要的三张表：
+--   - privchat_bot_tokens    Bot API 令牌（管理员签发，只存 SHA-256 摘要，可多把并存 / 单独吊销）
+--   - privchat_bot_commands  Bot 注册的斜杠命令列表（客户端拉取展示）
+--   - privchat_bot_updates   getUpdates 长轮询队列（收到的消息 / 按钮回调），offset 确认后删除，
+--                            未确认的保留 24 小时
+-- 内联按钮本身随消息 metadata.reply_markup 落在 privchat_messages，不另建表。
+
+CREATE TABLE IF NOT EXISTS privchat_bot_tokens (
+    token_id BIGSERIAL PRIMARY KEY,
+    bot_user_id BIGINT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    -- 令牌前若干位，便于管理员辨认是哪一把
+    token_prefix TEXT NOT NULL,
+    label TEXT NOT NULL DEFAULT '',
+    last_used_at BIGINT,
+    revoked_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bot_tokens_bot
+    ON privchat_bot_tokens (bot_user_id);
+
+CREATE TABLE IF NOT EXISTS privchat_bot_commands (
+    bot_user_id BIGINT NOT NULL,
+    command TEXT NOT NULL,
+    description TEXT NOT NULL,
+    sort_order INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (bot_user_id, command)
+);
+
+CREATE TABLE IF NOT EXISTS privchat_bot_updates (
+    update_id BIGSERIAL PRIMARY KEY,
+    bot_user_id BIGINT NOT NULL,
+    -- message | callback_query
+    update_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bot_updates_bot
+    ON privchat_bot_updates (bot_user_id, update_id);
+
+CREATE INDEX IF NOT EXISTS idx_bot_updates_created
+    ON privchat_bot_updates (created_at);
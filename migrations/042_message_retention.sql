@@ -0,0 +1,30 @@
+-- 042: 消息保留期（retention）
+--
+-- 平台默认保留期来自配置 [message] retention_secs（0 = 永久保留）；
+-- 群主 / 私聊双方可以为单个会话设置更短的保留期，存在本表。
+-- 有效保留期 = min(平台默认, 会话覆盖)，会话覆盖不能突破平台上限（合规要求）。
+--
+-- 后台清理任务按会话删除超期消息（级联删掉 privchat_message_file_refs，文件引用释放后
+-- 交由文件回收处理），同时删除对应的 commit log、@提及、Reaction、送达回执、置顶与
+-- 离线队列行，并在 commit log 追加一条 `channel.history_expired` 通知客户端清理本地缓存。
+
+CREATE TABLE IF NOT EXISTS privchat_channel_retention (
+    channel_id BIGINT PRIMARY KEY,
+    retention_secs BIGINT NOT NULL CHECK (retention_secs > 0),
+    updated_by BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+-- 离线队列按会话 + 消息清理
+CREATE INDEX IF NOT EXISTS idx_offline_message_queue_channel_msg
+    ON privchat_offline_message_queue (channel_id, server_msg_id);
+
+-- 待发送的 history_expired 通知：与每批删除同事务登记，通知写入 commit log 后才删除。
+-- 删完消息后进程崩溃或写 commit 失败，下一轮清理仍会补发——不然客户端永远留着已过期的本地消息。
+CREATE TABLE IF NOT EXISTS privchat_retention_pending_notices (
+    channel_id BIGINT PRIMARY KEY,
+    channel_type SMALLINT NOT NULL,
+    expired_before BIGINT NOT NULL,
+    expired_up_to_pts BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
@@ -0,0 +1,46 @@
+-- 043: 阅后即焚 / 定时消失消息
+--
+-- 发送方可以为单条消息设置 TTL（消息 metadata.self_destruct），私聊双方或群主 / 管理员
+-- 也可以为会话设置默认计时器（本迁移的 privchat_channel_disappearing），新消息未单独
+-- 指定时按会话计时器处理。
+--
+-- 计时起点两种：
+--   - start_on_read = false：发送即开始倒计时，expires_at 在写入时确定；
+--   - start_on_read = true ：接收方首次已读（read_pts 越过消息 pts）时才开始，
+--     expires_at 在已读游标推进时回填，之前为 NULL。
+--
+-- 到期后后台任务硬删除消息（级联删掉 privchat_message_file_refs）、对应 commit log、
+-- @提及、Reaction、送达回执、置顶与离线队列行，不再被引用的附件连同物理文件一起删除；
+-- 随后在 commit log 追加一条 `message.expired` 通知所有设备删除本地副本。
+
+CREATE TABLE IF NOT EXISTS privchat_channel_disappearing (
+    channel_id BIGINT PRIMARY KEY,
+    ttl_secs BIGINT NOT NULL CHECK (ttl_secs > 0),
+    start_on_read BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_by BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+CREATE TABLE IF NOT EXISTS privchat_message_expiry (
+    message_id BIGINT PRIMARY KEY,
+    -- 消息表按 created_at 分区，删除时带上分区键
+    message_created_at BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    pts BIGINT NOT NULL,
+    sender_id BIGINT NOT NULL,
+    ttl_secs BIGINT NOT NULL CHECK (ttl_secs > 0),
+    start_on_read BOOLEAN NOT NULL DEFAULT FALSE,
+    -- NULL = 等待接收方首次已读
+    expires_at BIGINT,
+    created_at BIGINT NOT NULL DEFAULT now_millis()
+);
+
+-- 后台清理按到期时间扫描
+CREATE INDEX IF NOT EXISTS idx_message_expiry_expires_at
+    ON privchat_message_expiry (expires_at)
+    WHERE expires_at IS NOT NULL;
+
+-- 已读游标推进时按会话 + pts 找待启动的倒计时
+CREATE INDEX IF NOT EXISTS idx_message_expiry_pending_read
+    ON privchat_message_expiry (channel_id, pts)
+    WHERE expires_at IS NULL;
@@ -0,0 +1,52 @@
+-- 044: 用户自助注销 + 个人数据导出
+--
+-- 注销：用户发起后进入冷静期（execute_at = requested_at + deletion_grace_secs），
+-- 冷静期内可撤销（直接删行）。后台任务扫描到期行执行最终注销：吊销设备、
+-- 转让 / 退出所拥有的群、按配置策略处理已发送消息、释放上传文件并匿名化用户行，
+-- 完成后写 completed_at 留档，用户名 / 手机 / 邮箱在匿名化时释放，可被重新注册。
+--
+-- 导出：每个请求一行，status：pending → running → ready | failed；ready 的归档
+-- 过了 expires_at 后由后台删除物理文件并标记 expired。running 超过租约期视为节点
+-- 崩溃，允许重新领取。
+
+CREATE TABLE IF NOT EXISTS privchat_account_deletions (
+    user_id BIGINT PRIMARY KEY,
+    requested_at BIGINT NOT NULL DEFAULT now_millis(),
+    execute_at BIGINT NOT NULL,
+    completed_at BIGINT
+);
+
+-- 后台任务按到期时间扫描待执行的注销
+CREATE INDEX IF NOT EXISTS idx_account_deletions_due
+    ON privchat_account_deletions (execute_at)
+    WHERE completed_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS privchat_data_exports (
+    export_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'running', 'ready', 'failed', 'expired')),
+    -- 归档写入的存储源与对象路径（ready 后有值）
+    storage_source_id INT,
+    object_path TEXT,
+    size_bytes BIGINT,
+    error TEXT,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    started_at BIGINT,
+    completed_at BIGINT,
+    expires_at BIGINT
+);
+
+-- 用户查询自己的导出记录（倒序）
+CREATE INDEX IF NOT EXISTS idx_data_exports_user
+    ON privchat_data_exports (user_id, export_id DESC);
+
+-- worker 领取待处理 / 租约过期的任务
+CREATE INDEX IF NOT EXISTS idx_data_exports_pending
+    ON privchat_data_exports (created_at)
+    WHERE status IN ('pending', 'running');
+
+-- 清理过期归档
+CREATE INDEX IF NOT EXISTS idx_data_exports_expires_at
+    ON privchat_data_exports (expires_at)
+    WHERE status = 'ready';
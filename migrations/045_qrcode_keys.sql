@@ -0,0 +1,53 @@
+-- 045: 二维码 Key 与扫描日志持久化
+--
+-- 用户名片 / 群邀请等二维码此前只存在进程内存：重启即全部失效，且在 A 节点生成的
+-- Key 在 B 节点无法解析。改为 Postgres 为真源、Redis 缓存热点 Key。
+--
+-- 使用次数在解析时以条件 UPDATE 原子递增（未撤销、未过期、未达上限才 +1），
+-- 多节点并发扫描一次性 / 限次二维码不会超发。过期超过保留期的 Key 与旧扫描日志由后台清理。
+
+CREATE TABLE IF NOT EXISTS privchat_qrcode_keys (
+    qr_key TEXT PRIMARY KEY,
+    qr_type TEXT NOT NULL CHECK (qr_type IN ('user', 'group', 'auth', 'feature')),
+    target_id TEXT NOT NULL,
+    creator_id TEXT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT now_millis(),
+    expire_at BIGINT,
+    max_usage INT,
+    used_count INT NOT NULL DEFAULT 0,
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    revoked_at BIGINT,
+    -- 扩展信息（群邀请 token 等）
+    metadata JSONB NOT NULL DEFAULT '{}'::jsonb
+);
+
+-- 按目标查找当前有效 Key（刷新 / 撤销旧 Key）
+CREATE INDEX IF NOT EXISTS idx_qrcode_keys_target
+    ON privchat_qrcode_keys (target_id, created_at DESC)
+    WHERE NOT revoked;
+
+-- 列出用户创建的 Key
+CREATE INDEX IF NOT EXISTS idx_qrcode_keys_creator
+    ON privchat_qrcode_keys (creator_id, created_at DESC);
+
+-- 清理过期 Key
+CREATE INDEX IF NOT EXISTS idx_qrcode_keys_expire_at
+    ON privchat_qrcode_keys (expire_at)
+    WHERE expire_at IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS privchat_qrcode_scan_logs (
+    log_id BIGSERIAL PRIMARY KEY,
+    qr_key TEXT NOT NULL REFERENCES privchat_qrcode_keys (qr_key) ON DELETE CASCADE,
+    scanner_id TEXT NOT NULL,
+    scanned_at BIGINT NOT NULL DEFAULT now_millis(),
+    success BOOLEAN NOT NULL,
+    error TEXT
+);
+
+-- 管理端按 Key 查看扫描历史（倒序）
+CREATE INDEX IF NOT EXISTS idx_qrcode_scan_logs_key
+    ON privchat_qrcode_scan_logs (qr_key, log_id DESC);
+
+-- 清理旧扫描日志
+CREATE INDEX IF NOT EXISTS idx_qrcode_scan_logs_scanned_at
+    ON privchat_qrcode_scan_logs (scanned_at);
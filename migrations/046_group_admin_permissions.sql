@@ -0,0 +1,13 @@
+-- 046: 管理员细粒度权限与自定义头衔
+--
+-- privchat_group_members.permissions 早已存在但从未被读取：所有鉴权都只看 role。
+-- 现在它承载群主为单个管理员定制的权限集（MemberPermissions 的完整 JSON），
+-- '{}' 表示沿用角色默认；仅对 role = Admin 的行生效，群主/普通成员一律按角色计算。
+-- 角色变更（升降级）时服务端会把 permissions 重置为 '{}'、title 置空。
+
+ALTER TABLE privchat_group_members ADD COLUMN IF NOT EXISTS title VARCHAR(32);
+
+COMMENT ON COLUMN privchat_group_members.permissions IS
+    '群主为管理员定制的权限集（MemberPermissions JSON），''{}'' = 角色默认，仅 Admin 生效';
+COMMENT ON COLUMN privchat_group_members.title IS
+    '管理员自定义头衔（群主设置），非管理员恒为 NULL';
@@ -0,0 +1,11 @@
+-- 047: 群慢速模式
+--
+-- all_muted / allow_member_post 只能整体开关发言，大群里限制不了单个成员的发言频率。
+-- slow_mode_secs > 0 时普通成员两次发言之间至少间隔这么多秒（群主/管理员豁免），0 = 关闭。
+-- 间隔配置以本列为真源；每个成员的发言窗口存在 Redis（privchat:group:{gid}:slow:{uid}），多节点共享。
+
+ALTER TABLE privchat_groups
+    ADD COLUMN IF NOT EXISTS slow_mode_secs INT NOT NULL DEFAULT 0
+    CHECK (slow_mode_secs >= 0 AND slow_mode_secs <= 3600);
+
+COMMENT ON COLUMN privchat_groups.slow_mode_secs IS '慢速模式：普通成员两次发言的最小间隔（秒），0 = 关闭';
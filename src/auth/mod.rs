@@ -28,6 +28,7 @@ pub mod token_capability;
 pub mod token_issue_service;
 pub mod token_revocation;
 pub mod token_service;
+pub mod totp;
 pub mod unified_token_service;
 
 // 重新导出主要类型
@@ -37,7 +38,7 @@ pub use models::{
     Device, DeviceInfo, DeviceItem, DeviceListResponse, DeviceType, IssueTokenRequest,
     IssueTokenResponse, ServiceKeyConfig,
 };
-pub use password::{hash_password, verify_password, PASSWORD_COST};
+pub use password::{hash_password, verify_password, MIN_PASSWORD_LENGTH, PASSWORD_COST};
 pub use service_key_manager::{ServiceKeyManager, ServiceKeyStrategy};
 pub use session_state::{KickReason, KickedDevice, SessionState, SessionVerifyResult};
 pub use token::TokenAuth;
@@ -46,6 +47,7 @@ pub use token_issue_service::TokenIssueService;
 pub use token_revocation::TokenRevocationService;
 pub use token_service::{
     IssueClaims, IssuedToken, JwkRsa, JwkSet, TokenService, UnifiedTokenClaims, VerifyError,
+    AMR_OTP, AMR_PASSWORD, AMR_RECOVERY_CODE,
 };
 pub use unified_token_service::{
     IntrospectResult, IssueParams, IssueResult, RevokeRequest, UnifiedTokenService,
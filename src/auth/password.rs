@@ -29,6 +29,9 @@ use bcrypt::{hash, verify, DEFAULT_COST};
 /// - 14: 约 1200ms（高安全场景）
 pub const PASSWORD_COST: u32 = DEFAULT_COST; // 12
 
+/// 密码最小长度（注册 / 改密 / 找回密码共用）
+pub const MIN_PASSWORD_LENGTH: usize = 6;
+
 /// 加密密码
 ///
 /// 使用 bcrypt 算法将明文密码加密为哈希值
@@ -38,6 +38,13 @@ pub const TOKEN_TYPE_ACCESS: &str = "access";
 /// `typ` 字段：Refresh token。
 pub const TOKEN_TYPE_REFRESH: &str = "refresh";
 
+/// `amr` 取值（RFC 8176）：密码。
+pub const AMR_PASSWORD: &str = "pwd";
+/// `amr` 取值：TOTP 动态码。
+pub const AMR_OTP: &str = "otp";
+/// `amr` 取值：两步验证恢复码（非 RFC 8176 注册值）。
+pub const AMR_RECOVERY_CODE: &str = "rec";
+
 /// 统一 token claims（spec §4）。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnifiedTokenClaims {
@@ -54,6 +61,18 @@ pub struct UnifiedTokenClaims {
     pub scope: Vec<String>,
     pub business_system_id: String,
     pub app_id: String,
+    /// 认证方式（RFC 8176 `amr`）；旧 token 与 platform 签发的 token 没有此字段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amr: Vec<String>,
+}
+
+impl UnifiedTokenClaims {
+    /// 签发该 token 的登录是否经过两步验证
+    pub fn mfa_used(&self) -> bool {
+        self.amr
+            .iter()
+            .any(|m| m == AMR_OTP || m == AMR_RECOVERY_CODE)
+    }
 }
 
 /// 签发参数。
@@ -66,6 +85,7 @@ pub struct IssueClaims {
     pub audience: Vec<String>,
     pub business_system_id: String,
     pub app_id: String,
+    pub amr: Vec<String>,
 }
 
 /// 签发结果（含原始 token 文本 + 关键 claim 摘要）。
@@ -222,6 +242,7 @@ impl TokenService {
             scope: params.scope,
             business_system_id: params.business_system_id,
             app_id: params.app_id,
+            amr: params.amr,
         };
 
         let mut header = Header::new(self.algorithm);
@@ -340,6 +361,7 @@ impl TokenService {
             audience: self.config.default_audience.clone(),
             business_system_id: business_system_id.to_string(),
             app_id: app_id.to_string(),
+            amr: Vec::new(),
         }
     }
 
@@ -382,6 +404,48 @@ impl TokenService {
         self.issue_refresh(claims).map(|t| t.token)
     }
 
+    /// 签发 access token 字符串，附带认证方式 `amr`（内置登录 / 两步验证 / refresh 续期沿用）。
+    pub fn issue_token_with_amr(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        business_system_id: &str,
+        app_id: &str,
+        session_version: i64,
+        amr: Vec<String>,
+    ) -> Result<String> {
+        let mut claims = self.default_issue_claims(
+            user_id,
+            device_id,
+            business_system_id,
+            app_id,
+            session_version,
+        );
+        claims.amr = amr;
+        self.issue_access(claims).map(|t| t.token)
+    }
+
+    /// 签发 refresh token 字符串，附带认证方式 `amr`，续期时据此还原到新 access token。
+    pub fn issue_refresh_token_with_amr(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        business_system_id: &str,
+        app_id: &str,
+        session_version: i64,
+        amr: Vec<String>,
+    ) -> Result<String> {
+        let mut claims = self.default_issue_claims(
+            user_id,
+            device_id,
+            business_system_id,
+            app_id,
+            session_version,
+        );
+        claims.amr = amr;
+        self.issue_refresh(claims).map(|t| t.token)
+    }
+
     /// 兼容老调用：验 access token，返回 [`UnifiedTokenClaims`]。
     /// 错误映射到 [`ServerError`]（[`VerifyError::Expired`] → `TokenExpired`）。
     pub fn verify_token(&self, token: &str) -> Result<UnifiedTokenClaims> {
@@ -0,0 +1,226 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! TOTP 两步验证算法（RFC 6238 / RFC 4226）
+//!
+//! 参数固定为主流验证器 App（Google Authenticator、1Password、Authy…）的默认值：
+//! HMAC-SHA1、6 位、30 秒步长。这里只有纯函数，存储与登录流程见 `TwoFactorService`。
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// 动态码位数
+pub const TOTP_DIGITS: u32 = 6;
+/// 时间步长（秒）
+pub const TOTP_PERIOD_SECS: u64 = 30;
+/// 共享密钥字节数（RFC 4226 推荐 160 bit）
+pub const TOTP_SECRET_BYTES: usize = 20;
+/// 允许的时钟偏差（前后各几个时间步）
+pub const TOTP_SKEW_STEPS: u64 = 1;
+/// 每次生成的恢复码个数
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// 恢复码字符集：去掉 0/o、1/l/i 等容易抄错的字符
+const RECOVERY_CODE_CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// RFC 4648 base32 编码（不带 `=` 填充，otpauth URI 约定）
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// base32 解码：忽略大小写、空格与 `=` 填充；含非法字符返回 `None`
+pub fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let upper = c.to_ascii_uppercase() as u8;
+        let value = BASE32_ALPHABET.iter().position(|&b| b == upper)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(((buffer >> bits) & 0xff) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// 生成新的共享密钥（base32 文本，直接写进 otpauth URI）
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; TOTP_SECRET_BYTES];
+    rand::thread_rng().fill(&mut bytes[..]);
+    base32_encode(&bytes)
+}
+
+/// HOTP（RFC 4226）：HMAC-SHA1 + 动态截断
+pub fn hotp(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = HmacSha1::new_from_slice(secret).expect("HMAC 接受任意长度的密钥");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+/// Unix 秒所在的时间步
+pub fn time_step(unix_secs: u64) -> u64 {
+    unix_secs / TOTP_PERIOD_SECS
+}
+
+/// 指定时间的动态码
+pub fn totp_at(secret: &[u8], unix_secs: u64) -> u32 {
+    hotp(secret, time_step(unix_secs))
+}
+
+/// 校验用户输入的动态码，命中返回对应时间步（调用方据此防重放）
+///
+/// 前后各容忍 [`TOTP_SKEW_STEPS`] 个时间步，优先匹配当前步。
+pub fn verify_totp(secret: &[u8], code: &str, unix_secs: u64) -> Option<u64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let expected: u32 = code.parse().ok()?;
+
+    let current = time_step(unix_secs);
+    let mut candidates = vec![current];
+    for d in 1..=TOTP_SKEW_STEPS {
+        candidates.extend(current.checked_sub(d));
+        candidates.push(current + d);
+    }
+    candidates
+        .into_iter()
+        .find(|&step| hotp(secret, step) == expected)
+}
+
+/// 生成一组恢复码（明文只在生成时返回给用户一次，形如 `abcd-efgh`）
+pub fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (0..8)
+                .map(|_| {
+                    RECOVERY_CODE_CHARSET[rng.gen_range(0..RECOVERY_CODE_CHARSET.len())] as char
+                })
+                .collect();
+            format!("{}-{}", &raw[..4], &raw[4..])
+        })
+        .collect()
+}
+
+/// 恢复码摘要（SHA-256 hex）；输入先去掉分隔符并转小写，用户手抄时不必在意格式
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_base32_roundtrip() {
+        assert_eq!(
+            base32_encode(RFC_SECRET),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_decode("my======").unwrap(), b"f");
+        assert_eq!(
+            base32_decode("GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ").unwrap(),
+            RFC_SECRET
+        );
+        assert!(base32_decode("GEZ1").is_none());
+
+        let secret = generate_secret();
+        assert_eq!(base32_decode(&secret).unwrap().len(), TOTP_SECRET_BYTES);
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        // RFC 6238 附录 B（SHA1），取后 6 位
+        assert_eq!(totp_at(RFC_SECRET, 59), 287082);
+        assert_eq!(totp_at(RFC_SECRET, 1111111109), 81804);
+        assert_eq!(totp_at(RFC_SECRET, 1234567890), 5924);
+        assert_eq!(totp_at(RFC_SECRET, 2000000000), 279037);
+    }
+
+    #[test]
+    fn test_verify_totp_skew() {
+        let now = 1234567890;
+        let step = time_step(now);
+        let current = format!("{:06}", totp_at(RFC_SECRET, now));
+        let previous = format!("{:06}", totp_at(RFC_SECRET, now - TOTP_PERIOD_SECS));
+        let stale = format!("{:06}", totp_at(RFC_SECRET, now - 3 * TOTP_PERIOD_SECS));
+
+        assert_eq!(verify_totp(RFC_SECRET, &current, now), Some(step));
+        assert_eq!(verify_totp(RFC_SECRET, &previous, now), Some(step - 1));
+        assert_eq!(verify_totp(RFC_SECRET, &stale, now), None);
+        assert_eq!(verify_totp(RFC_SECRET, "12345", now), None);
+        assert_eq!(verify_totp(RFC_SECRET, "abcdef", now), None);
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert!(codes
+            .iter()
+            .all(|c| c.len() == 9 && c.as_bytes()[4] == b'-'));
+
+        assert_eq!(
+            hash_recovery_code("abcd-efgh"),
+            hash_recovery_code(" ABCD EFGH ")
+        );
+        assert_ne!(
+            hash_recovery_code("abcd-efgh"),
+            hash_recovery_code("abcd-efgk")
+        );
+    }
+}
@@ -209,6 +209,7 @@ impl UnifiedTokenService {
             audience: audience.clone(),
             business_system_id,
             app_id: params.device_info.app_id.clone(),
+            amr: Vec::new(),
         };
 
         // 5) 签 access + refresh
@@ -326,6 +327,7 @@ impl UnifiedTokenService {
                     audience: claims.aud.clone(),
                     business_system_id: claims.business_system_id.clone(),
                     app_id: claims.app_id.clone(),
+                    amr: claims.amr.clone(),
                 };
                 let access = self.rsa.issue_access(issue_claims).map_err(|e| {
                     ServerError::Internal(format!("TokenService access 签发失败: {}", e))
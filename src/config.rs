@@ -288,16 +288,86 @@ impl Default for AccountMode {
     }
 }
 
+/// 注销账号后其已发送消息的处理方式。
+///
+/// - [`Keep`](DeletedMessagePolicy::Keep)：保留消息内容，发送者资料匿名化为「已注销用户」
+/// - [`Redact`](DeletedMessagePolicy::Redact)：清空消息内容与附件引用，仅保留撤回占位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletedMessagePolicy {
+    Keep,
+    Redact,
+}
+
+impl Default for DeletedMessagePolicy {
+    fn default() -> Self {
+        DeletedMessagePolicy::Keep
+    }
+}
+
 /// `[account]` 配置块。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountConfig {
     pub mode: AccountMode,
+    /// 两步验证 otpauth URI 中的 issuer（验证器 App 里显示的服务名）
+    #[serde(default = "default_totp_issuer")]
+    pub totp_issuer: String,
+    /// 找回密码令牌有效期（秒）
+    #[serde(default = "default_password_reset_ttl_secs")]
+    pub password_reset_ttl_secs: u64,
+    /// 邮件 / 短信网关地址。配置后找回密码令牌以 JSON POST 到此网关投递。
+    #[serde(default)]
+    pub password_reset_gateway_url: Option<String>,
+    /// 调用网关时携带的 Bearer 令牌
+    #[serde(default)]
+    pub password_reset_gateway_token: Option<String>,
+    /// 开发开关：未配网关时改用本地投递（令牌只写入 outbox 文件）。
+    /// 🔴 outbox 中是令牌明文，生产环境不得开启。
+    #[serde(default)]
+    pub password_reset_dev_local_sender: bool,
+    /// 本地投递时追加写入的文件（JSON Lines），仅在开发开关打开时生效。
+    #[serde(default)]
+    pub password_reset_outbox: Option<String>,
+    /// 用户自助注销的冷静期（秒），期间可撤销
+    #[serde(default = "default_deletion_grace_secs")]
+    pub deletion_grace_secs: u64,
+    /// 注销完成后其已发送消息的处理方式
+    #[serde(default)]
+    pub deletion_message_policy: DeletedMessagePolicy,
+    /// 个人数据导出包的保留时长（秒），到期后删除归档文件
+    #[serde(default = "default_export_ttl_secs")]
+    pub export_ttl_secs: u64,
+}
+
+fn default_totp_issuer() -> String {
+    "PrivChat".to_string()
+}
+
+fn default_password_reset_ttl_secs() -> u64 {
+    1800
+}
+
+fn default_deletion_grace_secs() -> u64 {
+    14 * 24 * 3600
+}
+
+fn default_export_ttl_secs() -> u64 {
+    7 * 24 * 3600
 }
 
 impl Default for AccountConfig {
     fn default() -> Self {
         Self {
             mode: AccountMode::Builtin,
+            totp_issuer: default_totp_issuer(),
+            password_reset_ttl_secs: default_password_reset_ttl_secs(),
+            password_reset_gateway_url: None,
+            password_reset_gateway_token: None,
+            password_reset_dev_local_sender: false,
+            password_reset_outbox: None,
+            deletion_grace_secs: default_deletion_grace_secs(),
+            deletion_message_policy: DeletedMessagePolicy::default(),
+            export_ttl_secs: default_export_ttl_secs(),
         }
     }
 }
@@ -400,11 +470,7 @@ impl ServerConfig {
     pub fn with_redis(mut self, redis_url: String) -> Self {
         self.cache.redis = Some(RedisConfig {
             url: redis_url,
-            pool_size: 50,
-            min_idle: 10,
-            connection_timeout_secs: 5,
-            command_timeout_ms: 5000,
-            idle_timeout_secs: 300,
+            ..RedisConfig::default()
         });
         self
     }
@@ -463,6 +529,23 @@ impl ServerConfig {
             self.database.statement_timeout_ms =
                 value.parse().unwrap_or(self.database.statement_timeout_ms);
         }
+        if let Ok(value) = env::var("DATABASE_REPLICA_URLS") {
+            self.database.replica_urls = value
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(value) = env::var("PRIVCHAT_DB_REPLICA_MAX_LAG_MS") {
+            self.database.replica_max_lag_ms =
+                value.parse().unwrap_or(self.database.replica_max_lag_ms);
+        }
+        if let Ok(value) = env::var("PRIVCHAT_DB_REPLICA_CHECK_INTERVAL_SECS") {
+            self.database.replica_check_interval_secs = value
+                .parse()
+                .unwrap_or(self.database.replica_check_interval_secs);
+        }
         if let Ok(max_conn) = env::var("PRIVCHAT_MAX_CONNECTIONS") {
             self.max_connections = max_conn.parse().unwrap_or(self.max_connections);
         }
@@ -525,16 +608,28 @@ impl ServerConfig {
         // Redis 配置
         if let Ok(redis_url) = env::var("REDIS_URL") {
             self.redis_url = redis_url.clone();
-            let existing = self.cache.redis.as_ref();
             self.cache.redis = Some(RedisConfig {
                 url: redis_url,
-                pool_size: existing.map_or(50, |r| r.pool_size),
-                min_idle: existing.map_or(10, |r| r.min_idle),
-                connection_timeout_secs: existing.map_or(5, |r| r.connection_timeout_secs),
-                command_timeout_ms: existing.map_or(5000, |r| r.command_timeout_ms),
-                idle_timeout_secs: existing.map_or(300, |r| r.idle_timeout_secs),
+                ..self.cache.redis.clone().unwrap_or_default()
             });
         }
+        if let Ok(Ok(mode)) = env::var("REDIS_MODE").map(|v| v.parse::<RedisMode>()) {
+            self.cache.redis.get_or_insert_with(Default::default).mode = mode;
+        }
+        if let Ok(nodes) = env::var("REDIS_NODES") {
+            self.cache.redis.get_or_insert_with(Default::default).nodes = nodes
+                .split(',')
+                .map(str::trim)
+                .filter(|node| !node.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(master) = env::var("REDIS_SENTINEL_MASTER") {
+            self.cache
+                .redis
+                .get_or_insert_with(Default::default)
+                .sentinel_master = master;
+        }
 
         // 管理 API 端口
         if let Ok(admin_port) = env::var("PRIVCHAT_ADMIN_API_PORT") {
@@ -602,6 +697,12 @@ impl ServerConfig {
         if let Ok(access_token) = env::var("PUSH_FCM_ACCESS_TOKEN") {
             self.push.fcm.access_token = Some(access_token);
         }
+        if let Ok(path) = env::var("PUSH_FCM_SERVICE_ACCOUNT_PATH") {
+            self.push.fcm.service_account_path = Some(path);
+        }
+        if let Ok(endpoint) = env::var("PUSH_FCM_TOKEN_ENDPOINT") {
+            self.push.fcm.token_endpoint = Some(endpoint);
+        }
 
         // HMS
         if let Ok(v) = env::var("PUSH_HMS_ENABLED") {
@@ -731,6 +832,29 @@ impl ServerConfig {
             self.push.meizu.endpoint = Some(endpoint);
         }
 
+        if let Ok(v) = env::var("PUSH_INTENT_TTL_SECS") {
+            if let Ok(parsed) = v.parse::<u64>() {
+                self.push.intent_ttl_secs = parsed.max(1);
+            }
+        }
+
+        // 推送重试
+        if let Ok(v) = env::var("PUSH_RETRY_MAX_ATTEMPTS") {
+            if let Ok(parsed) = v.parse::<u32>() {
+                self.push.retry.max_attempts = parsed.max(1);
+            }
+        }
+        if let Ok(v) = env::var("PUSH_RETRY_BASE_DELAY_MS") {
+            if let Ok(parsed) = v.parse::<u64>() {
+                self.push.retry.base_delay_ms = parsed;
+            }
+        }
+        if let Ok(v) = env::var("PUSH_RETRY_MAX_DELAY_MS") {
+            if let Ok(parsed) = v.parse::<u64>() {
+                self.push.retry.max_delay_ms = parsed;
+            }
+        }
+
         // QR_CODE_SPEC v1.3 — 二维码 URL 基址环境覆盖
         if let Ok(qr_base_url) = env::var("PRIVCHAT_QR_BASE_URL") {
             self.qr_base_url = qr_base_url;
@@ -765,14 +889,9 @@ impl ServerConfig {
             self.database_url = db_url.clone();
         }
         if let Some(redis_url) = &cli.redis_url {
-            let existing = self.cache.redis.as_ref();
             self.cache.redis = Some(RedisConfig {
                 url: redis_url.clone(),
-                pool_size: existing.map_or(50, |r| r.pool_size),
-                min_idle: existing.map_or(10, |r| r.min_idle),
-                connection_timeout_secs: existing.map_or(5, |r| r.connection_timeout_secs),
-                command_timeout_ms: existing.map_or(5000, |r| r.command_timeout_ms),
-                idle_timeout_secs: existing.map_or(300, |r| r.idle_timeout_secs),
+                ..self.cache.redis.clone().unwrap_or_default()
             });
         }
         if let Some(jwt_secret) = &cli.jwt_secret {
@@ -1020,6 +1139,8 @@ struct TomlPushConfig {
     lenovo: Option<TomlPushLenovoConfig>,
     zte: Option<TomlPushZteConfig>,
     meizu: Option<TomlPushMeizuConfig>,
+    retry: Option<TomlPushRetryConfig>,
+    intent_ttl_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1037,6 +1158,8 @@ struct TomlPushFcmConfig {
     enabled: Option<bool>,
     project_id: Option<String>,
     access_token: Option<String>,
+    service_account_path: Option<String>,
+    token_endpoint: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1103,6 +1226,13 @@ struct TomlPushMeizuConfig {
     endpoint: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct TomlPushRetryConfig {
+    max_attempts: Option<u32>,
+    base_delay_ms: Option<u64>,
+    max_delay_ms: Option<u64>,
+}
+
 /// 单条网关监听配置（listeners 数组元素，生产级可扩展）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GatewayListenerConfig {
@@ -1221,12 +1351,25 @@ struct TomlDatabaseConfig {
     max_lifetime_seconds: Option<u64>,
     /// PostgreSQL statement_timeout，0 表示禁用。
     statement_timeout_ms: Option<u64>,
+    /// 只读副本连接串；也可通过 DATABASE_REPLICA_URLS（逗号分隔）覆盖。
+    replica_urls: Option<Vec<String>>,
+    replica_max_lag_ms: Option<u64>,
+    replica_check_interval_secs: Option<u64>,
 }
 
 /// TOML `[account]` 段。
 #[derive(Debug, Deserialize)]
 struct TomlAccountConfig {
     mode: Option<AccountMode>,
+    totp_issuer: Option<String>,
+    password_reset_ttl_secs: Option<u64>,
+    password_reset_gateway_url: Option<String>,
+    password_reset_gateway_token: Option<String>,
+    password_reset_dev_local_sender: Option<bool>,
+    password_reset_outbox: Option<String>,
+    deletion_grace_secs: Option<u64>,
+    deletion_message_policy: Option<DeletedMessagePolicy>,
+    export_ttl_secs: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1246,6 +1389,9 @@ struct TomlRedisConfig {
     connection_timeout: Option<u64>,
     command_timeout_ms: Option<u64>,
     idle_timeout: Option<u64>,
+    mode: Option<RedisMode>,
+    nodes: Option<Vec<String>>,
+    sentinel_master: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1261,6 +1407,10 @@ struct TomlRoomConfig {
     history_ttl_seconds: Option<usize>,
     max_subscriptions_per_session: Option<usize>,
     max_channel_subscribers_online: Option<usize>,
+    max_inflight_broadcasts_per_room: Option<usize>,
+    chat_max_length: Option<usize>,
+    chat_blocked_keywords: Option<Vec<String>>,
+    chat_blocked_patterns: Option<Vec<String>>,
 }
 
 /// 单个存储源（storage_source_id）配置：无 region 字段，按 default_storage_source_id 选择
@@ -1449,6 +1599,11 @@ impl From<TomlConfig> for ServerConfig {
                         connection_timeout_secs: redis.connection_timeout.unwrap_or(5),
                         command_timeout_ms: redis.command_timeout_ms.unwrap_or(5000),
                         idle_timeout_secs: redis.idle_timeout.unwrap_or(300),
+                        mode: redis.mode.unwrap_or_default(),
+                        nodes: redis.nodes.unwrap_or_default(),
+                        sentinel_master: redis
+                            .sentinel_master
+                            .unwrap_or_else(default_sentinel_master),
                     });
                 }
             }
@@ -1484,6 +1639,15 @@ impl From<TomlConfig> for ServerConfig {
             if let Some(statement_timeout_ms) = database.statement_timeout_ms {
                 config.database.statement_timeout_ms = statement_timeout_ms;
             }
+            if let Some(replica_urls) = database.replica_urls {
+                config.database.replica_urls = replica_urls;
+            }
+            if let Some(replica_max_lag_ms) = database.replica_max_lag_ms {
+                config.database.replica_max_lag_ms = replica_max_lag_ms;
+            }
+            if let Some(replica_check_interval_secs) = database.replica_check_interval_secs {
+                config.database.replica_check_interval_secs = replica_check_interval_secs;
+            }
         }
 
         if let Some(room) = toml.room {
@@ -1502,6 +1666,18 @@ impl From<TomlConfig> for ServerConfig {
             if let Some(max_channel_subscribers_online) = room.max_channel_subscribers_online {
                 config.room.max_channel_subscribers_online = max_channel_subscribers_online;
             }
+            if let Some(max_inflight) = room.max_inflight_broadcasts_per_room {
+                config.room.max_inflight_broadcasts_per_room = max_inflight;
+            }
+            if let Some(chat_max_length) = room.chat_max_length {
+                config.room.chat_max_length = chat_max_length;
+            }
+            if let Some(keywords) = room.chat_blocked_keywords {
+                config.room.chat_blocked_keywords = keywords;
+            }
+            if let Some(patterns) = room.chat_blocked_patterns {
+                config.room.chat_blocked_patterns = patterns;
+            }
         }
 
         if let Some(file) = toml.file {
@@ -1587,6 +1763,33 @@ impl From<TomlConfig> for ServerConfig {
             if let Some(mode) = account.mode {
                 config.account.mode = mode;
             }
+            if let Some(totp_issuer) = account.totp_issuer {
+                config.account.totp_issuer = totp_issuer;
+            }
+            if let Some(ttl) = account.password_reset_ttl_secs {
+                config.account.password_reset_ttl_secs = ttl;
+            }
+            if let Some(url) = account.password_reset_gateway_url {
+                config.account.password_reset_gateway_url = Some(url).filter(|s| !s.is_empty());
+            }
+            if let Some(token) = account.password_reset_gateway_token {
+                config.account.password_reset_gateway_token = Some(token).filter(|s| !s.is_empty());
+            }
+            if let Some(dev) = account.password_reset_dev_local_sender {
+                config.account.password_reset_dev_local_sender = dev;
+            }
+            if let Some(outbox) = account.password_reset_outbox {
+                config.account.password_reset_outbox = Some(outbox).filter(|s| !s.is_empty());
+            }
+            if let Some(grace) = account.deletion_grace_secs {
+                config.account.deletion_grace_secs = grace;
+            }
+            if let Some(policy) = account.deletion_message_policy {
+                config.account.deletion_message_policy = policy;
+            }
+            if let Some(ttl) = account.export_ttl_secs {
+                config.account.export_ttl_secs = ttl;
+            }
         }
 
         if let Some(system_msg) = toml.system_message {
@@ -1609,6 +1812,12 @@ impl From<TomlConfig> for ServerConfig {
                 // 允许 0 表示不限制时效；负数归一化为 0。
                 config.message.recall_time_limit_secs = limit.max(0);
             }
+            if let Some(retention) = message.retention_secs {
+                config.message.retention_secs = retention.max(0);
+            }
+            if let Some(interval) = message.retention_sweep_interval_secs {
+                config.message.retention_sweep_interval_secs = interval.max(60);
+            }
         }
 
         if let Some(push) = toml.push {
@@ -1645,6 +1854,12 @@ impl From<TomlConfig> for ServerConfig {
                 if let Some(access_token) = fcm.access_token {
                     config.push.fcm.access_token = Some(access_token);
                 }
+                if let Some(service_account_path) = fcm.service_account_path {
+                    config.push.fcm.service_account_path = Some(service_account_path);
+                }
+                if let Some(token_endpoint) = fcm.token_endpoint {
+                    config.push.fcm.token_endpoint = Some(token_endpoint);
+                }
             }
             if let Some(hms) = push.hms {
                 if let Some(enabled) = hms.enabled {
@@ -1758,6 +1973,20 @@ impl From<TomlConfig> for ServerConfig {
                     config.push.meizu.endpoint = Some(endpoint);
                 }
             }
+            if let Some(intent_ttl_secs) = push.intent_ttl_secs {
+                config.push.intent_ttl_secs = intent_ttl_secs.max(1);
+            }
+            if let Some(retry) = push.retry {
+                if let Some(max_attempts) = retry.max_attempts {
+                    config.push.retry.max_attempts = max_attempts.max(1);
+                }
+                if let Some(base_delay_ms) = retry.base_delay_ms {
+                    config.push.retry.base_delay_ms = base_delay_ms;
+                }
+                if let Some(max_delay_ms) = retry.max_delay_ms {
+                    config.push.retry.max_delay_ms = max_delay_ms;
+                }
+            }
         }
 
         // [server_event]：require both URL and master key to enable.
@@ -1943,8 +2172,16 @@ pub struct RoomConfig {
     pub history_ttl_seconds: usize,
     /// 每个 session 最多同时订阅的频道数。
     pub max_subscriptions_per_session: usize,
-    /// 单个 room/channel 最大在线订阅 session 数。
+    /// 单个 room/channel 最大在线订阅 session 数（按节点计）。
     pub max_channel_subscribers_online: usize,
+    /// 单个 room 在本节点同时进行中的广播上限；超出时新广播被丢弃（背压）。
+    pub max_inflight_broadcasts_per_room: usize,
+    /// 订阅者聊天：单条消息最大字符数（房间设置未指定时使用）。
+    pub chat_max_length: usize,
+    /// 订阅者聊天：全局违禁关键词（大小写不敏感的子串匹配）。
+    pub chat_blocked_keywords: Vec<String>,
+    /// 订阅者聊天：全局违禁正则；非法表达式启动时忽略并告警。
+    pub chat_blocked_patterns: Vec<String>,
 }
 
 impl Default for RoomConfig {
@@ -1955,6 +2192,10 @@ impl Default for RoomConfig {
             history_ttl_seconds: 86_400,
             max_subscriptions_per_session: 32,
             max_channel_subscribers_online: 20_000,
+            max_inflight_broadcasts_per_room: 4,
+            chat_max_length: 200,
+            chat_blocked_keywords: Vec::new(),
+            chat_blocked_patterns: Vec::new(),
         }
     }
 }
@@ -1974,6 +2215,13 @@ pub struct DatabaseConfig {
     pub max_lifetime_seconds: u64,
     /// PostgreSQL statement_timeout（毫秒）；0 表示禁用。
     pub statement_timeout_ms: u64,
+    /// 只读副本连接串；为空 = 所有查询走主库。
+    /// 列表 / 搜索 / 历史 / 实体同步首屏等重读路径按延迟挑选副本，副本不可用时回落主库。
+    pub replica_urls: Vec<String>,
+    /// 副本复制延迟上限（毫秒）；超过即暂停使用该副本，直到追平。
+    pub replica_max_lag_ms: u64,
+    /// 副本延迟探测间隔（秒）
+    pub replica_check_interval_secs: u64,
 }
 
 impl Default for DatabaseConfig {
@@ -1985,6 +2233,9 @@ impl Default for DatabaseConfig {
             idle_timeout_seconds: 600,
             max_lifetime_seconds: 1800,
             statement_timeout_ms: 5000,
+            replica_urls: Vec::new(),
+            replica_max_lag_ms: 1000,
+            replica_check_interval_secs: 2,
         }
     }
 }
@@ -2004,6 +2255,63 @@ pub struct RedisConfig {
     pub command_timeout_ms: u64,
     /// 空闲连接超时时间（秒）— 超过此时间的空闲连接被回收
     pub idle_timeout_secs: u64,
+    /// 部署模式：standalone（默认，只用 `url`）/ sentinel / cluster
+    #[serde(default)]
+    pub mode: RedisMode,
+    /// Sentinel 模式：哨兵地址列表；Cluster 模式：种子节点列表（为空时用 `url`）。
+    ///
+    /// Sentinel 模式下 `url` 里的密码 / db 用于连接哨兵选出的主节点。
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    /// Sentinel 监控的主节点名
+    #[serde(default = "default_sentinel_master")]
+    pub sentinel_master: String,
+}
+
+/// Redis 部署模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedisMode {
+    /// 单节点
+    #[default]
+    Standalone,
+    /// 哨兵托管的主从，主节点故障时自动切到新主
+    Sentinel,
+    /// Redis Cluster，key 按 slot 分布到多个分片
+    Cluster,
+}
+
+impl std::str::FromStr for RedisMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "standalone" => Ok(Self::Standalone),
+            "sentinel" => Ok(Self::Sentinel),
+            "cluster" => Ok(Self::Cluster),
+            other => Err(format!("未知的 Redis 模式: {}", other)),
+        }
+    }
+}
+
+fn default_sentinel_master() -> String {
+    "mymaster".to_string()
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://127.0.0.1:6379".to_string(),
+            pool_size: 50,
+            min_idle: 10,
+            connection_timeout_secs: 5,
+            command_timeout_ms: 5000,
+            idle_timeout_secs: 300,
+            mode: RedisMode::Standalone,
+            nodes: Vec::new(),
+            sentinel_master: default_sentinel_master(),
+        }
+    }
 }
 
 impl RedisConfig {
@@ -2048,6 +2356,10 @@ pub struct PushConfig {
     pub zte: PushZteConfig,
     /// Meizu 配置
     pub meizu: PushMeizuConfig,
+    /// 厂商发送失败的重试策略（所有 Provider 共用）
+    pub retry: PushRetryConfig,
+    /// Push Intent 状态在 Redis 中的保留时间（秒），过期后不可再撤销 / 取消
+    pub intent_ttl_secs: u64,
 }
 
 impl Default for PushConfig {
@@ -2064,6 +2376,8 @@ impl Default for PushConfig {
             lenovo: PushLenovoConfig::default(),
             zte: PushZteConfig::default(),
             meizu: PushMeizuConfig::default(),
+            retry: PushRetryConfig::default(),
+            intent_ttl_secs: 3600,
         }
     }
 }
@@ -2096,8 +2410,14 @@ impl Default for PushApnsConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushFcmConfig {
     pub enabled: bool,
+    /// Firebase 项目 ID；配置了 service account 时可省略，取 key 里的 `project_id`
     pub project_id: Option<String>,
+    /// 静态 OAuth access token（1 小时过期，仅调试用；配置了 service account 时忽略）
     pub access_token: Option<String>,
+    /// Google service account JSON key 路径（推荐），服务端自动换取并刷新 access token
+    pub service_account_path: Option<String>,
+    /// 可选 OAuth token 端点，默认取 key 里的 `token_uri`（`https://oauth2.googleapis.com/token`）
+    pub token_endpoint: Option<String>,
 }
 
 impl Default for PushFcmConfig {
@@ -2106,6 +2426,8 @@ impl Default for PushFcmConfig {
             enabled: false,
             project_id: None,
             access_token: None,
+            service_account_path: None,
+            token_endpoint: None,
         }
     }
 }
@@ -2264,6 +2586,29 @@ impl Default for PushMeizuConfig {
     }
 }
 
+/// 推送重试策略
+///
+/// 只对可重试错误（429 / 5xx / 网络超时）生效；token 失效与其它 4xx 不重试。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushRetryConfig {
+    /// 总尝试次数（含首次），`1` 表示不重试
+    pub max_attempts: u32,
+    /// 首次重试前的等待（毫秒），之后每次翻倍
+    pub base_delay_ms: u64,
+    /// 单次等待上限（毫秒），厂商给的 Retry-After 也受此约束
+    pub max_delay_ms: u64,
+}
+
+impl Default for PushRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay_ms: 1000,
+            max_delay_ms: 30_000,
+        }
+    }
+}
+
 /// 在线状态管理器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OnlineStatusConfig {
@@ -2400,7 +2745,7 @@ struct TomlSystemMessageConfig {
 
 /// 消息相关配置。
 ///
-/// 目前承载"撤回时效"——普通用户撤回自己消息的时间窗口。
+/// 承载"撤回时效"——普通用户撤回自己消息的时间窗口，以及消息保留期。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageConfig {
     /// 普通用户撤回自己消息的时效（秒）。群主/管理员不受限。
@@ -2409,6 +2754,15 @@ pub struct MessageConfig {
     /// - `0`（或配置为 null / 省略）：**不限制时效**，普通用户任意时间均可撤回自己的消息
     #[serde(default = "default_recall_time_limit_secs")]
     pub recall_time_limit_secs: i64,
+    /// 平台默认消息保留期（秒），超期消息由后台任务删除。
+    ///
+    /// - `> 0`：所有会话最多保留这么久；会话级覆盖只能更短
+    /// - `0`：永久保留，只有设置了会话级保留期的会话会被清理
+    #[serde(default)]
+    pub retention_secs: i64,
+    /// 保留期清理任务的执行间隔（秒）
+    #[serde(default = "default_retention_sweep_interval_secs")]
+    pub retention_sweep_interval_secs: u64,
 }
 
 fn default_recall_time_limit_secs() -> i64 {
@@ -2416,10 +2770,16 @@ fn default_recall_time_limit_secs() -> i64 {
     172800
 }
 
+fn default_retention_sweep_interval_secs() -> u64 {
+    600
+}
+
 impl Default for MessageConfig {
     fn default() -> Self {
         Self {
             recall_time_limit_secs: default_recall_time_limit_secs(),
+            retention_secs: 0,
+            retention_sweep_interval_secs: default_retention_sweep_interval_secs(),
         }
     }
 }
@@ -2427,6 +2787,8 @@ impl Default for MessageConfig {
 #[derive(Debug, Deserialize)]
 struct TomlMessageConfig {
     recall_time_limit_secs: Option<i64>,
+    retention_secs: Option<i64>,
+    retention_sweep_interval_secs: Option<u64>,
 }
 
 // =====================================================
@@ -32,6 +32,12 @@ pub enum DomainEvent {
         message_type: String,
         timestamp: i64,
         device_id: Option<String>, // ✨ Phase 3.5: 可选的设备ID（设备级 Intent）
+        /// 被 @ 的用户（客户端选择），推送策略判断「仅 @ 通知」用
+        #[serde(default)]
+        mentioned_user_ids: Vec<u64>,
+        /// 是否 @全体成员
+        #[serde(default)]
+        is_mention_all: bool,
     },
 
     /// 消息已撤销（Phase 3）
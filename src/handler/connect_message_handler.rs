@@ -526,6 +526,7 @@ impl ConnectMessageHandler {
             risk_score: risk_score as i16,
             is_new_device: first_device_login,
             is_new_location: false, // TODO: 检查是否为新 IP 地址
+            mfa_used: claims.mfa_used(),
             risk_factors: if risk_score > 50 {
                 Some(vec!["high_risk_ip".to_string()]) // TODO: 实现风险因素收集
             } else {
@@ -80,6 +80,8 @@ pub struct SendMessageHandler {
     transport: Arc<RwLock<Option<Arc<msgtrans::TransportServer>>>>,
     /// 黑名单服务（用于拦截被拉黑用户的消息）
     blacklist_service: Arc<crate::service::BlacklistService>,
+    /// 群慢速模式窗口（发送权限判定的一部分）
+    group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
     /// ✨ pts 生成器
     pts_generator: Arc<PtsGenerator>,
     /// ✨ 消息去重服务
@@ -87,8 +89,6 @@ pub struct SendMessageHandler {
     privacy_service: Arc<crate::service::PrivacyService>,
     /// ✨ 好友服务（用于检查好友关系）
     friend_service: Arc<crate::service::FriendService>,
-    /// ✨ @提及服务（用于处理@提及功能）
-    mention_service: Arc<crate::service::MentionService>,
     /// ✨ 消息仓库（PostgreSQL）
     message_repository: Arc<PgMessageRepository>,
     delivery_service: Arc<crate::service::CommittedTimelineDeliveryService>,
@@ -107,6 +107,10 @@ pub struct SendMessageHandler {
     user_repository: Option<Arc<crate::repository::UserRepository>>,
     /// 缓存管理器（user_type 查询的 L1 入口）。
     cache_manager: Option<Arc<crate::infra::CacheManager>>,
+    /// 表情包服务（sticker 消息按目录校验、记录最近使用）。None = 只校验字段齐全。
+    sticker_service: Option<Arc<crate::service::StickerService>>,
+    /// Bot 平台服务（发给 Bot 的私聊消息 / 群里 @Bot 的消息写入更新队列）。None = 不投递。
+    bot_service: Option<Arc<crate::service::BotService>>,
 }
 
 // 临时全局 EventBus（MVP 阶段简化方案）
@@ -146,11 +150,11 @@ impl SendMessageHandler {
         pts_generator: Arc<PtsGenerator>,
         privacy_service: Arc<crate::service::PrivacyService>,
         friend_service: Arc<crate::service::FriendService>,
-        mention_service: Arc<crate::service::MentionService>,
         message_repository: Arc<PgMessageRepository>,
         auth_session_manager: Arc<AuthSessionManager>,
         delivery_service: Arc<crate::service::CommittedTimelineDeliveryService>,
         user_device_repo: Option<Arc<crate::repository::UserDeviceRepository>>, // ✨ Phase 3.5
+        group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
     ) -> Self {
         Self {
             // channel_service 已合并到 channel_service
@@ -159,10 +163,10 @@ impl SendMessageHandler {
             channel_service,
             transport: Arc::new(RwLock::new(None)),
             blacklist_service,
+            group_slow_mode_service,
             pts_generator,
             privacy_service,
             friend_service,
-            mention_service,
             message_repository,
             delivery_service,
             event_bus: None,
@@ -171,6 +175,8 @@ impl SendMessageHandler {
             server_event_client: None,
             user_repository: None,
             cache_manager: None,
+            sticker_service: None,
+            bot_service: None,
         }
     }
 
@@ -190,6 +196,16 @@ impl SendMessageHandler {
         self.cache_manager = Some(cache_manager);
     }
 
+    /// 注入表情包服务（sticker 消息按真实目录校验）
+    pub fn set_sticker_service(&mut self, sticker_service: Arc<crate::service::StickerService>) {
+        self.sticker_service = Some(sticker_service);
+    }
+
+    /// 注入 Bot 平台服务（消息投递到 Bot 更新队列）
+    pub fn set_bot_service(&mut self, bot_service: Arc<crate::service::BotService>) {
+        self.bot_service = Some(bot_service);
+    }
+
     /// 设置事件总线（在服务器启动后调用）
     pub fn set_event_bus(&mut self, event_bus: Arc<crate::infra::EventBus>) {
         self.event_bus = Some(event_bus);
@@ -958,25 +974,31 @@ impl MessageHandler for SendMessageHandler {
         // 🔴 这套判定**只有一份**，在 `service::send_authorization`。曾经它内联在这里，
         // 于是每条新的写入路径都得自己重想一遍——`message/forward` 第一版就只校验了
         // 「是不是目标会话成员」，被禁言、被拉黑的用户能从转发那条路把消息发出去。
-        if let Err(refusal) = crate::service::send_authorization::authorize_send_to_channel(
+        //
+        // 慢速模式窗口不在这里占用：后面的内容/附件校验还可能拒掉这条消息，
+        // 窗口留到落库前（5.1.6）才 claim。
+        let send_permit = match crate::service::send_authorization::authorize_send_to_channel(
             &self.send_authorization_deps(),
             &channel,
             from_uid,
         )
         .await
         {
-            warn!(
-                "❌ SendMessageHandler: 用户 {} 向频道 {} 发送被拒: {:?}",
-                from_uid, send_message_request.channel_id, refusal
-            );
-            return self
-                .create_error_response(
-                    &send_message_request,
-                    refusal.error_code(),
-                    &refusal.message(),
-                )
-                .await;
-        }
+            Ok(permit) => permit,
+            Err(refusal) => {
+                warn!(
+                    "❌ SendMessageHandler: 用户 {} 向频道 {} 发送被拒: {:?}",
+                    from_uid, send_message_request.channel_id, refusal
+                );
+                return self
+                    .create_error_response(
+                        &send_message_request,
+                        refusal.error_code(),
+                        &refusal.message(),
+                    )
+                    .await;
+            }
+        };
 
         // 4. 消息类型来自协议层 message_type（u32），payload 仅解析 content + metadata + reply_to_message_id + mentioned_user_ids + message_source
         let content_message_type =
@@ -1050,12 +1072,58 @@ impl MessageHandler for SendMessageHandler {
                 .await;
         }
 
+        // 4.7. 单条消息阅后即焚计时（metadata.self_destruct），Room 不支持
+        let self_destruct = match metadata
+            .as_deref()
+            .and_then(|m| serde_json::from_str::<Value>(m).ok())
+            .map(|m| crate::service::disappearing_service::parse_self_destruct(&m))
+            .transpose()
+        {
+            Ok(timer) => timer.flatten(),
+            Err(e) => {
+                warn!("❌ SendMessageHandler: 阅后即焚参数无效: {}", e);
+                return self
+                    .create_error_response(
+                        &send_message_request,
+                        ErrorCode::InvalidParams,
+                        &format!("阅后即焚参数无效: {}", e),
+                    )
+                    .await;
+            }
+        };
+
         // 5. ✨ 处理@提及（使用客户端传递的用户ID列表，类似 Telegram）
         // 客户端在输入 @ 时已经选择了用户，所以直接使用 mentioned_user_ids
-        // 消息内容中仍然显示 @用户昵称，但实际关联的是用户ID
-        let is_mention_all = content.contains("@全体成员")
-            || content.contains("@all")
-            || content.contains("@everyone");
+        // 消息内容中仍然显示 @用户昵称，但实际关联的是用户ID；
+        // mentioned_user_ids 中的 0（MENTION_ALL_USER_ID）表示 @全体成员。
+        // 旧客户端不带结构化 @全体成员，仍按正文整词识别兼容。
+        let (requested_mention_ids, structured_mention_all) =
+            crate::service::mention_service::split_mention_targets(&mentioned_user_ids);
+        let is_mention_all = channel.channel_type == crate::model::channel::ChannelType::Group
+            && (structured_mention_all
+                || crate::service::MentionService::parse_mentions(&content).1);
+
+        // 🔴 @全体成员必须在落库前做权限检查：消息一旦提交就已投递，事后再拒只会让
+        // 发送方看到失败、其他人却收到了消息。
+        if is_mention_all && !channel.check_permission(&from_uid, |perms| perms.can_at_all) {
+            warn!(
+                "❌ SendMessageHandler: 用户 {} 无权@全体成员",
+                send_message_request.from_uid
+            );
+            return self
+                .create_error_response(
+                    &send_message_request,
+                    ErrorCode::PermissionDenied,
+                    "无权@全体成员，仅群主和管理员可以",
+                )
+                .await;
+        }
+
+        // 只记录会话成员；@自己没有意义
+        let mention_user_ids: Vec<u64> = requested_mention_ids
+            .into_iter()
+            .filter(|&user_id| user_id != from_uid && channel.is_member(user_id))
+            .collect();
 
         // ✨ 5.1.5. 持久化消息、pts、commit 和附件绑定。
         // channel_id 现在直接是 u64，使用 channel.id 作为 channel_id。
@@ -1154,6 +1222,31 @@ impl MessageHandler for SendMessageHandler {
         let (_, commit_content) = canonical_event
             .to_legacy_commit(channel_id, channel_type_code)
             .map_err(|error| ServerError::Protocol(format!("构造兼容消息投影失败: {error}")))?;
+        // 5.1.6. 全部校验通过，占用慢速模式窗口。之后没能新写入消息就交还。
+        let send_claim = match send_permit.claim().await {
+            Ok(claim) => claim,
+            Err(refusal) => {
+                warn!(
+                    "❌ SendMessageHandler: 用户 {} 向频道 {} 发送被拒: {:?}",
+                    from_uid, send_message_request.channel_id, refusal
+                );
+                return self
+                    .create_error_response(
+                        &send_message_request,
+                        refusal.error_code(),
+                        &refusal.message(),
+                    )
+                    .await;
+            }
+        };
+
+        // @提及（权限与成员过滤已在上面完成）随消息同事务落库。
+        // 未读 @ 由会话已读 pts 判定，这里只记录，不维护计数。
+        let mut mention_targets = mention_user_ids.clone();
+        if is_mention_all {
+            mention_targets.push(crate::service::mention_service::MENTION_ALL_USER_ID);
+        }
+
         let tx_result = match self
             .message_repository
             .create_message_and_commit_atomic(AtomicMessageCommitRequest {
@@ -1165,6 +1258,13 @@ impl MessageHandler for SendMessageHandler {
                 channel_type: channel_type_code as i16,
                 event: canonical_event,
                 sender_username: None,
+                // 阅后即焚计时（单条 TTL 优先，其次会话默认）与消息同事务登记
+                expiry: crate::repository::MessageExpiryArm::for_new_message(
+                    channel_type_code,
+                    self_destruct,
+                ),
+                broadcast_attrs: None,
+                mention_targets,
             })
             .await
         {
@@ -1174,6 +1274,7 @@ impl MessageHandler for SendMessageHandler {
                     "❌ SendMessageHandler: 事务化保存消息失败 channel_id={} user={} local_message_id={}: {}",
                     channel_id, from_uid, send_message_request.local_message_id, e
                 );
+                send_claim.release().await;
                 // 用 e 自己的码，别一律按 DatabaseError 报。事务里出的不一定是数据库故障：
                 // 附件引用不可用之类的终局判定也从这里出来，硬编码成 DatabaseError(7)
                 // 会把它送进客户端的**可重试**段——消息于是每十几秒重试一次、永远不失败。
@@ -1202,6 +1303,7 @@ impl MessageHandler for SendMessageHandler {
                 "🔄 SendMessageHandler: 并发 DB 幂等命中 - user={}, local_message_id={}, message_id={}, pts={}",
                 from_uid, send_message_request.local_message_id, message.message_id, pts
             );
+            send_claim.release().await;
             return self
                 .create_success_response(&send_message_request, &message_record, pts)
                 .await;
@@ -1223,6 +1325,13 @@ impl MessageHandler for SendMessageHandler {
             message.message_id, pts
         );
 
+        if matches!(
+            content_message_type,
+            privchat_protocol::ContentMessageType::Sticker
+        ) {
+            self.record_sticker_usage(from_uid, &metadata_value);
+        }
+
         let commit = privchat_protocol::rpc::sync::ServerCommit {
             event_id,
             pts,
@@ -1381,6 +1490,8 @@ impl MessageHandler for SendMessageHandler {
                     message_type: content_message_type.as_str().to_string(),
                     timestamp: message_record.created_at.timestamp(),
                     device_id: None,
+                    mentioned_user_ids: mention_user_ids.clone(),
+                    is_mention_all,
                 };
 
                 if let Err(e) = event_bus.publish(event) {
@@ -1392,77 +1503,43 @@ impl MessageHandler for SendMessageHandler {
             }
         }
 
-        // 5.2. ✨ 处理@提及（使用客户端传递的用户ID列表）
-        // 客户端已经选择了用户，mentioned_user_ids 是用户ID列表
-        // 不需要解析字符串，直接使用客户端传递的用户ID
-
-        // 5.3. ✨ 处理@提及（使用客户端传递的用户ID列表）
-        // 如果是群聊且@全体成员，需要权限检查
-        if is_mention_all && channel.channel_type == crate::model::channel::ChannelType::Group {
-            // 检查发送者是否有@全体成员的权限（群主或管理员）
-            if let Some(member) = channel.members.get(&from_uid) {
-                let can_mention_all = matches!(
-                    member.role,
-                    crate::model::channel::MemberRole::Owner
-                        | crate::model::channel::MemberRole::Admin
-                );
+        // 5.3. 出站 Webhook：每条消息一次，只带索引不带正文（正文由订阅方按需拉取）
+        crate::service::emit_webhook(
+            crate::service::webhook_service::WEBHOOK_EVENT_MESSAGE_COMMITTED,
+            serde_json::json!({
+                "message_id": message_record.message_id,
+                "channel_id": channel_id,
+                "channel_type": channel.channel_type.to_wire_u8(),
+                "sender_id": from_uid,
+                "pts": pts,
+                "message_type": content_message_type.as_str(),
+                "mentioned_user_ids": mention_user_ids,
+                "is_mention_all": is_mention_all,
+                "created_at": message_record.created_at.timestamp_millis(),
+            }),
+        );
 
-                if !can_mention_all {
-                    warn!(
-                        "❌ SendMessageHandler: 用户 {} 无权@全体成员",
-                        send_message_request.from_uid
-                    );
-                    return self
-                        .create_error_response(
-                            &send_message_request,
-                            ErrorCode::PermissionDenied,
-                            "无权@全体成员，仅群主和管理员可以",
-                        )
+        // 5.4. Bot 更新队列：私聊里发给 Bot 的消息、群里 @Bot 的消息（Bot 发的不回灌）
+        if let Some(bot_service) = self.bot_service.clone() {
+            let candidates: Vec<u64> = match channel.direct_peer(from_uid) {
+                Some(peer) => vec![peer],
+                None => mention_user_ids.clone(),
+            };
+            if !candidates.is_empty() {
+                let update = serde_json::json!({
+                    "message_id": message_record.message_id,
+                    "channel_id": channel_id,
+                    "channel_type": channel.channel_type.to_wire_u8(),
+                    "sender_id": from_uid,
+                    "content": content.clone(),
+                    "message_type": content_message_type.as_str(),
+                    "created_at": message_record.created_at.timestamp_millis(),
+                });
+                tokio::spawn(async move {
+                    bot_service
+                        .dispatch_message(from_uid, &candidates, &update)
                         .await;
-                }
-
-                // @全体成员时，获取所有成员ID
-                let all_member_ids: Vec<u64> = channel.get_member_ids();
-                // 记录@提及（包含所有成员）
-                if let Err(e) = self
-                    .mention_service
-                    .record_mention(
-                        message_record.message_id,
-                        send_message_request.channel_id,
-                        all_member_ids,
-                        true,
-                    )
-                    .await
-                {
-                    warn!("⚠️ SendMessageHandler: 记录@全体成员失败: {}", e);
-                }
-            }
-        } else if !mentioned_user_ids.is_empty() {
-            // 记录@提及（使用客户端传递的用户ID列表）
-            // 验证用户ID是否都在频道中
-            let valid_user_ids: Vec<u64> = mentioned_user_ids
-                .iter()
-                .filter(|&&user_id| channel.members.contains_key(&user_id))
-                .cloned()
-                .collect();
-
-            if valid_user_ids.len() != mentioned_user_ids.len() {
-                warn!("⚠️ SendMessageHandler: 部分@的用户不在频道中，已过滤");
-            }
-
-            if !valid_user_ids.is_empty() {
-                if let Err(e) = self
-                    .mention_service
-                    .record_mention(
-                        message_record.message_id,
-                        send_message_request.channel_id,
-                        valid_user_ids,
-                        false,
-                    )
-                    .await
-                {
-                    warn!("⚠️ SendMessageHandler: 记录@提及失败: {}", e);
-                }
+                });
             }
         }
 
@@ -1788,6 +1865,7 @@ impl SendMessageHandler {
             friend_service: self.friend_service.clone(),
             blacklist_service: self.blacklist_service.clone(),
             privacy_service: self.privacy_service.clone(),
+            group_slow_mode_service: self.group_slow_mode_service.clone(),
         }
     }
 
@@ -1940,7 +2018,7 @@ impl SendMessageHandler {
     /// 验证表情包消息 metadata
     async fn validate_sticker_metadata(&self, metadata: &Value) -> crate::Result<()> {
         // 验证必需字段（扁平结构）
-        metadata
+        let sticker_id = metadata
             .get("sticker_id")
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
@@ -1949,7 +2027,7 @@ impl SendMessageHandler {
                 )
             })?;
 
-        metadata
+        let image_url = metadata
             .get("image_url")
             .and_then(|v| v.as_str())
             .ok_or_else(|| {
@@ -1958,9 +2036,40 @@ impl SendMessageHandler {
                 )
             })?;
 
+        // 按目录校验：表情必须存在、所属库上架，且 image_url 与目录一致——
+        // 否则客户端可以借 sticker 类型把任意外链图片塞进会话。
+        if let Some(sticker_service) = &self.sticker_service {
+            let sticker = sticker_service.resolve_sendable(sticker_id).await?;
+            if sticker.image_url != image_url {
+                return Err(crate::error::ServerError::Validation(format!(
+                    "sticker 消息的 image_url 与表情 {} 不一致",
+                    sticker_id
+                )));
+            }
+        }
+
         Ok(())
     }
 
+    /// 记录最近使用的表情（fire-and-forget，失败不影响发送结果）
+    fn record_sticker_usage(&self, user_id: u64, metadata: &Value) {
+        let Some(sticker_service) = self.sticker_service.clone() else {
+            return;
+        };
+        let Some(sticker_id) = metadata.get("sticker_id").and_then(|v| v.as_str()) else {
+            return;
+        };
+        let sticker_id = sticker_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = sticker_service.record_usage(user_id, &sticker_id).await {
+                warn!(
+                    "⚠️ 记录最近使用表情失败 user={} sticker_id={}: {}",
+                    user_id, sticker_id, e
+                );
+            }
+        });
+    }
+
     /// 验证网址预览消息 metadata
     ///
     /// 仅强制 `url` 必填；`title` / `description` / `thumbnail_file_id` 由 SDK 应用层预览
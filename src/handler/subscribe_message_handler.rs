@@ -87,7 +87,11 @@ impl SubscribeMessageHandler {
         session_id: SessionId,
         channel_id: u64,
     ) -> Result<()> {
-        let history = room_history_service.list_recent_history(channel_id).await?;
+        let mut history = room_history_service.list_recent_history(channel_id).await?;
+        // 置顶槽位（topic = chat.pin）排在历史之后回放，客户端据此恢复置顶条
+        if let Some(pinned) = room_history_service.get_pinned(channel_id).await? {
+            history.push(pinned);
+        }
         if history.is_empty() {
             return Ok(());
         }
@@ -295,8 +299,8 @@ impl MessageHandler for SubscribeMessageHandler {
                                 "📡 SubscribeMessageHandler: Session {} 订阅频道 {}",
                                 context.session_id, channel_id
                             );
-                            should_replay_room_history = channel_type == 2
-                                && self.room_history_service.subscribe_history_enabled();
+                            // 历史关闭时仍需回放置顶槽位
+                            should_replay_room_history = channel_type == 2;
                             // presence 与订阅严格绑定：DM/Group 订阅成功后，立即把频道对端成员的
                             // 当前 presence 快照推给**本会话**（初始态），后续变化由广播跟进。
                             // 这样「订阅即拿到 presence」，不依赖客户端单独 presence/status/get。
@@ -231,6 +231,29 @@ pub struct ListShadowBannedResponse {
     pub total: usize,
 }
 
+/// 设置 Shadow Ban 请求
+#[derive(Debug, Deserialize)]
+pub struct ShadowBanRequest {
+    /// 只封禁指定设备；缺省封禁用户全部设备
+    pub device_id: Option<String>,
+    /// 违规类型（spam / suspicious_activity / ...），缺省 suspicious_activity
+    pub violation: Option<String>,
+    /// 备注（运营可见）
+    pub note: Option<String>,
+    /// 封禁时长，秒。None 表示无期限，直到解封
+    pub duration_secs: Option<u64>,
+}
+
+/// 设置 Shadow Ban 响应
+#[derive(Debug, Serialize)]
+pub struct ShadowBanResponse {
+    pub success: bool,
+    pub user_id: u64,
+    pub affected_devices: usize,
+    pub expires_at: Option<i64>,
+    pub message: String,
+}
+
 /// 解除 Shadow Ban 响应
 #[derive(Debug, Serialize)]
 pub struct UnshadowBanResponse {
@@ -576,3 +599,131 @@ pub struct RoomBroadcastRequest {
     pub content: String,
     pub sender_id: Option<u64>,
 }
+
+// =====================================================
+// 表情包管理
+// =====================================================
+
+/// 创建表情包库请求
+#[derive(Debug, Deserialize)]
+pub struct CreateStickerPackageRequest {
+    pub package_id: String,
+    pub name: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// 缩略图（先经 `POST /stickers/images` 上传）
+    pub thumbnail_file_id: Option<u64>,
+    /// 默认上架
+    pub is_enabled: Option<bool>,
+    pub sort_order: Option<i32>,
+}
+
+/// 更新表情包库请求（缺省字段保持原值）
+#[derive(Debug, Deserialize)]
+pub struct UpdateStickerPackageRequest {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub thumbnail_file_id: Option<u64>,
+    pub is_enabled: Option<bool>,
+    pub sort_order: Option<i32>,
+}
+
+/// 表情图片上传响应
+#[derive(Debug, Serialize)]
+pub struct StickerImageResponse {
+    pub file_id: u64,
+    pub file_url: String,
+    pub mime_type: String,
+    pub file_size: u64,
+}
+
+// =====================================================
+// 出站 Webhook
+// =====================================================
+
+/// 创建 Webhook 端点请求
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookEndpointRequest {
+    pub url: String,
+    /// 订阅的事件类型；`["*"]` 表示全部
+    pub event_types: Vec<String>,
+    /// 签名密钥；缺省由服务端生成，只在创建响应里返回一次
+    pub secret: Option<String>,
+    pub description: Option<String>,
+    /// 默认启用
+    pub is_enabled: Option<bool>,
+}
+
+/// 更新 Webhook 端点请求（缺省字段保持原值）
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookEndpointRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub is_enabled: Option<bool>,
+    /// 轮换签名密钥：新密钥在响应里返回一次
+    #[serde(default)]
+    pub rotate_secret: bool,
+}
+
+/// 投递日志查询参数（按 delivery_id 倒序，`before_id` 翻页）
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub endpoint_id: Option<u64>,
+    /// pending / succeeded / dead
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub before_id: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+/// 批量重放死信请求
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplayWebhookDeadLettersRequest {
+    /// 缺省 = 全部端点
+    pub endpoint_id: Option<u64>,
+}
+
+// =====================================================
+// Bot 平台
+// =====================================================
+
+/// 签发 Bot 令牌请求
+#[derive(Debug, Default, Deserialize)]
+pub struct IssueBotTokenRequest {
+    /// 备注（哪个部署 / 哪个环境在用）
+    pub label: Option<String>,
+}
+
+/// Bot 发消息请求（`POST /api/bot/messages`）
+#[derive(Debug, Deserialize)]
+pub struct BotSendMessageRequest {
+    pub channel_id: u64,
+    pub content: String,
+    /// 内联键盘（`{"inline_keyboard": [[{"text", "callback_data" | "url"}]]}`）
+    pub reply_markup: Option<crate::model::InlineKeyboardMarkup>,
+}
+
+/// Bot 撤回消息请求（`POST /api/bot/messages/revoke`）
+#[derive(Debug, Deserialize)]
+pub struct BotRevokeMessageRequest {
+    pub channel_id: u64,
+    pub message_id: u64,
+}
+
+/// Bot 设置命令列表请求（整体替换）
+#[derive(Debug, Deserialize)]
+pub struct SetBotCommandsRequest {
+    pub commands: Vec<crate::model::BotCommand>,
+}
+
+/// getUpdates 查询参数
+#[derive(Debug, Deserialize)]
+pub struct BotUpdatesQuery {
+    /// 上次收到的最大 update_id + 1；之前的更新视为已确认
+    pub offset: Option<i64>,
+    pub limit: Option<u32>,
+    /// 长轮询等待秒数（0 = 立即返回）
+    pub timeout: Option<u64>,
+}
@@ -0,0 +1,84 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `/api/app/account/exports/*` — 个人数据导出归档下载
+//!
+//! 认证：`Authorization: Bearer <access token>`，只有导出任务所属用户可以下载，
+//! 其他人一律返回 404（不暴露任务是否存在）。
+//!
+//! - `GET /api/app/account/exports/{export_id}`   下载 tar 归档（流式）
+
+use crate::error::{Result, ServerError};
+use crate::http::FileServerState;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap},
+    response::Response,
+    routing::get,
+    Router,
+};
+use tracing::warn;
+
+pub fn create_route() -> Router<FileServerState> {
+    Router::new().route("/api/app/account/exports/{export_id}", get(download_export))
+}
+
+/// 下载导出归档
+///
+/// GET /api/app/account/exports/:export_id
+async fn download_export(
+    State(state): State<FileServerState>,
+    headers: HeaderMap,
+    Path(export_id): Path<u64>,
+) -> Result<Response> {
+    let (Some(auth), Some(data_export_service)) = (&state.auth, &state.data_export_service) else {
+        return Err(ServerError::ServiceUnavailable(
+            "个人数据导出未启用".to_string(),
+        ));
+    };
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            ServerError::Unauthorized("缺少 Authorization: Bearer <token>".to_string())
+        })?;
+    let user_id = auth.user_of(bearer).await.map_err(|reason| {
+        warn!(
+            "⚠️ 导出下载登录态无效: export_id={}, reason={}",
+            export_id, reason
+        );
+        ServerError::Unauthorized("登录态无效".to_string())
+    })?;
+
+    let download = data_export_service
+        .open_download(user_id, export_id)
+        .await?;
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-tar")
+        .header(header::CONTENT_LENGTH, download.size)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", download.filename),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from_stream(download.stream))
+        .map_err(|e| ServerError::Internal(format!("构建下载响应失败: {}", e)))
+}
@@ -49,15 +49,12 @@ use axum::{
     routing::{delete, get, post, put},
     Router,
 };
-use futures::stream::{self, StreamExt};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::{BTreeSet, HashMap};
 use std::net::SocketAddr;
 use tracing::{debug, info, warn};
 
-const ROOM_BROADCAST_MAX_CONCURRENCY: usize = 128;
-
 /// 创建管理 API 路由（返回相对路径 router，由 caller 决定前缀挂载点）
 pub fn create_route() -> Router<AdminServerState> {
     Router::new()
@@ -97,6 +94,11 @@ pub fn create_route() -> Router<AdminServerState> {
         .route("/room", get(list_room_channels))
         .route("/room/{channel_id}", get(get_room_channel))
         .route("/room/{channel_id}/broadcast", post(room_broadcast))
+        .route("/room/{channel_id}/chat", get(get_room_chat_settings))
+        .route("/room/{channel_id}/chat", put(update_room_chat_settings))
+        // 二维码：查看 Key 与扫描历史 / 撤销
+        .route("/qrcode/{qr_key}/scans", get(get_qrcode_scans))
+        .route("/qrcode/{qr_key}/revoke", post(revoke_qrcode))
         // 好友管理
         .route("/friendships", post(create_friendship)) // 创建好友关系
         .route("/friendships", get(list_friendships))
@@ -117,6 +119,11 @@ pub fn create_route() -> Router<AdminServerState> {
         // === P0: 用户封禁/解封 ===
         .route("/users/{user_id}/suspend", post(suspend_user))
         .route("/users/{user_id}/unsuspend", post(unsuspend_user))
+        // 两步验证：查看状态 / 重置（用户丢失验证器设备）
+        .route(
+            "/users/{user_id}/2fa",
+            get(get_user_two_factor).delete(reset_user_two_factor),
+        )
         // === P0: 设备强制踢出 ===
         .route("/devices/{device_id}/revoke", post(revoke_device))
         .route(
@@ -148,6 +155,7 @@ pub fn create_route() -> Router<AdminServerState> {
         .route("/system-messages/senders", get(list_system_senders))
         // === P0: 安全管控 ===
         .route("/security/shadow-banned", get(list_shadow_banned))
+        .route("/security/shadow-ban/{user_id}", post(shadow_ban_user))
         .route("/security/shadow-ban/{user_id}", delete(unshadow_ban_user))
         .route(
             "/security/users/{user_id}/state",
@@ -974,23 +982,19 @@ async fn list_room_channels(
         .unwrap_or(20)
         .min(100);
 
-    let all_channels = state.subscribe_manager.get_all_channels();
-    let total_channels = all_channels.len();
-    let total_sessions = state.subscribe_manager.get_total_session_count();
+    let channel_ids = state.room_fanout.channel_ids().await;
+    let total_channels = channel_ids.len();
+    let total_sessions = state.room_fanout.total_sessions().await;
 
-    // 分页
+    // 分页（在线数为全集群之和）
     let start = ((page - 1) * page_size) as usize;
-    let channels: Vec<Value> = all_channels
-        .into_iter()
-        .skip(start)
-        .take(page_size as usize)
-        .map(|(channel_id, session_count)| {
-            json!({
-                "channel_id": channel_id,
-                "online_count": session_count,
-            })
-        })
-        .collect();
+    let mut channels = Vec::new();
+    for channel_id in channel_ids.into_iter().skip(start).take(page_size as usize) {
+        channels.push(json!({
+            "channel_id": channel_id,
+            "online_count": state.room_fanout.online_count(channel_id).await,
+        }));
+    }
 
     Ok(ApiEnvelope::ok(json!({
         "channels": channels,
@@ -1011,7 +1015,7 @@ async fn get_room_channel(
 ) -> ApiResult<Value> {
     verify_service_key(&headers, &state).await?;
 
-    let online_count = state.subscribe_manager.get_channel_online_count(channel_id);
+    let online_count = state.room_fanout.online_count(channel_id).await;
 
     Ok(ApiEnvelope::ok(json!({
         "channel_id": channel_id,
@@ -1022,6 +1026,9 @@ async fn get_room_channel(
 /// Room 频道广播
 ///
 /// POST /api/service/room/:channel_id/broadcast
+///
+/// 集群模式下经 Redis 中继到其他持有订阅者的节点；`delivered` 只统计本节点的投递。
+/// 该 room 在本节点进行中的广播已达上限时返回限流错误，不写入历史。
 async fn room_broadcast(
     State(state): State<AdminServerState>,
     headers: HeaderMap,
@@ -1030,8 +1037,7 @@ async fn room_broadcast(
 ) -> ApiResult<Value> {
     verify_service_key(&headers, &state).await?;
 
-    let sessions = state.subscribe_manager.get_channel_sessions(channel_id);
-    let online_count = sessions.len();
+    let permit = state.room_fanout.try_acquire(channel_id)?;
 
     let message_content = payload.content.clone();
     let publisher = payload.sender_id.map(|id| id.to_string());
@@ -1061,7 +1067,10 @@ async fn room_broadcast(
         );
     }
 
-    if sessions.is_empty() {
+    let report = state.room_fanout.publish(permit, publish_request).await?;
+    let online_count = state.room_fanout.online_count(channel_id).await;
+
+    if online_count == 0 {
         debug!(
             "📡 Room broadcast: channel_id={}, 无在线订阅者，仅写入历史",
             channel_id
@@ -1071,63 +1080,140 @@ async fn room_broadcast(
             "channel_id": channel_id,
             "online_count": 0,
             "delivered": 0,
+            "relayed_nodes": report.relayed_nodes,
             "server_message_id": server_msg_id,
             "message": "频道内无在线订阅者"
         })));
     }
 
-    let transport = state.connection_manager.transport_server.read().await;
-    let Some(server) = transport.as_ref() else {
-        return Err(ServerError::Internal("TransportServer 未就绪".to_string()));
-    };
-
-    let server = server.clone();
-    drop(transport);
-
-    let payload_bytes = privchat_protocol::encode_message(&publish_request)
-        .map_err(|e| ServerError::Protocol(format!("编码消息失败: {}", e)))?;
-
-    // 有界并发广播：避免在线人数过多时为每个 session spawn 一个 task。
-    let payload_bytes = std::sync::Arc::new(payload_bytes);
-    let delivered = stream::iter(sessions)
-        .map(|sid| {
-            let server = server.clone();
-            let bytes = payload_bytes.clone();
-            async move {
-                let options = msgtrans::SendOptions::new()
-                    .biz_type(privchat_protocol::protocol::MessageType::PublishRequest as u8);
-                match server
-                    .send_with_options(sid.clone(), (*bytes).clone().into(), options)
-                    .await {
-                    Ok(_) => {
-                        debug!("📡 Room publish -> session {} 成功", sid);
-                        true
-                    }
-                    Err(e) => {
-                        warn!("📡 Room publish -> session {} 失败: {}", sid, e);
-                        false
-                    }
-                }
-            }
-        })
-        .buffer_unordered(ROOM_BROADCAST_MAX_CONCURRENCY)
-        .fold(0usize, |acc, ok| async move { acc + usize::from(ok) })
-        .await;
-
     info!(
-        "📡 Admin: Room 广播 channel_id={}, 在线={}, 投递={}",
-        channel_id, online_count, delivered
+        "📡 Admin: Room 广播 channel_id={}, 在线={}, 本节点投递={}/{}, 中继节点={}, 跳过节点={}",
+        channel_id,
+        online_count,
+        report.delivered,
+        report.local_sessions,
+        report.relayed_nodes,
+        report.skipped_nodes
     );
 
     Ok(ApiEnvelope::ok(json!({
         "success": true,
         "channel_id": channel_id,
         "online_count": online_count,
-        "delivered": delivered,
+        "delivered": report.delivered,
+        "relayed_nodes": report.relayed_nodes,
+        "skipped_nodes": report.skipped_nodes,
         "server_message_id": server_msg_id,
     })))
 }
 
+/// 获取 Room 聊天设置
+///
+/// GET /api/service/room/:channel_id/chat
+async fn get_room_chat_settings(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let settings = state.room_chat_service.settings(channel_id).await?;
+    Ok(ApiEnvelope::ok(json!({
+        "channel_id": channel_id,
+        "settings": settings,
+    })))
+}
+
+/// 覆盖 Room 聊天设置（开关、慢速模式、单条长度、主持人、房间违禁词）
+///
+/// PUT /api/service/room/:channel_id/chat
+async fn update_room_chat_settings(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(channel_id): Path<u64>,
+    Json(settings): Json<crate::service::RoomChatSettings>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let settings = state
+        .room_chat_service
+        .update_settings(channel_id, settings)
+        .await?;
+    Ok(ApiEnvelope::ok(json!({
+        "channel_id": channel_id,
+        "settings": settings,
+    })))
+}
+
+// =====================================================
+// 二维码管理
+// =====================================================
+
+/// 查看二维码 Key 及其扫描历史（新 -> 旧）
+///
+/// GET /api/service/qrcode/:qr_key/scans?limit=50
+async fn get_qrcode_scans(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(qr_key): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let limit = params
+        .get("limit")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(50)
+        .clamp(1, 500);
+
+    let record = state
+        .qrcode_service
+        .get(&qr_key)
+        .await?
+        .ok_or_else(|| ServerError::NotFound(format!("QR Key 不存在: {}", qr_key)))?;
+    let scans = state.qrcode_service.get_scan_logs(&qr_key, limit).await?;
+
+    Ok(ApiEnvelope::ok(json!({
+        "qr_key": record.qr_key,
+        "qr_type": record.qr_type.as_str(),
+        "target_id": record.target_id,
+        "creator_id": record.creator_id,
+        "created_at": record.created_at.timestamp_millis(),
+        "expire_at": record.expire_at.map(|t| t.timestamp_millis()),
+        "max_usage": record.max_usage,
+        "used_count": record.used_count,
+        "revoked": record.revoked,
+        "scans": scans
+            .iter()
+            .map(|log| json!({
+                "scanner_id": log.scanner_id,
+                "scanned_at": log.scanned_at.timestamp_millis(),
+                "success": log.success,
+                "error": log.error,
+            }))
+            .collect::<Vec<_>>(),
+    })))
+}
+
+/// 撤销二维码 Key（如被滥用的群邀请码）
+///
+/// POST /api/service/qrcode/:qr_key/revoke
+async fn revoke_qrcode(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(qr_key): Path<String>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state.qrcode_service.revoke(&qr_key).await?;
+    info!("🔒 Admin: 撤销 QR Key {}", qr_key);
+
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "qr_key": qr_key,
+    })))
+}
+
 // =====================================================
 // 好友管理
 // =====================================================
@@ -1351,6 +1437,7 @@ async fn list_login_logs(
                 "risk_score": log.risk_score,
                 "is_new_device": log.is_new_device,
                 "is_new_location": log.is_new_location,
+                "mfa_used": log.mfa_used,
                 "created_at": log.created_at,
             })
         })
@@ -1402,6 +1489,7 @@ async fn get_login_log(
         "risk_score": log.risk_score,
         "is_new_device": log.is_new_device,
         "is_new_location": log.is_new_location,
+        "mfa_used": log.mfa_used,
         "risk_factors": log.risk_factors,
         "notification_sent": log.notification_sent,
         "notification_method": log.notification_method,
@@ -1785,6 +1873,46 @@ async fn unsuspend_user(
     }))
 }
 
+// =====================================================
+// 两步验证
+// =====================================================
+
+/// 查看用户两步验证状态
+///
+/// GET /api/service/users/:user_id/2fa
+async fn get_user_two_factor(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(user_id): Path<u64>,
+) -> ApiResult<crate::service::TwoFactorStatus> {
+    verify_service_key(&headers, &state).await?;
+
+    let status = state.two_factor_service.status(user_id).await?;
+    Ok(ApiEnvelope::ok(status))
+}
+
+/// 重置用户两步验证：清除密钥与恢复码，下次登录只需密码
+///
+/// DELETE /api/service/users/:user_id/2fa
+async fn reset_user_two_factor(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(user_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let reset = state.two_factor_service.admin_reset(user_id).await?;
+    info!(
+        "🔓 管理员重置两步验证: user_id={}, had_2fa={}",
+        user_id, reset
+    );
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "user_id": user_id,
+        "reset": reset,
+    })))
+}
+
 // =====================================================
 // P0: 设备强制踢出
 // =====================================================
@@ -2132,6 +2260,64 @@ async fn list_shadow_banned(
     }))
 }
 
+/// 设置用户的 Shadow Ban（持久化，跨节点 / 重启生效）
+///
+/// POST /api/service/security/shadow-ban/:user_id
+async fn shadow_ban_user(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(user_id): Path<u64>,
+    Json(request): Json<dto::ShadowBanRequest>,
+) -> ApiResult<dto::ShadowBanResponse> {
+    verify_service_key(&headers, &state).await?;
+
+    let violation = match request.violation.as_deref() {
+        Some(v) => crate::security::ViolationType::from_str(v)
+            .ok_or_else(|| ServerError::Validation(format!("未知的违规类型: {}", v)))?,
+        None => crate::security::ViolationType::SuspiciousActivity,
+    };
+    let duration = request.duration_secs.map(std::time::Duration::from_secs);
+
+    let device_ids: Vec<String> = match request.device_id {
+        Some(device_id) => vec![device_id],
+        None => state
+            .device_manager_db
+            .get_user_devices(user_id)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询用户设备失败: {}", e)))?
+            .into_iter()
+            .map(|d| d.device_id)
+            .collect(),
+    };
+    if device_ids.is_empty() {
+        return Err(ServerError::Validation(format!(
+            "用户 {} 没有可封禁的设备",
+            user_id
+        )));
+    }
+
+    for device_id in &device_ids {
+        state
+            .security_service
+            .manual_ban(
+                user_id,
+                device_id,
+                violation,
+                duration,
+                request.note.as_deref(),
+            )
+            .await?;
+    }
+
+    Ok(ApiEnvelope::ok(dto::ShadowBanResponse {
+        success: true,
+        user_id,
+        affected_devices: device_ids.len(),
+        expires_at: duration.map(|d| chrono::Utc::now().timestamp_millis() + d.as_millis() as i64),
+        message: "Shadow Ban 已生效".to_string(),
+    }))
+}
+
 /// 解除用户的 Shadow Ban
 ///
 /// DELETE /api/service/security/shadow-ban/:user_id
@@ -0,0 +1,335 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Bot 平台 HTTP API
+//!
+//! 两组路由：
+//!
+//! `/api/service/bots/*` —— 管理员（`X-Service-Key`）
+//! - `GET    /{bot_user_id}/tokens`              令牌列表（不含明文）
+//! - `POST   /{bot_user_id}/tokens`              签发令牌（明文只返回这一次）
+//! - `DELETE /{bot_user_id}/tokens/{token_id}`   吊销令牌
+//! - `GET    /{bot_user_id}/commands`            查看 Bot 注册的命令
+//!
+//! `/api/bot/*` —— Bot 自身（`Authorization: Bot <token>`）
+//! - `GET    /me`                 当前 Bot 信息
+//! - `GET    /commands`           命令列表
+//! - `PUT    /commands`           整体替换命令列表
+//! - `POST   /messages`           向所在会话发消息（可带内联键盘）
+//! - `POST   /messages/revoke`    撤回自己发的消息（受撤回时效限制）
+//! - `GET    /updates`            长轮询拉取收到的消息与按钮回调
+
+use crate::error::{Result, ServerError};
+use crate::http::dto::admin as dto;
+use crate::http::routes::admin::verify_service_key;
+use crate::http::{AdminServerState, ApiEnvelope, ApiResult};
+use crate::model::channel::ChannelType;
+use crate::model::{BotCommand, REPLY_MARKUP_KEY};
+use crate::service::bot_service::parse_bot_authorization;
+use crate::service::send_authorization::{authorize_send_to_channel, SendAuthorizationDeps};
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use privchat_protocol::ContentMessageType;
+use serde_json::{json, Value};
+use tracing::info;
+
+/// Bot 消息正文上限（字符）
+const MAX_BOT_MESSAGE_CHARS: usize = 4096;
+
+pub fn create_service_route() -> Router<AdminServerState> {
+    Router::new()
+        .route("/{bot_user_id}/tokens", get(list_tokens).post(issue_token))
+        .route("/{bot_user_id}/tokens/{token_id}", delete(revoke_token))
+        .route("/{bot_user_id}/commands", get(get_bot_commands))
+}
+
+pub fn create_bot_route() -> Router<AdminServerState> {
+    Router::new()
+        .route("/me", get(get_me))
+        .route("/commands", get(get_commands).put(set_commands))
+        .route("/messages", post(send_message))
+        .route("/messages/revoke", post(revoke_message))
+        .route("/updates", get(get_updates))
+}
+
+/// 校验 `Authorization: Bot <token>`，返回 Bot user_id
+async fn verify_bot_token(headers: &HeaderMap, state: &AdminServerState) -> Result<u64> {
+    let token = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_bot_authorization)
+        .ok_or_else(|| ServerError::Unauthorized("缺少 Authorization: Bot <token>".to_string()))?;
+    state.bot_service.authenticate(token).await
+}
+
+// ───────────────────────── 管理员 ─────────────────────────
+
+/// 令牌列表
+///
+/// GET /api/service/bots/:bot_user_id/tokens
+async fn list_tokens(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(bot_user_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let tokens = state.bot_service.list_tokens(bot_user_id).await?;
+    Ok(ApiEnvelope::ok(json!({ "tokens": tokens })))
+}
+
+/// 签发令牌
+///
+/// POST /api/service/bots/:bot_user_id/tokens
+async fn issue_token(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(bot_user_id): Path<u64>,
+    Json(request): Json<dto::IssueBotTokenRequest>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let (token, record) = state
+        .bot_service
+        .issue_token(bot_user_id, request.label.as_deref().unwrap_or_default())
+        .await?;
+    Ok(ApiEnvelope::ok(json!({
+        "token": token,
+        "token_info": record,
+    })))
+}
+
+/// 吊销令牌
+///
+/// DELETE /api/service/bots/:bot_user_id/tokens/:token_id
+async fn revoke_token(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path((bot_user_id, token_id)): Path<(u64, u64)>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state
+        .bot_service
+        .revoke_token(bot_user_id, token_id)
+        .await?;
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "token_id": token_id,
+    })))
+}
+
+/// 查看 Bot 注册的命令
+///
+/// GET /api/service/bots/:bot_user_id/commands
+async fn get_bot_commands(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(bot_user_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let commands = state.bot_service.get_commands(bot_user_id).await?;
+    Ok(ApiEnvelope::ok(json!({ "commands": commands })))
+}
+
+// ───────────────────────── Bot 自身 ─────────────────────────
+
+/// 当前 Bot 信息
+///
+/// GET /api/bot/me
+async fn get_me(State(state): State<AdminServerState>, headers: HeaderMap) -> ApiResult<Value> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let bot = state.bot_service.get_bot(bot_user_id).await?;
+    Ok(ApiEnvelope::ok(json!({
+        "bot_user_id": bot_user_id,
+        "username": bot.username,
+        "display_name": bot.display_name,
+        "avatar_url": bot.avatar_url,
+    })))
+}
+
+/// 命令列表
+///
+/// GET /api/bot/commands
+async fn get_commands(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<BotCommand>> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let commands = state.bot_service.get_commands(bot_user_id).await?;
+    Ok(ApiEnvelope::ok(commands))
+}
+
+/// 整体替换命令列表（空列表 = 清空）
+///
+/// PUT /api/bot/commands
+async fn set_commands(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Json(request): Json<dto::SetBotCommandsRequest>,
+) -> ApiResult<Vec<BotCommand>> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let commands = state
+        .bot_service
+        .set_commands(bot_user_id, &request.commands)
+        .await?;
+    info!(
+        "✅ Bot 命令已更新: bot_user_id={}, count={}",
+        bot_user_id,
+        commands.len()
+    );
+    Ok(ApiEnvelope::ok(commands))
+}
+
+/// 向所在会话发消息
+///
+/// POST /api/bot/messages
+///
+/// Bot 必须是该会话成员（私聊为会话双方之一，群聊为在群成员）；Room 不支持。
+/// 之后过与客户端相同的发送鉴权：慢速模式冷却中返回 429，其余拒绝返回 403。
+async fn send_message(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Json(request): Json<dto::BotSendMessageRequest>,
+) -> ApiResult<Value> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let content = request.content.trim().to_string();
+    if content.is_empty() || content.chars().count() > MAX_BOT_MESSAGE_CHARS {
+        return Err(ServerError::Validation(format!(
+            "消息内容长度必须在 1~{} 之间",
+            MAX_BOT_MESSAGE_CHARS
+        )));
+    }
+    let mut metadata = serde_json::Map::new();
+    if let Some(markup) = request.reply_markup {
+        markup.validate().map_err(ServerError::Validation)?;
+        metadata.insert(REPLY_MARKUP_KEY.to_string(), json!(markup));
+    }
+
+    let channel = state
+        .channel_service
+        .get_channel_opt(request.channel_id)
+        .await
+        .ok_or_else(|| ServerError::NotFound(format!("会话 {} 不存在", request.channel_id)))?;
+    if channel.channel_type == ChannelType::Room {
+        return Err(ServerError::Validation(
+            "Bot 不能向 Room 频道发消息".to_string(),
+        ));
+    }
+    if !channel.is_member(bot_user_id) {
+        return Err(ServerError::Forbidden(format!(
+            "Bot 不是会话 {} 的成员",
+            request.channel_id
+        )));
+    }
+    // 与客户端发消息同一套判定：禁言、发言角色、拉黑、慢速模式都不能因为
+    // 换了个入口就绕过去。
+    let deps = SendAuthorizationDeps {
+        channel_service: state.channel_service.clone(),
+        friend_service: state.friend_service.clone(),
+        blacklist_service: state.blacklist_service.clone(),
+        privacy_service: state.privacy_service.clone(),
+        group_slow_mode_service: state.group_slow_mode_service.clone(),
+    };
+    let permit = authorize_send_to_channel(&deps, &channel, bot_user_id).await?;
+    // 请求已校验完，占用慢速模式窗口；写入失败时交还
+    let claim = permit.claim().await?;
+
+    let result = match state
+        .message_service
+        .send_message(crate::service::ServerSendMessageRequest {
+            channel_id: request.channel_id,
+            sender_id: bot_user_id,
+            content,
+            message_type: ContentMessageType::Text,
+            metadata: Value::Object(metadata),
+            channel_type: channel.channel_type.to_wire_u8(),
+            recipient_user_ids: channel.get_member_ids(),
+            dedup_key: None,
+            attachment_refs_override: None,
+        })
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            claim.release().await;
+            return Err(ServerError::Internal(format!("Bot 发送消息失败: {}", e)));
+        }
+    };
+
+    Ok(ApiEnvelope::ok(json!({
+        "message_id": result.message_id,
+        "channel_id": request.channel_id,
+        "pts": result.pts,
+        "created_at": result.created_at,
+    })))
+}
+
+/// 撤回自己发的消息
+///
+/// POST /api/bot/messages/revoke
+async fn revoke_message(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Json(request): Json<dto::BotRevokeMessageRequest>,
+) -> ApiResult<Value> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let summary = state
+        .message_service
+        .revoke_message_with_auth(request.message_id, request.channel_id, bot_user_id)
+        .await?;
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "message_id": summary.message_id,
+        "channel_id": summary.channel_id,
+        "revoked_at": summary.revoked_at_ms,
+    })))
+}
+
+/// 长轮询拉取更新
+///
+/// GET /api/bot/updates?offset=&limit=&timeout=
+async fn get_updates(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Query(query): Query<dto::BotUpdatesQuery>,
+) -> ApiResult<Value> {
+    let bot_user_id = verify_bot_token(&headers, &state).await?;
+
+    let updates = state
+        .bot_service
+        .get_updates(
+            bot_user_id,
+            query.offset,
+            query
+                .limit
+                .unwrap_or(crate::service::bot_service::MAX_UPDATES_PER_POLL),
+            query.timeout.unwrap_or(0),
+        )
+        .await?;
+    Ok(ApiEnvelope::ok(json!({ "updates": updates })))
+}
@@ -20,19 +20,28 @@
 //! 路由结构：
 //! - 文件服务（端口 9083，对外）：
 //!   - `/api/app/files/upload` - 文件上传
+//!   - `/api/app/account/exports/{export_id}` - 个人数据导出归档下载（Bearer 鉴权）
 //!   - `/metrics` - Prometheus 指标
 //! - Service API（端口 9090，仅内网）：
 //!   - `/api/service/*` - 服务对服务的内网管理接口（统一前缀，X-Service-Key 鉴权）
+//!   - `/api/service/stickers/*` - 表情包库管理
+//!   - `/api/service/webhooks/*` - 出站 Webhook 端点 / 投递日志
+//!   - `/api/service/bots/*` - Bot 令牌签发 / 吊销
+//!   - `/api/bot/*` - Bot 平台 API（`Authorization: Bot <token>` 鉴权）
 //!
 //! 历史 `/api/admin/*` 前缀已于 v1.3 移除（spec SERVICE_API_SPEC v1.3）。
 
+pub mod account_exports;
 pub mod admin;
 pub mod auth;
 pub mod auth_jwks;
+pub mod bots;
 pub mod metrics;
 pub mod room_tickets;
+pub mod stickers;
 pub mod transfer;
 pub mod upload;
+pub mod webhooks;
 
 use crate::http::{AdminServerState, FileServerState};
 use axum::{routing::get, Router};
@@ -42,6 +51,7 @@ pub fn create_file_routes() -> Router<FileServerState> {
     Router::new()
         .route("/metrics", get(metrics::metrics_handler))
         .merge(upload::create_route())
+        .merge(account_exports::create_route())
 }
 
 /// 创建 Service API 路由（统一 `/api/service/*` 前缀）。
@@ -67,4 +77,11 @@ pub fn create_admin_routes() -> Router<AdminServerState> {
         // X-Service-Key gate; business APIs call this after deciding the
         // requesting user should be admitted to a Room channel.
         .nest("/api/service/room-tickets", room_tickets::create_route())
+        // 表情包库管理：目录 + 图片上传（X-Service-Key）
+        .nest("/api/service/stickers", stickers::create_route())
+        // 出站 Webhook：端点 CRUD + 投递日志 / 死信重放（X-Service-Key）
+        .nest("/api/service/webhooks", webhooks::create_route())
+        // Bot 平台：令牌管理走 X-Service-Key；`/api/bot/*` 由 Bot 令牌鉴权
+        .nest("/api/service/bots", bots::create_service_route())
+        .nest("/api/bot", bots::create_bot_route())
 }
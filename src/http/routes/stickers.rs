@@ -0,0 +1,353 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `/api/service/stickers/*` — 表情包库管理
+//!
+//! 目录存 PostgreSQL，图片经 FileService 写入默认存储源（business_type = `sticker`）。
+//! 认证：`X-Service-Key`（与 `/api/service/*` 其它端点一致）。
+//!
+//! - `GET    /packages`                               全部表情包库（含已下架）
+//! - `POST   /packages`                               创建表情包库
+//! - `GET    /packages/{package_id}`                  表情包库详情（含表情）
+//! - `PUT    /packages/{package_id}`                  更新表情包库（上下架、排序、缩略图…）
+//! - `DELETE /packages/{package_id}`                  删除表情包库
+//! - `POST   /packages/{package_id}/stickers`         上传图片并添加表情（multipart）
+//! - `DELETE /packages/{package_id}/stickers/{id}`    移除表情
+//! - `POST   /images`                                 仅上传图片（缩略图用，multipart）
+
+use crate::error::{Result, ServerError};
+use crate::http::dto::admin as dto;
+use crate::http::routes::admin::verify_service_key;
+use crate::http::{AdminServerState, ApiEnvelope, ApiResult};
+use crate::repository::{NewSticker, NewStickerPackage, StickerPackageUpdate};
+use crate::service::sticker_service::STICKER_MAX_IMAGE_BYTES;
+use crate::service::{Sticker, StickerPackage};
+use axum::{
+    extract::{DefaultBodyLimit, Path, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use axum_extra::extract::Multipart;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::info;
+
+/// multipart 除图片外的文本字段上限，body limit 在图片上限之上留出这部分余量
+const FORM_OVERHEAD_BYTES: usize = 64 * 1024;
+
+pub fn create_route() -> Router<AdminServerState> {
+    Router::new()
+        .route("/packages", get(list_packages).post(create_package))
+        .route(
+            "/packages/{package_id}",
+            get(get_package).put(update_package).delete(delete_package),
+        )
+        .route("/packages/{package_id}/stickers", post(add_sticker))
+        .route(
+            "/packages/{package_id}/stickers/{sticker_id}",
+            delete(remove_sticker),
+        )
+        .route("/images", post(upload_image))
+        .layer(DefaultBodyLimit::max(
+            STICKER_MAX_IMAGE_BYTES + FORM_OVERHEAD_BYTES,
+        ))
+}
+
+/// 上传的图片字段
+struct ImagePart {
+    data: bytes::Bytes,
+    filename: String,
+    mime_type: String,
+}
+
+/// 读取 multipart：`file` 字段作为图片，其余按文本字段收集
+async fn read_form(
+    multipart: &mut Multipart,
+) -> Result<(Option<ImagePart>, HashMap<String, String>)> {
+    let mut image = None;
+    let mut fields = HashMap::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ServerError::Validation(format!("解析 multipart 失败: {}", e)))?
+    {
+        let name = field.name().unwrap_or("").to_string();
+        if name == "file" {
+            let filename = field
+                .file_name()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "sticker.png".to_string());
+            let mime_type = field
+                .content_type()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "application/octet-stream".to_string());
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| ServerError::Validation(format!("读取图片失败: {}", e)))?;
+            image = Some(ImagePart {
+                data,
+                filename,
+                mime_type,
+            });
+        } else {
+            let value = field
+                .text()
+                .await
+                .map_err(|e| ServerError::Validation(format!("读取字段 {} 失败: {}", name, e)))?;
+            fields.insert(name, value);
+        }
+    }
+    Ok((image, fields))
+}
+
+fn parse_form_number<T: std::str::FromStr>(
+    fields: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<T>> {
+    match fields.get(name).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        Some(v) => v
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| ServerError::Validation(format!("{} 格式无效: {}", name, v))),
+        None => Ok(None),
+    }
+}
+
+/// 全部表情包库（含已下架）
+///
+/// GET /api/service/stickers/packages
+async fn list_packages(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let packages = state.sticker_service.list_all_packages().await?;
+    Ok(ApiEnvelope::ok(json!({
+        "packages": packages,
+        "total": packages.len(),
+    })))
+}
+
+/// 创建表情包库
+///
+/// POST /api/service/stickers/packages
+async fn create_package(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Json(request): Json<dto::CreateStickerPackageRequest>,
+) -> ApiResult<StickerPackage> {
+    verify_service_key(&headers, &state).await?;
+
+    let package = state
+        .sticker_service
+        .create_package(NewStickerPackage {
+            package_id: request.package_id,
+            name: request.name,
+            author: request.author.unwrap_or_default(),
+            description: request.description.unwrap_or_default(),
+            thumbnail_file_id: request.thumbnail_file_id,
+            is_enabled: request.is_enabled.unwrap_or(true),
+            sort_order: request.sort_order.unwrap_or(0),
+        })
+        .await?;
+
+    info!("✅ 创建表情包库: {}", package.package_id);
+    Ok(ApiEnvelope::ok(package))
+}
+
+/// 表情包库详情（含已下架的库）
+///
+/// GET /api/service/stickers/packages/:package_id
+async fn get_package(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(package_id): Path<String>,
+) -> ApiResult<StickerPackage> {
+    verify_service_key(&headers, &state).await?;
+
+    let package = state
+        .sticker_service
+        .get_package_detail(&package_id, true)
+        .await?
+        .ok_or_else(|| ServerError::NotFound(format!("表情包库 {} 不存在", package_id)))?;
+    Ok(ApiEnvelope::ok(package))
+}
+
+/// 更新表情包库
+///
+/// PUT /api/service/stickers/packages/:package_id
+async fn update_package(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(package_id): Path<String>,
+    Json(request): Json<dto::UpdateStickerPackageRequest>,
+) -> ApiResult<StickerPackage> {
+    verify_service_key(&headers, &state).await?;
+
+    let package = state
+        .sticker_service
+        .update_package(
+            &package_id,
+            StickerPackageUpdate {
+                name: request.name,
+                author: request.author,
+                description: request.description,
+                thumbnail_file_id: request.thumbnail_file_id,
+                is_enabled: request.is_enabled,
+                sort_order: request.sort_order,
+            },
+        )
+        .await?;
+
+    info!("✅ 更新表情包库: {}", package_id);
+    Ok(ApiEnvelope::ok(package))
+}
+
+/// 删除表情包库
+///
+/// DELETE /api/service/stickers/packages/:package_id
+async fn delete_package(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(package_id): Path<String>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state.sticker_service.delete_package(&package_id).await?;
+
+    info!("🗑️ 删除表情包库: {}", package_id);
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "package_id": package_id,
+    })))
+}
+
+/// 上传图片并向表情包库添加表情
+///
+/// POST /api/service/stickers/packages/:package_id/stickers
+///
+/// multipart 字段：`file`（必填）、`sticker_id`（必填）、`alt_text`、`emoji`、
+/// `width`、`height`（缺省取文件元数据）、`sort_order`
+async fn add_sticker(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(package_id): Path<String>,
+    mut multipart: Multipart,
+) -> ApiResult<Sticker> {
+    verify_service_key(&headers, &state).await?;
+
+    let (image, fields) = read_form(&mut multipart).await?;
+    let image = image.ok_or_else(|| ServerError::Validation("缺少 file 字段".to_string()))?;
+    let sticker_id = fields
+        .get("sticker_id")
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ServerError::Validation("缺少 sticker_id 字段".to_string()))?;
+    let width = parse_form_number::<u32>(&fields, "width")?;
+    let height = parse_form_number::<u32>(&fields, "height")?;
+    let sort_order = parse_form_number::<i32>(&fields, "sort_order")?.unwrap_or(0);
+
+    let metadata = state
+        .sticker_service
+        .store_image(image.data, &image.filename, &image.mime_type)
+        .await?;
+    let file_id = metadata.file_id;
+
+    let result = state
+        .sticker_service
+        .add_sticker(NewSticker {
+            sticker_id,
+            package_id: package_id.clone(),
+            file_id,
+            alt_text: fields.get("alt_text").cloned().unwrap_or_default(),
+            emoji: fields.get("emoji").cloned().filter(|s| !s.is_empty()),
+            width: width.or(metadata.width).unwrap_or(0),
+            height: height.or(metadata.height).unwrap_or(0),
+            mime_type: metadata.mime_type,
+            sort_order,
+        })
+        .await;
+
+    match result {
+        Ok(sticker) => {
+            info!(
+                "✅ 添加表情: {}/{} (file_id={})",
+                package_id, sticker.sticker_id, file_id
+            );
+            Ok(ApiEnvelope::ok(sticker))
+        }
+        Err(e) => {
+            // 图片已入库但目录没写上：回收图片，不留孤儿文件
+            state.sticker_service.discard_image(file_id).await;
+            Err(e)
+        }
+    }
+}
+
+/// 从表情包库移除表情
+///
+/// DELETE /api/service/stickers/packages/:package_id/stickers/:sticker_id
+async fn remove_sticker(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path((package_id, sticker_id)): Path<(String, String)>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state
+        .sticker_service
+        .remove_sticker(&package_id, &sticker_id)
+        .await?;
+
+    info!("🗑️ 移除表情: {}/{}", package_id, sticker_id);
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "package_id": package_id,
+        "sticker_id": sticker_id,
+    })))
+}
+
+/// 仅上传图片（表情包库缩略图），返回 file_id 供创建 / 更新表情包库时引用
+///
+/// POST /api/service/stickers/images
+async fn upload_image(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> ApiResult<dto::StickerImageResponse> {
+    verify_service_key(&headers, &state).await?;
+
+    let (image, _) = read_form(&mut multipart).await?;
+    let image = image.ok_or_else(|| ServerError::Validation("缺少 file 字段".to_string()))?;
+    let metadata = state
+        .sticker_service
+        .store_image(image.data, &image.filename, &image.mime_type)
+        .await?;
+
+    Ok(ApiEnvelope::ok(dto::StickerImageResponse {
+        file_id: metadata.file_id,
+        file_url: state
+            .sticker_service
+            .image_url(&metadata.file_path, metadata.storage_source_id),
+        mime_type: metadata.mime_type,
+        file_size: metadata.file_size,
+    }))
+}
@@ -0,0 +1,314 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! `/api/service/webhooks/*` — 出站 Webhook 管理
+//!
+//! 认证：`X-Service-Key`（与 `/api/service/*` 其它端点一致）。
+//!
+//! - `GET    /event-types`                         可订阅的事件类型
+//! - `GET    /endpoints`                           端点列表（不含 secret）
+//! - `POST   /endpoints`                           创建端点（响应里返回一次 secret）
+//! - `GET    /endpoints/{endpoint_id}`             端点详情
+//! - `PUT    /endpoints/{endpoint_id}`             更新端点（`rotate_secret` 轮换密钥）
+//! - `DELETE /endpoints/{endpoint_id}`             删除端点（投递日志一并删除）
+//! - `GET    /deliveries`                          投递日志（按端点 / 状态 / 事件类型过滤）
+//! - `GET    /deliveries/{delivery_id}`            投递详情（含 body）
+//! - `POST   /deliveries/{delivery_id}/replay`     重放单条投递
+//! - `GET    /dead-letters`                        死信视图
+//! - `POST   /dead-letters/replay`                 批量重放死信（`?endpoint_id=` 限定端点）
+
+use crate::error::Result;
+use crate::http::dto::admin as dto;
+use crate::http::routes::admin::verify_service_key;
+use crate::http::{AdminServerState, ApiEnvelope, ApiResult};
+use crate::repository::{
+    WebhookDeliveryFilter, WebhookEndpoint, WebhookEndpointUpdate, DELIVERY_STATUS_DEAD,
+};
+use crate::service::webhook_service::{generate_secret, WEBHOOK_EVENT_TYPES};
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde_json::{json, Value};
+use tracing::info;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+pub fn create_route() -> Router<AdminServerState> {
+    Router::new()
+        .route("/event-types", get(list_event_types))
+        .route("/endpoints", get(list_endpoints).post(create_endpoint))
+        .route(
+            "/endpoints/{endpoint_id}",
+            get(get_endpoint)
+                .put(update_endpoint)
+                .delete(delete_endpoint),
+        )
+        .route("/deliveries", get(list_deliveries))
+        .route("/deliveries/{delivery_id}", get(get_delivery))
+        .route("/deliveries/{delivery_id}/replay", post(replay_delivery))
+        .route("/dead-letters", get(list_dead_letters))
+        .route("/dead-letters/replay", post(replay_dead_letters))
+}
+
+fn delivery_filter(query: dto::WebhookDeliveryQuery) -> WebhookDeliveryFilter {
+    WebhookDeliveryFilter {
+        endpoint_id: query.endpoint_id,
+        status: query.status.filter(|s| !s.is_empty()),
+        event_type: query.event_type.filter(|s| !s.is_empty()),
+        before_id: query.before_id,
+        limit: query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE),
+    }
+}
+
+async fn list_deliveries_page(
+    state: &AdminServerState,
+    filter: WebhookDeliveryFilter,
+) -> Result<Value> {
+    let deliveries = state.webhook_service.list_deliveries(&filter).await?;
+    let next_before_id = if deliveries.len() as u32 >= filter.limit {
+        deliveries.last().map(|d| d.delivery_id)
+    } else {
+        None
+    };
+    Ok(json!({
+        "deliveries": deliveries,
+        "next_before_id": next_before_id,
+    }))
+}
+
+/// 可订阅的事件类型
+///
+/// GET /api/service/webhooks/event-types
+async fn list_event_types(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    Ok(ApiEnvelope::ok(
+        json!({ "event_types": WEBHOOK_EVENT_TYPES }),
+    ))
+}
+
+/// 端点列表
+///
+/// GET /api/service/webhooks/endpoints
+async fn list_endpoints(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let endpoints = state.webhook_service.list_endpoints().await?;
+    Ok(ApiEnvelope::ok(json!({
+        "endpoints": endpoints,
+        "total": endpoints.len(),
+    })))
+}
+
+/// 创建端点
+///
+/// POST /api/service/webhooks/endpoints
+async fn create_endpoint(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Json(request): Json<dto::CreateWebhookEndpointRequest>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let endpoint = state
+        .webhook_service
+        .create_endpoint(
+            &request.url,
+            &request.event_types,
+            request.secret,
+            request.description.unwrap_or_default(),
+            request.is_enabled.unwrap_or(true),
+        )
+        .await?;
+
+    info!(
+        "✅ 创建 Webhook 端点: endpoint_id={}, url={}",
+        endpoint.endpoint_id, endpoint.url
+    );
+    Ok(ApiEnvelope::ok(json!({
+        "secret": endpoint.secret,
+        "endpoint": endpoint,
+    })))
+}
+
+/// 端点详情
+///
+/// GET /api/service/webhooks/endpoints/:endpoint_id
+async fn get_endpoint(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(endpoint_id): Path<u64>,
+) -> ApiResult<WebhookEndpoint> {
+    verify_service_key(&headers, &state).await?;
+
+    let endpoint = state.webhook_service.get_endpoint(endpoint_id).await?;
+    Ok(ApiEnvelope::ok(endpoint))
+}
+
+/// 更新端点
+///
+/// PUT /api/service/webhooks/endpoints/:endpoint_id
+async fn update_endpoint(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(endpoint_id): Path<u64>,
+    Json(request): Json<dto::UpdateWebhookEndpointRequest>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let new_secret = request.rotate_secret.then(generate_secret);
+    let endpoint = state
+        .webhook_service
+        .update_endpoint(
+            endpoint_id,
+            WebhookEndpointUpdate {
+                url: request.url,
+                secret: new_secret.clone(),
+                event_types: request.event_types,
+                description: request.description,
+                is_enabled: request.is_enabled,
+            },
+        )
+        .await?;
+
+    info!("✅ 更新 Webhook 端点: endpoint_id={}", endpoint_id);
+    let mut data = json!({ "endpoint": endpoint });
+    if let Some(secret) = new_secret {
+        data["secret"] = json!(secret);
+    }
+    Ok(ApiEnvelope::ok(data))
+}
+
+/// 删除端点
+///
+/// DELETE /api/service/webhooks/endpoints/:endpoint_id
+async fn delete_endpoint(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(endpoint_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state.webhook_service.delete_endpoint(endpoint_id).await?;
+
+    info!("🗑️ 删除 Webhook 端点: endpoint_id={}", endpoint_id);
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "endpoint_id": endpoint_id,
+    })))
+}
+
+/// 投递日志
+///
+/// GET /api/service/webhooks/deliveries?endpoint_id=&status=&event_type=&before_id=&limit=
+async fn list_deliveries(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Query(query): Query<dto::WebhookDeliveryQuery>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let page = list_deliveries_page(&state, delivery_filter(query)).await?;
+    Ok(ApiEnvelope::ok(page))
+}
+
+/// 投递详情（含投递体，便于排查签名问题）
+///
+/// GET /api/service/webhooks/deliveries/:delivery_id
+async fn get_delivery(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(delivery_id): Path<u64>,
+) -> ApiResult<crate::repository::WebhookDelivery> {
+    verify_service_key(&headers, &state).await?;
+
+    let delivery = state.webhook_service.get_delivery(delivery_id).await?;
+    Ok(ApiEnvelope::ok(delivery))
+}
+
+/// 重放单条投递：尝试次数归零，worker 下一轮重新投递（body 与 event_id 不变）
+///
+/// POST /api/service/webhooks/deliveries/:delivery_id/replay
+async fn replay_delivery(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Path(delivery_id): Path<u64>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    state.webhook_service.replay_delivery(delivery_id).await?;
+
+    info!("🔁 重放 Webhook 投递: delivery_id={}", delivery_id);
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "delivery_id": delivery_id,
+    })))
+}
+
+/// 死信视图：超过最大尝试次数仍失败的投递
+///
+/// GET /api/service/webhooks/dead-letters?endpoint_id=&event_type=&before_id=&limit=
+async fn list_dead_letters(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Query(query): Query<dto::WebhookDeliveryQuery>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let mut filter = delivery_filter(query);
+    filter.status = Some(DELIVERY_STATUS_DEAD.to_string());
+    let page = list_deliveries_page(&state, filter).await?;
+    Ok(ApiEnvelope::ok(page))
+}
+
+/// 批量重放死信（对端恢复后使用）
+///
+/// POST /api/service/webhooks/dead-letters/replay?endpoint_id=
+async fn replay_dead_letters(
+    State(state): State<AdminServerState>,
+    headers: HeaderMap,
+    Query(request): Query<dto::ReplayWebhookDeadLettersRequest>,
+) -> ApiResult<Value> {
+    verify_service_key(&headers, &state).await?;
+
+    let replayed = state
+        .webhook_service
+        .replay_dead_letters(request.endpoint_id)
+        .await?;
+
+    info!(
+        "🔁 批量重放 Webhook 死信: endpoint_id={:?}, count={}",
+        request.endpoint_id, replayed
+    );
+    Ok(ApiEnvelope::ok(json!({
+        "success": true,
+        "replayed": replayed,
+    })))
+}
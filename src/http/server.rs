@@ -26,14 +26,14 @@ use tracing::info;
 use crate::auth::DeviceManagerDb;
 use crate::auth::{ServiceKeyManager, TokenIssueService};
 use crate::http::routes;
-use crate::infra::{ConnectionManager, SubscribeManager};
+use crate::infra::{ConnectionManager, RoomFanout, SubscribeManager};
 use crate::repository::{LoginLogRepository, PgMessageRepository, UserRepository};
 // UserRepository is not exposed in AdminServerState — admin handlers must go through UserService.
 // It is still needed as a constructor dependency for AdminService (until that also converges).
 use crate::security::SecurityService;
 use crate::service::{
-    AdminService, ChannelService, FileService, FriendService, MessageService, RoomHistoryService,
-    UploadTokenService, UserService,
+    AdminService, ChannelService, FileService, FriendService, MessageService, QRCodeService,
+    RoomChatService, RoomHistoryService, UploadTokenService, UserService,
 };
 
 /// 文件服务器共享状态
@@ -50,6 +50,10 @@ pub struct FileServerState {
     /// `None` = 没接验证器（单元测试装配），此时要求登录态的端点一律拒绝，
     /// 而不是放行——缺省必须是拒绝。
     pub auth: Option<Arc<dyn UploadAuthenticator>>,
+    /// 个人数据导出（`GET /api/app/account/exports/{export_id}` 下载归档）。
+    ///
+    /// `None` = 未装配（单元测试），下载端点返回 503。
+    pub data_export_service: Option<Arc<crate::service::DataExportService>>,
 }
 
 /// 「这个 bearer 是谁」——文件服务需要知道的**全部**。
@@ -89,6 +93,12 @@ pub struct AdminServerState {
     pub admin_service: Arc<AdminService>,
     pub subscribe_manager: Arc<SubscribeManager>,
     pub room_history_service: Arc<RoomHistoryService>,
+    /// Room 广播扇出（本节点投递 + 跨节点中继、集群在线数）。
+    pub room_fanout: Arc<RoomFanout>,
+    /// Room 订阅者聊天设置（开关 / 慢速模式 / 主持人）。
+    pub room_chat_service: Arc<RoomChatService>,
+    /// 二维码 Key 与扫描日志（管理端查看扫描历史）。
+    pub qrcode_service: Arc<QRCodeService>,
     pub message_service: Arc<MessageService>,
     pub user_service: Arc<UserService>,
     /// Web 扫码登录场景服务（spec QR_API §4）。
@@ -102,6 +112,18 @@ pub struct AdminServerState {
     pub room_ticket: Option<Arc<crate::config::RoomTicketConfig>>,
     /// 隐私服务(PROFILE_VISIBILITY P2:平台级开关 admin 读写)。
     pub privacy_service: Arc<crate::service::PrivacyService>,
+    /// 黑名单服务（Bot 发消息的发送鉴权）。
+    pub blacklist_service: Arc<crate::service::BlacklistService>,
+    /// 群慢速模式服务（Bot 发消息的发送鉴权）。
+    pub group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
+    /// 表情包服务（`/api/service/stickers/*` 目录管理）。
+    pub sticker_service: Arc<crate::service::StickerService>,
+    /// 两步验证服务（管理员重置用户 2FA）。
+    pub two_factor_service: Arc<crate::service::TwoFactorService>,
+    /// 出站 Webhook 服务（`/api/service/webhooks/*` 端点与投递日志）。
+    pub webhook_service: Arc<crate::service::WebhookService>,
+    /// Bot 平台服务（`/api/bot/*` 与 Bot 令牌管理）。
+    pub bot_service: Arc<crate::service::BotService>,
 }
 
 /// HTTP 文件服务器（对外，0.0.0.0）
@@ -115,6 +137,7 @@ impl FileHttpServer {
         file_service: Arc<FileService>,
         upload_token_service: Arc<UploadTokenService>,
         auth: Option<Arc<dyn UploadAuthenticator>>,
+        data_export_service: Option<Arc<crate::service::DataExportService>>,
         port: u16,
     ) -> Self {
         Self {
@@ -122,6 +145,7 @@ impl FileHttpServer {
                 file_service,
                 upload_token_service,
                 auth,
+                data_export_service,
             },
             port,
         }
@@ -176,6 +200,9 @@ impl AdminHttpServer {
         security_service: Arc<SecurityService>,
         subscribe_manager: Arc<SubscribeManager>,
         room_history_service: Arc<RoomHistoryService>,
+        room_fanout: Arc<RoomFanout>,
+        room_chat_service: Arc<RoomChatService>,
+        qrcode_service: Arc<QRCodeService>,
         message_service: Arc<MessageService>,
         user_service: Arc<UserService>,
         qr_login_service: Arc<crate::service::qr_login_service::QrLoginService>,
@@ -183,6 +210,12 @@ impl AdminHttpServer {
         unified_token_service: Arc<crate::auth::UnifiedTokenService>,
         room_ticket: Option<Arc<crate::config::RoomTicketConfig>>,
         privacy_service: Arc<crate::service::PrivacyService>,
+        blacklist_service: Arc<crate::service::BlacklistService>,
+        group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
+        sticker_service: Arc<crate::service::StickerService>,
+        two_factor_service: Arc<crate::service::TwoFactorService>,
+        webhook_service: Arc<crate::service::WebhookService>,
+        bot_service: Arc<crate::service::BotService>,
         port: u16,
     ) -> Self {
         let admin_service = Arc::new(AdminService::new(
@@ -206,6 +239,9 @@ impl AdminHttpServer {
                 admin_service,
                 subscribe_manager,
                 room_history_service,
+                room_fanout,
+                room_chat_service,
+                qrcode_service,
                 message_service,
                 user_service,
                 qr_login_service,
@@ -213,6 +249,12 @@ impl AdminHttpServer {
                 unified_token_service,
                 room_ticket,
                 privacy_service,
+                blacklist_service,
+                group_slow_mode_service,
+                sticker_service,
+                two_factor_service,
+                webhook_service,
+                bot_service,
             },
             port,
         }
@@ -56,6 +56,13 @@ lazy_static! {
         // 用户登录 - 返回 JWT token，可直接用于 AuthorizationRequest
         set.insert("account/auth/login".to_string());
 
+        // 两步验证登录 - login 返回 challenge_token 后提交动态码 / 恢复码，此时还没有 token
+        set.insert("account/auth/login_2fa".to_string());
+
+        // 找回密码 - 忘记密码时没有 token；申请对外结果恒定，重置凭一次性令牌
+        set.insert("account/password/reset/request".to_string());
+        set.insert("account/password/reset/confirm".to_string());
+
         // 用户注册 - 注册成功后返回 JWT token，可直接用于 AuthorizationRequest
         set.insert("account/user/register".to_string());
 
@@ -131,6 +138,9 @@ mod tests {
         assert!(is_anonymous_rpc_route("system/health"));
         assert!(is_anonymous_rpc_route("system/info"));
         assert!(is_anonymous_rpc_route("account/auth/login"));
+        assert!(is_anonymous_rpc_route("account/auth/login_2fa"));
+        assert!(is_anonymous_rpc_route("account/password/reset/request"));
+        assert!(is_anonymous_rpc_route("account/password/reset/confirm"));
         assert!(is_anonymous_rpc_route("account/user/register"));
         assert!(is_anonymous_rpc_route("account/auth/refresh"));
         assert!(is_anonymous_rpc_route("qr_login/create_scene"));
@@ -140,6 +150,8 @@ mod tests {
         assert!(!is_anonymous_rpc_route("message/send"));
         assert!(!is_anonymous_rpc_route("group/member/remove"));
         assert!(!is_anonymous_rpc_route("user/profile/update"));
+        assert!(!is_anonymous_rpc_route("account/2fa/disable"));
+        assert!(!is_anonymous_rpc_route("account/password/change"));
     }
 
     #[test]
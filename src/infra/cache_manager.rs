@@ -15,8 +15,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bb8::Pool;
-use bb8_redis::RedisConnectionManager;
 use moka::future::Cache;
 use redis::AsyncCommands;
 use serde::{Deserialize, Serialize};
@@ -92,8 +90,8 @@ pub struct CachedChannel {
     pub last_activity: chrono::DateTime<chrono::Utc>,
 }
 
-/// Redis 连接池类型
-pub type RedisPool = Pool<RedisConnectionManager>;
+/// Redis 连接池类型（单节点 / Sentinel / Cluster）
+pub type RedisPool = crate::infra::redis_pool::RedisPool;
 
 /// 缓存管理器
 /// 支持基于内存大小的 L1 缓存 + Redis L2 缓存
@@ -275,18 +273,10 @@ impl CacheManager {
         let (redis_pool, redis_config) = if let Some(redis_config) = &config.redis {
             info!("🔧 Initializing Redis L2 cache: {}", redis_config.url);
 
-            let manager = RedisConnectionManager::new(redis_config.url.clone()).map_err(|e| {
-                ServerError::Internal(format!("Failed to create Redis manager: {}", e))
-            })?;
-
-            let pool = Pool::builder()
-                .max_size(redis_config.pool_size)
-                .connection_timeout(redis_config.connection_timeout())
-                .build(manager)
+            // 按 mode 建立单节点 / Sentinel / Cluster 连接
+            let pool = crate::infra::redis_pool::RedisPool::connect(redis_config)
                 .await
-                .map_err(|e| {
-                    ServerError::Internal(format!("Failed to create Redis pool: {}", e))
-                })?;
+                .map_err(ServerError::Internal)?;
 
             // 测试连接
             {
@@ -96,13 +96,24 @@ impl SessionOwnershipRegistry {
             device_id: device_id.to_string(),
             session_id: session_id.as_u64(),
         })?;
-        self.redis
-            .zadd(
+        // 只有仍持有节点租约时才登记归属：租约被抢走的节点不能再写入 owner 记录
+        let written = self
+            .redis
+            .zadd_if_fenced(
+                &node_lease_key(&self.node_id),
+                &self.node_lease_token,
                 &owner_key(user_id),
                 (chrono::Utc::now().timestamp_millis() + OWNER_LEASE_TTL_MS) as f64,
                 &member,
+                OWNER_LEASE_TTL_MS * 2,
             )
             .await?;
+        if !written {
+            return Err(anyhow!(
+                "cluster node lease for {:?} is no longer held",
+                self.node_id
+            ));
+        }
         Ok(())
     }
 
@@ -387,12 +398,17 @@ fn response_from_report(request_id: &str, report: DeliveryReport) -> CrossNodeDi
     }
 }
 
+// 节点租约与 owner 集合在 fenced 注册脚本里一起访问，Redis Cluster 下必须同 slot，
+// 因此共享 `{privchat:cluster}` hash tag。代价是所有 owner 集合集中在一个 slot，
+// 这部分数据量与在线会话数成正比、体积很小，可以接受；分发队列 / 应答 key 不加 tag，仍然分散。
+const OWNERSHIP_HASH_TAG: &str = "{privchat:cluster}";
+
 fn node_lease_key(node_id: &str) -> String {
-    format!("privchat:cluster:node:{node_id}")
+    format!("{OWNERSHIP_HASH_TAG}:node:{node_id}")
 }
 
 fn owner_key(user_id: u64) -> String {
-    format!("privchat:cluster:owners:{user_id}")
+    format!("{OWNERSHIP_HASH_TAG}:owners:{user_id}")
 }
 
 fn dispatch_queue_key(node_id: &str) -> String {
@@ -413,6 +429,16 @@ mod tests {
         assert!(dispatch_response_key("node-a", "req-1").contains("node-a:req-1"));
     }
 
+    #[test]
+    fn ownership_keys_share_a_cluster_slot() {
+        use crate::infra::redis_pool::key_slot;
+        assert_eq!(
+            key_slot(&node_lease_key("node-a")),
+            key_slot(&owner_key(42))
+        );
+        assert_eq!(key_slot(&owner_key(1)), key_slot(&owner_key(u64::MAX)));
+    }
+
     #[test]
     fn typed_dispatch_roundtrip_preserves_large_ids() {
         let request = CrossNodeDispatchRequest {
@@ -447,6 +473,7 @@ mod tests {
                 connection_timeout_secs: 2,
                 command_timeout_ms: 2_000,
                 idle_timeout_secs: 30,
+                ..Default::default()
             })
             .await
             .unwrap(),
@@ -234,6 +234,7 @@ pub mod stages {
     pub const FANOUT: &str = "fanout";
     pub const PUSH_SENT: &str = "push_sent";
     pub const PUSH_FAILED: &str = "push_failed";
+    pub const PUSH_RETRY: &str = "push_retry";
     pub const OFFLINE_ENQUEUED: &str = "offline_enqueued";
     pub const CATCHUP_DELIVERED: &str = "catchup_delivered";
 }
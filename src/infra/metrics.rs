@@ -46,6 +46,8 @@ const GAUGE_REDIS_POOL_ACTIVE: &str = "privchat_redis_pool_active";
 const GAUGE_REDIS_POOL_IDLE: &str = "privchat_redis_pool_idle";
 const GAUGE_DB_POOL_ACTIVE: &str = "privchat_db_pool_active";
 const GAUGE_DB_POOL_IDLE: &str = "privchat_db_pool_idle";
+/// 只读副本复制延迟（毫秒，label: replica；-1 = 探测失败）
+const GAUGE_DB_REPLICA_LAG_MS: &str = "privchat_db_replica_lag_ms";
 const GAUGE_OFFLINE_QUEUE_DEPTH: &str = "privchat_offline_queue_depth";
 const COUNTER_OFFLINE_TRY_SEND_FAIL: &str = "privchat_offline_try_send_fail_total";
 const COUNTER_OFFLINE_FALLBACK: &str = "privchat_offline_fallback_total";
@@ -240,6 +242,11 @@ pub fn record_db_pool(active: u32, idle: u32) {
     metrics::gauge!(GAUGE_DB_POOL_IDLE).set(idle as f64);
 }
 
+/// 更新只读副本复制延迟（Gauge）。
+pub fn record_db_replica_lag(replica: &str, lag_ms: i64) {
+    metrics::gauge!(GAUGE_DB_REPLICA_LAG_MS, "replica" => replica.to_string()).set(lag_ms as f64);
+}
+
 /// 更新离线队列深度（Gauge）。
 pub fn record_offline_queue_depth(depth: usize) {
     metrics::gauge!(GAUGE_OFFLINE_QUEUE_DEPTH).set(depth as f64);
@@ -45,11 +45,15 @@ pub mod metrics;
 pub mod offline_worker;
 pub mod presence_manager_with_db;
 pub mod presence_tracker;
+pub mod read_replica;
 pub mod redis;
+pub mod redis_pool;
+pub mod room_fanout; // room 广播的本节点投递与跨节点中继
 pub mod session_manager;
 pub mod single_instance_guard;
 pub mod snowflake;
 pub mod subscribe_manager; // 频道事件订阅管理
+pub mod tar_archive; // 最小 ustar 归档写入（个人数据导出）
 pub mod typing_rate_limiter; // typing 限频器 // ✨ 新增：事件总线
                              // pub mod redis_cache;
                              // pub mod service_protocol;
@@ -86,6 +90,7 @@ pub use presence_manager_with_db::{
     PresenceStats as PresenceStateStoreStats,
 };
 pub use presence_tracker::PresenceTracker;
+pub use room_fanout::{RoomFanout, RoomPublishPermit, RoomPublishReport};
 pub use session_manager::{SessionInfo, SessionManager}; // ✨ 新增
 pub use single_instance_guard::{SingleInstanceGuard, SingleInstanceGuardError};
 pub use subscribe_manager::{
@@ -95,6 +100,7 @@ pub use typing_rate_limiter::TypingRateLimiter;
 
 // 数据库连接管理
 pub use database::Database;
+pub use read_replica::{read_pool, with_primary_reads, with_primary_reads_if, ReadReplicas};
 
 // Snowflake ID生成器
 pub use snowflake::{next_channel_id, next_message_id};
@@ -0,0 +1,261 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! PostgreSQL 只读副本路由
+//!
+//! 主库只有一个 `PgPool`，列表 / 搜索 / 历史 / 实体同步首屏这些重读路径和消息提交抢连接。
+//! 配置 `[database] replica_urls` 后，这些路径改用 [`read_pool`] 取连接池：
+//!
+//! - 后台按 `replica_check_interval_secs` 探测每个副本的复制延迟，
+//!   延迟超过 `replica_max_lag_ms`、与主库断开（没有 streaming 的 WAL receiver）
+//!   或探测失败的副本暂停使用；
+//! - 可用副本轮询分摊，没有可用副本时回落主库；
+//! - 必须读到自己刚写入数据的路径用 [`with_primary_reads`] 包住，范围内的读一律走主库。
+//!
+//! 写入、事务以及未改用 [`read_pool`] 的查询不受影响，始终在主库。
+
+use crate::config::DatabaseConfig;
+use crate::infra::database::{mask_database_url, pool_options};
+use sqlx::PgPool;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+static GLOBAL_READ_REPLICAS: OnceLock<Arc<ReadReplicas>> = OnceLock::new();
+
+tokio::task_local! {
+    /// 设置时本任务内的 [`read_pool`] 一律返回主库
+    static PRIMARY_READS: ();
+}
+
+/// 副本探测 SQL：非 recovery（未做复制的实例）视为无延迟；
+/// 没有处于 streaming 的 WAL receiver 时返回 -1（与主库断开）；
+/// 流复制正常且已收到的 WAL 全部回放时主库空闲也不算延迟，否则取最后回放事务距今的毫秒数。
+///
+/// 🔴 receiver 检查必须在 LSN 相等判断之前：与主库断开后不再收 WAL，
+/// receive_lsn 停住、回放很快追平，两者相等会被当成零延迟，副本停在断开那一刻却照常接读。
+/// 读 `pg_stat_wal_receiver.status` 需要 `pg_read_all_stats`，否则为 NULL，副本按不可用处理。
+const LAG_QUERY: &str = r#"
+SELECT CASE
+    WHEN NOT pg_is_in_recovery() THEN 0
+    WHEN NOT EXISTS (
+        SELECT 1 FROM pg_stat_wal_receiver WHERE status = 'streaming'
+    ) THEN -1
+    WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+    ELSE COALESCE(
+        (EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp()) * 1000)::BIGINT,
+        -1
+    )
+END
+"#;
+
+struct Replica {
+    label: String,
+    pool: PgPool,
+    usable: AtomicBool,
+    /// 最近一次探测到的延迟（毫秒）；-1 = 探测失败 / 未知
+    lag_ms: AtomicI64,
+}
+
+/// 只读副本集合
+pub struct ReadReplicas {
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+    max_lag_ms: u64,
+    check_interval: Duration,
+}
+
+impl ReadReplicas {
+    /// 按配置创建副本连接池（惰性连接：副本不可达不影响启动）并做一次延迟探测。
+    /// 未配置副本时返回 None。
+    pub async fn connect(config: &DatabaseConfig) -> Result<Option<Self>, sqlx::Error> {
+        if config.replica_urls.is_empty() {
+            return Ok(None);
+        }
+        let mut replicas = Vec::with_capacity(config.replica_urls.len());
+        for url in &config.replica_urls {
+            let pool = pool_options(config).connect_lazy(url)?;
+            replicas.push(Replica {
+                label: mask_database_url(url),
+                pool,
+                usable: AtomicBool::new(false),
+                lag_ms: AtomicI64::new(-1),
+            });
+        }
+        let set = Self {
+            replicas,
+            next: AtomicUsize::new(0),
+            max_lag_ms: config.replica_max_lag_ms,
+            check_interval: Duration::from_secs(config.replica_check_interval_secs.max(1)),
+        };
+        set.refresh_lag().await;
+        info!(
+            "✅ PostgreSQL 只读副本已配置: {} 个（可用 {}），延迟上限 {}ms",
+            set.replicas.len(),
+            set.usable_count(),
+            set.max_lag_ms
+        );
+        Ok(Some(set))
+    }
+
+    /// 轮询挑一个当前可用的副本
+    pub fn pick(&self) -> Option<&PgPool> {
+        let len = self.replicas.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..len)
+            .map(|i| &self.replicas[(start + i) % len])
+            .find(|r| r.usable.load(Ordering::Relaxed))
+            .map(|r| &r.pool)
+    }
+
+    pub fn usable_count(&self) -> usize {
+        self.replicas
+            .iter()
+            .filter(|r| r.usable.load(Ordering::Relaxed))
+            .count()
+    }
+
+    /// 探测所有副本的复制延迟并更新可用状态
+    pub async fn refresh_lag(&self) {
+        for replica in &self.replicas {
+            let lag_ms = match tokio::time::timeout(
+                self.check_interval,
+                sqlx::query_scalar::<_, i64>(LAG_QUERY).fetch_one(&replica.pool),
+            )
+            .await
+            {
+                Ok(Ok(lag_ms)) if lag_ms < 0 => {
+                    warn!(
+                        "⚠️ 只读副本未在流复制（无 streaming WAL receiver）: {}",
+                        replica.label
+                    );
+                    -1
+                }
+                Ok(Ok(lag_ms)) => lag_ms,
+                Ok(Err(e)) => {
+                    warn!("⚠️ 只读副本延迟探测失败: {}: {}", replica.label, e);
+                    -1
+                }
+                Err(_) => {
+                    warn!("⚠️ 只读副本延迟探测超时: {}", replica.label);
+                    -1
+                }
+            };
+            let usable = lag_within_limit(lag_ms, self.max_lag_ms);
+            let was_usable = replica.usable.swap(usable, Ordering::Relaxed);
+            replica.lag_ms.store(lag_ms, Ordering::Relaxed);
+            crate::infra::metrics::record_db_replica_lag(&replica.label, lag_ms);
+            if was_usable != usable {
+                if usable {
+                    info!(
+                        "✅ 只读副本恢复使用: {}（延迟 {}ms）",
+                        replica.label, lag_ms
+                    );
+                } else {
+                    warn!(
+                        "⚠️ 只读副本暂停使用，读请求回落主库: {}（延迟 {}ms，上限 {}ms）",
+                        replica.label, lag_ms, self.max_lag_ms
+                    );
+                }
+            }
+        }
+    }
+
+    /// 后台定时探测延迟
+    pub fn spawn_lag_monitor(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(self.check_interval);
+            loop {
+                tick.tick().await;
+                self.refresh_lag().await;
+            }
+        });
+    }
+}
+
+/// 延迟可接受：探测成功且不超过上限
+fn lag_within_limit(lag_ms: i64, max_lag_ms: u64) -> bool {
+    lag_ms >= 0 && lag_ms as u64 <= max_lag_ms
+}
+
+pub fn set_global_read_replicas(replicas: Arc<ReadReplicas>) {
+    let _ = GLOBAL_READ_REPLICAS.set(replicas);
+}
+
+pub fn get_global_read_replicas() -> Option<Arc<ReadReplicas>> {
+    GLOBAL_READ_REPLICAS.get().cloned()
+}
+
+/// 读路径取连接池：有可用副本且不在 [`with_primary_reads`] 范围内时返回副本，否则返回 `primary`
+pub fn read_pool(primary: &PgPool) -> &PgPool {
+    if PRIMARY_READS.try_with(|_| ()).is_ok() {
+        return primary;
+    }
+    GLOBAL_READ_REPLICAS
+        .get()
+        .and_then(|replicas| replicas.pick())
+        .unwrap_or(primary)
+}
+
+/// 读自己刚写入的数据：范围内的 [`read_pool`] 一律返回主库
+pub async fn with_primary_reads<F: Future>(fut: F) -> F::Output {
+    PRIMARY_READS.scope((), fut).await
+}
+
+/// `primary == true` 时等同 [`with_primary_reads`]，否则照常路由
+pub async fn with_primary_reads_if<F: Future>(primary: bool, fut: F) -> F::Output {
+    if primary {
+        with_primary_reads(fut).await
+    } else {
+        fut.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lag_limit_rejects_unknown_and_stale_replicas() {
+        assert!(lag_within_limit(0, 1000));
+        assert!(lag_within_limit(1000, 1000));
+        assert!(!lag_within_limit(1001, 1000));
+        assert!(!lag_within_limit(-1, 1000));
+    }
+
+    #[test]
+    fn lag_query_requires_a_streaming_receiver_before_trusting_lsns() {
+        let receiver = LAG_QUERY.find("pg_stat_wal_receiver").unwrap();
+        let lsn_shortcut = LAG_QUERY.find("pg_last_wal_receive_lsn()").unwrap();
+        assert!(receiver < lsn_shortcut);
+        assert!(LAG_QUERY.contains("status = 'streaming'"));
+    }
+
+    #[tokio::test]
+    async fn primary_reads_scope_is_visible_only_inside() {
+        assert!(PRIMARY_READS.try_with(|_| ()).is_err());
+        with_primary_reads(async {
+            assert!(PRIMARY_READS.try_with(|_| ()).is_ok());
+        })
+        .await;
+        with_primary_reads_if(false, async {
+            assert!(PRIMARY_READS.try_with(|_| ()).is_err());
+        })
+        .await;
+    }
+}
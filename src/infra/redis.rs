@@ -16,20 +16,19 @@
 // limitations under the License.
 
 // RedisClient - Redis客户端实现
-// 基于 bb8-redis 连接池
+// 基于 RedisPool（单节点 / Sentinel 为 bb8 连接池，Cluster 为多路复用集群连接）
 
-use bb8::Pool;
-use bb8_redis::RedisConnectionManager;
 use redis::AsyncCommands;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::config::RedisConfig;
+use crate::infra::redis_pool::{group_by_slot, RedisConn, RedisPool, RedisPoolState};
 
 /// Redis 客户端（基于连接池）
 pub struct RedisClient {
-    pool: Arc<Pool<RedisConnectionManager>>,
+    pool: Arc<RedisPool>,
     /// 单条 Redis 命令的执行超时
     command_timeout: Duration,
 }
@@ -37,20 +36,9 @@ pub struct RedisClient {
 impl RedisClient {
     /// 创建新的 Redis 客户端（从 RedisConfig 配置）
     pub async fn new(config: &RedisConfig) -> Result<Self, crate::error::ServerError> {
-        let manager = RedisConnectionManager::new(config.url.clone()).map_err(|e| {
-            crate::error::ServerError::Internal(format!("Failed to create Redis manager: {}", e))
-        })?;
-
-        let pool = Pool::builder()
-            .max_size(config.pool_size)
-            .min_idle(Some(config.min_idle))
-            .connection_timeout(config.connection_timeout())
-            .idle_timeout(Some(config.idle_timeout()))
-            .build(manager)
+        let pool = RedisPool::connect(config)
             .await
-            .map_err(|e| {
-                crate::error::ServerError::Internal(format!("Failed to create Redis pool: {}", e))
-            })?;
+            .map_err(crate::error::ServerError::Internal)?;
 
         let command_timeout = config.command_timeout();
 
@@ -69,7 +57,8 @@ impl RedisClient {
         }
 
         tracing::info!(
-            "✅ Redis 连接池已创建 (pool_size={}, min_idle={}, conn_timeout={}s, cmd_timeout={}ms, idle_timeout={}s)",
+            "✅ Redis 连接池已创建 (mode={:?}, pool_size={}, min_idle={}, conn_timeout={}s, cmd_timeout={}ms, idle_timeout={}s)",
+            config.mode,
             config.pool_size,
             config.min_idle,
             config.connection_timeout_secs,
@@ -84,19 +73,36 @@ impl RedisClient {
     }
 
     /// 获取连接池状态（活跃连接数、空闲连接数）
-    pub fn pool_state(&self) -> bb8::State {
+    pub fn pool_state(&self) -> RedisPoolState {
         self.pool.state()
     }
 
     /// 从连接池获取连接
-    async fn get_conn(
-        &self,
-    ) -> Result<bb8::PooledConnection<'_, RedisConnectionManager>, crate::error::ServerError> {
+    pub(crate) async fn get_conn(&self) -> Result<RedisConn<'_>, crate::error::ServerError> {
         self.pool.get().await.map_err(|e| {
             crate::error::ServerError::Internal(format!("Failed to get Redis connection: {}", e))
         })
     }
 
+    /// 执行一组各自带 key 的命令。
+    ///
+    /// 单节点 / Sentinel 下合成一个 pipeline；Cluster 下按 slot 拆成多个 pipeline
+    /// （slot 内保持原顺序），避免跨 slot 的 pipeline 被 MOVED 拒绝。
+    async fn query_keyed_pipeline(
+        conn: &mut RedisConn<'_>,
+        commands: Vec<(String, redis::Cmd)>,
+    ) -> redis::RedisResult<()> {
+        let split = conn.is_cluster();
+        for group in group_by_slot(commands, split) {
+            let mut pipe = redis::pipe();
+            for cmd in group {
+                pipe.add_command(cmd).ignore();
+            }
+            pipe.query_async::<()>(conn).await?;
+        }
+        Ok(())
+    }
+
     /// 执行带超时的 Redis 操作
     async fn with_timeout<F, T>(&self, op: F) -> Result<T, crate::error::ServerError>
     where
@@ -162,7 +168,7 @@ impl RedisClient {
                 .arg("NX")
                 .arg("EX")
                 .arg(seconds)
-                .query_async(&mut *conn)
+                .query_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis SET NX EX failed: {e}"))
@@ -191,7 +197,7 @@ impl RedisClient {
                 .key(key)
                 .arg(expected_value)
                 .arg(seconds)
-                .invoke_async(&mut *conn)
+                .invoke_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!(
@@ -203,6 +209,238 @@ impl RedisClient {
         .await
     }
 
+    /// 仅当当前值等于 expected 时改写为 new_value，保留原 TTL（Redis 6.0+ KEEPTTL）。
+    pub async fn compare_and_set_keepttl(
+        &self,
+        key: &str,
+        expected_value: &str,
+        new_value: &str,
+    ) -> Result<bool, crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                redis.call('SET', KEYS[1], ARGV[2], 'KEEPTTL')
+                return 1
+            end
+            return 0
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let swapped: i64 = redis::Script::new(SCRIPT)
+                .key(key)
+                .arg(expected_value)
+                .arg(new_value)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis compare-and-set failed: {e}"
+                    ))
+                })?;
+            Ok(swapped == 1)
+        })
+        .await
+    }
+
+    /// 间隔窗口：窗口空闲（不存在，或 `存入时间 + interval_ms` 已不晚于现在）时
+    /// 以 Redis 服务器时间占用并返回 `(true, 占用时间, 现在)`；仍在冷却返回
+    /// `(false, 原占用时间, 现在)`，时间均为毫秒。
+    ///
+    /// 判定与写入在一个脚本里完成，并发请求只有一个能占到窗口；
+    /// 按当前 `interval_ms` 判定，间隔调短后旧窗口不必等旧 TTL 过期。
+    pub async fn acquire_interval_window(
+        &self,
+        key: &str,
+        interval_ms: u64,
+    ) -> Result<(bool, i64, i64), crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            local interval = tonumber(ARGV[1])
+            local t = redis.call('TIME')
+            local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+            local started = tonumber(redis.call('GET', KEYS[1]))
+            if started ~= nil and started + interval > now then
+                return {0, started, now}
+            end
+            redis.call('SET', KEYS[1], tostring(now), 'PX', interval)
+            return {1, now, now}
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let (acquired, started, now): (i64, i64, i64) = redis::Script::new(SCRIPT)
+                .key(key)
+                .arg(interval_ms)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis interval window failed: {e}"
+                    ))
+                })?;
+            Ok((acquired == 1, started, now))
+        })
+        .await
+    }
+
+    /// 仅当当前值等于 expected 时删除 key，返回是否删除。
+    pub async fn compare_and_del(
+        &self,
+        key: &str,
+        expected_value: &str,
+    ) -> Result<bool, crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            if redis.call('GET', KEYS[1]) == ARGV[1] then
+                return redis.call('DEL', KEYS[1])
+            end
+            return 0
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let deleted: i64 = redis::Script::new(SCRIPT)
+                .key(key)
+                .arg(expected_value)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis compare-and-delete failed: {e}"
+                    ))
+                })?;
+            Ok(deleted == 1)
+        })
+        .await
+    }
+
+    /// 仅当 `guard_key` 仍存在时对 `counter_key` 执行 INCR，返回递增后的值；
+    /// `guard_key` 不存在返回 None。计数器首次创建时继承 `guard_key` 的剩余 TTL。
+    ///
+    /// 检查与递增在一个脚本里完成，并发调用各自拿到不同的序号，不会丢失计数。
+    /// 两个 key 在同一个脚本里访问，Cluster 模式下必须共享 hash tag（落在同一 slot）。
+    pub async fn incr_if_exists(
+        &self,
+        guard_key: &str,
+        counter_key: &str,
+    ) -> Result<Option<i64>, crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            local ttl = redis.call('PTTL', KEYS[1])
+            if ttl == -2 then
+                return -1
+            end
+            local n = redis.call('INCR', KEYS[2])
+            if n == 1 and ttl > 0 then
+                redis.call('PEXPIRE', KEYS[2], ttl)
+            end
+            return n
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let n: i64 = redis::Script::new(SCRIPT)
+                .key(guard_key)
+                .key(counter_key)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!("Redis guarded INCR failed: {e}"))
+                })?;
+            Ok((n >= 0).then_some(n))
+        })
+        .await
+    }
+
+    /// 令牌桶：按 Redis 服务器时间补充令牌后尝试消耗 `cost`，返回是否放行。
+    ///
+    /// 桶存为 HASH {tokens, ts}，整个读-改-写在一个脚本里完成，多节点并发扣减也不会超发；
+    /// 用服务器 TIME 而不是各节点本地时钟，避免节点间时钟漂移影响补充速率。
+    /// 桶在回满所需时间之后自动过期。
+    pub async fn token_bucket_consume(
+        &self,
+        key: &str,
+        cost: f64,
+        rate: f64,
+        capacity: f64,
+    ) -> Result<bool, crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            local cost = tonumber(ARGV[1])
+            local rate = tonumber(ARGV[2])
+            local capacity = tonumber(ARGV[3])
+            local t = redis.call('TIME')
+            local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+            local data = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+            local tokens = tonumber(data[1])
+            local ts = tonumber(data[2])
+            if tokens == nil or ts == nil then
+                tokens = capacity
+                ts = now
+            end
+            tokens = math.min(capacity, tokens + math.max(0, now - ts) / 1000 * rate)
+            local allowed = 0
+            if tokens >= cost then
+                tokens = tokens - cost
+                allowed = 1
+            end
+            redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', tostring(now))
+            local ttl_ms = 60000
+            if rate > 0 then
+                ttl_ms = math.ceil(capacity / rate * 1000) + 1000
+            end
+            redis.call('PEXPIRE', KEYS[1], ttl_ms)
+            return allowed
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let allowed: i64 = redis::Script::new(SCRIPT)
+                .key(key)
+                .arg(cost)
+                .arg(rate)
+                .arg(capacity)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!("Redis token bucket failed: {e}"))
+                })?;
+            Ok(allowed == 1)
+        })
+        .await
+    }
+
+    /// 受 fencing 保护的 ZADD：仅当 `fence_key` 的值仍等于 `expected` 时写入成员并刷新
+    /// 有序集合的 TTL，返回是否写入。
+    ///
+    /// 两个 key 在同一个脚本里访问，Cluster 模式下必须共享 hash tag（落在同一 slot）。
+    pub async fn zadd_if_fenced(
+        &self,
+        fence_key: &str,
+        expected: &str,
+        key: &str,
+        score: f64,
+        member: &str,
+        ttl_ms: i64,
+    ) -> Result<bool, crate::error::ServerError> {
+        const SCRIPT: &str = r#"
+            if redis.call('GET', KEYS[1]) ~= ARGV[1] then
+                return 0
+            end
+            redis.call('ZADD', KEYS[2], ARGV[2], ARGV[3])
+            redis.call('PEXPIRE', KEYS[2], ARGV[4])
+            return 1
+        "#;
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let written: i64 = redis::Script::new(SCRIPT)
+                .key(fence_key)
+                .key(key)
+                .arg(expected)
+                .arg(score)
+                .arg(member)
+                .arg(ttl_ms)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!("Redis fenced ZADD failed: {e}"))
+                })?;
+            Ok(written == 1)
+        })
+        .await
+    }
+
     /// GET key
     pub async fn get(&self, key: &str) -> Result<Option<String>, crate::error::ServerError> {
         self.with_timeout(async {
@@ -221,7 +459,7 @@ impl RedisClient {
             let mut conn = self.get_conn().await?;
             let result: Option<String> = redis::cmd("GETDEL")
                 .arg(key)
-                .query_async(&mut *conn)
+                .query_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis GETDEL failed: {}", e))
@@ -286,7 +524,7 @@ impl RedisClient {
                 .arg(key)
                 .arg(field)
                 .arg(delta)
-                .query_async(&mut *conn)
+                .query_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis HINCRBY failed: {}", e))
@@ -316,7 +554,7 @@ impl RedisClient {
             if expire_seconds > 0 {
                 pipe.cmd("EXPIRE").arg(key).arg(expire_seconds).ignore();
             }
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
+            pipe.query_async::<()>(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!("Redis HINCRBY pipeline failed: {}", e))
             })?;
             Ok(())
@@ -337,18 +575,30 @@ impl RedisClient {
 
         self.with_timeout(async {
             let mut conn = self.get_conn().await?;
-            let mut pipe = redis::pipe();
+            let mut commands = Vec::with_capacity(increments.len() * 2);
             for (key, field, delta) in increments {
-                pipe.cmd("HINCRBY").arg(key).arg(field).arg(*delta).ignore();
+                commands.push((
+                    key.clone(),
+                    redis::cmd("HINCRBY")
+                        .arg(key)
+                        .arg(field)
+                        .arg(*delta)
+                        .clone(),
+                ));
                 if expire_seconds > 0 {
-                    pipe.cmd("EXPIRE").arg(key).arg(expire_seconds).ignore();
+                    commands.push((
+                        key.clone(),
+                        redis::cmd("EXPIRE").arg(key).arg(expire_seconds).clone(),
+                    ));
                 }
             }
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
-                crate::error::ServerError::Internal(format!(
-                    "Redis multi-key HINCRBY pipeline failed: {e}"
-                ))
-            })?;
+            Self::query_keyed_pipeline(&mut conn, commands)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis multi-key HINCRBY pipeline failed: {e}"
+                    ))
+                })?;
             Ok(())
         })
         .await
@@ -365,7 +615,7 @@ impl RedisClient {
             let result: Option<i64> = redis::cmd("HGET")
                 .arg(key)
                 .arg(field)
-                .query_async(&mut *conn)
+                .query_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis HGET failed: {}", e))
@@ -384,7 +634,7 @@ impl RedisClient {
             let mut conn = self.get_conn().await?;
             let result: HashMap<String, i64> = redis::cmd("HGETALL")
                 .arg(key)
-                .query_async(&mut *conn)
+                .query_async(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis HGETALL failed: {}", e))
@@ -407,7 +657,7 @@ impl RedisClient {
                 .arg(key)
                 .arg(field)
                 .arg(value)
-                .query_async::<usize>(&mut *conn)
+                .query_async::<usize>(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!("Redis HSET failed: {}", e))
@@ -434,7 +684,7 @@ impl RedisClient {
             for field in fields {
                 cmd.arg(field);
             }
-            cmd.query_async::<usize>(&mut *conn).await.map_err(|e| {
+            cmd.query_async::<usize>(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!("Redis HDEL failed: {}", e))
             })?;
             Ok(())
@@ -445,6 +695,7 @@ impl RedisClient {
     /// KEYS pattern - 查找匹配的 key。
     ///
     /// 注意：不要在业务热路径使用；在线态判断应走 ConnectionManager 或 O(1) presence 索引。
+    /// Cluster 模式下 KEYS 只会路由到单个节点，结果不完整，仅适合诊断用途。
     pub async fn keys(&self, pattern: &str) -> Result<Vec<String>, crate::error::ServerError> {
         self.with_timeout(async {
             let mut conn = self.get_conn().await?;
@@ -494,7 +745,7 @@ impl RedisClient {
             if let Some(limit) = limit {
                 cmd.arg("LIMIT").arg(0).arg(limit);
             }
-            let result: Vec<String> = cmd.query_async(&mut *conn).await.map_err(|e| {
+            let result: Vec<String> = cmd.query_async(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!("Redis ZRANGEBYSCORE failed: {}", e))
             })?;
             Ok(result)
@@ -536,7 +787,7 @@ impl RedisClient {
                 .arg(key)
                 .arg(min)
                 .arg(max)
-                .query_async::<usize>(&mut *conn)
+                .query_async::<usize>(&mut conn)
                 .await
                 .map_err(|e| {
                     crate::error::ServerError::Internal(format!(
@@ -548,6 +799,68 @@ impl RedisClient {
         .await
     }
 
+    /// SET key value EX ttl，并把 member 以 score 写入多个有序集合索引。
+    ///
+    /// 索引内 score 小于 `prune_below` 的成员顺带清掉，索引 key 同样刷新 TTL，
+    /// 长期活跃的索引不会无限增长。
+    #[allow(clippy::too_many_arguments)]
+    pub async fn setex_with_zindexes(
+        &self,
+        key: &str,
+        value: &str,
+        ttl_seconds: usize,
+        index_keys: &[String],
+        member: &str,
+        score: f64,
+        prune_below: f64,
+    ) -> Result<(), crate::error::ServerError> {
+        self.with_timeout(async {
+            let mut conn = self.get_conn().await?;
+            let mut commands = Vec::with_capacity(1 + index_keys.len() * 3);
+            commands.push((
+                key.to_string(),
+                redis::cmd("SET")
+                    .arg(key)
+                    .arg(value)
+                    .arg("EX")
+                    .arg(ttl_seconds)
+                    .clone(),
+            ));
+            for index_key in index_keys {
+                commands.push((
+                    index_key.clone(),
+                    redis::cmd("ZADD")
+                        .arg(index_key)
+                        .arg(score)
+                        .arg(member)
+                        .clone(),
+                ));
+                commands.push((
+                    index_key.clone(),
+                    redis::cmd("ZREMRANGEBYSCORE")
+                        .arg(index_key)
+                        .arg("-inf")
+                        .arg(format!("({}", prune_below))
+                        .clone(),
+                ));
+                commands.push((
+                    index_key.clone(),
+                    redis::cmd("EXPIRE").arg(index_key).arg(ttl_seconds).clone(),
+                ));
+            }
+            Self::query_keyed_pipeline(&mut conn, commands)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis SETEX + ZADD index pipeline failed: {}",
+                        e
+                    ))
+                })?;
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn zrem(&self, key: &str, member: &str) -> Result<(), crate::error::ServerError> {
         self.with_timeout(async {
             let mut conn = self.get_conn().await?;
@@ -678,7 +991,7 @@ impl RedisClient {
                 pipe.expire(key, ttl_seconds as i64).ignore();
             }
 
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
+            pipe.query_async::<()>(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!(
                     "Redis recent buffer pipeline failed: {}",
                     e
@@ -713,7 +1026,7 @@ impl RedisClient {
                 pipe.expire(key, ttl_seconds as i64).ignore();
             }
 
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
+            pipe.query_async::<()>(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!(
                     "Redis list batch pipeline failed: {}",
                     e
@@ -739,21 +1052,23 @@ impl RedisClient {
         self.with_timeout(async {
             let mut conn = self.get_conn().await?;
             let stop = limit.saturating_sub(1) as isize;
-            let mut pipe = redis::pipe();
+            let mut commands = Vec::with_capacity(keys.len() * 3);
             for key in keys {
-                pipe.lpush(key, value).ignore();
-                pipe.ltrim(key, 0, stop).ignore();
+                commands.push((key.clone(), redis::Cmd::lpush(key, value)));
+                commands.push((key.clone(), redis::Cmd::ltrim(key, 0, stop)));
                 if ttl_seconds > 0 {
-                    pipe.expire(key, ttl_seconds as i64).ignore();
+                    commands.push((key.clone(), redis::Cmd::expire(key, ttl_seconds as i64)));
                 }
             }
 
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
-                crate::error::ServerError::Internal(format!(
-                    "Redis multi-key list pipeline failed: {}",
-                    e
-                ))
-            })?;
+            Self::query_keyed_pipeline(&mut conn, commands)
+                .await
+                .map_err(|e| {
+                    crate::error::ServerError::Internal(format!(
+                        "Redis multi-key list pipeline failed: {}",
+                        e
+                    ))
+                })?;
             Ok(())
         })
         .await
@@ -785,7 +1100,7 @@ impl RedisClient {
                 }
             }
 
-            pipe.query_async::<()>(&mut *conn).await.map_err(|e| {
+            pipe.query_async::<()>(&mut conn).await.map_err(|e| {
                 crate::error::ServerError::Internal(format!(
                     "Redis list replace pipeline failed: {}",
                     e
@@ -0,0 +1,344 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Redis 连接后端：单节点 / Sentinel / Cluster
+//!
+//! 所有 Redis 使用方（[`RedisClient`](super::redis::RedisClient)、`CacheManager` L2、
+//! `DeliveryTracker`）都经 [`RedisPool`] 取连接，部署模式只在这里分叉：
+//!
+//! - **standalone**：bb8 连接池直连 `url`；
+//! - **sentinel**：bb8 连接池，新建连接时向哨兵询问当前主节点；取出连接时校验
+//!   `ROLE` 仍是 master，故障转移后旧主上的连接被丢弃、重连到新主；
+//! - **cluster**：redis-rs 的多路复用集群连接，按 key 的 slot 路由、自动跟随 MOVED / ASK。
+//!
+//! Cluster 模式下一条命令 / 一个 Lua 脚本涉及的多个 key 必须落在同一 slot：
+//! 需要原子操作的 key 共享同一个 `{hash tag}`；多 key 的 pipeline 由
+//! `RedisClient` 按 [`key_slot`] 拆分。
+
+use crate::config::{RedisConfig, RedisMode};
+use bb8::{Pool, PooledConnection};
+use bb8_redis::RedisConnectionManager;
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{SentinelClient, SentinelNodeConnectionInfo, SentinelServerType};
+use redis::{Cmd, IntoConnectionInfo, Pipeline, RedisError, RedisFuture, Value};
+
+/// Cluster 的 slot 总数
+const CLUSTER_SLOTS: u16 = 16384;
+
+/// 哨兵托管主节点的连接管理器
+///
+/// 每次建连都经哨兵解析主节点地址；`is_valid` 用 `ROLE` 确认对端仍是 master。
+pub struct SentinelConnectionManager {
+    client: tokio::sync::Mutex<SentinelClient>,
+}
+
+impl SentinelConnectionManager {
+    pub fn new(config: &RedisConfig) -> Result<Self, RedisError> {
+        // 主节点的认证 / db 取自 `url`（哨兵本身的地址在 `nodes`）
+        let master_info = config.url.as_str().into_connection_info()?;
+        let client = SentinelClient::build(
+            config.nodes.clone(),
+            config.sentinel_master.clone(),
+            Some(SentinelNodeConnectionInfo {
+                tls_mode: None,
+                redis_connection_info: Some(master_info.redis),
+            }),
+            SentinelServerType::Master,
+        )?;
+        Ok(Self {
+            client: tokio::sync::Mutex::new(client),
+        })
+    }
+}
+
+impl bb8::ManageConnection for SentinelConnectionManager {
+    type Connection = MultiplexedConnection;
+    type Error = RedisError;
+
+    async fn connect(&self) -> Result<Self::Connection, Self::Error> {
+        self.client.lock().await.get_async_connection().await
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<(), Self::Error> {
+        let role: Value = redis::cmd("ROLE").query_async(conn).await?;
+        if is_master_role(&role) {
+            Ok(())
+        } else {
+            Err(RedisError::from((
+                redis::ErrorKind::ReadOnly,
+                "Sentinel 主节点已切换，连接指向的实例不再是 master",
+            )))
+        }
+    }
+
+    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
+        false
+    }
+}
+
+/// `ROLE` 回复的第一个元素是否为 `master`
+fn is_master_role(role: &Value) -> bool {
+    match role {
+        Value::Array(items) => {
+            matches!(
+                items.first(),
+                Some(Value::BulkString(bytes)) if bytes.as_slice() == b"master"
+            ) || matches!(items.first(), Some(Value::SimpleString(s)) if s == "master")
+        }
+        _ => false,
+    }
+}
+
+/// Redis 连接池（按部署模式）
+pub enum RedisPool {
+    Standalone(Pool<RedisConnectionManager>),
+    Sentinel(Pool<SentinelConnectionManager>),
+    /// 集群连接本身是多路复用的，clone 即共享，不需要池
+    Cluster(ClusterConnection),
+}
+
+/// 连接池状态（指标上报用）
+#[derive(Debug, Clone, Copy)]
+pub struct RedisPoolState {
+    pub connections: u32,
+    pub idle_connections: u32,
+}
+
+impl RedisPool {
+    /// 按配置建立连接池（不做连通性检查，调用方自行 PING）
+    pub async fn connect(config: &RedisConfig) -> Result<Self, String> {
+        match config.mode {
+            RedisMode::Standalone => {
+                let manager = RedisConnectionManager::new(config.url.clone())
+                    .map_err(|e| format!("Failed to create Redis manager: {}", e))?;
+                let pool = Self::pool_builder(config)
+                    .build(manager)
+                    .await
+                    .map_err(|e| format!("Failed to create Redis pool: {}", e))?;
+                Ok(Self::Standalone(pool))
+            }
+            RedisMode::Sentinel => {
+                if config.nodes.is_empty() {
+                    return Err("Sentinel 模式需要配置 nodes（哨兵地址）".to_string());
+                }
+                let manager = SentinelConnectionManager::new(config)
+                    .map_err(|e| format!("Failed to create Redis Sentinel manager: {}", e))?;
+                let pool = Self::pool_builder(config)
+                    .build(manager)
+                    .await
+                    .map_err(|e| format!("Failed to create Redis Sentinel pool: {}", e))?;
+                Ok(Self::Sentinel(pool))
+            }
+            RedisMode::Cluster => {
+                let nodes = if config.nodes.is_empty() {
+                    vec![config.url.clone()]
+                } else {
+                    config.nodes.clone()
+                };
+                let client = ClusterClient::builder(nodes)
+                    .connection_timeout(config.connection_timeout())
+                    .response_timeout(config.command_timeout())
+                    .build()
+                    .map_err(|e| format!("Failed to create Redis Cluster client: {}", e))?;
+                let conn = client
+                    .get_async_connection()
+                    .await
+                    .map_err(|e| format!("Failed to connect Redis Cluster: {}", e))?;
+                Ok(Self::Cluster(conn))
+            }
+        }
+    }
+
+    fn pool_builder<M: bb8::ManageConnection>(config: &RedisConfig) -> bb8::Builder<M> {
+        Pool::builder()
+            .max_size(config.pool_size)
+            .min_idle(Some(config.min_idle))
+            .connection_timeout(config.connection_timeout())
+            .idle_timeout(Some(config.idle_timeout()))
+    }
+
+    /// 取一条连接
+    pub async fn get(&self) -> Result<RedisConn<'_>, String> {
+        match self {
+            Self::Standalone(pool) => pool
+                .get()
+                .await
+                .map(RedisConn::Standalone)
+                .map_err(|e| e.to_string()),
+            Self::Sentinel(pool) => pool
+                .get()
+                .await
+                .map(RedisConn::Sentinel)
+                .map_err(|e| e.to_string()),
+            Self::Cluster(conn) => Ok(RedisConn::Cluster(conn.clone())),
+        }
+    }
+
+    /// 连接池状态；Cluster 模式是多路复用连接，固定报告 1 条活跃连接
+    pub fn state(&self) -> RedisPoolState {
+        let (connections, idle_connections) = match self {
+            Self::Standalone(pool) => {
+                let state = pool.state();
+                (state.connections, state.idle_connections)
+            }
+            Self::Sentinel(pool) => {
+                let state = pool.state();
+                (state.connections, state.idle_connections)
+            }
+            Self::Cluster(_) => (1, 0),
+        };
+        RedisPoolState {
+            connections,
+            idle_connections,
+        }
+    }
+
+    pub fn is_cluster(&self) -> bool {
+        matches!(self, Self::Cluster(_))
+    }
+}
+
+/// 从 [`RedisPool`] 取出的连接；实现 `ConnectionLike`，可直接用 `AsyncCommands` / pipeline / 脚本
+pub enum RedisConn<'a> {
+    Standalone(PooledConnection<'a, RedisConnectionManager>),
+    Sentinel(PooledConnection<'a, SentinelConnectionManager>),
+    Cluster(ClusterConnection),
+}
+
+impl RedisConn<'_> {
+    pub fn is_cluster(&self) -> bool {
+        matches!(self, Self::Cluster(_))
+    }
+}
+
+impl ConnectionLike for RedisConn<'_> {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Standalone(conn) => conn.req_packed_command(cmd),
+            Self::Sentinel(conn) => conn.req_packed_command(cmd),
+            Self::Cluster(conn) => conn.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Standalone(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Sentinel(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Standalone(conn) => conn.get_db(),
+            Self::Sentinel(conn) => conn.get_db(),
+            Self::Cluster(conn) => conn.get_db(),
+        }
+    }
+}
+
+/// key 所在的 Cluster slot（CRC16-XMODEM mod 16384，遵守 `{hash tag}` 规则）
+pub fn key_slot(key: &str) -> u16 {
+    let bytes = key.as_bytes();
+    let hashed = match bytes.iter().position(|&b| b == b'{') {
+        Some(open) => match bytes[open + 1..].iter().position(|&b| b == b'}') {
+            // `{}` 空 tag 不算，整 key 参与计算
+            Some(len) if len > 0 => &bytes[open + 1..open + 1 + len],
+            _ => bytes,
+        },
+        None => bytes,
+    };
+    crc16_xmodem(hashed) % CLUSTER_SLOTS
+}
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// 把 (key, 命令) 按 slot 分组（组内保持原顺序）；`split == false` 时整体一组
+pub(crate) fn group_by_slot<T>(items: Vec<(String, T)>, split: bool) -> Vec<Vec<T>> {
+    if !split {
+        return vec![items.into_iter().map(|(_, item)| item).collect()];
+    }
+    let mut order: Vec<u16> = Vec::new();
+    let mut groups: std::collections::HashMap<u16, Vec<T>> = std::collections::HashMap::new();
+    for (key, item) in items {
+        let slot = key_slot(&key);
+        groups
+            .entry(slot)
+            .or_insert_with(|| {
+                order.push(slot);
+                Vec::new()
+            })
+            .push(item);
+    }
+    order
+        .into_iter()
+        .filter_map(|slot| groups.remove(&slot))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_slot_matches_redis_reference_values() {
+        // 参考值来自 Redis 文档 / `CLUSTER KEYSLOT`
+        assert_eq!(key_slot("123456789"), 12739);
+        assert_eq!(key_slot("foo"), 12182);
+        assert_eq!(key_slot("{user1000}.following"), key_slot("user1000"));
+        assert_eq!(key_slot("{}foo"), key_slot("{}foo"));
+        assert_ne!(key_slot("{}foo"), key_slot("foo"));
+    }
+
+    #[test]
+    fn group_by_slot_keeps_order_within_each_slot() {
+        let items = vec![
+            ("{a}1".to_string(), 1),
+            ("{b}1".to_string(), 2),
+            ("{a}2".to_string(), 3),
+        ];
+        assert_eq!(group_by_slot(items.clone(), false), vec![vec![1, 2, 3]]);
+        assert_eq!(group_by_slot(items, true), vec![vec![1, 3], vec![2]]);
+    }
+
+    #[test]
+    fn master_role_detection() {
+        let master = Value::Array(vec![Value::BulkString(b"master".to_vec())]);
+        let replica = Value::Array(vec![Value::BulkString(b"slave".to_vec())]);
+        assert!(is_master_role(&master));
+        assert!(!is_master_role(&replica));
+    }
+}
@@ -0,0 +1,614 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Room 广播扇出：本节点投递 + 跨节点中继
+//!
+//! [`SubscribeManager`] 只记录本节点的订阅。集群模式（`PRIVCHAT_CLUSTER_MODE`）下：
+//! - 各节点把「room → 本节点在线订阅数」同步到 Redis：节点登记在 room 的 ZSET 里
+//!   （分数为租约到期时间），计数写在带 TTL 的独立 key 上；节点失联后租约过期，
+//!   计数和中继目标随之失效；
+//! - 广播时把 `PublishRequest` 推入其余持有订阅者节点的中继队列，再投递本节点订阅者；
+//!   对端的中继 worker 取出后投递给它本地的订阅者（fire-and-forget，不等待应答）；
+//! - 背压：每个 room 在每个节点上同时进行中的广播数有上限，订阅者消费慢造成积压时
+//!   新广播被丢弃；目标节点中继队列超长时跳过该节点，避免一个热 room 拖垮整个集群。
+
+use crate::error::{Result, ServerError};
+use crate::infra::redis::RedisClient;
+use crate::infra::{ConnectionManager, SubscribeManager};
+use dashmap::DashMap;
+use futures::stream::{self, StreamExt};
+use privchat_protocol::protocol::{MessageType, PublishRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+/// 单次广播内向本节点 session 发送的并发度
+const ROOM_DELIVERY_CONCURRENCY: usize = 128;
+/// 订阅变化同步到 Redis 的间隔
+const ROOM_SYNC_INTERVAL: Duration = Duration::from_millis(500);
+/// 全量刷新（续租）间隔
+const ROOM_LEASE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+/// 节点 room 计数的租约时长
+const ROOM_LEASE_TTL_SECS: usize = 30;
+/// 单个节点中继队列的长度上限
+const ROOM_RELAY_QUEUE_MAX_LEN: usize = 10_000;
+/// 中继消息的最大时效；直播类事件晚到没有意义，超时直接丢弃
+const ROOM_RELAY_MAX_AGE_MS: i64 = 10_000;
+/// 背压信号量表超过该大小时回收空闲项
+const ROOM_LIMITER_PRUNE_THRESHOLD: usize = 1024;
+
+/// 有订阅者的 room 索引（member = channel_id，score = 租约到期时间）
+const ROOMS_INDEX_KEY: &str = "privchat:cluster:rooms";
+/// 参与 room 计数的节点索引（member = node_id，score = 租约到期时间）
+const ROOM_NODES_INDEX_KEY: &str = "privchat:cluster:room-nodes";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoomRelayEnvelope {
+    origin_node_id: String,
+    channel_id: u64,
+    sent_at: i64,
+    request: PublishRequest,
+}
+
+/// 一次 room 广播的结果
+#[derive(Debug, Clone, Default)]
+pub struct RoomPublishReport {
+    /// 本节点在线订阅 session 数
+    pub local_sessions: usize,
+    /// 本节点投递成功数
+    pub delivered: usize,
+    /// 已中继到的其他节点数
+    pub relayed_nodes: usize,
+    /// 因中继队列积压或 Redis 错误被跳过的节点数
+    pub skipped_nodes: usize,
+}
+
+/// 本节点的 room 广播名额；持有期间计入该 room 的进行中广播数
+pub struct RoomPublishPermit {
+    channel_id: u64,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// 集群模式下的 Redis 连接与本节点标识
+struct RoomCluster {
+    redis: Arc<RedisClient>,
+    node_id: String,
+}
+
+pub struct RoomFanout {
+    subscribe_manager: Arc<SubscribeManager>,
+    connections: Arc<ConnectionManager>,
+    max_inflight_per_room: usize,
+    /// channel_id -> 进行中广播的信号量
+    inflight: DashMap<u64, Arc<Semaphore>>,
+    cluster: RwLock<Option<Arc<RoomCluster>>>,
+}
+
+impl RoomFanout {
+    pub fn new(
+        subscribe_manager: Arc<SubscribeManager>,
+        connections: Arc<ConnectionManager>,
+        max_inflight_per_room: usize,
+    ) -> Self {
+        Self {
+            subscribe_manager,
+            connections,
+            max_inflight_per_room: max_inflight_per_room.max(1),
+            inflight: DashMap::new(),
+            cluster: RwLock::new(None),
+        }
+    }
+
+    /// 开启跨节点中继：启动订阅计数同步与本节点中继队列 worker
+    pub async fn enable_cluster(self: &Arc<Self>, redis: Arc<RedisClient>, node_id: String) {
+        self.subscribe_manager.enable_change_tracking();
+        let cluster = Arc::new(RoomCluster { redis, node_id });
+        *self.cluster.write().await = Some(cluster.clone());
+        self.spawn_sync(cluster.clone());
+        self.spawn_relay_worker(cluster);
+        info!("✅ Room 跨节点扇出已启动");
+    }
+
+    /// 占用该 room 的一个广播名额；进行中的广播已达上限时返回 `RateLimit`
+    pub fn try_acquire(&self, channel_id: u64) -> Result<RoomPublishPermit> {
+        if self.inflight.len() > ROOM_LIMITER_PRUNE_THRESHOLD {
+            let max = self.max_inflight_per_room;
+            self.inflight.retain(|_, semaphore| {
+                Arc::strong_count(semaphore) > 1 || semaphore.available_permits() < max
+            });
+        }
+        let semaphore = self
+            .inflight
+            .entry(channel_id)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_inflight_per_room)))
+            .clone();
+        semaphore
+            .try_acquire_owned()
+            .map(|permit| RoomPublishPermit {
+                channel_id,
+                _permit: permit,
+            })
+            .map_err(|_| {
+                ServerError::RateLimit(format!("Room {} 广播积压，请稍后重试", channel_id))
+            })
+    }
+
+    /// 广播到 room 的全部在线订阅者（本节点直接投递，其他节点经中继队列）
+    pub async fn publish(
+        &self,
+        permit: RoomPublishPermit,
+        request: PublishRequest,
+    ) -> Result<RoomPublishReport> {
+        let channel_id = permit.channel_id;
+        let payload = privchat_protocol::encode_message(&request)
+            .map_err(|e| ServerError::Protocol(format!("编码消息失败: {}", e)))?;
+
+        let mut report = RoomPublishReport::default();
+        let cluster = self.cluster.read().await.clone();
+        if let Some(cluster) = cluster {
+            let (relayed, skipped) = self.relay(&cluster, channel_id, request).await;
+            report.relayed_nodes = relayed;
+            report.skipped_nodes = skipped;
+        }
+
+        let (local_sessions, delivered) = self.deliver_local(channel_id, payload).await?;
+        report.local_sessions = local_sessions;
+        report.delivered = delivered;
+        drop(permit);
+        Ok(report)
+    }
+
+    /// room 在线订阅数（集群模式下为全集群之和；Redis 不可用时退回本节点计数）
+    pub async fn online_count(&self, channel_id: u64) -> usize {
+        let local = self.subscribe_manager.get_channel_online_count(channel_id);
+        let cluster = self.cluster.read().await.clone();
+        let Some(cluster) = cluster else {
+            return local;
+        };
+        match cluster
+            .remote_sum(&room_nodes_key(channel_id), |node| {
+                room_count_key(channel_id, node)
+            })
+            .await
+        {
+            Ok(remote) => local + remote,
+            Err(e) => {
+                warn!(
+                    "⚠️ 读取 room {} 集群在线数失败，仅返回本节点: {}",
+                    channel_id, e
+                );
+                local
+            }
+        }
+    }
+
+    /// 有在线订阅者的 room 列表（按 channel_id 升序）
+    ///
+    /// 集群模式下包含其他节点上的 room；刚变空的 room 在租约到期前仍可能出现。
+    pub async fn channel_ids(&self) -> Vec<u64> {
+        let mut ids: BTreeSet<u64> = self
+            .subscribe_manager
+            .get_all_channels()
+            .into_iter()
+            .map(|(channel_id, _)| channel_id)
+            .collect();
+        let cluster = self.cluster.read().await.clone();
+        if let Some(cluster) = cluster {
+            match cluster.live_members(ROOMS_INDEX_KEY).await {
+                Ok(members) => ids.extend(members.iter().filter_map(|m| m.parse::<u64>().ok())),
+                Err(e) => warn!("⚠️ 读取集群 room 索引失败，仅返回本节点: {}", e),
+            }
+        }
+        ids.into_iter().collect()
+    }
+
+    /// 订阅了任意 room 的 session 总数（其他节点的部分按续租周期刷新）
+    pub async fn total_sessions(&self) -> usize {
+        let local = self.subscribe_manager.get_total_session_count();
+        let cluster = self.cluster.read().await.clone();
+        let Some(cluster) = cluster else {
+            return local;
+        };
+        match cluster
+            .remote_sum(ROOM_NODES_INDEX_KEY, node_sessions_key)
+            .await
+        {
+            Ok(remote) => local + remote,
+            Err(e) => {
+                warn!("⚠️ 读取集群 room session 总数失败，仅返回本节点: {}", e);
+                local
+            }
+        }
+    }
+
+    /// 推入其他节点的中继队列，返回 (已中继节点数, 跳过节点数)
+    async fn relay(
+        &self,
+        cluster: &RoomCluster,
+        channel_id: u64,
+        request: PublishRequest,
+    ) -> (usize, usize) {
+        let nodes = match cluster.live_members(&room_nodes_key(channel_id)).await {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                warn!(
+                    "⚠️ 读取 room {} 订阅节点失败，跳过跨节点中继: {}",
+                    channel_id, e
+                );
+                return (0, 0);
+            }
+        };
+        let envelope = RoomRelayEnvelope {
+            origin_node_id: cluster.node_id.clone(),
+            channel_id,
+            sent_at: chrono::Utc::now().timestamp_millis(),
+            request,
+        };
+        let encoded = match serde_json::to_string(&envelope) {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                warn!("⚠️ room {} 中继消息编码失败: {}", channel_id, e);
+                return (0, 0);
+            }
+        };
+
+        let (mut relayed, mut skipped) = (0, 0);
+        for node in nodes.iter().filter(|node| **node != cluster.node_id) {
+            let queue = relay_queue_key(node);
+            match cluster.redis.llen(&queue).await {
+                Ok(len) if len >= ROOM_RELAY_QUEUE_MAX_LEN => {
+                    warn!(
+                        "📡 Room 中继队列积压 node={}, len={}，跳过 room {}",
+                        node, len, channel_id
+                    );
+                    skipped += 1;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("📡 Room 中继队列长度读取失败 node={}: {}", node, e);
+                    skipped += 1;
+                    continue;
+                }
+            }
+            match cluster.redis.rpush(&queue, &encoded).await {
+                Ok(()) => relayed += 1,
+                Err(e) => {
+                    warn!(
+                        "📡 Room 中继失败 node={}, channel_id={}: {}",
+                        node, channel_id, e
+                    );
+                    skipped += 1;
+                }
+            }
+        }
+        (relayed, skipped)
+    }
+
+    /// 投递给本节点订阅者，返回 (在线 session 数, 投递成功数)
+    async fn deliver_local(&self, channel_id: u64, payload: Vec<u8>) -> Result<(usize, usize)> {
+        let sessions = self.subscribe_manager.get_channel_sessions(channel_id);
+        if sessions.is_empty() {
+            return Ok((0, 0));
+        }
+        let online = sessions.len();
+
+        let server = self
+            .connections
+            .transport_server
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| ServerError::Internal("TransportServer 未就绪".to_string()))?;
+
+        // 有界并发广播：避免在线人数过多时为每个 session spawn 一个 task。
+        let payload = Arc::new(payload);
+        let delivered = stream::iter(sessions)
+            .map(|sid| {
+                let server = server.clone();
+                let bytes = payload.clone();
+                async move {
+                    let options =
+                        msgtrans::SendOptions::new().biz_type(MessageType::PublishRequest as u8);
+                    match server
+                        .send_with_options(sid, (*bytes).clone().into(), options)
+                        .await
+                    {
+                        Ok(_) => {
+                            debug!("📡 Room publish -> session {} 成功", sid);
+                            true
+                        }
+                        Err(e) => {
+                            warn!("📡 Room publish -> session {} 失败: {}", sid, e);
+                            false
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(ROOM_DELIVERY_CONCURRENCY)
+            .fold(0usize, |acc, ok| async move { acc + usize::from(ok) })
+            .await;
+        Ok((online, delivered))
+    }
+
+    fn spawn_sync(self: &Arc<Self>, cluster: Arc<RoomCluster>) {
+        let fanout = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(ROOM_SYNC_INTERVAL);
+            let mut next_refresh = Instant::now();
+            loop {
+                tick.tick().await;
+                for channel_id in fanout.subscribe_manager.take_changed_channels() {
+                    let count = fanout
+                        .subscribe_manager
+                        .get_channel_online_count(channel_id);
+                    if let Err(e) = cluster.publish_room_count(channel_id, count).await {
+                        warn!("⚠️ 同步 room {} 在线数失败: {}", channel_id, e);
+                    }
+                }
+                if Instant::now() >= next_refresh {
+                    if let Err(e) = cluster.refresh_all(&fanout.subscribe_manager).await {
+                        warn!("⚠️ Room 计数续租失败: {}", e);
+                    }
+                    next_refresh = Instant::now() + ROOM_LEASE_REFRESH_INTERVAL;
+                }
+            }
+        });
+    }
+
+    fn spawn_relay_worker(self: &Arc<Self>, cluster: Arc<RoomCluster>) {
+        let fanout = Arc::clone(self);
+        tokio::spawn(async move {
+            let queue = relay_queue_key(&cluster.node_id);
+            loop {
+                let raw = match cluster.redis.lpop(&queue).await {
+                    Ok(Some(raw)) => raw,
+                    Ok(None) => {
+                        tokio::time::sleep(Duration::from_millis(25)).await;
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("📡 Room 中继队列读取失败: {}", e);
+                        tokio::time::sleep(Duration::from_millis(250)).await;
+                        continue;
+                    }
+                };
+                let envelope = match serde_json::from_str::<RoomRelayEnvelope>(&raw) {
+                    Ok(envelope) => envelope,
+                    Err(e) => {
+                        warn!("📡 Room 中继消息格式错误: {}", e);
+                        continue;
+                    }
+                };
+                let age_ms = chrono::Utc::now().timestamp_millis() - envelope.sent_at;
+                if age_ms > ROOM_RELAY_MAX_AGE_MS {
+                    debug!(
+                        "📡 丢弃过期的 room 中继消息 channel_id={}, origin={}, age={}ms",
+                        envelope.channel_id, envelope.origin_node_id, age_ms
+                    );
+                    continue;
+                }
+                // 背压：本节点该 room 的投递仍在积压时丢弃，不阻塞其他 room 的中继
+                let permit = match fanout.try_acquire(envelope.channel_id) {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        warn!(
+                            "📡 Room {} 本节点投递积压，丢弃来自 {} 的中继广播",
+                            envelope.channel_id, envelope.origin_node_id
+                        );
+                        continue;
+                    }
+                };
+                let fanout = fanout.clone();
+                tokio::spawn(async move {
+                    let channel_id = envelope.channel_id;
+                    let result = match privchat_protocol::encode_message(&envelope.request) {
+                        Ok(payload) => fanout.deliver_local(channel_id, payload).await,
+                        Err(e) => Err(ServerError::Protocol(format!("编码消息失败: {}", e))),
+                    };
+                    drop(permit);
+                    match result {
+                        Ok((online, delivered)) => debug!(
+                            "📡 Room 中继投递 channel_id={}, 在线={}, 投递={}",
+                            channel_id, online, delivered
+                        ),
+                        Err(e) => warn!("📡 Room 中继投递失败 channel_id={}: {}", channel_id, e),
+                    }
+                });
+            }
+        });
+    }
+}
+
+impl RoomCluster {
+    /// 租约仍有效的成员（顺带清理过期成员）
+    async fn live_members(&self, key: &str) -> Result<Vec<String>> {
+        let now = chrono::Utc::now().timestamp_millis() as f64;
+        self.redis
+            .zremrangebyscore(key, f64::NEG_INFINITY, now)
+            .await?;
+        self.redis
+            .zrangebyscore(key, now, f64::INFINITY, None)
+            .await
+    }
+
+    /// 其他存活节点在各自计数 key 上的值之和
+    async fn remote_sum(
+        &self,
+        nodes_key: &str,
+        count_key: impl Fn(&str) -> String,
+    ) -> Result<usize> {
+        let mut total = 0;
+        for node in self.live_members(nodes_key).await? {
+            if node == self.node_id {
+                continue;
+            }
+            if let Some(raw) = self.redis.get(&count_key(&node)).await? {
+                total += raw.parse::<usize>().unwrap_or(0);
+            }
+        }
+        Ok(total)
+    }
+
+    /// 写入本节点在某 room 的在线数；为 0 时撤销登记
+    async fn publish_room_count(&self, channel_id: u64, count: usize) -> Result<()> {
+        let nodes_key = room_nodes_key(channel_id);
+        let count_key = room_count_key(channel_id, &self.node_id);
+        if count == 0 {
+            self.redis.zrem(&nodes_key, &self.node_id).await?;
+            self.redis.del(&count_key).await?;
+            return Ok(());
+        }
+        let expires_at = lease_expires_at();
+        self.redis
+            .setex(&count_key, ROOM_LEASE_TTL_SECS, &count.to_string())
+            .await?;
+        self.redis
+            .zadd(&nodes_key, expires_at, &self.node_id)
+            .await?;
+        // 所有节点都失联后整个 ZSET 也随之过期
+        self.redis
+            .expire(&nodes_key, ROOM_LEASE_TTL_SECS * 2)
+            .await?;
+        self.redis
+            .zadd(ROOMS_INDEX_KEY, expires_at, &channel_id.to_string())
+            .await?;
+        Ok(())
+    }
+
+    /// 续租本节点全部 room 的计数与 session 总数
+    async fn refresh_all(&self, subscribe_manager: &SubscribeManager) -> Result<()> {
+        for (channel_id, count) in subscribe_manager.get_all_channels() {
+            self.publish_room_count(channel_id, count).await?;
+        }
+        self.redis
+            .setex(
+                &node_sessions_key(&self.node_id),
+                ROOM_LEASE_TTL_SECS,
+                &subscribe_manager.get_total_session_count().to_string(),
+            )
+            .await?;
+        self.redis
+            .zadd(ROOM_NODES_INDEX_KEY, lease_expires_at(), &self.node_id)
+            .await?;
+        Ok(())
+    }
+}
+
+fn lease_expires_at() -> f64 {
+    (chrono::Utc::now().timestamp_millis() + ROOM_LEASE_TTL_SECS as i64 * 1000) as f64
+}
+
+fn room_nodes_key(channel_id: u64) -> String {
+    format!("privchat:cluster:room:{channel_id}:nodes")
+}
+
+fn room_count_key(channel_id: u64, node_id: &str) -> String {
+    format!("privchat:cluster:room:{channel_id}:online:{node_id}")
+}
+
+fn node_sessions_key(node_id: &str) -> String {
+    format!("privchat:cluster:room-sessions:{node_id}")
+}
+
+fn relay_queue_key(node_id: &str) -> String {
+    format!("privchat:cluster:room-relay:{node_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fanout(max_inflight: usize) -> RoomFanout {
+        RoomFanout::new(
+            Arc::new(SubscribeManager::new()),
+            Arc::new(ConnectionManager::new()),
+            max_inflight,
+        )
+    }
+
+    #[test]
+    fn room_keys_are_scoped_by_room_and_node() {
+        assert_ne!(room_count_key(1, "node-a"), room_count_key(1, "node-b"));
+        assert_ne!(room_count_key(1, "node-a"), room_count_key(2, "node-a"));
+        assert_ne!(relay_queue_key("node-a"), relay_queue_key("node-b"));
+        assert!(room_nodes_key(42).contains(":42:"));
+    }
+
+    #[test]
+    fn inflight_broadcasts_are_bounded_per_room() {
+        let fanout = fanout(2);
+        let first = fanout.try_acquire(100).unwrap();
+        let _second = fanout.try_acquire(100).unwrap();
+        assert!(matches!(
+            fanout.try_acquire(100),
+            Err(ServerError::RateLimit(_))
+        ));
+        // 其他 room 不受影响
+        assert!(fanout.try_acquire(200).is_ok());
+
+        drop(first);
+        assert!(fanout.try_acquire(100).is_ok());
+    }
+
+    #[tokio::test]
+    async fn single_node_counts_fall_back_to_local_subscriptions() {
+        let fanout = fanout(1);
+        fanout
+            .subscribe_manager
+            .subscribe(msgtrans::SessionId::from(1_u64), 300)
+            .unwrap();
+        fanout
+            .subscribe_manager
+            .subscribe(msgtrans::SessionId::from(2_u64), 300)
+            .unwrap();
+        fanout
+            .subscribe_manager
+            .subscribe(msgtrans::SessionId::from(2_u64), 100)
+            .unwrap();
+
+        assert_eq!(fanout.online_count(300).await, 2);
+        assert_eq!(fanout.channel_ids().await, vec![100, 300]);
+        assert_eq!(fanout.total_sessions().await, 2);
+    }
+
+    #[test]
+    fn relay_envelope_roundtrip_preserves_large_ids() {
+        let envelope = RoomRelayEnvelope {
+            origin_node_id: "node-a".to_string(),
+            channel_id: u64::MAX - 1,
+            sent_at: 1,
+            request: PublishRequest {
+                channel_id: u64::MAX - 1,
+                topic: None,
+                timestamp: 1,
+                payload: b"hello".to_vec(),
+                publisher: Some("10001".to_string()),
+                server_message_id: Some(u64::MAX - 2),
+            },
+        };
+        let encoded = serde_json::to_string(&envelope).unwrap();
+        let decoded: RoomRelayEnvelope = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded.channel_id, envelope.channel_id);
+        assert_eq!(decoded.request.server_message_id, Some(u64::MAX - 2));
+        assert_eq!(decoded.request.payload, b"hello".to_vec());
+    }
+}
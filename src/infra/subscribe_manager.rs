@@ -21,10 +21,12 @@
 //! - 订阅绑定到 session_id，不是 user_id
 //! - 每个 session 可同时订阅多个频道
 //! - 推送目标是 session_id，不是 user_id
+//! - 只记录本节点的订阅；集群内的 room 计数与广播中继见 [`RoomFanout`](super::RoomFanout)
 
 use dashmap::DashMap;
 use msgtrans::SessionId;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use tracing::{debug, info};
 
@@ -37,6 +39,10 @@ pub struct SubscribeManager {
     write_lock: Mutex<()>,
     max_subscriptions_per_session: usize,
     max_channel_subscribers_online: usize,
+    /// 订阅人数发生变化的频道（集群模式下由 `RoomFanout` 取走并同步到 Redis）
+    changed_channels: Mutex<HashSet<u64>>,
+    /// 未开启时不记录变化，单节点部署不积累集合
+    track_changes: AtomicBool,
 }
 
 impl SubscribeManager {
@@ -57,9 +63,35 @@ impl SubscribeManager {
             write_lock: Mutex::new(()),
             max_subscriptions_per_session: max_subscriptions_per_session.max(1),
             max_channel_subscribers_online: max_channel_subscribers_online.max(1),
+            changed_channels: Mutex::new(HashSet::new()),
+            track_changes: AtomicBool::new(false),
         }
     }
 
+    /// 开启订阅变化记录（集群 room 计数同步用）
+    pub fn enable_change_tracking(&self) {
+        self.track_changes.store(true, Ordering::Relaxed);
+    }
+
+    /// 取走自上次调用以来订阅人数变化过的频道
+    pub fn take_changed_channels(&self) -> Vec<u64> {
+        let mut changed = self
+            .changed_channels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        changed.drain().collect()
+    }
+
+    fn mark_changed(&self, channel_id: u64) {
+        if !self.track_changes.load(Ordering::Relaxed) {
+            return;
+        }
+        self.changed_channels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(channel_id);
+    }
+
     /// 订阅频道
     /// 返回 Ok(()) 或 Err(reason) 如果频道已满或 session 订阅数超限
     pub fn subscribe(&self, session_id: SessionId, channel_id: u64) -> Result<(), &'static str> {
@@ -120,6 +152,7 @@ impl SubscribeManager {
             .entry(session_id.clone())
             .or_insert_with(HashSet::new)
             .insert(channel_id);
+        self.mark_changed(channel_id);
 
         info!(
             "Session {} subscribed to channel {}",
@@ -149,6 +182,7 @@ impl SubscribeManager {
         // 从 channel_routes 中移除
         if removed {
             self.unsubscribe_from_channel(session_id, channel_id);
+            self.mark_changed(channel_id);
             info!(
                 "Session {} unsubscribed from channel {}",
                 session_id, channel_id
@@ -184,6 +218,7 @@ impl SubscribeManager {
 
         for channel_id in &channels {
             self.unsubscribe_from_channel(session_id, *channel_id);
+            self.mark_changed(*channel_id);
         }
 
         let result: Vec<u64> = channels.into_iter().collect();
@@ -388,6 +423,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_changed_channels_only_tracked_when_enabled() {
+        let manager = SubscribeManager::new();
+        manager.subscribe(sid(1), 100).unwrap();
+        assert!(manager.take_changed_channels().is_empty());
+
+        manager.enable_change_tracking();
+        manager.subscribe(sid(1), 200).unwrap();
+        manager.subscribe(sid(2), 200).unwrap();
+        manager.unsubscribe(&sid(1), 100);
+        let mut changed = manager.take_changed_channels();
+        changed.sort_unstable();
+        assert_eq!(changed, vec![100, 200]);
+        assert!(manager.take_changed_channels().is_empty());
+
+        manager.on_session_disconnect(&sid(2));
+        assert_eq!(manager.take_changed_channels(), vec![200]);
+    }
+
     #[test]
     fn test_channel_session_snapshot_is_deduplicated() {
         let manager = SubscribeManager::new();
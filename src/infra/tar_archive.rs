@@ -0,0 +1,176 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 最小 ustar 归档写入
+//!
+//! 个人数据导出要把若干 JSON 与用户上传的文件打成一个可下载的 `.tar`。只需要写普通文件，
+//! 不值得为此引入完整的 tar 依赖：每个条目 = 512 字节头 + 内容 + 补齐到 512 的零字节，
+//! 归档以两个全零块结束。头部按 POSIX ustar 生成，GNU tar / bsdtar / 7-Zip 均可解开。
+
+/// tar 块大小
+pub const BLOCK_SIZE: usize = 512;
+/// 归档结束标记：两个全零块
+pub const END_OF_ARCHIVE: [u8; BLOCK_SIZE * 2] = [0; BLOCK_SIZE * 2];
+/// 单个条目的大小上限（size 字段 11 位八进制）
+pub const MAX_ENTRY_SIZE: u64 = 0o77777777777;
+
+const NAME_LEN: usize = 100;
+const PREFIX_LEN: usize = 155;
+
+/// 生成一个普通文件条目的头部块。
+///
+/// `path` 用 `/` 分隔；超过 100 字节时按 ustar 规则拆成 prefix + name，
+/// 仍放不下或包含 `..` / 绝对路径时返回错误。
+pub fn entry_header(path: &str, size: u64, mtime_secs: u64) -> Result<[u8; BLOCK_SIZE], String> {
+    if path.is_empty() || path.starts_with('/') || path.split('/').any(|part| part == "..") {
+        return Err(format!("非法的归档路径: {}", path));
+    }
+    if size > MAX_ENTRY_SIZE {
+        return Err(format!("归档条目过大: {} bytes", size));
+    }
+    let (prefix, name) = split_path(path)?;
+
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime_secs.min(MAX_ENTRY_SIZE));
+    // 计算校验和时 chksum 字段按 8 个空格计
+    header[148..156].fill(b' ');
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+    write_octal(&mut header[148..155], checksum as u64);
+    header[155] = b' ';
+    Ok(header)
+}
+
+/// 内容之后需要补齐的零字节数
+pub fn padding_len(size: u64) -> usize {
+    let rem = (size % BLOCK_SIZE as u64) as usize;
+    if rem == 0 {
+        0
+    } else {
+        BLOCK_SIZE - rem
+    }
+}
+
+/// 把用户提供的文件名收敛成单个安全的路径段（去掉分隔符 / 控制字符，限制长度）。
+pub fn sanitize_entry_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c| c == '.' || c == ' ');
+    let mut out = String::new();
+    for c in cleaned.chars() {
+        if out.len() + c.len_utf8() > 80 {
+            break;
+        }
+        out.push(c);
+    }
+    if out.is_empty() {
+        "file".to_string()
+    } else {
+        out
+    }
+}
+
+fn split_path(path: &str) -> Result<(&str, &str), String> {
+    if path.len() <= NAME_LEN {
+        return Ok(("", path));
+    }
+    // 从左往右找第一个能让 name 放进 100 字节、prefix 放进 155 字节的分隔点
+    for (idx, _) in path.match_indices('/') {
+        let (prefix, name) = (&path[..idx], &path[idx + 1..]);
+        if prefix.len() <= PREFIX_LEN && !name.is_empty() && name.len() <= NAME_LEN {
+            return Ok((prefix, name));
+        }
+    }
+    Err(format!("归档路径过长: {}", path))
+}
+
+/// 写入以 NUL 结尾、左侧补 0 的八进制数字
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_octal(field: &[u8]) -> u64 {
+        let text = std::str::from_utf8(field)
+            .unwrap()
+            .trim_end_matches(['\0', ' ']);
+        u64::from_str_radix(text, 8).unwrap()
+    }
+
+    #[test]
+    fn header_fields_and_checksum() {
+        let header = entry_header("profile.json", 1234, 1_700_000_000).unwrap();
+        assert_eq!(&header[..12], b"profile.json");
+        assert_eq!(parse_octal(&header[124..136]), 1234);
+        assert_eq!(parse_octal(&header[136..148]), 1_700_000_000);
+        assert_eq!(header[156], b'0');
+        assert_eq!(&header[257..263], b"ustar\0");
+
+        let mut zeroed = header;
+        zeroed[148..156].fill(b' ');
+        let expected: u64 = zeroed.iter().map(|b| *b as u64).sum();
+        assert_eq!(parse_octal(&header[148..156]), expected);
+    }
+
+    #[test]
+    fn long_paths_use_prefix() {
+        let dir = "files/".to_string() + &"d".repeat(60);
+        let path = format!("{}/{}", dir, "n".repeat(90));
+        let header = entry_header(&path, 0, 0).unwrap();
+        assert_eq!(&header[..90], "n".repeat(90).as_bytes());
+        assert_eq!(&header[345..345 + dir.len()], dir.as_bytes());
+
+        assert!(entry_header(&"x".repeat(300), 0, 0).is_err());
+        assert!(entry_header("../etc/passwd", 0, 0).is_err());
+        assert!(entry_header("/abs", 0, 0).is_err());
+    }
+
+    #[test]
+    fn padding_and_names() {
+        assert_eq!(padding_len(0), 0);
+        assert_eq!(padding_len(1), 511);
+        assert_eq!(padding_len(512), 0);
+        assert_eq!(padding_len(513), 511);
+
+        assert_eq!(sanitize_entry_name("../a/b\\c.txt"), "_a_b_c.txt");
+        assert_eq!(sanitize_entry_name(".."), "file");
+        assert!(sanitize_entry_name(&"长".repeat(100)).len() <= 80);
+    }
+}
@@ -199,6 +199,7 @@ mod tests {
             session_version: 1,
             token_type: "access".to_string(),
             scope: vec!["im".to_string()],
+            amr: Vec::new(),
         }
     }
 
@@ -0,0 +1,244 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bot 平台模型：斜杠命令、内联按钮键盘
+//!
+//! 内联键盘随消息 `metadata.reply_markup` 下发，形状与 Telegram Bot API 的
+//! `InlineKeyboardMarkup` 一致，便于现成的 Bot 代码迁移：
+//!
+//! ```json
+//! { "inline_keyboard": [[{ "text": "确认", "callback_data": "confirm:42" }]] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 消息 metadata 中内联键盘的键
+pub const REPLY_MARKUP_KEY: &str = "reply_markup";
+
+/// 单个 Bot 最多注册的命令数
+pub const MAX_BOT_COMMANDS: usize = 100;
+const MAX_COMMAND_LEN: usize = 32;
+const MAX_COMMAND_DESCRIPTION_LEN: usize = 256;
+
+/// 一个键盘最多的按钮数 / 行数 / 每行按钮数
+const MAX_KEYBOARD_BUTTONS: usize = 100;
+const MAX_KEYBOARD_ROWS: usize = 20;
+const MAX_ROW_BUTTONS: usize = 8;
+const MAX_BUTTON_TEXT_LEN: usize = 64;
+/// callback_data 上限（字节），与 Telegram 一致
+pub const MAX_CALLBACK_DATA_BYTES: usize = 64;
+
+/// Bot 斜杠命令（`command` 不带前导 `/`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BotCommand {
+    pub command: String,
+    pub description: String,
+}
+
+impl BotCommand {
+    /// 校验并归一化：去掉前导 `/`、转小写；命令只允许 `a-z0-9_`
+    pub fn normalized(&self) -> Result<Self, String> {
+        let command = self
+            .command
+            .trim()
+            .trim_start_matches('/')
+            .to_ascii_lowercase();
+        if command.is_empty() || command.len() > MAX_COMMAND_LEN {
+            return Err(format!("命令长度必须在 1~{} 之间", MAX_COMMAND_LEN));
+        }
+        if !command
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!("命令 {} 只能包含小写字母、数字和下划线", command));
+        }
+        let description = self.description.trim().to_string();
+        if description.is_empty() || description.chars().count() > MAX_COMMAND_DESCRIPTION_LEN {
+            return Err(format!(
+                "命令 {} 的描述长度必须在 1~{} 之间",
+                command, MAX_COMMAND_DESCRIPTION_LEN
+            ));
+        }
+        Ok(Self {
+            command,
+            description,
+        })
+    }
+}
+
+/// 校验整组命令（整体替换语义）：数量上限 + 去重
+pub fn normalize_bot_commands(commands: &[BotCommand]) -> Result<Vec<BotCommand>, String> {
+    if commands.len() > MAX_BOT_COMMANDS {
+        return Err(format!("命令数不能超过 {}", MAX_BOT_COMMANDS));
+    }
+    let mut out: Vec<BotCommand> = Vec::with_capacity(commands.len());
+    for command in commands {
+        let command = command.normalized()?;
+        if out.iter().any(|c| c.command == command.command) {
+            return Err(format!("命令 {} 重复", command.command));
+        }
+        out.push(command);
+    }
+    Ok(out)
+}
+
+/// 内联按钮：`callback_data` 与 `url` 二选一
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineKeyboardButton {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// 内联键盘（按行排列）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineKeyboardMarkup {
+    pub inline_keyboard: Vec<Vec<InlineKeyboardButton>>,
+}
+
+impl InlineKeyboardMarkup {
+    /// 从消息 metadata 取键盘；没有或格式不对时返回 None
+    pub fn from_metadata(metadata: &Value) -> Option<Self> {
+        metadata
+            .get(REPLY_MARKUP_KEY)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.inline_keyboard.is_empty() || self.inline_keyboard.len() > MAX_KEYBOARD_ROWS {
+            return Err(format!("键盘行数必须在 1~{} 之间", MAX_KEYBOARD_ROWS));
+        }
+        let mut total = 0;
+        for row in &self.inline_keyboard {
+            if row.is_empty() || row.len() > MAX_ROW_BUTTONS {
+                return Err(format!("每行按钮数必须在 1~{} 之间", MAX_ROW_BUTTONS));
+            }
+            total += row.len();
+            for button in row {
+                let text_len = button.text.trim().chars().count();
+                if text_len == 0 || text_len > MAX_BUTTON_TEXT_LEN {
+                    return Err(format!("按钮文字长度必须在 1~{} 之间", MAX_BUTTON_TEXT_LEN));
+                }
+                match (&button.callback_data, &button.url) {
+                    (Some(data), None) => {
+                        if data.is_empty() || data.len() > MAX_CALLBACK_DATA_BYTES {
+                            return Err(format!(
+                                "callback_data 长度必须在 1~{} 字节之间",
+                                MAX_CALLBACK_DATA_BYTES
+                            ));
+                        }
+                    }
+                    (None, Some(url)) => {
+                        if !(url.starts_with("https://") || url.starts_with("http://")) {
+                            return Err("按钮 url 只支持 http / https".to_string());
+                        }
+                    }
+                    _ => {
+                        return Err("按钮必须且只能设置 callback_data 或 url 之一".to_string());
+                    }
+                }
+            }
+        }
+        if total > MAX_KEYBOARD_BUTTONS {
+            return Err(format!("按钮总数不能超过 {}", MAX_KEYBOARD_BUTTONS));
+        }
+        Ok(())
+    }
+
+    /// 键盘上是否有 `callback_data` 等于 `data` 的按钮（回调必须对应真实按钮）
+    pub fn has_callback(&self, data: &str) -> bool {
+        self.inline_keyboard
+            .iter()
+            .flatten()
+            .any(|b| b.callback_data.as_deref() == Some(data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn keyboard(value: Value) -> InlineKeyboardMarkup {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn commands_are_normalized_and_deduplicated() {
+        let commands = normalize_bot_commands(&[BotCommand {
+            command: "/Help".to_string(),
+            description: " 查看帮助 ".to_string(),
+        }])
+        .unwrap();
+        assert_eq!(commands[0].command, "help");
+        assert_eq!(commands[0].description, "查看帮助");
+
+        let dup = [
+            BotCommand {
+                command: "help".to_string(),
+                description: "a".to_string(),
+            },
+            BotCommand {
+                command: "/help".to_string(),
+                description: "b".to_string(),
+            },
+        ];
+        assert!(normalize_bot_commands(&dup).is_err());
+        assert!(BotCommand {
+            command: "say-hi".to_string(),
+            description: "x".to_string(),
+        }
+        .normalized()
+        .is_err());
+    }
+
+    #[test]
+    fn keyboard_buttons_need_exactly_one_action() {
+        let ok = keyboard(json!({"inline_keyboard": [[
+            {"text": "确认", "callback_data": "confirm:1"},
+            {"text": "文档", "url": "https://privchat.dev"}
+        ]]}));
+        assert!(ok.validate().is_ok());
+        assert!(ok.has_callback("confirm:1"));
+        assert!(!ok.has_callback("confirm:2"));
+
+        let both = keyboard(json!({"inline_keyboard": [[
+            {"text": "x", "callback_data": "a", "url": "https://privchat.dev"}
+        ]]}));
+        assert!(both.validate().is_err());
+
+        let too_long = keyboard(json!({"inline_keyboard": [[
+            {"text": "x", "callback_data": "a".repeat(MAX_CALLBACK_DATA_BYTES + 1)}
+        ]]}));
+        assert!(too_long.validate().is_err());
+
+        assert!(keyboard(json!({"inline_keyboard": []})).validate().is_err());
+    }
+
+    #[test]
+    fn keyboard_is_read_from_message_metadata() {
+        let metadata = json!({
+            "reply_markup": {"inline_keyboard": [[{"text": "是", "callback_data": "yes"}]]}
+        });
+        let markup = InlineKeyboardMarkup::from_metadata(&metadata).unwrap();
+        assert!(markup.has_callback("yes"));
+        assert!(InlineKeyboardMarkup::from_metadata(&json!({})).is_none());
+    }
+}
@@ -154,6 +154,56 @@ impl ChannelStatus {
     }
 }
 
+/// 用户对单个会话的通知级别（privchat_user_channels.notify_level）
+///
+/// 🔴 `is_muted` 是旧字段，与 `Nothing` 保持同步：静音即「不通知」。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelNotifyLevel {
+    /// 所有消息都通知
+    #[default]
+    All = 0,
+    /// 仅 @我 / @全体成员 时通知
+    Mentions = 1,
+    /// 不通知（免打扰）
+    #[serde(rename = "none")]
+    Nothing = 2,
+}
+
+impl ChannelNotifyLevel {
+    /// 从 i16 转换（未知值按 All 处理）
+    pub fn from_i16(value: i16) -> Self {
+        match value {
+            1 => ChannelNotifyLevel::Mentions,
+            2 => ChannelNotifyLevel::Nothing,
+            _ => ChannelNotifyLevel::All,
+        }
+    }
+
+    /// 转换为 i16（DB 表示）
+    pub fn to_i16(self) -> i16 {
+        self as i16
+    }
+
+    /// 从 RPC 字符串解析："all" / "mentions" / "none"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "all" => Some(ChannelNotifyLevel::All),
+            "mentions" => Some(ChannelNotifyLevel::Mentions),
+            "none" => Some(ChannelNotifyLevel::Nothing),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelNotifyLevel::All => "all",
+            ChannelNotifyLevel::Mentions => "mentions",
+            ChannelNotifyLevel::Nothing => "none",
+        }
+    }
+}
+
 // ============================================================================
 // 成员角色和权限
 // ============================================================================
@@ -198,8 +248,11 @@ impl MemberRole {
     }
 }
 
-/// 会话成员权限（完整版，预留未来功能）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 会话成员权限
+///
+/// 群主恒为全部权限、普通成员恒为角色默认；管理员的权限集可由群主逐人定制，
+/// 持久化在 `privchat_group_members.permissions`（`'{}'` = 角色默认），见 [`MemberPermissions::effective`]。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MemberPermissions {
     // === 基础权限 ===
     /// 是否可以发送消息
@@ -213,17 +266,25 @@ pub struct MemberPermissions {
     /// 是否可以管理权限（设置管理员、修改权限）
     pub can_manage_permissions: bool,
 
-    // === 消息管理权限（预留，暂不实现） ===
+    // === 消息管理权限 ===
     /// 撤回任意成员的消息
     pub can_revoke_any_message: bool,
     /// 置顶消息
     pub can_pin_message: bool,
 
-    // === 特殊权限（预留，暂不实现） ===
+    // === 特殊权限 ===
     /// @所有人
     pub can_at_all: bool,
     /// 修改群设置
     pub can_edit_settings: bool,
+    /// 秩序管理：禁言 / 解禁成员、全员禁言、Reaction 白名单。
+    /// 早于该字段保存的定制权限集里没有它，按管理员默认（允许）补齐。
+    #[serde(default = "default_can_moderate")]
+    pub can_moderate: bool,
+}
+
+fn default_can_moderate() -> bool {
+    true
 }
 
 impl Default for MemberPermissions {
@@ -238,6 +299,7 @@ impl Default for MemberPermissions {
             can_pin_message: false,
             can_at_all: false,
             can_edit_settings: false,
+            can_moderate: false,
         }
     }
 }
@@ -255,6 +317,7 @@ impl MemberPermissions {
             can_pin_message: true,
             can_at_all: true,
             can_edit_settings: true,
+            can_moderate: true,
         }
     }
 
@@ -270,6 +333,7 @@ impl MemberPermissions {
             can_pin_message: true,
             can_at_all: true,
             can_edit_settings: false, // 不能修改群设置
+            can_moderate: true,
         }
     }
 
@@ -285,6 +349,7 @@ impl MemberPermissions {
             can_pin_message: false,
             can_at_all: false,
             can_edit_settings: false,
+            can_moderate: false,
         }
     }
 
@@ -296,6 +361,20 @@ impl MemberPermissions {
             MemberRole::Member => Self::member(),
         }
     }
+
+    /// 按角色 + 群主定制的权限集算出实际生效的权限。
+    ///
+    /// 只有管理员吃定制集：群主的权限不可被削减，普通成员也不能借一份脏数据提权。
+    /// 管理员的 `can_manage_permissions` 恒为 false——任命/调整管理员只属于群主。
+    pub fn effective(role: MemberRole, custom: Option<&MemberPermissions>) -> Self {
+        match (role, custom) {
+            (MemberRole::Admin, Some(custom)) => Self {
+                can_manage_permissions: false,
+                ..custom.clone()
+            },
+            _ => Self::from_role(role),
+        }
+    }
 }
 
 // ============================================================================
@@ -313,6 +392,9 @@ pub struct ChannelMember {
     pub role: MemberRole,
     /// 成员权限
     pub permissions: MemberPermissions,
+    /// 管理员头衔（群主自定义，仅管理员有效）
+    #[serde(default)]
+    pub title: Option<String>,
     /// 加入时间
     pub joined_at: DateTime<Utc>,
     /// 最后活跃时间
@@ -384,6 +466,7 @@ pub fn apply_group_role_truth(
         if let Some(member) = members.get_mut(user_id) {
             if member.role != *role {
                 member.role = *role;
+                member.permissions = MemberPermissions::from_role(*role);
                 repaired += 1;
             }
         }
@@ -391,6 +474,32 @@ pub fn apply_group_role_truth(
     repaired
 }
 
+/// 把真源（`privchat_group_members`）里群主给管理员定制的权限集与头衔套到成员上，
+/// 返回权限被改写的条目数。须在 [`apply_group_role_truth`] 之后调用——权限按真源角色计算。
+///
+/// 投递镜像里的 permissions 只是建群/入群时的快照，不跟随群主后续的调整；
+/// 这里按 [`MemberPermissions::effective`] 重算，非管理员的定制集一律忽略。
+pub fn apply_group_admin_grants(
+    members: &mut HashMap<u64, ChannelMember>,
+    grants: &[(u64, Option<MemberPermissions>, Option<String>)],
+) -> usize {
+    let mut changed = 0;
+    for (user_id, custom, title) in grants {
+        if let Some(member) = members.get_mut(user_id) {
+            let effective = MemberPermissions::effective(member.role, custom.as_ref());
+            if member.permissions != effective {
+                member.permissions = effective;
+                changed += 1;
+            }
+            member.title = match member.role {
+                MemberRole::Admin => title.clone(),
+                _ => None,
+            };
+        }
+    }
+    changed
+}
+
 /// 被禁言时的拒绝提示：区分「永久」与「剩余时长」（对标 QQ/Telegram 体验）。
 pub fn mute_reject_message(mute_until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
     const PERMANENT_THRESHOLD_SECS: i64 = 50 * 365 * 86_400; // rpc 层「永久」= now+100 年
@@ -425,6 +534,7 @@ impl ChannelMember {
             display_name: None,
             role,
             permissions,
+            title: None,
             joined_at: Utc::now(),
             last_active_at: Utc::now(),
             is_muted: false,
@@ -486,13 +596,16 @@ impl ChannelParticipant {
         joined_at: i64,          // 毫秒时间戳
         left_at: Option<i64>,    // 毫秒时间戳
     ) -> Self {
+        let role = MemberRole::from_i16(role);
         Self {
             channel_id: channel_id as u64,
             user_id: user_id as u64,
-            role: MemberRole::from_i16(role),
+            role,
             nickname,
+            // 参与者行的 permissions 默认是 `{}`，解析不出完整权限时按角色取默认权限，
+            // 否则群主/管理员会被当成无任何特权（例如无法 @全体成员）
             permissions: serde_json::from_value(permissions)
-                .unwrap_or_else(|_| MemberPermissions::default()),
+                .unwrap_or_else(|_| MemberPermissions::from_role(role)),
             mute_until: mute_until.and_then(|ts| DateTime::from_timestamp_millis(ts)),
             joined_at: DateTime::from_timestamp_millis(joined_at).unwrap_or_else(|| Utc::now()),
             left_at: left_at.and_then(|ts| DateTime::from_timestamp_millis(ts)),
@@ -532,6 +645,7 @@ impl ChannelParticipant {
             display_name: self.nickname.clone(),
             role: self.role,
             permissions: self.permissions.clone(),
+            title: None,
             joined_at: self.joined_at,
             last_active_at: self.joined_at, // 默认使用加入时间
             is_muted: self.mute_until.map_or(false, |dt| dt > Utc::now()),
@@ -635,8 +749,15 @@ pub struct GroupPolicy {
     /// 服务端在创建副本**之前**拒绝。已完成的转发不追溯——副本是目标会话里的
     /// 独立消息，追溯删除等于让第三方的会话被源群单方面改写。
     pub forbid_forward: bool,
+    /// 慢速模式：普通成员两次发言的最小间隔（秒），0 = 关闭。群主/管理员豁免。
+    ///
+    /// 配置以 DB 为真源，每个成员的发言窗口在 Redis（见 `GroupSlowModeService`），多节点共享。
+    pub slow_mode_secs: u32,
 }
 
+/// 群慢速模式间隔上限（秒），与 `privchat_groups.slow_mode_secs` 的 CHECK 约束一致。
+pub const MAX_GROUP_SLOW_MODE_SECS: u32 = 3600;
+
 impl Default for GroupPolicy {
     fn default() -> Self {
         Self {
@@ -647,6 +768,7 @@ impl Default for GroupPolicy {
             all_muted: false,
             allow_member_post: true,
             forbid_forward: false,
+            slow_mode_secs: 0,
         }
     }
 }
@@ -912,12 +1034,19 @@ impl Channel {
             return Err("Cannot change owner role".to_string());
         }
 
+        // 角色未变：保留群主给该管理员定制的权限集与头衔
+        if member.role == new_role {
+            return Ok(());
+        }
+
         member.role = new_role.clone();
         member.permissions = match new_role {
             MemberRole::Owner => MemberPermissions::owner(),
             MemberRole::Admin => MemberPermissions::admin(),
             MemberRole::Member => MemberPermissions::member(),
         };
+        // 角色变更即作废旧的定制权限与头衔（与 DB 侧 set_member_role_admin 的重置一致）
+        member.title = None;
 
         self.updated_at = Utc::now();
         Ok(())
@@ -1507,6 +1636,66 @@ mod tests {
         assert_eq!(members.get(&7).unwrap().role, MemberRole::Owner);
     }
 
+    #[test]
+    fn admin_grants_apply_only_to_admins() {
+        let mut members = HashMap::new();
+        members.insert(1, ChannelMember::new(1, MemberRole::Owner));
+        members.insert(2, ChannelMember::new(2, MemberRole::Admin));
+        members.insert(3, ChannelMember::new(3, MemberRole::Member));
+
+        let custom = MemberPermissions {
+            can_remove_member: false,
+            can_manage_permissions: true, // 脏数据：管理员不能拿到管理权限
+            ..MemberPermissions::admin()
+        };
+        let grants = vec![
+            (
+                1,
+                Some(MemberPermissions::member()),
+                Some("群主".to_string()),
+            ),
+            (2, Some(custom), Some("纪律委员".to_string())),
+            (
+                3,
+                Some(MemberPermissions::owner()),
+                Some("冒充".to_string()),
+            ),
+        ];
+        let changed = apply_group_admin_grants(&mut members, &grants);
+
+        assert_eq!(changed, 1, "只有管理员吃定制集");
+        let admin = members.get(&2).unwrap();
+        assert!(!admin.permissions.can_remove_member);
+        assert!(!admin.permissions.can_manage_permissions);
+        assert_eq!(admin.title.as_deref(), Some("纪律委员"));
+        assert_eq!(
+            members.get(&1).unwrap().permissions,
+            MemberPermissions::owner()
+        );
+        assert_eq!(members.get(&1).unwrap().title, None);
+        assert_eq!(
+            members.get(&3).unwrap().permissions,
+            MemberPermissions::member()
+        );
+        assert_eq!(members.get(&3).unwrap().title, None);
+    }
+
+    #[test]
+    fn admin_without_a_custom_set_gets_role_defaults() {
+        let mut members = HashMap::new();
+        let mut admin = ChannelMember::new(2, MemberRole::Admin);
+        admin.permissions = MemberPermissions::member(); // 镜像里的陈旧快照
+        members.insert(2, admin);
+
+        let changed = apply_group_admin_grants(&mut members, &[(2, None, None)]);
+
+        assert_eq!(changed, 1);
+        assert_eq!(
+            members.get(&2).unwrap().permissions,
+            MemberPermissions::admin()
+        );
+    }
+
     #[test]
     fn hydrating_a_participant_keeps_the_real_join_time() {
         // ChannelMember::new() 会把 joined_at 打成 now；hydrate 必须走
@@ -1530,6 +1719,36 @@ mod tests {
         assert_eq!(member.role, MemberRole::Owner);
     }
 
+    #[test]
+    fn hydrating_a_participant_without_stored_permissions_uses_role_defaults() {
+        // participants.permissions 默认 `{}`：不能退回成员默认权限，否则群主无法 @全体成员
+        let owner = ChannelParticipant::from_db_row(
+            513,
+            7,
+            0,
+            None,
+            serde_json::Value::Object(serde_json::Map::new()),
+            None,
+            0,
+            None,
+        )
+        .to_channel_member();
+        assert!(owner.permissions.can_at_all);
+
+        let member = ChannelParticipant::from_db_row(
+            513,
+            8,
+            2,
+            None,
+            serde_json::Value::Object(serde_json::Map::new()),
+            None,
+            0,
+            None,
+        )
+        .to_channel_member();
+        assert!(!member.permissions.can_at_all);
+    }
+
     #[test]
     fn test_member_permissions() {
         let owner_perms = MemberPermissions::owner();
@@ -20,6 +20,7 @@
 #![allow(ambiguous_glob_reexports)]
 
 // 基础模型
+pub mod bot;
 pub mod device;
 pub mod friend;
 pub mod message;
@@ -48,6 +49,7 @@ pub mod user_channel;
 
 // 重新导出常用类型
 pub use blacklist::*;
+pub use bot::*;
 pub use channel::*;
 pub use device::*;
 pub use file_upload::*;
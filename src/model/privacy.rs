@@ -51,6 +51,26 @@ pub struct UserPrivacySettings {
     /// 默认 true，允许接收非好友消息
     pub allow_receive_message_from_non_friend: bool,
 
+    /// 谁可以看到我的最后上线时间
+    #[serde(default)]
+    pub last_seen: VisibilityRule,
+
+    /// 谁可以看到我是否在线
+    #[serde(default)]
+    pub online_status: VisibilityRule,
+
+    /// 谁可以看到我的头像
+    #[serde(default)]
+    pub avatar: VisibilityRule,
+
+    /// 谁可以看到我的手机号（默认仅好友）
+    #[serde(default = "VisibilityRule::contacts")]
+    pub phone: VisibilityRule,
+
+    /// 谁可以看到我的已读回执
+    #[serde(default)]
+    pub read_receipts: VisibilityRule,
+
     /// 更新时间
     pub updated_at: DateTime<Utc>,
 }
@@ -68,10 +88,45 @@ impl UserPrivacySettings {
             allow_search_by_qrcode: true,
             allow_view_by_non_friend: false, // 默认不允许非好友查看
             allow_receive_message_from_non_friend: true, // 默认允许接收非好友消息（类似QQ/Telegram/Zalo）
+            last_seen: VisibilityRule::default(),
+            online_status: VisibilityRule::default(),
+            avatar: VisibilityRule::default(),
+            phone: VisibilityRule::contacts(),
+            read_receipts: VisibilityRule::default(),
             updated_at: Utc::now(),
         }
     }
 
+    /// 取某一项信息的可见性规则
+    pub fn rule(&self, key: PrivacyKey) -> &VisibilityRule {
+        match key {
+            PrivacyKey::LastSeen => &self.last_seen,
+            PrivacyKey::OnlineStatus => &self.online_status,
+            PrivacyKey::Avatar => &self.avatar,
+            PrivacyKey::Phone => &self.phone,
+            PrivacyKey::ReadReceipts => &self.read_receipts,
+        }
+    }
+
+    /// viewer 能否看到本用户的某一项信息
+    ///
+    /// `is_contact` 为双方是否好友。对等项（最后上线 / 在线状态 / 已读回执）与
+    /// Telegram 一致：自己对对方隐藏了，也就看不到对方的。本人总是可见。
+    pub fn visible_to(
+        &self,
+        key: PrivacyKey,
+        viewer: &UserPrivacySettings,
+        is_contact: bool,
+    ) -> bool {
+        if self.user_id == viewer.user_id {
+            return true;
+        }
+        if !self.rule(key).allows(viewer.user_id, is_contact) {
+            return false;
+        }
+        !key.is_reciprocal() || viewer.rule(key).allows(self.user_id, is_contact)
+    }
+
     /// 检查是否允许被搜索（根据搜索类型）
     pub fn allows_search(&self, search_type: SearchType) -> bool {
         match search_type {
@@ -83,6 +138,120 @@ impl UserPrivacySettings {
     }
 }
 
+/// 可见性规则中每个例外名单的最大长度
+pub const MAX_VISIBILITY_EXCEPTIONS: usize = 1000;
+
+/// 受可见性规则控制的信息项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyKey {
+    LastSeen,
+    OnlineStatus,
+    Avatar,
+    Phone,
+    ReadReceipts,
+}
+
+impl PrivacyKey {
+    pub const ALL: [PrivacyKey; 5] = [
+        PrivacyKey::LastSeen,
+        PrivacyKey::OnlineStatus,
+        PrivacyKey::Avatar,
+        PrivacyKey::Phone,
+        PrivacyKey::ReadReceipts,
+    ];
+
+    /// 存储 / 协议字段名
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PrivacyKey::LastSeen => "last_seen",
+            PrivacyKey::OnlineStatus => "online_status",
+            PrivacyKey::Avatar => "avatar",
+            PrivacyKey::Phone => "phone",
+            PrivacyKey::ReadReceipts => "read_receipts",
+        }
+    }
+
+    /// 是否对等：隐藏自己的同时也看不到别人的
+    pub fn is_reciprocal(self) -> bool {
+        matches!(
+            self,
+            PrivacyKey::LastSeen | PrivacyKey::OnlineStatus | PrivacyKey::ReadReceipts
+        )
+    }
+}
+
+/// 可见范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// 所有人
+    #[default]
+    Everyone,
+    /// 仅好友
+    Contacts,
+    /// 所有人都不可见
+    Nobody,
+}
+
+/// 可见性规则：基础范围 + 例外名单。
+///
+/// 拒绝名单优先于允许名单，两者都优先于基础范围
+/// （例如「仅好友，但不给 A 看；另外允许非好友 B 看」）。
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct VisibilityRule {
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// 例外：始终可见
+    #[serde(default)]
+    pub allow_user_ids: Vec<u64>,
+    /// 例外：始终不可见
+    #[serde(default)]
+    pub deny_user_ids: Vec<u64>,
+}
+
+impl VisibilityRule {
+    /// 仅好友可见、无例外
+    pub fn contacts() -> Self {
+        Self {
+            visibility: Visibility::Contacts,
+            ..Self::default()
+        }
+    }
+
+    /// viewer 是否在本规则下可见（不含本人判断与对等判断）
+    pub fn allows(&self, viewer_id: u64, is_contact: bool) -> bool {
+        if self.deny_user_ids.contains(&viewer_id) {
+            return false;
+        }
+        if self.allow_user_ids.contains(&viewer_id) {
+            return true;
+        }
+        match self.visibility {
+            Visibility::Everyone => true,
+            Visibility::Contacts => is_contact,
+            Visibility::Nobody => false,
+        }
+    }
+
+    /// 规范化例外名单：去重排序，同时出现在两个名单里的以拒绝为准，去掉 owner 自己。
+    /// 名单超长返回错误信息。
+    pub fn normalized(mut self, owner_id: u64) -> Result<Self, String> {
+        for list in [&mut self.allow_user_ids, &mut self.deny_user_ids] {
+            list.retain(|&id| id != 0 && id != owner_id);
+            list.sort_unstable();
+            list.dedup();
+            if list.len() > MAX_VISIBILITY_EXCEPTIONS {
+                return Err(format!("例外名单最多 {} 人", MAX_VISIBILITY_EXCEPTIONS));
+            }
+        }
+        let deny = &self.deny_user_ids;
+        self.allow_user_ids
+            .retain(|id| deny.binary_search(id).is_err());
+        Ok(self)
+    }
+}
+
 /// 搜索类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SearchType {
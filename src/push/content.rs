@@ -0,0 +1,377 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::ChannelType;
+use crate::repository::{UserPushSettings, UserPushSettingsRepository, UserRepository};
+use crate::service::{ChannelService, UnreadCountService};
+use std::sync::Arc;
+use tracing::debug;
+
+/// 推送文案语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushLocale {
+    ZhHans,
+    ZhHant,
+    En,
+}
+
+impl PushLocale {
+    /// 解析 BCP 47 语言标签；空值按简体中文，其余未支持的语言回退英文
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = tag.trim().to_ascii_lowercase().replace('_', "-");
+        if tag.is_empty() {
+            return PushLocale::ZhHans;
+        }
+        if tag.starts_with("zh") {
+            let traditional = ["hant", "tw", "hk", "mo"]
+                .iter()
+                .any(|part| tag.split('-').any(|p| p == *part));
+            return if traditional {
+                PushLocale::ZhHant
+            } else {
+                PushLocale::ZhHans
+            };
+        }
+        PushLocale::En
+    }
+
+    fn templates(self) -> &'static Templates {
+        match self {
+            PushLocale::ZhHans => &ZH_HANS,
+            PushLocale::ZhHant => &ZH_HANT,
+            PushLocale::En => &EN,
+        }
+    }
+}
+
+/// 单一语言的通知文案
+struct Templates {
+    new_message: &'static str,
+    group_chat: &'static str,
+    hidden_body: &'static str,
+    sender_separator: &'static str,
+    image: &'static str,
+    voice: &'static str,
+    video: &'static str,
+    file: &'static str,
+    location: &'static str,
+    contact_card: &'static str,
+    sticker: &'static str,
+    link: &'static str,
+    red_packet: &'static str,
+    money_transfer: &'static str,
+}
+
+const ZH_HANS: Templates = Templates {
+    new_message: "新消息",
+    group_chat: "群聊",
+    hidden_body: "你收到了一条新消息",
+    sender_separator: "：",
+    image: "[图片]",
+    voice: "[语音]",
+    video: "[视频]",
+    file: "[文件]",
+    location: "[位置]",
+    contact_card: "[名片]",
+    sticker: "[表情]",
+    link: "[链接]",
+    red_packet: "[红包]",
+    money_transfer: "[转账]",
+};
+
+const ZH_HANT: Templates = Templates {
+    new_message: "新訊息",
+    group_chat: "群組",
+    hidden_body: "你收到了一則新訊息",
+    sender_separator: "：",
+    image: "[圖片]",
+    voice: "[語音]",
+    video: "[影片]",
+    file: "[檔案]",
+    location: "[位置]",
+    contact_card: "[名片]",
+    sticker: "[貼圖]",
+    link: "[連結]",
+    red_packet: "[紅包]",
+    money_transfer: "[轉帳]",
+};
+
+const EN: Templates = Templates {
+    new_message: "New message",
+    group_chat: "Group chat",
+    hidden_body: "You have a new message",
+    sender_separator: ": ",
+    image: "[Photo]",
+    voice: "[Voice message]",
+    video: "[Video]",
+    file: "[File]",
+    location: "[Location]",
+    contact_card: "[Contact]",
+    sticker: "[Sticker]",
+    link: "[Link]",
+    red_packet: "[Red packet]",
+    money_transfer: "[Transfer]",
+};
+
+/// 渲染通知文案所需的输入
+#[derive(Debug, Clone, Default)]
+pub struct NotificationInput<'a> {
+    /// 消息类型字符串（与 ContentMessageType::as_str 一致）
+    pub message_type: &'a str,
+    /// 文本预览（仅 text / link 会直接展示）
+    pub preview: &'a str,
+    pub sender_name: Option<&'a str>,
+    /// 群名；私聊为 None
+    pub group_name: Option<&'a str>,
+    pub is_group: bool,
+    pub hide_preview: bool,
+}
+
+/// 渲染结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationText {
+    pub title: String,
+    pub body: String,
+}
+
+/// 按语言模板渲染通知标题与正文
+///
+/// - 私聊：标题为发送者，正文为内容
+/// - 群聊：标题为群名，正文为「发送者：内容」
+/// - 隐藏预览：不出现发送者、群名与正文
+pub fn render_notification(locale: PushLocale, input: &NotificationInput<'_>) -> NotificationText {
+    let t = locale.templates();
+    if input.hide_preview {
+        return NotificationText {
+            title: t.new_message.to_string(),
+            body: t.hidden_body.to_string(),
+        };
+    }
+
+    let content = message_body(t, input.message_type, input.preview);
+    let sender = input.sender_name.map(str::trim).filter(|s| !s.is_empty());
+
+    if input.is_group {
+        let title = input
+            .group_name
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(t.group_chat)
+            .to_string();
+        let body = match sender {
+            Some(sender) => format!("{}{}{}", sender, t.sender_separator, content),
+            None => content,
+        };
+        NotificationText { title, body }
+    } else {
+        NotificationText {
+            title: sender.unwrap_or(t.new_message).to_string(),
+            body: content,
+        }
+    }
+}
+
+fn message_body(t: &Templates, message_type: &str, preview: &str) -> String {
+    let placeholder = match message_type {
+        "image" => t.image,
+        "voice" => t.voice,
+        "video" => t.video,
+        "file" | "audio" => t.file,
+        "location" => t.location,
+        "contact_card" => t.contact_card,
+        "sticker" => t.sticker,
+        "red_packet" => t.red_packet,
+        "money_transfer" => t.money_transfer,
+        "link" if preview.trim().is_empty() => t.link,
+        _ => {
+            let preview = preview.trim();
+            return if preview.is_empty() {
+                t.new_message.to_string()
+            } else {
+                preview.to_string()
+            };
+        }
+    };
+    placeholder.to_string()
+}
+
+/// 会话维度的分组 / 折叠 key
+pub fn conversation_thread_id(conversation_id: u64) -> String {
+    format!("conv_{}", conversation_id)
+}
+
+/// 推送内容解析器
+///
+/// 按接收者补齐发送者昵称、群名、语言偏好与未读角标；
+/// 任一查询失败都退化为默认文案，不阻断推送。
+pub struct PushContentResolver {
+    user_repo: Arc<UserRepository>,
+    channel_service: Arc<ChannelService>,
+    unread_count_service: Arc<UnreadCountService>,
+    push_settings_repo: Arc<UserPushSettingsRepository>,
+}
+
+/// 解析后的接收者视角内容
+#[derive(Debug, Clone)]
+pub struct ResolvedContent {
+    pub text: NotificationText,
+    pub badge: Option<u32>,
+}
+
+impl PushContentResolver {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        channel_service: Arc<ChannelService>,
+        unread_count_service: Arc<UnreadCountService>,
+        push_settings_repo: Arc<UserPushSettingsRepository>,
+    ) -> Self {
+        Self {
+            user_repo,
+            channel_service,
+            unread_count_service,
+            push_settings_repo,
+        }
+    }
+
+    pub async fn resolve(
+        &self,
+        recipient_id: u64,
+        sender_id: u64,
+        conversation_id: u64,
+        message_type: &str,
+        preview: &str,
+    ) -> ResolvedContent {
+        let settings = match self.push_settings_repo.get(recipient_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                debug!(
+                    "[PUSH CONTENT] Failed to load push settings of user {}: {}",
+                    recipient_id, e
+                );
+                UserPushSettings::default_for(recipient_id)
+            }
+        };
+
+        let (is_group, group_name) =
+            match self.channel_service.get_channel_opt(conversation_id).await {
+                Some(channel) => (
+                    channel.channel_type != ChannelType::Direct,
+                    channel.metadata.name,
+                ),
+                None => (false, None),
+            };
+
+        let sender_name = if settings.hide_preview {
+            None
+        } else {
+            match self.user_repo.find_by_id(sender_id).await {
+                Ok(Some(user)) => user.display_name.or(user.username),
+                Ok(None) => None,
+                Err(e) => {
+                    debug!("[PUSH CONTENT] Failed to load sender {}: {}", sender_id, e);
+                    None
+                }
+            }
+        };
+
+        let text = render_notification(
+            PushLocale::from_tag(&settings.locale),
+            &NotificationInput {
+                message_type,
+                preview,
+                sender_name: sender_name.as_deref(),
+                group_name: group_name.as_deref(),
+                is_group,
+                hide_preview: settings.hide_preview,
+            },
+        );
+
+        let badge = match self.unread_count_service.get_all(recipient_id).await {
+            Ok(counts) => Some(counts.values().sum::<u64>().min(u32::MAX as u64) as u32),
+            Err(e) => {
+                debug!(
+                    "[PUSH CONTENT] Failed to load unread counts of user {}: {}",
+                    recipient_id, e
+                );
+                None
+            }
+        };
+
+        ResolvedContent { text, badge }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_tags() {
+        assert_eq!(PushLocale::from_tag("zh-CN"), PushLocale::ZhHans);
+        assert_eq!(PushLocale::from_tag("zh_TW"), PushLocale::ZhHant);
+        assert_eq!(PushLocale::from_tag("zh-Hant-HK"), PushLocale::ZhHant);
+        assert_eq!(PushLocale::from_tag("en-US"), PushLocale::En);
+        assert_eq!(PushLocale::from_tag("ja"), PushLocale::En);
+        assert_eq!(PushLocale::from_tag(""), PushLocale::ZhHans);
+    }
+
+    #[test]
+    fn direct_and_group_rendering() {
+        let direct = render_notification(
+            PushLocale::ZhHans,
+            &NotificationInput {
+                message_type: "image",
+                preview: "https://cdn/x.jpg",
+                sender_name: Some("Alice"),
+                ..Default::default()
+            },
+        );
+        assert_eq!(direct.title, "Alice");
+        assert_eq!(direct.body, "[图片]");
+
+        let group = render_notification(
+            PushLocale::En,
+            &NotificationInput {
+                message_type: "text",
+                preview: "hello",
+                sender_name: Some("Bob"),
+                group_name: Some("Team"),
+                is_group: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(group.title, "Team");
+        assert_eq!(group.body, "Bob: hello");
+    }
+
+    #[test]
+    fn hide_preview_hides_everything() {
+        let text = render_notification(
+            PushLocale::ZhHans,
+            &NotificationInput {
+                message_type: "text",
+                preview: "secret",
+                sender_name: Some("Alice"),
+                group_name: Some("Team"),
+                is_group: true,
+                hide_preview: true,
+            },
+        );
+        assert_eq!(text.title, "新消息");
+        assert!(!text.body.contains("secret") && !text.body.contains("Alice"));
+    }
+}
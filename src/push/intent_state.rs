@@ -15,34 +15,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::push::intent_store::{IntentRecord, IntentStore, MemoryIntentStore};
 use crate::push::types::IntentStatus;
-use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tracing::warn;
 
 /// Intent 状态管理器（共享状态）
 ///
 /// 用于 Planner 和 Worker 之间共享 Intent 状态
 /// Phase 3: 支持撤销和取消推送
+///
+/// 状态真源是 [`IntentStore`]：生产用 Redis 实现，重启不丢、任意节点都能取消；
+/// `new()` 使用进程内存储，仅供测试和单节点场景。
+/// 存储故障时只记日志，不阻断推送主流程。
 pub struct IntentStateManager {
-    // message_id -> intent_ids 映射（用于撤销，Phase 3.5: 一个消息可能有多个设备级 Intent）
-    intent_by_message: Arc<RwLock<HashMap<u64, Vec<String>>>>,
-    // user_id -> intent_ids 映射（用于取消）
-    intents_by_user: Arc<RwLock<HashMap<u64, Vec<String>>>>,
-    // ✨ Phase 3.5: device_id -> intent_ids 映射（用于设备级取消）
-    intents_by_device: Arc<RwLock<HashMap<String, Vec<String>>>>,
-    // intent_id -> status 映射
-    intent_status: Arc<RwLock<HashMap<String, IntentStatus>>>,
+    store: Arc<dyn IntentStore>,
 }
 
 impl IntentStateManager {
     pub fn new() -> Self {
-        Self {
-            intent_by_message: Arc::new(RwLock::new(HashMap::new())),
-            intents_by_user: Arc::new(RwLock::new(HashMap::new())),
-            intents_by_device: Arc::new(RwLock::new(HashMap::new())), // ✨ Phase 3.5
-            intent_status: Arc::new(RwLock::new(HashMap::new())),
-        }
+        Self::with_store(Arc::new(MemoryIntentStore::default()))
+    }
+
+    /// 使用指定的 Intent 存储
+    pub fn with_store(store: Arc<dyn IntentStore>) -> Self {
+        Self { store }
     }
 
     /// 注册 Intent（用户级，兼容旧逻辑）
@@ -59,137 +57,132 @@ impl IntentStateManager {
         user_id: u64,
         device_id: &str,
     ) {
-        // message_id -> intent_ids（一个消息可能有多个设备级 Intent）
-        {
-            let mut map = self.intent_by_message.write().await;
-            map.entry(message_id)
-                .or_insert_with(Vec::new)
-                .push(intent_id.to_string());
-        }
-
-        // user_id -> intent_ids
-        {
-            let mut map = self.intents_by_user.write().await;
-            map.entry(user_id)
-                .or_insert_with(Vec::new)
-                .push(intent_id.to_string());
-        }
-
-        // ✨ Phase 3.5: device_id -> intent_ids
-        if !device_id.is_empty() {
-            let mut map = self.intents_by_device.write().await;
-            map.entry(device_id.to_string())
-                .or_insert_with(Vec::new)
-                .push(intent_id.to_string());
-        }
-
-        // intent_id -> status
-        {
-            let mut map = self.intent_status.write().await;
-            map.insert(intent_id.to_string(), IntentStatus::Pending);
+        let record = IntentRecord {
+            intent_id: intent_id.to_string(),
+            message_id,
+            user_id,
+            device_id: device_id.to_string(),
+        };
+        if let Err(e) = self.store.register(&record).await {
+            warn!(
+                "[INTENT STATE] Failed to register intent {}: {}",
+                intent_id, e
+            );
         }
     }
 
     /// 获取 Intent 状态
     pub async fn get_status(&self, intent_id: &str) -> Option<IntentStatus> {
-        let map = self.intent_status.read().await;
-        map.get(intent_id).copied()
+        match self.store.get_status(intent_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                warn!(
+                    "[INTENT STATE] Failed to get status of intent {}: {}",
+                    intent_id, e
+                );
+                None
+            }
+        }
     }
 
     /// 标记 Intent 为 revoked（Phase 3.5: 支持多个设备级 Intent）
     pub async fn mark_revoked(&self, message_id: u64) -> usize {
-        // 查找所有 intent_ids
-        let intent_ids = {
-            let map = self.intent_by_message.read().await;
-            map.get(&message_id).cloned().unwrap_or_default()
-        };
-
-        if intent_ids.is_empty() {
-            return 0;
-        }
-
-        // 标记所有 Intent 为 revoked
-        let mut count = 0;
-        let mut map = self.intent_status.write().await;
-        for intent_id in intent_ids {
-            if let Some(status) = map.get_mut(&intent_id) {
-                if *status == IntentStatus::Pending {
-                    *status = IntentStatus::Revoked;
-                    count += 1;
-                }
+        match self.store.intents_by_message(message_id).await {
+            Ok(intent_ids) => self.transition_all(intent_ids, IntentStatus::Revoked).await,
+            Err(e) => {
+                warn!(
+                    "[INTENT STATE] Failed to list intents of message {}: {}",
+                    message_id, e
+                );
+                0
             }
         }
-
-        count
     }
 
     /// 标记用户的所有待推送 Intent 为 cancelled
     pub async fn mark_cancelled(&self, user_id: u64) -> usize {
-        // 查找该用户的所有 Intent
-        let intent_ids = {
-            let map = self.intents_by_user.read().await;
-            map.get(&user_id).cloned().unwrap_or_default()
-        };
-
-        if intent_ids.is_empty() {
-            return 0;
-        }
-
-        // 标记为 cancelled
-        let mut count = 0;
-        let mut map = self.intent_status.write().await;
-        for intent_id in intent_ids {
-            if let Some(status) = map.get_mut(&intent_id) {
-                if *status == IntentStatus::Pending {
-                    *status = IntentStatus::Cancelled;
-                    count += 1;
-                }
+        match self.store.intents_by_user(user_id).await {
+            Ok(intent_ids) => {
+                self.transition_all(intent_ids, IntentStatus::Cancelled)
+                    .await
+            }
+            Err(e) => {
+                warn!(
+                    "[INTENT STATE] Failed to list intents of user {}: {}",
+                    user_id, e
+                );
+                0
             }
         }
-
-        count
     }
 
     /// ✨ Phase 3.5: 按设备取消 Intent
+    ///
+    /// 指定 message_id 时只取消该消息在这台设备上的 Intent（消息已送达），
+    /// 否则取消该设备全部待推送 Intent（设备上线）。
     pub async fn mark_cancelled_by_device(
         &self,
         device_id: &str,
-        _message_id: Option<u64>,
+        message_id: Option<u64>,
     ) -> usize {
-        // 查找该设备的所有 Intent
-        let intent_ids = {
-            let map = self.intents_by_device.read().await;
-            map.get(device_id).cloned().unwrap_or_default()
+        let device_intents = match self.store.intents_by_device(device_id).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                warn!(
+                    "[INTENT STATE] Failed to list intents of device {}: {}",
+                    device_id, e
+                );
+                return 0;
+            }
         };
 
-        if intent_ids.is_empty() {
-            return 0;
-        }
+        let intent_ids = match message_id {
+            Some(message_id) => {
+                let message_intents: HashSet<String> =
+                    match self.store.intents_by_message(message_id).await {
+                        Ok(ids) => ids.into_iter().collect(),
+                        Err(e) => {
+                            warn!(
+                                "[INTENT STATE] Failed to list intents of message {}: {}",
+                                message_id, e
+                            );
+                            return 0;
+                        }
+                    };
+                device_intents
+                    .into_iter()
+                    .filter(|id| message_intents.contains(id))
+                    .collect()
+            }
+            None => device_intents,
+        };
 
-        // 如果指定了 message_id，需要过滤（暂时先取消所有，可以优化）
-        let mut count = 0;
-        let mut map = self.intent_status.write().await;
+        self.transition_all(intent_ids, IntentStatus::Cancelled)
+            .await
+    }
 
+    /// 清理已完成的 Intent
+    ///
+    /// 存储按 TTL 自动过期，无需显式清理；保留接口兼容旧调用方。
+    pub async fn cleanup_completed(&self, _intent_id: &str) {}
+
+    /// 把仍处于 Pending 的 Intent 迁移到目标状态，返回实际迁移的数量
+    async fn transition_all(&self, intent_ids: Vec<String>, to: IntentStatus) -> usize {
+        let mut count = 0;
         for intent_id in intent_ids {
-            // TODO: 如果指定了 message_id，需要检查 Intent 的 message_id
-            // 暂时先取消所有该设备的 Intent
-            if let Some(status) = map.get_mut(&intent_id) {
-                if *status == IntentStatus::Pending {
-                    *status = IntentStatus::Cancelled;
-                    count += 1;
-                }
+            match self.store.transition_from_pending(&intent_id, to).await {
+                Ok(true) => count += 1,
+                Ok(false) => {}
+                Err(e) => warn!(
+                    "[INTENT STATE] Failed to mark intent {} as {}: {}",
+                    intent_id,
+                    to.as_str(),
+                    e
+                ),
             }
         }
-
         count
     }
-
-    /// 清理已完成的 Intent（可选，防止内存泄漏）
-    pub async fn cleanup_completed(&self, _intent_id: &str) {
-        // 从所有映射中移除
-        // 注意：这里需要知道 message_id 和 user_id，暂时不实现
-        // 未来可以添加一个反向索引
-    }
 }
 
 impl Default for IntentStateManager {
@@ -197,3 +190,22 @@ impl Default for IntentStateManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn delivered_cancels_only_that_message_on_device() {
+        let state = IntentStateManager::new();
+        state.register_device_intent("a", 1, 7, "ios-7").await;
+        state.register_device_intent("b", 2, 7, "ios-7").await;
+
+        assert_eq!(state.mark_cancelled_by_device("ios-7", Some(1)).await, 1);
+        assert_eq!(state.get_status("a").await, Some(IntentStatus::Cancelled));
+        assert_eq!(state.get_status("b").await, Some(IntentStatus::Pending));
+
+        assert_eq!(state.mark_cancelled_by_device("ios-7", None).await, 1);
+        assert_eq!(state.mark_revoked(2).await, 0);
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Result;
+use crate::infra::redis::RedisClient;
+use crate::push::types::IntentStatus;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Intent 默认存活时间：过期后状态与索引自动消失，查询视为不存在
+pub const DEFAULT_INTENT_TTL: Duration = Duration::from_secs(3600);
+
+/// 注册 Intent 时写入的索引信息
+#[derive(Debug, Clone)]
+pub struct IntentRecord {
+    pub intent_id: String,
+    pub message_id: u64,
+    pub user_id: u64,
+    /// 空字符串表示用户级 Intent（不进设备索引）
+    pub device_id: String,
+}
+
+/// Intent 状态存储
+///
+/// Planner 注册 Intent、事件处理方取消 / 撤销、Worker 发送前检查，三者可能在不同节点，
+/// 所以状态必须放在共享存储里。状态迁移只允许 `Pending -> *`，由存储保证原子性。
+#[async_trait]
+pub trait IntentStore: Send + Sync {
+    /// 注册 Intent（初始状态 Pending）
+    async fn register(&self, record: &IntentRecord) -> Result<()>;
+
+    /// 查询状态；不存在或已过期返回 None
+    async fn get_status(&self, intent_id: &str) -> Result<Option<IntentStatus>>;
+
+    /// 仅当当前为 Pending 时改为 `to`，返回是否发生了迁移
+    async fn transition_from_pending(&self, intent_id: &str, to: IntentStatus) -> Result<bool>;
+
+    /// 某条消息的所有未过期 Intent
+    async fn intents_by_message(&self, message_id: u64) -> Result<Vec<String>>;
+
+    /// 某个用户的所有未过期 Intent
+    async fn intents_by_user(&self, user_id: u64) -> Result<Vec<String>>;
+
+    /// 某台设备的所有未过期 Intent
+    async fn intents_by_device(&self, device_id: &str) -> Result<Vec<String>>;
+}
+
+// ============================================================
+// 内存实现（单节点 / 测试）
+// ============================================================
+
+struct MemoryIntentEntry {
+    status: IntentStatus,
+    message_id: u64,
+    user_id: u64,
+    device_id: String,
+    expires_at: Instant,
+}
+
+/// 进程内 Intent 存储
+///
+/// 重启即丢失，也无法跨节点取消，仅用于测试和单节点开发环境。
+pub struct MemoryIntentStore {
+    ttl: Duration,
+    entries: RwLock<HashMap<String, MemoryIntentEntry>>,
+}
+
+impl MemoryIntentStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn collect<F>(&self, filter: F) -> Vec<String>
+    where
+        F: Fn(&MemoryIntentEntry) -> bool,
+    {
+        let now = Instant::now();
+        let entries = self.entries.read().await;
+        entries
+            .iter()
+            .filter(|(_, entry)| entry.expires_at > now && filter(entry))
+            .map(|(intent_id, _)| intent_id.clone())
+            .collect()
+    }
+}
+
+impl Default for MemoryIntentStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTENT_TTL)
+    }
+}
+
+#[async_trait]
+impl IntentStore for MemoryIntentStore {
+    async fn register(&self, record: &IntentRecord) -> Result<()> {
+        let now = Instant::now();
+        let mut entries = self.entries.write().await;
+        // 注册时顺带清理过期项，避免常驻进程内存无限增长
+        entries.retain(|_, entry| entry.expires_at > now);
+        entries.insert(
+            record.intent_id.clone(),
+            MemoryIntentEntry {
+                status: IntentStatus::Pending,
+                message_id: record.message_id,
+                user_id: record.user_id,
+                device_id: record.device_id.clone(),
+                expires_at: now + self.ttl,
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_status(&self, intent_id: &str) -> Result<Option<IntentStatus>> {
+        let entries = self.entries.read().await;
+        Ok(entries
+            .get(intent_id)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.status))
+    }
+
+    async fn transition_from_pending(&self, intent_id: &str, to: IntentStatus) -> Result<bool> {
+        let mut entries = self.entries.write().await;
+        match entries.get_mut(intent_id) {
+            Some(entry)
+                if entry.expires_at > Instant::now() && entry.status == IntentStatus::Pending =>
+            {
+                entry.status = to;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn intents_by_message(&self, message_id: u64) -> Result<Vec<String>> {
+        Ok(self.collect(|entry| entry.message_id == message_id).await)
+    }
+
+    async fn intents_by_user(&self, user_id: u64) -> Result<Vec<String>> {
+        Ok(self.collect(|entry| entry.user_id == user_id).await)
+    }
+
+    async fn intents_by_device(&self, device_id: &str) -> Result<Vec<String>> {
+        Ok(self
+            .collect(|entry| !entry.device_id.is_empty() && entry.device_id == device_id)
+            .await)
+    }
+}
+
+// ============================================================
+// Redis 实现（生产 / 多节点）
+// ============================================================
+
+/// 基于 Redis 的 Intent 存储
+///
+/// - `privchat:push:intent:{intent_id}` → 状态字符串，带 TTL
+/// - `privchat:push:intents:msg|user|dev:{id}` → ZSET，member 为 intent_id，score 为过期时间（毫秒）
+///
+/// 索引按 score 过滤已过期成员，写入时顺带修剪，不依赖 KEYS / SCAN。
+pub struct RedisIntentStore {
+    redis: Arc<RedisClient>,
+    ttl: Duration,
+}
+
+impl RedisIntentStore {
+    pub fn new(redis: Arc<RedisClient>, ttl: Duration) -> Self {
+        Self { redis, ttl }
+    }
+
+    fn intent_key(intent_id: &str) -> String {
+        format!("privchat:push:intent:{}", intent_id)
+    }
+
+    fn message_index_key(message_id: u64) -> String {
+        format!("privchat:push:intents:msg:{}", message_id)
+    }
+
+    fn user_index_key(user_id: u64) -> String {
+        format!("privchat:push:intents:user:{}", user_id)
+    }
+
+    fn device_index_key(device_id: &str) -> String {
+        format!("privchat:push:intents:dev:{}", device_id)
+    }
+
+    fn ttl_secs(&self) -> usize {
+        self.ttl.as_secs().max(1) as usize
+    }
+
+    async fn live_members(&self, index_key: &str) -> Result<Vec<String>> {
+        let now_ms = chrono::Utc::now().timestamp_millis() as f64;
+        self.redis
+            .zrangebyscore(index_key, now_ms, f64::INFINITY, None)
+            .await
+    }
+}
+
+#[async_trait]
+impl IntentStore for RedisIntentStore {
+    async fn register(&self, record: &IntentRecord) -> Result<()> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let expires_at_ms = now_ms + self.ttl.as_millis() as i64;
+
+        let mut index_keys = vec![
+            Self::message_index_key(record.message_id),
+            Self::user_index_key(record.user_id),
+        ];
+        if !record.device_id.is_empty() {
+            index_keys.push(Self::device_index_key(&record.device_id));
+        }
+
+        self.redis
+            .setex_with_zindexes(
+                &Self::intent_key(&record.intent_id),
+                IntentStatus::Pending.as_str(),
+                self.ttl_secs(),
+                &index_keys,
+                &record.intent_id,
+                expires_at_ms as f64,
+                now_ms as f64,
+            )
+            .await
+    }
+
+    async fn get_status(&self, intent_id: &str) -> Result<Option<IntentStatus>> {
+        let raw = self.redis.get(&Self::intent_key(intent_id)).await?;
+        Ok(raw.as_deref().and_then(IntentStatus::from_str))
+    }
+
+    async fn transition_from_pending(&self, intent_id: &str, to: IntentStatus) -> Result<bool> {
+        self.redis
+            .compare_and_set_keepttl(
+                &Self::intent_key(intent_id),
+                IntentStatus::Pending.as_str(),
+                to.as_str(),
+            )
+            .await
+    }
+
+    async fn intents_by_message(&self, message_id: u64) -> Result<Vec<String>> {
+        self.live_members(&Self::message_index_key(message_id))
+            .await
+    }
+
+    async fn intents_by_user(&self, user_id: u64) -> Result<Vec<String>> {
+        self.live_members(&Self::user_index_key(user_id)).await
+    }
+
+    async fn intents_by_device(&self, device_id: &str) -> Result<Vec<String>> {
+        self.live_members(&Self::device_index_key(device_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(intent_id: &str, message_id: u64, user_id: u64, device_id: &str) -> IntentRecord {
+        IntentRecord {
+            intent_id: intent_id.to_string(),
+            message_id,
+            user_id,
+            device_id: device_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_store_transitions_only_from_pending() {
+        let store = MemoryIntentStore::default();
+        store.register(&record("i1", 10, 1, "d1")).await.unwrap();
+
+        assert!(store
+            .transition_from_pending("i1", IntentStatus::Revoked)
+            .await
+            .unwrap());
+        assert!(!store
+            .transition_from_pending("i1", IntentStatus::Cancelled)
+            .await
+            .unwrap());
+        assert_eq!(
+            store.get_status("i1").await.unwrap(),
+            Some(IntentStatus::Revoked)
+        );
+        assert!(!store
+            .transition_from_pending("missing", IntentStatus::Revoked)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn memory_store_indexes_and_expiry() {
+        let store = MemoryIntentStore::new(Duration::from_millis(20));
+        store.register(&record("i1", 10, 1, "d1")).await.unwrap();
+        store.register(&record("i2", 10, 2, "")).await.unwrap();
+
+        let mut by_message = store.intents_by_message(10).await.unwrap();
+        by_message.sort();
+        assert_eq!(by_message, vec!["i1".to_string(), "i2".to_string()]);
+        assert_eq!(store.intents_by_device("d1").await.unwrap(), vec!["i1"]);
+        assert!(store.intents_by_device("").await.unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(store.get_status("i1").await.unwrap(), None);
+        assert!(store.intents_by_user(1).await.unwrap().is_empty());
+    }
+}
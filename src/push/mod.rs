@@ -15,13 +15,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod content;
 pub mod intent_state;
+pub mod intent_store;
 pub mod planner;
+pub mod policy;
 pub mod provider;
+pub mod retry;
 pub mod types;
 pub mod worker; // ✨ Phase 3: Intent 状态管理
 
+pub use content::PushContentResolver;
 pub use intent_state::IntentStateManager;
+pub use intent_store::{IntentStore, MemoryIntentStore, RedisIntentStore};
 pub use planner::PushPlanner;
+pub use policy::PushPolicyResolver;
+pub use retry::PushRetryPolicy;
 pub use types::{IntentStatus, PushIntent, PushPayload, PushTask, PushVendor};
 pub use worker::PushWorker;
@@ -20,7 +20,11 @@ use crate::error::Result;
 use crate::infra::connection_manager::ConnectionManager;
 use crate::infra::event_bus::EventBus;
 use crate::infra::redis::RedisClient;
+use crate::push::content::{
+    conversation_thread_id, render_notification, NotificationInput, PushContentResolver, PushLocale,
+};
 use crate::push::intent_state::IntentStateManager;
+use crate::push::policy::{MentionHint, PushDecision, PushPolicyResolver};
 use crate::push::types::{PushIntent, PushPayload};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -39,6 +43,8 @@ pub struct PushPlanner {
     redis: Option<Arc<RedisClient>>,
     connection_manager: Option<Arc<ConnectionManager>>,
     intent_state: Arc<IntentStateManager>, // Phase 3: 共享状态管理器
+    content_resolver: Option<Arc<PushContentResolver>>,
+    policy_resolver: Option<Arc<PushPolicyResolver>>,
 }
 
 impl PushPlanner {
@@ -47,6 +53,8 @@ impl PushPlanner {
             redis: None,
             connection_manager: None,
             intent_state: Arc::new(IntentStateManager::new()),
+            content_resolver: None,
+            policy_resolver: None,
         }
     }
 
@@ -56,6 +64,8 @@ impl PushPlanner {
             redis: Some(redis),
             connection_manager: None,
             intent_state: Arc::new(IntentStateManager::new()),
+            content_resolver: None,
+            policy_resolver: None,
         }
     }
 
@@ -68,6 +78,8 @@ impl PushPlanner {
             redis,
             connection_manager: None,
             intent_state,
+            content_resolver: None,
+            policy_resolver: None,
         }
     }
 
@@ -81,9 +93,23 @@ impl PushPlanner {
             redis,
             connection_manager: Some(connection_manager),
             intent_state,
+            content_resolver: None,
+            policy_resolver: None,
         }
     }
 
+    /// 设置推送内容解析器（发送者昵称、群名、语言、角标）
+    pub fn with_content_resolver(mut self, content_resolver: Arc<PushContentResolver>) -> Self {
+        self.content_resolver = Some(content_resolver);
+        self
+    }
+
+    /// 设置推送策略解析器（会话通知级别、@ 判断、免打扰时段）
+    pub fn with_policy_resolver(mut self, policy_resolver: Arc<PushPolicyResolver>) -> Self {
+        self.policy_resolver = Some(policy_resolver);
+        self
+    }
+
     /// 获取 Intent 状态管理器（供 Worker 使用）
     pub fn intent_state(&self) -> Arc<IntentStateManager> {
         Arc::clone(&self.intent_state)
@@ -158,8 +184,11 @@ impl PushPlanner {
             sender_id,
             recipient_id,
             content_preview,
+            message_type,
             timestamp,
             device_id,
+            mentioned_user_ids,
+            is_mention_all,
         ) = match event {
             DomainEvent::MessageCommitted {
                 message_id,
@@ -167,17 +196,22 @@ impl PushPlanner {
                 sender_id,
                 recipient_id,
                 content_preview,
+                message_type,
                 timestamp,
                 device_id, // ✨ Phase 3.5: 可选的设备ID
-                ..
+                mentioned_user_ids,
+                is_mention_all,
             } => (
                 message_id,
                 conversation_id,
                 sender_id,
                 recipient_id,
                 content_preview,
+                message_type,
                 timestamp,
                 device_id,
+                mentioned_user_ids,
+                is_mention_all,
             ),
             _ => {
                 error!("[PUSH PLANNER] Unexpected event type in handle_message_committed");
@@ -190,8 +224,37 @@ impl PushPlanner {
             message_id, recipient_id, sender_id, device_id
         );
 
+        let mention = MentionHint {
+            mentioned_user_ids: &mentioned_user_ids,
+            is_mention_all,
+            preview: &content_preview,
+        };
+
         // ✨ Phase 3.5: 如果指定了 device_id，只为该设备生成 Intent
         if let Some(device_id) = device_id {
+            let decision = self
+                .evaluate_policy(recipient_id, conversation_id, &mention)
+                .await;
+            if decision == PushDecision::Skip {
+                debug!(
+                    "[PUSH PLANNER] Push suppressed by notification settings: user_id={}, channel_id={}",
+                    recipient_id, conversation_id
+                );
+                return Ok(());
+            }
+
+            let mut payload = self
+                .build_payload(
+                    recipient_id,
+                    sender_id,
+                    conversation_id,
+                    message_id,
+                    &message_type,
+                    &content_preview,
+                )
+                .await;
+            payload.silent = decision == PushDecision::Silent;
+
             let intent_id = Uuid::new_v4().to_string();
             let intent = PushIntent::new(
                 intent_id.clone(),
@@ -200,13 +263,7 @@ impl PushPlanner {
                 recipient_id,
                 device_id.clone(), // ✨ 设备级 Intent
                 sender_id,
-                PushPayload {
-                    r#type: "new_message".to_string(),
-                    conversation_id,
-                    message_id,
-                    sender_id,
-                    content_preview,
-                },
+                payload,
                 timestamp,
             );
 
@@ -238,11 +295,34 @@ impl PushPlanner {
             return Ok(());
         }
 
+        let decision = self
+            .evaluate_policy(recipient_id, conversation_id, &mention)
+            .await;
+        if decision == PushDecision::Skip {
+            debug!(
+                "[PUSH PLANNER] Push suppressed by notification settings: user_id={}, channel_id={}",
+                recipient_id, conversation_id
+            );
+            return Ok(());
+        }
+
         debug!(
             "[PUSH PLANNER] User {} is offline, generating push intent (legacy mode)",
             recipient_id
         );
 
+        let mut payload = self
+            .build_payload(
+                recipient_id,
+                sender_id,
+                conversation_id,
+                message_id,
+                &message_type,
+                &content_preview,
+            )
+            .await;
+        payload.silent = decision == PushDecision::Silent;
+
         // 生成用户级 PushIntent（兼容旧逻辑）
         let intent_id = Uuid::new_v4().to_string();
         let intent = PushIntent::new(
@@ -252,13 +332,7 @@ impl PushPlanner {
             recipient_id,
             "".to_string(), // 旧逻辑：device_id 为空
             sender_id,
-            PushPayload {
-                r#type: "new_message".to_string(),
-                conversation_id,
-                message_id,
-                sender_id,
-                content_preview,
-            },
+            payload,
             timestamp,
         );
 
@@ -280,6 +354,78 @@ impl PushPlanner {
         Ok(())
     }
 
+    /// 按接收者的通知设置决定是否推送；未配置策略解析器时一律正常推送
+    async fn evaluate_policy(
+        &self,
+        recipient_id: u64,
+        conversation_id: u64,
+        mention: &MentionHint<'_>,
+    ) -> PushDecision {
+        match &self.policy_resolver {
+            Some(resolver) => {
+                resolver
+                    .evaluate(
+                        recipient_id,
+                        conversation_id,
+                        mention,
+                        chrono::Utc::now().timestamp_millis(),
+                    )
+                    .await
+            }
+            None => PushDecision::Alert,
+        }
+    }
+
+    /// 按接收者渲染推送内容；未配置解析器时使用默认语言、无昵称 / 角标
+    async fn build_payload(
+        &self,
+        recipient_id: u64,
+        sender_id: u64,
+        conversation_id: u64,
+        message_id: u64,
+        message_type: &str,
+        preview: &str,
+    ) -> PushPayload {
+        let (text, badge) = match &self.content_resolver {
+            Some(resolver) => {
+                let resolved = resolver
+                    .resolve(
+                        recipient_id,
+                        sender_id,
+                        conversation_id,
+                        message_type,
+                        preview,
+                    )
+                    .await;
+                (resolved.text, resolved.badge)
+            }
+            None => (
+                render_notification(
+                    PushLocale::ZhHans,
+                    &NotificationInput {
+                        message_type,
+                        preview,
+                        ..Default::default()
+                    },
+                ),
+                None,
+            ),
+        };
+
+        PushPayload {
+            r#type: "new_message".to_string(),
+            conversation_id,
+            message_id,
+            sender_id,
+            message_type: message_type.to_string(),
+            title: text.title,
+            body: text.body,
+            badge,
+            thread_id: conversation_thread_id(conversation_id),
+            silent: false,
+        }
+    }
+
     /// 检查用户是否在线。
     ///
     /// 在线态真源是 ConnectionManager；这里禁止 Redis KEYS 热路径。
@@ -0,0 +1,284 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::ChannelNotifyLevel;
+use crate::repository::{UserPushSettings, UserPushSettingsRepository, UserRepository};
+use crate::service::{ChannelService, MentionService};
+use chrono::{TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// 一天的分钟数
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// 推送决策
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushDecision {
+    /// 正常推送（响铃 / 横幅）
+    Alert,
+    /// 静默推送：只更新通知中心与角标，不响铃
+    Silent,
+    /// 不推送
+    Skip,
+}
+
+/// 按会话通知级别、是否被 @、是否处于免打扰时段得出推送决策
+///
+/// 会话级过滤优先：不通知的会话即使被 @ 也不推；仅 @ 的会话没被 @ 不推。
+/// 通过过滤后，免打扰时段内降级为静默推送。
+pub fn decide(level: ChannelNotifyLevel, mentioned: bool, in_dnd: bool) -> PushDecision {
+    match level {
+        ChannelNotifyLevel::Nothing => return PushDecision::Skip,
+        ChannelNotifyLevel::Mentions if !mentioned => return PushDecision::Skip,
+        _ => {}
+    }
+    if in_dnd {
+        PushDecision::Silent
+    } else {
+        PushDecision::Alert
+    }
+}
+
+/// 免打扰时段是否包含某一分钟（start > end 表示跨零点，start == end 表示全天）
+pub fn dnd_window_contains(start_minute: u16, end_minute: u16, minute_of_day: u16) -> bool {
+    if start_minute == end_minute {
+        return true;
+    }
+    if start_minute < end_minute {
+        (start_minute..end_minute).contains(&minute_of_day)
+    } else {
+        minute_of_day >= start_minute || minute_of_day < end_minute
+    }
+}
+
+/// 用户在 `now_millis` 时刻是否处于免打扰时段（按用户时区换算）
+///
+/// 时区无法解析时按 UTC 计算，不因为脏数据整段失效。
+pub fn is_in_dnd(settings: &UserPushSettings, now_millis: i64) -> bool {
+    if !settings.dnd_enabled {
+        return false;
+    }
+    let tz: Tz = settings.timezone.parse().unwrap_or_else(|_| {
+        warn!(
+            "[PUSH POLICY] Invalid timezone {:?} for user {}, falling back to UTC",
+            settings.timezone, settings.user_id
+        );
+        Tz::UTC
+    });
+    let Some(now) = Utc.timestamp_millis_opt(now_millis).single() else {
+        return false;
+    };
+    let local = now.with_timezone(&tz);
+    let minute_of_day = (local.hour() * 60 + local.minute()) as u16;
+    dnd_window_contains(
+        settings.dnd_start_minute % MINUTES_PER_DAY,
+        settings.dnd_end_minute % MINUTES_PER_DAY,
+        minute_of_day,
+    )
+}
+
+/// 消息中的 @ 信息
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MentionHint<'a> {
+    /// 客户端选择的被 @ 用户
+    pub mentioned_user_ids: &'a [u64],
+    /// 是否 @全体成员（发送端已校验权限）
+    pub is_mention_all: bool,
+    /// 内容预览：旧客户端不带 mentioned_user_ids 时按用户名解析
+    pub preview: &'a str,
+}
+
+/// 推送策略解析器：读取接收者的会话通知级别与免打扰设置
+///
+/// 🔴 查询失败时放行（按「全部通知、不在免打扰」处理）：宁可多推一条，不能丢消息提醒。
+pub struct PushPolicyResolver {
+    channel_service: Arc<ChannelService>,
+    push_settings_repo: Arc<UserPushSettingsRepository>,
+    user_repo: Arc<UserRepository>,
+}
+
+impl PushPolicyResolver {
+    pub fn new(
+        channel_service: Arc<ChannelService>,
+        push_settings_repo: Arc<UserPushSettingsRepository>,
+        user_repo: Arc<UserRepository>,
+    ) -> Self {
+        Self {
+            channel_service,
+            push_settings_repo,
+            user_repo,
+        }
+    }
+
+    /// 计算某条消息对某个接收者的推送决策
+    pub async fn evaluate(
+        &self,
+        recipient_id: u64,
+        conversation_id: u64,
+        mention: &MentionHint<'_>,
+        now_millis: i64,
+    ) -> PushDecision {
+        let level = self
+            .channel_service
+            .get_channel_notify_level(recipient_id, conversation_id)
+            .await
+            .unwrap_or_else(|e| {
+                warn!(
+                    "[PUSH POLICY] Failed to load notify level: user_id={}, channel_id={}, error={}",
+                    recipient_id, conversation_id, e
+                );
+                ChannelNotifyLevel::All
+            });
+        if level == ChannelNotifyLevel::Nothing {
+            return PushDecision::Skip;
+        }
+
+        // 只有「仅 @」的会话才需要判断是否被 @，省掉一次用户查询
+        let mentioned =
+            level == ChannelNotifyLevel::Mentions && self.is_mentioned(recipient_id, mention).await;
+
+        let in_dnd = match self.push_settings_repo.get(recipient_id).await {
+            Ok(settings) => is_in_dnd(&settings, now_millis),
+            Err(e) => {
+                warn!(
+                    "[PUSH POLICY] Failed to load push settings: user_id={}, error={}",
+                    recipient_id, e
+                );
+                false
+            }
+        };
+
+        let decision = decide(level, mentioned, in_dnd);
+        debug!(
+            "[PUSH POLICY] user_id={}, channel_id={}, level={}, mentioned={}, in_dnd={} => {:?}",
+            recipient_id,
+            conversation_id,
+            level.as_str(),
+            mentioned,
+            in_dnd,
+            decision
+        );
+        decision
+    }
+
+    async fn is_mentioned(&self, recipient_id: u64, mention: &MentionHint<'_>) -> bool {
+        if mention.is_mention_all || mention.mentioned_user_ids.contains(&recipient_id) {
+            return true;
+        }
+
+        // @全体成员以事件里的 is_mention_all 为准（发送端已做权限检查），这里只认用户名
+        let (usernames, _) = MentionService::parse_mentions(mention.preview);
+        if usernames.is_empty() {
+            return false;
+        }
+        match self.user_repo.find_by_id(recipient_id).await {
+            Ok(Some(user)) => mentions_username(&usernames, user.username.as_deref()),
+            Ok(None) => false,
+            Err(e) => {
+                warn!(
+                    "[PUSH POLICY] Failed to load recipient for mention check: user_id={}, error={}",
+                    recipient_id, e
+                );
+                false
+            }
+        }
+    }
+}
+
+fn mentions_username(usernames: &[String], username: Option<&str>) -> bool {
+    match username {
+        Some(username) if !username.is_empty() => usernames
+            .iter()
+            .any(|mentioned| mentioned.eq_ignore_ascii_case(username)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(start: u16, end: u16, timezone: &str) -> UserPushSettings {
+        UserPushSettings {
+            dnd_enabled: true,
+            dnd_start_minute: start,
+            dnd_end_minute: end,
+            timezone: timezone.to_string(),
+            ..UserPushSettings::default_for(1)
+        }
+    }
+
+    /// 2026-01-01 00:00:00 UTC + 指定分钟
+    fn utc_millis(hour: i64, minute: i64) -> i64 {
+        1_767_225_600_000 + (hour * 60 + minute) * 60_000
+    }
+
+    #[test]
+    fn notify_level_filters_before_dnd() {
+        use ChannelNotifyLevel::*;
+        assert_eq!(decide(All, false, false), PushDecision::Alert);
+        assert_eq!(decide(All, false, true), PushDecision::Silent);
+        assert_eq!(decide(Mentions, false, false), PushDecision::Skip);
+        assert_eq!(decide(Mentions, true, false), PushDecision::Alert);
+        assert_eq!(decide(Mentions, true, true), PushDecision::Silent);
+        assert_eq!(decide(Nothing, true, false), PushDecision::Skip);
+    }
+
+    #[test]
+    fn dnd_window_handles_midnight_wrap() {
+        assert!(dnd_window_contains(1320, 480, 23 * 60));
+        assert!(dnd_window_contains(1320, 480, 0));
+        assert!(dnd_window_contains(1320, 480, 479));
+        assert!(!dnd_window_contains(1320, 480, 480));
+        assert!(!dnd_window_contains(1320, 480, 12 * 60));
+
+        assert!(dnd_window_contains(13 * 60, 14 * 60, 13 * 60 + 30));
+        assert!(!dnd_window_contains(13 * 60, 14 * 60, 14 * 60));
+
+        assert!(dnd_window_contains(600, 600, 0));
+    }
+
+    #[test]
+    fn dnd_uses_user_timezone() {
+        // 22:00-08:00 Asia/Shanghai（UTC+8）
+        let s = settings(1320, 480, "Asia/Shanghai");
+        // 15:00 UTC = 23:00 上海 → 免打扰
+        assert!(is_in_dnd(&s, utc_millis(15, 0)));
+        // 02:00 UTC = 10:00 上海 → 不在免打扰
+        assert!(!is_in_dnd(&s, utc_millis(2, 0)));
+    }
+
+    #[test]
+    fn dnd_disabled_or_invalid_timezone() {
+        let mut s = settings(1320, 480, "Asia/Shanghai");
+        s.dnd_enabled = false;
+        assert!(!is_in_dnd(&s, utc_millis(15, 0)));
+
+        // 无效时区按 UTC：23:00 UTC 在 22:00-08:00 内
+        let s = settings(1320, 480, "Mars/Olympus");
+        assert!(is_in_dnd(&s, utc_millis(23, 0)));
+    }
+
+    #[test]
+    fn username_mention_is_case_insensitive() {
+        let usernames = vec!["Alice".to_string()];
+        assert!(mentions_username(&usernames, Some("alice")));
+        assert!(!mentions_username(&usernames, Some("bob")));
+        assert!(!mentions_username(&usernames, None));
+    }
+}
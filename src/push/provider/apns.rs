@@ -16,11 +16,13 @@
 // limitations under the License.
 
 use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde_json::json;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{error, info};
@@ -98,20 +100,34 @@ impl ApnsProvider {
 
     /// 构建 APNs 消息 payload
     fn build_apns_payload(&self, task: &PushTask) -> serde_json::Value {
-        json!({
-            "aps": {
-                "alert": {
-                    "title": "新消息",
-                    "body": task.payload.content_preview
-                },
-                "badge": 1,
-                "sound": "default"
+        let mut aps = json!({
+            "alert": {
+                "title": task.payload.title,
+                "body": task.payload.body
             },
+            "sound": "default",
+            // 同一会话的通知在通知中心归为一组
+            "thread-id": task.payload.thread_id
+        });
+        if let Some(badge) = task.payload.badge {
+            aps["badge"] = json!(badge);
+        }
+        if task.payload.silent {
+            // 免打扰：不响铃，passive 级别不亮屏、不打断（iOS 15+）
+            if let Some(aps) = aps.as_object_mut() {
+                aps.remove("sound");
+            }
+            aps["interruption-level"] = json!("passive");
+        }
+
+        json!({
+            "aps": aps,
             "data": {
                 "type": task.payload.r#type,
                 "conversation_id": task.payload.conversation_id.to_string(),
                 "message_id": task.payload.message_id.to_string(),
                 "sender_id": task.payload.sender_id.to_string(),
+                "message_type": task.payload.message_type,
             }
         })
     }
@@ -119,7 +135,7 @@ impl ApnsProvider {
 
 #[async_trait]
 impl PushProvider for ApnsProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         // 1. 生成 JWT Token
         let jwt_token = self.generate_jwt_token()?;
 
@@ -147,37 +163,28 @@ impl PushProvider for ApnsProvider {
             .post(&url)
             .header("authorization", format!("bearer {}", jwt_token))
             .header("apns-topic", &self.bundle_id)
-            .header("apns-priority", "10")
+            .header(
+                "apns-priority",
+                if task.payload.silent { "5" } else { "10" },
+            )
             .header("apns-push-type", "alert")
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("APNs request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("APNs", &e))?;
 
         let status = response.status();
         if status.is_success() {
             info!("[APNs] Push sent successfully: task_id={}", task.task_id);
             Ok(())
         } else {
+            let retry_after = parse_retry_after(response.headers());
             let error_text = response.text().await.unwrap_or_default();
             error!(
                 "[APNs] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, error_text
             );
-
-            // 解析 APNs 错误码
-            let error_msg =
-                if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&error_text) {
-                    if let Some(reason) = error_json.get("reason").and_then(|r| r.as_str()) {
-                        format!("APNs error: {} ({})", reason, status)
-                    } else {
-                        format!("APNs push failed: status={}", status)
-                    }
-                } else {
-                    format!("APNs push failed: status={}, error={}", status, error_text)
-                };
-
-            Err(ServerError::Internal(error_msg))
+            Err(classify_apns_error(status, retry_after, &error_text))
         }
     }
 
@@ -185,3 +192,52 @@ impl PushProvider for ApnsProvider {
         PushVendor::Apns
     }
 }
+
+/// 解析 APNs 错误响应
+///
+/// 410 Unregistered、BadDeviceToken、DeviceTokenNotForTopic 说明 token 已不可用。
+fn classify_apns_error(
+    status: StatusCode,
+    retry_after: Option<std::time::Duration>,
+    body: &str,
+) -> PushError {
+    let reason = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("reason").and_then(|r| r.as_str()).map(str::to_string));
+
+    match reason.as_deref() {
+        Some(r @ ("Unregistered" | "BadDeviceToken" | "DeviceTokenNotForTopic")) => {
+            PushError::InvalidToken(format!("APNs error: {} ({})", r, status))
+        }
+        _ if status == StatusCode::GONE => {
+            PushError::InvalidToken(format!("APNs push failed: status={}", status))
+        }
+        Some(reason) => PushError::from_status("APNs", status, retry_after, reason),
+        None => PushError::from_status("APNs", status, retry_after, body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apns_dead_token_reasons() {
+        let gone = classify_apns_error(StatusCode::GONE, None, r#"{"reason":"Unregistered"}"#);
+        assert!(matches!(gone, PushError::InvalidToken(_)));
+        let bad = classify_apns_error(
+            StatusCode::BAD_REQUEST,
+            None,
+            r#"{"reason":"BadDeviceToken"}"#,
+        );
+        assert!(matches!(bad, PushError::InvalidToken(_)));
+        let throttled = classify_apns_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            None,
+            r#"{"reason":"TooManyRequests"}"#,
+        );
+        assert!(throttled.is_retryable());
+        let topic = classify_apns_error(StatusCode::BAD_REQUEST, None, r#"{"reason":"BadTopic"}"#);
+        assert!(matches!(topic, PushError::Permanent(_)));
+    }
+}
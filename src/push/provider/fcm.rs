@@ -16,12 +16,16 @@
 // limitations under the License.
 
 use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::fcm_auth::{ServiceAccountKey, ServiceAccountTokenSource};
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde_json::json;
-use tracing::{error, info};
+use std::sync::Arc;
+use tracing::{error, info, warn};
 
 /// FCM (Firebase Cloud Messaging) Provider
 ///
@@ -29,11 +33,19 @@ use tracing::{error, info};
 pub struct FcmProvider {
     client: Client,
     project_id: String,
-    access_token: String, // OAuth 2.0 access token
+    credentials: FcmCredentials,
+}
+
+/// FCM 鉴权方式
+enum FcmCredentials {
+    /// 静态 OAuth 2.0 access token（1 小时过期，仅适合调试）
+    Static(String),
+    /// Service account 自动换取并刷新
+    ServiceAccount(Arc<ServiceAccountTokenSource>),
 }
 
 impl FcmProvider {
-    /// 创建新的 FCM Provider
+    /// 创建新的 FCM Provider（静态 access token）
     ///
     /// # 参数
     /// - project_id: Firebase 项目 ID
@@ -42,31 +54,99 @@ impl FcmProvider {
         Self {
             client: Client::new(),
             project_id,
-            access_token,
+            credentials: FcmCredentials::Static(access_token),
+        }
+    }
+
+    /// 使用 service account JSON key 创建 FCM Provider
+    ///
+    /// # 参数
+    /// - service_account_path: service account JSON key 文件路径
+    /// - project_id: Firebase 项目 ID，为空时取 key 里的 `project_id`
+    /// - token_endpoint: 覆盖 OAuth token 端点（测试用），默认取 key 里的 `token_uri`
+    pub fn from_service_account(
+        service_account_path: &str,
+        project_id: Option<String>,
+        token_endpoint: Option<String>,
+    ) -> Result<Self> {
+        let raw = std::fs::read_to_string(service_account_path).map_err(|e| {
+            ServerError::Internal(format!("Failed to read FCM service account key: {}", e))
+        })?;
+        let key = ServiceAccountKey::from_json(&raw)?;
+        let project_id = project_id
+            .or_else(|| key.project_id.clone())
+            .ok_or_else(|| {
+                ServerError::Internal(
+                    "FCM project_id missing in config and service account key".to_string(),
+                )
+            })?;
+        let token_source = ServiceAccountTokenSource::new(&key, token_endpoint)?;
+
+        Ok(Self {
+            client: Client::new(),
+            project_id,
+            credentials: FcmCredentials::ServiceAccount(Arc::new(token_source)),
+        })
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        match &self.credentials {
+            FcmCredentials::Static(token) => Ok(token.clone()),
+            FcmCredentials::ServiceAccount(source) => source.access_token().await,
         }
     }
 
     /// 构建 FCM 消息 payload
     fn build_fcm_payload(&self, task: &PushTask) -> serde_json::Value {
+        let mut android_notification = json!({
+            // 同一会话只保留最新一条通知
+            "tag": task.payload.thread_id
+        });
+        let mut aps = json!({
+            "thread-id": task.payload.thread_id
+        });
+        if let Some(badge) = task.payload.badge {
+            android_notification["notification_count"] = json!(badge);
+            aps["badge"] = json!(badge);
+        }
+        if task.payload.silent {
+            // 免打扰：低优先级、不响铃，iOS 端 passive
+            android_notification["notification_priority"] = json!("PRIORITY_LOW");
+            android_notification["default_sound"] = json!(false);
+            android_notification["default_vibrate_timings"] = json!(false);
+            aps["interruption-level"] = json!("passive");
+        }
+        let (android_priority, apns_priority) = if task.payload.silent {
+            ("normal", "5")
+        } else {
+            ("high", "10")
+        };
+
         json!({
             "message": {
                 "token": task.push_token,
                 "notification": {
-                    "title": "新消息",
-                    "body": task.payload.content_preview
+                    "title": task.payload.title,
+                    "body": task.payload.body
                 },
                 "data": {
                     "type": task.payload.r#type,
                     "conversation_id": task.payload.conversation_id.to_string(),
                     "message_id": task.payload.message_id.to_string(),
                     "sender_id": task.payload.sender_id.to_string(),
+                    "message_type": task.payload.message_type,
                 },
                 "android": {
-                    "priority": "high"
+                    "priority": android_priority,
+                    "collapse_key": task.payload.thread_id,
+                    "notification": android_notification
                 },
                 "apns": {
                     "headers": {
-                        "apns-priority": "10"
+                        "apns-priority": apns_priority
+                    },
+                    "payload": {
+                        "aps": aps
                     }
                 }
             }
@@ -76,13 +156,18 @@ impl FcmProvider {
 
 #[async_trait]
 impl PushProvider for FcmProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!(
             "https://fcm.googleapis.com/v1/projects/{}/messages:send",
             self.project_id
         );
 
         let payload = self.build_fcm_payload(task);
+        // 换取 token 失败多为网络 / Google 侧问题，按可重试处理
+        let access_token = self
+            .access_token()
+            .await
+            .map_err(|e| PushError::transient(e.to_string()))?;
 
         info!(
             "[FCM] Sending push: task_id={}, user_id={}, device_id={}",
@@ -92,27 +177,36 @@ impl PushProvider for FcmProvider {
         let response = self
             .client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
+            .header("Authorization", format!("Bearer {}", access_token))
             .header("Content-Type", "application/json")
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("FCM request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("FCM", &e))?;
 
         let status = response.status();
         if status.is_success() {
             info!("[FCM] Push sent successfully: task_id={}", task.task_id);
             Ok(())
         } else {
+            let retry_after = parse_retry_after(response.headers());
             let error_text = response.text().await.unwrap_or_default();
             error!(
                 "[FCM] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, error_text
             );
-            Err(ServerError::Internal(format!(
-                "FCM push failed: status={}, error={}",
-                status, error_text
-            )))
+            if status == StatusCode::UNAUTHORIZED {
+                if let FcmCredentials::ServiceAccount(source) = &self.credentials {
+                    // token 被提前吊销：丢弃缓存，重试时重新换取
+                    warn!("[FCM] Access token rejected, will refresh on retry");
+                    source.invalidate().await;
+                    return Err(PushError::transient(format!(
+                        "FCM push failed: status={}, error={}",
+                        status, error_text
+                    )));
+                }
+            }
+            Err(classify_fcm_error(status, retry_after, &error_text))
         }
     }
 
@@ -120,3 +214,50 @@ impl PushProvider for FcmProvider {
         PushVendor::Fcm
     }
 }
+
+/// 解析 FCM v1 错误响应
+///
+/// `error.details[].errorCode` 为 UNREGISTERED / SENDER_ID_MISMATCH 时 token 已不可用，
+/// 其余按 HTTP 状态码分类（QUOTA_EXCEEDED 是 429，UNAVAILABLE / INTERNAL 是 5xx）。
+fn classify_fcm_error(
+    status: StatusCode,
+    retry_after: Option<std::time::Duration>,
+    body: &str,
+) -> PushError {
+    let error_code = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| {
+            v.pointer("/error/details")?
+                .as_array()?
+                .iter()
+                .find_map(|d| d.get("errorCode")?.as_str().map(str::to_string))
+        });
+
+    match error_code.as_deref() {
+        Some(code @ ("UNREGISTERED" | "SENDER_ID_MISMATCH")) => {
+            PushError::InvalidToken(format!("FCM error: {} ({})", code, status))
+        }
+        _ => PushError::from_status("FCM", status, retry_after, body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fcm_unregistered_is_invalid_token() {
+        let body = r#"{"error":{"code":404,"status":"NOT_FOUND","details":[{"@type":"type.googleapis.com/google.firebase.fcm.v1.FcmError","errorCode":"UNREGISTERED"}]}}"#;
+        assert!(matches!(
+            classify_fcm_error(StatusCode::NOT_FOUND, None, body),
+            PushError::InvalidToken(_)
+        ));
+
+        let quota = r#"{"error":{"code":429,"status":"RESOURCE_EXHAUSTED","details":[{"errorCode":"QUOTA_EXCEEDED"}]}}"#;
+        assert!(classify_fcm_error(StatusCode::TOO_MANY_REQUESTS, None, quota).is_retryable());
+        assert!(matches!(
+            classify_fcm_error(StatusCode::UNAUTHORIZED, None, "{}"),
+            PushError::Permanent(_)
+        ));
+    }
+}
@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -62,17 +63,26 @@ impl HmsProvider {
             "conversation_id": task.payload.conversation_id,
             "message_id": task.payload.message_id,
             "sender_id": task.payload.sender_id,
+            "message_type": task.payload.message_type,
         });
 
+        let mut notification = json!({
+            "title": task.payload.title,
+            "body": task.payload.body,
+            // 同一会话只保留最新一条通知
+            "tag": task.payload.thread_id
+        });
+        if task.payload.silent {
+            // 免打扰：低重要级别，不响铃不弹横幅
+            notification["importance"] = json!("LOW");
+        }
+
         json!({
             "validate_only": false,
             "message": {
                 "token": [task.push_token],
                 "android": {
-                    "notification": {
-                        "title": "新消息",
-                        "body": task.payload.content_preview
-                    },
+                    "notification": notification,
                     "data": data.to_string()
                 }
             }
@@ -82,7 +92,7 @@ impl HmsProvider {
 
 #[async_trait]
 impl PushProvider for HmsProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!("{}/v1/{}/messages:send", self.endpoint, self.app_id);
         let payload = self.build_hms_payload(task);
 
@@ -99,26 +109,82 @@ impl PushProvider for HmsProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("HMS request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("HMS", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
-        if status.is_success() {
-            info!("[HMS] Push sent successfully: task_id={}", task.task_id);
-            Ok(())
+        let result = if status.is_success() {
+            // HMS 业务错误也是 HTTP 200，要看 body 里的 code
+            check_hms_result_code(&body)
         } else {
-            error!(
+            Err(PushError::from_status("HMS", status, retry_after, &body))
+        };
+
+        match &result {
+            Ok(()) => info!("[HMS] Push sent successfully: task_id={}", task.task_id),
+            Err(_) => error!(
                 "[HMS] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
-            );
-            Err(ServerError::Internal(format!(
-                "HMS push failed: status={}, error={}",
-                status, body
-            )))
+            ),
         }
+        result
     }
 
     fn vendor(&self) -> PushVendor {
         PushVendor::Hms
     }
 }
+
+/// HMS 成功码
+const HMS_CODE_SUCCESS: &str = "80000000";
+/// 全部 token 无效
+const HMS_CODE_INVALID_TOKEN: &str = "80300007";
+/// 服务端内部错误，官方建议稍后重试
+const HMS_CODE_SYSTEM_ERROR: &str = "81000001";
+
+/// 解析 HTTP 200 响应里的业务 code；body 无法解析时按成功处理（兼容旧行为）
+fn check_hms_result_code(body: &str) -> PushResult {
+    let code = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("code").and_then(|c| c.as_str()).map(str::to_string));
+
+    match code.as_deref() {
+        None | Some(HMS_CODE_SUCCESS) => Ok(()),
+        Some(HMS_CODE_INVALID_TOKEN) => Err(PushError::InvalidToken(format!(
+            "HMS error: code={}, body={}",
+            HMS_CODE_INVALID_TOKEN, body
+        ))),
+        Some(HMS_CODE_SYSTEM_ERROR) => Err(PushError::transient(format!(
+            "HMS error: code={}, body={}",
+            HMS_CODE_SYSTEM_ERROR, body
+        ))),
+        Some(code) => Err(PushError::Permanent(format!(
+            "HMS error: code={}, body={}",
+            code, body
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hms_result_codes() {
+        assert!(check_hms_result_code(r#"{"code":"80000000","msg":"Success"}"#).is_ok());
+        assert!(check_hms_result_code("").is_ok());
+        assert!(matches!(
+            check_hms_result_code(r#"{"code":"80300007","msg":"All the tokens are invalid"}"#),
+            Err(PushError::InvalidToken(_))
+        ));
+        assert!(matches!(
+            check_hms_result_code(r#"{"code":"81000001","msg":"System inner error"}"#),
+            Err(PushError::Transient { .. })
+        ));
+        assert!(matches!(
+            check_hms_result_code(r#"{"code":"80200003","msg":"OAuth token expired"}"#),
+            Err(PushError::Permanent(_))
+        ));
+    }
+}
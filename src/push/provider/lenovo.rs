@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -52,8 +53,8 @@ impl LenovoProvider {
         json!({
             "app_id": self.app_id,
             "token": task.push_token,
-            "title": "新消息",
-            "body": task.payload.content_preview,
+            "title": task.payload.title,
+            "body": task.payload.body,
             "data": {
                 "type": task.payload.r#type,
                 "conversation_id": task.payload.conversation_id,
@@ -66,7 +67,7 @@ impl LenovoProvider {
 
 #[async_trait]
 impl PushProvider for LenovoProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!("{}/v1/{}/messages:send", self.endpoint, self.app_id);
         let payload = self.build_payload(task);
 
@@ -83,9 +84,10 @@ impl PushProvider for LenovoProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("Lenovo request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("Lenovo", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         if status.is_success() {
             info!("[LENOVO] Push sent successfully: task_id={}", task.task_id);
@@ -95,10 +97,7 @@ impl PushProvider for LenovoProvider {
                 "[LENOVO] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
             );
-            Err(ServerError::Internal(format!(
-                "Lenovo push failed: status={}, error={}",
-                status, body
-            )))
+            Err(PushError::from_status("Lenovo", status, retry_after, &body))
         }
     }
 
@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -52,8 +53,8 @@ impl MeizuProvider {
         json!({
             "app_id": self.app_id,
             "push_ids": [task.push_token],
-            "title": "新消息",
-            "content": task.payload.content_preview,
+            "title": task.payload.title,
+            "content": task.payload.body,
             "payload": {
                 "type": task.payload.r#type,
                 "conversation_id": task.payload.conversation_id,
@@ -66,7 +67,7 @@ impl MeizuProvider {
 
 #[async_trait]
 impl PushProvider for MeizuProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!(
             "{}/garcia/api/server/push/varnished/pushByPushId",
             self.endpoint
@@ -86,9 +87,10 @@ impl PushProvider for MeizuProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("Meizu request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("Meizu", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         if status.is_success() {
             info!("[MEIZU] Push sent successfully: task_id={}", task.task_id);
@@ -98,10 +100,7 @@ impl PushProvider for MeizuProvider {
                 "[MEIZU] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
             );
-            Err(ServerError::Internal(format!(
-                "Meizu push failed: status={}, error={}",
-                status, body
-            )))
+            Err(PushError::from_status("Meizu", status, retry_after, &body))
         }
     }
 
@@ -15,8 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::Result;
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{PushProvider, PushResult};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use tracing::info;
@@ -28,18 +27,20 @@ pub struct MockProvider;
 
 #[async_trait]
 impl PushProvider for MockProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         info!(
             "[MOCK PUSH] Sending push: intent_id={}, task_id={}, user_id={}, device_id={}, vendor={:?}",
             task.intent_id, task.task_id, task.user_id, task.device_id, task.vendor
         );
         info!(
-            "[MOCK PUSH] Payload: type={}, conversation_id={}, message_id={}, sender_id={}, preview={}",
+            "[MOCK PUSH] Payload: type={}, conversation_id={}, message_id={}, sender_id={}, title={}, body={}, badge={:?}",
             task.payload.r#type, 
             task.payload.conversation_id,
             task.payload.message_id,
             task.payload.sender_id,
-            task.payload.content_preview
+            task.payload.title,
+            task.payload.body,
+            task.payload.badge
         );
         Ok(())
     }
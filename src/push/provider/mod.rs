@@ -17,6 +17,7 @@
 
 pub mod apns;
 pub mod fcm; // ✨ Phase 2: FCM Provider
+pub mod fcm_auth;
 pub mod hms;
 pub mod lenovo;
 pub mod meizu;
@@ -34,7 +35,7 @@ pub use lenovo::LenovoProvider;
 pub use meizu::MeizuProvider;
 pub use mock::MockProvider;
 pub use oppo::OppoProvider;
-pub use provider_trait::PushProvider; // ✨ Phase 3
+pub use provider_trait::{PushError, PushProvider, PushResult}; // ✨ Phase 3
 pub use vivo::VivoProvider;
 pub use xiaomi::XiaomiProvider;
 pub use zte::ZteProvider;
@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -54,8 +55,8 @@ impl OppoProvider {
             "target_value": [task.push_token],
             "notification": {
                 "app_message_id": task.task_id,
-                "title": "新消息",
-                "content": task.payload.content_preview
+                "title": task.payload.title,
+                "content": task.payload.body
             },
             "extra": {
                 "type": task.payload.r#type,
@@ -70,7 +71,7 @@ impl OppoProvider {
 
 #[async_trait]
 impl PushProvider for OppoProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!("{}/server/v1/message/notification/unicast", self.endpoint);
         let payload = self.build_payload(task);
 
@@ -87,9 +88,10 @@ impl PushProvider for OppoProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("OPPO request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("OPPO", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         if status.is_success() {
             info!("[OPPO] Push sent successfully: task_id={}", task.task_id);
@@ -99,10 +101,7 @@ impl PushProvider for OppoProvider {
                 "[OPPO] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
             );
-            Err(ServerError::Internal(format!(
-                "OPPO push failed: status={}, error={}",
-                status, body
-            )))
+            Err(PushError::from_status("OPPO", status, retry_after, &body))
         }
     }
 
@@ -15,21 +15,150 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::Result;
+use crate::error::ServerError;
 use crate::push::types::PushVendor;
 use async_trait::async_trait;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::fmt;
+use std::time::Duration;
 
 // 前向声明，避免循环依赖
 use crate::push::types::PushTask;
 
+/// 推送发送结果
+pub type PushResult = std::result::Result<(), PushError>;
+
+/// 厂商推送失败的分类
+///
+/// Worker 只依据分类决定后续动作，不再解析各家错误码：
+/// - `Transient`：429 / 5xx / 网络超时，按退避策略重试
+/// - `InvalidToken`：token 已注销或不属于本应用，停用该设备 token，不重试
+/// - `Permanent`：鉴权失败、参数错误等，重试也不会成功
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushError {
+    Transient {
+        reason: String,
+        /// 厂商通过 Retry-After 指定的等待时间
+        retry_after: Option<Duration>,
+    },
+    InvalidToken(String),
+    Permanent(String),
+}
+
+impl PushError {
+    pub fn transient(reason: impl Into<String>) -> Self {
+        Self::Transient {
+            reason: reason.into(),
+            retry_after: None,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Transient { .. })
+    }
+
+    /// 按 HTTP 状态码做通用分类（不识别 token 失效，交给各 Provider 先判断）
+    pub fn from_status(
+        vendor: &str,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        body: &str,
+    ) -> Self {
+        let reason = format!("{} push failed: status={}, error={}", vendor, status, body);
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            Self::Transient {
+                reason,
+                retry_after,
+            }
+        } else {
+            Self::Permanent(reason)
+        }
+    }
+
+    /// 请求没发出去或没收到响应：超时 / 连接失败都按可重试处理
+    pub fn from_request(vendor: &str, e: &reqwest::Error) -> Self {
+        let reason = format!("{} request failed: {}", vendor, e);
+        if e.is_builder() {
+            Self::Permanent(reason)
+        } else {
+            Self::transient(reason)
+        }
+    }
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transient { reason, .. } => write!(f, "transient: {}", reason),
+            Self::InvalidToken(reason) => write!(f, "invalid token: {}", reason),
+            Self::Permanent(reason) => write!(f, "permanent: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PushError {}
+
+/// 本地错误（签名、读私钥等）不会因重试而恢复
+impl From<ServerError> for PushError {
+    fn from(e: ServerError) -> Self {
+        Self::Permanent(e.to_string())
+    }
+}
+
+/// 解析 Retry-After 响应头（只支持秒数形式，HTTP-date 形式忽略）
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
 /// Push Provider Trait（推送提供者接口）
 ///
 /// MVP 阶段最小化实现
 #[async_trait]
 pub trait PushProvider: Send + Sync {
     /// 发送推送
-    async fn send(&self, task: &PushTask) -> Result<()>;
+    ///
+    /// 失败时必须返回分类后的 [`PushError`]，Worker 据此决定重试或停用 token
+    async fn send(&self, task: &PushTask) -> PushResult;
 
     /// 获取 Provider 对应的 Vendor
     fn vendor(&self) -> PushVendor;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn status_classification() {
+        assert!(
+            PushError::from_status("FCM", StatusCode::TOO_MANY_REQUESTS, None, "").is_retryable()
+        );
+        assert!(
+            PushError::from_status("FCM", StatusCode::SERVICE_UNAVAILABLE, None, "").is_retryable()
+        );
+        assert!(!PushError::from_status("FCM", StatusCode::BAD_REQUEST, None, "").is_retryable());
+        assert!(!PushError::from_status("FCM", StatusCode::UNAUTHORIZED, None, "").is_retryable());
+    }
+
+    #[test]
+    fn retry_after_seconds_only() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(12)));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+}
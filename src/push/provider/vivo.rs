@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -52,8 +53,8 @@ impl VivoProvider {
         json!({
             "regIds": [task.push_token],
             "notification": {
-                "title": "新消息",
-                "content": task.payload.content_preview
+                "title": task.payload.title,
+                "content": task.payload.body
             },
             "params": {
                 "type": task.payload.r#type,
@@ -68,7 +69,7 @@ impl VivoProvider {
 
 #[async_trait]
 impl PushProvider for VivoProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!("{}/message/send", self.endpoint);
         let payload = self.build_payload(task);
 
@@ -85,9 +86,10 @@ impl PushProvider for VivoProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("Vivo request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("Vivo", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         if status.is_success() {
             info!("[VIVO] Push sent successfully: task_id={}", task.task_id);
@@ -97,10 +99,7 @@ impl PushProvider for VivoProvider {
                 "[VIVO] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
             );
-            Err(ServerError::Internal(format!(
-                "Vivo push failed: status={}, error={}",
-                status, body
-            )))
+            Err(PushError::from_status("Vivo", status, retry_after, &body))
         }
     }
 
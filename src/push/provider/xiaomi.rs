@@ -15,8 +15,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::{Result, ServerError};
-use crate::push::provider::provider_trait::PushProvider;
+use crate::push::provider::provider_trait::{
+    parse_retry_after, PushError, PushProvider, PushResult,
+};
 use crate::push::types::{PushTask, PushVendor};
 use async_trait::async_trait;
 use reqwest::Client;
@@ -55,8 +56,8 @@ impl XiaomiProvider {
         json!({
             "app_id": self.app_id,
             "registration_id": task.push_token,
-            "title": "新消息",
-            "description": task.payload.content_preview,
+            "title": task.payload.title,
+            "description": task.payload.body,
             "payload": json!({
                 "type": task.payload.r#type,
                 "conversation_id": task.payload.conversation_id,
@@ -69,7 +70,7 @@ impl XiaomiProvider {
 
 #[async_trait]
 impl PushProvider for XiaomiProvider {
-    async fn send(&self, task: &PushTask) -> Result<()> {
+    async fn send(&self, task: &PushTask) -> PushResult {
         let url = format!("{}/v3/message/regid", self.endpoint);
         let payload = self.build_payload(task);
 
@@ -86,9 +87,10 @@ impl PushProvider for XiaomiProvider {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| ServerError::Internal(format!("Xiaomi request failed: {}", e)))?;
+            .map_err(|e| PushError::from_request("Xiaomi", &e))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers());
         let body = response.text().await.unwrap_or_default();
         if status.is_success() {
             info!("[XIAOMI] Push sent successfully: task_id={}", task.task_id);
@@ -98,10 +100,7 @@ impl PushProvider for XiaomiProvider {
                 "[XIAOMI] Push failed: task_id={}, status={}, error={}",
                 task.task_id, status, body
             );
-            Err(ServerError::Internal(format!(
-                "Xiaomi push failed: status={}, error={}",
-                status, body
-            )))
+            Err(PushError::from_status("Xiaomi", status, retry_after, &body))
         }
     }
 
@@ -0,0 +1,109 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::PushRetryConfig;
+use std::time::Duration;
+
+/// 推送重试策略（指数退避）
+///
+/// 第 n 次失败后等待 `base_delay * 2^(n-1)`，不超过 `max_delay`；
+/// 厂商给了 Retry-After 时取两者较大值，同样受 `max_delay` 约束。
+#[derive(Debug, Clone)]
+pub struct PushRetryPolicy {
+    /// 总尝试次数（含首次）
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl PushRetryPolicy {
+    pub fn from_config(config: &PushRetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+        }
+    }
+
+    /// 已失败 `failed_attempts` 次后的等待时间；`None` 表示次数用尽，不再重试
+    pub fn next_delay(
+        &self,
+        failed_attempts: u32,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if failed_attempts == 0 || failed_attempts >= self.max_attempts {
+            return None;
+        }
+        let exp = failed_attempts.saturating_sub(1).min(31);
+        let backoff = self.base_delay.saturating_mul(1u32 << exp);
+        let delay = match retry_after {
+            Some(hint) => backoff.max(hint),
+            None => backoff,
+        };
+        Some(delay.min(self.max_delay))
+    }
+}
+
+impl Default for PushRetryPolicy {
+    fn default() -> Self {
+        Self::from_config(&PushRetryConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PushRetryPolicy {
+        PushRetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(3),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let p = policy();
+        assert_eq!(p.next_delay(1, None), Some(Duration::from_millis(500)));
+        assert_eq!(p.next_delay(2, None), Some(Duration::from_millis(1000)));
+        assert_eq!(p.next_delay(3, None), Some(Duration::from_millis(2000)));
+        assert_eq!(p.next_delay(4, None), None);
+    }
+
+    #[test]
+    fn retry_after_is_honored_within_cap() {
+        let p = policy();
+        assert_eq!(
+            p.next_delay(1, Some(Duration::from_secs(2))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            p.next_delay(1, Some(Duration::from_secs(60))),
+            Some(Duration::from_secs(3))
+        );
+    }
+
+    #[test]
+    fn single_attempt_never_retries() {
+        let p = PushRetryPolicy {
+            max_attempts: 1,
+            ..policy()
+        };
+        assert_eq!(p.next_delay(1, None), None);
+    }
+}
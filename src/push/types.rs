@@ -66,13 +66,28 @@ impl PushVendor {
 }
 
 /// 推送 Payload
+///
+/// title / body 已按接收者的语言与隐藏预览设置渲染好，Provider 直接使用，
+/// 原始消息内容不会出现在这里。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushPayload {
     pub r#type: String, // "new_message"
     pub conversation_id: u64,
     pub message_id: u64,
     pub sender_id: u64,
-    pub content_preview: String,
+    /// 消息类型（"text" / "image" / ...）
+    pub message_type: String,
+    /// 通知标题：私聊为发送者昵称，群聊为群名
+    pub title: String,
+    /// 通知正文：文本内容或按类型的占位文案（[图片] / [语音] ...）
+    pub body: String,
+    /// 桌面角标（接收者全部未读数），取不到时不设置
+    pub badge: Option<u32>,
+    /// 会话维度的分组 / 折叠 key（APNs thread-id、Android tag / collapse_key）
+    pub thread_id: String,
+    /// 静默推送（免打扰时段）：不响铃、不打断，只进通知中心并更新角标
+    #[serde(default)]
+    pub silent: bool,
 }
 
 /// Intent 状态（Phase 3）
@@ -85,6 +100,29 @@ pub enum IntentStatus {
     Revoked,    // 已撤销（消息撤销）
 }
 
+impl IntentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntentStatus::Pending => "pending",
+            IntentStatus::Processing => "processing",
+            IntentStatus::Sent => "sent",
+            IntentStatus::Cancelled => "cancelled",
+            IntentStatus::Revoked => "revoked",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(IntentStatus::Pending),
+            "processing" => Some(IntentStatus::Processing),
+            "sent" => Some(IntentStatus::Sent),
+            "cancelled" => Some(IntentStatus::Cancelled),
+            "revoked" => Some(IntentStatus::Revoked),
+            _ => None,
+        }
+    }
+}
+
 /// PushIntent（设备级，Phase 3.5）
 #[derive(Debug, Clone)]
 pub struct PushIntent {
@@ -15,12 +15,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::Result;
+use crate::error::{Result, ServerError};
 use crate::push::intent_state::IntentStateManager;
 use crate::push::provider::{
     ApnsProvider, FcmProvider, HmsProvider, LenovoProvider, MeizuProvider, MockProvider,
-    OppoProvider, PushProvider, VivoProvider, XiaomiProvider, ZteProvider,
+    OppoProvider, PushError, PushProvider, VivoProvider, XiaomiProvider, ZteProvider,
 };
+use crate::push::retry::PushRetryPolicy;
 use crate::push::types::{IntentStatus, PushIntent, PushTask, PushVendor};
 use crate::repository::UserDeviceRepository;
 use std::sync::Arc;
@@ -36,6 +37,7 @@ use uuid::Uuid;
 /// - 展开 Intent 为设备级 PushTask
 /// - 调用 Provider 发送推送
 /// - 检查 Intent 状态（撤销/取消）
+/// - 可重试错误按退避策略后台重试，token 失效时停用该设备 token
 pub struct PushWorker {
     receiver: mpsc::Receiver<PushIntent>,
     mock_provider: Arc<MockProvider>,
@@ -51,6 +53,7 @@ pub struct PushWorker {
     meizu_provider: Option<Arc<MeizuProvider>>,
     device_repo: Option<Arc<UserDeviceRepository>>,
     intent_state: Option<Arc<IntentStateManager>>, // Phase 3: Intent 状态管理器
+    retry_policy: PushRetryPolicy,
 }
 
 impl PushWorker {
@@ -70,6 +73,7 @@ impl PushWorker {
             meizu_provider: None,
             device_repo: None,
             intent_state: None,
+            retry_policy: PushRetryPolicy::default(),
         }
     }
 
@@ -93,6 +97,7 @@ impl PushWorker {
             meizu_provider: None,
             device_repo: Some(device_repo),
             intent_state: None,
+            retry_policy: PushRetryPolicy::default(),
         }
     }
 
@@ -127,9 +132,16 @@ impl PushWorker {
             meizu_provider,
             device_repo: Some(device_repo),
             intent_state: Some(intent_state),
+            retry_policy: PushRetryPolicy::default(),
         }
     }
 
+    /// 设置厂商发送失败的重试策略
+    pub fn with_retry_policy(mut self, retry_policy: PushRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     /// 启动 Worker，处理 Intent
     pub async fn start(&mut self) -> Result<()> {
         info!("[PUSH WORKER] Started");
@@ -208,7 +220,7 @@ impl PushWorker {
                         };
 
                         // 调用 Provider
-                        return self.process_single_task(&task).await;
+                        return self.process_single_task(task).await;
                     }
                     Ok(None) => {
                         debug!(
@@ -262,6 +274,7 @@ impl PushWorker {
         // 2. 为每个设备生成 PushTask
         let mut success_count = 0;
         let mut failed_count = 0;
+        let mut retrying_count = 0;
 
         for device in devices {
             let task = PushTask {
@@ -274,322 +287,105 @@ impl PushWorker {
                 payload: intent.payload.clone(),
             };
 
-            // 3. 根据 vendor 选择 Provider
-            let provider: Arc<dyn PushProvider> = match task.vendor {
-                PushVendor::Fcm => {
-                    // 如果配置了 FCM Provider，使用它；否则降级到 Mock
-                    if let Some(ref fcm) = self.fcm_provider {
-                        fcm.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] FCM Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Apns => {
-                    // Phase 3: 如果配置了 APNs Provider，使用它；否则降级到 Mock
-                    if let Some(ref apns) = self.apns_provider {
-                        apns.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] APNs Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Hms => {
-                    if let Some(ref hms) = self.hms_provider {
-                        hms.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] HMS Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Xiaomi => {
-                    if let Some(ref xiaomi) = self.xiaomi_provider {
-                        xiaomi.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] Xiaomi Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Oppo => {
-                    if let Some(ref oppo) = self.oppo_provider {
-                        oppo.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] OPPO Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Vivo => {
-                    if let Some(ref vivo) = self.vivo_provider {
-                        vivo.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] Vivo Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Honor => {
-                    if let Some(ref honor) = self.honor_provider {
-                        honor.clone() as Arc<dyn PushProvider>
-                    } else if let Some(ref hms) = self.hms_provider {
-                        hms.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] Honor/HMS Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Lenovo => {
-                    if let Some(ref lenovo) = self.lenovo_provider {
-                        lenovo.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] Lenovo Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Zte => {
-                    if let Some(ref zte) = self.zte_provider {
-                        zte.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] ZTE Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-                PushVendor::Meizu => {
-                    if let Some(ref meizu) = self.meizu_provider {
-                        meizu.clone() as Arc<dyn PushProvider>
-                    } else {
-                        warn!(
-                            "[PUSH WORKER] Meizu Provider not configured, using mock for task {}",
-                            task.task_id
-                        );
-                        self.mock_provider.clone() as Arc<dyn PushProvider>
-                    }
-                }
-            };
-
-            match provider.send(&task).await {
-                Ok(_) => {
-                    success_count += 1;
-                    debug!("[PUSH WORKER] Task {} sent successfully", task.task_id);
-                    // [TRACE] Node 3: push_sent
-                    {
-                        use crate::infra::delivery_trace::{global_trace_store, stages};
-                        global_trace_store()
-                            .record(
-                                intent.message_id,
-                                stages::PUSH_SENT,
-                                format!("device={}", task.device_id),
-                            )
-                            .await;
-                    }
-                }
-                Err(e) => {
-                    failed_count += 1;
-                    error!("[PUSH WORKER] Failed to send task {}: {}", task.task_id, e);
-                    // [TRACE] Node 4: push_failed
-                    {
-                        use crate::infra::delivery_trace::{global_trace_store, stages};
-                        global_trace_store()
-                            .record(
-                                intent.message_id,
-                                stages::PUSH_FAILED,
-                                format!("device={} err={}", task.device_id, e),
-                            )
-                            .await;
-                    }
-                }
+            // 3. 根据 vendor 选择 Provider 并发送
+            match self.send_task(task).await {
+                TaskOutcome::Sent => success_count += 1,
+                TaskOutcome::RetryScheduled => retrying_count += 1,
+                TaskOutcome::Failed(_) => failed_count += 1,
             }
         }
 
         info!(
-            "[PUSH WORKER] Intent processed: intent_id={}, success={}, failed={}",
-            intent.intent_id, success_count, failed_count
+            "[PUSH WORKER] Intent processed: intent_id={}, success={}, failed={}, retrying={}",
+            intent.intent_id, success_count, failed_count, retrying_count
         );
 
         Ok(())
     }
 
     /// ✨ Phase 3.5: 处理单个 Task（设备级 Intent）
-    async fn process_single_task(&self, task: &PushTask) -> Result<()> {
-        // 根据 vendor 选择 Provider
-        let provider: Arc<dyn PushProvider> = match task.vendor {
-            PushVendor::Fcm => {
-                if let Some(ref fcm) = self.fcm_provider {
-                    fcm.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] FCM Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Apns => {
-                if let Some(ref apns) = self.apns_provider {
-                    apns.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] APNs Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Hms => {
-                if let Some(ref hms) = self.hms_provider {
-                    hms.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] HMS Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Xiaomi => {
-                if let Some(ref xiaomi) = self.xiaomi_provider {
-                    xiaomi.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] Xiaomi Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Oppo => {
-                if let Some(ref oppo) = self.oppo_provider {
-                    oppo.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] OPPO Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Vivo => {
-                if let Some(ref vivo) = self.vivo_provider {
-                    vivo.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] Vivo Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
+    async fn process_single_task(&self, task: PushTask) -> Result<()> {
+        let task_id = task.task_id.clone();
+        match self.send_task(task).await {
+            TaskOutcome::Sent => {
+                info!(
+                    "[PUSH WORKER] Device-level task {} sent successfully",
+                    task_id
+                );
+                Ok(())
             }
+            TaskOutcome::RetryScheduled => Ok(()),
+            TaskOutcome::Failed(e) => Err(ServerError::Internal(format!(
+                "device-level task {} failed: {}",
+                task_id, e
+            ))),
+        }
+    }
+
+    /// 根据 vendor 选择 Provider，未配置时降级到 Mock
+    fn select_provider(&self, task: &PushTask) -> Arc<dyn PushProvider> {
+        let provider: Option<Arc<dyn PushProvider>> = match task.vendor {
+            PushVendor::Fcm => as_dyn(&self.fcm_provider),
+            PushVendor::Apns => as_dyn(&self.apns_provider),
+            PushVendor::Hms => as_dyn(&self.hms_provider),
+            // Honor 未单独配置时复用 HMS
             PushVendor::Honor => {
-                if let Some(ref honor) = self.honor_provider {
-                    honor.clone() as Arc<dyn PushProvider>
-                } else if let Some(ref hms) = self.hms_provider {
-                    hms.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] Honor/HMS Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Lenovo => {
-                if let Some(ref lenovo) = self.lenovo_provider {
-                    lenovo.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] Lenovo Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Zte => {
-                if let Some(ref zte) = self.zte_provider {
-                    zte.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] ZTE Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
-            }
-            PushVendor::Meizu => {
-                if let Some(ref meizu) = self.meizu_provider {
-                    meizu.clone() as Arc<dyn PushProvider>
-                } else {
-                    warn!(
-                        "[PUSH WORKER] Meizu Provider not configured, using mock for task {}",
-                        task.task_id
-                    );
-                    self.mock_provider.clone() as Arc<dyn PushProvider>
-                }
+                as_dyn(&self.honor_provider).or_else(|| as_dyn(&self.hms_provider))
             }
+            PushVendor::Xiaomi => as_dyn(&self.xiaomi_provider),
+            PushVendor::Oppo => as_dyn(&self.oppo_provider),
+            PushVendor::Vivo => as_dyn(&self.vivo_provider),
+            PushVendor::Lenovo => as_dyn(&self.lenovo_provider),
+            PushVendor::Zte => as_dyn(&self.zte_provider),
+            PushVendor::Meizu => as_dyn(&self.meizu_provider),
         };
 
-        match provider.send(task).await {
-            Ok(_) => {
-                info!(
-                    "[PUSH WORKER] Device-level task {} sent successfully",
-                    task.task_id
-                );
-                // [TRACE] Node 3: push_sent (device-level)
-                {
-                    use crate::infra::delivery_trace::{global_trace_store, stages};
-                    global_trace_store()
-                        .record(
-                            task.payload.message_id,
-                            stages::PUSH_SENT,
-                            format!("device={}", task.device_id),
-                        )
-                        .await;
-                }
-                Ok(())
+        provider.unwrap_or_else(|| {
+            warn!(
+                "[PUSH WORKER] {:?} Provider not configured, using mock for task {}",
+                task.vendor, task.task_id
+            );
+            self.mock_provider.clone() as Arc<dyn PushProvider>
+        })
+    }
+
+    /// 首次发送；可重试错误转入后台重试，不阻塞队列里后续的 Intent
+    async fn send_task(&self, task: PushTask) -> TaskOutcome {
+        let provider = self.select_provider(&task);
+        let error = match provider.send(&task).await {
+            Ok(()) => {
+                debug!("[PUSH WORKER] Task {} sent successfully", task.task_id);
+                record_sent(&task).await;
+                return TaskOutcome::Sent;
             }
-            Err(e) => {
-                error!(
-                    "[PUSH WORKER] Failed to send device-level task {}: {}",
-                    task.task_id, e
+            Err(e) => e,
+        };
+
+        if let PushError::Transient { retry_after, .. } = &error {
+            if let Some(delay) = self.retry_policy.next_delay(1, *retry_after) {
+                warn!(
+                    "[PUSH WORKER] Task {} failed transiently, retry in {:?}: {}",
+                    task.task_id, delay, error
                 );
-                // [TRACE] Node 4: push_failed (device-level)
-                {
-                    use crate::infra::delivery_trace::{global_trace_store, stages};
-                    global_trace_store()
-                        .record(
-                            task.payload.message_id,
-                            stages::PUSH_FAILED,
-                            format!("device={} err={}", task.device_id, e),
-                        )
-                        .await;
-                }
-                Err(e)
+                record_retry(&task, 1, &error).await;
+                tokio::spawn(retry_task(
+                    provider,
+                    task,
+                    self.retry_policy.clone(),
+                    self.intent_state.clone(),
+                    self.device_repo.clone(),
+                    delay,
+                ));
+                return TaskOutcome::RetryScheduled;
             }
         }
+
+        error!(
+            "[PUSH WORKER] Failed to send task {}: {}",
+            task.task_id, error
+        );
+        record_failed(&task, &error, self.device_repo.as_ref()).await;
+        TaskOutcome::Failed(error)
     }
 
     /// 处理 Mock Task（降级方案）
@@ -604,7 +400,158 @@ impl PushWorker {
             payload: intent.payload,
         };
 
-        self.mock_provider.send(&task).await?;
+        self.mock_provider
+            .send(&task)
+            .await
+            .map_err(|e| ServerError::Internal(e.to_string()))?;
         Ok(())
     }
 }
+
+fn as_dyn<P: PushProvider + 'static>(provider: &Option<Arc<P>>) -> Option<Arc<dyn PushProvider>> {
+    provider.clone().map(|p| p as Arc<dyn PushProvider>)
+}
+
+/// 单个 Task 首次发送的结果
+enum TaskOutcome {
+    Sent,
+    RetryScheduled,
+    Failed(PushError),
+}
+
+/// 后台重试：每次等待后先确认 Intent 没被撤销/取消，再重新发送
+async fn retry_task(
+    provider: Arc<dyn PushProvider>,
+    task: PushTask,
+    policy: PushRetryPolicy,
+    intent_state: Option<Arc<IntentStateManager>>,
+    device_repo: Option<Arc<UserDeviceRepository>>,
+    first_delay: std::time::Duration,
+) {
+    let mut failed_attempts = 1;
+    let mut delay = first_delay;
+
+    loop {
+        tokio::time::sleep(delay).await;
+
+        if let Some(intent_state) = &intent_state {
+            if matches!(
+                intent_state.get_status(&task.intent_id).await,
+                Some(IntentStatus::Revoked) | Some(IntentStatus::Cancelled)
+            ) {
+                info!(
+                    "[PUSH WORKER] Intent {} revoked/cancelled, stop retrying task {}",
+                    task.intent_id, task.task_id
+                );
+                return;
+            }
+        }
+
+        let error = match provider.send(&task).await {
+            Ok(()) => {
+                info!(
+                    "[PUSH WORKER] Task {} sent after {} retries",
+                    task.task_id, failed_attempts
+                );
+                record_sent(&task).await;
+                return;
+            }
+            Err(e) => e,
+        };
+        failed_attempts += 1;
+
+        let next = match &error {
+            PushError::Transient { retry_after, .. } => {
+                policy.next_delay(failed_attempts, *retry_after)
+            }
+            _ => None,
+        };
+        match next {
+            Some(next_delay) => {
+                warn!(
+                    "[PUSH WORKER] Task {} attempt {} failed, retry in {:?}: {}",
+                    task.task_id, failed_attempts, next_delay, error
+                );
+                record_retry(&task, failed_attempts, &error).await;
+                delay = next_delay;
+            }
+            None => {
+                error!(
+                    "[PUSH WORKER] Task {} gave up after {} attempts: {}",
+                    task.task_id, failed_attempts, error
+                );
+                record_failed(&task, &error, device_repo.as_ref()).await;
+                return;
+            }
+        }
+    }
+}
+
+async fn record_sent(task: &PushTask) {
+    // [TRACE] Node 3: push_sent
+    use crate::infra::delivery_trace::{global_trace_store, stages};
+    global_trace_store()
+        .record(
+            task.payload.message_id,
+            stages::PUSH_SENT,
+            format!("device={}", task.device_id),
+        )
+        .await;
+}
+
+async fn record_retry(task: &PushTask, failed_attempts: u32, error: &PushError) {
+    use crate::infra::delivery_trace::{global_trace_store, stages};
+    global_trace_store()
+        .record(
+            task.payload.message_id,
+            stages::PUSH_RETRY,
+            format!(
+                "device={} attempt={} err={}",
+                task.device_id, failed_attempts, error
+            ),
+        )
+        .await;
+}
+
+/// 最终失败：记录 trace；token 失效时停用该设备 token，后续 Intent 不再打到厂商
+async fn record_failed(
+    task: &PushTask,
+    error: &PushError,
+    device_repo: Option<&Arc<UserDeviceRepository>>,
+) {
+    // [TRACE] Node 4: push_failed
+    {
+        use crate::infra::delivery_trace::{global_trace_store, stages};
+        global_trace_store()
+            .record(
+                task.payload.message_id,
+                stages::PUSH_FAILED,
+                format!("device={} err={}", task.device_id, error),
+            )
+            .await;
+    }
+
+    if !matches!(error, PushError::InvalidToken(_)) {
+        return;
+    }
+    let Some(repo) = device_repo else {
+        return;
+    };
+    match repo
+        .invalidate_push_token(task.user_id, &task.device_id, &task.push_token)
+        .await
+    {
+        Ok(true) => warn!(
+            "[PUSH WORKER] Push token invalidated: user_id={}, device_id={}, vendor={:?}",
+            task.user_id, task.device_id, task.vendor
+        ),
+        Ok(false) => debug!(
+            "[PUSH WORKER] Push token already replaced: user_id={}, device_id={}",
+            task.user_id, task.device_id
+        ),
+        Err(e) => error!(
+            "[PUSH WORKER] Failed to invalidate push token: user_id={}, device_id={}, error={}",
+            task.user_id, task.device_id, e
+        ),
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use crate::rpc::qr::{generate_qr_key, is_qr_key_unique_violation, QR_KEY_MAX_GENERATION_RETRIES};
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+
+/// 一条自助注销请求
+#[derive(Debug, Clone)]
+pub struct AccountDeletionRecord {
+    pub user_id: u64,
+    pub requested_at: i64,
+    /// 冷静期结束、开始执行最终注销的时间（毫秒）
+    pub execute_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+/// 一个会话内本批被清空内容的消息
+#[derive(Debug, Clone)]
+pub struct RedactedChannelMessages {
+    pub channel_id: u64,
+    /// DB 表示的频道类型（Direct=0 / Group=1）
+    pub channel_type: i16,
+    pub message_ids: Vec<u64>,
+}
+
+/// 一批清空内容的结果
+#[derive(Debug, Clone, Default)]
+pub struct RedactedBatch {
+    pub channels: Vec<RedactedChannelMessages>,
+    /// 随消息清空而释放引用的附件 file_id（去重）
+    pub released_file_ids: Vec<u64>,
+}
+
+impl RedactedBatch {
+    pub fn message_count(&self) -> usize {
+        self.channels.iter().map(|c| c.message_ids.len()).sum()
+    }
+}
+
+/// 用户自助注销：冷静期记录 + 最终注销时的数据清理
+pub struct AccountDeletionRepository {
+    pool: PgPool,
+}
+
+impl AccountDeletionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get(&self, user_id: u64) -> Result<Option<AccountDeletionRecord>> {
+        let row = sqlx::query(
+            r#"
+            SELECT user_id, requested_at, execute_at, completed_at
+            FROM privchat_account_deletions
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询注销请求失败: {}", e)))?;
+        Ok(row.map(|row| AccountDeletionRecord {
+            user_id: row.get::<i64, _>("user_id") as u64,
+            requested_at: row.get("requested_at"),
+            execute_at: row.get("execute_at"),
+            completed_at: row.get("completed_at"),
+        }))
+    }
+
+    /// 登记注销请求；已有待执行的请求时保持原冷静期不变。返回当前生效的记录。
+    pub async fn schedule(
+        &self,
+        user_id: u64,
+        now_ms: i64,
+        execute_at: i64,
+    ) -> Result<AccountDeletionRecord> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_account_deletions (user_id, requested_at, execute_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(now_ms)
+        .bind(execute_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存注销请求失败: {}", e)))?;
+        self.get(user_id)
+            .await?
+            .ok_or_else(|| ServerError::Internal("注销请求写入后未找到".to_string()))
+    }
+
+    /// 撤销冷静期内的注销请求；已完成的注销不可撤销
+    pub async fn cancel(&self, user_id: u64) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM privchat_account_deletions WHERE user_id = $1 AND completed_at IS NULL",
+        )
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("撤销注销请求失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 认领一批冷静期已结束的注销。
+    ///
+    /// 认领时把 execute_at 推后一个租约期：执行中途崩溃的注销在租约到期后重新可领，
+    /// 多节点同时扫描时以 `SKIP LOCKED` 互不重复。
+    pub async fn claim_due(&self, now_ms: i64, lease_ms: i64, limit: i64) -> Result<Vec<u64>> {
+        let ids: Vec<i64> = sqlx::query_scalar(
+            r#"
+            UPDATE privchat_account_deletions
+            SET execute_at = $1 + $2
+            WHERE user_id IN (
+                SELECT user_id FROM privchat_account_deletions
+                WHERE completed_at IS NULL AND execute_at <= $1
+                ORDER BY execute_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING user_id
+            "#,
+        )
+        .bind(now_ms)
+        .bind(lease_ms)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("认领到期注销失败: {}", e)))?;
+        Ok(ids.into_iter().map(|id| id as u64).collect())
+    }
+
+    pub async fn mark_completed(&self, user_id: u64, now_ms: i64) -> Result<()> {
+        sqlx::query("UPDATE privchat_account_deletions SET completed_at = $2 WHERE user_id = $1")
+            .bind(user_id as i64)
+            .bind(now_ms)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("标记注销完成失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 用户作为群主的群
+    pub async fn list_owned_groups(&self, user_id: u64) -> Result<Vec<u64>> {
+        let ids: Vec<i64> =
+            sqlx::query_scalar("SELECT group_id FROM privchat_groups WHERE owner_id = $1")
+                .bind(user_id as i64)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| ServerError::Database(format!("查询用户拥有的群失败: {}", e)))?;
+        Ok(ids.into_iter().map(|id| id as u64).collect())
+    }
+
+    /// 用户当前所在的群
+    pub async fn list_joined_groups(&self, user_id: u64) -> Result<Vec<u64>> {
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT group_id FROM privchat_group_members WHERE user_id = $1 AND left_at IS NULL",
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询用户所在的群失败: {}", e)))?;
+        Ok(ids.into_iter().map(|id| id as u64).collect())
+    }
+
+    /// 群主注销时的继任者：最早加入的管理员，没有管理员时取最早加入的成员
+    pub async fn pick_group_successor(&self, group_id: u64, owner_id: u64) -> Result<Option<u64>> {
+        let id: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM privchat_group_members
+            WHERE group_id = $1 AND user_id <> $2 AND left_at IS NULL
+            ORDER BY role ASC, joined_at ASC, user_id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(group_id as i64)
+        .bind(owner_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询群主继任者失败: {}", e)))?;
+        Ok(id.map(|id| id as u64))
+    }
+
+    /// 用户上传的全部文件
+    pub async fn list_uploaded_file_ids(&self, user_id: u64) -> Result<Vec<u64>> {
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT file_id FROM privchat_file_uploads WHERE uploader_id = $1 ORDER BY file_id",
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询用户上传文件失败: {}", e)))?;
+        Ok(ids.into_iter().map(|id| id as u64).collect())
+    }
+
+    /// 清空一批（最早的 `limit` 条）该用户发送的消息：内容与 metadata 置空并标记撤回，
+    /// 解除附件引用，commit log 中的原文同步抹掉（legacy content 换成占位、canonical 置空）。
+    pub async fn redact_messages_batch(
+        &self,
+        user_id: u64,
+        now_ms: i64,
+        limit: i64,
+    ) -> Result<RedactedBatch> {
+        let db_err = |e: sqlx::Error| ServerError::Database(format!("清空注销用户消息失败: {}", e));
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        let rows = sqlx::query(
+            r#"
+            WITH target AS (
+                SELECT message_id, created_at
+                FROM privchat_messages
+                WHERE sender_id = $1 AND NOT (revoked AND content = '')
+                ORDER BY created_at
+                LIMIT $3
+            )
+            UPDATE privchat_messages m
+            SET content = '',
+                metadata = '{}'::jsonb,
+                revoked = true,
+                revoked_at = COALESCE(m.revoked_at, $2),
+                revoked_by = COALESCE(m.revoked_by, $1),
+                updated_at = $2
+            FROM target t
+            WHERE m.message_id = t.message_id AND m.created_at = t.created_at
+            RETURNING m.message_id, m.channel_id,
+                (SELECT c.channel_type FROM privchat_channels c
+                 WHERE c.channel_id = m.channel_id) AS channel_type
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(now_ms)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?;
+        if rows.is_empty() {
+            tx.commit().await.map_err(db_err)?;
+            return Ok(RedactedBatch::default());
+        }
+
+        let message_ids: Vec<i64> = rows.iter().map(|r| r.get("message_id")).collect();
+        let mut released_file_ids: Vec<u64> = sqlx::query_scalar::<_, i64>(
+            "DELETE FROM privchat_message_file_refs WHERE message_id = ANY($1) RETURNING file_id",
+        )
+        .bind(&message_ids)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .map(|id| id as u64)
+        .collect();
+        released_file_ids.sort_unstable();
+        released_file_ids.dedup();
+
+        sqlx::query(
+            r#"
+            UPDATE privchat_commit_log
+            SET content = '{"redacted": true}'::jsonb,
+                event_schema_version = NULL,
+                canonical_event = NULL
+            WHERE sender_id = $1 AND server_msg_id = ANY($2)
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(&message_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        tx.commit().await.map_err(db_err)?;
+
+        let mut by_channel: BTreeMap<i64, (i16, Vec<u64>)> = BTreeMap::new();
+        for row in &rows {
+            let entry = by_channel.entry(row.get("channel_id")).or_insert_with(|| {
+                (
+                    row.get::<Option<i16>, _>("channel_type").unwrap_or(0),
+                    Vec::new(),
+                )
+            });
+            entry.1.push(row.get::<i64, _>("message_id") as u64);
+        }
+        Ok(RedactedBatch {
+            channels: by_channel
+                .into_iter()
+                .map(
+                    |(channel_id, (channel_type, message_ids))| RedactedChannelMessages {
+                        channel_id: channel_id as u64,
+                        channel_type,
+                        message_ids,
+                    },
+                )
+                .collect(),
+            released_file_ids,
+        })
+    }
+
+    /// 匿名化用户行并删除其个人数据，单事务完成。
+    ///
+    /// 用户名 / 手机 / 邮箱 / 密码置空（可被重新注册），昵称换成 `display_name`，
+    /// 状态置为已注销，名片二维码轮换；好友关系、黑名单、设置、推送设备、令牌、
+    /// 两步验证、表情收藏与登录日志等随之删除。commit log 中冗余的发送者用户名一并抹掉。
+    pub async fn anonymize_user(
+        &self,
+        user_id: u64,
+        display_name: &str,
+        now_ms: i64,
+    ) -> Result<()> {
+        let db_err = |e: sqlx::Error| ServerError::Database(format!("匿名化注销用户失败: {}", e));
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            sqlx::query("SAVEPOINT anonymize_user")
+                .execute(&mut *tx)
+                .await
+                .map_err(db_err)?;
+            let result = sqlx::query(
+                r#"
+                UPDATE privchat_users
+                SET username = NULL,
+                    phone = NULL,
+                    email = NULL,
+                    password_hash = NULL,
+                    display_name = $2,
+                    avatar_url = NULL,
+                    privacy_settings = '{}'::jsonb,
+                    business_system_id = NULL,
+                    status = 3,
+                    qr_key = $3,
+                    updated_at = $4
+                WHERE user_id = $1
+                "#,
+            )
+            .bind(user_id as i64)
+            .bind(display_name)
+            .bind(generate_qr_key())
+            .bind(now_ms)
+            .execute(&mut *tx)
+            .await;
+            match result {
+                Ok(_) => break,
+                // 极小概率撞上 qr_key UNIQUE：回到保存点换一个再试
+                Err(e)
+                    if is_qr_key_unique_violation(&e)
+                        && attempt < QR_KEY_MAX_GENERATION_RETRIES =>
+                {
+                    sqlx::query("ROLLBACK TO SAVEPOINT anonymize_user")
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(db_err)?;
+                }
+                Err(e) => return Err(db_err(e)),
+            }
+        }
+
+        for sql in [
+            "DELETE FROM privchat_friendships WHERE user_id = $1 OR friend_id = $1",
+            "DELETE FROM privchat_blacklist WHERE user_id = $1 OR blocked_user_id = $1",
+            "DELETE FROM privchat_user_settings WHERE user_id = $1",
+            "DELETE FROM privchat_user_push_settings WHERE user_id = $1",
+            "DELETE FROM privchat_user_devices WHERE user_id = $1",
+            "DELETE FROM privchat_user_last_seen WHERE user_id = $1",
+            "DELETE FROM privchat_user_totp WHERE user_id = $1",
+            "DELETE FROM privchat_user_recovery_codes WHERE user_id = $1",
+            "DELETE FROM privchat_password_reset_tokens WHERE user_id = $1",
+            "DELETE FROM privchat_user_sticker_packages WHERE user_id = $1",
+            "DELETE FROM privchat_user_recent_stickers WHERE user_id = $1",
+            "DELETE FROM privchat_login_logs WHERE user_id = $1",
+            "UPDATE privchat_commit_log SET sender_username = NULL WHERE sender_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(user_id as i64)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_err)?;
+        }
+
+        tx.commit().await.map_err(db_err)?;
+        Ok(())
+    }
+}
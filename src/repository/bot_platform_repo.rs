@@ -0,0 +1,260 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use crate::model::BotCommand;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+/// 计算 Bot 令牌的 SHA-256 hex（lowercase），库里只存摘要
+pub fn hash_bot_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// Bot 令牌（不含摘要，供管理 API 列表展示）
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BotTokenRecord {
+    pub token_id: i64,
+    pub bot_user_id: i64,
+    pub token_prefix: String,
+    pub label: String,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// getUpdates 队列中的一条更新
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BotUpdateRecord {
+    pub update_id: i64,
+    pub update_type: String,
+    pub payload: Value,
+    pub created_at: i64,
+}
+
+/// Bot 平台 Repository（令牌 / 命令 / 更新队列）
+pub struct BotPlatformRepository {
+    pool: PgPool,
+}
+
+impl BotPlatformRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // ───────────────────────── 令牌 ─────────────────────────
+
+    pub async fn create_token(
+        &self,
+        bot_user_id: u64,
+        token_hash: &str,
+        token_prefix: &str,
+        label: &str,
+    ) -> Result<BotTokenRecord> {
+        sqlx::query_as::<_, BotTokenRecord>(
+            r#"
+            INSERT INTO privchat_bot_tokens (bot_user_id, token_hash, token_prefix, label)
+            VALUES ($1, $2, $3, $4)
+            RETURNING token_id, bot_user_id, token_prefix, label, last_used_at, revoked_at, created_at
+            "#,
+        )
+        .bind(bot_user_id as i64)
+        .bind(token_hash)
+        .bind(token_prefix)
+        .bind(label)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入 Bot 令牌失败: {}", e)))
+    }
+
+    pub async fn list_tokens(&self, bot_user_id: u64) -> Result<Vec<BotTokenRecord>> {
+        sqlx::query_as::<_, BotTokenRecord>(
+            r#"
+            SELECT token_id, bot_user_id, token_prefix, label, last_used_at, revoked_at, created_at
+            FROM privchat_bot_tokens
+            WHERE bot_user_id = $1
+            ORDER BY token_id DESC
+            "#,
+        )
+        .bind(bot_user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Bot 令牌失败: {}", e)))
+    }
+
+    /// 令牌摘要 → Bot user_id（已吊销的不返回），顺带刷新 last_used_at
+    pub async fn authenticate(&self, token_hash: &str, now_ms: i64) -> Result<Option<u64>> {
+        let bot_user_id: Option<i64> = sqlx::query_scalar(
+            r#"
+            UPDATE privchat_bot_tokens SET last_used_at = $2
+            WHERE token_hash = $1 AND revoked_at IS NULL
+            RETURNING bot_user_id
+            "#,
+        )
+        .bind(token_hash)
+        .bind(now_ms)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("校验 Bot 令牌失败: {}", e)))?;
+        Ok(bot_user_id.map(|id| id as u64))
+    }
+
+    /// 吊销令牌；不存在或已吊销时返回 false
+    pub async fn revoke_token(&self, bot_user_id: u64, token_id: u64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_bot_tokens SET revoked_at = now_millis()
+            WHERE token_id = $1 AND bot_user_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(token_id as i64)
+        .bind(bot_user_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("吊销 Bot 令牌失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ───────────────────────── 命令 ─────────────────────────
+
+    pub async fn list_commands(&self, bot_user_id: u64) -> Result<Vec<BotCommand>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT command, description FROM privchat_bot_commands
+            WHERE bot_user_id = $1
+            ORDER BY sort_order, command
+            "#,
+        )
+        .bind(bot_user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Bot 命令失败: {}", e)))?;
+        Ok(rows
+            .into_iter()
+            .map(|(command, description)| BotCommand {
+                command,
+                description,
+            })
+            .collect())
+    }
+
+    /// 整体替换命令列表（按传入顺序排序）
+    pub async fn replace_commands(&self, bot_user_id: u64, commands: &[BotCommand]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        sqlx::query("DELETE FROM privchat_bot_commands WHERE bot_user_id = $1")
+            .bind(bot_user_id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("清空 Bot 命令失败: {}", e)))?;
+
+        for (index, command) in commands.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO privchat_bot_commands (bot_user_id, command, description, sort_order)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(bot_user_id as i64)
+            .bind(&command.command)
+            .bind(&command.description)
+            .bind(index as i32)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("写入 Bot 命令失败: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))
+    }
+
+    // ───────────────────────── 更新队列 ─────────────────────────
+
+    pub async fn push_update(
+        &self,
+        bot_user_id: u64,
+        update_type: &str,
+        payload: &Value,
+    ) -> Result<i64> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO privchat_bot_updates (bot_user_id, update_type, payload)
+            VALUES ($1, $2, $3)
+            RETURNING update_id
+            "#,
+        )
+        .bind(bot_user_id as i64)
+        .bind(update_type)
+        .bind(payload)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入 Bot 更新失败: {}", e)))
+    }
+
+    /// 确认 `offset` 之前的更新（删除 update_id < offset）
+    pub async fn ack_updates(&self, bot_user_id: u64, offset: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM privchat_bot_updates WHERE bot_user_id = $1 AND update_id < $2",
+        )
+        .bind(bot_user_id as i64)
+        .bind(offset)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("确认 Bot 更新失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 取 update_id >= offset 的更新（升序）
+    pub async fn fetch_updates(
+        &self,
+        bot_user_id: u64,
+        offset: i64,
+        limit: u32,
+    ) -> Result<Vec<BotUpdateRecord>> {
+        sqlx::query_as::<_, BotUpdateRecord>(
+            r#"
+            SELECT update_id, update_type, payload, created_at FROM privchat_bot_updates
+            WHERE bot_user_id = $1 AND update_id >= $2
+            ORDER BY update_id
+            LIMIT $3
+            "#,
+        )
+        .bind(bot_user_id as i64)
+        .bind(offset)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Bot 更新失败: {}", e)))
+    }
+
+    /// 清理早于 `before_ms` 的未确认更新
+    pub async fn purge_updates(&self, before_ms: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM privchat_bot_updates WHERE created_at < $1")
+            .bind(before_ms)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("清理 Bot 更新失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+}
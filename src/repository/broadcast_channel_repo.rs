@@ -0,0 +1,662 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 广播频道仓库 —— 频道资料、订阅关系、频道消息属性与订阅者已读/忽略状态。
+//! 表结构见 `migrations/031_broadcast_channels.sql`。
+//!
+//! 订阅关系就是 `privchat_channel_participants` 的行（role 0/1/2 = 主/管理员/订阅者），
+//! dispatch 快照直接读它，这里只负责与 `subscriber_count` 同事务维护。
+
+use crate::error::{Result, ServerError};
+use crate::model::channel::MemberRole;
+use crate::model::message::{ChannelMessage, ChannelMessageStatus};
+use chrono::{DateTime, Utc};
+use privchat_protocol::ContentMessageType;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// 发布时随消息同一事务写入的广播属性。message_id、频道、发布者与发布时间取自消息本身。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BroadcastMessageAttrs {
+    pub title: Option<String>,
+    pub is_urgent: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// 在消息事务内写入广播属性（见 `PgMessageRepository::create_message_and_commit_atomic`）。
+pub(crate) async fn insert_message_attrs_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    message: &crate::model::message::Message,
+    attrs: &BroadcastMessageAttrs,
+) -> std::result::Result<(), sqlx::Error> {
+    let published_at = message.created_at.timestamp_millis();
+    sqlx::query(INSERT_MESSAGE_ATTRS_SQL)
+        .bind(message.message_id as i64)
+        .bind(message.channel_id as i64)
+        .bind(Some(message.sender_id as i64))
+        .bind(attrs.title.as_deref())
+        .bind(attrs.is_urgent)
+        .bind(attrs.expires_at.map(|t| t.timestamp_millis()))
+        .bind(published_at)
+        .bind(published_at)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// 幂等：同一 message_id 重放不覆盖。
+const INSERT_MESSAGE_ATTRS_SQL: &str = r#"
+    INSERT INTO privchat_channel_messages
+        (message_id, channel_id, publisher_id, title, is_urgent,
+         expires_at, published_at, updated_at)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    ON CONFLICT (message_id) DO NOTHING
+"#;
+
+/// 广播频道资料（`privchat_broadcast_channels` 一行）。
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct BroadcastChannelRecord {
+    pub channel_id: i64,
+    pub owner_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub avatar_url: Option<String>,
+    pub subscriber_count: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 频道内容流的一项：频道消息 + 时间线 pts + 当前查看者的已读/忽略状态。
+#[derive(Debug, Clone)]
+pub struct BroadcastFeedItem {
+    pub message: ChannelMessage,
+    pub pts: u64,
+    /// None = 查看者从未读过/忽略过这条。
+    pub status: Option<ChannelMessageStatus>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FeedRow {
+    message_id: i64,
+    channel_id: i64,
+    pts: i64,
+    content: String,
+    message_type: i16,
+    metadata: Option<Value>,
+    publisher_id: Option<i64>,
+    title: Option<String>,
+    is_urgent: Option<bool>,
+    expires_at: Option<i64>,
+    published_at: i64,
+    updated_at: i64,
+    status_device_id: Option<String>,
+    is_read: Option<bool>,
+    is_dismissed: Option<bool>,
+    read_at: Option<i64>,
+    dismissed_at: Option<i64>,
+}
+
+/// `requested` 中不在 `found` 里的 id（`requested` 已排序去重）
+fn missing_message_ids(requested: &[i64], found: &[i64]) -> Vec<i64> {
+    requested
+        .iter()
+        .copied()
+        .filter(|id| !found.contains(id))
+        .collect()
+}
+
+fn millis_to_datetime(ms: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(ms).unwrap_or_else(Utc::now)
+}
+
+impl FeedRow {
+    fn into_item(self, viewer_id: u64) -> BroadcastFeedItem {
+        let message_id = self.message_id as u64;
+        let status = self.status_device_id.map(|device_id| ChannelMessageStatus {
+            message_id,
+            user_id: viewer_id,
+            device_id,
+            is_read: self.is_read.unwrap_or(false),
+            is_dismissed: self.is_dismissed.unwrap_or(false),
+            read_at: self.read_at.map(millis_to_datetime),
+            dismissed_at: self.dismissed_at.map(millis_to_datetime),
+        });
+        BroadcastFeedItem {
+            message: ChannelMessage {
+                message_id,
+                channel_id: self.channel_id as u64,
+                publisher_id: self.publisher_id.map(|id| id as u64),
+                title: self.title,
+                content: self.content,
+                message_type: ContentMessageType::from_u32(self.message_type as u32)
+                    .unwrap_or(ContentMessageType::Text),
+                metadata: self
+                    .metadata
+                    .unwrap_or_else(|| Value::Object(serde_json::Map::new())),
+                is_urgent: self.is_urgent.unwrap_or(false),
+                expires_at: self.expires_at.map(millis_to_datetime),
+                published_at: millis_to_datetime(self.published_at),
+                updated_at: millis_to_datetime(self.updated_at),
+            },
+            pts: self.pts.max(0) as u64,
+            status,
+        }
+    }
+}
+
+/// 广播频道仓库。
+#[derive(Clone)]
+pub struct BroadcastChannelRepository {
+    pool: Arc<PgPool>,
+}
+
+impl BroadcastChannelRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// 建频道：`privchat_channels`（type 2）+ 频道资料 + 频道主参与者行，同一事务。
+    ///
+    /// channel_id 与私聊/群共用 `privchat_channels_channel_id_seq`（见 009）。
+    pub async fn create_channel(
+        &self,
+        owner_id: u64,
+        name: &str,
+        description: Option<&str>,
+        avatar_url: Option<&str>,
+    ) -> Result<BroadcastChannelRecord> {
+        let now = Utc::now().timestamp_millis();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启建频道事务失败: {}", e)))?;
+
+        let channel_id = sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO privchat_channels (channel_type, created_at, updated_at)
+            VALUES (2, $1, $1)
+            RETURNING channel_id
+            "#,
+        )
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("创建广播频道失败: {}", e)))?;
+
+        let record = sqlx::query_as::<_, BroadcastChannelRecord>(
+            r#"
+            INSERT INTO privchat_broadcast_channels
+                (channel_id, owner_id, name, description, avatar_url,
+                 subscriber_count, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, 0, $6, $6)
+            RETURNING channel_id, owner_id, name, description, avatar_url,
+                      subscriber_count, created_at, updated_at
+            "#,
+        )
+        .bind(channel_id)
+        .bind(owner_id as i64)
+        .bind(name)
+        .bind(description)
+        .bind(avatar_url)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入广播频道资料失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_participants (channel_id, user_id, role, joined_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(channel_id)
+        .bind(owner_id as i64)
+        .bind(MemberRole::Owner as i16)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入频道主失败: {}", e)))?;
+
+        Self::ensure_user_channel_row_in_tx(&mut tx, channel_id, owner_id as i64, now).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交建频道事务失败: {}", e)))?;
+        Ok(record)
+    }
+
+    pub async fn find_channel(&self, channel_id: u64) -> Result<Option<BroadcastChannelRecord>> {
+        sqlx::query_as::<_, BroadcastChannelRecord>(
+            r#"
+            SELECT channel_id, owner_id, name, description, avatar_url,
+                   subscriber_count, created_at, updated_at
+            FROM privchat_broadcast_channels
+            WHERE channel_id = $1
+            "#,
+        )
+        .bind(channel_id as i64)
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询广播频道失败: {}", e)))
+    }
+
+    /// 用户在频道里的角色；None = 未订阅（或已退订）。
+    pub async fn member_role(&self, channel_id: u64, user_id: u64) -> Result<Option<MemberRole>> {
+        let role = sqlx::query_scalar::<_, Option<i16>>(
+            r#"
+            SELECT role
+            FROM privchat_channel_participants
+            WHERE channel_id = $1 AND user_id = $2 AND left_at IS NULL
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(user_id as i64)
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询频道成员角色失败: {}", e)))?;
+        Ok(role.map(|r| MemberRole::from_i16(r.unwrap_or(MemberRole::Member as i16))))
+    }
+
+    /// 订阅。返回 `true` = 本次新订阅；`false` = 已在频道内（含主/管理员），未改任何状态。
+    pub async fn subscribe(&self, channel_id: u64, user_id: u64) -> Result<bool> {
+        let now = Utc::now().timestamp_millis();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启订阅事务失败: {}", e)))?;
+        Self::lock_channel_in_tx(&mut tx, channel_id).await?;
+
+        // 只复活已退订的行；在籍成员（包括主/管理员）冲突时不动，角色不会被降成订阅者。
+        let joined = sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_participants (channel_id, user_id, role, joined_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (channel_id, user_id) DO UPDATE SET
+                role = EXCLUDED.role,
+                joined_at = EXCLUDED.joined_at,
+                left_at = NULL
+            WHERE privchat_channel_participants.left_at IS NOT NULL
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(user_id as i64)
+        .bind(MemberRole::Member as i16)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入订阅关系失败: {}", e)))?;
+
+        if joined.rows_affected() == 0 {
+            tx.rollback().await.ok();
+            return Ok(false);
+        }
+
+        Self::ensure_user_channel_row_in_tx(&mut tx, channel_id as i64, user_id as i64, now)
+            .await?;
+        Self::adjust_subscriber_count_in_tx(&mut tx, channel_id, 1, now).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交订阅事务失败: {}", e)))?;
+        Ok(true)
+    }
+
+    /// 退订。只作用于订阅者（role 2）；返回 `false` = 本来就不是订阅者。
+    pub async fn unsubscribe(&self, channel_id: u64, user_id: u64) -> Result<bool> {
+        let now = Utc::now().timestamp_millis();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启退订事务失败: {}", e)))?;
+        Self::lock_channel_in_tx(&mut tx, channel_id).await?;
+
+        let left = sqlx::query(
+            r#"
+            UPDATE privchat_channel_participants
+            SET left_at = $3
+            WHERE channel_id = $1 AND user_id = $2 AND left_at IS NULL AND role = $4
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(user_id as i64)
+        .bind(now)
+        .bind(MemberRole::Member as i16)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入退订失败: {}", e)))?;
+
+        if left.rows_affected() == 0 {
+            tx.rollback().await.ok();
+            return Ok(false);
+        }
+
+        Self::adjust_subscriber_count_in_tx(&mut tx, channel_id, -1, now).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交退订事务失败: {}", e)))?;
+        Ok(true)
+    }
+
+    /// 设置/撤销发布管理员。频道主行不受影响；目标必须已在频道内。
+    ///
+    /// 订阅者 ⇄ 管理员会改变 subscriber_count（计数只算 role 2）。
+    pub async fn set_admin(&self, channel_id: u64, user_id: u64, is_admin: bool) -> Result<bool> {
+        let now = Utc::now().timestamp_millis();
+        let (from_role, to_role, delta) = if is_admin {
+            (MemberRole::Member, MemberRole::Admin, -1)
+        } else {
+            (MemberRole::Admin, MemberRole::Member, 1)
+        };
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启设置管理员事务失败: {}", e)))?;
+        Self::lock_channel_in_tx(&mut tx, channel_id).await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE privchat_channel_participants
+            SET role = $3
+            WHERE channel_id = $1 AND user_id = $2 AND left_at IS NULL AND role = $4
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(user_id as i64)
+        .bind(to_role as i16)
+        .bind(from_role as i16)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新频道管理员失败: {}", e)))?;
+
+        if updated.rows_affected() == 0 {
+            tx.rollback().await.ok();
+            return Ok(false);
+        }
+
+        Self::adjust_subscriber_count_in_tx(&mut tx, channel_id, delta, now).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交设置管理员事务失败: {}", e)))?;
+        Ok(true)
+    }
+
+    /// 当前订阅者 user_id（不含主/管理员），发布时用于未读计数。
+    pub async fn list_subscriber_ids(&self, channel_id: u64) -> Result<Vec<u64>> {
+        let rows = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT user_id
+            FROM privchat_channel_participants
+            WHERE channel_id = $1 AND left_at IS NULL AND role = $2
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(MemberRole::Member as i16)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询频道订阅者失败: {}", e)))?;
+        Ok(rows.into_iter().map(|id| id as u64).collect())
+    }
+
+    /// 写入频道消息的广播属性。幂等：同一 message_id 重放不覆盖。
+    pub async fn insert_message_attrs(&self, message: &ChannelMessage) -> Result<()> {
+        sqlx::query(INSERT_MESSAGE_ATTRS_SQL)
+            .bind(message.message_id as i64)
+            .bind(message.channel_id as i64)
+            .bind(message.publisher_id.map(|id| id as i64))
+            .bind(message.title.as_deref())
+            .bind(message.is_urgent)
+            .bind(message.expires_at.map(|t| t.timestamp_millis()))
+            .bind(message.published_at.timestamp_millis())
+            .bind(message.updated_at.timestamp_millis())
+            .execute(self.pool.as_ref())
+            .await
+            .map_err(|e| ServerError::Database(format!("写入频道消息属性失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 内容流分页（按 message_id 倒序，`before_message_id` 为游标）。
+    ///
+    /// 以 `privchat_messages` 为主表、LEFT JOIN 广播属性：即便属性行因进程崩溃没写成，
+    /// 已进入时间线的消息也不会从列表里消失。已撤回/已删除/已过期的不返回。
+    pub async fn list_messages(
+        &self,
+        channel_id: u64,
+        viewer_id: u64,
+        before_message_id: Option<u64>,
+        limit: u32,
+        include_dismissed: bool,
+    ) -> Result<Vec<BroadcastFeedItem>> {
+        let now = Utc::now().timestamp_millis();
+        let rows = sqlx::query_as::<_, FeedRow>(
+            r#"
+            SELECT
+                m.message_id, m.channel_id, m.pts, m.content, m.message_type, m.metadata,
+                COALESCE(cm.publisher_id, m.sender_id) AS publisher_id,
+                cm.title, cm.is_urgent, cm.expires_at,
+                COALESCE(cm.published_at, m.created_at) AS published_at,
+                COALESCE(cm.updated_at, m.updated_at) AS updated_at,
+                s.device_id AS status_device_id, s.is_read, s.is_dismissed,
+                s.read_at, s.dismissed_at
+            FROM privchat_messages m
+            LEFT JOIN privchat_channel_messages cm ON cm.message_id = m.message_id
+            LEFT JOIN privchat_channel_message_status s
+                   ON s.message_id = m.message_id AND s.user_id = $2
+            WHERE m.channel_id = $1
+              AND m.deleted = false
+              AND m.revoked = false
+              AND (cm.expires_at IS NULL OR cm.expires_at > $3)
+              AND ($4::BIGINT IS NULL OR m.message_id < $4)
+              AND ($5 OR COALESCE(s.is_dismissed, false) = false)
+            ORDER BY m.message_id DESC
+            LIMIT $6
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(viewer_id as i64)
+        .bind(now)
+        .bind(before_message_id.map(|id| id as i64))
+        .bind(include_dismissed)
+        .bind(limit as i64)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询频道内容失败: {}", e)))?;
+
+        Ok(rows.into_iter().map(|r| r.into_item(viewer_id)).collect())
+    }
+
+    /// 批量标记已读。任一消息不属于该频道时返回 `NotFound`，否则返回写入条数。
+    pub async fn mark_read(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        message_ids: &[u64],
+    ) -> Result<u64> {
+        self.upsert_status(channel_id, user_id, device_id, message_ids, false)
+            .await
+    }
+
+    /// 批量标记忽略（隐含已读）。
+    pub async fn mark_dismissed(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        message_ids: &[u64],
+    ) -> Result<u64> {
+        self.upsert_status(channel_id, user_id, device_id, message_ids, true)
+            .await
+    }
+
+    /// 写入已读 / 忽略状态。任一 message_id 在本频道没有广播属性行时整批不写，
+    /// 返回 `NotFound`——静默跳过会让客户端以为标记成功，换台设备又冒出来。
+    async fn upsert_status(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        message_ids: &[u64],
+        dismiss: bool,
+    ) -> Result<u64> {
+        if message_ids.is_empty() {
+            return Ok(0);
+        }
+        let now = Utc::now().timestamp_millis();
+        let mut ids: Vec<i64> = message_ids.iter().map(|id| *id as i64).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+        let found: Vec<i64> = sqlx::query_scalar(
+            r#"
+            SELECT message_id FROM privchat_channel_messages
+            WHERE message_id = ANY($1) AND channel_id = $2
+            FOR SHARE
+            "#,
+        )
+        .bind(&ids)
+        .bind(channel_id as i64)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询频道消息属性失败: {}", e)))?;
+        let missing = missing_message_ids(&ids, &found);
+        if !missing.is_empty() {
+            return Err(ServerError::NotFound(format!(
+                "频道 {} 中不存在消息: {:?}",
+                channel_id, missing
+            )));
+        }
+
+        // read_at / dismissed_at 只记第一次，重复标记不刷新时间。
+        let result = sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_message_status
+                (message_id, user_id, device_id, is_read, is_dismissed, read_at, dismissed_at)
+            SELECT id, $2, $3, true, $4, $5, CASE WHEN $4 THEN $5 ELSE NULL END
+            FROM UNNEST($1::BIGINT[]) AS id
+            ON CONFLICT (message_id, user_id) DO UPDATE SET
+                device_id = EXCLUDED.device_id,
+                is_read = true,
+                read_at = COALESCE(privchat_channel_message_status.read_at, EXCLUDED.read_at),
+                is_dismissed = privchat_channel_message_status.is_dismissed OR EXCLUDED.is_dismissed,
+                dismissed_at = COALESCE(privchat_channel_message_status.dismissed_at, EXCLUDED.dismissed_at)
+            "#,
+        )
+        .bind(&ids)
+        .bind(user_id as i64)
+        .bind(device_id)
+        .bind(dismiss)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入频道消息状态失败: {}", e)))?;
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交频道消息状态失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 锁住频道资料行，串行化同一频道的订阅变更与计数维护。
+    async fn lock_channel_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        channel_id: u64,
+    ) -> Result<()> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT channel_id FROM privchat_broadcast_channels WHERE channel_id = $1 FOR UPDATE",
+        )
+        .bind(channel_id as i64)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("锁定广播频道失败: {}", e)))?
+        .ok_or_else(|| ServerError::ChannelNotFound(format!("广播频道不存在: {}", channel_id)))?;
+        Ok(())
+    }
+
+    async fn adjust_subscriber_count_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        channel_id: u64,
+        delta: i64,
+        now: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE privchat_broadcast_channels
+            SET subscriber_count = GREATEST(subscriber_count + $2, 0),
+                updated_at = $3
+            WHERE channel_id = $1
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(delta)
+        .bind(now)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新订阅数失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 会话列表行：订阅后频道出现在用户的会话列表里（entity 同步读这张表）。
+    async fn ensure_user_channel_row_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        channel_id: i64,
+        user_id: i64,
+        now: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_user_channels
+                (user_id, channel_id, unread_count, is_pinned, is_muted, updated_at)
+            VALUES ($1, $2, 0, false, false, $3)
+            ON CONFLICT (user_id, channel_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(channel_id)
+        .bind(now)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入会话列表行失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::missing_message_ids;
+
+    #[test]
+    fn status_writes_report_ids_without_attrs() {
+        assert!(missing_message_ids(&[1, 2, 3], &[3, 1, 2]).is_empty());
+        assert_eq!(missing_message_ids(&[1, 2, 3], &[2]), vec![1, 3]);
+        assert_eq!(missing_message_ids(&[7], &[]), vec![7]);
+    }
+}
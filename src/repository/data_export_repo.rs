@@ -0,0 +1,392 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use serde_json::Value;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+pub const EXPORT_STATUS_PENDING: &str = "pending";
+pub const EXPORT_STATUS_RUNNING: &str = "running";
+pub const EXPORT_STATUS_READY: &str = "ready";
+pub const EXPORT_STATUS_FAILED: &str = "failed";
+pub const EXPORT_STATUS_EXPIRED: &str = "expired";
+
+const EXPORT_COLUMNS: &str = "export_id, user_id, status, storage_source_id, object_path, \
+     size_bytes, error, created_at, started_at, completed_at, expires_at";
+
+/// 一条个人数据导出任务
+#[derive(Debug, Clone)]
+pub struct DataExportRecord {
+    pub export_id: u64,
+    pub user_id: u64,
+    pub status: String,
+    pub storage_source_id: Option<u32>,
+    pub object_path: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub expires_at: Option<i64>,
+}
+
+impl DataExportRecord {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            export_id: row.get::<i64, _>("export_id") as u64,
+            user_id: row.get::<i64, _>("user_id") as u64,
+            status: row.get("status"),
+            storage_source_id: row
+                .get::<Option<i32>, _>("storage_source_id")
+                .map(|id| id as u32),
+            object_path: row.get("object_path"),
+            size_bytes: row.get("size_bytes"),
+            error: row.get("error"),
+            created_at: row.get("created_at"),
+            started_at: row.get("started_at"),
+            completed_at: row.get("completed_at"),
+            expires_at: row.get("expires_at"),
+        }
+    }
+}
+
+/// 个人数据导出：任务记录 + 导出内容查询
+pub struct DataExportRepository {
+    pool: PgPool,
+}
+
+impl DataExportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, user_id: u64) -> Result<DataExportRecord> {
+        let row = sqlx::query(&format!(
+            "INSERT INTO privchat_data_exports (user_id) VALUES ($1) RETURNING {}",
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("创建导出任务失败: {}", e)))?;
+        Ok(DataExportRecord::from_row(&row))
+    }
+
+    pub async fn get(&self, export_id: u64) -> Result<Option<DataExportRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM privchat_data_exports WHERE export_id = $1",
+            EXPORT_COLUMNS
+        ))
+        .bind(export_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询导出任务失败: {}", e)))?;
+        Ok(row.as_ref().map(DataExportRecord::from_row))
+    }
+
+    /// 用户尚未完成（排队中 / 生成中）的导出任务
+    pub async fn find_active(&self, user_id: u64) -> Result<Option<DataExportRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM privchat_data_exports \
+             WHERE user_id = $1 AND status IN ('pending', 'running') \
+             ORDER BY export_id DESC LIMIT 1",
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询导出任务失败: {}", e)))?;
+        Ok(row.as_ref().map(DataExportRecord::from_row))
+    }
+
+    pub async fn list_by_user(&self, user_id: u64, limit: i64) -> Result<Vec<DataExportRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM privchat_data_exports WHERE user_id = $1 \
+             ORDER BY export_id DESC LIMIT $2",
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询导出任务失败: {}", e)))?;
+        Ok(rows.iter().map(DataExportRecord::from_row).collect())
+    }
+
+    /// 认领一个待处理任务（或租约已过期的生成中任务）
+    pub async fn claim_next(&self, now_ms: i64, lease_ms: i64) -> Result<Option<DataExportRecord>> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE privchat_data_exports
+            SET status = 'running', started_at = $1
+            WHERE export_id = (
+                SELECT export_id FROM privchat_data_exports
+                WHERE status = 'pending'
+                   OR (status = 'running' AND started_at < $1 - $2)
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(now_ms)
+        .bind(lease_ms)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("认领导出任务失败: {}", e)))?;
+        Ok(row.as_ref().map(DataExportRecord::from_row))
+    }
+
+    pub async fn mark_ready(
+        &self,
+        export_id: u64,
+        storage_source_id: u32,
+        object_path: &str,
+        size_bytes: u64,
+        now_ms: i64,
+        expires_at: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE privchat_data_exports
+            SET status = 'ready', storage_source_id = $2, object_path = $3,
+                size_bytes = $4, error = NULL, completed_at = $5, expires_at = $6
+            WHERE export_id = $1
+            "#,
+        )
+        .bind(export_id as i64)
+        .bind(storage_source_id as i32)
+        .bind(object_path)
+        .bind(size_bytes as i64)
+        .bind(now_ms)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新导出任务失败: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn mark_failed(&self, export_id: u64, error: &str, now_ms: i64) -> Result<()> {
+        sqlx::query(
+            "UPDATE privchat_data_exports SET status = 'failed', error = $2, completed_at = $3 \
+             WHERE export_id = $1",
+        )
+        .bind(export_id as i64)
+        .bind(error)
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新导出任务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 把已过期的归档标记为 expired 并返回，调用方据此删除物理对象
+    pub async fn take_expired(&self, now_ms: i64, limit: i64) -> Result<Vec<DataExportRecord>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            UPDATE privchat_data_exports
+            SET status = 'expired'
+            WHERE export_id IN (
+                SELECT export_id FROM privchat_data_exports
+                WHERE status = 'ready' AND expires_at <= $1
+                ORDER BY expires_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(now_ms)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清理过期导出失败: {}", e)))?;
+        Ok(rows.iter().map(DataExportRecord::from_row).collect())
+    }
+
+    /// 注销时作废用户的全部导出：未完成的标记失败，已生成的标记过期并返回以删除归档
+    pub async fn expire_all_for_user(&self, user_id: u64) -> Result<Vec<DataExportRecord>> {
+        sqlx::query(
+            "UPDATE privchat_data_exports SET status = 'failed', error = 'account deleted' \
+             WHERE user_id = $1 AND status IN ('pending', 'running')",
+        )
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("作废导出任务失败: {}", e)))?;
+        let rows = sqlx::query(&format!(
+            "UPDATE privchat_data_exports SET status = 'expired' \
+             WHERE user_id = $1 AND status = 'ready' RETURNING {}",
+            EXPORT_COLUMNS
+        ))
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("作废导出任务失败: {}", e)))?;
+        Ok(rows.iter().map(DataExportRecord::from_row).collect())
+    }
+
+    // ───────────────────────── 导出内容 ─────────────────────────
+
+    /// 用户资料（不含密码哈希）
+    pub async fn load_profile(&self, user_id: u64) -> Result<Value> {
+        self.fetch_json(
+            "SELECT to_jsonb(u) - 'password_hash' FROM privchat_users u WHERE user_id = $1",
+            user_id,
+        )
+        .await
+    }
+
+    /// 用户设置与推送偏好
+    pub async fn load_settings(&self, user_id: u64) -> Result<Value> {
+        self.fetch_json(
+            r#"
+            SELECT jsonb_build_object(
+                'settings', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(s) ORDER BY s.setting_key)
+                    FROM privchat_user_settings s WHERE s.user_id = $1), '[]'::jsonb),
+                'push', (SELECT to_jsonb(p) FROM privchat_user_push_settings p WHERE p.user_id = $1)
+            )
+            "#,
+            user_id,
+        )
+        .await
+    }
+
+    /// 好友关系与黑名单
+    pub async fn load_contacts(&self, user_id: u64) -> Result<Value> {
+        self.fetch_json(
+            r#"
+            SELECT jsonb_build_object(
+                'friends', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(f) ORDER BY f.created_at)
+                    FROM privchat_friendships f WHERE f.user_id = $1), '[]'::jsonb),
+                'blacklist', COALESCE((
+                    SELECT jsonb_agg(to_jsonb(b) ORDER BY b.created_at)
+                    FROM privchat_blacklist b WHERE b.user_id = $1), '[]'::jsonb)
+            )
+            "#,
+            user_id,
+        )
+        .await
+    }
+
+    /// 群成员身份（含已退出的群）
+    pub async fn load_group_memberships(&self, user_id: u64) -> Result<Value> {
+        self.fetch_json(
+            r#"
+            SELECT COALESCE(jsonb_agg(
+                to_jsonb(m) || jsonb_build_object('group_name', g.name, 'group_owner_id', g.owner_id)
+                ORDER BY m.joined_at), '[]'::jsonb)
+            FROM privchat_group_members m
+            LEFT JOIN privchat_groups g ON g.group_id = m.group_id
+            WHERE m.user_id = $1
+            "#,
+            user_id,
+        )
+        .await
+    }
+
+    pub async fn load_login_logs(&self, user_id: u64) -> Result<Value> {
+        self.fetch_json(
+            r#"
+            SELECT COALESCE(jsonb_agg(to_jsonb(l) ORDER BY l.created_at), '[]'::jsonb)
+            FROM privchat_login_logs l WHERE l.user_id = $1
+            "#,
+            user_id,
+        )
+        .await
+    }
+
+    /// 用户上传的文件元数据（不含存储路径）
+    pub async fn load_uploaded_files(&self, user_id: u64) -> Result<Vec<Value>> {
+        let rows: Vec<Value> = sqlx::query_scalar(
+            r#"
+            SELECT to_jsonb(f) - 'file_path' - 'storage_source_id'
+            FROM privchat_file_uploads f
+            WHERE f.uploader_id = $1
+            ORDER BY f.file_id
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询导出文件失败: {}", e)))?;
+        Ok(rows)
+    }
+
+    /// 按 (created_at, message_id) 键集翻页读取用户自己发送的消息
+    pub async fn load_messages_page(
+        &self,
+        user_id: u64,
+        after: Option<(i64, i64)>,
+        limit: i64,
+    ) -> Result<Vec<(i64, i64, Value)>> {
+        let (after_created_at, after_message_id) = after.unwrap_or((i64::MIN, i64::MIN));
+        let rows = sqlx::query(
+            r#"
+            SELECT m.created_at, m.message_id,
+                   jsonb_build_object(
+                       'message_id', m.message_id,
+                       'channel_id', m.channel_id,
+                       'message_type', m.message_type,
+                       'content', m.content,
+                       'metadata', m.metadata,
+                       'reply_to_message_id', m.reply_to_message_id,
+                       'created_at', m.created_at,
+                       'revoked', m.revoked
+                   ) AS message
+            FROM privchat_messages m
+            WHERE m.sender_id = $1
+              AND NOT COALESCE(m.deleted, false)
+              AND (m.created_at, m.message_id) > ($2, $3)
+            ORDER BY m.created_at, m.message_id
+            LIMIT $4
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(after_created_at)
+        .bind(after_message_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询导出消息失败: {}", e)))?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get("created_at"),
+                    row.get("message_id"),
+                    row.get("message"),
+                )
+            })
+            .collect())
+    }
+
+    async fn fetch_json(&self, sql: &str, user_id: u64) -> Result<Value> {
+        let value: Option<Option<Value>> = sqlx::query_scalar(sql)
+            .bind(user_id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询导出数据失败: {}", e)))?;
+        Ok(value.flatten().unwrap_or(Value::Null))
+    }
+}
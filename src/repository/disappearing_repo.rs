@@ -0,0 +1,371 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use crate::model::channel::ChannelType;
+use crate::repository::retention_repo::delete_message_dependents;
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+
+/// 会话默认消失计时器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisappearingTimer {
+    pub ttl_secs: i64,
+    /// true = 接收方首次已读后才开始倒计时
+    pub start_on_read: bool,
+}
+
+/// 待写入的消息消失计时
+#[derive(Debug, Clone)]
+pub struct NewMessageExpiry {
+    pub message_id: u64,
+    /// 消息 created_at（毫秒，消息表分区键）
+    pub message_created_at: i64,
+    pub channel_id: u64,
+    pub pts: u64,
+    pub sender_id: u64,
+    pub timer: DisappearingTimer,
+}
+
+/// 新消息的消失计时登记方式。由发送路径决定，在消息落库的**同一事务**里写入
+/// （见 `PgMessageRepository::create_message_and_commit_atomic`）——事务外登记一旦
+/// 失败，消息就成了一条永不消失的「阅后即焚」消息。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageExpiryArm {
+    /// 不登记（Room 不支持阅后即焚）
+    #[default]
+    Skip,
+    /// 单条消息显式计时（metadata.self_destruct）
+    Explicit(DisappearingTimer),
+    /// 按会话默认计时；会话未设置时不登记
+    ChannelDefault,
+}
+
+impl MessageExpiryArm {
+    /// 按频道类型（线上编码）与单条计时决定登记方式
+    pub fn for_new_message(channel_type: u8, explicit: Option<DisappearingTimer>) -> Self {
+        match explicit {
+            _ if channel_type == ChannelType::Room.to_wire_u8() => Self::Skip,
+            Some(timer) => Self::Explicit(timer),
+            None => Self::ChannelDefault,
+        }
+    }
+}
+
+/// 在新消息的事务内登记消失计时。会话默认计时在同一条语句里读取，
+/// 与消息看到的是同一个快照。
+pub(crate) async fn arm_new_message_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    arm: MessageExpiryArm,
+    message: &crate::model::message::Message,
+    now_ms: i64,
+) -> std::result::Result<(), sqlx::Error> {
+    let explicit = match arm {
+        MessageExpiryArm::Skip => return Ok(()),
+        MessageExpiryArm::Explicit(timer) => Some(timer),
+        MessageExpiryArm::ChannelDefault => None,
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO privchat_message_expiry
+            (message_id, message_created_at, channel_id, pts, sender_id,
+             ttl_secs, start_on_read, expires_at)
+        SELECT $1, $2, $3, $4, $5, t.ttl_secs, t.start_on_read,
+            CASE
+                WHEN NOT t.start_on_read THEN $8 + t.ttl_secs * 1000
+                WHEN EXISTS (
+                    SELECT 1 FROM privchat_channel_read_cursor
+                    WHERE channel_id = $3 AND user_id <> $5 AND last_read_pts >= $4
+                ) THEN $8 + t.ttl_secs * 1000
+            END
+        FROM (
+            SELECT $6::BIGINT AS ttl_secs, $7::BOOLEAN AS start_on_read
+            WHERE $6::BIGINT IS NOT NULL
+            UNION ALL
+            SELECT ttl_secs, start_on_read FROM privchat_channel_disappearing
+            WHERE channel_id = $3 AND $6::BIGINT IS NULL
+        ) t
+        ON CONFLICT (message_id) DO NOTHING
+        "#,
+    )
+    .bind(message.message_id as i64)
+    .bind(message.created_at.timestamp_millis())
+    .bind(message.channel_id as i64)
+    .bind(message.pts.unwrap_or(0))
+    .bind(message.sender_id as i64)
+    .bind(explicit.map(|timer| timer.ttl_secs))
+    .bind(explicit.is_some_and(|timer| timer.start_on_read))
+    .bind(now_ms)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// 一个会话内本批被硬删除的消息
+#[derive(Debug, Clone)]
+pub struct ExpiredChannelMessages {
+    pub channel_id: u64,
+    /// DB 表示的频道类型（Direct=0 / Group=1）
+    pub channel_type: i16,
+    pub message_ids: Vec<u64>,
+}
+
+/// 一批到期清理的结果
+#[derive(Debug, Clone, Default)]
+pub struct ExpiredBatch {
+    pub channels: Vec<ExpiredChannelMessages>,
+    /// 随消息删除而释放引用的附件 file_id（去重），是否还被其他消息引用由调用方判断
+    pub released_file_ids: Vec<u64>,
+}
+
+impl ExpiredBatch {
+    pub fn message_count(&self) -> usize {
+        self.channels.iter().map(|c| c.message_ids.len()).sum()
+    }
+}
+
+/// 阅后即焚：会话默认计时器 + 单条消息到期时间
+pub struct DisappearingRepository {
+    pool: PgPool,
+}
+
+impl DisappearingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_channel_timer(&self, channel_id: u64) -> Result<Option<DisappearingTimer>> {
+        let row = sqlx::query(
+            "SELECT ttl_secs, start_on_read FROM privchat_channel_disappearing WHERE channel_id = $1",
+        )
+        .bind(channel_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询会话消失计时器失败: {}", e)))?;
+        Ok(row.map(|row| DisappearingTimer {
+            ttl_secs: row.get("ttl_secs"),
+            start_on_read: row.get("start_on_read"),
+        }))
+    }
+
+    pub async fn upsert_channel_timer(
+        &self,
+        channel_id: u64,
+        timer: DisappearingTimer,
+        updated_by: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_disappearing
+                (channel_id, ttl_secs, start_on_read, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, now_millis())
+            ON CONFLICT (channel_id) DO UPDATE
+            SET ttl_secs = EXCLUDED.ttl_secs,
+                start_on_read = EXCLUDED.start_on_read,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(timer.ttl_secs)
+        .bind(timer.start_on_read)
+        .bind(updated_by as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存会话消失计时器失败: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn delete_channel_timer(&self, channel_id: u64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM privchat_channel_disappearing WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("关闭会话消失计时器失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 为消息写入消失计时，返回到期时间（毫秒）；`Ok(None)` 表示消息已经设置过计时。
+    ///
+    /// 已读后计时的消息，如果写入时已有其他成员的已读游标越过它（补设计时的情形），
+    /// 倒计时立即开始；否则 expires_at 留空，等 [`Self::start_countdown_on_read`] 回填。
+    pub async fn insert_message_expiry(
+        &self,
+        expiry: &NewMessageExpiry,
+        now_ms: i64,
+    ) -> Result<Option<Option<i64>>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO privchat_message_expiry
+                (message_id, message_created_at, channel_id, pts, sender_id,
+                 ttl_secs, start_on_read, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7,
+                CASE
+                    WHEN NOT $7 THEN $8 + $6 * 1000
+                    WHEN EXISTS (
+                        SELECT 1 FROM privchat_channel_read_cursor
+                        WHERE channel_id = $3 AND user_id <> $5 AND last_read_pts >= $4
+                    ) THEN $8 + $6 * 1000
+                END)
+            ON CONFLICT (message_id) DO NOTHING
+            RETURNING expires_at
+            "#,
+        )
+        .bind(expiry.message_id as i64)
+        .bind(expiry.message_created_at)
+        .bind(expiry.channel_id as i64)
+        .bind(expiry.pts as i64)
+        .bind(expiry.sender_id as i64)
+        .bind(expiry.timer.ttl_secs)
+        .bind(expiry.timer.start_on_read)
+        .bind(now_ms)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存消息消失计时失败: {}", e)))?;
+        Ok(row.map(|row| row.get::<Option<i64>, _>("expires_at")))
+    }
+
+    /// 已读游标推进后，启动 `reader_id` 已读范围内他人发送的、等待已读的倒计时
+    pub async fn start_countdown_on_read(
+        &self,
+        channel_id: u64,
+        reader_id: u64,
+        read_pts: u64,
+        now_ms: i64,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_message_expiry
+            SET expires_at = $4 + ttl_secs * 1000
+            WHERE channel_id = $1
+              AND expires_at IS NULL
+              AND start_on_read
+              AND pts <= $3
+              AND sender_id <> $2
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(reader_id as i64)
+        .bind(read_pts as i64)
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("启动已读倒计时失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 硬删除一批（最早到期的 `limit` 条）已到期消息及其 commit log / 附属行，单事务完成。
+    ///
+    /// 到期行以 `FOR UPDATE SKIP LOCKED` 认领，多节点同时清理时互不重复。
+    /// 文件引用先于消息删除以拿到被释放的 file_id。
+    pub async fn purge_expired_batch(&self, now_ms: i64, limit: i64) -> Result<ExpiredBatch> {
+        let db_err = |e: sqlx::Error| ServerError::Database(format!("清理到期消息失败: {}", e));
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT e.message_id, e.message_created_at, e.channel_id, e.pts,
+                   COALESCE(c.channel_type, 0::SMALLINT) AS channel_type
+            FROM privchat_message_expiry e
+            LEFT JOIN privchat_channels c ON c.channel_id = e.channel_id
+            WHERE e.expires_at IS NOT NULL AND e.expires_at <= $1
+            ORDER BY e.expires_at
+            LIMIT $2
+            FOR UPDATE OF e SKIP LOCKED
+            "#,
+        )
+        .bind(now_ms)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?;
+        if rows.is_empty() {
+            tx.commit().await.map_err(db_err)?;
+            return Ok(ExpiredBatch::default());
+        }
+
+        let message_ids: Vec<i64> = rows.iter().map(|r| r.get("message_id")).collect();
+        let created_ats: Vec<i64> = rows.iter().map(|r| r.get("message_created_at")).collect();
+
+        let mut released_file_ids: Vec<u64> = sqlx::query_scalar::<_, i64>(
+            "DELETE FROM privchat_message_file_refs WHERE message_id = ANY($1) RETURNING file_id",
+        )
+        .bind(&message_ids)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?
+        .into_iter()
+        .map(|id| id as u64)
+        .collect();
+        released_file_ids.sort_unstable();
+        released_file_ids.dedup();
+
+        sqlx::query(
+            r#"
+            DELETE FROM privchat_messages m
+            USING unnest($1::BIGINT[], $2::BIGINT[]) AS d(message_id, created_at)
+            WHERE m.message_id = d.message_id AND m.created_at = d.created_at
+            "#,
+        )
+        .bind(&message_ids)
+        .bind(&created_ats)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        // (channel_id) -> (channel_type, message_ids, pts)
+        let mut by_channel: BTreeMap<i64, (i16, Vec<i64>, Vec<i64>)> = BTreeMap::new();
+        for row in &rows {
+            let entry = by_channel
+                .entry(row.get("channel_id"))
+                .or_insert_with(|| (row.get("channel_type"), Vec::new(), Vec::new()));
+            entry.1.push(row.get("message_id"));
+            entry.2.push(row.get("pts"));
+        }
+
+        let mut channels = Vec::with_capacity(by_channel.len());
+        for (channel_id, (channel_type, ids, pts)) in by_channel {
+            delete_message_dependents(&mut tx, channel_id as u64, &ids)
+                .await
+                .map_err(db_err)?;
+            // 消息的 commit 内容带原文，必须一并删除，否则 get_difference 仍会返回
+            sqlx::query(
+                r#"
+                DELETE FROM privchat_commit_log
+                WHERE channel_id = $1 AND pts = ANY($2) AND server_msg_id = ANY($3)
+                "#,
+            )
+            .bind(channel_id)
+            .bind(&pts)
+            .bind(&ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+            channels.push(ExpiredChannelMessages {
+                channel_id: channel_id as u64,
+                channel_type,
+                message_ids: ids.into_iter().map(|id| id as u64).collect(),
+            });
+        }
+
+        tx.commit().await.map_err(db_err)?;
+        Ok(ExpiredBatch {
+            channels,
+            released_file_ids,
+        })
+    }
+}
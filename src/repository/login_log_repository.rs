@@ -57,6 +57,8 @@ pub struct LoginLog {
     pub risk_factors: Option<serde_json::Value>, // JSONB
     pub is_new_device: bool,
     pub is_new_location: bool,
+    /// 本次登录所用 token 是否经过两步验证签发
+    pub mfa_used: bool,
 
     // 通知状态
     pub notification_sent: bool,
@@ -96,6 +98,7 @@ pub struct CreateLoginLogRequest {
     pub risk_score: i16,
     pub is_new_device: bool,
     pub is_new_location: bool,
+    pub mfa_used: bool,
     pub risk_factors: Option<Vec<String>>,
     pub metadata: Option<serde_json::Value>,
 }
@@ -153,20 +156,20 @@ impl LoginLogRepository {
                 device_type, device_name, device_model, os_version,
                 app_id, app_version, ip_address, user_agent,
                 login_method, auth_source,
-                status, risk_score, is_new_device, is_new_location, risk_factors, metadata
+                status, risk_score, is_new_device, is_new_location, mfa_used, risk_factors, metadata
             ) VALUES (
                 $1, $2, $3, $4,
                 $5, $6, $7, $8,
                 $9, $10, $11, $12,
                 $13, $14,
-                $15, $16, $17, $18, $19, $20
+                $15, $16, $17, $18, $19, $20, $21
             )
             RETURNING
                 log_id, user_id, device_id, token_jti, token_created_at, token_first_used_at,
                 device_type, device_name, device_model, os_version,
                 app_id, app_version, ip_address, user_agent,
                 login_method, auth_source,
-                status, risk_score, is_new_device, is_new_location, risk_factors,
+                status, risk_score, is_new_device, is_new_location, mfa_used, risk_factors,
                 notification_sent, notification_method, notification_sent_at,
                 metadata, created_at
             "#,
@@ -188,6 +191,7 @@ impl LoginLogRepository {
             req.risk_score,
             req.is_new_device,
             req.is_new_location,
+            req.mfa_used,
             risk_factors_json,
             req.metadata,
         )
@@ -267,7 +271,7 @@ impl LoginLogRepository {
                 device_type, device_name, device_model, os_version,
                 app_id, app_version, ip_address, user_agent,
                 login_method, auth_source,
-                status, risk_score, is_new_device, is_new_location, risk_factors,
+                status, risk_score, is_new_device, is_new_location, mfa_used, risk_factors,
                 notification_sent, notification_method, notification_sent_at,
                 metadata, created_at
             FROM privchat_login_logs
@@ -407,7 +411,7 @@ impl LoginLogRepository {
                 device_type, device_name, device_model, os_version,
                 app_id, app_version, ip_address, user_agent,
                 login_method, auth_source,
-                status, risk_score, is_new_device, is_new_location, risk_factors,
+                status, risk_score, is_new_device, is_new_location, mfa_used, risk_factors,
                 notification_sent, notification_method, notification_sent_at,
                 metadata, created_at
             FROM privchat_login_logs
@@ -478,7 +482,7 @@ impl LoginLogRepository {
                 device_type, device_name, device_model, os_version,
                 app_id, app_version, ip_address, user_agent,
                 login_method, auth_source,
-                status, risk_score, is_new_device, is_new_location, risk_factors,
+                status, risk_score, is_new_device, is_new_location, mfa_used, risk_factors,
                 notification_sent, notification_method, notification_sent_at,
                 metadata, created_at
             FROM privchat_login_logs
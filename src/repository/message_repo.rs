@@ -18,7 +18,13 @@
 //! 消息仓库 - PostgreSQL 实现
 
 use crate::error::DatabaseError;
+use crate::infra::read_pool;
 use crate::model::message::Message;
+use crate::repository::broadcast_channel_repo::{
+    insert_message_attrs_in_tx, BroadcastMessageAttrs,
+};
+use crate::repository::disappearing_repo::{arm_new_message_in_tx, MessageExpiryArm};
+use crate::service::mention_service::insert_mentions_in_tx;
 use privchat_protocol::rpc::sync::ServerCommit;
 use privchat_protocol::{
     CanonicalTimelineEvent, FlatBufferMessage, CANONICAL_TIMELINE_EVENT_SCHEMA_V1,
@@ -112,6 +118,12 @@ pub struct AtomicMessageCommitRequest {
     pub channel_type: i16,
     pub event: CanonicalTimelineEvent,
     pub sender_username: Option<String>,
+    /// 阅后即焚计时，与消息同事务登记（所有发送路径都必须给出，见 [`MessageExpiryArm`]）。
+    pub expiry: MessageExpiryArm,
+    /// 广播频道发布的标题 / 紧急 / 过期属性，与消息同事务写入；普通消息为 None。
+    pub broadcast_attrs: Option<BroadcastMessageAttrs>,
+    /// 被@用户（含 `MENTION_ALL_USER_ID` 表示 @全体成员），与消息同事务写入；无 @ 为空。
+    pub mention_targets: Vec<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -679,6 +691,40 @@ impl PgMessageRepository {
             })?;
         }
 
+        // 阅后即焚计时与消息同事务：登记失败则整条消息回滚，不会留下一条
+        // 「设了计时却永远不消失」的消息。
+        arm_new_message_in_tx(&mut tx, request.expiry, &message, now)
+            .await
+            .map_err(|e| {
+                DatabaseError::Database(format!(
+                    "Failed to arm disappearing timer for message_id={}: {}",
+                    message.message_id, e
+                ))
+            })?;
+
+        // 广播属性与消息同事务：属性缺失的发布会让标题 / 紧急 / 过期静默丢失，
+        // 订阅者的已读 / 忽略也无处落。
+        if let Some(attrs) = request.broadcast_attrs.as_ref() {
+            insert_message_attrs_in_tx(&mut tx, &message, attrs)
+                .await
+                .map_err(|e| {
+                    DatabaseError::Database(format!(
+                        "Failed to save broadcast attrs for message_id={}: {}",
+                        message.message_id, e
+                    ))
+                })?;
+        }
+
+        // @提及与消息同事务：事务外补写失败只能打日志，被@的人就永远看不到这条未读 @。
+        insert_mentions_in_tx(&mut tx, &message, &request.mention_targets)
+            .await
+            .map_err(|e| {
+                DatabaseError::Database(format!(
+                    "Failed to record mentions for message_id={}: {}",
+                    message.message_id, e
+                ))
+            })?;
+
         // 维护 message head（V019）。与建消息同事务：head 落后于消息会让客户端以为
         // 自己已经追平最新，从而不去补那几条差值——那是静默丢消息。
         //
@@ -1138,7 +1184,7 @@ impl PgMessageRepository {
         query_builder = query_builder.bind(offset as i64);
 
         let rows = query_builder
-            .fetch_all(self.pool.as_ref())
+            .fetch_all(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("查询消息列表失败: {}", e)))?;
 
@@ -1177,7 +1223,7 @@ impl PgMessageRepository {
         }
 
         let total_result: (i64,) = count_query
-            .fetch_one(self.pool.as_ref())
+            .fetch_one(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("统计消息数失败: {}", e)))?;
 
@@ -1255,7 +1301,7 @@ impl PgMessageRepository {
                 .timestamp_millis();
 
         let total_result = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM privchat_messages")
-            .fetch_one(self.pool.as_ref())
+            .fetch_one(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("统计总消息数失败: {}", e)))?;
 
@@ -1263,7 +1309,7 @@ impl PgMessageRepository {
             "SELECT COUNT(*) FROM privchat_messages WHERE created_at >= $1",
         )
         .bind(today_start_ts)
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("统计今日消息数失败: {}", e)))?;
 
@@ -1271,7 +1317,7 @@ impl PgMessageRepository {
             "SELECT COUNT(*) FROM privchat_messages WHERE created_at >= $1",
         )
         .bind(week_start_ts)
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("统计本周消息数失败: {}", e)))?;
 
@@ -1279,7 +1325,7 @@ impl PgMessageRepository {
             "SELECT COUNT(*) FROM privchat_messages WHERE created_at >= $1",
         )
         .bind(month_start_ts)
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("统计本月消息数失败: {}", e)))?;
 
@@ -1813,7 +1859,7 @@ impl MessageRepository for PgMessageRepository {
         };
 
         let rows = query
-            .fetch_all(self.pool.as_ref())
+            .fetch_all(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("Failed to list messages: {}", e)))?;
 
@@ -2059,7 +2105,7 @@ impl MessageRepository for PgMessageRepository {
         query_builder = query_builder.bind(offset as i64);
 
         let rows = query_builder
-            .fetch_all(self.pool.as_ref())
+            .fetch_all(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("搜索消息失败: {}", e)))?;
 
@@ -2129,7 +2175,7 @@ impl MessageRepository for PgMessageRepository {
         }
 
         let count_row = count_query
-            .fetch_one(self.pool.as_ref())
+            .fetch_one(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("统计搜索结果失败: {}", e)))?;
 
@@ -2167,7 +2213,7 @@ impl PgMessageRepository {
             "SELECT array_to_string(tsvector_to_array(privchat_search_tokens($1)), ' & ')",
         )
         .bind(raw_query)
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("tokenize query failed: {}", e)))?;
         Ok(row.0.unwrap_or_default())
@@ -2236,8 +2282,7 @@ impl PgMessageRepository {
             query = query.bind(ts).bind(id);
         }
 
-        let mut tx = self
-            .pool
+        let mut tx = read_pool(&self.pool)
             .begin()
             .await
             .map_err(|e| DatabaseError::Database(format!("search begin tx failed: {}", e)))?;
@@ -2350,7 +2395,7 @@ impl PgMessageRepository {
             .bind(anchor_created_at)
             .bind(anchor_message_id)
             .bind(limit.clamp(1, 50))
-            .fetch_all(self.pool.as_ref())
+            .fetch_all(read_pool(&self.pool))
             .await
             .map_err(|e| DatabaseError::Database(format!("list context failed: {}", e)))?;
 
@@ -2555,6 +2600,41 @@ mod atomic_dispatch_tests {
         );
     }
 
+    /// 阅后即焚计时必须和消息同事务登记：事务外登记失败时只能打日志，
+    /// 消息就成了一条永不消失的「阅后即焚」消息。
+    #[test]
+    fn disappearing_timer_is_armed_inside_the_commit_transaction() {
+        let src = include_str!("message_repo.rs");
+        let fn_start = src
+            .find("pub async fn create_message_and_commit_atomic")
+            .expect("commit fn");
+        let fn_end = src[fn_start..]
+            .find("\n    pub async fn ")
+            .map(|off| fn_start + off)
+            .unwrap_or(src.len());
+        let body = &src[fn_start..fn_end];
+        let arm = body
+            .find("arm_new_message_in_tx(&mut tx")
+            .expect("commit path must arm the disappearing timer");
+        let commit = body.find("tx.commit()").expect("commit call");
+        assert!(arm < commit, "timer must be armed before the tx commits");
+    }
+
+    /// @提及同理：未读 @ 只看这张表，消息提交了而提及没写进去就是静默丢 @。
+    #[test]
+    fn mentions_are_recorded_inside_the_commit_transaction() {
+        let src = include_str!("message_repo.rs");
+        let fn_start = src
+            .find("pub async fn create_message_and_commit_atomic")
+            .expect("commit fn");
+        let body = &src[fn_start..];
+        let record = body
+            .find("insert_mentions_in_tx(&mut tx")
+            .expect("commit path must record mentions");
+        let commit = body.find("tx.commit()").expect("commit call");
+        assert!(record < commit, "mentions must be recorded before the tx commits");
+    }
+
     /// 跑一次 head 推进，返回受影响行数（0 = 被守卫挡住，没有产生写）。
     async fn advance_head(
         repo: &PgMessageRepository,
@@ -2711,6 +2791,9 @@ mod atomic_dispatch_tests {
                 ),
             }),
             sender_username: None,
+            expiry: MessageExpiryArm::Skip,
+            broadcast_attrs: None,
+            mention_targets: Vec::new(),
         })
         .await
         .expect("atomic media message commit");
@@ -2846,6 +2929,9 @@ mod atomic_dispatch_tests {
                 ),
             }),
             sender_username: None,
+            expiry: MessageExpiryArm::Skip,
+            broadcast_attrs: None,
+            mention_targets: Vec::new(),
         })
         .await
         .expect("commit media message");
@@ -3000,6 +3086,9 @@ mod atomic_dispatch_tests {
                     ),
                 }),
                 sender_username: None,
+                expiry: MessageExpiryArm::Skip,
+                broadcast_attrs: None,
+            mention_targets: Vec::new(),
             })
             .await
             .expect("atomic message commit");
@@ -92,34 +92,81 @@ impl<T> PaginationResult<T> {
 }
 
 // 模块导出（暂时注释掉数据库相关的）
+pub mod account_deletion_repo; // 用户自助注销（冷静期 / 最终注销清理）
 pub mod approval_repo; // #72A 群审批申请持久化
 pub mod bot_follow_repo;
+pub mod bot_platform_repo; // Bot API 令牌 / 斜杠命令 / getUpdates 队列
+pub mod broadcast_channel_repo; // 广播频道（订阅号/公告）
 pub mod channel_repo;
+pub mod data_export_repo; // 个人数据导出任务与导出内容查询
 pub mod device_repo;
+pub mod disappearing_repo; // 阅后即焚（会话计时器 / 单条消息到期）
 pub mod file_upload_repo;
 pub mod login_log_repository;
 pub mod message_repo;
+pub mod password_reset_repo; // 内置账号找回密码（一次性重置令牌）
 pub mod presence_repository;
+pub mod qrcode_repo; // 二维码 Key / 扫描日志持久化
 pub mod refresh_token_repository; // v1.3 unified refresh token store
+pub mod retention_repo; // 消息保留期（会话覆盖 / 超期清理）
+pub mod security_ban_repo; // 管理员手动封禁（Shadow Ban 持久化）
+pub mod sticker_repo; // 表情包库 / 用户收藏 / 最近使用
+pub mod two_factor_repo; // 内置账号两步验证（TOTP / 恢复码）
 pub mod user_device_repo; // ✨ 新增：用户设备 Repository
+pub mod user_push_settings_repo; // 用户级推送偏好（语言 / 隐藏预览 / 免打扰）
 pub mod user_repo;
+pub mod webhook_repo; // 出站 Webhook 端点 / 投递日志
 
 // 重新导出 PostgreSQL Repository 实现
+pub use account_deletion_repo::{
+    AccountDeletionRecord, AccountDeletionRepository, RedactedBatch, RedactedChannelMessages,
+};
 pub use approval_repo::ApprovalRepository;
 pub use bot_follow_repo::{
     BotFollowRecord, BotFollowRepository, FollowUpsertOutcome, STATUS_FOLLOWED, STATUS_UNFOLLOWED,
 };
+pub use bot_platform_repo::{
+    hash_bot_token, BotPlatformRepository, BotTokenRecord, BotUpdateRecord,
+};
+pub use broadcast_channel_repo::{
+    BroadcastChannelRecord, BroadcastChannelRepository, BroadcastFeedItem, BroadcastMessageAttrs,
+};
 pub use channel_repo::{ChannelRepository, PgChannelRepository};
+pub use data_export_repo::{
+    DataExportRecord, DataExportRepository, EXPORT_STATUS_EXPIRED, EXPORT_STATUS_FAILED,
+    EXPORT_STATUS_PENDING, EXPORT_STATUS_READY, EXPORT_STATUS_RUNNING,
+};
 pub use device_repo::*;
+pub use disappearing_repo::{
+    DisappearingRepository, DisappearingTimer, ExpiredBatch, ExpiredChannelMessages,
+    MessageExpiryArm, NewMessageExpiry,
+};
 pub use file_upload_repo::FileUploadRepository;
 pub use login_log_repository::{CreateLoginLogRequest, LoginLogQuery, LoginLogRepository};
 pub use message_repo::{
     AtomicMessageCommitRequest, AtomicTimelineEventRequest, ClientRegistryClaim,
     MessageDeliveryReceiptRecord, MessageRepository, PgMessageRepository,
 };
+pub use password_reset_repo::{hash_reset_token, PasswordResetRepository};
 pub use presence_repository::PresenceRepository;
+pub use qrcode_repo::QRCodeRepository;
 pub use refresh_token_repository::{
     hash_refresh_token, RefreshTokenRecord, RefreshTokenRepository,
 };
+pub use retention_repo::{ExpiringChannel, PendingExpiryNotice, PurgedBatch, RetentionRepository};
+pub use security_ban_repo::{SecurityBan, SecurityBanRepository};
+pub use sticker_repo::{
+    NewSticker, NewStickerPackage, StickerPackageRecord, StickerPackageUpdate, StickerRecord,
+    StickerRepository,
+};
+pub use two_factor_repo::{TwoFactorRepository, UserTotp};
 pub use user_device_repo::{UserDevice, UserDeviceRepository}; // ✨ 新增
+pub use user_push_settings_repo::{
+    UserPushSettings, UserPushSettingsRepository, UserPushSettingsUpdate,
+};
 pub use user_repo::UserRepository;
+pub use webhook_repo::{
+    ClaimedWebhookDelivery, NewWebhookEndpoint, WebhookDelivery, WebhookDeliveryFilter,
+    WebhookEndpoint, WebhookEndpointUpdate, WebhookRepository, DELIVERY_STATUS_DEAD,
+    DELIVERY_STATUS_PENDING, DELIVERY_STATUS_SUCCEEDED,
+};
@@ -0,0 +1,176 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+/// 计算重置令牌的 SHA-256 hex（lowercase），库里只存摘要
+pub fn hash_reset_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.trim().as_bytes()))
+}
+
+/// 找回密码令牌 Repository
+pub struct PasswordResetRepository {
+    pool: PgPool,
+}
+
+impl PasswordResetRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 最近一次申请的时间（毫秒），用于限制重复申请
+    pub async fn last_created_at(&self, user_id: u64) -> Result<Option<i64>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT MAX(created_at) FROM privchat_password_reset_tokens
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询重置令牌失败: {}", e)))
+    }
+
+    /// 写入新令牌；同一事务内作废该用户之前未使用的令牌
+    pub async fn create(
+        &self,
+        user_id: u64,
+        token_hash: &str,
+        channel: &str,
+        expires_at: i64,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            UPDATE privchat_password_reset_tokens
+            SET used_at = now_millis()
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("作废旧重置令牌失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_password_reset_tokens (token_hash, user_id, channel, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(token_hash)
+        .bind(user_id as i64)
+        .bind(channel)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入重置令牌失败: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 核销令牌并改写密码，单事务完成：未过期且未使用时标记已用、写入新密码摘要并返回 user_id
+    /// （并发提交只有一个成功）。令牌无效返回 `None`；改密失败时令牌随事务回滚，仍可再用。
+    pub async fn consume_and_set_password(
+        &self,
+        token_hash: &str,
+        password_hash: &str,
+    ) -> Result<Option<u64>> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        let user_id: Option<i64> = sqlx::query_scalar(
+            r#"
+            UPDATE privchat_password_reset_tokens
+            SET used_at = now_millis()
+            WHERE token_hash = $1 AND used_at IS NULL AND expires_at > now_millis()
+            RETURNING user_id
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("核销重置令牌失败: {}", e)))?;
+        let Some(user_id) = user_id else {
+            return Ok(None);
+        };
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE privchat_users
+            SET password_hash = $2, updated_at = now_millis()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新密码失败: {}", e)))?;
+        if updated.rows_affected() == 0 {
+            return Err(ServerError::UserNotFound(user_id.to_string()));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(Some(user_id as u64))
+    }
+
+    /// 作废用户全部未使用的令牌（密码已通过其它途径修改）
+    pub async fn revoke_for_user(&self, user_id: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE privchat_password_reset_tokens
+            SET used_at = now_millis()
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("作废重置令牌失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_reset_token() {
+        let hash = hash_reset_token("abc");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_reset_token(" abc\n"));
+        assert_ne!(hash, hash_reset_token("abd"));
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use crate::model::{QRKeyRecord, QRScanLog, QRType};
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+const KEY_COLUMNS: &str = "qr_key, qr_type, target_id, creator_id, created_at, expire_at, \
+     max_usage, used_count, revoked, metadata";
+
+fn millis_to_datetime(ms: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(ms).single().unwrap_or_default()
+}
+
+fn key_from_row(row: &PgRow) -> Result<QRKeyRecord> {
+    let qr_type: String = row.get("qr_type");
+    Ok(QRKeyRecord {
+        qr_key: row.get("qr_key"),
+        qr_type: QRType::from_str(&qr_type)
+            .ok_or_else(|| ServerError::Database(format!("未知的 qr_type: {}", qr_type)))?,
+        target_id: row.get("target_id"),
+        creator_id: row.get("creator_id"),
+        created_at: millis_to_datetime(row.get("created_at")),
+        expire_at: row
+            .get::<Option<i64>, _>("expire_at")
+            .map(millis_to_datetime),
+        max_usage: row.get("max_usage"),
+        used_count: row.get("used_count"),
+        revoked: row.get("revoked"),
+        metadata: row.get("metadata"),
+    })
+}
+
+fn scan_log_from_row(row: &PgRow) -> QRScanLog {
+    QRScanLog {
+        qr_key: row.get("qr_key"),
+        scanner_id: row.get("scanner_id"),
+        scanned_at: millis_to_datetime(row.get("scanned_at")),
+        success: row.get("success"),
+        error: row.get("error"),
+    }
+}
+
+/// 二维码 Key 与扫描日志
+pub struct QRCodeRepository {
+    pool: PgPool,
+}
+
+impl QRCodeRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn insert(&self, record: &QRKeyRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO privchat_qrcode_keys \
+             (qr_key, qr_type, target_id, creator_id, created_at, expire_at, max_usage, \
+              used_count, revoked, metadata) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(&record.qr_key)
+        .bind(record.qr_type.as_str())
+        .bind(&record.target_id)
+        .bind(&record.creator_id)
+        .bind(record.created_at.timestamp_millis())
+        .bind(record.expire_at.map(|t| t.timestamp_millis()))
+        .bind(record.max_usage)
+        .bind(record.used_count)
+        .bind(record.revoked)
+        .bind(&record.metadata)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存 QR Key 失败: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn get(&self, qr_key: &str) -> Result<Option<QRKeyRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM privchat_qrcode_keys WHERE qr_key = $1",
+            KEY_COLUMNS
+        ))
+        .bind(qr_key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 QR Key 失败: {}", e)))?;
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    /// 目标当前（最新且未撤销）的 Key
+    pub async fn find_active_by_target(&self, target_id: &str) -> Result<Option<QRKeyRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM privchat_qrcode_keys \
+             WHERE target_id = $1 AND NOT revoked \
+             ORDER BY created_at DESC LIMIT 1",
+            KEY_COLUMNS
+        ))
+        .bind(target_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询目标 QR Key 失败: {}", e)))?;
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    /// 原子占用一次使用次数：仅在未撤销、未过期、未达上限时 +1，否则返回 None
+    pub async fn consume(&self, qr_key: &str, now_ms: i64) -> Result<Option<QRKeyRecord>> {
+        let row = sqlx::query(&format!(
+            "UPDATE privchat_qrcode_keys SET used_count = used_count + 1 \
+             WHERE qr_key = $1 AND NOT revoked \
+               AND (expire_at IS NULL OR expire_at > $2) \
+               AND (max_usage IS NULL OR used_count < max_usage) \
+             RETURNING {}",
+            KEY_COLUMNS
+        ))
+        .bind(qr_key)
+        .bind(now_ms)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新 QR Key 使用次数失败: {}", e)))?;
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    /// 撤销；Key 不存在返回 None（已撤销的保持原撤销时间）
+    pub async fn revoke(&self, qr_key: &str, now_ms: i64) -> Result<Option<QRKeyRecord>> {
+        let row = sqlx::query(&format!(
+            "UPDATE privchat_qrcode_keys \
+             SET revoked = TRUE, revoked_at = COALESCE(revoked_at, $2) \
+             WHERE qr_key = $1 RETURNING {}",
+            KEY_COLUMNS
+        ))
+        .bind(qr_key)
+        .bind(now_ms)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("撤销 QR Key 失败: {}", e)))?;
+        row.as_ref().map(key_from_row).transpose()
+    }
+
+    pub async fn list_by_creator(
+        &self,
+        creator_id: &str,
+        include_revoked: bool,
+    ) -> Result<Vec<QRKeyRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM privchat_qrcode_keys \
+             WHERE creator_id = $1 AND ($2 OR NOT revoked) \
+             ORDER BY created_at DESC",
+            KEY_COLUMNS
+        ))
+        .bind(creator_id)
+        .bind(include_revoked)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 QR Key 列表失败: {}", e)))?;
+        rows.iter().map(key_from_row).collect()
+    }
+
+    pub async fn insert_scan_log(&self, log: &QRScanLog) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO privchat_qrcode_scan_logs (qr_key, scanner_id, scanned_at, success, error) \
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&log.qr_key)
+        .bind(&log.scanner_id)
+        .bind(log.scanned_at.timestamp_millis())
+        .bind(log.success)
+        .bind(&log.error)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入 QR 扫描日志失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 某个 Key 的扫描日志（新 -> 旧）
+    pub async fn list_scan_logs(&self, qr_key: &str, limit: i64) -> Result<Vec<QRScanLog>> {
+        let rows = sqlx::query(
+            "SELECT qr_key, scanner_id, scanned_at, success, error \
+             FROM privchat_qrcode_scan_logs WHERE qr_key = $1 \
+             ORDER BY log_id DESC LIMIT $2",
+        )
+        .bind(qr_key)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 QR 扫描日志失败: {}", e)))?;
+        Ok(rows.iter().map(scan_log_from_row).collect())
+    }
+
+    /// 删除过期早于 `expired_before_ms` 的 Key（扫描日志级联删除），返回 Key 数
+    pub async fn purge_expired_keys(&self, expired_before_ms: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM privchat_qrcode_keys \
+             WHERE expire_at IS NOT NULL AND expire_at < $1",
+        )
+        .bind(expired_before_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清理过期 QR Key 失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 删除早于 `scanned_before_ms` 的扫描日志，返回条数
+    pub async fn purge_scan_logs(&self, scanned_before_ms: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM privchat_qrcode_scan_logs WHERE scanned_at < $1")
+            .bind(scanned_before_ms)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("清理 QR 扫描日志失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+}
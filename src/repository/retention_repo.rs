@@ -0,0 +1,337 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sqlx::{PgPool, Row};
+
+/// 到期待清理的会话（有效保留期已合并平台默认与会话覆盖）
+#[derive(Debug, Clone)]
+pub struct ExpiringChannel {
+    pub channel_id: u64,
+    /// DB 表示的频道类型（Direct=0 / Group=1）
+    pub channel_type: i16,
+    pub retention_secs: i64,
+}
+
+/// 一批清理的结果
+#[derive(Debug, Clone, Default)]
+pub struct PurgedBatch {
+    pub messages: u64,
+    /// 本批删除消息中最大的 pts（没有删除时为 0）
+    pub max_pts: u64,
+}
+
+/// 已删消息、尚未写入 commit log 的 `channel.history_expired` 通知
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingExpiryNotice {
+    pub channel_id: u64,
+    /// DB 表示的频道类型（Direct=0 / Group=1）
+    pub channel_type: i16,
+    pub expired_before: i64,
+    pub expired_up_to_pts: u64,
+}
+
+/// 消息保留期：会话级覆盖 + 超期消息清理
+pub struct RetentionRepository {
+    pool: PgPool,
+}
+
+impl RetentionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 会话级保留期覆盖（秒）；`None` 表示跟随平台默认
+    pub async fn get_channel_retention(&self, channel_id: u64) -> Result<Option<i64>> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT retention_secs FROM privchat_channel_retention WHERE channel_id = $1",
+        )
+        .bind(channel_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询会话保留期失败: {}", e)))
+    }
+
+    pub async fn upsert_channel_retention(
+        &self,
+        channel_id: u64,
+        retention_secs: i64,
+        updated_by: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_retention (channel_id, retention_secs, updated_by, updated_at)
+            VALUES ($1, $2, $3, now_millis())
+            ON CONFLICT (channel_id) DO UPDATE
+            SET retention_secs = EXCLUDED.retention_secs,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(retention_secs)
+        .bind(updated_by as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存会话保留期失败: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn delete_channel_retention(&self, channel_id: u64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM privchat_channel_retention WHERE channel_id = $1")
+            .bind(channel_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("清除会话保留期失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 存在超期消息的会话。
+    ///
+    /// 有效保留期 = LEAST(平台默认, 会话覆盖)，平台默认为 0 时只看会话覆盖
+    /// （LEAST 忽略 NULL）。EXISTS 走 (channel_id, created_at) 索引，没有超期消息的会话不返回。
+    pub async fn list_expiring_channels(
+        &self,
+        platform_retention_secs: i64,
+        now_ms: i64,
+    ) -> Result<Vec<ExpiringChannel>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.channel_id, t.channel_type, t.retention_secs
+            FROM (
+                SELECT c.channel_id, c.channel_type,
+                       LEAST(NULLIF($1::BIGINT, 0), r.retention_secs) AS retention_secs
+                FROM privchat_channels c
+                LEFT JOIN privchat_channel_retention r ON r.channel_id = c.channel_id
+            ) t
+            WHERE t.retention_secs IS NOT NULL
+              AND EXISTS (
+                  SELECT 1 FROM privchat_messages m
+                  WHERE m.channel_id = t.channel_id
+                    AND m.created_at < $2 - t.retention_secs * 1000
+              )
+            "#,
+        )
+        .bind(platform_retention_secs)
+        .bind(now_ms)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询待清理会话失败: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExpiringChannel {
+                channel_id: row.get::<i64, _>("channel_id") as u64,
+                channel_type: row.get("channel_type"),
+                retention_secs: row.get("retention_secs"),
+            })
+            .collect())
+    }
+
+    /// 删除一批（最旧的 `limit` 条）早于 `cutoff_ms` 的消息及其附属行，单事务完成。
+    ///
+    /// - 消息 → 文件引用（privchat_message_file_refs）由外键 ON DELETE CASCADE 一并删除；
+    /// - @提及、Reaction、送达回执、置顶、PG 离线队列按 message_id 删除；
+    /// - 若会话的 message head 指向被删消息，head 置空（被删的是最旧的一批，head 被删即会话已清空）；
+    /// - 同事务登记待发送的 history_expired 通知（见 [`Self::list_pending_notices`]），
+    ///   消息删了通知就一定会发出去。
+    ///
+    /// 同一会话被其他节点清理时（advisory 锁拿不到）返回 `None`。
+    pub async fn purge_channel_batch(
+        &self,
+        channel_id: u64,
+        channel_type: i16,
+        cutoff_ms: i64,
+        limit: i64,
+    ) -> Result<Option<PurgedBatch>> {
+        let db_err = |e: sqlx::Error| ServerError::Database(format!("清理超期消息失败: {}", e));
+        let mut tx = self.pool.begin().await.map_err(db_err)?;
+
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext($1))")
+            .bind(format!("retention:{}", channel_id))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        if !locked {
+            return Ok(None);
+        }
+
+        let rows = sqlx::query(
+            r#"
+            WITH doomed AS (
+                SELECT message_id, created_at
+                FROM privchat_messages
+                WHERE channel_id = $1 AND created_at < $2
+                ORDER BY created_at
+                LIMIT $3
+            )
+            DELETE FROM privchat_messages m
+            USING doomed d
+            WHERE m.message_id = d.message_id AND m.created_at = d.created_at
+            RETURNING m.message_id, m.pts
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(cutoff_ms)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        if rows.is_empty() {
+            tx.commit().await.map_err(db_err)?;
+            return Ok(Some(PurgedBatch::default()));
+        }
+
+        let message_ids: Vec<i64> = rows.iter().map(|r| r.get("message_id")).collect();
+        let max_pts = rows
+            .iter()
+            .map(|r| r.get::<i64, _>("pts"))
+            .max()
+            .unwrap_or(0) as u64;
+
+        delete_message_dependents(&mut tx, channel_id, &message_ids)
+            .await
+            .map_err(db_err)?;
+
+        // 多批（乃至多轮）未发出的通知合并成一条：取最晚的截止时间与最大的 pts
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_retention_pending_notices AS p
+                (channel_id, channel_type, expired_before, expired_up_to_pts, updated_at)
+            VALUES ($1, $2, $3, $4, now_millis())
+            ON CONFLICT (channel_id) DO UPDATE
+            SET expired_before = GREATEST(p.expired_before, EXCLUDED.expired_before),
+                expired_up_to_pts = GREATEST(p.expired_up_to_pts, EXCLUDED.expired_up_to_pts),
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(channel_type)
+        .bind(cutoff_ms)
+        .bind(max_pts as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        tx.commit().await.map_err(db_err)?;
+        Ok(Some(PurgedBatch {
+            messages: message_ids.len() as u64,
+            max_pts,
+        }))
+    }
+
+    /// 删除会话中早于 `cutoff_ms` 的 commit log（消息、撤回、Reaction 等时间线事件）
+    pub async fn purge_channel_commits(&self, channel_id: u64, cutoff_ms: i64) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM privchat_commit_log WHERE channel_id = $1 AND server_timestamp < $2",
+        )
+        .bind(channel_id as i64)
+        .bind(cutoff_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清理超期 commit log 失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 已删消息、尚未写入 commit log 的通知（含此前几轮失败遗留的）
+    pub async fn list_pending_notices(&self) -> Result<Vec<PendingExpiryNotice>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT channel_id, channel_type, expired_before, expired_up_to_pts
+            FROM privchat_retention_pending_notices
+            ORDER BY updated_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询待发送的过期通知失败: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingExpiryNotice {
+                channel_id: row.get::<i64, _>("channel_id") as u64,
+                channel_type: row.get("channel_type"),
+                expired_before: row.get("expired_before"),
+                expired_up_to_pts: row.get::<i64, _>("expired_up_to_pts") as u64,
+            })
+            .collect())
+    }
+
+    /// 通知已写入 commit log 后删除登记。
+    ///
+    /// 只删与 `notice` 完全一致的行：发送期间又有新的一批登记进来（截止时间或 pts 变大），
+    /// 那条留给下一轮补发。
+    pub async fn clear_pending_notice(&self, notice: &PendingExpiryNotice) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM privchat_retention_pending_notices
+            WHERE channel_id = $1 AND expired_before = $2 AND expired_up_to_pts = $3
+            "#,
+        )
+        .bind(notice.channel_id as i64)
+        .bind(notice.expired_before)
+        .bind(notice.expired_up_to_pts as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清除过期通知登记失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// 删除已删消息的附属行：@提及、Reaction、送达回执、置顶、消失计时、PG 离线队列；
+/// 会话 message head 指向被删消息时置空。
+///
+/// 文件引用（privchat_message_file_refs）由消息外键级联删除，不在这里处理。
+/// 调用方负责在同一事务里删除消息本身。
+pub(crate) async fn delete_message_dependents(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    channel_id: u64,
+    message_ids: &[i64],
+) -> std::result::Result<(), sqlx::Error> {
+    for sql in [
+        "DELETE FROM privchat_message_mentions WHERE message_id = ANY($1)",
+        "DELETE FROM privchat_message_reactions WHERE message_id = ANY($1)",
+        "DELETE FROM privchat_message_delivery_receipts WHERE server_message_id = ANY($1)",
+        "DELETE FROM privchat_group_pinned_messages WHERE message_id = ANY($1)",
+        "DELETE FROM privchat_message_expiry WHERE message_id = ANY($1)",
+    ] {
+        sqlx::query(sql)
+            .bind(message_ids)
+            .execute(&mut **tx)
+            .await?;
+    }
+    sqlx::query(
+        "DELETE FROM privchat_offline_message_queue WHERE channel_id = $1 AND server_msg_id = ANY($2)",
+    )
+    .bind(channel_id as i64)
+    .bind(message_ids)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        r#"
+        UPDATE privchat_channels
+        SET server_latest_message_pts = NULL, server_latest_message_id = NULL
+        WHERE channel_id = $1 AND server_latest_message_id = ANY($2)
+        "#,
+    )
+    .bind(channel_id as i64)
+    .bind(message_ids)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sqlx::PgPool;
+
+/// 管理员手动封禁记录
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SecurityBan {
+    pub user_id: i64,
+    pub device_id: String,
+    /// 违规类型（`ViolationType::as_str`）
+    pub violation: String,
+    pub note: Option<String>,
+    /// 到期时间（毫秒）；None 表示无期限
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// 手动封禁 Repository（Shadow Ban 的持久化真源）
+pub struct SecurityBanRepository {
+    pool: PgPool,
+}
+
+impl SecurityBanRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 写入 / 覆盖某台设备的封禁
+    pub async fn upsert(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        violation: &str,
+        note: Option<&str>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_security_bans (user_id, device_id, violation, note, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, device_id)
+            DO UPDATE SET
+                violation = EXCLUDED.violation,
+                note = EXCLUDED.note,
+                expires_at = EXCLUDED.expires_at,
+                created_at = now_millis()
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(device_id)
+        .bind(violation)
+        .bind(note)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入封禁记录失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 删除某台设备的封禁
+    pub async fn delete_by_device(&self, user_id: u64, device_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM privchat_security_bans WHERE user_id = $1 AND device_id = $2")
+            .bind(user_id as i64)
+            .bind(device_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除封禁记录失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 删除用户的全部封禁，返回删除行数
+    pub async fn delete_by_user(&self, user_id: u64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM privchat_security_bans WHERE user_id = $1")
+            .bind(user_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除封禁记录失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 所有未到期的封禁（启动时回灌 Redis 用），顺带清掉已到期的行
+    pub async fn list_active(&self, now_ms: i64) -> Result<Vec<SecurityBan>> {
+        sqlx::query(
+            "DELETE FROM privchat_security_bans WHERE expires_at IS NOT NULL AND expires_at <= $1",
+        )
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清理过期封禁失败: {}", e)))?;
+
+        sqlx::query_as::<_, SecurityBan>(
+            r#"
+            SELECT user_id, device_id, violation, note, expires_at, created_at
+            FROM privchat_security_bans
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询封禁记录失败: {}", e)))
+    }
+}
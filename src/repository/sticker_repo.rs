@@ -0,0 +1,404 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sqlx::PgPool;
+
+/// 表情包库记录（`sticker_count` 为查询时聚合）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StickerPackageRecord {
+    pub package_id: String,
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    pub thumbnail_file_id: Option<i64>,
+    pub is_enabled: bool,
+    pub sort_order: i32,
+    pub sticker_count: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// 缩略图在存储源中的路径（文件记录缺失时为 None）
+    pub thumbnail_path: Option<String>,
+    pub thumbnail_source_id: Option<i32>,
+}
+
+/// 表情记录（`package_enabled` 来自所属库，校验消息引用时用）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StickerRecord {
+    pub sticker_id: String,
+    pub package_id: String,
+    pub file_id: i64,
+    pub alt_text: String,
+    pub emoji: Option<String>,
+    pub width: i32,
+    pub height: i32,
+    pub mime_type: String,
+    pub sort_order: i32,
+    pub package_enabled: bool,
+    /// 图片在存储源中的路径（文件记录缺失时为 None）
+    pub file_path: Option<String>,
+    pub storage_source_id: Option<i32>,
+}
+
+/// 新建表情包库
+#[derive(Debug, Clone)]
+pub struct NewStickerPackage {
+    pub package_id: String,
+    pub name: String,
+    pub author: String,
+    pub description: String,
+    pub thumbnail_file_id: Option<u64>,
+    pub is_enabled: bool,
+    pub sort_order: i32,
+}
+
+/// 表情包库的部分更新（`None` 字段保持原值）
+#[derive(Debug, Clone, Default)]
+pub struct StickerPackageUpdate {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    pub thumbnail_file_id: Option<u64>,
+    pub is_enabled: Option<bool>,
+    pub sort_order: Option<i32>,
+}
+
+/// 新建表情
+#[derive(Debug, Clone)]
+pub struct NewSticker {
+    pub sticker_id: String,
+    pub package_id: String,
+    pub file_id: u64,
+    pub alt_text: String,
+    pub emoji: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: String,
+    pub sort_order: i32,
+}
+
+const PACKAGE_COLUMNS: &str = r#"
+    p.package_id, p.name, p.author, p.description, p.thumbnail_file_id,
+    p.is_enabled, p.sort_order,
+    (SELECT COUNT(*) FROM privchat_stickers s WHERE s.package_id = p.package_id) AS sticker_count,
+    p.created_at, p.updated_at,
+    t.file_path AS thumbnail_path, t.storage_source_id AS thumbnail_source_id
+"#;
+
+/// 缩略图 / 图片的存储位置直接 JOIN 文件表取，列表页不必逐条查 FileService
+const THUMBNAIL_JOIN: &str = "LEFT JOIN privchat_file_uploads t ON t.file_id = p.thumbnail_file_id";
+
+const STICKER_COLUMNS: &str = r#"
+    s.sticker_id, s.package_id, s.file_id, s.alt_text, s.emoji,
+    s.width, s.height, s.mime_type, s.sort_order,
+    p.is_enabled AS package_enabled,
+    f.file_path, f.storage_source_id
+"#;
+
+const STICKER_JOIN: &str = "JOIN privchat_sticker_packages p ON p.package_id = s.package_id \
+     LEFT JOIN privchat_file_uploads f ON f.file_id = s.file_id";
+
+/// 23505 = unique_violation
+fn map_insert_error(e: sqlx::Error, what: &str) -> ServerError {
+    if let sqlx::Error::Database(db_err) = &e {
+        if db_err.code().as_deref() == Some("23505") {
+            return ServerError::Duplicate(format!("{} 已存在", what));
+        }
+    }
+    ServerError::Database(format!("写入{}失败: {}", what, e))
+}
+
+/// 表情包 Repository
+pub struct StickerRepository {
+    pool: PgPool,
+}
+
+impl StickerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 表情包库列表；`include_disabled = false` 时只返回上架的库
+    pub async fn list_packages(&self, include_disabled: bool) -> Result<Vec<StickerPackageRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_sticker_packages p {} \
+             WHERE ($1 OR p.is_enabled) \
+             ORDER BY p.sort_order, p.package_id",
+            PACKAGE_COLUMNS, THUMBNAIL_JOIN
+        );
+        sqlx::query_as::<_, StickerPackageRecord>(&sql)
+            .bind(include_disabled)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询表情包库失败: {}", e)))
+    }
+
+    pub async fn get_package(&self, package_id: &str) -> Result<Option<StickerPackageRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_sticker_packages p {} WHERE p.package_id = $1",
+            PACKAGE_COLUMNS, THUMBNAIL_JOIN
+        );
+        sqlx::query_as::<_, StickerPackageRecord>(&sql)
+            .bind(package_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询表情包库失败: {}", e)))
+    }
+
+    pub async fn create_package(&self, package: &NewStickerPackage) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_sticker_packages
+                (package_id, name, author, description, thumbnail_file_id, is_enabled, sort_order)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&package.package_id)
+        .bind(&package.name)
+        .bind(&package.author)
+        .bind(&package.description)
+        .bind(package.thumbnail_file_id.map(|id| id as i64))
+        .bind(package.is_enabled)
+        .bind(package.sort_order)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| map_insert_error(e, "表情包库"))?;
+        Ok(())
+    }
+
+    /// 部分更新；库不存在时返回 false
+    pub async fn update_package(
+        &self,
+        package_id: &str,
+        update: &StickerPackageUpdate,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_sticker_packages SET
+                name = COALESCE($2, name),
+                author = COALESCE($3, author),
+                description = COALESCE($4, description),
+                thumbnail_file_id = COALESCE($5, thumbnail_file_id),
+                is_enabled = COALESCE($6, is_enabled),
+                sort_order = COALESCE($7, sort_order),
+                updated_at = now_millis()
+            WHERE package_id = $1
+            "#,
+        )
+        .bind(package_id)
+        .bind(update.name.as_deref())
+        .bind(update.author.as_deref())
+        .bind(update.description.as_deref())
+        .bind(update.thumbnail_file_id.map(|id| id as i64))
+        .bind(update.is_enabled)
+        .bind(update.sort_order)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新表情包库失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 删除表情包库（表情、收藏、最近使用随外键级联删除）
+    pub async fn delete_package(&self, package_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM privchat_sticker_packages WHERE package_id = $1")
+            .bind(package_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除表情包库失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_stickers(&self, package_id: &str) -> Result<Vec<StickerRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_stickers s {} \
+             WHERE s.package_id = $1 \
+             ORDER BY s.sort_order, s.sticker_id",
+            STICKER_COLUMNS, STICKER_JOIN
+        );
+        sqlx::query_as::<_, StickerRecord>(&sql)
+            .bind(package_id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询表情失败: {}", e)))
+    }
+
+    pub async fn get_sticker(&self, sticker_id: &str) -> Result<Option<StickerRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_stickers s {} WHERE s.sticker_id = $1",
+            STICKER_COLUMNS, STICKER_JOIN
+        );
+        sqlx::query_as::<_, StickerRecord>(&sql)
+            .bind(sticker_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询表情失败: {}", e)))
+    }
+
+    pub async fn insert_sticker(&self, sticker: &NewSticker) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_stickers
+                (sticker_id, package_id, file_id, alt_text, emoji, width, height, mime_type, sort_order)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(&sticker.sticker_id)
+        .bind(&sticker.package_id)
+        .bind(sticker.file_id as i64)
+        .bind(&sticker.alt_text)
+        .bind(sticker.emoji.as_deref())
+        .bind(sticker.width as i32)
+        .bind(sticker.height as i32)
+        .bind(&sticker.mime_type)
+        .bind(sticker.sort_order)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| map_insert_error(e, "表情"))?;
+
+        sqlx::query(
+            "UPDATE privchat_sticker_packages SET updated_at = now_millis() WHERE package_id = $1",
+        )
+        .bind(&sticker.package_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新表情包库失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 删除表情；不存在时返回 false
+    pub async fn delete_sticker(&self, package_id: &str, sticker_id: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM privchat_stickers WHERE package_id = $1 AND sticker_id = $2")
+                .bind(package_id)
+                .bind(sticker_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| ServerError::Database(format!("删除表情失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 用户收藏的表情包库（只含上架的，按收藏时间倒序）
+    pub async fn list_user_packages(&self, user_id: u64) -> Result<Vec<StickerPackageRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_user_sticker_packages u \
+             JOIN privchat_sticker_packages p ON p.package_id = u.package_id {} \
+             WHERE u.user_id = $1 AND p.is_enabled \
+             ORDER BY u.added_at DESC",
+            PACKAGE_COLUMNS, THUMBNAIL_JOIN
+        );
+        sqlx::query_as::<_, StickerPackageRecord>(&sql)
+            .bind(user_id as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询用户表情包失败: {}", e)))
+    }
+
+    /// 加入收藏；已收藏时返回 false
+    pub async fn add_user_package(&self, user_id: u64, package_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO privchat_user_sticker_packages (user_id, package_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, package_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(package_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("收藏表情包失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 移出收藏；未收藏时返回 false
+    pub async fn remove_user_package(&self, user_id: u64, package_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM privchat_user_sticker_packages WHERE user_id = $1 AND package_id = $2",
+        )
+        .bind(user_id as i64)
+        .bind(package_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("移除收藏表情包失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 记录一次使用，并把该用户的最近列表裁剪到 `keep` 条
+    pub async fn record_recent(&self, user_id: u64, sticker_id: &str, keep: i64) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_user_recent_stickers (user_id, sticker_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, sticker_id)
+            DO UPDATE SET
+                use_count = privchat_user_recent_stickers.use_count + 1,
+                last_used_at = now_millis()
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(sticker_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("记录最近使用表情失败: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM privchat_user_recent_stickers
+            WHERE user_id = $1 AND sticker_id IN (
+                SELECT sticker_id FROM privchat_user_recent_stickers
+                WHERE user_id = $1
+                ORDER BY last_used_at DESC
+                OFFSET $2
+            )
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(keep)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("裁剪最近使用表情失败: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 最近使用的表情（只含上架库中的，按最后使用时间倒序）
+    pub async fn list_recent(&self, user_id: u64, limit: i64) -> Result<Vec<StickerRecord>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_user_recent_stickers r \
+             JOIN privchat_stickers s ON s.sticker_id = r.sticker_id {} \
+             WHERE r.user_id = $1 AND p.is_enabled \
+             ORDER BY r.last_used_at DESC \
+             LIMIT $2",
+            STICKER_COLUMNS, STICKER_JOIN
+        );
+        sqlx::query_as::<_, StickerRecord>(&sql)
+            .bind(user_id as i64)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询最近使用表情失败: {}", e)))
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sqlx::PgPool;
+
+/// 用户的 TOTP 绑定
+#[derive(Debug, Clone)]
+pub struct UserTotp {
+    pub user_id: u64,
+    /// base32 共享密钥
+    pub secret: String,
+    /// 未确认（setup 之后还没用动态码确认）时为 false，登录不要求动态码
+    pub enabled: bool,
+    /// 最近一次通过校验的时间步（防重放）
+    pub last_used_step: u64,
+    pub created_at: i64,
+    pub confirmed_at: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct Row {
+    user_id: i64,
+    secret: String,
+    enabled: bool,
+    last_used_step: i64,
+    created_at: i64,
+    confirmed_at: Option<i64>,
+}
+
+impl From<Row> for UserTotp {
+    fn from(row: Row) -> Self {
+        Self {
+            user_id: row.user_id as u64,
+            secret: row.secret,
+            enabled: row.enabled,
+            last_used_step: row.last_used_step as u64,
+            created_at: row.created_at,
+            confirmed_at: row.confirmed_at,
+        }
+    }
+}
+
+/// 两步验证 Repository（TOTP 密钥 + 恢复码摘要）
+pub struct TwoFactorRepository {
+    pool: PgPool,
+}
+
+impl TwoFactorRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get(&self, user_id: u64) -> Result<Option<UserTotp>> {
+        let row = sqlx::query_as::<_, Row>(
+            r#"
+            SELECT user_id, secret, enabled, last_used_step, created_at, confirmed_at
+            FROM privchat_user_totp
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询两步验证配置失败: {}", e)))?;
+        Ok(row.map(UserTotp::from))
+    }
+
+    /// 写入待确认的密钥；已启用时不覆盖，返回 false
+    pub async fn upsert_pending(&self, user_id: u64, secret: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO privchat_user_totp (user_id, secret, enabled, last_used_step)
+            VALUES ($1, $2, FALSE, 0)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret,
+                last_used_step = 0,
+                created_at = now_millis(),
+                confirmed_at = NULL
+            WHERE privchat_user_totp.enabled = FALSE
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(secret)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("保存两步验证密钥失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 确认启用：同一事务内写入首批恢复码摘要；已启用或未 setup 返回 false
+    pub async fn enable(&self, user_id: u64, step: u64, code_hashes: &[String]) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE privchat_user_totp
+            SET enabled = TRUE, last_used_step = $2, confirmed_at = now_millis()
+            WHERE user_id = $1 AND enabled = FALSE
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(step as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("启用两步验证失败: {}", e)))?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        Self::write_recovery_codes_in_tx(&mut tx, user_id, code_hashes).await?;
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(true)
+    }
+
+    /// 删除密钥与全部恢复码（用户关闭 / 管理员重置）；原本没有配置返回 false
+    pub async fn delete(&self, user_id: u64) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        sqlx::query("DELETE FROM privchat_user_recovery_codes WHERE user_id = $1")
+            .bind(user_id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除恢复码失败: {}", e)))?;
+        let deleted = sqlx::query("DELETE FROM privchat_user_totp WHERE user_id = $1")
+            .bind(user_id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除两步验证配置失败: {}", e)))?
+            .rows_affected();
+
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(deleted > 0)
+    }
+
+    /// 占用时间步：只有比上次更新的步才能通过，同一个动态码（含并发提交）只生效一次
+    pub async fn consume_step(&self, user_id: u64, step: u64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_user_totp
+            SET last_used_step = $2
+            WHERE user_id = $1 AND enabled = TRUE AND last_used_step < $2
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(step as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新动态码时间步失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 整批替换恢复码（重新生成后旧码全部失效）
+    pub async fn replace_recovery_codes(&self, user_id: u64, code_hashes: &[String]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+        Self::write_recovery_codes_in_tx(&mut tx, user_id, code_hashes).await?;
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    async fn write_recovery_codes_in_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user_id: u64,
+        code_hashes: &[String],
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM privchat_user_recovery_codes WHERE user_id = $1")
+            .bind(user_id as i64)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除旧恢复码失败: {}", e)))?;
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_user_recovery_codes (user_id, code_hash)
+            SELECT $1, UNNEST($2::VARCHAR[])
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(code_hashes)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入恢复码失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 核销一个恢复码；不存在或已用过返回 false
+    pub async fn consume_recovery_code(&self, user_id: u64, code_hash: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_user_recovery_codes
+            SET used_at = now_millis()
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("核销恢复码失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 剩余可用的恢复码个数
+    pub async fn count_remaining_recovery_codes(&self, user_id: u64) -> Result<u32> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM privchat_user_recovery_codes
+            WHERE user_id = $1 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("统计恢复码失败: {}", e)))?;
+        Ok(count as u32)
+    }
+}
@@ -205,6 +205,34 @@ impl UserDeviceRepository {
         Ok(())
     }
 
+    /// 停用已失效的推送 token（厂商明确返回 token 已注销 / 不存在）
+    ///
+    /// 🔴 只在库里仍是这个 token 时才清空：设备可能已上报新 token，
+    /// 旧任务的失败结果不能把新 token 一起抹掉。
+    pub async fn invalidate_push_token(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        push_token: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_user_devices
+            SET push_token = NULL,
+                updated_at = NOW()
+            WHERE user_id = $1 AND device_id = $2 AND push_token = $3
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(device_id)
+        .bind(push_token)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("停用推送 token 失败: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     async fn query_platform(&self, user_id: u64, device_id: &str) -> Result<Option<String>> {
         let row = sqlx::query_scalar::<_, String>(
             r#"
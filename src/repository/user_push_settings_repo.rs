@@ -0,0 +1,177 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use sqlx::PgPool;
+
+/// 默认推送语言
+pub const DEFAULT_PUSH_LOCALE: &str = "zh-CN";
+/// 默认时区（IANA 名称）
+pub const DEFAULT_PUSH_TIMEZONE: &str = "UTC";
+
+/// 用户级推送偏好
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserPushSettings {
+    pub user_id: u64,
+    /// BCP 47 语言标签（如 `zh-CN` / `en`）
+    pub locale: String,
+    /// 隐藏通知预览：不显示发送者、群名与正文
+    pub hide_preview: bool,
+    /// 是否开启免打扰时段
+    pub dnd_enabled: bool,
+    /// 免打扰开始（用户时区内一天中的分钟数，0..1440）
+    pub dnd_start_minute: u16,
+    /// 免打扰结束（小于开始时表示跨零点）
+    pub dnd_end_minute: u16,
+    /// IANA 时区名（如 `Asia/Shanghai`）
+    pub timezone: String,
+    pub updated_at: i64,
+}
+
+impl UserPushSettings {
+    /// 用户没有设置过时的默认值
+    pub fn default_for(user_id: u64) -> Self {
+        Self {
+            user_id,
+            locale: DEFAULT_PUSH_LOCALE.to_string(),
+            hide_preview: false,
+            dnd_enabled: false,
+            dnd_start_minute: 22 * 60,
+            dnd_end_minute: 8 * 60,
+            timezone: DEFAULT_PUSH_TIMEZONE.to_string(),
+            updated_at: 0,
+        }
+    }
+}
+
+/// 推送偏好的部分更新（`None` 字段保持原值）
+#[derive(Debug, Clone, Default)]
+pub struct UserPushSettingsUpdate {
+    pub locale: Option<String>,
+    pub hide_preview: Option<bool>,
+    pub dnd_enabled: Option<bool>,
+    pub dnd_start_minute: Option<u16>,
+    pub dnd_end_minute: Option<u16>,
+    pub timezone: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct Row {
+    user_id: i64,
+    locale: String,
+    hide_preview: bool,
+    dnd_enabled: bool,
+    dnd_start_minute: i16,
+    dnd_end_minute: i16,
+    timezone: String,
+    updated_at: i64,
+}
+
+impl From<Row> for UserPushSettings {
+    fn from(row: Row) -> Self {
+        Self {
+            user_id: row.user_id as u64,
+            locale: row.locale,
+            hide_preview: row.hide_preview,
+            dnd_enabled: row.dnd_enabled,
+            dnd_start_minute: row.dnd_start_minute as u16,
+            dnd_end_minute: row.dnd_end_minute as u16,
+            timezone: row.timezone,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// 用户推送偏好 Repository
+pub struct UserPushSettingsRepository {
+    pool: PgPool,
+}
+
+impl UserPushSettingsRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// 读取推送偏好；没有行时返回默认值
+    pub async fn get(&self, user_id: u64) -> Result<UserPushSettings> {
+        let row = sqlx::query_as::<_, Row>(
+            r#"
+            SELECT user_id, locale, hide_preview, dnd_enabled, dnd_start_minute,
+                   dnd_end_minute, timezone, updated_at
+            FROM privchat_user_push_settings
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id as i64)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询推送偏好失败: {}", e)))?;
+
+        Ok(row
+            .map(UserPushSettings::from)
+            .unwrap_or_else(|| UserPushSettings::default_for(user_id)))
+    }
+
+    /// 部分更新推送偏好，返回更新后的完整设置
+    pub async fn upsert(
+        &self,
+        user_id: u64,
+        update: &UserPushSettingsUpdate,
+    ) -> Result<UserPushSettings> {
+        let row = sqlx::query_as::<_, Row>(
+            r#"
+            INSERT INTO privchat_user_push_settings (
+                user_id, locale, hide_preview, dnd_enabled, dnd_start_minute,
+                dnd_end_minute, timezone, updated_at
+            )
+            VALUES (
+                $1,
+                COALESCE($2, 'zh-CN'),
+                COALESCE($3, FALSE),
+                COALESCE($4, FALSE),
+                COALESCE($5, 1320),
+                COALESCE($6, 480),
+                COALESCE($7, 'UTC'),
+                now_millis()
+            )
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                locale = COALESCE($2, privchat_user_push_settings.locale),
+                hide_preview = COALESCE($3, privchat_user_push_settings.hide_preview),
+                dnd_enabled = COALESCE($4, privchat_user_push_settings.dnd_enabled),
+                dnd_start_minute = COALESCE($5, privchat_user_push_settings.dnd_start_minute),
+                dnd_end_minute = COALESCE($6, privchat_user_push_settings.dnd_end_minute),
+                timezone = COALESCE($7, privchat_user_push_settings.timezone),
+                updated_at = now_millis()
+            RETURNING user_id, locale, hide_preview, dnd_enabled, dnd_start_minute,
+                      dnd_end_minute, timezone, updated_at
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(update.locale.as_deref())
+        .bind(update.hide_preview)
+        .bind(update.dnd_enabled)
+        .bind(update.dnd_start_minute.map(|m| m as i16))
+        .bind(update.dnd_end_minute.map(|m| m as i16))
+        .bind(update.timezone.as_deref())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新推送偏好失败: {}", e)))?;
+
+        Ok(row.into())
+    }
+}
@@ -18,6 +18,7 @@
 //! 用户仓库 - PostgreSQL 实现
 
 use crate::error::DatabaseError;
+use crate::infra::read_pool;
 use crate::model::user::User;
 use crate::rpc::qr::{generate_qr_key, is_qr_key_unique_violation, QR_KEY_MAX_GENERATION_RETRIES};
 use sqlx::PgPool;
@@ -682,6 +683,35 @@ impl UserRepository {
         Ok(user.clone())
     }
 
+    /// 只更新密码哈希（改密 / 找回密码）
+    pub async fn update_password_hash(
+        &self,
+        user_id: u64,
+        password_hash: &str,
+    ) -> Result<(), DatabaseError> {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let rows_affected = sqlx::query(
+            r#"
+            UPDATE privchat_users
+            SET password_hash = $2, updated_at = $3
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(password_hash)
+        .bind(now)
+        .execute(self.pool.as_ref())
+        .await
+        .map_err(|e| DatabaseError::Database(format!("Failed to update password: {}", e)))?;
+
+        if rows_affected.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound("User not found".to_string()));
+        }
+
+        Ok(())
+    }
+
     /// 更新用户最后登录时间
     pub async fn update_last_login(
         &self,
@@ -854,7 +884,7 @@ impl UserRepository {
         .bind(page_size as i64)
         .bind(offset as i64)
         .bind(user_type)
-        .fetch_all(self.pool.as_ref())
+        .fetch_all(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("Failed to query users: {}", e)))?;
 
@@ -886,7 +916,7 @@ impl UserRepository {
             "SELECT COUNT(*) FROM privchat_users WHERE ($1::int2 IS NULL OR user_type = $1)",
         )
         .bind(user_type)
-        .fetch_one(self.pool.as_ref())
+        .fetch_one(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("Failed to count users: {}", e)))?;
 
@@ -1012,7 +1042,7 @@ impl UserRepository {
             "#,
         )
         .bind(search_pattern)
-        .fetch_all(self.pool.as_ref())
+        .fetch_all(read_pool(&self.pool))
         .await
         .map_err(|e| DatabaseError::Database(format!("Failed to search users: {}", e)))?;
 
@@ -0,0 +1,416 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::{Result, ServerError};
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// 投递状态：待投递 / 重试中
+pub const DELIVERY_STATUS_PENDING: &str = "pending";
+/// 投递状态：已成功（对端返回 2xx）
+pub const DELIVERY_STATUS_SUCCEEDED: &str = "succeeded";
+/// 投递状态：死信（超过最大尝试次数，等待人工重放）
+pub const DELIVERY_STATUS_DEAD: &str = "dead";
+
+/// Webhook 端点（`secret` 只在创建 / 轮换时返回给管理员，序列化时跳过）
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookEndpoint {
+    pub endpoint_id: i64,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub description: String,
+    pub is_enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 投递记录（列表不带 body，详情才带）
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub delivery_id: i64,
+    pub endpoint_id: i64,
+    pub event_id: String,
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// worker 领取到的一次投递（带端点地址和签名密钥）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClaimedWebhookDelivery {
+    pub delivery_id: i64,
+    pub endpoint_id: i64,
+    pub event_id: String,
+    pub event_type: String,
+    pub body: String,
+    /// 含本次在内的尝试次数
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+/// 新建端点
+#[derive(Debug, Clone)]
+pub struct NewWebhookEndpoint {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub description: String,
+    pub is_enabled: bool,
+}
+
+/// 端点的部分更新（`None` 字段保持原值）
+#[derive(Debug, Clone, Default)]
+pub struct WebhookEndpointUpdate {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub is_enabled: Option<bool>,
+}
+
+/// 投递记录查询条件（按 delivery_id 倒序翻页）
+#[derive(Debug, Clone, Default)]
+pub struct WebhookDeliveryFilter {
+    pub endpoint_id: Option<u64>,
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub before_id: Option<u64>,
+    pub limit: u32,
+}
+
+const ENDPOINT_COLUMNS: &str = "endpoint_id, url, secret, event_types, description, is_enabled, \
+     created_at, updated_at";
+
+const DELIVERY_COLUMNS: &str = "delivery_id, endpoint_id, event_id, event_type, status, attempts, \
+     next_attempt_at, last_status_code, last_error, delivered_at, created_at, updated_at";
+
+/// Webhook 端点 + 投递日志 Repository
+pub struct WebhookRepository {
+    pool: PgPool,
+}
+
+impl WebhookRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list_endpoints(&self) -> Result<Vec<WebhookEndpoint>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_webhook_endpoints ORDER BY endpoint_id",
+            ENDPOINT_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookEndpoint>(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询 Webhook 端点失败: {}", e)))
+    }
+
+    pub async fn get_endpoint(&self, endpoint_id: u64) -> Result<Option<WebhookEndpoint>> {
+        let sql = format!(
+            "SELECT {} FROM privchat_webhook_endpoints WHERE endpoint_id = $1",
+            ENDPOINT_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookEndpoint>(&sql)
+            .bind(endpoint_id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询 Webhook 端点失败: {}", e)))
+    }
+
+    pub async fn create_endpoint(&self, endpoint: &NewWebhookEndpoint) -> Result<WebhookEndpoint> {
+        let sql = format!(
+            "INSERT INTO privchat_webhook_endpoints (url, secret, event_types, description, is_enabled) \
+             VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+            ENDPOINT_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookEndpoint>(&sql)
+            .bind(&endpoint.url)
+            .bind(&endpoint.secret)
+            .bind(&endpoint.event_types)
+            .bind(&endpoint.description)
+            .bind(endpoint.is_enabled)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("创建 Webhook 端点失败: {}", e)))
+    }
+
+    /// 部分更新；端点不存在时返回 None
+    pub async fn update_endpoint(
+        &self,
+        endpoint_id: u64,
+        update: &WebhookEndpointUpdate,
+    ) -> Result<Option<WebhookEndpoint>> {
+        let sql = format!(
+            r#"
+            UPDATE privchat_webhook_endpoints SET
+                url = COALESCE($2, url),
+                secret = COALESCE($3, secret),
+                event_types = COALESCE($4, event_types),
+                description = COALESCE($5, description),
+                is_enabled = COALESCE($6, is_enabled),
+                updated_at = now_millis()
+            WHERE endpoint_id = $1
+            RETURNING {}
+            "#,
+            ENDPOINT_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookEndpoint>(&sql)
+            .bind(endpoint_id as i64)
+            .bind(update.url.as_deref())
+            .bind(update.secret.as_deref())
+            .bind(update.event_types.as_ref())
+            .bind(update.description.as_deref())
+            .bind(update.is_enabled)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("更新 Webhook 端点失败: {}", e)))
+    }
+
+    /// 删除端点（投递日志随外键级联删除）
+    pub async fn delete_endpoint(&self, endpoint_id: u64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM privchat_webhook_endpoints WHERE endpoint_id = $1")
+            .bind(endpoint_id as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除 Webhook 端点失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 所有启用端点订阅的事件类型（去重）
+    pub async fn subscribed_event_types(&self) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT unnest(event_types) FROM privchat_webhook_endpoints
+            WHERE is_enabled
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Webhook 订阅失败: {}", e)))
+    }
+
+    /// 为订阅了 `event_type` 的每个启用端点写一行待投递记录，返回写入行数
+    pub async fn enqueue(&self, event_id: &str, event_type: &str, body: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO privchat_webhook_deliveries (endpoint_id, event_id, event_type, body)
+            SELECT endpoint_id, $1, $2, $3 FROM privchat_webhook_endpoints
+            WHERE is_enabled AND ($2 = ANY(event_types) OR '*' = ANY(event_types))
+            "#,
+        )
+        .bind(event_id)
+        .bind(event_type)
+        .bind(body)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("写入 Webhook 投递失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 领取到期投递：尝试次数 +1，并把 next_attempt_at 推后 `lease_ms` 作为租约。
+    ///
+    /// `SKIP LOCKED` 让多节点并发领取互不阻塞；停用端点的投递留在队列里，重新启用后继续。
+    pub async fn claim_due(
+        &self,
+        now_ms: i64,
+        lease_ms: i64,
+        limit: u32,
+    ) -> Result<Vec<ClaimedWebhookDelivery>> {
+        sqlx::query_as::<_, ClaimedWebhookDelivery>(
+            r#"
+            WITH due AS (
+                SELECT d.delivery_id FROM privchat_webhook_deliveries d
+                JOIN privchat_webhook_endpoints e ON e.endpoint_id = d.endpoint_id
+                WHERE d.status = 'pending' AND d.next_attempt_at <= $1 AND e.is_enabled
+                ORDER BY d.next_attempt_at
+                LIMIT $3
+                FOR UPDATE OF d SKIP LOCKED
+            )
+            UPDATE privchat_webhook_deliveries d SET
+                attempts = d.attempts + 1,
+                next_attempt_at = $1 + $2,
+                updated_at = $1
+            FROM due, privchat_webhook_endpoints e
+            WHERE d.delivery_id = due.delivery_id AND e.endpoint_id = d.endpoint_id
+            RETURNING d.delivery_id, d.endpoint_id, d.event_id, d.event_type, d.body,
+                      d.attempts, e.url, e.secret
+            "#,
+        )
+        .bind(now_ms)
+        .bind(lease_ms)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("领取 Webhook 投递失败: {}", e)))
+    }
+
+    /// 记录一次投递结果。
+    ///
+    /// `retry_at = None` 时：成功置 succeeded，失败置 dead；否则回到 pending 等待 `retry_at`。
+    /// 以领取时的 `attempts` 做 CAS——期间被管理员重放（attempts 归零）的记录不会被覆盖。
+    #[allow(clippy::too_many_arguments)]
+    pub async fn record_attempt(
+        &self,
+        delivery_id: i64,
+        attempts: i32,
+        succeeded: bool,
+        retry_at: Option<i64>,
+        status_code: Option<i32>,
+        error: Option<&str>,
+        now_ms: i64,
+    ) -> Result<()> {
+        let status = match (succeeded, retry_at) {
+            (true, _) => DELIVERY_STATUS_SUCCEEDED,
+            (false, Some(_)) => DELIVERY_STATUS_PENDING,
+            (false, None) => DELIVERY_STATUS_DEAD,
+        };
+        sqlx::query(
+            r#"
+            UPDATE privchat_webhook_deliveries SET
+                status = $3,
+                next_attempt_at = COALESCE($4, next_attempt_at),
+                last_status_code = $5,
+                last_error = $6,
+                delivered_at = CASE WHEN $3 = 'succeeded' THEN $7 ELSE delivered_at END,
+                updated_at = $7
+            WHERE delivery_id = $1 AND attempts = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(delivery_id)
+        .bind(attempts)
+        .bind(status)
+        .bind(retry_at)
+        .bind(status_code)
+        .bind(error)
+        .bind(now_ms)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("更新 Webhook 投递失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 按条件倒序列出投递记录（不含 body）
+    pub async fn list_deliveries(
+        &self,
+        filter: &WebhookDeliveryFilter,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let sql = format!(
+            r#"
+            SELECT {}, NULL::TEXT AS body FROM privchat_webhook_deliveries
+            WHERE ($1::BIGINT IS NULL OR endpoint_id = $1)
+              AND ($2::TEXT IS NULL OR status = $2)
+              AND ($3::TEXT IS NULL OR event_type = $3)
+              AND ($4::BIGINT IS NULL OR delivery_id < $4)
+            ORDER BY delivery_id DESC
+            LIMIT $5
+            "#,
+            DELIVERY_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookDelivery>(&sql)
+            .bind(filter.endpoint_id.map(|id| id as i64))
+            .bind(filter.status.as_deref())
+            .bind(filter.event_type.as_deref())
+            .bind(filter.before_id.map(|id| id as i64))
+            .bind(filter.limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询 Webhook 投递失败: {}", e)))
+    }
+
+    /// 投递详情（含 body）
+    pub async fn get_delivery(&self, delivery_id: u64) -> Result<Option<WebhookDelivery>> {
+        let sql = format!(
+            "SELECT {}, body FROM privchat_webhook_deliveries WHERE delivery_id = $1",
+            DELIVERY_COLUMNS
+        );
+        sqlx::query_as::<_, WebhookDelivery>(&sql)
+            .bind(delivery_id as i64)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询 Webhook 投递失败: {}", e)))
+    }
+
+    /// 重放单条投递（任意状态）：尝试次数归零、立即可领；记录不存在时返回 false
+    pub async fn replay_delivery(&self, delivery_id: u64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_webhook_deliveries SET
+                status = 'pending',
+                attempts = 0,
+                next_attempt_at = now_millis(),
+                last_error = NULL,
+                updated_at = now_millis()
+            WHERE delivery_id = $1
+            "#,
+        )
+        .bind(delivery_id as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("重放 Webhook 投递失败: {}", e)))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 批量重放死信（可限定端点），返回重放条数
+    pub async fn replay_dead(&self, endpoint_id: Option<u64>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE privchat_webhook_deliveries SET
+                status = 'pending',
+                attempts = 0,
+                next_attempt_at = now_millis(),
+                last_error = NULL,
+                updated_at = now_millis()
+            WHERE status = 'dead' AND ($1::BIGINT IS NULL OR endpoint_id = $1)
+            "#,
+        )
+        .bind(endpoint_id.map(|id| id as i64))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("重放 Webhook 死信失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+
+    /// 清理早于 `before_ms` 的成功投递（分批，返回删除行数）
+    pub async fn purge_succeeded(&self, before_ms: i64, batch: i64) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM privchat_webhook_deliveries WHERE delivery_id IN (
+                SELECT delivery_id FROM privchat_webhook_deliveries
+                WHERE status = 'succeeded' AND created_at < $1
+                LIMIT $2
+            )
+            "#,
+        )
+        .bind(before_ms)
+        .bind(batch)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ServerError::Database(format!("清理 Webhook 投递失败: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+}
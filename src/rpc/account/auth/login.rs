@@ -15,9 +15,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::auth::verify_password;
+use crate::auth::{verify_password, AMR_PASSWORD};
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
+use crate::service::PendingLogin;
 use privchat_protocol::rpc::auth::AuthLoginRequest;
 use serde_json::{json, Value};
 use uuid::Uuid;
@@ -28,7 +29,9 @@ use uuid::Uuid;
 /// - [`AccountMode::Builtin`](crate::config::AccountMode::Builtin)：使用 server 内置登录，返回 JWT token（独立部署）
 /// - [`AccountMode::Platform`](crate::config::AccountMode::Platform)：返回 forbidden，引导客户端走 platform 鉴权
 ///
-/// 登录成功后返回 JWT token，客户端可直接用于 AuthorizationRequest
+/// 登录成功后返回 JWT token，客户端可直接用于 AuthorizationRequest；
+/// 账号已启用两步验证时返回 `two_factor_required` + `challenge_token`，
+/// 由 `account/auth/login_2fa` 提交动态码或恢复码后签发 token
 pub async fn handle(
     body: Value,
     services: RpcServiceContext,
@@ -108,6 +111,58 @@ pub async fn handle(
         .map(|info| (info.app_id.clone(), info.device_type.as_str()))
         .unwrap_or_else(|| ("unknown".to_string(), "unknown"));
 
+    // 4.1 已启用两步验证：先不签 token，返回 challenge，客户端再调 account/auth/login_2fa
+    if services.two_factor_service.is_enabled(user_id).await? {
+        let pending = PendingLogin {
+            user_id,
+            device_id: device_id.to_string(),
+            app_id: app_id.clone(),
+            device_type: device_type_str.to_string(),
+        };
+        let (challenge_token, expires_at) = services
+            .two_factor_service
+            .create_challenge(&pending)
+            .await?;
+        tracing::debug!(
+            "🔐 需要两步验证: account={}, user_id={}, device_id={}",
+            account,
+            user_id,
+            device_id
+        );
+        return Ok(json!({
+            "success": false,
+            "two_factor_required": true,
+            "user_id": user_id,
+            "challenge_token": challenge_token,
+            "expires_at": expires_at,
+            "device_id": device_id,
+            "message": "需要两步验证"
+        }));
+    }
+
+    tracing::debug!("✅ 密码验证通过: account={}, user_id={}", account, user_id);
+    complete_login(
+        &services,
+        user_id,
+        device_id,
+        &app_id,
+        device_type_str,
+        vec![AMR_PASSWORD.to_string()],
+    )
+    .await
+}
+
+/// 登录验证（密码，及已启用时的两步验证）通过后：查询或创建设备，签发 access + refresh token
+///
+/// `amr` 写入 token，连接认证时据此在登录日志里记录是否使用了两步验证。
+pub(super) async fn complete_login(
+    services: &RpcServiceContext,
+    user_id: u64,
+    device_id: &str,
+    app_id: &str,
+    device_type_str: &str,
+    amr: Vec<String>,
+) -> RpcResult<Value> {
     // 5. 查询或创建设备，获取 session_version ✨
     let session_version = match services
         .device_manager_db
@@ -131,7 +186,7 @@ pub async fn handle(
                 user_id,
                 business_system_id: "privchat-internal".to_string(),
                 device_info: crate::auth::DeviceInfo {
-                    app_id: app_id.clone(),
+                    app_id: app_id.to_string(),
                     device_name: format!("{} Device", app_id),
                     device_model: "Unknown".to_string(),
                     os_version: "Unknown".to_string(),
@@ -178,15 +233,16 @@ pub async fn handle(
     // - session_version: 会话版本号（用于设备级撤销）✨ 新增
     // - jti: JWT ID（用于撤销）
     // - iss, aud, exp, iat: JWT 标准字段
+    // - amr: 认证方式（密码 / 两步验证）
     let token = services
         .jwt_service
-        .issue_token_with_version(
+        .issue_token_with_amr(
             user_id,
             device_id,
             "privchat-internal", // 内置认证系统的业务系统ID
-            &app_id,
+            app_id,
             session_version, // ✨ 使用设备的 session_version
-            None,            // 使用默认 TTL
+            amr.clone(),
         )
         .map_err(|e| RpcError::internal(format!("生成 JWT token 失败: {}", e)))?;
 
@@ -197,18 +253,18 @@ pub async fn handle(
     // 7.1 签发 refresh token（B1 non-rotation，typ=refresh，长 TTL）
     let refresh_token = services
         .jwt_service
-        .issue_refresh_token(
+        .issue_refresh_token_with_amr(
             user_id,
             device_id,
             "privchat-internal",
-            &app_id,
+            app_id,
             session_version,
+            amr,
         )
         .map_err(|e| RpcError::internal(format!("生成 refresh token 失败: {}", e)))?;
 
     tracing::debug!(
-        "✅ 用户登录成功: account={}, user_id={}, device_id={}, device_type={}, app_id={}",
-        account,
+        "✅ 用户登录成功: user_id={}, device_id={}, device_type={}, app_id={}",
         user_id,
         device_id,
         device_type_str,
@@ -0,0 +1,70 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::auth::AMR_PASSWORD;
+use crate::rpc::account::two_factor::parse_second_factor;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::Value;
+
+/// 处理 两步验证登录 请求（登录第二步）
+///
+/// `account/auth/login` 返回 `two_factor_required` 后调用，凭 challenge_token 提交
+/// 动态码或恢复码，通过后返回与 login 相同的 AuthResponse。
+///
+/// 请求：
+/// ```json
+/// { "challenge_token": "...", "code": "123456" }
+/// { "challenge_token": "...", "recovery_code": "abcd-efgh" }
+/// ```
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    _ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    if !services.config.account.is_builtin() {
+        return Err(RpcError::forbidden(
+            "account.mode=PLATFORM：server 内置登录已禁用".to_string(),
+        ));
+    }
+
+    let challenge_token = body
+        .get("challenge_token")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| RpcError::validation("challenge_token is required".to_string()))?;
+    let factor = parse_second_factor(&body)?;
+
+    let pending = services
+        .two_factor_service
+        .complete_challenge(challenge_token, &factor)
+        .await
+        .map_err(|e| {
+            tracing::warn!("❌ 两步验证失败: {}", e);
+            RpcError::from(e)
+        })?;
+
+    super::login::complete_login(
+        &services,
+        pending.user_id,
+        &pending.device_id,
+        &pending.app_id,
+        &pending.device_type,
+        vec![AMR_PASSWORD.to_string(), factor.amr().to_string()],
+    )
+    .await
+}
@@ -16,6 +16,7 @@
 // limitations under the License.
 
 pub mod login;
+pub mod login_2fa;
 pub mod logout;
 pub mod refresh;
 
@@ -34,6 +35,14 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    let services_login_2fa = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("account/auth/login_2fa", move |body, ctx| {
+            let services = services_login_2fa.clone();
+            Box::pin(async move { login_2fa::handle(body, services, ctx).await })
+        })
+        .await;
+
     let services_logout = services.clone();
     GLOBAL_RPC_ROUTER
         .register(routes::auth::LOGOUT, move |body, ctx| {
@@ -50,5 +59,5 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
-    tracing::debug!("🔧 Auth 模块路由注册完成（login/logout/refresh）");
+    tracing::debug!("🔧 Auth 模块路由注册完成（login/login_2fa/logout/refresh）");
 }
@@ -154,16 +154,16 @@ pub async fn handle(
         }
     }
 
-    // 4. 签发新的 access token（保持 session_version / business_system_id / app_id）
+    // 4. 签发新的 access token（保持 session_version / business_system_id / app_id / amr）
     let access_token = services
         .jwt_service
-        .issue_token_with_version(
+        .issue_token_with_amr(
             user_id,
             &device_id,
             &claims.business_system_id,
             &claims.app_id,
             session_version,
-            None,
+            claims.amr.clone(),
         )
         .map_err(|e| RpcError::internal(format!("签发 access token 失败: {}", e)))?;
 
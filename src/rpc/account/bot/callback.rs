@@ -0,0 +1,79 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! `account/bot/callback` —— 用户点击内联键盘按钮
+//!
+//! 回调必须对应消息上真实存在的按钮：消息由 Bot 发出、未撤回、调用者是会话成员，
+//! 且 `data` 与某个按钮的 `callback_data` 完全一致。通过后写入 Bot 更新队列
+//! （`callback_query`）并发 `bot.callback_queried` server event。
+
+use super::load_member_message;
+use crate::model::InlineKeyboardMarkup;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct BotCallbackRequest {
+    channel_id: u64,
+    message_id: u64,
+    data: String,
+}
+
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let request: BotCallbackRequest = serde_json::from_value(body)
+        .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let message = load_member_message(&services, request.channel_id, request.message_id, user_id)
+        .await?
+        .ok_or_else(|| RpcError::not_found("消息不存在".to_string()))?;
+    if message.revoked {
+        return Err(RpcError::validation("消息已被撤回".to_string()));
+    }
+    let has_button = InlineKeyboardMarkup::from_metadata(&message.metadata)
+        .is_some_and(|markup| markup.has_callback(&request.data));
+    if !has_button || !services.bot_service.is_bot(message.sender_id).await {
+        return Err(RpcError::validation("消息上没有对应的回调按钮".to_string()));
+    }
+
+    let callback_id = services
+        .bot_service
+        .record_callback(
+            message.sender_id,
+            user_id,
+            request.channel_id,
+            request.message_id,
+            &request.data,
+        )
+        .await
+        .map_err(|e| RpcError::internal(format!("记录按钮回调失败: {}", e)))?;
+
+    tracing::debug!(
+        "🤖 Bot 按钮回调: bot={}, user={}, message={}, callback_id={}",
+        message.sender_id,
+        user_id,
+        request.message_id,
+        callback_id
+    );
+
+    Ok(json!({ "callback_id": callback_id }))
+}
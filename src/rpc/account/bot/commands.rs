@@ -0,0 +1,54 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! `account/bot/commands` —— 查询 Bot 注册的斜杠命令（客户端输入 `/` 时展示）
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct BotCommandsRequest {
+    bot_user_id: u64,
+}
+
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let request: BotCommandsRequest = serde_json::from_value(body)
+        .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
+    crate::rpc::get_current_user_id(&ctx)?;
+
+    if !services.bot_service.is_bot(request.bot_user_id).await {
+        return Err(RpcError::not_found(format!(
+            "bot {} not found",
+            request.bot_user_id
+        )));
+    }
+    let commands = services
+        .bot_service
+        .get_commands(request.bot_user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询 Bot 命令失败: {}", e)))?;
+
+    Ok(json!({
+        "bot_user_id": request.bot_user_id,
+        "commands": commands,
+    }))
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! `account/bot/keyboards` —— 批量查询消息上的内联键盘
+//!
+//! 实时推送的消息 metadata 按协议类型解析，`reply_markup` 可能被丢弃；
+//! 客户端收到 Bot 消息后用这个接口补齐键盘。已撤回的消息不返回键盘。
+
+use super::load_member_message;
+use crate::model::InlineKeyboardMarkup;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// 单次最多查询的消息数
+const MAX_MESSAGE_IDS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct BotKeyboardsRequest {
+    channel_id: u64,
+    message_ids: Vec<u64>,
+}
+
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let request: BotKeyboardsRequest = serde_json::from_value(body)
+        .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    if request.message_ids.len() > MAX_MESSAGE_IDS {
+        return Err(RpcError::validation(format!(
+            "message_ids 最多 {} 个",
+            MAX_MESSAGE_IDS
+        )));
+    }
+
+    let mut keyboards = Vec::new();
+    for message_id in request.message_ids {
+        let Some(message) =
+            load_member_message(&services, request.channel_id, message_id, user_id).await?
+        else {
+            continue;
+        };
+        if message.revoked {
+            continue;
+        }
+        if let Some(markup) = InlineKeyboardMarkup::from_metadata(&message.metadata) {
+            keyboards.push(json!({
+                "message_id": message_id,
+                "reply_markup": markup,
+            }));
+        }
+    }
+
+    Ok(json!({
+        "channel_id": request.channel_id,
+        "keyboards": keyboards,
+    }))
+}
@@ -7,14 +7,20 @@
 
 //! `account/bot/*` RPC routes (spec `02-server/SERVICE_ACCOUNT_FOLLOW_SPEC`).
 
+pub mod callback;
+pub mod commands;
 pub mod follow;
+pub mod keyboards;
 pub mod unfollow;
 
 use super::super::router::GLOBAL_RPC_ROUTER;
 use super::super::RpcServiceContext;
+use crate::model::{channel::ChannelType, Message};
+use crate::repository::MessageRepository;
+use crate::rpc::error::{RpcError, RpcResult};
 use privchat_protocol::rpc::routes;
 
-/// 注册 Bot 关注 / 取消关注路由。
+/// 注册 Bot 关注 / 取消关注、命令与内联键盘回调路由。
 pub async fn register_routes(services: RpcServiceContext) {
     let router = GLOBAL_RPC_ROUTER.clone();
 
@@ -38,5 +44,62 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
-    tracing::debug!("📋 Bot follow 路由注册完成 (account/bot/follow + unfollow)");
+    router
+        .register("account/bot/commands", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { commands::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/bot/callback", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { callback::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/bot/keyboards", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { keyboards::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!(
+        "📋 Bot 路由注册完成 (account/bot/follow + unfollow + commands + callback + keyboards)"
+    );
+}
+
+/// 取会话内的一条消息：调用者必须是会话成员（Room 不支持 Bot 键盘）；
+/// 消息不存在或不属于该会话时返回 None
+pub(super) async fn load_member_message(
+    services: &RpcServiceContext,
+    channel_id: u64,
+    message_id: u64,
+    user_id: u64,
+) -> RpcResult<Option<Message>> {
+    let channel = services
+        .channel_service
+        .get_channel(&channel_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("会话不存在: {}", e)))?;
+    if channel.channel_type == ChannelType::Room || !channel.is_member(user_id) {
+        return Err(RpcError::forbidden("您不是该会话成员".to_string()));
+    }
+    let message = services
+        .message_repository
+        .as_ref()
+        .find_by_id(message_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询消息失败: {}", e)))?;
+    Ok(message.filter(|m| m.channel_id == channel_id))
 }
@@ -0,0 +1,76 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// 用户自助注销：发起（进入冷静期）/ 撤销 / 查询
+//
+// 最终注销由 AccountDeletionService 的后台任务在冷静期结束后执行。
+
+pub mod cancel;
+pub mod request;
+pub mod status;
+
+use super::super::router::GLOBAL_RPC_ROUTER;
+use super::super::RpcServiceContext;
+use crate::rpc::error::{RpcError, RpcResult};
+
+/// 注册注销模块的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    let router = GLOBAL_RPC_ROUTER.clone();
+
+    router
+        .register("account/deletion/request", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { request::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/deletion/cancel", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { cancel::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/deletion/status", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { status::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!("📋 AccountDeletion 模块路由注册完成 (request, cancel, status)");
+}
+
+/// 自助注销只对内置账号开放；PLATFORM 模式下账号生命周期由 platform 管理
+fn ensure_builtin(services: &RpcServiceContext) -> RpcResult<()> {
+    if services.config.account.is_builtin() {
+        Ok(())
+    } else {
+        Err(RpcError::forbidden(
+            "account.mode=PLATFORM：账号注销由 platform 负责".to_string(),
+        ))
+    }
+}
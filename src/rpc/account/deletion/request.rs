@@ -0,0 +1,69 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::auth::verify_password;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理 发起账号注销 请求
+///
+/// RPC: account/deletion/request
+///
+/// 请求：`{ "password": "..." }`（账号设置了密码时必填）
+///
+/// 登记注销并进入冷静期，冷静期内账号照常可用，可调用 `account/deletion/cancel` 撤销；
+/// 重复发起不会顺延冷静期。
+///
+/// 响应：`{ "scheduled": true, "requested_at": 1768200000000, "execute_at": 1769409600000 }`
+pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let user_id = get_current_user_id(&ctx)?;
+
+    let user = services
+        .user_repository
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询用户失败: {}", e)))?
+        .ok_or_else(|| RpcError::not_found("用户不存在".to_string()))?;
+
+    // 设置了密码的账号必须再次确认密码，防止拿到登录态的他人直接注销
+    if let Some(password_hash) = user.password_hash {
+        let password = body
+            .get("password")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .ok_or_else(|| RpcError::validation("password is required".to_string()))?;
+        let valid = tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+            .await
+            .map_err(|e| RpcError::internal(format!("密码验证任务调度失败: {}", e)))?
+            .map_err(|e| RpcError::internal(format!("密码验证失败: {}", e)))?;
+        if !valid {
+            tracing::warn!("❌ 账号注销: 密码错误 user_id={}", user_id);
+            return Err(RpcError::validation("密码错误".to_string()));
+        }
+    }
+
+    let status = services
+        .account_deletion_service
+        .request(user_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!(status))
+}
@@ -0,0 +1,40 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理 查询账号注销状态 请求
+///
+/// RPC: account/deletion/status
+///
+/// 响应：`{ "scheduled": false, "requested_at": null, "execute_at": null }`
+pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 查询账号注销状态 请求: {:?}", body);
+
+    super::ensure_builtin(&services)?;
+    let user_id = get_current_user_id(&ctx)?;
+
+    let status = services
+        .account_deletion_service
+        .status(user_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!(status))
+}
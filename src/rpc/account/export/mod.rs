@@ -0,0 +1,54 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// 个人数据导出：发起 / 查询
+//
+// 归档由 DataExportService 的后台任务生成，经文件服务器
+// `GET /api/app/account/exports/{export_id}` 下载。
+
+pub mod request;
+pub mod status;
+
+use super::super::router::GLOBAL_RPC_ROUTER;
+use super::super::RpcServiceContext;
+
+/// 注册数据导出模块的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    let router = GLOBAL_RPC_ROUTER.clone();
+
+    router
+        .register("account/export/request", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { request::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/export/status", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { status::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!("📋 DataExport 模块路由注册完成 (request, status)");
+}
@@ -0,0 +1,46 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理 发起个人数据导出 请求
+///
+/// RPC: account/export/request
+///
+/// 排队生成归档；已有排队中 / 生成中的导出时直接返回该任务。
+///
+/// 响应：
+/// ```json
+/// { "export_id": 12, "status": "pending", "size_bytes": null, "error": null,
+///   "created_at": 1768200000000, "completed_at": null, "expires_at": null,
+///   "download_path": null }
+/// ```
+pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 发起个人数据导出 请求: {:?}", body);
+
+    let user_id = get_current_user_id(&ctx)?;
+
+    let export = services
+        .data_export_service
+        .request(user_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!(export))
+}
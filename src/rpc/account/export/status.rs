@@ -0,0 +1,52 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理 查询个人数据导出 请求
+///
+/// RPC: account/export/status
+///
+/// 请求：`{ "export_id": 12 }`（可选，不传返回最近的导出任务）
+///
+/// 响应：`{ "exports": [ { "export_id": 12, "status": "ready", ...,
+/// "download_path": "/api/app/account/exports/12" } ] }`
+pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 查询个人数据导出 请求: {:?}", body);
+
+    let user_id = get_current_user_id(&ctx)?;
+    let exports = match body.get("export_id").and_then(|v| v.as_u64()) {
+        Some(export_id) => {
+            let export = services
+                .data_export_service
+                .get(user_id, export_id)
+                .await
+                .map_err(RpcError::from)?
+                .ok_or_else(|| RpcError::not_found(format!("导出任务 {} 不存在", export_id)))?;
+            vec![export]
+        }
+        None => services
+            .data_export_service
+            .list(user_id)
+            .await
+            .map_err(RpcError::from)?,
+    };
+
+    Ok(json!({ "exports": exports }))
+}
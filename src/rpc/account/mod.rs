@@ -17,9 +17,14 @@
 
 pub mod auth;
 pub mod bot;
+pub mod deletion;
+pub mod export;
+pub mod notification;
+pub mod password;
 pub mod privacy;
 pub mod profile;
 pub mod search;
+pub mod two_factor;
 pub mod user;
 
 use super::RpcServiceContext;
@@ -30,9 +35,14 @@ pub async fn register_routes(services: RpcServiceContext) {
     auth::register_routes(services.clone()).await; // 测试用的认证接口
     search::register_routes(services.clone()).await; // 用户搜索接口
     privacy::register_routes(services.clone()).await; // 隐私设置接口
+    notification::register_routes(services.clone()).await; // 推送偏好（语言 / 隐藏预览 / 免打扰）
+    two_factor::register_routes(services.clone()).await; // 两步验证（TOTP / 恢复码）
+    password::register_routes(services.clone()).await; // 修改密码 / 找回密码
+    deletion::register_routes(services.clone()).await; // 自助注销（冷静期 / 撤销）
+    export::register_routes(services.clone()).await; // 个人数据导出
     bot::register_routes(services.clone()).await; // Bot 关注 / 取消关注（spec SERVICE_ACCOUNT_FOLLOW_SPEC）
                                                   // TODO: 暂时注释 profile 模块
                                                   // profile::register_routes(services.clone()).await;
 
-    tracing::debug!("📋 Account 系统路由注册完成 (user, auth, search, privacy, bot 模块)");
+    tracing::debug!("📋 Account 系统路由注册完成 (user, auth, search, privacy, notification, two_factor, password, deletion, export, bot 模块)");
 }
@@ -0,0 +1,55 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::Value;
+
+/// 处理 获取推送偏好 请求
+///
+/// RPC: account/notification/get
+///
+/// 响应：
+/// ```json
+/// {
+///   "user_id": 1001,
+///   "locale": "zh-CN",
+///   "hide_preview": false,
+///   "dnd_enabled": true,
+///   "dnd_start_minute": 1320,
+///   "dnd_end_minute": 480,
+///   "timezone": "Asia/Shanghai",
+///   "updated_at": 1768200000000
+/// }
+/// ```
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 获取推送偏好 请求: {:?}", body);
+
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let settings = services
+        .user_push_settings_repo
+        .get(user_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(super::settings_to_json(&settings))
+}
@@ -0,0 +1,66 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// 用户级推送偏好（通知语言 / 隐藏预览 / 免打扰时段）
+
+pub mod get;
+pub mod update;
+
+use super::super::router::GLOBAL_RPC_ROUTER;
+use super::super::RpcServiceContext;
+use crate::repository::UserPushSettings;
+use serde_json::{json, Value};
+
+/// 注册推送偏好模块的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    let router = GLOBAL_RPC_ROUTER.clone();
+
+    router
+        .register("account/notification/get", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { get::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/notification/update", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { update::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!("📋 Notification 模块路由注册完成 (get, update)");
+}
+
+fn settings_to_json(settings: &UserPushSettings) -> Value {
+    json!({
+        "user_id": settings.user_id,
+        "locale": settings.locale,
+        "hide_preview": settings.hide_preview,
+        "dnd_enabled": settings.dnd_enabled,
+        "dnd_start_minute": settings.dnd_start_minute,
+        "dnd_end_minute": settings.dnd_end_minute,
+        "timezone": settings.timezone,
+        "updated_at": settings.updated_at,
+    })
+}
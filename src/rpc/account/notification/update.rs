@@ -0,0 +1,155 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::repository::UserPushSettingsUpdate;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// locale 最大长度（与表字段 VARCHAR(16) 一致）
+const MAX_LOCALE_LEN: usize = 16;
+/// 一天的分钟数（免打扰时段取值范围 0..1440）
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+#[derive(Debug, Deserialize)]
+struct NotificationUpdateRequest {
+    #[serde(default)]
+    locale: Option<String>,
+    #[serde(default)]
+    hide_preview: Option<bool>,
+    #[serde(default)]
+    dnd_enabled: Option<bool>,
+    #[serde(default)]
+    dnd_start_minute: Option<u16>,
+    #[serde(default)]
+    dnd_end_minute: Option<u16>,
+    #[serde(default)]
+    timezone: Option<String>,
+}
+
+/// 处理 更新推送偏好 请求
+///
+/// RPC: account/notification/update
+///
+/// 请求参数（只更新提供的字段）：
+/// ```json
+/// {
+///   "locale": "en-US",            // 可选，BCP 47 语言标签
+///   "hide_preview": true,         // 可选，通知中隐藏发送者与内容
+///   "dnd_enabled": true,          // 可选，免打扰时段内静默推送
+///   "dnd_start_minute": 1320,     // 可选，22:00（用户时区内一天中的分钟数）
+///   "dnd_end_minute": 480,        // 可选，08:00，小于开始表示跨零点
+///   "timezone": "Asia/Shanghai"   // 可选，IANA 时区名
+/// }
+/// ```
+///
+/// 响应：同 account/notification/get
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 更新推送偏好 请求: {:?}", body);
+
+    let request: NotificationUpdateRequest = serde_json::from_value(body)
+        .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let locale = request
+        .locale
+        .as_deref()
+        .map(str::trim)
+        .map(|locale| {
+            if is_valid_locale(locale) {
+                Ok(locale)
+            } else {
+                Err(RpcError::validation(format!("不支持的 locale: {}", locale)))
+            }
+        })
+        .transpose()?;
+
+    for minute in [request.dnd_start_minute, request.dnd_end_minute]
+        .into_iter()
+        .flatten()
+    {
+        if minute >= MINUTES_PER_DAY {
+            return Err(RpcError::validation(format!(
+                "免打扰时间超出范围（0-1439 分钟）: {}",
+                minute
+            )));
+        }
+    }
+
+    let timezone = request
+        .timezone
+        .as_deref()
+        .map(str::trim)
+        .map(|timezone| {
+            if timezone.parse::<chrono_tz::Tz>().is_ok() {
+                Ok(timezone.to_string())
+            } else {
+                Err(RpcError::validation(format!("不支持的时区: {}", timezone)))
+            }
+        })
+        .transpose()?;
+
+    let update = UserPushSettingsUpdate {
+        locale: locale.map(str::to_string),
+        hide_preview: request.hide_preview,
+        dnd_enabled: request.dnd_enabled,
+        dnd_start_minute: request.dnd_start_minute,
+        dnd_end_minute: request.dnd_end_minute,
+        timezone,
+    };
+    let settings = services
+        .user_push_settings_repo
+        .upsert(user_id, &update)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 推送偏好更新成功: user_id={}, locale={}, hide_preview={}, dnd_enabled={}",
+        user_id,
+        settings.locale,
+        settings.hide_preview,
+        settings.dnd_enabled
+    );
+    Ok(super::settings_to_json(&settings))
+}
+
+fn is_valid_locale(locale: &str) -> bool {
+    !locale.is_empty()
+        && locale.len() <= MAX_LOCALE_LEN
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_validation() {
+        assert!(is_valid_locale("zh-CN"));
+        assert!(is_valid_locale("zh_Hant_TW"));
+        assert!(!is_valid_locale(""));
+        assert!(!is_valid_locale("en US"));
+        assert!(!is_valid_locale("a-very-long-locale-tag"));
+    }
+}
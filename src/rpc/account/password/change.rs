@@ -0,0 +1,159 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::auth::verify_password;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+fn parse_session_id(raw: &str) -> RpcResult<msgtrans::SessionId> {
+    let id = raw
+        .strip_prefix("session-")
+        .unwrap_or(raw)
+        .parse::<u64>()
+        .map_err(|e| RpcError::validation(format!("invalid session_id '{}': {}", raw, e)))?;
+    Ok(msgtrans::SessionId::from(id))
+}
+
+/// 处理 修改密码 请求
+///
+/// RPC: account/password/change
+///
+/// 请求：`{ "old_password": "...", "new_password": "..." }`
+///
+/// 校验原密码后写入新密码，bump 全部设备的 session_version 并断开其它设备；
+/// 当前设备用新的 session_version 重新签发 token（沿用当前 token 的 app_id / amr）。
+///
+/// 响应：`{ "success": true, "token", "refresh_token", "expires_at", "device_id", "sessions_revoked" }`
+pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let user_id = get_current_user_id(&ctx)?;
+    let old_password = super::required_str(&body, "old_password")?;
+    let new_password = super::required_str(&body, "new_password")?;
+    super::validate_new_password(&new_password)?;
+    if old_password == new_password {
+        return Err(RpcError::validation("新密码不能与原密码相同".to_string()));
+    }
+
+    // 当前连接绑定的 token 声明：重新签发时沿用 app_id / business_system_id / amr
+    let session_id = ctx
+        .session_id
+        .as_deref()
+        .ok_or_else(|| RpcError::unauthorized("missing session_id".to_string()))
+        .and_then(parse_session_id)?;
+    let session = services
+        .auth_session_manager
+        .get_session_info(&session_id)
+        .await
+        .ok_or_else(|| RpcError::unauthorized("会话不存在".to_string()))?;
+    let device_id = session.device_id.clone();
+    let claims = session.jwt_claims;
+
+    // 1. 校验原密码
+    let user = services
+        .user_repository
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询用户失败: {}", e)))?
+        .ok_or_else(|| RpcError::not_found("用户不存在".to_string()))?;
+    let password_hash = user.password_hash.ok_or_else(|| {
+        RpcError::validation("此账号未设置密码，请使用外部认证系统登录".to_string())
+    })?;
+    let valid = tokio::task::spawn_blocking(move || verify_password(&old_password, &password_hash))
+        .await
+        .map_err(|e| RpcError::internal(format!("密码验证任务调度失败: {}", e)))?
+        .map_err(|e| RpcError::internal(format!("密码验证失败: {}", e)))?;
+    if !valid {
+        tracing::warn!("❌ 修改密码: 原密码错误 user_id={}", user_id);
+        return Err(RpcError::validation("原密码错误".to_string()));
+    }
+
+    // 2. 写入新密码，作废尚未使用的找回密码令牌
+    let new_hash = super::hash_password_blocking(new_password).await?;
+    services
+        .user_repository
+        .update_password_hash(user_id, &new_hash)
+        .await
+        .map_err(|e| RpcError::internal(format!("更新密码失败: {}", e)))?;
+    if let Err(e) = services
+        .password_reset_service
+        .revoke_pending(user_id)
+        .await
+    {
+        tracing::warn!("⚠️ 作废找回密码令牌失败: user_id={}, err={}", user_id, e);
+    }
+
+    // 3. 旧 token 全部失效，断开其它设备
+    let sessions_revoked = services
+        .device_manager_db
+        .bump_user_sessions(user_id)
+        .await
+        .map_err(RpcError::from)?;
+    let disconnected = services
+        .connection_manager
+        .disconnect_other_devices(user_id, &device_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    // 4. 当前设备按新 session_version 重新签发
+    let (_, session_version) = services
+        .device_manager_db
+        .get_device_with_version(user_id, &device_id)
+        .await
+        .map_err(RpcError::from)?
+        .ok_or_else(|| RpcError::not_found("设备不存在".to_string()))?;
+    let token = services
+        .jwt_service
+        .issue_token_with_amr(
+            user_id,
+            &device_id,
+            &claims.business_system_id,
+            &claims.app_id,
+            session_version,
+            claims.amr.clone(),
+        )
+        .map_err(|e| RpcError::internal(format!("生成 JWT token 失败: {}", e)))?;
+    let refresh_token = services
+        .jwt_service
+        .issue_refresh_token_with_amr(
+            user_id,
+            &device_id,
+            &claims.business_system_id,
+            &claims.app_id,
+            session_version,
+            claims.amr,
+        )
+        .map_err(|e| RpcError::internal(format!("生成 refresh token 失败: {}", e)))?;
+    let expires_at = (chrono::Utc::now()
+        + chrono::Duration::seconds(services.jwt_service.default_ttl()))
+    .timestamp_millis();
+
+    tracing::info!(
+        "🔑 密码已修改: user_id={}, device_id={}, sessions_revoked={}, disconnected={}",
+        user_id,
+        device_id,
+        sessions_revoked,
+        disconnected.len()
+    );
+    Ok(json!({
+        "success": true,
+        "token": token,
+        "refresh_token": refresh_token,
+        "expires_at": expires_at,
+        "device_id": device_id,
+        "sessions_revoked": sessions_revoked,
+    }))
+}
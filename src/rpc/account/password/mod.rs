@@ -0,0 +1,106 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// 内置账号密码：登录态修改密码 / 通过一次性令牌找回密码
+//
+// 两条路径改完密码都会 bump 全部设备的 session_version，旧 token 随即失效；
+// 修改密码时为当前设备重新签发 token，调用方无需重新登录。
+
+pub mod change;
+pub mod reset_confirm;
+pub mod reset_request;
+
+use super::super::router::GLOBAL_RPC_ROUTER;
+use super::super::RpcServiceContext;
+use crate::auth::{hash_password, MIN_PASSWORD_LENGTH};
+use crate::rpc::error::{RpcError, RpcResult};
+use serde_json::Value;
+
+/// 注册密码模块的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    let router = GLOBAL_RPC_ROUTER.clone();
+
+    router
+        .register("account/password/change", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { change::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/password/reset/request", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { reset_request::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/password/reset/confirm", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { reset_confirm::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!("📋 Password 模块路由注册完成 (change, reset/request, reset/confirm)");
+}
+
+/// 密码只对内置账号开放；PLATFORM 模式下账号与登录都不归 server 管
+fn ensure_builtin(services: &RpcServiceContext) -> RpcResult<()> {
+    if services.config.account.is_builtin() {
+        Ok(())
+    } else {
+        Err(RpcError::forbidden(
+            "account.mode=PLATFORM：密码由 platform 负责".to_string(),
+        ))
+    }
+}
+
+/// 读取必填的字符串字段（不 trim，密码允许首尾空白）
+fn required_str(body: &Value, name: &str) -> RpcResult<String> {
+    body.get(name)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .ok_or_else(|| RpcError::validation(format!("{} is required", name)))
+}
+
+/// 校验新密码长度
+fn validate_new_password(password: &str) -> RpcResult<()> {
+    if password.len() < MIN_PASSWORD_LENGTH {
+        return Err(RpcError::validation(format!(
+            "密码至少需要 {} 个字符",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// bcrypt 是 CPU 密集运算，放 spawn_blocking（见 account/auth/login）
+async fn hash_password_blocking(password: String) -> RpcResult<String> {
+    tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(|e| RpcError::internal(format!("密码加密任务调度失败: {}", e)))?
+        .map_err(|e| RpcError::internal(format!("密码加密失败: {}", e)))
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 重置密码 请求（匿名）
+///
+/// RPC: account/password/reset/confirm
+///
+/// 请求：`{ "token": "...", "new_password": "..." }`
+///
+/// 令牌一次性有效；重置后全部设备的旧 token 失效并断开连接，用户需用新密码重新登录。
+///
+/// 响应：`{ "success": true, "user_id": 123 }`
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    _ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let token = super::required_str(&body, "token")?;
+    let new_password = super::required_str(&body, "new_password")?;
+    // 先校验新密码，避免输错格式白白消耗令牌
+    super::validate_new_password(&new_password)?;
+
+    // 令牌核销与改写密码在同一事务里：改密失败时令牌不会被白白烧掉
+    let new_hash = super::hash_password_blocking(new_password).await?;
+    let user_id = services
+        .password_reset_service
+        .reset_password(&token, &new_hash)
+        .await
+        .map_err(RpcError::from)?;
+
+    let sessions_revoked = services
+        .device_manager_db
+        .bump_user_sessions(user_id)
+        .await
+        .map_err(RpcError::from)?;
+    for conn in services
+        .connection_manager
+        .get_user_connections(user_id)
+        .await
+    {
+        if let Err(e) = services
+            .connection_manager
+            .disconnect_device(user_id, &conn.device_id)
+            .await
+        {
+            tracing::warn!(
+                "⚠️ 重置密码后断开设备失败: user_id={}, device_id={}, err={}",
+                user_id,
+                conn.device_id,
+                e
+            );
+        }
+    }
+
+    tracing::info!(
+        "🔑 密码已通过令牌重置: user_id={}, sessions_revoked={}",
+        user_id,
+        sessions_revoked
+    );
+    Ok(json!({
+        "success": true,
+        "user_id": user_id,
+    }))
+}
@@ -0,0 +1,52 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 申请找回密码 请求（匿名）
+///
+/// RPC: account/password/reset/request
+///
+/// 请求：`{ "account": "用户名 / 邮箱 / +E.164 手机号" }`
+///
+/// 令牌经邮件或短信投递到账号绑定的联系方式。为避免被用来探测账号是否存在，
+/// 账号不存在、无法投递或申请过于频繁时同样返回成功。
+///
+/// 响应：`{ "success": true, "message": "..." }`
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    _ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let account = body
+        .get("account")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| RpcError::validation("account is required".to_string()))?;
+
+    if let Err(e) = services.password_reset_service.request_reset(account).await {
+        tracing::warn!("⚠️ 找回密码申请处理失败: account={}, err={}", account, e);
+    }
+
+    Ok(json!({
+        "success": true,
+        "message": "如果账号存在，重置令牌已发送到绑定的邮箱或手机"
+    }))
+}
@@ -42,6 +42,8 @@ use serde_json::{json, Value};
 ///   "allow_search_by_qrcode": true,
 ///   "allow_view_by_non_friend": false,
 ///   "allow_receive_message_from_non_friend": true,
+///   "last_seen": { "visibility": "everyone", "allow_user_ids": [], "deny_user_ids": [] },
+///   "online_status": { ... }, "avatar": { ... }, "phone": { ... }, "read_receipts": { ... },
 ///   "updated_at": "2026-01-12T12:00:00Z"
 /// }
 /// ```
@@ -79,6 +81,11 @@ pub async fn handle(
                 "allow_search_by_qrcode": settings.allow_search_by_qrcode,
                 "allow_view_by_non_friend": settings.allow_view_by_non_friend,
                 "allow_receive_message_from_non_friend": settings.allow_receive_message_from_non_friend,
+                "last_seen": settings.last_seen,
+                "online_status": settings.online_status,
+                "avatar": settings.avatar,
+                "phone": settings.phone,
+                "read_receipts": settings.read_receipts,
                 "updated_at": settings.updated_at.timestamp_millis()
             }))
         }
@@ -15,6 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::model::privacy::{PrivacyKey, VisibilityRule};
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
 use crate::service::PrivacySettingsUpdate;
@@ -35,7 +36,12 @@ use serde_json::{json, Value};
 ///   "allow_search_by_email": true,                 // 可选
 ///   "allow_search_by_qrcode": true,                // 可选
 ///   "allow_view_by_non_friend": false,             // 可选
-///   "allow_receive_message_from_non_friend": true  // 可选（类似QQ/Telegram/Zalo，用于客服系统）
+///   "allow_receive_message_from_non_friend": true, // 可选（类似QQ/Telegram/Zalo，用于客服系统）
+///   "last_seen": {                                 // 可选，同结构的还有 online_status / avatar / phone / read_receipts
+///     "visibility": "contacts",                    // everyone | contacts | nobody
+///     "allow_user_ids": [1002],                    // 例外：始终可见
+///     "deny_user_ids": [1003]                      // 例外：始终不可见（优先）
+///   }
 /// }
 /// ```
 ///
@@ -55,6 +61,21 @@ pub async fn handle(
 ) -> RpcResult<Value> {
     tracing::debug!("🔧 处理 更新隐私设置 请求: {:?}", body);
 
+    // 可见性规则不在协议层请求类型里，单独从 body 取
+    let rule = |key: PrivacyKey| -> RpcResult<Option<VisibilityRule>> {
+        match body.get(key.as_str()) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|e| RpcError::validation(format!("{} 格式错误: {}", key.as_str(), e))),
+        }
+    };
+    let last_seen = rule(PrivacyKey::LastSeen)?;
+    let online_status = rule(PrivacyKey::OnlineStatus)?;
+    let avatar = rule(PrivacyKey::Avatar)?;
+    let phone = rule(PrivacyKey::Phone)?;
+    let read_receipts = rule(PrivacyKey::ReadReceipts)?;
+
     // ✨ 使用协议层类型自动反序列化
     let mut request: AccountPrivacyUpdateRequest = serde_json::from_value(body)
         .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
@@ -74,6 +95,11 @@ pub async fn handle(
         allow_search_by_qrcode: request.allow_search_by_qrcode,
         allow_view_by_non_friend: request.allow_view_by_non_friend,
         allow_receive_message_from_non_friend: request.allow_receive_message_from_non_friend,
+        last_seen,
+        online_status,
+        avatar,
+        phone,
+        read_receipts,
     };
 
     // 更新隐私设置
@@ -96,6 +122,11 @@ pub async fn handle(
                 "allow_search_by_qrcode": settings.allow_search_by_qrcode,
                 "allow_view_by_non_friend": settings.allow_view_by_non_friend,
                 "allow_receive_message_from_non_friend": settings.allow_receive_message_from_non_friend,
+                "last_seen": settings.last_seen,
+                "online_status": settings.online_status,
+                "avatar": settings.avatar,
+                "phone": settings.phone,
+                "read_receipts": settings.read_receipts,
                 "updated_at": settings.updated_at.timestamp_millis()
             }))
         }
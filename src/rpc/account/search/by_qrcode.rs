@@ -15,11 +15,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::infra::cache_manager::CachedUserProfile;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::{helpers, RpcServiceContext};
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::account::search::AccountSearchByQRCodeRequest;
 use serde_json::{json, Value};
 
+/// 扫码查找结果的投影：username 仅好友可见，头像按目标用户的可见性规则
+fn qrcode_search_result(
+    user_id: u64,
+    profile: CachedUserProfile,
+    is_friend: bool,
+    search_session_id: u64,
+    visibility: &ProfileFieldVisibility,
+) -> Value {
+    json!({
+        "user_id": profile.user_id,
+        "username": if is_friend { profile.username } else { String::new() },
+        "nickname": profile.nickname,
+        "avatar_url": visibility.avatar(user_id, profile.avatar_url),
+        "search_session_id": search_session_id, // 用于后续查看详情
+    })
+}
+
 /// 处理 扫码查找用户 请求（精确搜索）
 ///
 /// 通过二维码精确查找用户，用于扫码添加好友等场景
@@ -88,13 +107,17 @@ pub async fn handle(
                         .friend_service
                         .is_friend(searcher_id, user_id)
                         .await;
-                    Ok(json!({
-                        "user_id": user_profile.user_id,
-                        "username": if is_friend { user_profile.username.clone() } else { String::new() },
-                        "nickname": user_profile.nickname,
-                        "avatar_url": user_profile.avatar_url,
-                        "search_session_id": search_record.search_session_id, // 用于后续查看详情
-                    }))
+                    let visibility = services
+                        .privacy_service
+                        .profile_visibility_or_hidden(searcher_id, &[user_id])
+                        .await;
+                    Ok(qrcode_search_result(
+                        user_id,
+                        user_profile,
+                        is_friend,
+                        search_record.search_session_id,
+                        &visibility,
+                    ))
                 }
                 Ok(None) => {
                     tracing::warn!(
@@ -126,3 +149,32 @@ pub async fn handle(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scanned_user_avatar_follows_the_visibility_rule() {
+        let profile = CachedUserProfile {
+            user_id: "20001".to_string(),
+            username: "alice".to_string(),
+            nickname: "Alice".to_string(),
+            avatar_url: Some("https://cdn/a.png".to_string()),
+            user_type: 0,
+            phone: None,
+            email: None,
+        };
+
+        let result = qrcode_search_result(
+            20001,
+            profile,
+            false,
+            7,
+            &ProfileFieldVisibility::hidden(10001),
+        );
+        assert!(result["avatar_url"].is_null());
+        assert_eq!(result["username"], "");
+        assert_eq!(result["search_session_id"], 7);
+    }
+}
@@ -16,13 +16,38 @@
 // limitations under the License.
 
 use crate::model::privacy::{SearchType, UserPrivacySettings};
+use crate::model::user::User;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::account::search::{
     AccountSearchQueryRequest, AccountSearchResponse, SearchedUser,
 };
 use serde_json::Value;
 
+/// 单条搜索结果的投影（头像按目标用户的可见性规则）
+fn searched_user(
+    user: &User,
+    username: String,
+    search_session_id: u64,
+    is_friend: bool,
+    can_send_message: bool,
+    is_follow: bool,
+    visibility: &ProfileFieldVisibility,
+) -> SearchedUser {
+    SearchedUser {
+        user_id: user.id,
+        username,
+        nickname: user.display_name.clone().unwrap_or_default(),
+        avatar_url: visibility.avatar(user.id, user.avatar_url.clone()),
+        user_type: user.user_type,
+        search_session_id,
+        is_friend,
+        can_send_message,
+        is_follow,
+    }
+}
+
 /// 处理 用户搜索 请求（精确搜索）
 ///
 /// 通过关键词精确搜索用户，支持搜索 username、phone、email
@@ -169,6 +194,12 @@ pub async fn handle(
                 }
             };
 
+            // 头像按目标用户的可见性规则投影
+            let visibility = services
+                .privacy_service
+                .profile_visibility_or_hidden(searcher_id, &[user_id])
+                .await;
+
             // 3. 创建搜索记录
             match services
                 .cache_manager
@@ -183,41 +214,35 @@ pub async fn handle(
                     } else {
                         String::new()
                     };
-                    let nickname = user.display_name.clone().unwrap_or_default();
                     tracing::debug!(
                         "✨ 创建搜索记录: username={}, user_id={}, search_session_id={}",
                         username,
                         user_id,
                         search_record.search_session_id
                     );
-                    results.push(SearchedUser {
-                        user_id: user.id,
+                    results.push(searched_user(
+                        &user,
                         username,
-                        nickname,
-                        avatar_url: user.avatar_url.clone(),
-                        user_type: user.user_type,
-                        search_session_id: search_record.search_session_id,
+                        search_record.search_session_id,
                         is_friend,
                         can_send_message,
                         is_follow,
-                    });
+                        &visibility,
+                    ));
                 }
                 Err(e) => {
                     tracing::warn!("⚠️ 创建搜索记录失败: {} -> {}: {}", searcher_id, user_id, e);
                     let username = user.username.clone().unwrap_or_default();
-                    let nickname = user.display_name.clone().unwrap_or_default();
-                    // 继续处理，但使用默认的 search_session_id
-                    results.push(SearchedUser {
-                        user_id: user.id,
+                    // 继续处理，但使用默认的 search_session_id（搜索记录创建失败时为 0）
+                    results.push(searched_user(
+                        &user,
                         username,
-                        nickname,
-                        avatar_url: user.avatar_url.clone(),
-                        user_type: user.user_type,
-                        search_session_id: 0, // 搜索记录创建失败时使用默认值
+                        0,
                         is_friend,
                         can_send_message,
                         is_follow,
-                    });
+                        &visibility,
+                    ));
                 }
             }
         }
@@ -237,3 +262,37 @@ pub async fn handle(
 
     Ok(serde_json::to_value(response).unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_result_avatar_follows_the_visibility_rule() {
+        let mut user = User::new(20001, "alice".to_string());
+        user.avatar_url = Some("https://cdn/a.png".to_string());
+
+        let hidden = searched_user(
+            &user,
+            String::new(),
+            7,
+            false,
+            true,
+            false,
+            &ProfileFieldVisibility::hidden(10001),
+        );
+        assert_eq!(hidden.avatar_url, None);
+        assert_eq!(hidden.search_session_id, 7);
+
+        let own = searched_user(
+            &user,
+            String::new(),
+            7,
+            false,
+            true,
+            false,
+            &ProfileFieldVisibility::hidden(20001),
+        );
+        assert_eq!(own.avatar_url.as_deref(), Some("https://cdn/a.png"));
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 关闭两步验证 请求
+///
+/// RPC: account/2fa/disable
+///
+/// 请求：`{ "code": "123456" }` 或 `{ "recovery_code": "abcd-efgh" }`
+///
+/// 响应：`{ "enabled": false }`
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let factor = super::parse_second_factor(&body)?;
+
+    services
+        .two_factor_service
+        .disable(user_id, &factor)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::info!("🔓 两步验证已关闭: user_id={}", user_id);
+    Ok(json!({ "enabled": false }))
+}
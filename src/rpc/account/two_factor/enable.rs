@@ -0,0 +1,52 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 确认启用两步验证 请求
+///
+/// RPC: account/2fa/enable
+///
+/// 请求：`{ "code": "123456" }`（验证器 App 当前动态码）
+///
+/// 响应（恢复码明文只返回这一次）：
+/// ```json
+/// { "enabled": true, "recovery_codes": ["abcd-efgh", "..."] }
+/// ```
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let code = super::parse_totp_code(&body)?;
+
+    let recovery_codes = services
+        .two_factor_service
+        .enable(user_id, &code)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::info!("🔐 两步验证已启用: user_id={}", user_id);
+    Ok(json!({
+        "enabled": true,
+        "recovery_codes": recovery_codes,
+    }))
+}
@@ -0,0 +1,128 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// 内置账号两步验证（TOTP）：绑定 / 确认 / 关闭 / 重新生成恢复码
+//
+// 登录时的第二步见 account/auth/login_2fa。
+
+pub mod disable;
+pub mod enable;
+pub mod recovery_codes;
+pub mod setup;
+pub mod status;
+
+use super::super::router::GLOBAL_RPC_ROUTER;
+use super::super::RpcServiceContext;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::service::SecondFactor;
+use serde_json::Value;
+
+/// 注册两步验证模块的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    let router = GLOBAL_RPC_ROUTER.clone();
+
+    router
+        .register("account/2fa/status", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { status::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/2fa/setup", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { setup::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/2fa/enable", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { enable::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/2fa/disable", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { disable::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("account/2fa/recovery_codes/regenerate", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { recovery_codes::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!(
+        "📋 TwoFactor 模块路由注册完成 (status, setup, enable, disable, recovery_codes/regenerate)"
+    );
+}
+
+/// 两步验证仅对内置账号开放；PLATFORM 模式下账号与登录都不归 server 管
+pub(crate) fn ensure_builtin(services: &RpcServiceContext) -> RpcResult<()> {
+    if services.config.account.is_builtin() {
+        Ok(())
+    } else {
+        Err(RpcError::forbidden(
+            "account.mode=PLATFORM：两步验证由 platform 负责".to_string(),
+        ))
+    }
+}
+
+/// 读取 body 中的第二因子：`code`（动态码）或 `recovery_code`（恢复码），二选一
+pub(crate) fn parse_second_factor(body: &Value) -> RpcResult<SecondFactor> {
+    let field = |name: &str| {
+        body.get(name)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    match (field("code"), field("recovery_code")) {
+        (Some(code), None) => Ok(SecondFactor::Totp(code)),
+        (None, Some(code)) => Ok(SecondFactor::RecoveryCode(code)),
+        _ => Err(RpcError::validation(
+            "code 与 recovery_code 必须且只能提供一个".to_string(),
+        )),
+    }
+}
+
+/// 读取必填的动态码 `code`
+pub(crate) fn parse_totp_code(body: &Value) -> RpcResult<String> {
+    body.get("code")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| RpcError::validation("code is required".to_string()))
+}
@@ -0,0 +1,63 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 生成两步验证密钥 请求
+///
+/// RPC: account/2fa/setup
+///
+/// 客户端把 `otpauth_uri` 渲染成二维码供验证器 App 扫描；未确认前可重复调用，旧密钥作废。
+/// 已启用时返回错误，需先关闭再重新绑定。
+///
+/// 响应：
+/// ```json
+/// {
+///   "secret": "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP",
+///   "otpauth_uri": "otpauth://totp/PrivChat:alice?secret=...&issuer=PrivChat&algorithm=SHA1&digits=6&period=30"
+/// }
+/// ```
+pub async fn handle(
+    _body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    super::ensure_builtin(&services)?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let user = services
+        .user_repository
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询用户失败: {}", e)))?
+        .ok_or_else(|| RpcError::not_found(format!("用户 {} 不存在", user_id)))?;
+    let account_label = user
+        .username
+        .or(user.email)
+        .unwrap_or_else(|| user_id.to_string());
+
+    let setup = services
+        .two_factor_service
+        .begin_setup(user_id, &account_label)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!("🔐 已生成两步验证密钥（待确认）: user_id={}", user_id);
+    Ok(json!(setup))
+}
@@ -184,7 +184,7 @@ pub async fn handle(
 
                     // 字段投影(D1/D2):username 仅 本人/好友/by_username 搜索来源;
                     // 其余回空串(滚动兼容:老客户端解析不炸,且天然清洗本地旧缓存)。
-                    // phone/email 只有本人可见——此前对任意查看者裸奔,一并收口。
+                    // email 只有本人可见;phone 按用户的可见性规则(默认仅好友)。
                     let username_visible =
                         is_self || verdict.is_friend || verdict.username_unlocked;
                     let projected_username = if username_visible {
@@ -193,6 +193,13 @@ pub async fn handle(
                         String::new()
                     };
 
+                    // 头像 / 手机号按目标用户的可见性规则投影(本人总是可见)。
+                    // 规则读不出来时按不可见处理,不因脏数据把手机号放出去。
+                    let visibility = services
+                        .privacy_service
+                        .profile_visibility_or_hidden(searcher_id, &[uid])
+                        .await;
+
                     // 签发查看凭证(§2.5.1):friend/apply 凭 grant_id 放行。
                     let grant = crate::model::privacy::ProfileViewGrant::new(
                         searcher_id,
@@ -212,8 +219,8 @@ pub async fn handle(
                         "user_id": uid,
                         "username": projected_username, // 账号(按可见性投影)
                         "nickname": user_profile.nickname, // 昵称
-                        "avatar_url": user_profile.avatar_url, // 头像
-                        "phone": if is_self { user_profile.phone.clone() } else { None }, // 仅本人
+                        "avatar_url": visibility.avatar(uid, user_profile.avatar_url.clone()), // 头像(按可见性规则)
+                        "phone": visibility.phone(uid, user_profile.phone.clone()), // 手机号(按可见性规则,默认仅好友)
                         "email": if is_self { user_profile.email.clone() } else { None }, // 仅本人
                         "user_type": user_profile.user_type, // 用户类型（0 普通 1 系统 2 机器人）
                         "is_friend": is_friend, // ✨ 是否好友
@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::auth::hash_password;
+use crate::auth::{hash_password, MIN_PASSWORD_LENGTH};
 use crate::model::user::User;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
@@ -60,8 +60,11 @@ pub async fn handle(
         return Err(RpcError::validation("用户名不能为空".to_string()));
     }
 
-    if request.password.len() < 6 {
-        return Err(RpcError::validation("密码至少需要 6 个字符".to_string()));
+    if request.password.len() < MIN_PASSWORD_LENGTH {
+        return Err(RpcError::validation(format!(
+            "密码至少需要 {} 个字符",
+            MIN_PASSWORD_LENGTH
+        )));
     }
 
     // 验证 device_id 必须是 UUID 格式（与认证时的验证保持一致）
@@ -107,6 +110,15 @@ pub async fn handle(
     let user_id = created_user.id;
     let device_id = &request.device_id;
 
+    crate::service::emit_webhook(
+        crate::service::webhook_service::WEBHOOK_EVENT_USER_REGISTERED,
+        json!({
+            "user_id": user_id,
+            "username": created_user.username,
+            "source": "register",
+        }),
+    );
+
     // 5. 从 device_info 中提取 app_id 和 device_type
     let (app_id, device_type_str) = request
         .device_info
@@ -0,0 +1,111 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 会话阅后即焚计时器
+//!
+//! - `channel/disappearing/get`：会话成员查询默认计时器
+//! - `channel/disappearing/update`：私聊任一方或群主 / 管理员设置，`ttl_secs: null` 关闭
+//!
+//! 计时器只作用于之后发出的消息；到期消息由后台任务硬删除，客户端经 sync 收到
+//! `message.expired` 后删除本地副本。
+
+use super::retention::load_member_channel;
+use crate::model::channel::{ChannelType, MemberRole};
+use crate::repository::DisappearingTimer;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 查询会话阅后即焚计时器 请求
+///
+/// 请求：`{ "channel_id": 123 }`
+///
+/// 响应：`{ "channel_id": 123, "ttl_secs": 86400 | null, "start_on_read": false }`
+pub async fn handle_get(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    load_member_channel(&services, channel_id, user_id).await?;
+
+    let timer = services
+        .disappearing_service
+        .get_channel_timer(channel_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(timer))
+}
+
+/// 处理 设置会话阅后即焚计时器 请求（私聊双方 / 群主、管理员）
+///
+/// 请求：`{ "channel_id": 123, "ttl_secs": 86400, "start_on_read": false }`，
+/// `ttl_secs` 传 `null` 关闭计时器
+///
+/// 响应：同 `channel/disappearing/get`
+pub async fn handle_update(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+    let ttl_secs: Option<i64> = match body.get("ttl_secs") {
+        None => return Err(RpcError::validation("ttl_secs is required".to_string())),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| RpcError::validation(format!("ttl_secs 格式错误: {}", e)))?,
+    };
+    let start_on_read = body
+        .get("start_on_read")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let channel = load_member_channel(&services, channel_id, operator_id).await?;
+    let allowed = match channel.channel_type {
+        ChannelType::Direct => true,
+        ChannelType::Group => channel
+            .members
+            .get(&operator_id)
+            .is_some_and(|m| matches!(m.role, MemberRole::Owner | MemberRole::Admin)),
+        ChannelType::Room => false,
+    };
+    if !allowed {
+        return Err(RpcError::forbidden(
+            "只有群主、管理员或私聊双方可以设置阅后即焚".to_string(),
+        ));
+    }
+
+    let timer = ttl_secs.map(|ttl_secs| DisappearingTimer {
+        ttl_secs,
+        start_on_read,
+    });
+    let result = services
+        .disappearing_service
+        .set_channel_timer(channel_id, operator_id, timer)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 会话阅后即焚计时器已更新: channel_id={}, operator={}, ttl_secs={:?}, start_on_read={}",
+        channel_id,
+        operator_id,
+        ttl_secs,
+        start_on_read
+    );
+    Ok(json!(result))
+}
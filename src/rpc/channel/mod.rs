@@ -18,9 +18,12 @@
 //! 频道系统 RPC 接口（私聊、群聊等会话功能）
 
 pub mod direct;
+pub mod disappearing;
 pub mod hide;
 pub mod mute;
+pub mod notify_level;
 pub mod pin;
+pub mod retention;
 
 use super::router::GLOBAL_RPC_ROUTER;
 use super::RpcServiceContext;
@@ -66,5 +69,48 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
-    tracing::debug!("💬 Channel 系统路由注册完成 (direct/get_or_create, pin, hide, mute)");
+    // ✨ 设置频道通知级别（全部 / 仅 @ / 不通知）
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("channel/notify_level", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { notify_level::handle(params, services, ctx).await })
+        })
+        .await;
+
+    // ✨ 会话消息保留期（群主 / 私聊双方设置）
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("channel/retention/get", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { retention::handle_get(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("channel/retention/update", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { retention::handle_update(params, services, ctx).await })
+        })
+        .await;
+
+    // ✨ 会话阅后即焚计时器（私聊双方 / 群主、管理员设置）
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("channel/disappearing/get", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { disappearing::handle_get(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("channel/disappearing/update", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { disappearing::handle_update(params, services, ctx).await })
+        })
+        .await;
+
+    tracing::debug!("💬 Channel 系统路由注册完成 (direct/get_or_create, pin, hide, mute, notify_level, retention, disappearing)");
 }
@@ -0,0 +1,77 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::ChannelNotifyLevel;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct ChannelNotifyLevelRequest {
+    channel_id: u64,
+    level: String,
+}
+
+/// 处理设置频道通知级别请求
+///
+/// RPC: channel/notify_level
+///
+/// 比 channel/mute 更细的通知偏好：
+/// - `all`：所有新消息都推送
+/// - `mentions`：仅 @我 / @全体成员 时推送
+/// - `none`：不推送（等同静音，会同步 is_muted）
+///
+/// 请求参数：
+/// ```json
+/// { "channel_id": 123, "level": "mentions" }
+/// ```
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理设置频道通知级别请求: {:?}", body);
+
+    let request: ChannelNotifyLevelRequest = serde_json::from_value(body)
+        .map_err(|e| RpcError::validation(format!("请求参数格式错误: {}", e)))?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let level = ChannelNotifyLevel::parse(&request.level).ok_or_else(|| {
+        RpcError::validation(format!(
+            "不支持的通知级别: {}（可选 all / mentions / none）",
+            request.level
+        ))
+    })?;
+
+    services
+        .channel_service
+        .set_channel_notify_level(user_id, request.channel_id, level)
+        .await
+        .map_err(|e| {
+            tracing::error!("❌ 设置频道通知级别失败: {}", e);
+            RpcError::internal(format!("设置频道通知级别失败: {}", e))
+        })?;
+
+    tracing::debug!(
+        "✅ 用户 {} 设置频道 {} 通知级别为 {}",
+        user_id,
+        request.channel_id,
+        level.as_str()
+    );
+    Ok(json!(true))
+}
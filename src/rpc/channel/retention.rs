@@ -0,0 +1,120 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 会话消息保留期
+//!
+//! - `channel/retention/get`：会话成员查询保留期（会话覆盖 / 平台默认 / 实际生效）
+//! - `channel/retention/update`：群主或私聊任一方设置保留期，`retention_secs: null` 恢复平台默认
+//!
+//! 保留期只能比平台默认更短；超期消息由后台任务删除，客户端经 sync 收到
+//! `channel.history_expired` 后清理本地缓存。
+
+use crate::model::channel::{Channel, ChannelType, MemberRole};
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+pub(super) async fn load_member_channel(
+    services: &RpcServiceContext,
+    channel_id: u64,
+    user_id: u64,
+) -> RpcResult<Channel> {
+    let channel = services
+        .channel_service
+        .get_channel(&channel_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("会话不存在: {}", e)))?;
+    if !channel.members.contains_key(&user_id) {
+        return Err(RpcError::forbidden("您不是会话成员".to_string()));
+    }
+    Ok(channel)
+}
+
+/// 处理 查询会话保留期 请求
+///
+/// 请求：`{ "channel_id": 123 }`
+///
+/// 响应：`{ "channel_id": 123, "retention_secs": 604800 | null,
+///          "platform_retention_secs": 31536000 | null, "effective_retention_secs": 604800 | null }`
+pub async fn handle_get(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    load_member_channel(&services, channel_id, user_id).await?;
+
+    let retention = services
+        .retention_service
+        .get_channel_retention(channel_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(retention))
+}
+
+/// 处理 设置会话保留期 请求（群主 / 私聊双方）
+///
+/// 请求：`{ "channel_id": 123, "retention_secs": 604800 }`，传 `null` 恢复平台默认
+///
+/// 响应：同 `channel/retention/get`
+pub async fn handle_update(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+    let retention_secs: Option<i64> = match body.get("retention_secs") {
+        None => {
+            return Err(RpcError::validation(
+                "retention_secs is required".to_string(),
+            ))
+        }
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| RpcError::validation(format!("retention_secs 格式错误: {}", e)))?,
+    };
+
+    let channel = load_member_channel(&services, channel_id, operator_id).await?;
+    let allowed = match channel.channel_type {
+        ChannelType::Direct => true,
+        ChannelType::Group => channel
+            .members
+            .get(&operator_id)
+            .is_some_and(|m| m.role == MemberRole::Owner),
+        ChannelType::Room => false,
+    };
+    if !allowed {
+        return Err(RpcError::forbidden(
+            "只有群主或私聊双方可以设置消息保留期".to_string(),
+        ));
+    }
+
+    let retention = services
+        .retention_service
+        .set_channel_retention(channel_id, operator_id, retention_secs)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 会话保留期已更新: channel_id={}, operator={}, retention_secs={:?}",
+        channel_id,
+        operator_id,
+        retention_secs
+    );
+    Ok(json!(retention))
+}
@@ -15,23 +15,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::rpc::error::RpcResult;
+use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
-use serde_json::{json, Value};
+use serde_json::Value;
 
 /// 处理 创建频道 请求
+///
+/// 调用者成为频道主（唯一能任命管理员的人）。频道本身不带成员，
+/// 订阅者通过 `channel/channel/subscribe` 加入。
 pub async fn handle(
     body: Value,
-    _services: RpcServiceContext,
+    services: RpcServiceContext,
     ctx: crate::rpc::RpcContext,
 ) -> RpcResult<Value> {
-    // TODO: 实现 创建频道 逻辑
     tracing::debug!("🔧 处理 创建频道 请求: {:?}", body);
 
-    // 临时返回成功响应
-    Ok(json!({
-        "status": "success",
-        "action": "创建频道",
-        "timestamp": chrono::Utc::now().timestamp_millis()
-    }))
+    let owner_id = crate::rpc::get_current_user_id(&ctx)?;
+    let name = body
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::validation("name is required".to_string()))?;
+    let description = body.get("description").and_then(|v| v.as_str());
+    let avatar_url = body.get("avatar_url").and_then(|v| v.as_str());
+
+    let record = services
+        .broadcast_channel_service
+        .create_channel(owner_id, name, description, avatar_url)
+        .await?;
+
+    tracing::debug!(
+        "✅ 用户 {} 创建广播频道 {} 成功",
+        owner_id,
+        record.channel_id
+    );
+    Ok(super::super::channel_to_json(&record))
 }
@@ -0,0 +1,52 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::MemberRole;
+use crate::rpc::error::RpcResult;
+use crate::rpc::RpcServiceContext;
+use serde_json::Value;
+
+/// 处理 频道资料 请求
+///
+/// 任何登录用户都能看资料（订阅前要先看得到），附带自己在频道里的角色：
+/// `my_role` 为 `owner` / `admin` / `subscriber`，null 表示未订阅。
+///
+/// 🔴 这里给字符串而不是数字：DB 角色编号（Owner=0）与协议群角色编号（Member=0）
+/// 不同，数字很容易被客户端按群角色解读。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 频道资料 请求: {:?}", body);
+
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+
+    let service = &services.broadcast_channel_service;
+    let record = service.get_channel(channel_id).await?;
+    let role = service.member_role(channel_id, user_id).await?;
+
+    let mut info = super::super::channel_to_json(&record);
+    info["my_role"] = match role {
+        Some(MemberRole::Owner) => Value::from("owner"),
+        Some(MemberRole::Admin) => Value::from("admin"),
+        Some(MemberRole::Member) => Value::from("subscriber"),
+        None => Value::Null,
+    };
+    Ok(info)
+}
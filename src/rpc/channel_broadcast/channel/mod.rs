@@ -16,7 +16,10 @@
 // limitations under the License.
 
 pub mod create;
+pub mod info;
+pub mod set_admin;
 pub mod subscribe;
+pub mod unsubscribe;
 
 use super::super::router::GLOBAL_RPC_ROUTER;
 use super::super::RpcServiceContext;
@@ -35,6 +38,16 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    router
+        .register("channel/channel/info", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { info::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
     router
         .register("channel/channel/subscribe", {
             let services = services.clone();
@@ -45,6 +58,26 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    router
+        .register("channel/channel/unsubscribe", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { unsubscribe::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("channel/channel/set_admin", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { set_admin::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
     // channel/channel/list 已废弃，列表数据由 entity/sync_entities 同步，客户端从本地读 get_channels / get_channel_list_entries
 
     tracing::debug!("📋 channel 模块路由注册完成");
@@ -0,0 +1,51 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 设置频道管理员 请求
+///
+/// 仅频道主可操作；管理员可以发布内容。目标用户必须先订阅频道。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 设置频道管理员 请求: {:?}", body);
+
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let target_user_id = crate::rpc::parse_u64_param(&body, "user_id")?;
+    let is_admin = body
+        .get("is_admin")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| RpcError::validation("is_admin is required".to_string()))?;
+
+    let changed = services
+        .broadcast_channel_service
+        .set_admin(channel_id, operator_id, target_user_id, is_admin)
+        .await?;
+
+    Ok(json!({
+        "channel_id": channel_id,
+        "user_id": target_user_id,
+        "is_admin": is_admin,
+        "changed": changed,
+    }))
+}
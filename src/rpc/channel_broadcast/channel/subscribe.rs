@@ -15,11 +15,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::error::RpcResult;
 use crate::rpc::RpcServiceContext;
 use serde_json::{json, Value};
 
 /// 处理 订阅频道 请求
+///
+/// 幂等：已订阅（或本身是频道主/管理员）时 `subscribed` 返回 false，角色不变。
 pub async fn handle(
     body: Value,
     services: RpcServiceContext,
@@ -29,38 +31,26 @@ pub async fn handle(
 
     // 从 ctx 获取当前用户 ID
     let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
 
-    let channel_id_str = body
-        .get("channel_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| RpcError::validation("channel_id is required".to_string()))?;
-    let channel_id = channel_id_str
-        .parse::<u64>()
-        .map_err(|_| RpcError::validation(format!("Invalid channel_id: {}", channel_id_str)))?;
+    let subscribed = services
+        .broadcast_channel_service
+        .subscribe(channel_id, user_id)
+        .await?;
+    let record = services
+        .broadcast_channel_service
+        .get_channel(channel_id)
+        .await?;
 
-    // 订阅频道（使用 Subscriber 角色）
-    match services
-        .channel_service
-        .join_channel(
-            channel_id,
-            user_id,
-            Some(crate::model::channel::MemberRole::Member),
-        )
-        .await
-    {
-        Ok(_) => {
-            tracing::debug!("✅ 用户 {} 成功订阅频道 {}", user_id, channel_id);
-            Ok(json!({
-                "status": "success",
-                "message": "频道订阅成功",
-                "channel_id": channel_id,
-                "user_id": user_id,
-                "subscribed_at": chrono::Utc::now().timestamp_millis()
-            }))
-        }
-        Err(e) => {
-            tracing::error!("❌ 订阅频道失败: {}", e);
-            Err(RpcError::internal(format!("订阅频道失败: {}", e)))
-        }
-    }
+    tracing::debug!(
+        "✅ 用户 {} 订阅频道 {} (new={})",
+        user_id,
+        channel_id,
+        subscribed
+    );
+    Ok(json!({
+        "channel_id": channel_id,
+        "subscribed": subscribed,
+        "subscriber_count": record.subscriber_count.max(0) as u64,
+    }))
 }
@@ -0,0 +1,55 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::RpcResult;
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 退订频道 请求
+///
+/// 幂等：本来就没订阅时 `unsubscribed` 返回 false。频道主/管理员不能直接退订。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 退订频道 请求: {:?}", body);
+
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+
+    let unsubscribed = services
+        .broadcast_channel_service
+        .unsubscribe(channel_id, user_id)
+        .await?;
+    let record = services
+        .broadcast_channel_service
+        .get_channel(channel_id)
+        .await?;
+
+    tracing::debug!(
+        "✅ 用户 {} 退订频道 {} (changed={})",
+        user_id,
+        channel_id,
+        unsubscribed
+    );
+    Ok(json!({
+        "channel_id": channel_id,
+        "unsubscribed": unsubscribed,
+        "subscriber_count": record.subscriber_count.max(0) as u64,
+    }))
+}
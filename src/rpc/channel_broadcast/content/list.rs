@@ -19,19 +19,55 @@ use crate::rpc::error::RpcResult;
 use crate::rpc::RpcServiceContext;
 use serde_json::{json, Value};
 
+/// 默认页大小
+const DEFAULT_LIMIT: u32 = 20;
+
 /// 处理 内容列表 请求
+///
+/// 按 message_id 倒序分页：首页不带 `before_message_id`，下一页传上一页最后一条的
+/// message_id。已撤回、已过期的不返回；已忽略的默认不返回（`include_dismissed=true` 可取回）。
 pub async fn handle(
     body: Value,
-    _services: RpcServiceContext,
+    services: RpcServiceContext,
     ctx: crate::rpc::RpcContext,
 ) -> RpcResult<Value> {
-    // TODO: 实现 内容列表 逻辑
     tracing::debug!("🔧 处理 内容列表 请求: {:?}", body);
 
-    // 临时返回成功响应
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let before_message_id = body.get("before_message_id").and_then(|v| v.as_u64());
+    let limit = body
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|v| v.min(u32::MAX as u64) as u32)
+        .unwrap_or(DEFAULT_LIMIT);
+    let include_dismissed = body
+        .get("include_dismissed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let items = services
+        .broadcast_channel_service
+        .list_messages(
+            channel_id,
+            user_id,
+            before_message_id,
+            limit,
+            include_dismissed,
+        )
+        .await?;
+
+    // 满页才可能还有下一页；游标就是本页最后一条。
+    let next_before_message_id = if items.len() as u32 >= limit.max(1) {
+        items.last().map(|item| item.message.message_id)
+    } else {
+        None
+    };
+    let messages: Vec<Value> = items.iter().map(super::super::feed_item_to_json).collect();
+
     Ok(json!({
-        "status": "success",
-        "action": "内容列表",
-        "timestamp": chrono::Utc::now().timestamp_millis()
+        "channel_id": channel_id,
+        "messages": messages,
+        "next_before_message_id": next_before_message_id,
     }))
 }
@@ -15,8 +15,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod dismiss;
 pub mod list;
 pub mod publish;
+pub mod read;
 
 use super::super::router::GLOBAL_RPC_ROUTER;
 use super::super::RpcServiceContext;
@@ -45,5 +47,25 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    router
+        .register("channel/content/read", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { read::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("channel/content/dismiss", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { dismiss::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
     tracing::debug!("📋 content 模块路由注册完成");
 }
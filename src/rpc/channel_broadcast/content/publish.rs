@@ -15,23 +15,81 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::rpc::error::RpcResult;
+use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
+use crate::service::PublishChannelMessageRequest;
+use privchat_protocol::ContentMessageType;
 use serde_json::{json, Value};
 
 /// 处理 发布内容 请求
+///
+/// 只有频道主/管理员可发布。消息进入频道时间线（pts + commit_log + dispatch），
+/// 订阅者像收普通消息一样实时收到；标题/紧急/过期另存为广播属性。
+///
+/// `local_message_id` 非 0 时按发布者幂等：重试返回同一条，`inserted=false`。
 pub async fn handle(
     body: Value,
-    _services: RpcServiceContext,
+    services: RpcServiceContext,
     ctx: crate::rpc::RpcContext,
 ) -> RpcResult<Value> {
-    // TODO: 实现 发布内容 逻辑
     tracing::debug!("🔧 处理 发布内容 请求: {:?}", body);
 
-    // 临时返回成功响应
+    let publisher_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let content = body
+        .get("content")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::validation("content is required".to_string()))?
+        .to_string();
+    let message_type = match body.get("message_type").and_then(|v| v.as_u64()) {
+        None => ContentMessageType::Text,
+        Some(value) => ContentMessageType::from_u32(value as u32)
+            .ok_or_else(|| RpcError::validation(format!("Invalid message_type: {}", value)))?,
+    };
+    let expires_at = match body.get("expires_at").and_then(|v| v.as_i64()) {
+        None => None,
+        Some(ms) => Some(
+            chrono::DateTime::from_timestamp_millis(ms)
+                .ok_or_else(|| RpcError::validation(format!("Invalid expires_at: {}", ms)))?,
+        ),
+    };
+
+    let published = services
+        .broadcast_channel_service
+        .publish(PublishChannelMessageRequest {
+            channel_id,
+            publisher_id,
+            title: body
+                .get("title")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            content,
+            message_type,
+            metadata: body.get("metadata").cloned().unwrap_or_else(|| json!({})),
+            is_urgent: body
+                .get("is_urgent")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            expires_at,
+            local_message_id: body
+                .get("local_message_id")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+        })
+        .await?;
+
+    tracing::debug!(
+        "✅ 频道 {} 发布内容 message_id={} pts={} inserted={}",
+        channel_id,
+        published.message.message_id,
+        published.pts,
+        published.inserted
+    );
     Ok(json!({
-        "status": "success",
-        "action": "发布内容",
-        "timestamp": chrono::Utc::now().timestamp_millis()
+        "channel_id": channel_id,
+        "message_id": published.message.message_id,
+        "pts": published.pts,
+        "published_at": published.message.published_at.timestamp_millis(),
+        "inserted": published.inserted,
     }))
 }
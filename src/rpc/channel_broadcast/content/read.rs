@@ -0,0 +1,46 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::rpc::error::RpcResult;
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 标记频道内容已读 请求
+///
+/// 只记录订阅者对单条内容的状态（内容流里的 is_read），与会话级 read_pts 互不替代。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 标记频道内容已读 请求: {:?}", body);
+
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = crate::rpc::parse_u64_param(&body, "channel_id")?;
+    let message_ids = super::super::parse_message_ids(&body)?;
+    let device_id = ctx.device_id.clone().unwrap_or_default();
+
+    let updated = services
+        .broadcast_channel_service
+        .mark_read(channel_id, user_id, &device_id, &message_ids)
+        .await?;
+
+    Ok(json!({
+        "channel_id": channel_id,
+        "updated": updated,
+    }))
+}
@@ -18,12 +18,90 @@
 pub mod channel;
 pub mod content;
 
+use super::error::{RpcError, RpcResult};
 use super::RpcServiceContext;
+use crate::repository::{BroadcastChannelRecord, BroadcastFeedItem};
+use serde_json::{json, Value};
 
-/// 注册频道系统的所有路由
+/// 注册广播频道系统的所有路由
 pub async fn register_routes(services: RpcServiceContext) {
     channel::register_routes(services.clone()).await;
     content::register_routes(services.clone()).await;
 
     tracing::debug!("📋 Channel 系统路由注册完成");
 }
+
+/// 频道资料 → 响应 JSON
+pub(crate) fn channel_to_json(record: &BroadcastChannelRecord) -> Value {
+    json!({
+        "channel_id": record.channel_id as u64,
+        "owner_id": record.owner_id as u64,
+        "name": record.name,
+        "description": record.description,
+        "avatar_url": record.avatar_url,
+        "subscriber_count": record.subscriber_count.max(0) as u64,
+        "created_at": record.created_at,
+        "updated_at": record.updated_at,
+    })
+}
+
+/// 内容流条目 → 响应 JSON。status 缺省表示查看者尚未读过/忽略过。
+pub(crate) fn feed_item_to_json(item: &BroadcastFeedItem) -> Value {
+    let message = &item.message;
+    let status = item.status.as_ref();
+    json!({
+        "message_id": message.message_id,
+        "channel_id": message.channel_id,
+        "pts": item.pts,
+        "publisher_id": message.publisher_id,
+        "title": message.title,
+        "content": message.content,
+        "message_type": message.message_type.as_str(),
+        "metadata": message.metadata,
+        "is_urgent": message.is_urgent,
+        "expires_at": message.expires_at.map(|t| t.timestamp_millis()),
+        "published_at": message.published_at.timestamp_millis(),
+        "updated_at": message.updated_at.timestamp_millis(),
+        "is_read": status.map_or(false, |s| s.is_read),
+        "is_dismissed": status.map_or(false, |s| s.is_dismissed),
+        "read_at": status.and_then(|s| s.read_at).map(|t| t.timestamp_millis()),
+        "dismissed_at": status.and_then(|s| s.dismissed_at).map(|t| t.timestamp_millis()),
+    })
+}
+
+/// 解析 `message_ids` 数组（read / dismiss 共用），单次最多 100 条。
+pub(crate) fn parse_message_ids(body: &Value) -> RpcResult<Vec<u64>> {
+    let ids = body
+        .get("message_ids")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| RpcError::validation("message_ids is required".to_string()))?;
+    if ids.is_empty() || ids.len() > 100 {
+        return Err(RpcError::validation(
+            "message_ids 数量必须在 1 到 100 之间".to_string(),
+        ));
+    }
+    ids.iter()
+        .map(|v| {
+            v.as_u64()
+                .ok_or_else(|| RpcError::validation("message_ids 必须是 u64 数组".to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_ids_must_be_a_bounded_u64_array() {
+        assert_eq!(
+            parse_message_ids(&json!({"message_ids": [1, 2, 3]})).unwrap(),
+            vec![1, 2, 3]
+        );
+        assert!(parse_message_ids(&json!({})).is_err());
+        assert!(parse_message_ids(&json!({"message_ids": []})).is_err());
+        assert!(parse_message_ids(&json!({"message_ids": ["1"]})).is_err());
+        let too_many: Vec<u64> = (0..101).collect();
+        assert!(parse_message_ids(&json!({ "message_ids": too_many })).is_err());
+    }
+}
@@ -115,6 +115,14 @@ pub async fn handle(
             user_id,
         )
         .await;
+        crate::service::emit_webhook(
+            crate::service::webhook_service::WEBHOOK_EVENT_FRIEND_ACCEPTED,
+            json!({
+                "requester_id": from_user_id,
+                "accepter_id": user_id,
+                "channel_id": channel_id,
+            }),
+        );
     }
     let publisher = EntityInvalidationPublisher::new(services.connection_manager.clone());
     if let Err(error) = publisher
@@ -15,12 +15,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::infra::cache_manager::CachedUserProfile;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::{helpers, RpcServiceContext};
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::account::search::SearchedUser;
 use privchat_protocol::rpc::contact::friend::{FriendPendingItem, FriendPendingResponse};
 use serde_json::Value;
 
+/// 申请者卡片（头像按申请者的可见性规则投影）
+fn requester_card(
+    from_user_id: u64,
+    profile: CachedUserProfile,
+    visibility: &ProfileFieldVisibility,
+) -> SearchedUser {
+    SearchedUser {
+        user_id: profile.user_id.parse().unwrap_or(0),
+        username: profile.username,
+        nickname: profile.nickname,
+        avatar_url: visibility.avatar(from_user_id, profile.avatar_url),
+        user_type: profile.user_type,
+        search_session_id: 0,
+        is_friend: false,
+        can_send_message: false,
+        is_follow: false,
+    }
+}
+
 /// 处理 待处理好友申请列表 请求
 pub async fn handle(
     body: Value,
@@ -34,6 +55,12 @@ pub async fn handle(
     match services.friend_service.get_pending_requests(user_id).await {
         Ok(requests) => {
             let mut items = Vec::with_capacity(requests.len());
+            // 申请者头像按其可见性规则投影
+            let requester_ids: Vec<u64> = requests.iter().map(|req| req.from_user_id).collect();
+            let visibility = services
+                .privacy_service
+                .profile_visibility_or_hidden(user_id, &requester_ids)
+                .await;
 
             for req in requests {
                 // 获取申请者用户资料
@@ -44,17 +71,7 @@ pub async fn handle(
                 )
                 .await
                 {
-                    Ok(Some(profile)) => SearchedUser {
-                        user_id: profile.user_id.parse().unwrap_or(0),
-                        username: profile.username,
-                        nickname: profile.nickname,
-                        avatar_url: profile.avatar_url,
-                        user_type: profile.user_type,
-                        search_session_id: 0,
-                        is_friend: false,
-                        can_send_message: false,
-                        is_follow: false,
-                    },
+                    Ok(Some(profile)) => requester_card(req.from_user_id, profile, &visibility),
                     _ => SearchedUser {
                         user_id: req.from_user_id,
                         username: format!("user{}", req.from_user_id),
@@ -99,3 +116,25 @@ pub async fn handle(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requester_avatar_follows_the_visibility_rule() {
+        let profile = CachedUserProfile {
+            user_id: "20001".to_string(),
+            username: "alice".to_string(),
+            nickname: "Alice".to_string(),
+            avatar_url: Some("https://cdn/a.png".to_string()),
+            user_type: 0,
+            phone: None,
+            email: None,
+        };
+
+        let card = requester_card(20001, profile, &ProfileFieldVisibility::hidden(10001));
+        assert_eq!(card.avatar_url, None);
+        assert_eq!(card.user_id, 20001);
+    }
+}
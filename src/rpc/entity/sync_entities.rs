@@ -19,9 +19,11 @@
 //!
 //! 按 entity_type 委托给对应 service 的业务逻辑：friend -> FriendService，group -> ChannelService。
 
+use crate::infra::with_primary_reads_if;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcContext;
 use crate::rpc::RpcServiceContext;
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::sync::{
     ChannelReadCursorSyncPayload, SyncEntitiesRequest, SyncEntitiesResponse, SyncEntityItem,
 };
@@ -98,6 +100,9 @@ pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -
     let user_id = sync_authenticated_user_id(&ctx)?;
 
     let since_version = request.since_version;
+    // 增量同步通常由推送触发，必须读到触发推送的那次写入 → 走主库；
+    // 全量首屏（since_version 为空 / 0）数据量大，交给只读副本。
+    let incremental = since_version.unwrap_or(0) > 0;
     let scope = request.scope.as_deref();
     let limit = request.limit.unwrap_or(100).min(200).max(1);
 
@@ -147,29 +152,22 @@ pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -
                 .map_err(|e| RpcError::internal(format!("用户资料同步失败: {}", e)))?;
             let has_more = users.len() >= limit as usize;
             let mut next_version = since_v;
+            // 头像按各用户的可见性规则投影（本人总是可见）
+            let page_user_ids: Vec<u64> = users.iter().map(|entry| entry.user.id).collect();
+            let visibility = services
+                .privacy_service
+                .profile_visibility(user_id, &page_user_ids)
+                .await
+                .map_err(|e| RpcError::internal(format!("用户资料同步失败: {}", e)))?;
 
             let mut items: Vec<SyncEntityItem> = Vec::with_capacity(users.len());
             for entry in users {
-                let user = entry.user;
-                let updated_at = user.updated_at.timestamp_millis();
                 next_version = next_version.max(entry.sync_version);
                 items.push(SyncEntityItem {
-                    entity_id: user.id.to_string(),
+                    entity_id: entry.user.id.to_string(),
                     version: entry.sync_version,
                     deleted: false,
-                    payload: Some(json!({
-                        "user_id": user.id,
-                        "uid": user.id,
-                        // PROFILE_VISIBILITY D5:user 实体是共享公开投影,username
-                        // 仅 self 行下发;好友的 username 由 friend 实体携带。
-                        // nickname 未设置则透传 null/空——不再 fallback 为 user_<uid>
-                        "username": if user.id == user_id { user.username.clone() } else { None },
-                        "nickname": user.display_name,
-                        "avatar": user.avatar_url.clone().unwrap_or_default(),
-                        "user_type": user.user_type,
-                        "status": user.status,
-                        "updated_at": updated_at
-                    })),
+                    payload: Some(user_entity_payload(&entry.user, user_id, &visibility)),
                 });
             }
 
@@ -180,30 +178,45 @@ pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -
                 min_version: None,
             }
         }
-        "group" => services
-            .channel_service
-            .sync_entities_page_for_groups(user_id, since_version, scope, limit)
-            .await
-            .map_err(|e| RpcError::internal(format!("群组同步失败: {}", e)))?,
-        "channel" => services
-            .channel_service
-            .sync_entities_page_for_channels(user_id, since_version, scope, limit)
-            .await
-            .map_err(|e| RpcError::internal(format!("会话列表同步失败: {}", e)))?,
+        "group" => with_primary_reads_if(
+            incremental,
+            services.channel_service.sync_entities_page_for_groups(
+                user_id,
+                since_version,
+                scope,
+                limit,
+            ),
+        )
+        .await
+        .map_err(|e| RpcError::internal(format!("群组同步失败: {}", e)))?,
+        "channel" => with_primary_reads_if(
+            incremental,
+            services.channel_service.sync_entities_page_for_channels(
+                user_id,
+                since_version,
+                scope,
+                limit,
+            ),
+        )
+        .await
+        .map_err(|e| RpcError::internal(format!("会话列表同步失败: {}", e)))?,
         "group_member" => {
             validate_group_member_scope(scope)?;
-            services
-                .channel_service
-                .sync_entities_page_for_group_members(user_id, since_version, scope, limit)
-                .await
-                .map_err(|e| match e {
-                    crate::error::ServerError::Validation(msg)
-                        if msg.contains("group_member sync requires scope") =>
-                    {
-                        entity_resync_required(msg)
-                    }
-                    other => RpcError::internal(format!("群成员同步失败: {}", other)),
-                })?
+            with_primary_reads_if(
+                incremental,
+                services
+                    .channel_service
+                    .sync_entities_page_for_group_members(user_id, since_version, scope, limit),
+            )
+            .await
+            .map_err(|e| match e {
+                crate::error::ServerError::Validation(msg)
+                    if msg.contains("group_member sync requires scope") =>
+                {
+                    entity_resync_required(msg)
+                }
+                other => RpcError::internal(format!("群成员同步失败: {}", other)),
+            })?
         }
         "channel_read_cursor" => {
             let since_v = since_version.unwrap_or(0);
@@ -264,6 +277,29 @@ pub async fn handle(body: Value, services: RpcServiceContext, ctx: RpcContext) -
     serde_json::to_value(response).map_err(|e| RpcError::internal(format!("序列化响应失败: {}", e)))
 }
 
+/// user 实体的共享公开投影
+fn user_entity_payload(
+    user: &crate::model::user::User,
+    viewer_id: u64,
+    visibility: &ProfileFieldVisibility,
+) -> Value {
+    json!({
+        "user_id": user.id,
+        "uid": user.id,
+        // PROFILE_VISIBILITY D5:user 实体是共享公开投影,username
+        // 仅 self 行下发;好友的 username 由 friend 实体携带。
+        // nickname 未设置则透传 null/空——不再 fallback 为 user_<uid>
+        "username": if user.id == viewer_id { user.username.clone() } else { None },
+        "nickname": user.display_name,
+        "avatar": visibility
+            .avatar(user.id, user.avatar_url.clone())
+            .unwrap_or_default(),
+        "user_type": user.user_type,
+        "status": user.status,
+        "updated_at": user.updated_at.timestamp_millis()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,4 +361,18 @@ mod tests {
     fn valid_group_member_scope_is_accepted() {
         validate_group_member_scope(Some("group:30001")).expect("group_id scope");
     }
+
+    #[test]
+    fn user_entity_avatar_follows_the_visibility_rule() {
+        let mut user = crate::model::user::User::new(20001, "alice".to_string());
+        user.avatar_url = Some("https://cdn/a.png".to_string());
+        let hidden = ProfileFieldVisibility::hidden(10001);
+
+        let payload = user_entity_payload(&user, 10001, &hidden);
+        assert_eq!(payload["avatar"], "");
+        assert!(payload["username"].is_null());
+
+        let own = user_entity_payload(&user, 20001, &ProfileFieldVisibility::hidden(20001));
+        assert_eq!(own["avatar"], "https://cdn/a.png");
+    }
 }
@@ -133,6 +133,10 @@ impl From<crate::error::ServerError> for RpcError {
             crate::error::ServerError::ServiceUnavailable(msg) => {
                 RpcError::from_code(ErrorCode::ServiceUnavailable, msg)
             }
+            // 限流（Room 慢速模式、广播积压等）交给客户端按频率提示，而不是当作服务端故障
+            crate::error::ServerError::RateLimit(msg) => {
+                RpcError::from_code(ErrorCode::RateLimitExceeded, msg)
+            }
             _ => RpcError::internal(err.to_string()),
         }
     }
@@ -160,4 +164,12 @@ mod service_error_mapping_tests {
             "压成 InternalError 会让客户端把可重试的故障当成终局拒绝",
         );
     }
+
+    #[test]
+    fn rate_limit_is_not_reported_as_internal_error() {
+        let mapped = RpcError::from(crate::error::ServerError::RateLimit(
+            "慢速模式：请 10 秒后再发言".to_string(),
+        ));
+        assert_eq!(mapped.code, ErrorCode::RateLimitExceeded);
+    }
 }
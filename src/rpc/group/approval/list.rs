@@ -80,20 +80,13 @@ pub async fn handle(
         .await
         .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
 
-    // 2. 验证操作者权限（Owner 或 Admin）
-    let operator_member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
-
-    if !matches!(
-        operator_member.role,
-        crate::model::channel::MemberRole::Owner | crate::model::channel::MemberRole::Admin
-    ) {
-        return Err(RpcError::forbidden(
-            "只有群主或管理员可以查看审批列表".to_string(),
-        ));
-    }
+    // 2. 验证操作者权限（can_invite：审批入群与邀请同权）
+    crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_invite,
+        "只有群主或被授权的管理员可以查看审批列表",
+    )?;
 
     // 3. 获取待审批请求
     let requests = services
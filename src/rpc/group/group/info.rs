@@ -82,6 +82,31 @@ fn admin_ids(members: &[crate::model::ChannelMember]) -> Vec<u64> {
         .collect()
 }
 
+/// 管理员头衔 uid → title（群主定制，未设置的不下发）。
+fn admin_titles(members: &[crate::model::ChannelMember]) -> serde_json::Map<String, Value> {
+    members
+        .iter()
+        .filter(|member| matches!(member.role, crate::model::channel::MemberRole::Admin))
+        .filter_map(|member| {
+            member
+                .title
+                .as_ref()
+                .map(|title| (member.user_id.to_string(), Value::String(title.clone())))
+        })
+        .collect()
+}
+
+/// 请求者在本群实际生效的权限集；非成员返回 null。
+///
+/// 管理员的权限由群主逐项定制，客户端不能再按 `my_role == "admin"` 推断能做什么。
+fn my_permissions_of(members: &[crate::model::ChannelMember], requester_id: u64) -> Value {
+    members
+        .iter()
+        .find(|member| member.user_id == requester_id)
+        .and_then(|member| serde_json::to_value(&member.permissions).ok())
+        .unwrap_or(Value::Null)
+}
+
 /// 处理 群组信息 请求
 pub async fn handle(
     body: Value,
@@ -143,7 +168,9 @@ pub async fn handle(
                     // 小写契约：Debug 的首字母大写曾把三端权限判定全打挂。
                     "my_role": my_role_of(&members, requester_id),
                     // 管理员有界（个位数），够气泡打【管理】标签用；群主见 owner_id。
-                    "admin_user_ids": admin_ids(&members)
+                    "admin_user_ids": admin_ids(&members),
+                    "admin_titles": admin_titles(&members),
+                    "my_permissions": my_permissions_of(&members, requester_id)
                 },
                 "timestamp": chrono::Utc::now().timestamp_millis()
             }))
@@ -157,7 +184,7 @@ pub async fn handle(
 
 #[cfg(test)]
 mod tests {
-    use super::{admin_ids, my_role_of, owner_id_of};
+    use super::{admin_ids, admin_titles, my_permissions_of, my_role_of, owner_id_of};
     use crate::model::channel::{ChannelMember, MemberRole};
 
     fn member(user_id: u64, role: MemberRole) -> ChannelMember {
@@ -206,4 +233,22 @@ mod tests {
         ];
         assert_eq!(admin_ids(&members), vec![8, 10]);
     }
+
+    /// 头衔只属于管理员；权限集按成员实际生效的下发，非成员拿不到。
+    #[test]
+    fn titles_and_permissions_follow_the_member() {
+        let mut admin = member(8, MemberRole::Admin);
+        admin.title = Some("纪律委员".to_string());
+        admin.permissions.can_remove_member = false;
+        let mut owner = member(7, MemberRole::Owner);
+        owner.title = Some("脏数据".to_string());
+        let members = vec![owner, admin];
+
+        let titles = admin_titles(&members);
+        assert_eq!(titles.len(), 1);
+        assert_eq!(titles["8"], "纪律委员");
+        assert_eq!(my_permissions_of(&members, 8)["can_remove_member"], false);
+        assert_eq!(my_permissions_of(&members, 7)["can_remove_member"], true);
+        assert!(my_permissions_of(&members, 999).is_null());
+    }
 }
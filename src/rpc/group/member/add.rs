@@ -88,6 +88,50 @@ pub async fn handle(
         _ => crate::model::channel::MemberRole::Member,
     };
 
+    // 邀请鉴权（原来这里一处校验都没有：任何登录用户都能往任意群里加人，甚至直接指定 owner）
+    //   - 群主角色只能走转让流程；任命管理员只属于群主（同 group/role/set）
+    //   - 邀请普通成员：需 can_invite（群主 / 被授予该权限的管理员），
+    //     或群开启了「允许成员邀请」（privchat_groups.allow_member_invite 为真源）
+    let channel = services
+        .channel_service
+        .get_channel(&group_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("Group not found: {}", e)))?;
+    let inviter = channel
+        .members
+        .get(&inviter_id)
+        .ok_or_else(|| RpcError::forbidden("Inviter is not a member".to_string()))?;
+    match member_role {
+        crate::model::channel::MemberRole::Owner => {
+            return Err(RpcError::validation(
+                "Cannot add a member as owner (use ownership transfer)".to_string(),
+            ));
+        }
+        crate::model::channel::MemberRole::Admin => {
+            if !matches!(inviter.role, crate::model::channel::MemberRole::Owner) {
+                return Err(RpcError::forbidden(
+                    "Only the owner can add a member as admin".to_string(),
+                ));
+            }
+        }
+        crate::model::channel::MemberRole::Member => {
+            if !inviter.permissions.can_invite {
+                let member_can_invite = services
+                    .channel_service
+                    .get_group_policy(group_id)
+                    .await
+                    .map_err(|e| RpcError::internal(format!("查询群设置失败: {}", e)))?
+                    .unwrap_or_default()
+                    .allow_member_invite;
+                if !member_can_invite {
+                    return Err(RpcError::forbidden(
+                        "You do not have permission to invite members".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
     // ✨ 先添加到数据库的参与者表（通过 channel_service）
     if let Err(e) = services
         .channel_service
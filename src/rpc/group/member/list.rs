@@ -15,8 +15,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::model::channel::ChannelMember;
+use crate::repository::user_repo::GroupMemberUserProjection;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::group::member::{
     GroupMemberInfo, GroupMemberListRequest, GroupMemberListResponse,
 };
@@ -74,6 +77,50 @@ fn resolve_display_name(
         .unwrap_or_else(|| user_id.to_string())
 }
 
+/// 单个成员的线上投影
+fn member_info(
+    member: &ChannelMember,
+    profile: Option<&GroupMemberUserProjection>,
+    viewer_id: u64,
+    visibility: &ProfileFieldVisibility,
+) -> GroupMemberInfo {
+    let alias = non_blank(member.display_name.as_deref()).map(str::to_owned);
+    let nickname = profile
+        .and_then(|profile| non_blank(profile.display_name.as_deref()))
+        .unwrap_or_default()
+        .to_owned();
+    // PROFILE_VISIBILITY: username remains visible only to the user themself.
+    let username = if member.user_id == viewer_id {
+        profile
+            .and_then(|profile| non_blank(profile.username.as_deref()))
+            .unwrap_or_default()
+            .to_owned()
+    } else {
+        String::new()
+    };
+    GroupMemberInfo {
+        user_id: member.user_id,
+        display_name: resolve_display_name(
+            alias.as_deref(),
+            Some(&nickname),
+            Some(&username),
+            member.user_id,
+        ),
+        alias,
+        username,
+        nickname,
+        avatar_url: visibility.avatar(
+            member.user_id,
+            profile.and_then(|profile| profile.avatar_url.clone()),
+        ),
+        user_type: profile.map(|profile| profile.user_type).unwrap_or_default(),
+        // Stable lowercase role contract for every client permission gate.
+        role: wire_role(member.role),
+        joined_at: member.joined_at.timestamp_millis().max(0) as u64,
+        is_muted: member.is_muted,
+    }
+}
+
 /// 处理 群成员列表 请求
 pub async fn handle(
     body: Value,
@@ -128,44 +175,23 @@ pub async fn handle(
                 .map(|profile| (profile.user_id as u64, profile))
                 .collect::<HashMap<_, _>>();
 
+            // 头像按各成员的可见性规则投影；规则读不出来时按不可见处理。
+            let visibility = services
+                .privacy_service
+                .profile_visibility_or_hidden(request.user_id, &user_ids)
+                .await;
+
             // Always preserve roster cardinality. A missing user projection is
             // represented by the privacy-safe uid fallback, not by dropping the member.
             let member_list = members
-                .into_iter()
+                .iter()
                 .map(|member| {
-                    let profile = profiles.get(&member.user_id);
-                    let alias = non_blank(member.display_name.as_deref()).map(str::to_owned);
-                    let nickname = profile
-                        .and_then(|profile| non_blank(profile.display_name.as_deref()))
-                        .unwrap_or_default()
-                        .to_owned();
-                    // PROFILE_VISIBILITY: username remains visible only to the user themself.
-                    let username = if member.user_id == request.user_id {
-                        profile
-                            .and_then(|profile| non_blank(profile.username.as_deref()))
-                            .unwrap_or_default()
-                            .to_owned()
-                    } else {
-                        String::new()
-                    };
-                    GroupMemberInfo {
-                        user_id: member.user_id,
-                        display_name: resolve_display_name(
-                            alias.as_deref(),
-                            Some(&nickname),
-                            Some(&username),
-                            member.user_id,
-                        ),
-                        alias,
-                        username,
-                        nickname,
-                        avatar_url: profile.and_then(|profile| profile.avatar_url.clone()),
-                        user_type: profile.map(|profile| profile.user_type).unwrap_or_default(),
-                        // Stable lowercase role contract for every client permission gate.
-                        role: wire_role(member.role),
-                        joined_at: member.joined_at.timestamp_millis().max(0) as u64,
-                        is_muted: member.is_muted,
-                    }
+                    member_info(
+                        member,
+                        profiles.get(&member.user_id),
+                        request.user_id,
+                        &visibility,
+                    )
                 })
                 .collect::<Vec<_>>();
 
@@ -190,7 +216,10 @@ pub async fn handle(
 
 #[cfg(test)]
 mod tests {
-    use super::{resolve_display_name, MAX_PAGE};
+    use super::{member_info, resolve_display_name, MAX_PAGE};
+    use crate::model::channel::{ChannelMember, MemberRole};
+    use crate::repository::user_repo::GroupMemberUserProjection;
+    use crate::service::ProfileFieldVisibility;
 
 
     /// 分页窗口的算术：与 handler 里那三行一致（skip/take + 上限钳制）。
@@ -245,4 +274,33 @@ mod tests {
         );
         assert_eq!(resolve_display_name(None, None, None, 7), "7");
     }
+
+    #[test]
+    fn member_avatar_follows_the_visibility_rule() {
+        let member = ChannelMember::new(20001, MemberRole::Member);
+        let profile = GroupMemberUserProjection {
+            user_id: 20001,
+            username: Some("alice".to_string()),
+            display_name: Some("Alice".to_string()),
+            avatar_url: Some("https://cdn/a.png".to_string()),
+            user_type: 0,
+        };
+
+        let info = member_info(
+            &member,
+            Some(&profile),
+            10001,
+            &ProfileFieldVisibility::hidden(10001),
+        );
+        assert_eq!(info.avatar_url, None);
+        assert_eq!(info.nickname, "Alice");
+
+        let own = member_info(
+            &member,
+            Some(&profile),
+            20001,
+            &ProfileFieldVisibility::hidden(20001),
+        );
+        assert_eq!(own.avatar_url.as_deref(), Some("https://cdn/a.png"));
+    }
 }
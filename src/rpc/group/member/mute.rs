@@ -20,6 +20,33 @@ use crate::rpc::RpcServiceContext;
 use privchat_protocol::rpc::group::member::GroupMemberMuteRequest;
 use serde_json::{json, Value};
 
+/// 禁言 / 解禁的权限闸口：操作者须持有 `can_moderate`（群主恒有，管理员默认有、
+/// 可被群主收回），再按权限矩阵（MEMBER_MUTE：群主>管理员>成员；不能操作自己/群主）
+/// 判定能否作用于目标。
+pub(super) fn authorize_mute(
+    channel: &crate::model::channel::Channel,
+    operator_id: u64,
+    target_id: u64,
+) -> RpcResult<()> {
+    let operator_member = crate::rpc::group::require_group_permission(
+        channel,
+        operator_id,
+        |p| p.can_moderate,
+        "只有群主或有禁言权限的管理员可以执行禁言操作",
+    )?;
+    let target_member = channel
+        .members
+        .get(&target_id)
+        .ok_or_else(|| RpcError::not_found("目标用户不是群成员".to_string()))?;
+    crate::model::channel::can_moderate_mute(
+        operator_id,
+        target_id,
+        operator_member.role,
+        target_member.role,
+    )
+    .map_err(|msg| RpcError::forbidden(msg.to_string()))
+}
+
 /// 处理 禁言成员 请求
 pub async fn handle(
     body: Value,
@@ -49,28 +76,13 @@ pub async fn handle(
     };
 
     // 调用 Channel 服务禁言成员
-    // 权限矩阵（MEMBER_MUTE）：群主>管理员>成员；不能操作自己/群主。
     {
         let channel = services
             .channel_service
             .get_channel(&group_id)
             .await
             .map_err(|e| RpcError::not_found(format!("Group not found: {}", e)))?;
-        let operator_member = channel
-            .members
-            .get(&operator_id)
-            .ok_or_else(|| RpcError::forbidden("操作者不是群成员".to_string()))?;
-        let target_member = channel
-            .members
-            .get(&user_id)
-            .ok_or_else(|| RpcError::not_found("目标用户不是群成员".to_string()))?;
-        crate::model::channel::can_moderate_mute(
-            operator_id,
-            user_id,
-            operator_member.role,
-            target_member.role,
-        )
-        .map_err(|msg| RpcError::forbidden(msg.to_string()))?;
+        authorize_mute(&channel, operator_id, user_id)?;
     }
 
     match services
@@ -105,3 +117,40 @@ pub async fn handle(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::authorize_mute;
+    use crate::model::channel::{Channel, ChannelMember, MemberPermissions, MemberRole};
+
+    const OWNER: u64 = 9_300_001;
+    const ADMIN: u64 = 9_300_002;
+    const MEMBER: u64 = 9_300_003;
+
+    fn group() -> Channel {
+        let mut channel = Channel::new_group(9_300_000, OWNER, None);
+        channel
+            .members
+            .insert(ADMIN, ChannelMember::new(ADMIN, MemberRole::Admin));
+        channel
+            .members
+            .insert(MEMBER, ChannelMember::new(MEMBER, MemberRole::Member));
+        channel
+    }
+
+    /// 群主收回管理员的禁言权限后，这名管理员就不能再禁言任何人。
+    #[test]
+    fn revoking_moderation_takes_effect_on_mute() {
+        let mut channel = group();
+        assert!(authorize_mute(&channel, ADMIN, MEMBER).is_ok());
+
+        channel.members.get_mut(&ADMIN).unwrap().permissions = MemberPermissions {
+            can_moderate: false,
+            ..MemberPermissions::admin()
+        };
+        assert!(authorize_mute(&channel, ADMIN, MEMBER).is_err());
+        // 权限矩阵仍然生效：群主能禁言管理员，管理员不能禁言群主
+        assert!(authorize_mute(&channel, OWNER, ADMIN).is_ok());
+        assert!(authorize_mute(&group(), ADMIN, OWNER).is_err());
+    }
+}
@@ -41,32 +41,35 @@ pub async fn handle(
     let group_id = request.group_id;
     let user_id = request.user_id; // 被移除的目标用户（从请求体）
 
-    // 验证操作者权限（必须是群主或管理员）
+    // 验证操作者权限（can_remove_member：群主，或被授予该权限的管理员）
     let channel = services
         .channel_service
         .get_channel(&group_id)
         .await
         .map_err(|e| RpcError::not_found(format!("Group not found: {}", e)))?;
 
-    let operator_member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("Operator is not a member".to_string()))?;
+    let operator_member = crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_remove_member,
+        "You do not have permission to remove members",
+    )?;
 
-    if !matches!(
-        operator_member.role,
-        crate::model::channel::MemberRole::Owner | crate::model::channel::MemberRole::Admin
-    ) {
-        return Err(RpcError::forbidden(
-            "Only owner or admin can remove members".to_string(),
-        ));
-    }
-
-    // 验证被移除者不是群主
+    // 验证被移除者不是群主；管理员之间不能互相移除（与禁言矩阵 can_moderate_mute 一致）
     if let Some(target_member) = channel.members.get(&user_id) {
         if matches!(target_member.role, crate::model::channel::MemberRole::Owner) {
             return Err(RpcError::forbidden("Cannot remove group owner".to_string()));
         }
+        if matches!(target_member.role, crate::model::channel::MemberRole::Admin)
+            && !matches!(
+                operator_member.role,
+                crate::model::channel::MemberRole::Owner
+            )
+        {
+            return Err(RpcError::forbidden(
+                "Only the owner can remove an admin".to_string(),
+            ));
+        }
     }
     let recipients = channel.members.keys().copied().collect::<Vec<_>>();
 
@@ -22,7 +22,30 @@ pub mod qrcode;
 pub mod role;
 pub mod settings;
 
+use super::error::{RpcError, RpcResult};
 use super::RpcServiceContext;
+use crate::model::channel::{Channel, ChannelMember, MemberPermissions};
+
+/// 群管理操作的统一鉴权：操作者必须是群成员，且其生效权限集
+/// （角色默认 + 群主为管理员定制的部分）包含所需权限。
+///
+/// 各 handler 不再各自 `matches!(role, Owner | Admin)`——那样群主收回某项授权后，
+/// 管理员照样能做。返回操作者成员信息，供调用方做「管理员不能动管理员」之类的附加判断。
+pub fn require_group_permission<'a>(
+    channel: &'a Channel,
+    operator_id: u64,
+    permission: fn(&MemberPermissions) -> bool,
+    denied: &str,
+) -> RpcResult<&'a ChannelMember> {
+    let member = channel
+        .members
+        .get(&operator_id)
+        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
+    if !permission(&member.permissions) {
+        return Err(RpcError::forbidden(denied.to_string()));
+    }
+    Ok(member)
+}
 
 /// 注册群组系统的所有路由
 pub async fn register_routes(services: RpcServiceContext) {
@@ -35,3 +58,30 @@ pub async fn register_routes(services: RpcServiceContext) {
 
     tracing::debug!("📋 Group 系统路由注册完成 (group, member, qrcode, settings, role, approval)");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::require_group_permission;
+    use crate::model::channel::{Channel, MemberPermissions, MemberRole};
+
+    #[test]
+    fn revoked_admin_permission_is_enforced() {
+        let mut channel = Channel::new_group(1, 10, None);
+        channel.add_member(20, Some(MemberRole::Admin)).unwrap();
+        channel.add_member(30, None).unwrap();
+
+        assert!(require_group_permission(&channel, 20, |p| p.can_pin_message, "x").is_ok());
+
+        channel.members.get_mut(&20).unwrap().permissions = MemberPermissions {
+            can_pin_message: false,
+            ..MemberPermissions::admin()
+        };
+        assert!(
+            require_group_permission(&channel, 20, |p| p.can_pin_message, "x").is_err(),
+            "群主收回的授权不能靠角色绕过",
+        );
+        assert!(require_group_permission(&channel, 30, |p| p.can_pin_message, "x").is_err());
+        assert!(require_group_permission(&channel, 99, |p| p.can_send_message, "x").is_err());
+        assert!(require_group_permission(&channel, 10, |p| p.can_pin_message, "x").is_ok());
+    }
+}
@@ -43,19 +43,13 @@ pub async fn handle(
         .await
         .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
 
-    let operator_member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
-
-    if !matches!(
-        operator_member.role,
-        crate::model::channel::MemberRole::Owner | crate::model::channel::MemberRole::Admin,
-    ) {
-        return Err(RpcError::forbidden(
-            "只有群主或管理员可以旋转群二维码".to_string(),
-        ));
-    }
+    // 群二维码即邀请入口，旋转它与邀请同权（can_invite）
+    crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_invite,
+        "只有群主或被授权的管理员可以旋转群二维码",
+    )?;
 
     // 2. 取旧 qr_key（仅用于响应回显，让客户端知道哪一张失效了）
     let old_qr_key: String =
@@ -15,6 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod permissions;
 pub mod set;
 pub mod transfer_owner;
 
@@ -45,5 +46,15 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
-    tracing::debug!("📋 Role 模块路由注册完成 (transfer_owner, set)");
+    router
+        .register("group/role/permissions", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { permissions::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!("📋 Role 模块路由注册完成 (transfer_owner, set, permissions)");
 }
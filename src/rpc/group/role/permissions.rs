@@ -0,0 +1,137 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::{MemberPermissions, MemberRole};
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 管理员头衔最大长度（字符数，与 privchat_group_members.title 列宽一致）
+const MAX_TITLE_CHARS: usize = 32;
+
+/// 处理 设置管理员权限与头衔 请求（`group/role/permissions`）
+///
+/// 参数：`group_id`、`user_id`（目标管理员）、`permissions`（完整 MemberPermissions 对象；
+/// 省略或 null = 恢复管理员默认权限）、`title`（省略、null 或空串 = 清除头衔）。
+/// 只有群主可以调用；目标必须是管理员。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 设置管理员权限 请求: {:?}", body);
+
+    let group_id = crate::rpc::parse_u64_param(&body, "group_id")?;
+    let user_id = crate::rpc::parse_u64_param(&body, "user_id")?;
+    let permissions = parse_permissions(body.get("permissions"))?;
+    let title = parse_title(body.get("title"))?;
+
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let channel = services
+        .channel_service
+        .get_channel(&group_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
+    let operator = channel
+        .members
+        .get(&operator_id)
+        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
+    // 与任免管理员同一条规则：只认群主（管理员的 can_manage_permissions 恒为 false）
+    if !matches!(operator.role, MemberRole::Owner) {
+        return Err(RpcError::forbidden(
+            "只有群主可以设置管理员权限".to_string(),
+        ));
+    }
+
+    let effective = services
+        .channel_service
+        .set_admin_permissions(group_id, user_id, permissions, title.clone())
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 管理员权限已更新: group={}, user={}, operator={}",
+        group_id,
+        user_id,
+        operator_id
+    );
+    Ok(json!({
+        "success": true,
+        "group_id": group_id,
+        "user_id": user_id,
+        "permissions": effective,
+        "title": title,
+    }))
+}
+
+fn parse_permissions(value: Option<&Value>) -> RpcResult<Option<MemberPermissions>> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| RpcError::validation(format!("permissions 格式错误: {}", e))),
+    }
+}
+
+fn parse_title(value: Option<&Value>) -> RpcResult<Option<String>> {
+    let title = match value {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(s)) => s.trim(),
+        Some(_) => return Err(RpcError::validation("title 必须是字符串".to_string())),
+    };
+    if title.is_empty() {
+        return Ok(None);
+    }
+    if title.chars().count() > MAX_TITLE_CHARS {
+        return Err(RpcError::validation(format!(
+            "头衔不能超过 {} 个字符",
+            MAX_TITLE_CHARS
+        )));
+    }
+    Ok(Some(title.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_permissions, parse_title};
+    use crate::model::channel::MemberPermissions;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn title_is_trimmed_and_bounded() {
+        assert_eq!(parse_title(None).unwrap(), None);
+        assert_eq!(parse_title(Some(&json!("  "))).unwrap(), None);
+        assert_eq!(
+            parse_title(Some(&json!(" 纪律委员 "))).unwrap().as_deref(),
+            Some("纪律委员")
+        );
+        assert!(parse_title(Some(&json!("长".repeat(33)))).is_err());
+        assert!(parse_title(Some(&json!(1))).is_err());
+    }
+
+    #[test]
+    fn permissions_must_be_a_complete_set() {
+        assert_eq!(parse_permissions(Some(&Value::Null)).unwrap(), None);
+        let full = serde_json::to_value(MemberPermissions::admin()).unwrap();
+        assert_eq!(
+            parse_permissions(Some(&full)).unwrap(),
+            Some(MemberPermissions::admin())
+        );
+        assert!(parse_permissions(Some(&json!({ "can_invite": true }))).is_err());
+    }
+}
@@ -40,6 +40,7 @@ use serde_json::Value;
 ///     "join_need_approval": false,
 ///     "member_can_invite": false,
 ///     "all_muted": false,
+///     "slow_mode_secs": 0,
 ///     "max_members": 500,
 ///     "announcement": "欢迎加入群组",
 ///     "description": "技术交流群",
@@ -111,5 +112,14 @@ pub async fn handle(
     tracing::debug!("✅ 获取群设置成功: group_id={}", group_id);
 
     let response = GroupSettingsGetResponse { group_id, settings };
-    serde_json::to_value(response).map_err(|e| RpcError::internal(format!("序列化响应失败: {}", e)))
+    let mut value = serde_json::to_value(response)
+        .map_err(|e| RpcError::internal(format!("序列化响应失败: {}", e)))?;
+    // 慢速模式尚不在协议的 GroupSettingsData 里，随 settings 对象一并下发
+    if let Some(settings) = value.get_mut("settings").and_then(Value::as_object_mut) {
+        settings.insert(
+            "slow_mode_secs".to_string(),
+            Value::from(policy.slow_mode_secs),
+        );
+    }
+    Ok(value)
 }
@@ -17,6 +17,8 @@
 
 pub mod get;
 pub mod mute_all;
+pub mod reactions;
+pub mod slow_mode;
 pub mod update;
 
 use super::super::router::GLOBAL_RPC_ROUTER;
@@ -56,5 +58,37 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
-    tracing::debug!("📋 Settings 模块路由注册完成 (get, update, mute_all)");
+    router
+        .register("group/settings/slow_mode", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { slow_mode::handle(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("group/settings/reactions/get", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { reactions::handle_get(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    router
+        .register("group/settings/reactions/update", {
+            let services = services.clone();
+            Box::new(move |body, ctx| {
+                let services = services.clone();
+                Box::pin(async move { reactions::handle_update(body, services, ctx).await })
+            })
+        })
+        .await;
+
+    tracing::debug!(
+        "📋 Settings 模块路由注册完成 (get, update, mute_all, slow_mode, reactions/get, reactions/update)"
+    );
 }
@@ -19,6 +19,18 @@ use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
 use serde_json::{json, Value};
 
+/// 全员禁言属于秩序管理（`can_moderate`），与禁言单个成员同一权限：
+/// 管理员默认持有，群主可逐人收回。
+fn authorize_mute_all(channel: &crate::model::channel::Channel, operator_id: u64) -> RpcResult<()> {
+    crate::rpc::group::require_group_permission(
+        channel,
+        operator_id,
+        |p| p.can_moderate,
+        "只有群主或有禁言权限的管理员可以设置全员禁言",
+    )?;
+    Ok(())
+}
+
 /// 处理 设置全员禁言 请求
 ///
 /// RPC: group/settings/mute_all
@@ -29,7 +41,7 @@ use serde_json::{json, Value};
 /// ```json
 /// {
 ///   "group_id": "group_123",
-///   "operator_id": "alice",   // 操作者ID（Owner/Admin）
+///   "operator_id": "alice",   // 操作者ID（需 can_moderate）
 ///   "muted": true              // true=开启全员禁言，false=关闭
 /// }
 /// ```
@@ -74,20 +86,8 @@ pub async fn handle(
         .await
         .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
 
-    // 2. 验证操作者权限（Owner 或 Admin）
-    let operator_member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
-
-    if !matches!(
-        operator_member.role,
-        crate::model::channel::MemberRole::Owner | crate::model::channel::MemberRole::Admin
-    ) {
-        return Err(RpcError::forbidden(
-            "只有群主或管理员可以设置全员禁言".to_string(),
-        ));
-    }
+    // 2. 验证操作者权限（can_moderate）
+    authorize_mute_all(&channel, operator_id)?;
 
     // 3. 设置全员禁言
     services
@@ -115,3 +115,43 @@ pub async fn handle(
         "updated_at": chrono::Utc::now().timestamp_millis()
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::authorize_mute_all;
+    use crate::model::channel::{Channel, ChannelMember, MemberPermissions, MemberRole};
+
+    const OWNER: u64 = 9_200_001;
+    const ADMIN: u64 = 9_200_002;
+    const MEMBER: u64 = 9_200_003;
+
+    fn group() -> Channel {
+        let mut channel = Channel::new_group(9_200_000, OWNER, None);
+        channel
+            .members
+            .insert(ADMIN, ChannelMember::new(ADMIN, MemberRole::Admin));
+        channel
+            .members
+            .insert(MEMBER, ChannelMember::new(MEMBER, MemberRole::Member));
+        channel
+    }
+
+    /// 默认权限的管理员仍能全员禁言（这一直是管理员的能力，不能因细分权限而丢失）。
+    #[test]
+    fn a_default_admin_may_mute_all() {
+        let channel = group();
+        assert!(authorize_mute_all(&channel, OWNER).is_ok());
+        assert!(authorize_mute_all(&channel, ADMIN).is_ok());
+        assert!(authorize_mute_all(&channel, MEMBER).is_err());
+    }
+
+    #[test]
+    fn an_admin_without_moderation_may_not_mute_all() {
+        let mut channel = group();
+        channel.members.get_mut(&ADMIN).unwrap().permissions = MemberPermissions {
+            can_moderate: false,
+            ..MemberPermissions::admin()
+        };
+        assert!(authorize_mute_all(&channel, ADMIN).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 群 Reaction 白名单
+//!
+//! - `group/settings/reactions/get`：群成员查询本群允许的 Reaction
+//! - `group/settings/reactions/update`：群主 / 管理员设置白名单
+//!
+//! `allowed_reactions` 为 `null` 表示不限制，空数组表示本群关闭 Reaction；
+//! 条目为 Unicode emoji 或自定义表情 `sticker:<sticker_id>`。
+
+use crate::model::channel::Channel;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 兼容数字与字符串两种形态的 group_id
+fn parse_group_id(body: &Value) -> RpcResult<u64> {
+    match body.get("group_id") {
+        Some(Value::String(s)) => s.parse::<u64>().ok(),
+        Some(v) => v.as_u64(),
+        None => None,
+    }
+    .ok_or_else(|| RpcError::validation("group_id is required".to_string()))
+}
+
+async fn load_group(services: &RpcServiceContext, group_id: u64) -> RpcResult<Channel> {
+    let channel = services
+        .channel_service
+        .get_channel(&group_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
+    if channel.group_id != Some(group_id) {
+        return Err(RpcError::not_found("群组不存在".to_string()));
+    }
+    Ok(channel)
+}
+
+/// 处理 查询群 Reaction 白名单 请求
+///
+/// 请求：`{ "group_id": 123 }`
+///
+/// 响应：`{ "group_id": 123, "allowed_reactions": ["👍", "sticker:cat_01"] | null }`
+pub async fn handle_get(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let group_id = parse_group_id(&body)?;
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let channel = load_group(&services, group_id).await?;
+    if !channel.members.contains_key(&user_id) {
+        return Err(RpcError::forbidden("您不是群组成员".to_string()));
+    }
+
+    let allowed = services
+        .reaction_service
+        .get_allowed_reactions(channel.id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!({
+        "group_id": group_id,
+        "allowed_reactions": allowed,
+    }))
+}
+
+/// 处理 设置群 Reaction 白名单 请求（群主 / 管理员）
+///
+/// 请求：`{ "group_id": 123, "allowed_reactions": ["👍", "❤️"] }`，传 `null` 恢复不限制
+///
+/// 响应：`{ "group_id": 123, "allowed_reactions": [...] | null }`（规范化、去重后的白名单）
+pub async fn handle_update(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let group_id = parse_group_id(&body)?;
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+    let allowed: Option<Vec<String>> = match body.get("allowed_reactions") {
+        None => {
+            return Err(RpcError::validation(
+                "allowed_reactions is required".to_string(),
+            ))
+        }
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| RpcError::validation(format!("allowed_reactions 格式错误: {}", e)))?,
+    };
+
+    let channel = load_group(&services, group_id).await?;
+    crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_moderate,
+        "只有群主或有管理权限的管理员可以设置 Reaction 白名单",
+    )?;
+
+    let saved = services
+        .reaction_service
+        .set_allowed_reactions(channel.id, operator_id, allowed.as_deref())
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 群 Reaction 白名单已更新: group_id={}, operator={}, allowed={:?}",
+        group_id,
+        operator_id,
+        saved
+    );
+    Ok(json!({
+        "group_id": group_id,
+        "allowed_reactions": saved,
+    }))
+}
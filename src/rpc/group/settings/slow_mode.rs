@@ -0,0 +1,132 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::channel::MAX_GROUP_SLOW_MODE_SECS;
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use crate::service::EntityInvalidationPublisher;
+use privchat_protocol::EntityMutationHint;
+use serde_json::{json, Value};
+
+/// 处理 设置群慢速模式 请求
+///
+/// RPC: group/settings/slow_mode
+///
+/// 请求参数：
+/// ```json
+/// {
+///   "group_id": 123,
+///   "slow_mode_secs": 30   // 普通成员两次发言的最小间隔，0 = 关闭，上限 3600
+/// }
+/// ```
+///
+/// 响应：
+/// ```json
+/// {
+///   "success": true,
+///   "group_id": 123,
+///   "slow_mode_secs": 30,
+///   "updated_at": 1736000000000
+/// }
+/// ```
+///
+/// 需 `can_edit_settings`（群主，或被授权的管理员）。群主/管理员自身不受慢速模式限制。
+pub async fn handle(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    tracing::debug!("🔧 处理 设置群慢速模式 请求: {:?}", body);
+
+    let group_id = crate::rpc::parse_u64_param(&body, "group_id")?;
+    let slow_mode_secs = parse_slow_mode_secs(&body)?;
+    let operator_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let channel = services
+        .channel_service
+        .get_channel(&group_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
+    crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_edit_settings,
+        "只有群主或被授权的管理员可以设置慢速模式",
+    )?;
+
+    services
+        .channel_service
+        .set_group_slow_mode(group_id, slow_mode_secs)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 群慢速模式已更新: group_id={}, slow_mode_secs={}, operator_id={}",
+        group_id,
+        slow_mode_secs,
+        operator_id
+    );
+
+    // 群设置随 group 实体下发：通知成员重新拉取（group/settings/get 带 slow_mode_secs）
+    let recipients = channel.members.keys().copied().collect::<Vec<_>>();
+    let publisher = EntityInvalidationPublisher::new(services.connection_manager.clone());
+    if let Err(error) = publisher
+        .publish_group_projection_change(recipients, group_id, EntityMutationHint::Upsert)
+        .await
+    {
+        tracing::warn!(group_id, %error, "group slow mode invalidation failed");
+    }
+
+    Ok(json!({
+        "success": true,
+        "group_id": group_id,
+        "slow_mode_secs": slow_mode_secs,
+        "updated_at": chrono::Utc::now().timestamp_millis()
+    }))
+}
+
+fn parse_slow_mode_secs(body: &Value) -> RpcResult<u32> {
+    let secs = crate::rpc::parse_u64_param(body, "slow_mode_secs")?;
+    if secs > MAX_GROUP_SLOW_MODE_SECS as u64 {
+        return Err(RpcError::validation(format!(
+            "slow_mode_secs 不能超过 {}",
+            MAX_GROUP_SLOW_MODE_SECS
+        )));
+    }
+    Ok(secs as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_slow_mode_secs;
+    use serde_json::json;
+
+    #[test]
+    fn slow_mode_interval_is_bounded() {
+        assert_eq!(
+            parse_slow_mode_secs(&json!({ "slow_mode_secs": 0 })).unwrap(),
+            0
+        );
+        assert_eq!(
+            parse_slow_mode_secs(&json!({ "slow_mode_secs": 3600 })).unwrap(),
+            3600
+        );
+        assert!(parse_slow_mode_secs(&json!({ "slow_mode_secs": 3601 })).is_err());
+        assert!(parse_slow_mode_secs(&json!({ "slow_mode_secs": -1 })).is_err());
+        assert!(parse_slow_mode_secs(&json!({})).is_err());
+    }
+}
@@ -28,7 +28,8 @@ use serde_json::Value;
 ///
 /// 请求/响应均使用 privchat-protocol 的 typed 类型
 /// ([`GroupSettingsUpdateRequest`] / [`GroupSettingsUpdateResponse`])，不手写 JSON 解析。
-/// 谁可以改群设置：**必须是本群成员，且必须是群主**。
+/// 谁可以改群设置：**必须是本群成员，且持有 `can_edit_settings`**——
+/// 群主恒有；管理员默认没有，须由群主通过 `group/role/permissions` 单独授予。
 ///
 /// 抽成函数是为了能对它写行为用例——它是这条写入路径上唯一的权限闸口，
 /// 而 handler 本体要跑起来得先造出三十多个服务依赖。
@@ -36,14 +37,12 @@ fn authorize_settings_update(
     channel: &crate::model::channel::Channel,
     operator_id: u64,
 ) -> RpcResult<()> {
-    let member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
-
-    if !matches!(member.role, crate::model::channel::MemberRole::Owner) {
-        return Err(RpcError::forbidden("只有群主可以修改群设置".to_string()));
-    }
+    crate::rpc::group::require_group_permission(
+        channel,
+        operator_id,
+        |p| p.can_edit_settings,
+        "只有群主或被授权的管理员可以修改群设置",
+    )?;
     Ok(())
 }
 
@@ -74,7 +73,7 @@ pub async fn handle(
         .await
         .map_err(|e| RpcError::not_found(format!("群组不存在: {}", e)))?;
 
-    // 3. 验证操作者权限（仅群主可以修改群设置）
+    // 3. 验证操作者权限（can_edit_settings）
     authorize_settings_update(&channel, operator_id)?;
 
     // 4. 从 typed patch 取各可选项
@@ -338,6 +337,20 @@ mod tests {
         }
     }
 
+    /// 群主显式授予 can_edit_settings 的管理员可以改群设置；授权跟人走，不跟角色走。
+    #[test]
+    fn an_admin_granted_edit_settings_may_change_group_settings() {
+        let mut channel = group();
+        channel.members.get_mut(&ADMIN).unwrap().permissions =
+            crate::model::channel::MemberPermissions {
+                can_edit_settings: true,
+                ..crate::model::channel::MemberPermissions::admin()
+            };
+
+        assert!(authorize_settings_update(&channel, ADMIN).is_ok());
+        assert!(authorize_settings_update(&channel, MEMBER).is_err());
+    }
+
     /// 认证上下文缺失 → 未认证，**不能**回落到客户端自报的 operator_id。
     ///
     /// 原实现是 `.unwrap_or(request.operator_id)`：没有认证信息时用请求体里的 id
@@ -73,11 +73,16 @@ pub async fn handle(
     };
 
     // 从数据库查询消息列表（仓库返回 DESC 最新在先，此处反转为 ASC 便于客户端按 1→2→3 展示）
-    match MessageRepository::list_by_channel(
-        services.message_repository.as_ref(),
-        channel_id,
-        limit,
-        before_created_at,
+    // 最新一页往往紧跟在推送之后拉取，必须读到刚提交的消息 → 走主库；
+    // 向前翻页读的是已落定的历史，可以走只读副本。
+    match crate::infra::with_primary_reads_if(
+        before_created_at.is_none(),
+        MessageRepository::list_by_channel(
+            services.message_repository.as_ref(),
+            channel_id,
+            limit,
+            before_created_at,
+        ),
     )
     .await
     {
@@ -0,0 +1,75 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 未读 @ 查询 RPC handler
+//!
+//! - `message/mention/unread`：按会话返回未读 @ 数与消息 ID，供客户端显示「有人@我」
+//!   并在未读 @ 间跳转。
+//!
+//! 未读以会话已读 pts 为准：客户端走现有的已读上报推进 pts 后，对应的未读 @ 自然清零，
+//! 不需要单独的「清除 @」接口。
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use crate::service::mention_service::MAX_UNREAD_MENTION_IDS;
+use serde_json::{json, Value};
+
+/// 兼容数字与字符串两种形态的 u64 字段
+fn parse_optional_u64(body: &Value, key: &str) -> RpcResult<Option<u64>> {
+    match body.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => s
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| RpcError::validation(format!("{} 格式错误", key))),
+        Some(v) => v
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| RpcError::validation(format!("{} 格式错误", key))),
+    }
+}
+
+/// 处理 查询未读 @ 请求：`message/mention/unread`
+///
+/// 请求：`{ "channel_id": 123 (可选，不传查全部会话), "limit": 20 (可选，每会话返回的消息 ID 数) }`
+///
+/// 响应：`{ "channels": [{ "channel_id": 123, "unread_count": 3, "message_ids": [..] }], "total": 3 }`
+/// `message_ids` 按 pts 升序，最早的未读 @ 在前。
+pub async fn handle_unread(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+    let channel_id = parse_optional_u64(&body, "channel_id")?;
+    let limit = parse_optional_u64(&body, "limit")?
+        .map(|v| v as usize)
+        .unwrap_or(MAX_UNREAD_MENTION_IDS);
+
+    let channels = services
+        .mention_service
+        .get_unread_mentions(user_id, channel_id, limit)
+        .await
+        .map_err(RpcError::from)?;
+    let total: u64 = channels.iter().map(|c| c.unread_count).sum();
+
+    Ok(json!({
+        "channels": channels,
+        "total": total,
+    }))
+}
+
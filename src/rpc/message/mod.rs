@@ -16,9 +16,11 @@
 // limitations under the License.
 
 pub mod history;
+pub mod mention;
 pub mod pin;
 pub mod reaction;
 pub mod revoke;
+pub mod self_destruct;
 pub mod status;
 
 use super::router::GLOBAL_RPC_ROUTER;
@@ -76,7 +78,29 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    // 注册未读 @ 查询路由
+    GLOBAL_RPC_ROUTER
+        .register("message/mention/unread", {
+            let services = services.clone();
+            move |params, ctx| {
+                let services = services.clone();
+                Box::pin(async move { mention::handle_unread(params, services, ctx).await })
+            }
+        })
+        .await;
+
+    // 注册单条消息阅后即焚路由（发送方补设计时）
+    GLOBAL_RPC_ROUTER
+        .register("message/self_destruct/set", {
+            let services = services.clone();
+            move |params, ctx| {
+                let services = services.clone();
+                Box::pin(async move { self_destruct::handle_set(params, services, ctx).await })
+            }
+        })
+        .await;
+
     tracing::debug!(
-        "📋 Message 系统路由注册完成 (history, status, reaction, revoke, pin, pin/list)"
+        "📋 Message 系统路由注册完成 (history, status, reaction, revoke, pin, pin/list, mention/unread, self_destruct/set)"
     );
 }
@@ -33,7 +33,8 @@ use privchat_protocol::rpc::message::pin::{
 };
 use serde_json::Value;
 
-/// 校验操作者是该群的群主 / 管理员；返回群对应的 channel 与操作者角色。
+/// 校验操作者持有置顶权限（`can_pin_message`：群主，或被授予该权限的管理员）；
+/// 返回群对应的 channel 与操作者角色。
 /// 返回错误则无权操作。
 async fn ensure_group_manager(
     services: &RpcServiceContext,
@@ -51,17 +52,13 @@ async fn ensure_group_manager(
         return Err(RpcError::not_found("群组不存在".to_string()));
     }
 
-    let member = channel
-        .members
-        .get(&operator_id)
-        .ok_or_else(|| RpcError::forbidden("您不是群组成员".to_string()))?;
-
-    let role = member.role;
-    if !matches!(role, MemberRole::Owner | MemberRole::Admin) {
-        return Err(RpcError::forbidden(
-            "只有群主或管理员可以置顶消息".to_string(),
-        ));
-    }
+    let role = crate::rpc::group::require_group_permission(
+        &channel,
+        operator_id,
+        |p| p.can_pin_message,
+        "只有群主或被授权的管理员可以置顶消息",
+    )?
+    .role;
     Ok((channel, role))
 }
 
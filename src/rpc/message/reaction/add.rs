@@ -15,14 +15,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::repository::MessageRepository;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
+use crate::service::ReactionKey;
 use privchat_protocol::rpc::message::reaction::MessageReactionAddRequest;
-use privchat_protocol::{CanonicalTimelineEvent, ReactionChangeEvent, ReactionOperation};
+use privchat_protocol::ReactionOperation;
 use serde_json::{json, Value};
 
 /// 处理 添加 Reaction 请求
+///
+/// `emoji` 为 Unicode emoji 或自定义表情 `sticker:<sticker_id>`；同一用户可对一条消息
+/// 添加多个不同的 Reaction（上限见 `MAX_REACTIONS_PER_USER`），群可设置白名单。
 pub async fn handle(
     body: Value,
     services: RpcServiceContext,
@@ -39,66 +42,41 @@ pub async fn handle(
 
     let user_id = request.user_id;
     let message_id = request.server_message_id;
-    let emoji = &request.emoji;
+    let key = ReactionKey::parse(&request.emoji).map_err(RpcError::from)?;
+    let channel = super::load_reaction_target(&services, message_id, user_id).await?;
 
     // Handler 只返回 data 负载，外层 code/message 由 RPC 层封装；协议约定 data 为裸 bool
-    match services
+    let added = services
         .reaction_service
-        .add_reaction(message_id, user_id, &emoji)
+        .add_reaction(message_id, channel.id, user_id, &key)
         .await
-    {
-        Ok(reaction) => {
-            if let Ok(Some(message)) = services
-                .message_repository
-                .as_ref()
-                .find_by_id(message_id)
-                .await
-            {
-                let channel_id = message.channel_id;
-                let channel_type = services
-                    .channel_service
-                    .get_channel(&channel_id)
-                    .await
-                    // sync commit 要 wire 编号（Direct=1/Group=2/Room=3），
-                    // 不是 DB 编号（0/1/2）。直接传 DB 值会让 message_repo 的
-                    // 类型校验拒写（Direct 的 0 更是非法 wire 值），reaction
-                    // 的 pts commit 静默失败、客户端收不到表态更新。
-                    .map(|channel| channel.channel_type.to_wire_u8())
-                    .unwrap_or_else(|_| crate::model::channel::ChannelType::Direct.to_wire_u8());
-                if let Err(e) = services
-                    .sync_service
-                    .append_server_event_commit(
-                        channel_id,
-                        channel_type,
-                        CanonicalTimelineEvent::ReactionChange(ReactionChangeEvent {
-                            target_server_message_id: message_id,
-                            actor_id: user_id,
-                            emoji: emoji.clone(),
-                            operation: ReactionOperation::Add,
-                        }),
-                        user_id,
-                    )
-                    .await
-                {
-                    tracing::warn!("⚠️ 写入 reaction pts commit 失败: {}", e);
-                }
-            }
-            tracing::debug!(
-                "✅ 成功添加 Reaction: user={}, message={}, emoji={}",
-                user_id,
-                message_id,
-                emoji
-            );
-            Ok(json!(true))
-        }
-        Err(e) => {
-            tracing::error!(
+        .map_err(|e| {
+            tracing::warn!(
                 "❌ 添加 Reaction 失败: user={}, message={}, error={}",
                 user_id,
                 message_id,
                 e
             );
-            Err(RpcError::internal(format!("添加 Reaction 失败: {}", e)))
-        }
+            RpcError::from(e)
+        })?;
+
+    if added {
+        super::commit_reaction_change(
+            &services,
+            &channel,
+            message_id,
+            user_id,
+            key.as_stored(),
+            ReactionOperation::Add,
+        )
+        .await;
     }
+    tracing::debug!(
+        "✅ 成功添加 Reaction: user={}, message={}, emoji={}, added={}",
+        user_id,
+        message_id,
+        key.as_stored(),
+        added
+    );
+    Ok(json!(true))
 }
@@ -52,7 +52,8 @@ pub async fn handle(
             Ok(json!({
                 "success": true,
                 "reactions": stats.reactions,
-                "total_count": stats.total_count
+                "total_count": stats.total_count,
+                "custom_emoji": stats.custom_emoji
             }))
         }
         Err(e) => {
@@ -22,6 +22,10 @@ pub mod stats;
 
 use super::super::router::GLOBAL_RPC_ROUTER;
 use super::super::RpcServiceContext;
+use crate::model::channel::{Channel, ChannelType};
+use crate::repository::MessageRepository;
+use crate::rpc::error::{RpcError, RpcResult};
+use privchat_protocol::{CanonicalTimelineEvent, ReactionChangeEvent, ReactionOperation};
 
 /// 注册 Reaction 模块的所有路由
 pub async fn register_routes(services: RpcServiceContext) {
@@ -69,3 +73,68 @@ pub async fn register_routes(services: RpcServiceContext) {
 
     tracing::debug!("📋 Reaction 模块路由注册完成 (add, remove, list, stats)");
 }
+
+/// 取消息所在会话并校验操作者可见：Room 之外必须是会话成员，已撤回的消息不能再表态
+pub(super) async fn load_reaction_target(
+    services: &RpcServiceContext,
+    message_id: u64,
+    user_id: u64,
+) -> RpcResult<Channel> {
+    let message = services
+        .message_repository
+        .as_ref()
+        .find_by_id(message_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询消息失败: {}", e)))?
+        .ok_or_else(|| RpcError::not_found("消息不存在".to_string()))?;
+    if message.revoked {
+        return Err(RpcError::validation("消息已被撤回".to_string()));
+    }
+    let channel = services
+        .channel_service
+        .get_channel(&message.channel_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("会话不存在: {}", e)))?;
+    if channel.channel_type != ChannelType::Room && !channel.is_member(user_id) {
+        return Err(RpcError::forbidden("您不是该会话成员".to_string()));
+    }
+    Ok(channel)
+}
+
+/// Reaction 变化写入会话 timeline（pts commit），其它设备与成员按增量同步收到
+///
+/// 只在状态真正变化时调用：重复添加 / 移除不存在的 Reaction 不产生 commit。
+pub(super) async fn commit_reaction_change(
+    services: &RpcServiceContext,
+    channel: &Channel,
+    message_id: u64,
+    user_id: u64,
+    emoji: String,
+    operation: ReactionOperation,
+) {
+    // sync commit 要 wire 编号（Direct=1/Group=2/Room=3），不是 DB 编号（0/1/2）。
+    // 直接传 DB 值会让 message_repo 的类型校验拒写（Direct 的 0 更是非法 wire 值），
+    // reaction 的 pts commit 静默失败、客户端收不到表态更新。
+    if let Err(e) = services
+        .sync_service
+        .append_server_event_commit(
+            channel.id,
+            channel.channel_type.to_wire_u8(),
+            CanonicalTimelineEvent::ReactionChange(ReactionChangeEvent {
+                target_server_message_id: message_id,
+                actor_id: user_id,
+                emoji,
+                operation,
+            }),
+            user_id,
+        )
+        .await
+    {
+        tracing::warn!(
+            "⚠️ 写入 reaction pts commit 失败: message={}, user={}, err={}",
+            message_id,
+            user_id,
+            e
+        );
+    }
+}
@@ -15,14 +15,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::repository::MessageRepository;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
 use privchat_protocol::rpc::message::reaction::MessageReactionRemoveRequest;
-use privchat_protocol::{CanonicalTimelineEvent, ReactionChangeEvent, ReactionOperation};
+use privchat_protocol::ReactionOperation;
 use serde_json::{json, Value};
 
 /// 处理 移除 Reaction 请求
+///
+/// 指定 `emoji` 时只移除该 Reaction；`emoji` 为空时移除当前用户对该消息的全部 Reaction。
 pub async fn handle(
     body: Value,
     services: RpcServiceContext,
@@ -39,73 +40,54 @@ pub async fn handle(
 
     let user_id = request.user_id;
     let message_id = request.server_message_id;
-    let requested_emoji = &request.emoji;
+    let requested_emoji = request.emoji.trim();
+    let channel = super::load_reaction_target(&services, message_id, user_id).await?;
 
-    // Handler 只返回 data 负载，外层 code/message 由 RPC 层封装；协议约定 data 为裸 bool
-    match services
-        .reaction_service
-        .remove_reaction(message_id, user_id)
-        .await
-    {
-        Ok(removed) => {
-            if let Ok(Some(message)) = services
-                .message_repository
-                .as_ref()
-                .find_by_id(message_id)
-                .await
-            {
-                let channel_id = message.channel_id;
-                let channel_type = services
-                    .channel_service
-                    .get_channel(&channel_id)
-                    .await
-                    // sync commit 要 wire 编号（Direct=1/Group=2/Room=3），
-                    // 不是 DB 编号（0/1/2）。直接传 DB 值会让 message_repo 的
-                    // 类型校验拒写（Direct 的 0 更是非法 wire 值），reaction
-                    // 的 pts commit 静默失败、客户端收不到表态更新。
-                    .map(|channel| channel.channel_type.to_wire_u8())
-                    .unwrap_or_else(|_| crate::model::channel::ChannelType::Direct.to_wire_u8());
-                let emoji = removed
-                    .as_ref()
-                    .map(|reaction| reaction.emoji.clone())
-                    .unwrap_or_else(|| requested_emoji.clone());
-                if let Err(e) = services
-                    .sync_service
-                    .append_server_event_commit(
-                        channel_id,
-                        channel_type,
-                        CanonicalTimelineEvent::ReactionChange(ReactionChangeEvent {
-                            target_server_message_id: message_id,
-                            actor_id: user_id,
-                            emoji,
-                            operation: ReactionOperation::Remove,
-                        }),
-                        user_id,
-                    )
-                    .await
-                {
-                    tracing::warn!("⚠️ 写入 reaction remove pts commit 失败: {}", e);
+    let removed = if requested_emoji.is_empty() {
+        services
+            .reaction_service
+            .remove_user_reactions(message_id, user_id)
+            .await
+    } else {
+        services
+            .reaction_service
+            .remove_reaction(message_id, user_id, requested_emoji)
+            .await
+            .map(|removed| {
+                if removed {
+                    vec![requested_emoji.to_string()]
+                } else {
+                    Vec::new()
                 }
-            }
-            tracing::debug!(
-                "✅ 成功移除 Reaction: user={}, message={}, emoji={}",
-                user_id,
-                message_id,
-                removed
-                    .as_ref()
-                    .map(|reaction| reaction.emoji.as_str())
-                    .unwrap_or(requested_emoji)
-            );
-            Ok(json!(true))
-        }
-        Err(e) => {
-            tracing::error!(
-                "❌ 移除 Reaction 失败: user={}, message={}, error={}",
-                user_id,
-                message_id,
-                e
-            );
-            Err(RpcError::internal(format!("移除 Reaction 失败: {}", e)))
-        }
+            })
+    }
+    .map_err(|e| {
+        tracing::error!(
+            "❌ 移除 Reaction 失败: user={}, message={}, error={}",
+            user_id,
+            message_id,
+            e
+        );
+        RpcError::from(e)
+    })?;
+
+    // Handler 只返回 data 负载，外层 code/message 由 RPC 层封装；协议约定 data 为裸 bool
+    for emoji in &removed {
+        super::commit_reaction_change(
+            &services,
+            &channel,
+            message_id,
+            user_id,
+            emoji.clone(),
+            ReactionOperation::Remove,
+        )
+        .await;
     }
+    tracing::debug!(
+        "✅ 成功移除 Reaction: user={}, message={}, removed={:?}",
+        user_id,
+        message_id,
+        removed
+    );
+    Ok(json!(true))
 }
@@ -0,0 +1,100 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 单条消息阅后即焚
+//!
+//! `message/self_destruct/set`：发送方为自己已发出的消息补设计时。发送时也可以直接在
+//! metadata 里带 `self_destruct`，两者同一套校验；一条消息只能设置一次。
+
+use crate::model::channel::ChannelType;
+use crate::repository::{DisappearingTimer, MessageRepository};
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::RpcServiceContext;
+use serde_json::{json, Value};
+
+/// 处理 设置单条消息阅后即焚 请求
+///
+/// 请求：`{ "message_id": 123, "ttl_secs": 30, "start_on_read": true }`
+///
+/// 响应：`{ "message_id": 123, "ttl_secs": 30, "start_on_read": true,
+///          "expires_at": 1700000000000 | null }`（`null` 表示等待接收方已读）
+pub async fn handle_set(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: crate::rpc::RpcContext,
+) -> RpcResult<Value> {
+    let message_id = crate::rpc::parse_u64_param(&body, "message_id")?;
+    let ttl_secs = body
+        .get("ttl_secs")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| RpcError::validation("ttl_secs is required".to_string()))?;
+    let start_on_read = body
+        .get("start_on_read")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let user_id = crate::rpc::get_current_user_id(&ctx)?;
+
+    let message = services
+        .message_repository
+        .find_by_id(message_id)
+        .await
+        .map_err(|e| RpcError::internal(format!("查询消息失败: {}", e)))?
+        .ok_or_else(|| RpcError::not_found("消息不存在".to_string()))?;
+    if message.sender_id != user_id {
+        return Err(RpcError::forbidden(
+            "只能为自己发送的消息设置阅后即焚".to_string(),
+        ));
+    }
+    if message.revoked || message.deleted {
+        return Err(RpcError::validation("消息已被撤回或删除".to_string()));
+    }
+    let pts = message
+        .pts
+        .ok_or_else(|| RpcError::validation("消息尚未提交".to_string()))?;
+
+    let channel = services
+        .channel_service
+        .get_channel(&message.channel_id)
+        .await
+        .map_err(|e| RpcError::not_found(format!("会话不存在: {}", e)))?;
+    if channel.channel_type == ChannelType::Room {
+        return Err(RpcError::validation("Room 不支持阅后即焚".to_string()));
+    }
+
+    let expires_at = services
+        .disappearing_service
+        .arm_sent_message(
+            message_id,
+            message.created_at.timestamp_millis(),
+            message.channel_id,
+            pts as u64,
+            user_id,
+            DisappearingTimer {
+                ttl_secs,
+                start_on_read,
+            },
+        )
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!({
+        "message_id": message_id,
+        "ttl_secs": ttl_secs,
+        "start_on_read": start_on_read,
+        "expires_at": expires_at,
+    }))
+}
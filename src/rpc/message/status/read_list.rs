@@ -16,11 +16,30 @@
 // limitations under the License.
 
 use super::policy::{ensure_read_list_allowed, parse_read_receipt_mode};
+use crate::infra::cache_manager::CachedUserProfile;
 use crate::repository::message_repo::MessageRepository;
 use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::{helpers, RpcServiceContext};
+use crate::service::ProfileFieldVisibility;
 use serde_json::{json, Value};
 
+/// 已读名单中单个成员的公开投影
+fn read_user_entry(
+    reader_id: u64,
+    profile: CachedUserProfile,
+    read_at: i64,
+    visibility: &ProfileFieldVisibility,
+) -> Value {
+    json!({
+        "user_id": profile.user_id,
+        // PROFILE_VISIBILITY:公开投影,不回他人 username。
+        "username": String::new(),
+        "nickname": profile.nickname,
+        "avatar_url": visibility.avatar(reader_id, profile.avatar_url),
+        "read_at": read_at
+    })
+}
+
 /// 处理 查询群消息已读列表 请求
 pub async fn handle(
     body: Value,
@@ -76,8 +95,32 @@ pub async fn handle(
         .list_read_members_by_message_pts(channel_id, message_pts, &member_ids)
         .await
         .map_err(|e| RpcError::internal(format!("查询已读列表失败: {}", e)))?;
+    // 已读回执隐私：对查询者隐藏回执的成员不出现在名单里（仍计入已读数，与 read_stats 一致）
+    let viewer_id = crate::rpc::get_current_user_id(&ctx)?;
+    let reader_ids: Vec<u64> = readers.iter().map(|reader| reader.user_id).collect();
+    let visible_readers = services
+        .privacy_service
+        .visible_owners(
+            viewer_id,
+            &reader_ids,
+            crate::model::privacy::PrivacyKey::ReadReceipts,
+        )
+        .await
+        .map_err(RpcError::from)?;
+    let visible_reader_ids: Vec<u64> = reader_ids
+        .iter()
+        .copied()
+        .filter(|reader_id| visible_readers.contains(reader_id))
+        .collect();
+    let profile_visibility = services
+        .privacy_service
+        .profile_visibility_or_hidden(viewer_id, &visible_reader_ids)
+        .await;
     let mut read_users_info = Vec::new();
-    for reader in readers.iter() {
+    for reader in readers
+        .iter()
+        .filter(|reader| visible_readers.contains(&reader.user_id))
+    {
         if let Ok(Some(profile)) = helpers::get_user_profile_with_fallback(
             reader.user_id,
             &services.user_repository,
@@ -85,17 +128,15 @@ pub async fn handle(
         )
         .await
         {
-            read_users_info.push(json!({
-                "user_id": profile.user_id,
-                // PROFILE_VISIBILITY:公开投影,不回他人 username。
-                "username": String::new(),
-                "nickname": profile.nickname,
-                "avatar_url": profile.avatar_url,
-                "read_at": reader.updated_at.timestamp_millis()
-            }));
+            read_users_info.push(read_user_entry(
+                reader.user_id,
+                profile,
+                reader.updated_at.timestamp_millis(),
+                &profile_visibility,
+            ));
         }
     }
-    let read_count = read_users_info.len() as u32;
+    let read_count = readers.len() as u32;
     tracing::debug!(
         "✅ 查询已读列表成功(投影): message_id={} channel_id={} message_pts={} 已读 {}/{}",
         message_id,
@@ -113,3 +154,32 @@ pub async fn handle(
         "read_list": read_users_info
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_avatar_follows_the_visibility_rule() {
+        let profile = CachedUserProfile {
+            user_id: "20001".to_string(),
+            username: "alice".to_string(),
+            nickname: "Alice".to_string(),
+            avatar_url: Some("https://cdn/a.png".to_string()),
+            user_type: 0,
+            phone: Some("+8613800000000".to_string()),
+            email: None,
+        };
+
+        let entry = read_user_entry(
+            20001,
+            profile,
+            1_700_000_000_000,
+            &ProfileFieldVisibility::hidden(10001),
+        );
+        assert!(entry["avatar_url"].is_null());
+        assert_eq!(entry["username"], "");
+        assert!(entry.get("phone").is_none());
+        assert_eq!(entry["read_at"], 1_700_000_000_000i64);
+    }
+}
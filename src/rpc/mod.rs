@@ -34,6 +34,7 @@ pub mod presence;
 pub mod qr; // QR_CODE_SPEC v1.3 — user/group qr_key 永久字段 + URL builder（与历史 qrcode 模块解耦）
 pub mod qr_login;
 pub mod qrcode;
+pub mod room;
 pub mod sticker;
 pub mod sync;
 pub mod user;
@@ -168,6 +169,30 @@ pub struct RpcServiceContext {
     /// `None` = 未配 `[server_event]` 表，业务点会跳过 best-effort 通知。
     /// 任何 server 内部 emit 的事件（bot.followed / bot.unfollowed / ...）都通过它。
     pub server_event_client: Option<Arc<crate::server_event::ServerEventClient>>,
+    /// 广播频道服务（订阅号 / 公告频道）
+    pub broadcast_channel_service: Arc<crate::service::BroadcastChannelService>,
+    /// 用户级推送偏好（语言 / 隐藏预览）
+    pub user_push_settings_repo: Arc<crate::repository::UserPushSettingsRepository>,
+    /// 内置账号两步验证（TOTP / 恢复码 / 登录 challenge）
+    pub two_factor_service: Arc<crate::service::TwoFactorService>,
+    /// 内置账号找回密码（一次性令牌 / 投递）
+    pub password_reset_service: Arc<crate::service::PasswordResetService>,
+    /// @提及（持久化记录 / 未读 @ 查询）
+    pub mention_service: Arc<crate::service::MentionService>,
+    /// Bot 平台（命令列表 / 内联键盘回调）
+    pub bot_service: Arc<crate::service::BotService>,
+    /// 消息保留期（会话级覆盖）
+    pub retention_service: Arc<crate::service::RetentionService>,
+    /// 阅后即焚（会话计时器 / 单条消息补设计时）
+    pub disappearing_service: Arc<crate::service::DisappearingService>,
+    /// 用户自助注销（冷静期 / 撤销）
+    pub account_deletion_service: Arc<crate::service::AccountDeletionService>,
+    /// 个人数据导出
+    pub data_export_service: Arc<crate::service::DataExportService>,
+    /// Room 订阅者聊天
+    pub room_chat_service: Arc<crate::service::RoomChatService>,
+    /// 群慢速模式窗口（sync/submit 发送权限判定）
+    pub group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
 }
 
 impl RpcServiceContext {
@@ -213,6 +238,18 @@ impl RpcServiceContext {
         qr_login_publisher: Arc<QrLoginPublisher>,
         bot_follow_repository: Arc<crate::repository::BotFollowRepository>,
         server_event_client: Option<Arc<crate::server_event::ServerEventClient>>,
+        broadcast_channel_service: Arc<crate::service::BroadcastChannelService>,
+        user_push_settings_repo: Arc<crate::repository::UserPushSettingsRepository>,
+        two_factor_service: Arc<crate::service::TwoFactorService>,
+        password_reset_service: Arc<crate::service::PasswordResetService>,
+        mention_service: Arc<crate::service::MentionService>,
+        bot_service: Arc<crate::service::BotService>,
+        retention_service: Arc<crate::service::RetentionService>,
+        disappearing_service: Arc<crate::service::DisappearingService>,
+        account_deletion_service: Arc<crate::service::AccountDeletionService>,
+        data_export_service: Arc<crate::service::DataExportService>,
+        room_chat_service: Arc<crate::service::RoomChatService>,
+        group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
     ) -> Self {
         Self {
             // channel_service 已合并到 channel_service
@@ -256,6 +293,18 @@ impl RpcServiceContext {
             qr_login_publisher,
             bot_follow_repository,
             server_event_client,
+            broadcast_channel_service,
+            user_push_settings_repo,
+            two_factor_service,
+            password_reset_service,
+            mention_service,
+            bot_service,
+            retention_service,
+            disappearing_service,
+            account_deletion_service,
+            data_export_service,
+            room_chat_service,
+            group_slow_mode_service,
         }
     }
 }
@@ -268,6 +317,7 @@ pub async fn init_rpc_system(services: RpcServiceContext) {
     device::register_routes(services.clone()).await;
     group::register_routes(services.clone()).await;
     channel::register_routes(services.clone()).await;
+    channel_broadcast::register_routes(services.clone()).await;
     sync::register_routes(services.clone()).await;
     entity::register_routes(services.clone()).await;
     message::register_routes(services.clone()).await;
@@ -277,8 +327,9 @@ pub async fn init_rpc_system(services: RpcServiceContext) {
     qr_login::register_routes(services.clone()).await;
     user::register_routes(services.clone()).await;
     presence::register_routes(services.clone()).await;
+    room::register_routes(services.clone()).await;
 
-    tracing::debug!("🔧 RPC 系统初始化完成 (所有模块已启用: account, contact, device, group, channel, entity, message, file, sticker, qrcode, user, presence)");
+    tracing::debug!("🔧 RPC 系统初始化完成 (所有模块已启用: account, contact, device, group, channel, channel_broadcast, entity, message, file, sticker, qrcode, user, presence, room)");
 }
 
 /// 处理 RPC 请求的入口函数
@@ -70,7 +70,8 @@ pub async fn handle(
     let records = services
         .qrcode_service
         .list_by_creator(creator_id, include_revoked)
-        .await;
+        .await
+        .map_err(|e| RpcError::internal(format!("获取 QR 码列表失败: {}", e)))?;
 
     // 转换为响应格式
     let qr_keys: Vec<Value> = records
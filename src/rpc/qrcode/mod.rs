@@ -23,7 +23,9 @@ pub mod revoke;
 pub mod utils;
 
 // 重新导出工具函数
-pub use utils::{extract_qr_key_from_url, extract_token_from_url, generate_random_token};
+pub use utils::{
+    build_otpauth_uri, extract_qr_key_from_url, extract_token_from_url, generate_random_token,
+};
 
 use super::router::GLOBAL_RPC_ROUTER;
 use super::RpcServiceContext;
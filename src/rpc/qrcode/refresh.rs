@@ -82,6 +82,8 @@ pub async fn handle(
         .qrcode_service
         .get(&new_qr_key)
         .await
+        .ok()
+        .flatten()
         .ok_or_else(|| RpcError::internal("无法获取新的 QR Key".to_string()))?;
 
     tracing::debug!(
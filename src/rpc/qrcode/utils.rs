@@ -73,6 +73,30 @@ pub fn generate_random_token() -> String {
         .collect()
 }
 
+/// 生成 TOTP 两步验证的 otpauth URI（验证器 App 扫码添加账号）
+///
+/// 格式见 Google Authenticator Key Uri Format：
+/// `otpauth://totp/{issuer}:{account}?secret=..&issuer=..&algorithm=SHA1&digits=6&period=30`
+///
+/// # 示例
+/// ```
+/// use privchat::rpc::qrcode::build_otpauth_uri;
+///
+/// let uri = build_otpauth_uri("PrivChat", "alice", "JBSWY3DPEHPK3PXP");
+/// assert!(uri.starts_with("otpauth://totp/PrivChat:alice?secret=JBSWY3DPEHPK3PXP"));
+/// ```
+pub fn build_otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    use crate::auth::totp::{TOTP_DIGITS, TOTP_PERIOD_SECS};
+    use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+    let issuer = utf8_percent_encode(issuer, NON_ALPHANUMERIC).to_string();
+    let account = utf8_percent_encode(account, NON_ALPHANUMERIC).to_string();
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={}&period={}",
+        TOTP_DIGITS, TOTP_PERIOD_SECS
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,4 +134,20 @@ mod tests {
         assert_eq!(token2.len(), 16);
         assert_ne!(token1, token2);
     }
+
+    #[test]
+    fn test_build_otpauth_uri() {
+        let uri = build_otpauth_uri("Priv Chat", "bob@example.com", "GEZDGNBV");
+        assert_eq!(
+            uri,
+            "otpauth://totp/Priv%20Chat:bob%40example%2Ecom?secret=GEZDGNBV&issuer=Priv%20Chat&algorithm=SHA1&digits=6&period=30"
+        );
+
+        let url = Url::parse(&uri).unwrap();
+        let secret = url
+            .query_pairs()
+            .find(|(k, _)| k == "secret")
+            .map(|(_, v)| v.to_string());
+        assert_eq!(secret.as_deref(), Some("GEZDGNBV"));
+    }
 }
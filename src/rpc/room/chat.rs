@@ -0,0 +1,198 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Room 订阅者聊天
+//!
+//! - `room/chat/info`：当前用户视角的聊天状态（是否开启、慢速模式、是否主持人 / 被禁言、置顶消息）
+//! - `room/chat/send`：已订阅当前 Room 的会话发言
+//! - `room/chat/delete`、`room/chat/mute`、`room/chat/unmute`、`room/chat/pin`、`room/chat/unpin`：主持人操作
+//!
+//! 聊天开关、慢速模式与主持人名单由管理 API `/api/admin/room/{channel_id}/chat` 维护。
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, parse_u64_param, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+fn parse_session_id(raw: &str) -> RpcResult<msgtrans::SessionId> {
+    let id = raw
+        .strip_prefix("session-")
+        .unwrap_or(raw)
+        .parse::<u64>()
+        .map_err(|e| RpcError::validation(format!("invalid session_id '{}': {}", raw, e)))?;
+    Ok(msgtrans::SessionId::from(id))
+}
+
+/// 处理 查询 Room 聊天状态 请求
+///
+/// 请求：`{ "channel_id": 123 }`
+pub async fn handle_info(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let user_id = get_current_user_id(&ctx)?;
+
+    let info = services
+        .room_chat_service
+        .info(channel_id, user_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(info))
+}
+
+/// 处理 Room 发言 请求
+///
+/// 请求：`{ "channel_id": 123, "text": "hello" }`
+///
+/// 响应：`{ "server_message_id": 1, "channel_id": 123, "user_id": 42, "text": "hello", "sent_at": 1700000000000 }`
+pub async fn handle_send(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let user_id = get_current_user_id(&ctx)?;
+    let text = body
+        .get("text")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError::validation("text is required".to_string()))?;
+    let session_id_raw = ctx
+        .session_id
+        .as_ref()
+        .ok_or_else(|| RpcError::unauthorized("missing session_id".to_string()))?;
+    let session_id = parse_session_id(session_id_raw)?;
+    let device_id = ctx.device_id.as_deref().unwrap_or_default();
+
+    let message = services
+        .room_chat_service
+        .send(channel_id, user_id, device_id, session_id, text)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(message))
+}
+
+/// 处理 主持人删除消息 请求
+///
+/// 请求：`{ "channel_id": 123, "server_message_id": 456 }`
+pub async fn handle_delete(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let server_message_id = parse_u64_param(&body, "server_message_id")?;
+    let moderator_id = get_current_user_id(&ctx)?;
+
+    let removed = services
+        .room_chat_service
+        .delete(channel_id, moderator_id, server_message_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!({
+        "channel_id": channel_id,
+        "server_message_id": server_message_id,
+        "removed_from_history": removed,
+    }))
+}
+
+/// 处理 主持人禁言 请求
+///
+/// 请求：`{ "channel_id": 123, "user_id": 42, "duration_secs": 600 }`，`duration_secs` 缺省或为 0 表示永久
+pub async fn handle_mute(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let target_user_id = parse_u64_param(&body, "user_id")?;
+    let duration_secs = body
+        .get("duration_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let moderator_id = get_current_user_id(&ctx)?;
+
+    let muted_until = services
+        .room_chat_service
+        .mute(channel_id, moderator_id, target_user_id, duration_secs)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!({
+        "channel_id": channel_id,
+        "user_id": target_user_id,
+        "muted_until": muted_until,
+    }))
+}
+
+/// 处理 主持人解除禁言 请求
+///
+/// 请求：`{ "channel_id": 123, "user_id": 42 }`
+pub async fn handle_unmute(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let target_user_id = parse_u64_param(&body, "user_id")?;
+    let moderator_id = get_current_user_id(&ctx)?;
+
+    services
+        .room_chat_service
+        .unmute(channel_id, moderator_id, target_user_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(true))
+}
+
+/// 处理 主持人置顶消息 请求（只能置顶近期历史中的聊天消息）
+///
+/// 请求：`{ "channel_id": 123, "server_message_id": 456 }`
+pub async fn handle_pin(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let server_message_id = parse_u64_param(&body, "server_message_id")?;
+    let moderator_id = get_current_user_id(&ctx)?;
+
+    let message = services
+        .room_chat_service
+        .pin(channel_id, moderator_id, server_message_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(message))
+}
+
+/// 处理 主持人取消置顶 请求
+///
+/// 请求：`{ "channel_id": 123 }`
+pub async fn handle_unpin(
+    body: Value,
+    services: RpcServiceContext,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let channel_id = parse_u64_param(&body, "channel_id")?;
+    let moderator_id = get_current_user_id(&ctx)?;
+
+    let unpinned = services
+        .room_chat_service
+        .unpin(channel_id, moderator_id)
+        .await
+        .map_err(RpcError::from)?;
+    Ok(json!(unpinned))
+}
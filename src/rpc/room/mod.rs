@@ -0,0 +1,84 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod chat;
+
+use super::router::GLOBAL_RPC_ROUTER;
+use super::RpcServiceContext;
+
+/// 注册 Room 系统的所有路由
+pub async fn register_routes(services: RpcServiceContext) {
+    // ✨ Room 订阅者聊天
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/info", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_info(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/send", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_send(params, services, ctx).await })
+        })
+        .await;
+
+    // ✨ 主持人操作
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/delete", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_delete(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/mute", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_mute(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/unmute", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_unmute(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/pin", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_pin(params, services, ctx).await })
+        })
+        .await;
+
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("room/chat/unpin", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { chat::handle_unpin(params, services, ctx).await })
+        })
+        .await;
+
+    tracing::debug!("💬 Room 系统路由注册完成 (chat/info, send, delete, mute, unmute, pin, unpin)");
+}
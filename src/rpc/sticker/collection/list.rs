@@ -0,0 +1,51 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 获取我收藏的表情包库 RPC 接口
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理获取收藏表情包库请求
+///
+/// 请求参数：（无）
+///
+/// 返回格式：同 `sticker/package/list`，按收藏时间倒序，已下架的库不返回
+/// ```json
+/// {
+///   "packages": [ ... ]
+/// }
+/// ```
+pub async fn handle(
+    services: RpcServiceContext,
+    _params: Value,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let user_id = get_current_user_id(&ctx)?;
+    tracing::debug!("🔧 处理收藏表情包库列表请求: user={}", user_id);
+
+    let packages = services
+        .sticker_service
+        .list_user_packages(user_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!({
+        "packages": packages
+    }))
+}
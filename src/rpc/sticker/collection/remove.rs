@@ -0,0 +1,67 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 取消收藏表情包库 RPC 接口
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use serde_json::{json, Value};
+
+/// 处理取消收藏表情包库请求
+///
+/// 请求参数：
+/// ```json
+/// {
+///   "package_id": "classic"
+/// }
+/// ```
+///
+/// 返回格式（未收藏时幂等，`removed = false`）：
+/// ```json
+/// {
+///   "success": true,
+///   "removed": true
+/// }
+/// ```
+pub async fn handle(
+    services: RpcServiceContext,
+    params: Value,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let user_id = get_current_user_id(&ctx)?;
+    let package_id = params["package_id"]
+        .as_str()
+        .ok_or_else(|| RpcError::validation("缺少 package_id 参数".to_string()))?;
+
+    let removed = services
+        .sticker_service
+        .remove_user_package(user_id, package_id)
+        .await
+        .map_err(RpcError::from)?;
+
+    tracing::debug!(
+        "✅ 用户 {} 取消收藏表情包库 {} (removed={})",
+        user_id,
+        package_id,
+        removed
+    );
+
+    Ok(json!({
+        "success": true,
+        "removed": removed
+    }))
+}
@@ -17,7 +17,9 @@
 
 //! 表情包系统 RPC 接口
 
+pub mod collection;
 pub mod package;
+pub mod recent;
 
 use super::router::GLOBAL_RPC_ROUTER;
 use super::RpcServiceContext;
@@ -42,5 +44,41 @@ pub async fn register_routes(services: RpcServiceContext) {
         })
         .await;
 
+    // 我收藏的表情包库
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("sticker/collection/list", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { collection::list::handle(services, params, ctx).await })
+        })
+        .await;
+
+    // 收藏表情包库
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("sticker/collection/add", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { collection::add::handle(services, params, ctx).await })
+        })
+        .await;
+
+    // 取消收藏表情包库
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("sticker/collection/remove", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { collection::remove::handle(services, params, ctx).await })
+        })
+        .await;
+
+    // 最近使用的表情
+    let services_clone = services.clone();
+    GLOBAL_RPC_ROUTER
+        .register("sticker/recent/list", move |params, ctx| {
+            let services = services_clone.clone();
+            Box::pin(async move { recent::list::handle(services, params, ctx).await })
+        })
+        .await;
+
     tracing::debug!("📦 Sticker 系统路由注册完成");
 }
@@ -40,10 +40,12 @@ use serde_json::{json, Value};
 ///     "author": "PrivChat",
 ///     "description": "...",
 ///     "sticker_count": 10,
+///     "updated_at": 1700000000000,
 ///     "stickers": [
 ///       {
 ///         "sticker_id": "classic_001",
 ///         "package_id": "classic",
+///         "file_id": 10001,
 ///         "image_url": "...",
 ///         "alt_text": "微笑",
 ///         "emoji": "😊",
@@ -66,8 +68,9 @@ pub async fn handle(services: RpcServiceContext, params: Value) -> RpcResult<Val
     // 获取表情包库详情
     let package = services
         .sticker_service
-        .get_package_detail(package_id)
+        .get_package_detail(package_id, false)
         .await
+        .map_err(RpcError::from)?
         .ok_or_else(|| RpcError::not_found(format!("表情包库 {} 不存在", package_id)))?;
 
     tracing::debug!("✅ 返回表情包库详情: {}", package_id);
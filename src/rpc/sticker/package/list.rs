@@ -17,7 +17,7 @@
 
 //! 获取表情包库列表 RPC 接口
 
-use crate::rpc::error::RpcResult;
+use crate::rpc::error::{RpcError, RpcResult};
 use crate::rpc::RpcServiceContext;
 use serde_json::{json, Value};
 
@@ -35,7 +35,10 @@ use serde_json::{json, Value};
 ///       "thumbnail_url": "...",
 ///       "author": "PrivChat",
 ///       "description": "...",
-///       "sticker_count": 10
+///       "is_enabled": true,
+///       "sort_order": 0,
+///       "sticker_count": 10,
+///       "updated_at": 1700000000000
 ///     }
 ///   ]
 /// }
@@ -44,7 +47,11 @@ pub async fn handle(services: RpcServiceContext, _params: Value) -> RpcResult<Va
     tracing::debug!("🔧 处理表情包库列表请求");
 
     // 获取所有表情包库
-    let packages = services.sticker_service.list_packages().await;
+    let packages = services
+        .sticker_service
+        .list_packages()
+        .await
+        .map_err(RpcError::from)?;
 
     tracing::debug!("✅ 返回 {} 个表情包库", packages.len());
 
@@ -0,0 +1,72 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 获取最近使用表情 RPC 接口
+
+use crate::rpc::error::{RpcError, RpcResult};
+use crate::rpc::{get_current_user_id, RpcContext, RpcServiceContext};
+use crate::service::sticker_service::RECENT_STICKER_LIMIT;
+use serde_json::{json, Value};
+
+/// 处理获取最近使用表情请求
+///
+/// 请求参数：
+/// ```json
+/// {
+///   "limit": 30
+/// }
+/// ```
+///
+/// 返回格式（按最后使用时间倒序；所属库下架的表情不返回）：
+/// ```json
+/// {
+///   "stickers": [
+///     {
+///       "sticker_id": "classic_001",
+///       "package_id": "classic",
+///       "file_id": 10001,
+///       "image_url": "...",
+///       "alt_text": "微笑",
+///       "emoji": "😊",
+///       "width": 128,
+///       "height": 128,
+///       "mime_type": "image/png"
+///     }
+///   ]
+/// }
+/// ```
+pub async fn handle(
+    services: RpcServiceContext,
+    params: Value,
+    ctx: RpcContext,
+) -> RpcResult<Value> {
+    let user_id = get_current_user_id(&ctx)?;
+    let limit = params["limit"]
+        .as_u64()
+        .map(|v| v as usize)
+        .unwrap_or(RECENT_STICKER_LIMIT);
+
+    let stickers = services
+        .sticker_service
+        .list_recent(user_id, limit)
+        .await
+        .map_err(RpcError::from)?;
+
+    Ok(json!({
+        "stickers": stickers
+    }))
+}
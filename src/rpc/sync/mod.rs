@@ -232,7 +232,9 @@ async fn handle_submit_rpc(
     // 群 `allow_member_post`、**拉黑**、好友与隐私。也就是说被拉黑的人从 wire
     // 发不出去，换 `sync/submit` 就发得出去——两条主路径对同一个问题给不同答案时，
     // 攻击者只会走松的那条。策略只能有一份。
-    {
+    //
+    // 慢速模式窗口交给 `handle_client_submit` 在消息校验通过、落库前占用。
+    let send_permit = {
         let channel = services
             .channel_service
             .get_channel_opt(channel_id)
@@ -244,27 +246,30 @@ async fn handle_submit_rpc(
             friend_service: services.friend_service.clone(),
             blacklist_service: services.blacklist_service.clone(),
             privacy_service: services.privacy_service.clone(),
+            group_slow_mode_service: services.group_slow_mode_service.clone(),
         };
-        if let Err(refusal) =
-            crate::service::send_authorization::authorize_send_to_channel(&deps, &channel, sender_id)
-                .await
-        {
-            // 保留 typed 错误码：客户端据此区分「被禁言」「被拉黑」「稍后重试」，
-            // 压成一个 forbidden 会让这些提示全变成同一句话。
-            return Err(RpcError::from_code(refusal.error_code(), refusal.message()));
-        }
-    }
+        // 保留 typed 错误码：客户端据此区分「被禁言」「被拉黑」「稍后重试」，
+        // 压成一个 forbidden 会让这些提示全变成同一句话。
+        crate::service::send_authorization::authorize_send_to_channel(&deps, &channel, sender_id)
+            .await
+            .map_err(|refusal| RpcError::from_code(refusal.error_code(), refusal.message()))?
+    };
 
     // device_id 来自认证会话（CODEX-8 幂等命名空间维度，服务端权威）。认证的 sync/submit 必然带
     // device；缺失即异常会话 —— fail closed 拒绝（否则所有异常会话落入同一空 device 命名空间）。
     let device_id = resolve_session_device_id(ctx.device_id.clone())?;
     let response = services
         .sync_service
-        .handle_client_submit(request, sender_id, &device_id)
+        .handle_client_submit(request, sender_id, &device_id, send_permit)
         .await
-        .map_err(|e| {
-            error!("SyncService.handle_client_submit 失败: {}", e);
-            RpcError::internal(format!("提交失败: {}", e))
+        .map_err(|e| match e {
+            // 慢速模式冷却 / 窗口判定不出来：保留 typed 错误码，客户端按「稍后再试」处理
+            crate::error::ServerError::RateLimit(_)
+            | crate::error::ServerError::ServiceUnavailable(_) => RpcError::from(e),
+            e => {
+                error!("SyncService.handle_client_submit 失败: {}", e);
+                RpcError::internal(format!("提交失败: {}", e))
+            }
         })?;
 
     if let Err(e) =
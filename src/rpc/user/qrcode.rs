@@ -22,12 +22,16 @@ use crate::rpc::qr::{
     QR_KEY_MAX_GENERATION_RETRIES,
 };
 use crate::rpc::RpcServiceContext;
+use crate::service::ProfileFieldVisibility;
 use privchat_protocol::rpc::user_qrcode::{
     UserQRCodeGetResponse, UserQRCodeRefreshResponse, UserQRCodeResolveRequest,
     UserQRCodeResolveResponse,
 };
 use serde_json::{json, Value};
 
+/// `(user_id, username, display_name, avatar_url, user_type)`
+type UserCardRow = (i64, String, Option<String>, Option<String>, i16);
+
 /// `user/qrcode/get` — 读自己的 qr_key。
 pub async fn get(
     _body: Value,
@@ -143,7 +147,7 @@ pub async fn resolve(
         return Err(RpcError::validation("qr_key is required".to_string()));
     }
 
-    let row = sqlx::query_as::<_, (i64, String, Option<String>, Option<String>, i16)>(
+    let row = sqlx::query_as::<_, UserCardRow>(
         r#"
         SELECT user_id, username, display_name, avatar_url, user_type
           FROM privchat_users
@@ -169,14 +173,56 @@ pub async fn resolve(
             .await
     };
 
-    let resp = UserQRCodeResolveResponse {
-        user_id: target_user_id,
-        username: row.1,
-        display_name: row.2,
-        avatar_url: row.3,
-        user_type: row.4,
+    let visibility = services
+        .privacy_service
+        .profile_visibility_or_hidden(operator_id, &[target_user_id])
+        .await;
+
+    Ok(json!(user_card(row, operator_id, is_friend, &visibility)))
+}
+
+/// 名片码对应的最小用户卡片（头像按目标用户的可见性规则投影）
+fn user_card(
+    row: UserCardRow,
+    operator_id: u64,
+    is_friend: bool,
+    visibility: &ProfileFieldVisibility,
+) -> UserQRCodeResolveResponse {
+    let (user_id, username, display_name, avatar_url, user_type) = row;
+    let user_id = user_id as u64;
+    UserQRCodeResolveResponse {
+        user_id,
+        username,
+        display_name,
+        avatar_url: visibility.avatar(user_id, avatar_url),
+        user_type,
         is_friend,
-        is_self,
-    };
-    Ok(json!(resp))
+        is_self: user_id == operator_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn card_avatar_follows_the_visibility_rule() {
+        let row = || {
+            (
+                20001,
+                "alice".to_string(),
+                Some("Alice".to_string()),
+                Some("https://cdn/a.png".to_string()),
+                0,
+            )
+        };
+
+        let card = user_card(row(), 10001, false, &ProfileFieldVisibility::hidden(10001));
+        assert_eq!(card.avatar_url, None);
+        assert!(!card.is_self);
+
+        let own = user_card(row(), 20001, true, &ProfileFieldVisibility::hidden(20001));
+        assert_eq!(own.avatar_url.as_deref(), Some("https://cdn/a.png"));
+        assert!(own.is_self);
+    }
 }
@@ -119,6 +119,33 @@ pub enum ViolationType {
     FanoutAbuse,
 }
 
+impl ViolationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ViolationType::RateLimit => "rate_limit",
+            ViolationType::AuthFailed => "auth_failed",
+            ViolationType::MalformedRequest => "malformed_request",
+            ViolationType::SuspiciousActivity => "suspicious_activity",
+            ViolationType::ProtocolAttack => "protocol_attack",
+            ViolationType::Spam => "spam",
+            ViolationType::FanoutAbuse => "fanout_abuse",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "rate_limit" => Some(ViolationType::RateLimit),
+            "auth_failed" => Some(ViolationType::AuthFailed),
+            "malformed_request" => Some(ViolationType::MalformedRequest),
+            "suspicious_activity" => Some(ViolationType::SuspiciousActivity),
+            "protocol_attack" => Some(ViolationType::ProtocolAttack),
+            "spam" => Some(ViolationType::Spam),
+            "fanout_abuse" => Some(ViolationType::FanoutAbuse),
+            _ => None,
+        }
+    }
+}
+
 /// 用户/设备信任度模型
 #[derive(Debug, Clone)]
 pub struct TrustScore {
@@ -349,6 +376,115 @@ impl ClientStateManager {
     pub fn trust_score_mut(&mut self) -> &mut TrustScore {
         &mut self.trust_score
     }
+
+    /// 导出可跨节点共享的快照（`Instant` 换算为 Unix 毫秒）
+    ///
+    /// 已过期的临时状态按 Normal 导出；状态转换历史只用于本地调试，不导出。
+    pub fn snapshot(&self, now_ms: i64) -> ClientStateSnapshot {
+        let now = Instant::now();
+        let state = self.current_state();
+        let state_expires_at_ms = if state == ClientState::Normal {
+            None
+        } else {
+            self.state_expiry
+                .map(|expiry| instant_to_millis(expiry, now, now_ms))
+        };
+        ClientStateSnapshot {
+            state,
+            state_expires_at_ms,
+            trust_score: self.trust_score.score,
+            account_age_days: self.trust_score.account_age_days,
+            violations: self
+                .trust_score
+                .violations
+                .iter()
+                .map(|v| ViolationSnapshot {
+                    violation_type: v.violation_type,
+                    at_ms: instant_to_millis(v.timestamp, now, now_ms),
+                    penalty: v.penalty,
+                })
+                .collect(),
+        }
+    }
+
+    /// 从快照恢复
+    pub fn from_snapshot(
+        user_id: u64,
+        device_id: String,
+        snapshot: &ClientStateSnapshot,
+        now_ms: i64,
+    ) -> Self {
+        let now = Instant::now();
+        let mut trust_score = TrustScore::new(user_id, device_id);
+        trust_score.score = snapshot.trust_score.min(1000);
+        trust_score.account_age_days = snapshot.account_age_days;
+        trust_score.violations = snapshot
+            .violations
+            .iter()
+            .map(|v| ViolationRecord {
+                violation_type: v.violation_type,
+                timestamp: millis_to_instant(v.at_ms, now, now_ms),
+                penalty: v.penalty,
+            })
+            .collect();
+
+        Self {
+            state: snapshot.state,
+            trust_score,
+            state_history: Vec::new(),
+            state_expiry: snapshot
+                .state_expires_at_ms
+                .map(|at_ms| millis_to_instant(at_ms, now, now_ms)),
+        }
+    }
+}
+
+/// 客户端状态快照（存 Redis / 内存，跨节点一致）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientStateSnapshot {
+    pub state: ClientState,
+    /// 临时状态的到期时间（Unix 毫秒）；None 表示永久或 Normal
+    pub state_expires_at_ms: Option<i64>,
+    pub trust_score: u32,
+    #[serde(default)]
+    pub account_age_days: u32,
+    #[serde(default)]
+    pub violations: Vec<ViolationSnapshot>,
+}
+
+impl ClientStateSnapshot {
+    /// 快照在 `now_ms` 时刻的有效状态（临时状态过期即恢复 Normal）
+    pub fn effective_state(&self, now_ms: i64) -> ClientState {
+        match self.state_expires_at_ms {
+            Some(at_ms) if at_ms <= now_ms => ClientState::Normal,
+            _ => self.state,
+        }
+    }
+}
+
+/// 违规记录快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViolationSnapshot {
+    pub violation_type: ViolationType,
+    pub at_ms: i64,
+    pub penalty: u32,
+}
+
+fn instant_to_millis(at: Instant, now: Instant, now_ms: i64) -> i64 {
+    if at >= now {
+        now_ms + at.duration_since(now).as_millis() as i64
+    } else {
+        now_ms - now.duration_since(at).as_millis() as i64
+    }
+}
+
+fn millis_to_instant(at_ms: i64, now: Instant, now_ms: i64) -> Instant {
+    if at_ms >= now_ms {
+        now + Duration::from_millis((at_ms - now_ms) as u64)
+    } else {
+        now.checked_sub(Duration::from_millis((now_ms - at_ms) as u64))
+            .unwrap_or(now)
+    }
 }
 
 #[cfg(test)]
@@ -390,4 +526,35 @@ mod tests {
         trust.reward_good_behavior(50);
         assert!(trust.score > 95);
     }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let now_ms = 1_767_225_600_000;
+        let mut manager = ClientStateManager::new(1001, "device_1".to_string());
+        manager.transition_to(
+            ClientState::ShadowBanned,
+            ViolationType::Spam,
+            Some(Duration::from_secs(600)),
+        );
+
+        let snapshot = manager.snapshot(now_ms);
+        assert_eq!(snapshot.state, ClientState::ShadowBanned);
+        let expires_at = snapshot.state_expires_at_ms.unwrap();
+        assert!((expires_at - (now_ms + 600_000)).abs() < 1_000);
+        assert_eq!(snapshot.violations.len(), 1);
+
+        let restored =
+            ClientStateManager::from_snapshot(1001, "device_1".into(), &snapshot, now_ms);
+        assert_eq!(restored.current_state(), ClientState::ShadowBanned);
+        assert_eq!(restored.trust_score().score, manager.trust_score().score);
+
+        // 到期后有效状态恢复 Normal
+        assert_eq!(
+            snapshot.effective_state(expires_at + 1),
+            ClientState::Normal
+        );
+        let expired =
+            ClientStateManager::from_snapshot(1001, "device_1".into(), &snapshot, expires_at + 1);
+        assert_eq!(expired.current_state(), ClientState::Normal);
+    }
 }
@@ -23,6 +23,7 @@
 /// - Fan-out 成本计算
 /// - IP 防护
 /// - Shadow Ban
+/// - 状态共享存储（Redis 跨节点一致，手动封禁落 Postgres）
 ///
 /// ## 分阶段启用策略
 ///
@@ -35,11 +36,14 @@ pub mod rate_limiter;
 pub mod room_ticket;
 pub mod upload_token;
 pub mod security_service;
+pub mod state_store;
 
-pub use client_state::{ClientState, ClientStateManager, TrustScore, ViolationType};
+pub use client_state::{
+    ClientState, ClientStateManager, ClientStateSnapshot, TrustScore, ViolationType,
+};
 pub use rate_limiter::{
-    FanoutCostCalculator, MultiDimensionRateLimiter, RateLimitConfig, RateLimitKey, RpcCost,
-    RpcCostTable,
+    FanoutCostCalculator, LocalTokenBuckets, MultiDimensionRateLimiter, RateLimitConfig,
+    RateLimitKey, RpcCost, RpcCostTable, TokenBucketStore,
 };
 pub use room_ticket::{
     sign as sign_room_ticket, sign_default as sign_room_ticket_default,
@@ -47,3 +51,7 @@ pub use room_ticket::{
     SCOPE_SUBSCRIBE,
 };
 pub use security_service::{SecurityCheckResult, SecurityConfig, SecurityMode, SecurityService};
+pub use state_store::{
+    IpProtectionRecord, MemorySecurityStateStore, RedisSecurityStateStore, RedisTokenBucketStore,
+    SecurityStateStore,
+};
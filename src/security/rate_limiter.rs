@@ -15,6 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use async_trait::async_trait;
 use parking_lot::Mutex;
 /// 多维度速率限制器
 ///
@@ -22,11 +23,14 @@ use parking_lot::Mutex;
 /// 1. 基于 cost 而不是简单计数
 /// 2. 支持 (user, msg_type), (user, channel), (channel) 三层限流
 /// 3. 考虑 fan-out 成本
-/// 4. 使用 sharded token bucket 保证高并发性能
+/// 4. 令牌桶存储可替换：单节点用 sharded 本地桶，集群用 Redis 共享桶
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Instant;
+use tracing::warn;
 
 use super::client_state::{TrustScore, ViolationType};
+use crate::error::Result;
 
 /// RPC 操作成本定义
 #[derive(Debug, Clone, Copy)]
@@ -85,6 +89,8 @@ impl RpcCostTable {
 
             // === 写操作 ===
             "messages.send" => RpcCost::stateful(5),
+            // Room 聊天：单条成本低，频率主要由 用户+房间 / 房间 两层令牌桶约束
+            "messages.send.room" => RpcCost::stateful(1),
             "channels.createChannel" => RpcCost::stateful(20),
             "channels.inviteMembers" => RpcCost::stateful(15),
 
@@ -163,6 +169,23 @@ pub enum RateLimitKey {
     Ip(String),
 }
 
+impl RateLimitKey {
+    /// 共享存储中的 key 后缀
+    pub fn storage_key(&self) -> String {
+        match self {
+            RateLimitKey::User(user_id) => format!("user:{}", user_id),
+            RateLimitKey::UserRpc(user_id, rpc_method) => {
+                format!("user_rpc:{}:{}", user_id, rpc_method)
+            }
+            RateLimitKey::UserChannel(user_id, channel_id) => {
+                format!("user_channel:{}:{}", user_id, channel_id)
+            }
+            RateLimitKey::Channel(channel_id) => format!("channel:{}", channel_id),
+            RateLimitKey::Ip(ip) => format!("ip:{}", ip),
+        }
+    }
+}
+
 /// 限流配置
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -244,24 +267,81 @@ impl ShardedBuckets {
     }
 }
 
+/// 令牌桶存储
+///
+/// 桶状态放在哪里决定了限流的作用范围：本地桶只对当前节点有效，
+/// 客户端换个节点重连就能拿到一只新桶；集群部署时必须用共享存储。
+#[async_trait]
+pub trait TokenBucketStore: Send + Sync {
+    /// 补充令牌后尝试消耗 `cost`，返回是否放行
+    async fn try_consume(
+        &self,
+        key: &RateLimitKey,
+        cost: f64,
+        rate: f64,
+        capacity: f64,
+    ) -> Result<bool>;
+}
+
+/// 进程内令牌桶（单节点 / 测试）
+pub struct LocalTokenBuckets {
+    buckets: ShardedBuckets,
+}
+
+impl LocalTokenBuckets {
+    pub fn new() -> Self {
+        Self {
+            buckets: ShardedBuckets::new(),
+        }
+    }
+}
+
+impl Default for LocalTokenBuckets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TokenBucketStore for LocalTokenBuckets {
+    async fn try_consume(
+        &self,
+        key: &RateLimitKey,
+        cost: f64,
+        rate: f64,
+        capacity: f64,
+    ) -> Result<bool> {
+        let shard = self.buckets.get_shard(key);
+        let mut buckets = shard.lock();
+
+        let bucket = buckets
+            .entry(key.clone())
+            .or_insert_with(|| TokenBucket::new(capacity, rate));
+
+        Ok(bucket.try_consume(cost))
+    }
+}
+
 /// 多维度限流器
 pub struct MultiDimensionRateLimiter {
     config: RateLimitConfig,
-    buckets: ShardedBuckets,
+    store: Arc<dyn TokenBucketStore>,
 }
 
 impl MultiDimensionRateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
-        Self {
-            config,
-            buckets: ShardedBuckets::new(),
-        }
+        Self::with_store(config, Arc::new(LocalTokenBuckets::new()))
+    }
+
+    /// 使用指定的令牌桶存储（集群部署传 Redis 实现）
+    pub fn with_store(config: RateLimitConfig, store: Arc<dyn TokenBucketStore>) -> Self {
+        Self { config, store }
     }
 
     /// 检查并消耗限流配额
     ///
     /// 返回：(是否允许, 违规类型)
-    pub fn check_and_consume(
+    pub async fn check_and_consume(
         &self,
         user_id: u64,
         rpc_method: &str,
@@ -273,24 +353,30 @@ impl MultiDimensionRateLimiter {
 
         // 1. 用户全局限流
         let user_key = RateLimitKey::User(user_id);
-        if !self.try_consume_internal(
-            &user_key,
-            cost.tokens as f64,
-            self.config.user_tokens_per_second * multiplier,
-            self.config.user_burst_capacity * multiplier,
-        ) {
+        if !self
+            .try_consume_internal(
+                &user_key,
+                cost.tokens as f64,
+                self.config.user_tokens_per_second * multiplier,
+                self.config.user_burst_capacity * multiplier,
+            )
+            .await
+        {
             return (false, Some(ViolationType::RateLimit));
         }
 
         // 2. 用户 + RPC 方法限流
         let user_rpc_key = RateLimitKey::UserRpc(user_id, rpc_method.to_string());
         let rpc_specific_rate = self.get_rpc_specific_rate(rpc_method, multiplier);
-        if !self.try_consume_internal(
-            &user_rpc_key,
-            cost.tokens as f64,
-            rpc_specific_rate.0,
-            rpc_specific_rate.1,
-        ) {
+        if !self
+            .try_consume_internal(
+                &user_rpc_key,
+                cost.tokens as f64,
+                rpc_specific_rate.0,
+                rpc_specific_rate.1,
+            )
+            .await
+        {
             return (false, Some(ViolationType::RateLimit));
         }
 
@@ -299,23 +385,29 @@ impl MultiDimensionRateLimiter {
             if rpc_method.starts_with("messages.send") {
                 // 用户 + 会话
                 let user_conv_key = RateLimitKey::UserChannel(user_id, conv_id);
-                if !self.try_consume_internal(
-                    &user_conv_key,
-                    1.0, // 消息按条计数
-                    self.config.channel_messages_per_second * multiplier,
-                    self.config.channel_burst_capacity * multiplier,
-                ) {
+                if !self
+                    .try_consume_internal(
+                        &user_conv_key,
+                        1.0, // 消息按条计数
+                        self.config.channel_messages_per_second * multiplier,
+                        self.config.channel_burst_capacity * multiplier,
+                    )
+                    .await
+                {
                     return (false, Some(ViolationType::FanoutAbuse));
                 }
 
                 // 会话全局（防止单个群被刷爆）
                 let conv_key = RateLimitKey::Channel(conv_id);
-                if !self.try_consume_internal(
-                    &conv_key,
-                    1.0,
-                    self.config.channel_messages_per_second * 10.0, // 群的总限流是单用户的10倍
-                    self.config.channel_burst_capacity * 10.0,
-                ) {
+                if !self
+                    .try_consume_internal(
+                        &conv_key,
+                        1.0,
+                        self.config.channel_messages_per_second * 10.0, // 群的总限流是单用户的10倍
+                        self.config.channel_burst_capacity * 10.0,
+                    )
+                    .await
+                {
                     return (false, Some(ViolationType::FanoutAbuse));
                 }
             }
@@ -325,21 +417,22 @@ impl MultiDimensionRateLimiter {
     }
 
     /// 内部消耗方法
-    fn try_consume_internal(
+    ///
+    /// 🔴 存储不可用时放行：限流是保护手段，不能因为 Redis 抖动把正常用户全部拒掉。
+    async fn try_consume_internal(
         &self,
         key: &RateLimitKey,
         cost: f64,
         rate: f64,
         capacity: f64,
     ) -> bool {
-        let shard = self.buckets.get_shard(key);
-        let mut buckets = shard.lock();
-
-        let bucket = buckets
-            .entry(key.clone())
-            .or_insert_with(|| TokenBucket::new(capacity, rate));
-
-        bucket.try_consume(cost)
+        match self.store.try_consume(key, cost, rate, capacity).await {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                warn!("限流存储不可用，放行 {:?}: {}", key, e);
+                true
+            }
+        }
     }
 
     /// 获取 RPC 特定的速率配置
@@ -363,7 +456,7 @@ impl MultiDimensionRateLimiter {
     }
 
     /// IP 连接限流（连接层使用）
-    pub fn check_ip_connection(&self, ip: &str) -> bool {
+    pub async fn check_ip_connection(&self, ip: &str) -> bool {
         let key = RateLimitKey::Ip(ip.to_string());
         self.try_consume_internal(
             &key,
@@ -371,6 +464,7 @@ impl MultiDimensionRateLimiter {
             self.config.ip_connections_per_second,
             self.config.ip_burst_capacity,
         )
+        .await
     }
 }
 
@@ -422,20 +516,23 @@ mod tests {
     use super::*;
     use crate::security::client_state::TrustScore;
 
-    #[test]
-    fn test_rate_limiter() {
+    #[tokio::test]
+    async fn test_rate_limiter() {
         let config = RateLimitConfig::default();
         let limiter = MultiDimensionRateLimiter::new(config);
         let trust = TrustScore::new(1001, "device_1".to_string());
 
         // 正常请求
-        let (allowed, _) = limiter.check_and_consume(1001, "users.getProfile", None, &trust);
+        let (allowed, _) = limiter
+            .check_and_consume(1001, "users.getProfile", None, &trust)
+            .await;
         assert!(allowed);
 
         // 高频请求
         for _ in 0..200 {
-            let (allowed, violation) =
-                limiter.check_and_consume(1001, "users.getProfile", None, &trust);
+            let (allowed, violation) = limiter
+                .check_and_consume(1001, "users.getProfile", None, &trust)
+                .await;
             if !allowed {
                 assert_eq!(violation, Some(ViolationType::RateLimit));
                 break;
@@ -30,14 +30,18 @@
 /// - ObserveOnly: 只记录，不处罚（早期推荐）
 /// - EnforceLight: 轻量限流，不启用高级特性
 /// - EnforceFull: 全部特性启用
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use super::client_state::{ClientState, ClientStateManager, ViolationType};
-use super::rate_limiter::{FanoutCostCalculator, MultiDimensionRateLimiter, RateLimitConfig};
+use super::rate_limiter::{
+    FanoutCostCalculator, LocalTokenBuckets, MultiDimensionRateLimiter, RateLimitConfig,
+    TokenBucketStore,
+};
+use super::state_store::{IpBanLevel, MemorySecurityStateStore, SecurityStateStore};
+use crate::error::Result;
+use crate::repository::SecurityBanRepository;
 
 /// 安全模式（分阶段启用）
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -92,74 +96,6 @@ pub enum SecurityMode {
     EnforceFull,
 }
 
-/// IP 保护记录
-#[derive(Debug, Clone)]
-struct IpProtectionRecord {
-    /// 最近违规时间戳
-    recent_violations: Vec<Instant>,
-    /// 临时封禁到期时间
-    temp_ban_until: Option<Instant>,
-    /// 是否永久封禁
-    permanent_ban: bool,
-}
-
-impl IpProtectionRecord {
-    fn new() -> Self {
-        Self {
-            recent_violations: Vec::new(),
-            temp_ban_until: None,
-            permanent_ban: false,
-        }
-    }
-
-    fn is_banned(&self) -> bool {
-        if self.permanent_ban {
-            return true;
-        }
-
-        if let Some(until) = self.temp_ban_until {
-            if Instant::now() < until {
-                return true;
-            }
-        }
-
-        false
-    }
-
-    fn add_violation(&mut self) {
-        let now = Instant::now();
-        self.recent_violations.push(now);
-
-        // 只保留最近1小时的记录
-        self.recent_violations
-            .retain(|t| now.duration_since(*t) < Duration::from_secs(3600));
-    }
-
-    fn should_temp_ban(&self) -> bool {
-        // 5分钟内10次违规
-        let five_min_ago = Instant::now() - Duration::from_secs(300);
-        let recent_count = self
-            .recent_violations
-            .iter()
-            .filter(|t| **t > five_min_ago)
-            .count();
-
-        recent_count >= 10
-    }
-
-    fn should_permanent_ban(&self) -> bool {
-        // 1小时内50次违规
-        let one_hour_ago = Instant::now() - Duration::from_secs(3600);
-        let recent_count = self
-            .recent_violations
-            .iter()
-            .filter(|t| **t > one_hour_ago)
-            .count();
-
-        recent_count >= 50
-    }
-}
-
 /// 安全检查结果
 #[derive(Debug, Clone)]
 pub struct SecurityCheckResult {
@@ -208,15 +144,18 @@ impl SecurityCheckResult {
 }
 
 /// 中央安全服务
+///
+/// 客户端状态、IP 防护记录、限流令牌桶都在可替换的存储里：
+/// 集群部署用 Redis，所有节点看到同一份状态；手动封禁另存 Postgres，重启后回灌。
 pub struct SecurityService {
-    /// 用户/设备状态管理器
-    client_states: Arc<RwLock<HashMap<(u64, String), ClientStateManager>>>,
+    /// 客户端状态 / IP 防护记录存储
+    store: Arc<dyn SecurityStateStore>,
 
     /// 多维度限流器
     rate_limiter: Arc<MultiDimensionRateLimiter>,
 
-    /// IP 保护记录
-    ip_protections: Arc<RwLock<HashMap<String, IpProtectionRecord>>>,
+    /// 手动封禁持久化（未配置时封禁只存在于状态存储）
+    ban_repo: Option<Arc<SecurityBanRepository>>,
 
     /// 配置
     config: SecurityConfig,
@@ -296,15 +235,38 @@ impl SecurityConfig {
 }
 
 impl SecurityService {
+    /// 进程内存储（单节点 / 测试）
     pub fn new(config: SecurityConfig) -> Self {
+        Self::with_store(
+            config,
+            Arc::new(MemorySecurityStateStore::new()),
+            Arc::new(LocalTokenBuckets::new()),
+        )
+    }
+
+    /// 使用指定的状态存储与令牌桶存储（集群部署传 Redis 实现）
+    pub fn with_store(
+        config: SecurityConfig,
+        store: Arc<dyn SecurityStateStore>,
+        bucket_store: Arc<dyn TokenBucketStore>,
+    ) -> Self {
         Self {
-            client_states: Arc::new(RwLock::new(HashMap::new())),
-            rate_limiter: Arc::new(MultiDimensionRateLimiter::new(config.rate_limit.clone())),
-            ip_protections: Arc::new(RwLock::new(HashMap::new())),
+            store,
+            rate_limiter: Arc::new(MultiDimensionRateLimiter::with_store(
+                config.rate_limit.clone(),
+                bucket_store,
+            )),
+            ban_repo: None,
             config,
         }
     }
 
+    /// 设置手动封禁的持久化 Repository
+    pub fn with_ban_repository(mut self, ban_repo: Arc<SecurityBanRepository>) -> Self {
+        self.ban_repo = Some(ban_repo);
+        self
+    }
+
     /// 连接层检查（IP 级别）
     pub async fn check_connection(&self, ip: &str) -> SecurityCheckResult {
         // ObserveOnly 模式：只记录，不限制
@@ -318,17 +280,17 @@ impl SecurityService {
         }
 
         // 1. 检查 IP 是否被封禁
-        let ip_protections = self.ip_protections.read().await;
-        if let Some(record) = ip_protections.get(ip) {
-            if record.is_banned() {
+        match self.store.load_ip(ip).await {
+            Ok(Some(record)) if record.is_banned(now_millis()) => {
                 warn!("🚫 IP {} 被封禁（模式: {:?}）", ip, self.config.mode);
                 return SecurityCheckResult::deny(format!("IP {} is banned", ip));
             }
+            Ok(_) => {}
+            Err(e) => warn!("读取 IP {} 防护记录失败（放行）: {}", ip, e),
         }
-        drop(ip_protections);
 
         // 2. 连接速率限制
-        if !self.rate_limiter.check_ip_connection(ip) {
+        if !self.rate_limiter.check_ip_connection(ip).await {
             // EnforceLight: 只记录，不封禁
             if self.config.mode == SecurityMode::EnforceLight {
                 warn!("⚠️ [EnforceLight] IP {} 连接超限（只记录）", ip);
@@ -363,12 +325,33 @@ impl SecurityService {
             return SecurityCheckResult::allow(ClientState::Normal);
         }
 
-        // 1. 获取或创建客户端状态管理器
-        let mut client_states = self.client_states.write().await;
-        let state_manager = client_states
-            .entry((user_id, device_id.to_string()))
-            .or_insert_with(|| ClientStateManager::new(user_id, device_id.to_string()));
+        // 1. 读取客户端状态（共享存储），检查后写回
+        let mut state_manager = self.load_state_manager(user_id, device_id).await;
+        let before = state_fingerprint(&state_manager);
+        let result = self
+            .evaluate_send_message(
+                &mut state_manager,
+                user_id,
+                channel_id,
+                recipient_count,
+                message_size,
+                is_media,
+            )
+            .await;
+        self.save_state_manager_if_changed(user_id, device_id, &state_manager, before)
+            .await;
+        result
+    }
 
+    async fn evaluate_send_message(
+        &self,
+        state_manager: &mut ClientStateManager,
+        user_id: u64,
+        channel_id: u64,
+        recipient_count: usize,
+        message_size: usize,
+        is_media: bool,
+    ) -> SecurityCheckResult {
         let current_state = state_manager.current_state();
 
         // 2. EnforceLight: 只检查基础状态，不启用 WriteDisabled 和 ShadowBan
@@ -407,13 +390,15 @@ impl SecurityService {
         };
 
         // 5. 限流检查
-        let trust_score = state_manager.trust_score();
-        let (allowed, violation) = self.rate_limiter.check_and_consume(
-            user_id,
-            "messages.send",
-            Some(channel_id),
-            trust_score,
-        );
+        let (allowed, violation) = self
+            .rate_limiter
+            .check_and_consume(
+                user_id,
+                "messages.send",
+                Some(channel_id),
+                state_manager.trust_score(),
+            )
+            .await;
 
         if !allowed {
             if let Some(violation_type) = violation {
@@ -480,12 +465,24 @@ impl SecurityService {
             return SecurityCheckResult::allow(ClientState::Normal);
         }
 
-        // 1. 获取或创建客户端状态管理器
-        let mut client_states = self.client_states.write().await;
-        let state_manager = client_states
-            .entry((user_id, device_id.to_string()))
-            .or_insert_with(|| ClientStateManager::new(user_id, device_id.to_string()));
+        // 1. 读取客户端状态（共享存储），检查后写回
+        let mut state_manager = self.load_state_manager(user_id, device_id).await;
+        let before = state_fingerprint(&state_manager);
+        let result = self
+            .evaluate_rpc(&mut state_manager, user_id, rpc_method, channel_id)
+            .await;
+        self.save_state_manager_if_changed(user_id, device_id, &state_manager, before)
+            .await;
+        result
+    }
 
+    async fn evaluate_rpc(
+        &self,
+        state_manager: &mut ClientStateManager,
+        user_id: u64,
+        rpc_method: &str,
+        channel_id: Option<u64>,
+    ) -> SecurityCheckResult {
         let current_state = state_manager.current_state();
 
         // 2. EnforceLight: 简化状态检查
@@ -521,10 +518,10 @@ impl SecurityService {
         }
 
         // 4. 限流检查
-        let trust_score = state_manager.trust_score();
-        let (allowed, violation) =
-            self.rate_limiter
-                .check_and_consume(user_id, rpc_method, channel_id, trust_score);
+        let (allowed, violation) = self
+            .rate_limiter
+            .check_and_consume(user_id, rpc_method, channel_id, state_manager.trust_score())
+            .await;
 
         if !allowed {
             if let Some(violation_type) = violation {
@@ -560,12 +557,10 @@ impl SecurityService {
 
     /// 认证失败记录
     pub async fn record_auth_failure(&self, user_id: u64, device_id: &str, ip: &str) {
-        let mut client_states = self.client_states.write().await;
-        let state_manager = client_states
-            .entry((user_id, device_id.to_string()))
-            .or_insert_with(|| ClientStateManager::new(user_id, device_id.to_string()));
-
+        let mut state_manager = self.load_state_manager(user_id, device_id).await;
         state_manager.auto_escalate(ViolationType::AuthFailed);
+        self.save_state_manager(user_id, device_id, &state_manager)
+            .await;
 
         // 同时记录 IP 违规
         self.record_ip_violation(ip).await;
@@ -573,78 +568,123 @@ impl SecurityService {
 
     /// 记录 IP 违规
     async fn record_ip_violation(&self, ip: &str) {
-        let mut ip_protections = self.ip_protections.write().await;
-        let record = ip_protections
-            .entry(ip.to_string())
-            .or_insert_with(IpProtectionRecord::new);
-
-        record.add_violation();
+        let now_ms = now_millis();
+        let mut record = match self.store.load_ip(ip).await {
+            Ok(record) => record.unwrap_or_default(),
+            Err(e) => {
+                warn!("读取 IP {} 防护记录失败: {}", ip, e);
+                return;
+            }
+        };
 
         // 检查是否需要封禁
-        if record.should_permanent_ban() {
-            record.permanent_ban = true;
-            error!("🚫 IP {} 已被永久封禁（严重违规）", ip);
-        } else if record.should_temp_ban() {
-            record.temp_ban_until = Some(Instant::now() + Duration::from_secs(1800)); // 30分钟
-            warn!("⚠️ IP {} 已被临时封禁 30分钟", ip);
+        match record.add_violation(now_ms) {
+            Some(IpBanLevel::Permanent) => error!("🚫 IP {} 已被永久封禁（严重违规）", ip),
+            Some(IpBanLevel::Temporary) => warn!("⚠️ IP {} 已被临时封禁 30分钟", ip),
+            None => {}
+        }
+
+        if let Err(e) = self.store.save_ip(ip, &record, now_ms).await {
+            warn!("写入 IP {} 防护记录失败: {}", ip, e);
         }
     }
 
     /// 奖励良好行为（恢复信任分）
     pub async fn reward_good_behavior(&self, user_id: u64, device_id: &str) {
-        let mut client_states = self.client_states.write().await;
-        if let Some(state_manager) = client_states.get_mut(&(user_id, device_id.to_string())) {
+        if let Some(mut state_manager) = self.find_state_manager(user_id, device_id).await {
             state_manager.trust_score_mut().reward_good_behavior(1);
+            self.save_state_manager(user_id, device_id, &state_manager)
+                .await;
         }
     }
 
     /// 获取用户状态（用于监控和调试）
     pub async fn get_user_state(&self, user_id: u64, device_id: &str) -> Option<ClientState> {
-        let client_states = self.client_states.read().await;
-        client_states
-            .get(&(user_id, device_id.to_string()))
+        self.find_state_manager(user_id, device_id)
+            .await
             .map(|m| m.current_state())
     }
 
     /// 获取用户信任分
     pub async fn get_trust_score(&self, user_id: u64, device_id: &str) -> Option<u32> {
-        let client_states = self.client_states.read().await;
-        client_states
-            .get(&(user_id, device_id.to_string()))
+        self.find_state_manager(user_id, device_id)
+            .await
             .map(|m| m.trust_score().score)
     }
 
     /// 手动封禁用户（管理员操作）
+    ///
+    /// 配置了 Repository 时先落 Postgres，再写状态存储；落库失败不封禁，避免「看似封了、重启就没了」。
     pub async fn manual_ban(
         &self,
         user_id: u64,
         device_id: &str,
         reason: ViolationType,
         duration: Option<Duration>,
-    ) {
-        let mut client_states = self.client_states.write().await;
-        let state_manager = client_states
-            .entry((user_id, device_id.to_string()))
-            .or_insert_with(|| ClientStateManager::new(user_id, device_id.to_string()));
+        note: Option<&str>,
+    ) -> Result<()> {
+        if let Some(ban_repo) = &self.ban_repo {
+            let expires_at = duration.map(|d| now_millis() + d.as_millis() as i64);
+            ban_repo
+                .upsert(user_id, device_id, reason.as_str(), note, expires_at)
+                .await?;
+        }
 
+        let mut state_manager = self.load_state_manager(user_id, device_id).await;
         state_manager.transition_to(ClientState::ShadowBanned, reason, duration);
+        self.save_state_manager(user_id, device_id, &state_manager)
+            .await;
         warn!(
             "管理员封禁用户 {} (设备: {}), 原因: {:?}",
             user_id, device_id, reason
         );
+        Ok(())
     }
 
     /// 手动解封用户
-    pub async fn manual_unban(&self, user_id: u64, device_id: &str) {
-        let mut client_states = self.client_states.write().await;
-        if let Some(state_manager) = client_states.get_mut(&(user_id, device_id.to_string())) {
+    pub async fn manual_unban(&self, user_id: u64, device_id: &str) -> Result<()> {
+        if let Some(ban_repo) = &self.ban_repo {
+            ban_repo.delete_by_device(user_id, device_id).await?;
+        }
+        if let Some(mut state_manager) = self.find_state_manager(user_id, device_id).await {
             state_manager.transition_to(
                 ClientState::Normal,
                 ViolationType::RateLimit, // 占位符
                 None,
             );
+            self.save_state_manager(user_id, device_id, &state_manager)
+                .await;
             info!("管理员解封用户 {} (设备: {})", user_id, device_id);
         }
+        Ok(())
+    }
+
+    /// 把 Postgres 中未到期的手动封禁回灌到状态存储（启动时调用）
+    ///
+    /// 已处于 Shadow Ban 的设备跳过，不重复扣信任分。返回回灌的设备数。
+    pub async fn restore_manual_bans(&self) -> Result<usize> {
+        let Some(ban_repo) = &self.ban_repo else {
+            return Ok(0);
+        };
+        let now_ms = now_millis();
+        let mut restored = 0;
+        for ban in ban_repo.list_active(now_ms).await? {
+            let user_id = ban.user_id as u64;
+            let mut state_manager = self.load_state_manager(user_id, &ban.device_id).await;
+            if state_manager.current_state() == ClientState::ShadowBanned {
+                continue;
+            }
+            let reason = ViolationType::from_str(&ban.violation)
+                .unwrap_or(ViolationType::SuspiciousActivity);
+            let duration = ban
+                .expires_at
+                .map(|until| Duration::from_millis((until - now_ms).max(0) as u64));
+            state_manager.transition_to(ClientState::ShadowBanned, reason, duration);
+            self.save_state_manager(user_id, &ban.device_id, &state_manager)
+                .await;
+            restored += 1;
+        }
+        Ok(restored)
     }
 
     // =====================================================
@@ -653,19 +693,23 @@ impl SecurityService {
 
     /// 获取所有处于 ShadowBanned 状态的用户/设备列表（管理 API）
     pub async fn list_shadow_banned(&self) -> Vec<(u64, String, ClientState, Option<u32>)> {
-        let client_states = self.client_states.read().await;
-        client_states
-            .iter()
-            .filter(|(_, mgr)| mgr.current_state() == ClientState::ShadowBanned)
-            .map(|((user_id, device_id), mgr)| {
-                (
-                    *user_id,
-                    device_id.clone(),
-                    mgr.current_state(),
-                    Some(mgr.trust_score().score),
-                )
-            })
-            .collect()
+        match self.store.list_shadow_banned(now_millis()).await {
+            Ok(banned) => banned
+                .into_iter()
+                .map(|(user_id, device_id, snapshot)| {
+                    (
+                        user_id,
+                        device_id,
+                        ClientState::ShadowBanned,
+                        Some(snapshot.trust_score),
+                    )
+                })
+                .collect(),
+            Err(e) => {
+                warn!("读取 Shadow Ban 列表失败: {}", e);
+                Vec::new()
+            }
+        }
     }
 
     /// 获取用户所有设备的安全状态（管理 API）
@@ -674,36 +718,40 @@ impl SecurityService {
         user_id: u64,
         device_ids: &[String],
     ) -> Vec<(String, Option<ClientState>, Option<u32>)> {
-        let client_states = self.client_states.read().await;
-        device_ids
-            .iter()
-            .map(|device_id| {
-                let key = (user_id, device_id.clone());
-                match client_states.get(&key) {
-                    Some(mgr) => (
-                        device_id.clone(),
-                        Some(mgr.current_state()),
-                        Some(mgr.trust_score().score),
-                    ),
-                    None => (device_id.clone(), None, None),
-                }
-            })
-            .collect()
+        let mut states = Vec::with_capacity(device_ids.len());
+        for device_id in device_ids {
+            match self.find_state_manager(user_id, device_id).await {
+                Some(mgr) => states.push((
+                    device_id.clone(),
+                    Some(mgr.current_state()),
+                    Some(mgr.trust_score().score),
+                )),
+                None => states.push((device_id.clone(), None, None)),
+            }
+        }
+        states
     }
 
     /// 解除用户所有设备的封禁（管理 API）
     ///
     /// 返回受影响的设备数
     pub async fn unban_all_user_devices(&self, user_id: u64, device_ids: &[String]) -> usize {
-        let mut client_states = self.client_states.write().await;
+        if let Some(ban_repo) = &self.ban_repo {
+            if let Err(e) = ban_repo.delete_by_user(user_id).await {
+                error!("删除用户 {} 的封禁记录失败: {}", user_id, e);
+            }
+        }
+
         let mut affected = 0;
         for device_id in device_ids {
-            if let Some(state_manager) = client_states.get_mut(&(user_id, device_id.clone())) {
+            if let Some(mut state_manager) = self.find_state_manager(user_id, device_id).await {
                 state_manager.transition_to(
                     ClientState::Normal,
                     ViolationType::RateLimit, // 占位符
                     None,
                 );
+                self.save_state_manager(user_id, device_id, &state_manager)
+                    .await;
                 affected += 1;
             }
         }
@@ -715,25 +763,98 @@ impl SecurityService {
 
     /// 定期清理过期数据（后台任务）
     pub async fn cleanup_expired_data(&self) {
-        let client_states = self.client_states.write().await;
-        let mut ip_protections = self.ip_protections.write().await;
-
-        // 清理过期的 IP 临时封禁
-        ip_protections.retain(|_, record| {
-            if let Some(until) = record.temp_ban_until {
-                if Instant::now() >= until {
-                    return false; // 移除过期的临时封禁
-                }
+        if let Err(e) = self.store.cleanup_expired(now_millis()).await {
+            warn!("安全状态清理失败: {}", e);
+        }
+    }
+
+    // =====================================================
+    // 状态存储读写
+    // =====================================================
+
+    /// 读取已有状态；不存在或读取失败返回 None
+    async fn find_state_manager(
+        &self,
+        user_id: u64,
+        device_id: &str,
+    ) -> Option<ClientStateManager> {
+        match self.store.load_client(user_id, device_id).await {
+            Ok(snapshot) => snapshot.map(|snapshot| {
+                ClientStateManager::from_snapshot(
+                    user_id,
+                    device_id.to_string(),
+                    &snapshot,
+                    now_millis(),
+                )
+            }),
+            Err(e) => {
+                warn!(
+                    "读取客户端安全状态失败: user={}, device={}, err={}",
+                    user_id, device_id, e
+                );
+                None
             }
-            !record.permanent_ban || !record.recent_violations.is_empty()
-        });
+        }
+    }
 
-        info!(
-            "安全服务清理完成：保留 {} 个客户端状态，{} 个 IP 记录",
-            client_states.len(),
-            ip_protections.len()
-        );
+    /// 读取或新建状态
+    ///
+    /// 🔴 存储不可用时按新客户端处理（放行），不能因为 Redis 抖动把所有请求拒掉。
+    async fn load_state_manager(&self, user_id: u64, device_id: &str) -> ClientStateManager {
+        self.find_state_manager(user_id, device_id)
+            .await
+            .unwrap_or_else(|| ClientStateManager::new(user_id, device_id.to_string()))
+    }
+
+    async fn save_state_manager(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        state_manager: &ClientStateManager,
+    ) {
+        let now_ms = now_millis();
+        let snapshot = state_manager.snapshot(now_ms);
+        if let Err(e) = self
+            .store
+            .save_client(user_id, device_id, &snapshot, now_ms)
+            .await
+        {
+            warn!(
+                "写入客户端安全状态失败: user={}, device={}, err={}",
+                user_id, device_id, e
+            );
+        }
     }
+
+    /// 状态有变化才写回，正常请求不产生写流量
+    async fn save_state_manager_if_changed(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        state_manager: &ClientStateManager,
+        before: StateFingerprint,
+    ) {
+        if state_fingerprint(state_manager) != before {
+            self.save_state_manager(user_id, device_id, state_manager)
+                .await;
+        }
+    }
+}
+
+/// 用于判断一次检查是否改动了状态：状态迁移和信任分变化都会刷新 last_updated
+type StateFingerprint = (ClientState, u32, Instant);
+
+fn state_fingerprint(state_manager: &ClientStateManager) -> StateFingerprint {
+    let trust_score = state_manager.trust_score();
+    (
+        state_manager.current_state(),
+        trust_score.score,
+        trust_score.last_updated,
+    )
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
 }
 
 #[cfg(test)]
@@ -0,0 +1,512 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// 安全状态存储
+///
+/// 客户端状态机 / 信任分、IP 防护记录和限流令牌桶如果只放在进程内存里，
+/// 恶意客户端换个节点重连就能清零，节点重启也会丢掉所有处罚。
+/// 集群部署用 Redis 实现，单节点 / 测试用内存实现。
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use super::client_state::{ClientState, ClientStateSnapshot};
+use super::rate_limiter::{RateLimitKey, TokenBucketStore};
+use crate::error::{Result, ServerError};
+use crate::infra::redis::RedisClient;
+
+/// 客户端状态保留时间：信任分与违规历史在最后一次变化后保留 7 天
+pub const CLIENT_STATE_TTL: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// IP 违规统计窗口
+const IP_VIOLATION_WINDOW_MS: i64 = 3600 * 1000;
+/// 临时封禁时长
+const IP_TEMP_BAN_MS: i64 = 1800 * 1000;
+
+/// IP 防护记录（时间均为 Unix 毫秒，可跨节点共享）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpProtectionRecord {
+    /// 最近违规时间戳
+    pub recent_violations_ms: Vec<i64>,
+    /// 临时封禁到期时间
+    pub temp_ban_until_ms: Option<i64>,
+    /// 是否永久封禁
+    pub permanent_ban: bool,
+}
+
+impl IpProtectionRecord {
+    pub fn is_banned(&self, now_ms: i64) -> bool {
+        self.permanent_ban || self.temp_ban_until_ms.is_some_and(|until| now_ms < until)
+    }
+
+    /// 记录一次违规并按阈值升级封禁，返回是否新触发了封禁
+    pub fn add_violation(&mut self, now_ms: i64) -> Option<IpBanLevel> {
+        self.recent_violations_ms.push(now_ms);
+        // 只保留最近1小时的记录
+        self.recent_violations_ms
+            .retain(|t| now_ms - *t < IP_VIOLATION_WINDOW_MS);
+
+        // 1小时内50次违规 -> 永久；5分钟内10次违规 -> 30分钟
+        if self.recent_violations_ms.len() >= 50 {
+            self.permanent_ban = true;
+            return Some(IpBanLevel::Permanent);
+        }
+        let five_min_ago = now_ms - 300 * 1000;
+        let recent_count = self
+            .recent_violations_ms
+            .iter()
+            .filter(|t| **t > five_min_ago)
+            .count();
+        if recent_count >= 10 {
+            self.temp_ban_until_ms = Some(now_ms + IP_TEMP_BAN_MS);
+            return Some(IpBanLevel::Temporary);
+        }
+        None
+    }
+
+    /// 记录已无意义（无封禁、无窗口内违规），可以删除
+    pub fn is_stale(&self, now_ms: i64) -> bool {
+        !self.is_banned(now_ms)
+            && self
+                .recent_violations_ms
+                .iter()
+                .all(|t| now_ms - *t >= IP_VIOLATION_WINDOW_MS)
+    }
+
+    /// 记录需要保留多久（None = 永久）
+    fn ttl_ms(&self, now_ms: i64) -> Option<i64> {
+        if self.permanent_ban {
+            return None;
+        }
+        let ban_left = self
+            .temp_ban_until_ms
+            .map(|until| until - now_ms)
+            .unwrap_or(0);
+        Some(ban_left.max(0) + IP_VIOLATION_WINDOW_MS)
+    }
+}
+
+/// IP 封禁级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpBanLevel {
+    Temporary,
+    Permanent,
+}
+
+/// 客户端状态记录应保留多久（None = 永久，用于无期限封禁）
+fn client_ttl_ms(snapshot: &ClientStateSnapshot, now_ms: i64) -> Option<i64> {
+    let base = CLIENT_STATE_TTL.as_millis() as i64;
+    if snapshot.state == ClientState::Normal {
+        return Some(base);
+    }
+    snapshot
+        .state_expires_at_ms
+        .map(|until| (until - now_ms).max(0).max(base))
+}
+
+/// 安全状态存储
+#[async_trait]
+pub trait SecurityStateStore: Send + Sync {
+    /// 读取客户端状态；不存在返回 None
+    async fn load_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+    ) -> Result<Option<ClientStateSnapshot>>;
+
+    /// 写入客户端状态（同时维护 Shadow Ban 索引）
+    async fn save_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        snapshot: &ClientStateSnapshot,
+        now_ms: i64,
+    ) -> Result<()>;
+
+    /// 当前处于 Shadow Ban 的所有用户/设备
+    async fn list_shadow_banned(
+        &self,
+        now_ms: i64,
+    ) -> Result<Vec<(u64, String, ClientStateSnapshot)>>;
+
+    /// 读取 IP 防护记录
+    async fn load_ip(&self, ip: &str) -> Result<Option<IpProtectionRecord>>;
+
+    /// 写入 IP 防护记录
+    async fn save_ip(&self, ip: &str, record: &IpProtectionRecord, now_ms: i64) -> Result<()>;
+
+    /// 清理过期数据（Redis 实现靠 TTL，只需修剪索引）
+    async fn cleanup_expired(&self, now_ms: i64) -> Result<()>;
+}
+
+// ============================================================
+// 内存实现（单节点 / 测试）
+// ============================================================
+
+/// 进程内安全状态存储
+///
+/// 重启即丢失，也不跨节点共享，仅用于测试和单节点开发环境。
+#[derive(Default)]
+pub struct MemorySecurityStateStore {
+    /// (user_id, device_id) -> (快照, 写入时间)
+    clients: RwLock<HashMap<(u64, String), (ClientStateSnapshot, i64)>>,
+    ips: RwLock<HashMap<String, IpProtectionRecord>>,
+}
+
+impl MemorySecurityStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SecurityStateStore for MemorySecurityStateStore {
+    async fn load_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+    ) -> Result<Option<ClientStateSnapshot>> {
+        let clients = self.clients.read().await;
+        Ok(clients
+            .get(&(user_id, device_id.to_string()))
+            .map(|(snapshot, _)| snapshot.clone()))
+    }
+
+    async fn save_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        snapshot: &ClientStateSnapshot,
+        now_ms: i64,
+    ) -> Result<()> {
+        let mut clients = self.clients.write().await;
+        clients.insert((user_id, device_id.to_string()), (snapshot.clone(), now_ms));
+        Ok(())
+    }
+
+    async fn list_shadow_banned(
+        &self,
+        now_ms: i64,
+    ) -> Result<Vec<(u64, String, ClientStateSnapshot)>> {
+        let clients = self.clients.read().await;
+        Ok(clients
+            .iter()
+            .filter(|(_, (snapshot, _))| {
+                snapshot.effective_state(now_ms) == ClientState::ShadowBanned
+            })
+            .map(|((user_id, device_id), (snapshot, _))| {
+                (*user_id, device_id.clone(), snapshot.clone())
+            })
+            .collect())
+    }
+
+    async fn load_ip(&self, ip: &str) -> Result<Option<IpProtectionRecord>> {
+        Ok(self.ips.read().await.get(ip).cloned())
+    }
+
+    async fn save_ip(&self, ip: &str, record: &IpProtectionRecord, _now_ms: i64) -> Result<()> {
+        self.ips
+            .write()
+            .await
+            .insert(ip.to_string(), record.clone());
+        Ok(())
+    }
+
+    async fn cleanup_expired(&self, now_ms: i64) -> Result<()> {
+        let mut clients = self.clients.write().await;
+        clients.retain(
+            |_, (snapshot, saved_at)| match client_ttl_ms(snapshot, now_ms) {
+                Some(ttl) => now_ms - *saved_at < ttl,
+                None => true,
+            },
+        );
+        let mut ips = self.ips.write().await;
+        ips.retain(|_, record| !record.is_stale(now_ms));
+        tracing::info!(
+            "安全状态清理完成：保留 {} 个客户端状态，{} 个 IP 记录",
+            clients.len(),
+            ips.len()
+        );
+        Ok(())
+    }
+}
+
+// ============================================================
+// Redis 实现（集群）
+// ============================================================
+
+/// Redis 安全状态存储
+///
+/// - `privchat:security:client:{user_id}:{device_id}`：客户端状态快照 JSON
+/// - `privchat:security:shadow_banned`：ZSET，member = `{user_id}:{device_id}`，score = 到期毫秒（永久为 +inf）
+/// - `privchat:security:ip:{ip}`：IP 防护记录 JSON
+///
+/// 🔴 客户端状态是「读-改-写」，没有跨节点锁：同一设备同时在两个节点上触发违规时
+/// 后写覆盖先写。设备通常只连一个节点，丢一次违规计数可以接受；手动封禁另有 Postgres 真源。
+pub struct RedisSecurityStateStore {
+    redis: Arc<RedisClient>,
+}
+
+impl RedisSecurityStateStore {
+    pub fn new(redis: Arc<RedisClient>) -> Self {
+        Self { redis }
+    }
+
+    fn client_key(user_id: u64, device_id: &str) -> String {
+        format!("privchat:security:client:{}:{}", user_id, device_id)
+    }
+
+    fn shadow_banned_key() -> &'static str {
+        "privchat:security:shadow_banned"
+    }
+
+    fn ip_key(ip: &str) -> String {
+        format!("privchat:security:ip:{}", ip)
+    }
+
+    async fn put_json(&self, key: &str, value: &str, ttl_ms: Option<i64>) -> Result<()> {
+        match ttl_ms {
+            // 向上取整到秒，至少 1 秒
+            Some(ttl_ms) => {
+                let ttl_secs = (ttl_ms.max(1) + 999) / 1000;
+                self.redis.setex(key, ttl_secs as usize, value).await
+            }
+            None => self.redis.set(key, value).await,
+        }
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| ServerError::Internal(format!("序列化安全状态失败: {}", e)))
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(raw: &str) -> Result<T> {
+    serde_json::from_str(raw).map_err(|e| ServerError::Internal(format!("解析安全状态失败: {}", e)))
+}
+
+#[async_trait]
+impl SecurityStateStore for RedisSecurityStateStore {
+    async fn load_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+    ) -> Result<Option<ClientStateSnapshot>> {
+        match self
+            .redis
+            .get(&Self::client_key(user_id, device_id))
+            .await?
+        {
+            Some(raw) => decode(&raw).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_client(
+        &self,
+        user_id: u64,
+        device_id: &str,
+        snapshot: &ClientStateSnapshot,
+        now_ms: i64,
+    ) -> Result<()> {
+        self.put_json(
+            &Self::client_key(user_id, device_id),
+            &encode(snapshot)?,
+            client_ttl_ms(snapshot, now_ms),
+        )
+        .await?;
+
+        let member = format!("{}:{}", user_id, device_id);
+        if snapshot.effective_state(now_ms) == ClientState::ShadowBanned {
+            let score = snapshot
+                .state_expires_at_ms
+                .map(|until| until as f64)
+                .unwrap_or(f64::INFINITY);
+            self.redis
+                .zadd(Self::shadow_banned_key(), score, &member)
+                .await
+        } else {
+            self.redis.zrem(Self::shadow_banned_key(), &member).await
+        }
+    }
+
+    async fn list_shadow_banned(
+        &self,
+        now_ms: i64,
+    ) -> Result<Vec<(u64, String, ClientStateSnapshot)>> {
+        let members = self
+            .redis
+            .zrangebyscore(
+                Self::shadow_banned_key(),
+                now_ms as f64,
+                f64::INFINITY,
+                None,
+            )
+            .await?;
+
+        let mut banned = Vec::with_capacity(members.len());
+        for member in members {
+            let Some((user_id, device_id)) = member
+                .split_once(':')
+                .and_then(|(uid, dev)| uid.parse::<u64>().ok().map(|uid| (uid, dev)))
+            else {
+                continue;
+            };
+            if let Some(snapshot) = self.load_client(user_id, device_id).await? {
+                if snapshot.effective_state(now_ms) == ClientState::ShadowBanned {
+                    banned.push((user_id, device_id.to_string(), snapshot));
+                }
+            }
+        }
+        Ok(banned)
+    }
+
+    async fn load_ip(&self, ip: &str) -> Result<Option<IpProtectionRecord>> {
+        match self.redis.get(&Self::ip_key(ip)).await? {
+            Some(raw) => decode(&raw).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_ip(&self, ip: &str, record: &IpProtectionRecord, now_ms: i64) -> Result<()> {
+        self.put_json(&Self::ip_key(ip), &encode(record)?, record.ttl_ms(now_ms))
+            .await
+    }
+
+    async fn cleanup_expired(&self, now_ms: i64) -> Result<()> {
+        // 状态与 IP 记录靠 TTL 过期，这里只修剪已到期的 Shadow Ban 索引
+        self.redis
+            .zremrangebyscore(Self::shadow_banned_key(), f64::NEG_INFINITY, now_ms as f64)
+            .await
+    }
+}
+
+/// Redis 共享令牌桶：所有节点扣同一只桶
+pub struct RedisTokenBucketStore {
+    redis: Arc<RedisClient>,
+}
+
+impl RedisTokenBucketStore {
+    pub fn new(redis: Arc<RedisClient>) -> Self {
+        Self { redis }
+    }
+}
+
+#[async_trait]
+impl TokenBucketStore for RedisTokenBucketStore {
+    async fn try_consume(
+        &self,
+        key: &RateLimitKey,
+        cost: f64,
+        rate: f64,
+        capacity: f64,
+    ) -> Result<bool> {
+        let key = format!("privchat:security:bucket:{}", key.storage_key());
+        self.redis
+            .token_bucket_consume(&key, cost, rate, capacity)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::client_state::{ClientStateManager, ViolationType};
+
+    const NOW_MS: i64 = 1_767_225_600_000;
+
+    #[test]
+    fn ip_record_escalates_and_expires() {
+        let mut record = IpProtectionRecord::default();
+        for i in 0..9 {
+            assert_eq!(record.add_violation(NOW_MS + i), None);
+        }
+        assert_eq!(
+            record.add_violation(NOW_MS + 9),
+            Some(IpBanLevel::Temporary)
+        );
+        assert!(record.is_banned(NOW_MS + 10));
+        assert!(!record.is_banned(NOW_MS + 9 + IP_TEMP_BAN_MS));
+        assert!(!record.is_stale(NOW_MS + IP_TEMP_BAN_MS));
+        assert!(record.is_stale(NOW_MS + IP_TEMP_BAN_MS + IP_VIOLATION_WINDOW_MS));
+
+        let mut record = IpProtectionRecord::default();
+        let mut level = None;
+        for i in 0..50 {
+            // 每分钟一次：不触发 5 分钟 10 次的临时封禁，1 小时累计 50 次永久封禁
+            level = record.add_violation(NOW_MS + i * 60_000).or(level);
+        }
+        assert_eq!(level, Some(IpBanLevel::Permanent));
+        assert_eq!(record.ttl_ms(NOW_MS), None);
+    }
+
+    #[tokio::test]
+    async fn memory_store_lists_shadow_banned_until_expiry() {
+        let store = MemorySecurityStateStore::new();
+        let mut manager = ClientStateManager::new(1001, "d1".to_string());
+        manager.transition_to(
+            ClientState::ShadowBanned,
+            ViolationType::Spam,
+            Some(Duration::from_secs(60)),
+        );
+        let snapshot = manager.snapshot(NOW_MS);
+        store
+            .save_client(1001, "d1", &snapshot, NOW_MS)
+            .await
+            .unwrap();
+        store
+            .save_client(
+                1002,
+                "d2",
+                &ClientStateManager::new(1002, "d2".to_string()).snapshot(NOW_MS),
+                NOW_MS,
+            )
+            .await
+            .unwrap();
+
+        let banned = store.list_shadow_banned(NOW_MS).await.unwrap();
+        assert_eq!(banned.len(), 1);
+        assert_eq!(banned[0].0, 1001);
+
+        let later = NOW_MS + 61_000;
+        assert!(store.list_shadow_banned(later).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn permanent_ban_has_no_ttl() {
+        let snapshot = ClientStateSnapshot {
+            state: ClientState::ShadowBanned,
+            state_expires_at_ms: None,
+            trust_score: 0,
+            account_age_days: 0,
+            violations: Vec::new(),
+        };
+        assert_eq!(client_ttl_ms(&snapshot, NOW_MS), None);
+
+        let normal = ClientStateSnapshot {
+            state: ClientState::Normal,
+            ..snapshot
+        };
+        assert_eq!(
+            client_ttl_ms(&normal, NOW_MS),
+            Some(CLIENT_STATE_TTL.as_millis() as i64)
+        );
+    }
+}
@@ -179,10 +179,32 @@ pub struct ChatServer {
     /// 好友关系服务（管理 API 资料变更的可见用户收件人解析）
     friend_service: Arc<crate::service::FriendService>,
     privacy_service: Arc<crate::service::PrivacyService>,
+    /// 黑名单服务（管理 API 的 Bot 发消息走同一套发送鉴权）
+    blacklist_service: Arc<crate::service::BlacklistService>,
+    /// 群慢速模式服务（管理 API 的 Bot 发消息走同一套发送鉴权）
+    group_slow_mode_service: Arc<crate::service::GroupSlowModeService>,
     /// SendMessageHandler 的引用（用于设置 TransportServer）
     send_message_handler: Arc<SendMessageHandler>,
     /// 文件服务
     file_service: Arc<crate::service::FileService>,
+    /// 表情包服务（管理 API 维护目录）
+    sticker_service: Arc<crate::service::StickerService>,
+    /// 两步验证服务（内置账号登录 + 管理员重置）
+    two_factor_service: Arc<crate::service::TwoFactorService>,
+    /// 出站 Webhook 服务（管理 API 维护端点，后台 worker 投递）
+    webhook_service: Arc<crate::service::WebhookService>,
+    /// Bot 平台服务（令牌 / 命令 / 更新队列）
+    bot_service: Arc<crate::service::BotService>,
+    /// 消息保留期服务（会话覆盖 + 后台清理超期消息）
+    retention_service: Arc<crate::service::RetentionService>,
+    /// 阅后即焚服务（会话计时器 + 后台硬删除到期消息）
+    disappearing_service: Arc<crate::service::DisappearingService>,
+    /// 自助注销服务（后台执行冷静期已结束的注销）
+    account_deletion_service: Arc<crate::service::AccountDeletionService>,
+    /// 个人数据导出服务（后台生成归档，文件服务器提供下载）
+    data_export_service: Arc<crate::service::DataExportService>,
+    /// 二维码服务（Key 与扫描日志持久化到 Postgres）
+    qrcode_service: Arc<crate::service::QRCodeService>,
     /// 上传 token 服务
     upload_token_service: Arc<crate::service::UploadTokenService>,
     /// Token 签发服务
@@ -225,8 +247,12 @@ pub struct ChatServer {
     offline_worker: Arc<crate::infra::OfflineMessageWorker>,
     /// Room 管理器（用于发布订阅频道管理）
     subscribe_manager: Arc<crate::infra::SubscribeManager>,
+    /// Room 广播扇出（集群模式下跨节点中继）
+    room_fanout: Arc<crate::infra::RoomFanout>,
     /// Room 订阅历史（Redis）
     room_history_service: Arc<crate::service::RoomHistoryService>,
+    /// Room 订阅者聊天（设置 / 禁言 / 置顶）
+    room_chat_service: Arc<crate::service::RoomChatService>,
     /// 通用服务端发消息服务（供登录通知、Admin API 等复用）
     message_service: Arc<crate::service::MessageService>,
     /// 用户服务（admin / RPC / job 统一入口，见 ADMIN_API_SPEC §1.4）
@@ -286,6 +312,14 @@ impl ChatServer {
         let database = Arc::new(database);
         info!("✅ 数据库连接池初始化完成");
 
+        // 只读副本（可选）：重读路径经 infra::read_pool 路由，副本延迟超限时回落主库
+        if let Some(replicas) = crate::infra::ReadReplicas::connect(&config.database)
+            .await
+            .map_err(|e| ServerError::Internal(format!("只读副本配置无效: {}", e)))?
+        {
+            crate::infra::read_replica::set_global_read_replicas(Arc::new(replicas));
+        }
+
         // 📦 初始化 Repository 层
         info!("📦 初始化 Repository 层...");
         let pool = Arc::new(database.pool().clone());
@@ -418,6 +452,11 @@ impl ChatServer {
             config.room.max_subscriptions_per_session,
             config.room.max_channel_subscribers_online
         );
+        let room_fanout = Arc::new(crate::infra::RoomFanout::new(
+            subscribe_manager.clone(),
+            connection_manager.clone(),
+            config.room.max_inflight_broadcasts_per_room,
+        ));
 
         // 4. 创建 Token 撤销服务
         let token_revocation_service = Arc::new(crate::auth::TokenRevocationService::new(
@@ -477,11 +516,7 @@ impl ChatServer {
             };
             crate::config::RedisConfig {
                 url,
-                pool_size: 50,
-                min_idle: 10,
-                connection_timeout_secs: 5,
-                command_timeout_ms: 5000,
-                idle_timeout_secs: 300,
+                ..Default::default()
             }
         });
         let redis_client = Arc::new(
@@ -505,6 +540,7 @@ impl ChatServer {
                 .set_session_ownership_registry(ownership.clone())
                 .await;
             ownership.start_maintenance(connection_manager.clone());
+            let room_node_id = ownership.node_id().to_string();
 
             let dispatch_bus = crate::infra::CrossNodeDispatchBus::new(
                 redis_client.clone(),
@@ -514,6 +550,10 @@ impl ChatServer {
             dispatch_bus.start_worker();
             message_router.set_cross_node_bus(dispatch_bus).await;
             info!("✅ Cross-node session ownership and dispatch bus started");
+
+            room_fanout
+                .enable_cluster(redis_client.clone(), room_node_id)
+                .await;
         }
 
         // 🔧 初始化 pts 同步系统（需要在 OfflineMessageWorker 之前创建）
@@ -551,7 +591,10 @@ impl ChatServer {
         info!("✅ CommittedTimelineDeliveryService 后台任务已启动");
 
         // 2.5 创建 DeliveryTracker（送达水位追踪）
-        let delivery_tracker = Arc::new(crate::service::DeliveryTracker::new(&config.redis_url)?);
+        let delivery_tracker = Arc::new(crate::service::DeliveryTracker::new(redis_client.clone()));
+        let group_slow_mode_service = Arc::new(crate::service::GroupSlowModeService::new(
+            redis_client.clone(),
+        ));
         info!("✅ DeliveryTracker 创建完成");
 
         // 3. 创建 OfflineMessageWorker
@@ -602,8 +645,13 @@ impl ChatServer {
             ));
         info!("✅ 黑名单服务初始化完成");
 
-        // 创建二维码服务
-        let qrcode_service = Arc::new(crate::service::QRCodeService::new());
+        // 创建二维码服务（Postgres 为真源 + Redis 缓存，重启不丢、跨节点可解析）
+        let qrcode_service = Arc::new(crate::service::QRCodeService::with_store(Arc::new(
+            crate::service::PersistentQRKeyStore::new(
+                Arc::new(crate::repository::QRCodeRepository::new((*pool).clone())),
+                redis_client.clone(),
+            ),
+        )));
         info!("✅ 二维码服务初始化完成");
 
         // 创建审批服务（#72A：pending 申请持久化到 DB，重启不丢）
@@ -642,7 +690,7 @@ impl ChatServer {
             pool.clone(),
         );
         read_state_inner.set_delivery_tracker(delivery_tracker.clone());
-        let read_state_service = Arc::new(read_state_inner);
+        read_state_inner.set_privacy_service(privacy_service.clone());
 
         // 创建文件服务（多数据中心：按 current_region 或 default_storage_source_id 选择存储源）
         info!("🔧 初始化文件服务...");
@@ -667,9 +715,41 @@ impl ChatServer {
             config.file_default_storage_source_id
         );
 
+        // 创建表情包服务（目录在 PostgreSQL，图片走 FileService 存储源）
+        info!("🔧 初始化表情包服务...");
+        let sticker_service = Arc::new(crate::service::StickerService::new(
+            Arc::new(crate::repository::StickerRepository::new((*pool).clone())),
+            file_service.clone(),
+        ));
+        info!("✅ 表情包服务初始化完成");
+
+        // 创建两步验证服务（密钥 / 恢复码在 PostgreSQL，登录 challenge 在 Redis）
+        let two_factor_service = Arc::new(crate::service::TwoFactorService::new(
+            Arc::new(crate::repository::TwoFactorRepository::new((*pool).clone())),
+            redis_client.clone(),
+            config.account.totp_issuer.clone(),
+        ));
+
+        // 创建出站 Webhook 服务（端点 / 投递日志在 PostgreSQL）；业务点经全局入口入队
+        let webhook_service = Arc::new(crate::service::WebhookService::new(Arc::new(
+            crate::repository::WebhookRepository::new((*pool).clone()),
+        )));
+        crate::service::webhook_service::set_global_webhook_service(webhook_service.clone());
+
+        // 创建找回密码服务（令牌在 PostgreSQL；投递方式按 [account] 配置选择，
+        // 本地投递只在显式打开开发开关时启用）
+        let password_reset_service = Arc::new(crate::service::PasswordResetService::new(
+            Arc::new(crate::repository::PasswordResetRepository::new(
+                (*pool).clone(),
+            )),
+            user_repository.clone(),
+            crate::service::password_reset_service::sender_from_config(&config.account),
+            config.account.password_reset_ttl_secs,
+        ));
+
         // 创建 @提及服务
         info!("🔧 初始化 @提及服务...");
-        let mention_service = Arc::new(crate::service::MentionService::new());
+        let mention_service = Arc::new(crate::service::MentionService::new(pool.clone()));
         info!("✅ @提及服务初始化完成");
 
         // ✨ Phase 3.5: 提前创建用户设备 Repository（供 SendMessageHandler 使用）
@@ -677,6 +757,9 @@ impl ChatServer {
             (*pool).clone(),
         ));
         info!("✅ UserDeviceRepository 创建完成（提前创建）");
+        let user_push_settings_repo = Arc::new(crate::repository::UserPushSettingsRepository::new(
+            (*pool).clone(),
+        ));
 
         // server-event/dispatch 出站 client（spec SERVER_EVENT_DISPATCH_SPEC §3）；
         // 统一所有 server→downstream emit（含 transfer.requested + bot.followed +
@@ -706,6 +789,60 @@ impl ChatServer {
             }
         };
 
+        // 创建 Bot 平台服务（令牌 / 命令 / 更新队列在 PostgreSQL）；按钮回调经 server event 通知
+        let bot_service = Arc::new(crate::service::BotService::new(
+            Arc::new(crate::repository::BotPlatformRepository::new(
+                (*pool).clone(),
+            )),
+            user_repository.clone(),
+            server_event_client.clone(),
+        ));
+
+        // 创建消息保留期服务（平台默认来自 [message] retention_secs，会话覆盖在 PostgreSQL）
+        let retention_service = Arc::new(crate::service::RetentionService::new(
+            Arc::new(crate::repository::RetentionRepository::new((*pool).clone())),
+            message_history_service.clone(),
+            config.message.retention_secs,
+        ));
+
+        // 创建阅后即焚服务（计时在 PostgreSQL；已读后计时的消息由 ReadStateService 启动倒计时）
+        let disappearing_service = Arc::new(crate::service::DisappearingService::new(
+            Arc::new(crate::repository::DisappearingRepository::new(
+                (*pool).clone(),
+            )),
+            message_history_service.clone(),
+            file_service.clone(),
+        ));
+        read_state_inner.set_disappearing_service(disappearing_service.clone());
+        let read_state_service = Arc::new(read_state_inner);
+
+        // 创建个人数据导出服务（归档写入默认存储源，经文件服务器下载）
+        let data_export_service = Arc::new(crate::service::DataExportService::new(
+            Arc::new(crate::repository::DataExportRepository::new(
+                (*pool).clone(),
+            )),
+            file_service.clone(),
+            config.account.export_ttl_secs,
+        ));
+
+        // 创建自助注销服务（冷静期 + 后台执行最终注销）
+        let account_deletion_service = Arc::new(crate::service::AccountDeletionService::new(
+            Arc::new(crate::repository::AccountDeletionRepository::new(
+                (*pool).clone(),
+            )),
+            user_repository.clone(),
+            device_manager_db.clone(),
+            connection_manager.clone(),
+            channel_service.clone(),
+            friend_service.clone(),
+            message_history_service.clone(),
+            file_service.clone(),
+            data_export_service.clone(),
+            cache_manager.clone(),
+            config.account.deletion_grace_secs,
+            config.account.deletion_message_policy,
+        ));
+
         // 创建 SendMessageHandler（需要 FileService、ChannelService 和 MessageRouter）
         let mut send_handler_inner = SendMessageHandler::new(
             message_history_service.clone(),
@@ -715,11 +852,11 @@ impl ChatServer {
             pts_generator.clone(),
             privacy_service.clone(),
             friend_service.clone(),
-            mention_service.clone(),
             message_repository.clone(),
             auth_session_manager.clone(),
             committed_delivery_service.clone(),
             Some(user_device_repo.clone()), // ✨ Phase 3.5: 传递 user_device_repo
+            group_slow_mode_service.clone(),
         );
         // Inject ServerEvent emit deps（spec SERVER_EVENT_DISPATCH_SPEC §11.1：
         // system_user.message_received）。`server_event_client` 可能为 None
@@ -729,6 +866,8 @@ impl ChatServer {
             user_repository.clone(),
             cache_manager.clone(),
         );
+        send_handler_inner.set_sticker_service(sticker_service.clone());
+        send_handler_inner.set_bot_service(bot_service.clone());
         let send_message_handler = Arc::new(send_handler_inner);
 
         // 创建通用服务端发消息服务（供登录通知、Admin API 等复用）
@@ -753,6 +892,16 @@ impl ChatServer {
             }
         );
 
+        // 广播频道服务：发布复用 MessageService 的 pts / commit / dispatch 链路
+        let broadcast_channel_service = Arc::new(crate::service::BroadcastChannelService::new(
+            Arc::new(crate::repository::BroadcastChannelRepository::new(
+                pool.clone(),
+            )),
+            channel_service.clone(),
+            message_service.clone(),
+        ));
+        info!("✅ BroadcastChannelService 创建完成");
+
         // 用户服务：admin / RPC / job 对 User 领域对象的唯一入口
         let user_service = Arc::new(crate::service::UserService::new(user_repository.clone()));
         info!("✅ UserService 创建完成");
@@ -832,14 +981,12 @@ impl ChatServer {
             }
         );
 
-        // 创建表情包服务
-        info!("🔧 初始化表情包服务...");
-        let sticker_service = Arc::new(crate::service::StickerService::new());
-        info!("✅ 表情包服务初始化完成");
-
         // 创建 Reaction 服务
         info!("🔧 初始化 Reaction 服务...");
-        let reaction_service = Arc::new(crate::service::ReactionService::new(pool.clone()));
+        let reaction_service = Arc::new(crate::service::ReactionService::new(
+            pool.clone(),
+            sticker_service.clone(),
+        ));
         info!("✅ Reaction 服务初始化完成");
 
         // 创建在线状态管理器
@@ -854,12 +1001,15 @@ impl ChatServer {
         let presence_tracker = Arc::new(crate::infra::PresenceTracker::new(
             presence_state_store.clone(),
         ));
-        let presence_service = Arc::new(crate::service::PresenceService::new(
-            presence_tracker,
-            channel_service.clone(),
-            subscribe_manager.clone(),
-            connection_manager.clone(),
-        ));
+        let presence_service = Arc::new(
+            crate::service::PresenceService::new(
+                presence_tracker,
+                channel_service.clone(),
+                subscribe_manager.clone(),
+                connection_manager.clone(),
+            )
+            .with_privacy_service(privacy_service.clone()),
+        );
         info!("✅ PresenceService 初始化完成");
 
         // 初始化 SyncService（pts 同步机制）
@@ -917,15 +1067,36 @@ impl ChatServer {
         let (push_tx, push_rx) = tokio::sync::mpsc::channel(1000);
 
         // 4. 创建共享的 Intent 状态管理器（Phase 3）
-        let intent_state = Arc::new(crate::push::IntentStateManager::new());
+        // 状态存 Redis：重启不丢，任意节点收到送达 / 撤销事件都能取消本节点规划的 Intent
+        let intent_state = Arc::new(crate::push::IntentStateManager::with_store(Arc::new(
+            crate::push::RedisIntentStore::new(
+                redis_client.clone(),
+                std::time::Duration::from_secs(config.push.intent_ttl_secs),
+            ),
+        )));
 
         // 5. 创建 Push Planner（在线态走 ConnectionManager 真源，不走 Redis KEYS）
+        // 推送文案按接收者渲染：发送者昵称 / 群名 / 语言模板 / 隐藏预览 / 未读角标
+        let push_content_resolver = Arc::new(crate::push::PushContentResolver::new(
+            user_repository.clone(),
+            channel_service.clone(),
+            unread_count_service.clone(),
+            user_push_settings_repo.clone(),
+        ));
+        // 推送策略：会话通知级别（全部 / 仅 @ / 不通知）+ 免打扰时段（静默推送）
+        let push_policy_resolver = Arc::new(crate::push::PushPolicyResolver::new(
+            channel_service.clone(),
+            user_push_settings_repo.clone(),
+            user_repository.clone(),
+        ));
         let push_planner = Arc::new(
             crate::push::PushPlanner::with_state_manager_and_connection_manager(
                 Some(redis_client.clone()),
                 Arc::clone(&intent_state),
                 connection_manager.clone(),
-            ),
+            )
+            .with_content_resolver(push_content_resolver)
+            .with_policy_resolver(push_policy_resolver),
         );
         let planner_event_bus = Arc::clone(&event_bus);
         let planner_tx = push_tx.clone();
@@ -938,32 +1109,42 @@ impl ChatServer {
 
         // 6. 根据配置初始化 Push Provider
         let fcm_provider = if config.push.enabled && config.push.fcm.enabled {
-            let project_id = config
-                .push
-                .fcm
-                .project_id
-                .as_deref()
-                .map(str::trim)
-                .filter(|v| !v.is_empty())
-                .map(str::to_string);
-            let access_token = config
-                .push
-                .fcm
-                .access_token
-                .as_deref()
-                .map(str::trim)
-                .filter(|v| !v.is_empty())
-                .map(str::to_string);
-            match (project_id, access_token) {
-                (Some(project_id), Some(access_token)) => {
-                    info!("✅ FCM Provider 已启用");
+            let non_empty = |v: &Option<String>| {
+                v.as_deref()
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+            };
+            let project_id = non_empty(&config.push.fcm.project_id);
+            let access_token = non_empty(&config.push.fcm.access_token);
+            let service_account_path = non_empty(&config.push.fcm.service_account_path);
+            let token_endpoint = non_empty(&config.push.fcm.token_endpoint);
+            match (service_account_path, project_id, access_token) {
+                (Some(path), project_id, _) => {
+                    match crate::push::provider::FcmProvider::from_service_account(
+                        &path,
+                        project_id,
+                        token_endpoint,
+                    ) {
+                        Ok(provider) => {
+                            info!("✅ FCM Provider 已启用（service account，自动刷新 token）");
+                            Some(Arc::new(provider))
+                        }
+                        Err(e) => {
+                            warn!("⚠️ FCM service account 加载失败，已降级为禁用: {}", e);
+                            None
+                        }
+                    }
+                }
+                (None, Some(project_id), Some(access_token)) => {
+                    warn!("⚠️ FCM Provider 使用静态 access_token，约 1 小时后过期，生产环境请配置 service_account_path");
                     Some(Arc::new(crate::push::provider::FcmProvider::new(
                         project_id,
                         access_token,
                     )))
                 }
                 _ => {
-                    warn!("⚠️ FCM Provider 已配置启用但缺少 project_id/access_token，已降级为禁用");
+                    warn!("⚠️ FCM Provider 已配置启用但缺少 service_account_path 或 project_id/access_token，已降级为禁用");
                     None
                 }
             }
@@ -1405,7 +1586,10 @@ impl ChatServer {
             lenovo_provider,
             zte_provider,
             meizu_provider,
-        );
+        )
+        .with_retry_policy(crate::push::PushRetryPolicy::from_config(
+            &config.push.retry,
+        ));
         tokio::spawn(async move {
             if let Err(e) = push_worker.start().await {
                 error!("❌ PushWorker 启动失败: {}", e);
@@ -1493,6 +1677,66 @@ impl ChatServer {
         let bot_follow_repository =
             Arc::new(crate::repository::BotFollowRepository::new(pool.clone()));
 
+        // 🔐 初始化安全系统
+        info!("🔐 初始化安全系统...");
+        let security_config: crate::security::SecurityConfig = config.security.clone().into();
+        info!("   - 安全模式: {:?}", security_config.mode);
+        info!(
+            "   - Shadow Ban: {}",
+            if security_config.enable_shadow_ban {
+                "启用"
+            } else {
+                "禁用"
+            }
+        );
+        info!(
+            "   - IP 封禁: {}",
+            if security_config.enable_ip_ban {
+                "启用"
+            } else {
+                "禁用"
+            }
+        );
+
+        // 安全状态放 Redis（跨节点一致），手动封禁以 Postgres 为真源并在启动时回灌
+        let security_service = Arc::new(
+            crate::security::SecurityService::with_store(
+                security_config,
+                Arc::new(crate::security::RedisSecurityStateStore::new(
+                    redis_client.clone(),
+                )),
+                Arc::new(crate::security::RedisTokenBucketStore::new(
+                    redis_client.clone(),
+                )),
+            )
+            .with_ban_repository(Arc::new(
+                crate::repository::SecurityBanRepository::new((*pool).clone()),
+            )),
+        );
+        match security_service.restore_manual_bans().await {
+            Ok(restored) if restored > 0 => info!("   - 回灌手动封禁: {} 个设备", restored),
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ 回灌手动封禁失败: {}", e),
+        }
+        let security_middleware = Arc::new(crate::middleware::SecurityMiddleware::new(
+            security_service.clone(),
+        ));
+        info!("✅ 安全系统初始化完成");
+
+        // Room 订阅者聊天（发言经安全系统多维限流，依赖上面的 SecurityService）
+        let room_chat_service = Arc::new(crate::service::RoomChatService::new(
+            redis_client.clone(),
+            room_history_service.clone(),
+            room_fanout.clone(),
+            subscribe_manager.clone(),
+            security_service.clone(),
+            &config.room,
+        ));
+        info!(
+            "✅ RoomChatService 创建完成 (chat_max_length={})",
+            config.room.chat_max_length
+        );
+
         // 初始化 RPC 系统
         info!("🔧 初始化 RPC 系统...");
         let rpc_services = crate::rpc::RpcServiceContext::new(
@@ -1537,37 +1781,22 @@ impl ChatServer {
             qr_login_publisher.clone(),
             bot_follow_repository.clone(),
             server_event_client.clone(),
+            broadcast_channel_service.clone(),
+            user_push_settings_repo.clone(),
+            two_factor_service.clone(),
+            password_reset_service,
+            mention_service.clone(),
+            bot_service.clone(),
+            retention_service.clone(),
+            disappearing_service.clone(),
+            account_deletion_service.clone(),
+            data_export_service.clone(),
+            room_chat_service.clone(),
+            group_slow_mode_service.clone(),
         );
         crate::rpc::init_rpc_system(rpc_services).await;
         info!("✅ RPC 系统初始化完成");
 
-        // 🔐 初始化安全系统
-        info!("🔐 初始化安全系统...");
-        let security_config: crate::security::SecurityConfig = config.security.clone().into();
-        info!("   - 安全模式: {:?}", security_config.mode);
-        info!(
-            "   - Shadow Ban: {}",
-            if security_config.enable_shadow_ban {
-                "启用"
-            } else {
-                "禁用"
-            }
-        );
-        info!(
-            "   - IP 封禁: {}",
-            if security_config.enable_ip_ban {
-                "启用"
-            } else {
-                "禁用"
-            }
-        );
-
-        let security_service = Arc::new(crate::security::SecurityService::new(security_config));
-        let security_middleware = Arc::new(crate::middleware::SecurityMiddleware::new(
-            security_service.clone(),
-        ));
-        info!("✅ 安全系统初始化完成");
-
         // 初始化业务 Handler 限流器
         let handler_limiter =
             crate::infra::handler_limiter::HandlerLimiter::new(config.handler_max_inflight);
@@ -1590,8 +1819,19 @@ impl ChatServer {
             channel_service,
             friend_service,
             privacy_service,
+            blacklist_service,
+            group_slow_mode_service,
             send_message_handler,
             file_service,
+            sticker_service,
+            two_factor_service,
+            webhook_service,
+            bot_service,
+            retention_service,
+            disappearing_service,
+            account_deletion_service,
+            data_export_service,
+            qrcode_service,
             upload_token_service,
             token_issue_service,
             auth_session_manager,
@@ -1613,7 +1853,9 @@ impl ChatServer {
             redis_client: Some(redis_client.clone()),
             offline_worker: offline_worker.clone(),
             subscribe_manager,
+            room_fanout,
             room_history_service,
+            room_chat_service,
             message_service,
             user_service,
             qr_login_service,
@@ -1869,6 +2111,57 @@ impl ChatServer {
             });
         }
 
+        // 出站 Webhook 投递（领取到期投递 / 指数退避重试 / 清理旧的成功记录）
+        self.webhook_service.clone().spawn_worker();
+
+        // 只读副本复制延迟探测（未配置副本时跳过）
+        if let Some(replicas) = crate::infra::read_replica::get_global_read_replicas() {
+            replicas.spawn_lag_monitor();
+        }
+
+        // Bot 更新队列：超过保留期仍未被 getUpdates 确认的更新每小时清理一次
+        {
+            let bot_service = self.bot_service.clone();
+            tokio::spawn(async move {
+                let mut tick = tokio::time::interval(std::time::Duration::from_secs(3600));
+                loop {
+                    tick.tick().await;
+                    match bot_service.purge_expired_updates().await {
+                        Ok(n) if n > 0 => info!("🧹 Bot 更新队列：清理过期更新 {n} 条"),
+                        Ok(_) => {}
+                        Err(e) => warn!("⚠️ Bot 更新队列清理失败: {e}"),
+                    }
+                }
+            });
+        }
+
+        // 消息保留期：周期性删除超期消息 / commit log，并通知客户端清理本地缓存
+        self.retention_service
+            .clone()
+            .spawn_sweeper(std::time::Duration::from_secs(
+                self.config.message.retention_sweep_interval_secs,
+            ));
+
+        // 阅后即焚：到期消息硬删除，追加 message.expired 通知所有设备
+        self.disappearing_service
+            .clone()
+            .spawn_sweeper(crate::service::disappearing_service::DISAPPEARING_SWEEP_INTERVAL);
+
+        // 个人数据导出：生成排队中的归档，删除过期归档
+        self.data_export_service
+            .clone()
+            .spawn_worker(crate::service::data_export_service::DATA_EXPORT_WORKER_INTERVAL);
+
+        // 二维码：清理过期超过保留期的 Key 与旧扫描日志
+        self.qrcode_service
+            .clone()
+            .spawn_purger(crate::service::qrcode_service::QR_PURGE_INTERVAL);
+
+        // 自助注销：冷静期结束的账号执行最终注销
+        self.account_deletion_service.clone().spawn_worker(
+            crate::service::account_deletion_service::ACCOUNT_DELETION_WORKER_INTERVAL,
+        );
+
         // 启动缓存统计任务
         self.start_cache_stats_reporter().await;
 
@@ -2239,6 +2532,7 @@ impl ChatServer {
             self.file_service.clone(),
             self.upload_token_service.clone(),
             Some(self.unified_token_service.clone()),
+            Some(self.data_export_service.clone()),
             self.config.http_file_server_port,
         );
 
@@ -2295,6 +2589,9 @@ impl ChatServer {
             self.security_service.clone(),
             self.subscribe_manager.clone(),
             self.room_history_service.clone(),
+            self.room_fanout.clone(),
+            self.room_chat_service.clone(),
+            self.qrcode_service.clone(),
             self.message_service.clone(),
             self.user_service.clone(),
             self.qr_login_service.clone(),
@@ -2302,6 +2599,12 @@ impl ChatServer {
             self.unified_token_service.clone(),
             self.config.room_ticket.clone().map(Arc::new),
             self.privacy_service.clone(),
+            self.blacklist_service.clone(),
+            self.group_slow_mode_service.clone(),
+            self.sticker_service.clone(),
+            self.two_factor_service.clone(),
+            self.webhook_service.clone(),
+            self.bot_service.clone(),
             self.config.admin_api_port,
         );
 
@@ -35,7 +35,8 @@ pub mod types;
 
 pub use client::{ServerEventClient, ServerEventError};
 pub use types::{
-    BotFollowedPayload, BotUnfollowedPayload, ServerEvent, ServerEventAck,
-    SystemUserMessageReceivedPayload, EVENT_TYPE_BOT_FOLLOWED, EVENT_TYPE_BOT_UNFOLLOWED,
-    EVENT_TYPE_SYSTEM_USER_MESSAGE_RECEIVED, SERVER_EVENT_DISPATCH_PATH,
+    BotCallbackQueriedPayload, BotFollowedPayload, BotUnfollowedPayload, ServerEvent,
+    ServerEventAck, SystemUserMessageReceivedPayload, EVENT_TYPE_BOT_CALLBACK_QUERIED,
+    EVENT_TYPE_BOT_FOLLOWED, EVENT_TYPE_BOT_UNFOLLOWED, EVENT_TYPE_SYSTEM_USER_MESSAGE_RECEIVED,
+    SERVER_EVENT_DISPATCH_PATH,
 };
@@ -50,6 +50,11 @@ pub const EVENT_TYPE_BOT_FOLLOWED: &str = "bot.followed";
 /// 用户 unfollow 一个 bot —— **ack-only**。Payload: [`BotUnfollowedPayload`]。
 pub const EVENT_TYPE_BOT_UNFOLLOWED: &str = "bot.unfollowed";
 
+/// 用户点了 bot 消息上的内联按钮 —— **ack-only**。
+/// Payload: [`BotCallbackQueriedPayload`]。同一回调也进 bot 的 `getUpdates` 队列，
+/// 下游 application 与独立部署的 bot 按需二选一消费。
+pub const EVENT_TYPE_BOT_CALLBACK_QUERIED: &str = "bot.callback_queried";
+
 /// Client wire `TransferRequest` 包装 —— **request-response**。
 /// Payload: [`TransferRequestedPayload`]；ack `response_payload` 解码后为
 /// [`TransferResponsePayload`]。详见 spec `CHANNEL_TRANSFER_SPEC` §5。
@@ -143,6 +148,14 @@ impl ServerEvent {
         Self::new(EVENT_TYPE_BOT_UNFOLLOWED, &payload, occurred_at_ms)
     }
 
+    /// Convenience builder: `bot.callback_queried`（ack-only）。
+    pub fn bot_callback_queried(
+        payload: &BotCallbackQueriedPayload,
+        occurred_at_ms: i64,
+    ) -> Result<Self, serde_json::Error> {
+        Self::new(EVENT_TYPE_BOT_CALLBACK_QUERIED, payload, occurred_at_ms)
+    }
+
     /// Convenience builder: `system_user.message_received`（ack-only）。
     ///
     /// Payload v1 只携带消息 **identity**（system_user_id / from_user_id /
@@ -251,6 +264,22 @@ pub struct BotUnfollowedPayload {
     pub occurred_at: i64,
 }
 
+/// Payload for `bot.callback_queried`。
+///
+/// `data` 是按钮的 `callback_data`，server 已校验它确实出现在该消息的键盘上。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotCallbackQueriedPayload {
+    /// server 生成的回调 ID，bot 侧幂等用
+    pub callback_id: String,
+    pub bot_user_id: u64,
+    /// 点按钮的用户
+    pub user_id: u64,
+    pub channel_id: u64,
+    pub message_id: u64,
+    pub data: String,
+    pub occurred_at: i64,
+}
+
 /// Payload for `system_user.message_received`（spec 07-application/SYSTEM_USER_SPEC §3.2）。
 ///
 /// **v1 不携带 message body** —— 只做触发索引。downstream 业务方需要消息
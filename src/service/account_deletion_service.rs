@@ -0,0 +1,425 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 用户自助注销
+//!
+//! 用户通过 `account/deletion/request`（内置账号需校验密码）发起注销，进入
+//! `deletion_grace_secs` 冷静期，期间账号照常可用，可用 `account/deletion/cancel` 撤销。
+//! 冷静期结束后由后台任务执行最终注销：
+//!
+//! 1. 吊销全部设备并断开在线连接；
+//! 2. 拥有的群转让给最早加入的管理员（没有管理员时取最早加入的成员），只剩自己的群直接解散；
+//!    随后退出所在的全部群；
+//! 3. 按 `deletion_message_policy` 处理已发送消息：`keep` 保留内容，`redact` 清空内容与附件
+//!    引用并追加 `message.redacted` commit；
+//! 4. 删除不再被任何消息引用的上传文件，以及全部个人数据导出归档；
+//! 5. 匿名化用户行（用户名 / 手机 / 邮箱释放，可被重新注册），删除好友关系、设置、令牌等个人数据，
+//!    并通知好友与会话成员刷新该用户资料。
+
+use crate::auth::{DeviceManagerDb, KickReason};
+use crate::config::DeletedMessagePolicy;
+use crate::error::{Result, ServerError};
+use crate::infra::{CacheManager, ConnectionManager};
+use crate::model::channel::ChannelType;
+use crate::repository::{AccountDeletionRecord, AccountDeletionRepository, UserRepository};
+use crate::service::sync::get_global_sync_service;
+use crate::service::{
+    ChannelService, DataExportService, EntityInvalidationPublisher, FileService, FriendService,
+    MessageHistoryService,
+};
+use privchat_protocol::{EntityInvalidation, EntityMutationHint};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 注销完成后用户的显示名
+pub const DELETED_ACCOUNT_DISPLAY_NAME: &str = "已注销用户";
+/// 后台任务轮询间隔
+pub const ACCOUNT_DELETION_WORKER_INTERVAL: Duration = Duration::from_secs(60);
+/// 执行中注销的租约：中途崩溃的注销在租约到期后重新执行（每一步都可重入）
+const DELETION_LEASE_MS: i64 = 30 * 60 * 1000;
+/// 每轮认领的注销数
+const DELETION_CLAIM_BATCH: i64 = 10;
+/// 每批清空的消息数
+const REDACT_BATCH_SIZE: i64 = 500;
+
+/// 注销状态（RPC 响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountDeletionStatus {
+    /// 是否有待执行的注销
+    pub scheduled: bool,
+    pub requested_at: Option<i64>,
+    /// 冷静期结束时间（毫秒）
+    pub execute_at: Option<i64>,
+}
+
+impl From<Option<AccountDeletionRecord>> for AccountDeletionStatus {
+    fn from(record: Option<AccountDeletionRecord>) -> Self {
+        match record.filter(|r| r.completed_at.is_none()) {
+            Some(r) => Self {
+                scheduled: true,
+                requested_at: Some(r.requested_at),
+                execute_at: Some(r.execute_at),
+            },
+            None => Self {
+                scheduled: false,
+                requested_at: None,
+                execute_at: None,
+            },
+        }
+    }
+}
+
+/// 一次最终注销的统计
+#[derive(Debug, Clone, Default)]
+pub struct AccountDeletionSummary {
+    pub revoked_devices: usize,
+    pub transferred_groups: usize,
+    pub dissolved_groups: usize,
+    pub left_groups: usize,
+    pub redacted_messages: u64,
+    pub released_files: u64,
+}
+
+/// 用户自助注销服务
+pub struct AccountDeletionService {
+    repository: Arc<AccountDeletionRepository>,
+    user_repository: Arc<UserRepository>,
+    device_manager_db: Arc<DeviceManagerDb>,
+    connection_manager: Arc<ConnectionManager>,
+    channel_service: Arc<ChannelService>,
+    friend_service: Arc<FriendService>,
+    message_history_service: Arc<MessageHistoryService>,
+    file_service: Arc<FileService>,
+    data_export_service: Arc<DataExportService>,
+    cache_manager: Arc<CacheManager>,
+    grace_secs: u64,
+    message_policy: DeletedMessagePolicy,
+}
+
+impl AccountDeletionService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        repository: Arc<AccountDeletionRepository>,
+        user_repository: Arc<UserRepository>,
+        device_manager_db: Arc<DeviceManagerDb>,
+        connection_manager: Arc<ConnectionManager>,
+        channel_service: Arc<ChannelService>,
+        friend_service: Arc<FriendService>,
+        message_history_service: Arc<MessageHistoryService>,
+        file_service: Arc<FileService>,
+        data_export_service: Arc<DataExportService>,
+        cache_manager: Arc<CacheManager>,
+        grace_secs: u64,
+        message_policy: DeletedMessagePolicy,
+    ) -> Self {
+        Self {
+            repository,
+            user_repository,
+            device_manager_db,
+            connection_manager,
+            channel_service,
+            friend_service,
+            message_history_service,
+            file_service,
+            data_export_service,
+            cache_manager,
+            grace_secs,
+            message_policy,
+        }
+    }
+
+    pub async fn status(&self, user_id: u64) -> Result<AccountDeletionStatus> {
+        Ok(self.repository.get(user_id).await?.into())
+    }
+
+    /// 发起注销；已有待执行的注销时保持原冷静期
+    pub async fn request(&self, user_id: u64) -> Result<AccountDeletionStatus> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let execute_at = now_ms + (self.grace_secs as i64).saturating_mul(1000);
+        let record = self
+            .repository
+            .schedule(user_id, now_ms, execute_at)
+            .await?;
+        if record.completed_at.is_some() {
+            return Err(ServerError::Validation("账号已注销".to_string()));
+        }
+        info!(
+            "🗑️ 账号注销已登记: user_id={}, execute_at={}",
+            user_id, record.execute_at
+        );
+        Ok(Some(record).into())
+    }
+
+    /// 冷静期内撤销注销，返回是否撤销了待执行的注销
+    pub async fn cancel(&self, user_id: u64) -> Result<bool> {
+        let cancelled = self.repository.cancel(user_id).await?;
+        if cancelled {
+            info!("↩️ 账号注销已撤销: user_id={}", user_id);
+        }
+        Ok(cancelled)
+    }
+
+    /// 执行一轮到期注销，返回完成数
+    pub async fn run_due_once(&self) -> Result<usize> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let user_ids = self
+            .repository
+            .claim_due(now_ms, DELETION_LEASE_MS, DELETION_CLAIM_BATCH)
+            .await?;
+        let mut completed = 0;
+        for user_id in user_ids {
+            match self.finalize(user_id).await {
+                Ok(summary) => {
+                    completed += 1;
+                    info!(
+                        "✅ 账号已注销: user_id={}, devices={}, groups(transferred/dissolved/left)={}/{}/{}, redacted_messages={}, files={}",
+                        user_id,
+                        summary.revoked_devices,
+                        summary.transferred_groups,
+                        summary.dissolved_groups,
+                        summary.left_groups,
+                        summary.redacted_messages,
+                        summary.released_files
+                    );
+                }
+                // 租约到期后重新认领，已完成的步骤可安全重放
+                Err(e) => warn!(
+                    "⚠️ 账号注销执行失败，稍后重试: user_id={}, error={}",
+                    user_id, e
+                ),
+            }
+        }
+        Ok(completed)
+    }
+
+    /// 启动后台任务
+    pub fn spawn_worker(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tick.tick().await;
+                if let Err(e) = self.run_due_once().await {
+                    warn!("⚠️ 认领到期账号注销失败: {}", e);
+                }
+            }
+        });
+    }
+
+    /// 最终注销（冷静期结束后由后台任务调用）
+    pub async fn finalize(&self, user_id: u64) -> Result<AccountDeletionSummary> {
+        let mut summary = AccountDeletionSummary::default();
+
+        // 好友与会话成员要在关系删除前解析出来，最后通知他们刷新资料
+        let friend_ids = self
+            .friend_service
+            .get_friends(user_id)
+            .await
+            .unwrap_or_default();
+        let mut recipients: BTreeSet<u64> = friend_ids.iter().copied().collect();
+        for channel in self
+            .channel_service
+            .get_user_channels(user_id)
+            .await
+            .channels
+        {
+            recipients.extend(channel.get_member_ids());
+        }
+        recipients.remove(&user_id);
+
+        // 1. 设备与在线连接
+        summary.revoked_devices = self
+            .device_manager_db
+            .revoke_all_devices(user_id, KickReason::AccountDeleted.to_string())
+            .await?;
+        let _ = self
+            .connection_manager
+            .disconnect_other_devices(user_id, "__none__")
+            .await;
+
+        // 2. 群：先处理拥有的群，再退出所在的全部群
+        let mut dissolved = BTreeSet::new();
+        for group_id in self.repository.list_owned_groups(user_id).await? {
+            match self
+                .repository
+                .pick_group_successor(group_id, user_id)
+                .await?
+            {
+                Some(successor) => {
+                    self.channel_service
+                        .transfer_group_owner(group_id, user_id, successor)
+                        .await?;
+                    summary.transferred_groups += 1;
+                }
+                None => {
+                    self.channel_service.dissolve_group_admin(group_id).await?;
+                    dissolved.insert(group_id);
+                    summary.dissolved_groups += 1;
+                }
+            }
+        }
+        for group_id in self.repository.list_joined_groups(user_id).await? {
+            if dissolved.contains(&group_id) {
+                continue;
+            }
+            // leave_channel 只处理已加载到内存的会话
+            let channel = self.channel_service.get_channel(&group_id).await?;
+            let members: Vec<u64> = channel.members.keys().copied().collect();
+            if self
+                .channel_service
+                .leave_channel(group_id, user_id)
+                .await?
+            {
+                summary.left_groups += 1;
+                let publisher = EntityInvalidationPublisher::new(self.connection_manager.clone());
+                if let Err(error) = publisher
+                    .publish_group_projection_change(members, group_id, EntityMutationHint::Delete)
+                    .await
+                {
+                    warn!(group_id, user_id, %error, "group member invalidation failed");
+                }
+            }
+        }
+
+        // 3. 已发送消息
+        if self.message_policy == DeletedMessagePolicy::Redact {
+            self.redact_messages(user_id, &mut summary).await?;
+        }
+
+        // 4. 文件与导出归档：仍被消息引用的附件保留（keep 策略下属于他人可见的历史）
+        for file_id in self.repository.list_uploaded_file_ids(user_id).await? {
+            match self.file_service.release_unreferenced_file(file_id).await {
+                Ok(true) => summary.released_files += 1,
+                Ok(false) => {}
+                Err(e) => warn!("⚠️ 释放注销用户文件失败: file_id={}, error={}", file_id, e),
+            }
+        }
+        self.data_export_service.purge_user_exports(user_id).await?;
+
+        // 5. 匿名化用户行、删除个人数据
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        self.repository
+            .anonymize_user(user_id, DELETED_ACCOUNT_DISPLAY_NAME, now_ms)
+            .await?;
+        self.repository.mark_completed(user_id, now_ms).await?;
+        self.refresh_profile_cache(user_id).await;
+
+        let publisher = EntityInvalidationPublisher::new(self.connection_manager.clone());
+        if let Err(error) = publisher
+            .publish_friend_change(friend_ids, user_id, EntityMutationHint::Delete)
+            .await
+        {
+            warn!(user_id, %error, "deleted account friend invalidation failed");
+        }
+        if let Err(error) = publisher
+            .publish_to_users(
+                recipients,
+                vec![EntityInvalidation {
+                    entity_type: "user".to_string(),
+                    entity_id: Some(user_id.to_string()),
+                    scope: Some(user_id.to_string()),
+                    target_version: 0,
+                    mutation_hint: EntityMutationHint::Upsert,
+                }],
+            )
+            .await
+        {
+            warn!(user_id, %error, "deleted account profile invalidation failed");
+        }
+
+        Ok(summary)
+    }
+
+    async fn redact_messages(
+        &self,
+        user_id: u64,
+        summary: &mut AccountDeletionSummary,
+    ) -> Result<()> {
+        loop {
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let batch = self
+                .repository
+                .redact_messages_batch(user_id, now_ms, REDACT_BATCH_SIZE)
+                .await?;
+            let count = batch.message_count();
+            if count == 0 {
+                break;
+            }
+            summary.redacted_messages += count as u64;
+
+            for channel in &batch.channels {
+                let _ = self
+                    .message_history_service
+                    .clear_channel_messages(&channel.channel_id)
+                    .await;
+                let wire_type = ChannelType::from_i16(channel.channel_type).to_wire_u8();
+                match get_global_sync_service() {
+                    Some(sync_service) => {
+                        if let Err(e) = sync_service
+                            .append_messages_redacted_commit(
+                                channel.channel_id,
+                                wire_type,
+                                &channel.message_ids,
+                                now_ms,
+                            )
+                            .await
+                        {
+                            warn!(
+                                "⚠️ 写入 message.redacted commit 失败: channel_id={}, error={}",
+                                channel.channel_id, e
+                            );
+                        }
+                    }
+                    None => warn!("⚠️ SyncService 未初始化，跳过 message.redacted commit"),
+                }
+            }
+
+            for file_id in batch.released_file_ids {
+                match self.file_service.release_unreferenced_file(file_id).await {
+                    Ok(true) => summary.released_files += 1,
+                    Ok(false) => {}
+                    Err(e) => warn!("⚠️ 释放注销用户附件失败: file_id={}, error={}", file_id, e),
+                }
+            }
+
+            if (count as i64) < REDACT_BATCH_SIZE {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 用匿名化后的用户行覆盖资料缓存，避免会话里继续显示旧昵称 / 头像
+    async fn refresh_profile_cache(&self, user_id: u64) {
+        match self.user_repository.find_by_id(user_id).await {
+            Ok(Some(user)) => {
+                let profile = crate::rpc::helpers::user_to_cached_profile(&user);
+                if let Err(e) = self.cache_manager.set_user_profile(user_id, profile).await {
+                    warn!(
+                        "⚠️ 刷新注销用户资料缓存失败: user_id={}, error={}",
+                        user_id, e
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("⚠️ 读取注销用户失败: user_id={}, error={}", user_id, e),
+        }
+    }
+}
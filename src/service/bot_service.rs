@@ -0,0 +1,367 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bot 平台服务
+//!
+//! Bot 是 `user_type = 2` 的普通账号。本服务补齐对外 Bot API 需要的部分：
+//!
+//! - **令牌**：管理员为 Bot 签发 `<bot_user_id>:<随机串>` 形式的令牌，库里只存摘要；
+//!   Bot 以 `Authorization: Bot <token>` 调 `/api/bot/*`
+//! - **命令**：Bot 注册斜杠命令列表，客户端经 RPC 拉取
+//! - **更新**：发给 Bot 的消息（私聊 / 群里 @Bot）和内联按钮回调进入 `getUpdates` 队列；
+//!   按钮回调另外以 `bot.callback_queried` server event 通知下游 application
+//!
+//! 更新队列的语义与 Telegram `getUpdates` 一致：`offset` 之前的更新视为已确认并删除，
+//! 未确认的保留 [`UPDATE_RETENTION_MS`]。长轮询在本节点用 `Notify` 即时唤醒，
+//! 其它节点写入的更新最多延迟一个 [`LONG_POLL_RECHECK`] 被看到。
+
+use crate::error::{Result, ServerError};
+use crate::model::{normalize_bot_commands, BotCommand, User, UserStatus};
+use crate::repository::{
+    hash_bot_token, BotPlatformRepository, BotTokenRecord, BotUpdateRecord, UserRepository,
+};
+use crate::server_event::{BotCallbackQueriedPayload, ServerEvent, ServerEventClient};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Bot 账号的 user_type（与 `model::user` 的 "2=机器人" 一致）
+pub const USER_TYPE_BOT: i16 = 2;
+
+/// 更新类型：收到消息
+pub const BOT_UPDATE_MESSAGE: &str = "message";
+/// 更新类型：内联按钮回调
+pub const BOT_UPDATE_CALLBACK_QUERY: &str = "callback_query";
+
+/// 未确认的更新保留 24 小时
+pub const UPDATE_RETENTION_MS: i64 = 24 * 3600 * 1000;
+/// 单次 getUpdates 最多返回条数 / 最长等待
+pub const MAX_UPDATES_PER_POLL: u32 = 100;
+pub const MAX_LONG_POLL_SECS: u64 = 50;
+/// 长轮询期间回查 DB 的间隔（覆盖其它节点写入的更新）
+const LONG_POLL_RECHECK: Duration = Duration::from_secs(2);
+
+/// 令牌随机部分的字节数（hex 后 48 字符）
+const TOKEN_RANDOM_BYTES: usize = 24;
+/// 列表里展示的令牌前缀长度
+const TOKEN_PREFIX_LEN: usize = 12;
+
+/// 生成 Bot 令牌：`<bot_user_id>:<hex>`
+pub fn generate_bot_token(bot_user_id: u64) -> String {
+    let random: [u8; TOKEN_RANDOM_BYTES] = rand::random();
+    format!("{}:{}", bot_user_id, hex::encode(random))
+}
+
+/// 解析 `Authorization: Bot <token>`
+pub fn parse_bot_authorization(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("bot") {
+        return None;
+    }
+    let token = token.trim();
+    (!token.is_empty()).then_some(token)
+}
+
+/// Bot 平台服务
+pub struct BotService {
+    repo: Arc<BotPlatformRepository>,
+    user_repository: Arc<UserRepository>,
+    server_event_client: Option<Arc<ServerEventClient>>,
+    /// 每个 Bot 一个唤醒器，push_update 时叫醒本节点上正在长轮询的 getUpdates
+    notifiers: Mutex<HashMap<u64, Arc<Notify>>>,
+}
+
+impl BotService {
+    pub fn new(
+        repo: Arc<BotPlatformRepository>,
+        user_repository: Arc<UserRepository>,
+        server_event_client: Option<Arc<ServerEventClient>>,
+    ) -> Self {
+        Self {
+            repo,
+            user_repository,
+            server_event_client,
+            notifiers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 取 Bot 账号；不存在 / 不是 Bot 时报错
+    pub async fn get_bot(&self, bot_user_id: u64) -> Result<User> {
+        let user = self
+            .user_repository
+            .find_by_id(bot_user_id)
+            .await
+            .map_err(|e| ServerError::Database(format!("查询用户失败: {}", e)))?
+            .ok_or_else(|| ServerError::NotFound(format!("Bot {} 不存在", bot_user_id)))?;
+        if user.user_type != USER_TYPE_BOT {
+            return Err(ServerError::Validation(format!(
+                "用户 {} 不是 Bot",
+                bot_user_id
+            )));
+        }
+        Ok(user)
+    }
+
+    /// 是否为 Bot 账号（查询失败按否处理）
+    pub async fn is_bot(&self, user_id: u64) -> bool {
+        if crate::config::is_system_user(user_id) {
+            return false;
+        }
+        matches!(
+            self.user_repository.find_by_id(user_id).await,
+            Ok(Some(user)) if user.user_type == USER_TYPE_BOT
+        )
+    }
+
+    // ───────────────────────── 令牌 ─────────────────────────
+
+    /// 签发令牌；明文只在这里返回一次
+    pub async fn issue_token(
+        &self,
+        bot_user_id: u64,
+        label: &str,
+    ) -> Result<(String, BotTokenRecord)> {
+        self.get_bot(bot_user_id).await?;
+        let token = generate_bot_token(bot_user_id);
+        let prefix: String = token.chars().take(TOKEN_PREFIX_LEN).collect();
+        let record = self
+            .repo
+            .create_token(bot_user_id, &hash_bot_token(&token), &prefix, label.trim())
+            .await?;
+        info!(
+            "🔑 签发 Bot 令牌: bot_user_id={}, token_id={}",
+            bot_user_id, record.token_id
+        );
+        Ok((token, record))
+    }
+
+    pub async fn list_tokens(&self, bot_user_id: u64) -> Result<Vec<BotTokenRecord>> {
+        self.repo.list_tokens(bot_user_id).await
+    }
+
+    pub async fn revoke_token(&self, bot_user_id: u64, token_id: u64) -> Result<()> {
+        if !self.repo.revoke_token(bot_user_id, token_id).await? {
+            return Err(ServerError::NotFound(format!(
+                "Bot {} 没有有效的令牌 {}",
+                bot_user_id, token_id
+            )));
+        }
+        info!(
+            "🔒 吊销 Bot 令牌: bot_user_id={}, token_id={}",
+            bot_user_id, token_id
+        );
+        Ok(())
+    }
+
+    /// 校验令牌，返回 Bot user_id。Bot 账号被停用后令牌随之失效。
+    pub async fn authenticate(&self, token: &str) -> Result<u64> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let bot_user_id = self
+            .repo
+            .authenticate(&hash_bot_token(token), now)
+            .await?
+            .ok_or_else(|| ServerError::Unauthorized("无效的 Bot 令牌".to_string()))?;
+        let bot = self
+            .get_bot(bot_user_id)
+            .await
+            .map_err(|_| ServerError::Unauthorized("无效的 Bot 令牌".to_string()))?;
+        if !matches!(bot.status, UserStatus::Active) {
+            return Err(ServerError::Forbidden(format!(
+                "Bot {} 已停用",
+                bot_user_id
+            )));
+        }
+        Ok(bot_user_id)
+    }
+
+    // ───────────────────────── 命令 ─────────────────────────
+
+    pub async fn get_commands(&self, bot_user_id: u64) -> Result<Vec<BotCommand>> {
+        self.repo.list_commands(bot_user_id).await
+    }
+
+    /// 整体替换命令列表，返回归一化后的命令
+    pub async fn set_commands(
+        &self,
+        bot_user_id: u64,
+        commands: &[BotCommand],
+    ) -> Result<Vec<BotCommand>> {
+        let commands = normalize_bot_commands(commands).map_err(ServerError::Validation)?;
+        self.repo.replace_commands(bot_user_id, &commands).await?;
+        Ok(commands)
+    }
+
+    // ───────────────────────── 更新队列 ─────────────────────────
+
+    fn notifier(&self, bot_user_id: u64) -> Arc<Notify> {
+        let mut notifiers = self.notifiers.lock().unwrap_or_else(|e| e.into_inner());
+        notifiers
+            .entry(bot_user_id)
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone()
+    }
+
+    pub async fn push_update(
+        &self,
+        bot_user_id: u64,
+        update_type: &str,
+        payload: &Value,
+    ) -> Result<i64> {
+        let update_id = self
+            .repo
+            .push_update(bot_user_id, update_type, payload)
+            .await?;
+        self.notifier(bot_user_id).notify_waiters();
+        Ok(update_id)
+    }
+
+    /// getUpdates：确认 `offset` 之前的更新，返回之后的更新；没有时最多等待 `timeout_secs`
+    pub async fn get_updates(
+        &self,
+        bot_user_id: u64,
+        offset: Option<i64>,
+        limit: u32,
+        timeout_secs: u64,
+    ) -> Result<Vec<BotUpdateRecord>> {
+        let offset = offset.unwrap_or(0);
+        if offset > 0 {
+            self.repo.ack_updates(bot_user_id, offset).await?;
+        }
+        let limit = limit.clamp(1, MAX_UPDATES_PER_POLL);
+        let deadline =
+            tokio::time::Instant::now() + Duration::from_secs(timeout_secs.min(MAX_LONG_POLL_SECS));
+        let notifier = self.notifier(bot_user_id);
+        loop {
+            // 先登记等待再查询，避免查询与 notify 之间的更新被漏掉
+            let notified = notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let updates = self.repo.fetch_updates(bot_user_id, offset, limit).await?;
+            let now = tokio::time::Instant::now();
+            if !updates.is_empty() || now >= deadline {
+                return Ok(updates);
+            }
+            let wait = (deadline - now).min(LONG_POLL_RECHECK);
+            let _ = tokio::time::timeout(wait, notified).await;
+        }
+    }
+
+    /// 用户消息投给 Bot：`candidates` 中的 Bot 各收到一条 `message` 更新。
+    /// 发送方本身是 Bot 时不投递，避免 Bot 之间互相触发。
+    pub async fn dispatch_message(&self, sender_id: u64, candidates: &[u64], message: &Value) {
+        if candidates.is_empty() || self.is_bot(sender_id).await {
+            return;
+        }
+        for &bot_user_id in candidates {
+            if bot_user_id == sender_id || !self.is_bot(bot_user_id).await {
+                continue;
+            }
+            if let Err(e) = self
+                .push_update(bot_user_id, BOT_UPDATE_MESSAGE, message)
+                .await
+            {
+                warn!(
+                    "⚠️ 写入 Bot 消息更新失败: bot_user_id={}, {}",
+                    bot_user_id, e
+                );
+            }
+        }
+    }
+
+    /// 记录一次按钮回调（调用方已校验消息 / 按钮 / 成员资格），返回 callback_id
+    pub async fn record_callback(
+        &self,
+        bot_user_id: u64,
+        user_id: u64,
+        channel_id: u64,
+        message_id: u64,
+        data: &str,
+    ) -> Result<String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let payload = BotCallbackQueriedPayload {
+            callback_id: format!("cbq_{}", Uuid::new_v4().simple()),
+            bot_user_id,
+            user_id,
+            channel_id,
+            message_id,
+            data: data.to_string(),
+            occurred_at: now,
+        };
+        let value = json!(payload);
+        self.push_update(bot_user_id, BOT_UPDATE_CALLBACK_QUERY, &value)
+            .await?;
+
+        if let Some(client) = self.server_event_client.clone() {
+            match ServerEvent::bot_callback_queried(&payload, now) {
+                Ok(event) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = client.send(&event).await {
+                            warn!(
+                                target: "bot_callback",
+                                "server-event 通知失败 (bot.callback_queried): {}",
+                                e
+                            );
+                        }
+                    });
+                }
+                Err(e) => warn!(
+                    target: "bot_callback",
+                    "server-event payload 序列化失败 (bot.callback_queried): {}",
+                    e
+                ),
+            }
+        }
+        Ok(payload.callback_id)
+    }
+
+    /// 清理超过保留期仍未确认的更新
+    pub async fn purge_expired_updates(&self) -> Result<u64> {
+        let cutoff = chrono::Utc::now().timestamp_millis() - UPDATE_RETENTION_MS;
+        self.repo.purge_updates(cutoff).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bot_token_embeds_bot_id_and_hashes_stably() {
+        let token = generate_bot_token(100000123);
+        assert!(token.starts_with("100000123:"));
+        assert_eq!(token.len(), "100000123:".len() + TOKEN_RANDOM_BYTES * 2);
+        assert_ne!(token, generate_bot_token(100000123));
+        assert_eq!(
+            hash_bot_token(&token),
+            hash_bot_token(&format!(" {} ", token))
+        );
+    }
+
+    #[test]
+    fn authorization_header_requires_bot_scheme() {
+        assert_eq!(parse_bot_authorization("Bot 1:abc"), Some("1:abc"));
+        assert_eq!(parse_bot_authorization("bot  1:abc "), Some("1:abc"));
+        assert_eq!(parse_bot_authorization("Bearer 1:abc"), None);
+        assert_eq!(parse_bot_authorization("Bot "), None);
+        assert_eq!(parse_bot_authorization("1:abc"), None);
+    }
+}
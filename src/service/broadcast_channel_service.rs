@@ -0,0 +1,369 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! BroadcastChannelService — 一对多广播频道（订阅号 / 公告 / 资讯流）
+//!
+//! - 频道主/管理员发布，订阅者只读；
+//! - 发布走 [`MessageService::send_broadcast_message`]：与普通消息同一条 pts / commit_log /
+//!   dispatch outbox 链路，订阅者按 `privchat_channel_participants` 快照投递；
+//! - 标题 / 紧急 / 过期等广播属性存 `privchat_channel_messages`，与消息同事务写入，
+//!   订阅者的已读 / 忽略状态存 `privchat_channel_message_status`。
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use privchat_protocol::ContentMessageType;
+use serde_json::Value;
+use tracing::info;
+
+use crate::error::{Result, ServerError};
+use crate::model::channel::{ChannelType, MemberRole};
+use crate::model::message::ChannelMessage;
+use crate::repository::{
+    BroadcastChannelRecord, BroadcastChannelRepository, BroadcastFeedItem, BroadcastMessageAttrs,
+};
+use crate::service::{ChannelService, MessageService, ServerSendMessageRequest};
+
+/// 频道名最大长度（字符数），与 `privchat_broadcast_channels.name` 列宽一致。
+pub const MAX_CHANNEL_NAME_CHARS: usize = 128;
+/// 标题最大长度（字符数），与 `privchat_channel_messages.title` 列宽一致。
+pub const MAX_TITLE_CHARS: usize = 256;
+/// 内容流单页上限。
+pub const MAX_LIST_LIMIT: u32 = 100;
+
+/// 发布请求
+pub struct PublishChannelMessageRequest {
+    pub channel_id: u64,
+    pub publisher_id: u64,
+    pub title: Option<String>,
+    pub content: String,
+    pub message_type: ContentMessageType,
+    pub metadata: Value,
+    pub is_urgent: bool,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// 客户端幂等号；非 0 时同一发布者重复提交只落一条。
+    pub local_message_id: u64,
+}
+
+/// 发布结果
+pub struct PublishedChannelMessage {
+    pub message: ChannelMessage,
+    pub pts: u64,
+    /// `false` = 幂等命中，返回的是既有那条。
+    pub inserted: bool,
+}
+
+pub struct BroadcastChannelService {
+    repository: Arc<BroadcastChannelRepository>,
+    channel_service: Arc<ChannelService>,
+    message_service: Arc<MessageService>,
+}
+
+/// 能否发布：只有频道主和管理员。
+pub fn can_publish(role: Option<MemberRole>) -> bool {
+    matches!(role, Some(MemberRole::Owner) | Some(MemberRole::Admin))
+}
+
+/// 频道名校验（trim 后非空、不超长），返回规范化后的名字。
+pub fn normalize_channel_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ServerError::Validation("频道名不能为空".to_string()));
+    }
+    if name.chars().count() > MAX_CHANNEL_NAME_CHARS {
+        return Err(ServerError::Validation(format!(
+            "频道名不能超过 {} 个字符",
+            MAX_CHANNEL_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+impl BroadcastChannelService {
+    pub fn new(
+        repository: Arc<BroadcastChannelRepository>,
+        channel_service: Arc<ChannelService>,
+        message_service: Arc<MessageService>,
+    ) -> Self {
+        Self {
+            repository,
+            channel_service,
+            message_service,
+        }
+    }
+
+    pub async fn create_channel(
+        &self,
+        owner_id: u64,
+        name: &str,
+        description: Option<&str>,
+        avatar_url: Option<&str>,
+    ) -> Result<BroadcastChannelRecord> {
+        let name = normalize_channel_name(name)?;
+        let record = self
+            .repository
+            .create_channel(owner_id, &name, description, avatar_url)
+            .await?;
+        info!(
+            "✅ BroadcastChannelService: 频道创建完成 channel_id={}, owner_id={}",
+            record.channel_id, owner_id
+        );
+        Ok(record)
+    }
+
+    pub async fn get_channel(&self, channel_id: u64) -> Result<BroadcastChannelRecord> {
+        self.repository
+            .find_channel(channel_id)
+            .await?
+            .ok_or_else(|| ServerError::ChannelNotFound(format!("广播频道不存在: {}", channel_id)))
+    }
+
+    pub async fn member_role(&self, channel_id: u64, user_id: u64) -> Result<Option<MemberRole>> {
+        self.repository.member_role(channel_id, user_id).await
+    }
+
+    /// 订阅。返回 `false` = 已经在频道内。
+    pub async fn subscribe(&self, channel_id: u64, user_id: u64) -> Result<bool> {
+        let subscribed = self.repository.subscribe(channel_id, user_id).await?;
+        if subscribed {
+            self.channel_service.forget_channel(channel_id).await;
+        }
+        Ok(subscribed)
+    }
+
+    /// 退订。频道主不能退订自己的频道；管理员需先被撤销管理员身份。
+    pub async fn unsubscribe(&self, channel_id: u64, user_id: u64) -> Result<bool> {
+        match self.repository.member_role(channel_id, user_id).await? {
+            Some(MemberRole::Owner) => {
+                return Err(ServerError::Validation(
+                    "频道主不能退订自己的频道".to_string(),
+                ))
+            }
+            Some(MemberRole::Admin) => {
+                return Err(ServerError::Validation(
+                    "管理员需先撤销管理员身份再退订".to_string(),
+                ))
+            }
+            _ => {}
+        }
+        let left = self.repository.unsubscribe(channel_id, user_id).await?;
+        if left {
+            self.channel_service.forget_channel(channel_id).await;
+        }
+        Ok(left)
+    }
+
+    /// 设置/撤销发布管理员，仅频道主可操作。
+    pub async fn set_admin(
+        &self,
+        channel_id: u64,
+        operator_id: u64,
+        target_user_id: u64,
+        is_admin: bool,
+    ) -> Result<bool> {
+        let channel = self.get_channel(channel_id).await?;
+        if channel.owner_id as u64 != operator_id {
+            return Err(ServerError::PermissionDenied(
+                "只有频道主可以设置管理员".to_string(),
+            ));
+        }
+        if target_user_id == operator_id {
+            return Err(ServerError::Validation(
+                "不能修改频道主自己的角色".to_string(),
+            ));
+        }
+        let changed = self
+            .repository
+            .set_admin(channel_id, target_user_id, is_admin)
+            .await?;
+        if changed {
+            self.channel_service.forget_channel(channel_id).await;
+        }
+        Ok(changed)
+    }
+
+    /// 发布一条频道消息。
+    ///
+    /// 广播属性与消息同事务写入：要么两者都在，要么整条发布失败，
+    /// 不会出现订阅者看得到、却没有标题/紧急/过期也记不了已读的消息。
+    /// 带 `local_message_id` 的重试幂等命中既有消息时补写属性（早于本事务化的发布可能缺属性）。
+    pub async fn publish(
+        &self,
+        req: PublishChannelMessageRequest,
+    ) -> Result<PublishedChannelMessage> {
+        let role = self
+            .repository
+            .member_role(req.channel_id, req.publisher_id)
+            .await?;
+        if !can_publish(role) {
+            return Err(ServerError::PermissionDenied(
+                "只有频道主或管理员可以发布内容".to_string(),
+            ));
+        }
+        if req.content.trim().is_empty() {
+            return Err(ServerError::Validation("发布内容不能为空".to_string()));
+        }
+        if let Some(title) = req.title.as_deref() {
+            if title.chars().count() > MAX_TITLE_CHARS {
+                return Err(ServerError::Validation(format!(
+                    "标题不能超过 {} 个字符",
+                    MAX_TITLE_CHARS
+                )));
+            }
+        }
+        if let Some(expires_at) = req.expires_at {
+            if expires_at <= Utc::now() {
+                return Err(ServerError::Validation(
+                    "过期时间必须晚于当前时间".to_string(),
+                ));
+            }
+        }
+
+        let subscribers = self.repository.list_subscriber_ids(req.channel_id).await?;
+        let dedup_key = (req.local_message_id != 0).then(|| {
+            format!(
+                "channel_publish:{}:{}:{}",
+                req.channel_id, req.publisher_id, req.local_message_id
+            )
+        });
+        let sent = self
+            .message_service
+            .send_broadcast_message(
+                ServerSendMessageRequest {
+                    channel_id: req.channel_id,
+                    sender_id: req.publisher_id,
+                    content: req.content.clone(),
+                    message_type: req.message_type,
+                    metadata: req.metadata.clone(),
+                    channel_type: ChannelType::Room.to_wire_u8(),
+                    recipient_user_ids: subscribers,
+                    dedup_key,
+                    attachment_refs_override: None,
+                },
+                BroadcastMessageAttrs {
+                    title: req.title.clone(),
+                    is_urgent: req.is_urgent,
+                    expires_at: req.expires_at,
+                },
+            )
+            .await
+            .map_err(|e| ServerError::Internal(format!("发布频道消息失败: {}", e)))?;
+
+        let published_at =
+            DateTime::from_timestamp_millis(sent.created_at).unwrap_or_else(Utc::now);
+        let message = ChannelMessage {
+            message_id: sent.message_id,
+            channel_id: req.channel_id,
+            publisher_id: Some(req.publisher_id),
+            title: req.title,
+            content: req.content,
+            message_type: req.message_type,
+            metadata: req.metadata,
+            is_urgent: req.is_urgent,
+            expires_at: req.expires_at,
+            published_at,
+            updated_at: published_at,
+        };
+        if !sent.inserted {
+            self.repository.insert_message_attrs(&message).await?;
+        }
+
+        Ok(PublishedChannelMessage {
+            message,
+            pts: sent.pts,
+            inserted: sent.inserted,
+        })
+    }
+
+    /// 内容流分页。只有频道内成员（订阅者/管理员/频道主）可读。
+    pub async fn list_messages(
+        &self,
+        channel_id: u64,
+        viewer_id: u64,
+        before_message_id: Option<u64>,
+        limit: u32,
+        include_dismissed: bool,
+    ) -> Result<Vec<BroadcastFeedItem>> {
+        self.ensure_member(channel_id, viewer_id).await?;
+        self.repository
+            .list_messages(
+                channel_id,
+                viewer_id,
+                before_message_id,
+                limit.clamp(1, MAX_LIST_LIMIT),
+                include_dismissed,
+            )
+            .await
+    }
+
+    pub async fn mark_read(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        message_ids: &[u64],
+    ) -> Result<u64> {
+        self.ensure_member(channel_id, user_id).await?;
+        self.repository
+            .mark_read(channel_id, user_id, device_id, message_ids)
+            .await
+    }
+
+    pub async fn mark_dismissed(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        message_ids: &[u64],
+    ) -> Result<u64> {
+        self.ensure_member(channel_id, user_id).await?;
+        self.repository
+            .mark_dismissed(channel_id, user_id, device_id, message_ids)
+            .await
+    }
+
+    /// 非成员一律按「频道不存在」处理，不泄露频道是否存在（同 `ensure_channel_visible`）。
+    async fn ensure_member(&self, channel_id: u64, user_id: u64) -> Result<MemberRole> {
+        self.repository
+            .member_role(channel_id, user_id)
+            .await?
+            .ok_or_else(|| ServerError::ChannelNotFound(format!("广播频道不存在: {}", channel_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_owner_and_admin_can_publish() {
+        assert!(can_publish(Some(MemberRole::Owner)));
+        assert!(can_publish(Some(MemberRole::Admin)));
+        assert!(!can_publish(Some(MemberRole::Member)));
+        assert!(!can_publish(None));
+    }
+
+    #[test]
+    fn channel_name_is_trimmed_and_bounded() {
+        assert_eq!(normalize_channel_name("  公告  ").unwrap(), "公告");
+        assert!(normalize_channel_name("   ").is_err());
+        let at_limit = "频".repeat(MAX_CHANNEL_NAME_CHARS);
+        assert!(normalize_channel_name(&at_limit).is_ok());
+        let too_long = "频".repeat(MAX_CHANNEL_NAME_CHARS + 1);
+        assert!(normalize_channel_name(&too_long).is_err());
+    }
+}
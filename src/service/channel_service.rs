@@ -24,8 +24,9 @@ use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
 use crate::error::{Result, ServerError};
+use crate::infra::read_pool;
 use crate::model::channel::{
-    Channel, ChannelListResponse, ChannelResponse, ChannelStatus, ChannelType,
+    Channel, ChannelListResponse, ChannelNotifyLevel, ChannelResponse, ChannelStatus, ChannelType,
     CreateChannelRequest, MemberRole,
 };
 use crate::repository::{ChannelRepository, PgChannelRepository};
@@ -152,6 +153,21 @@ fn db_role_to_wire(
     }
 }
 
+/// `privchat_group_members.permissions` → 群主定制的权限集。
+///
+/// `'{}'`（列默认值）/ NULL / 解析失败都视为「未定制」，由角色默认兜底——
+/// 一份坏 JSON 不应让管理员丢掉全部权限，也不应给任何人提权。
+fn parse_custom_permissions(
+    value: Option<serde_json::Value>,
+) -> Option<crate::model::channel::MemberPermissions> {
+    match value {
+        Some(serde_json::Value::Object(map)) if !map.is_empty() => {
+            serde_json::from_value(serde_json::Value::Object(map)).ok()
+        }
+        _ => None,
+    }
+}
+
 impl ChannelService {
     /// 创建新的会话服务
     pub fn new(config: ChannelServiceConfig, channel_repository: Arc<PgChannelRepository>) -> Self {
@@ -247,6 +263,7 @@ impl ChannelService {
                 channel_id,
                 is_pinned,
                 is_muted,
+                notify_level,
                 updated_at
             )
             VALUES (
@@ -254,11 +271,18 @@ impl ChannelService {
                 $2,
                 COALESCE($3, false),
                 COALESCE($4, false),
+                CASE WHEN COALESCE($4, false) THEN 2 ELSE 0 END,
                 $5
             )
             ON CONFLICT (user_id, channel_id) DO UPDATE SET
                 is_pinned = COALESCE($3, privchat_user_channels.is_pinned),
                 is_muted = COALESCE($4, privchat_user_channels.is_muted),
+                -- 静音开关与通知级别同步：静音 = 不通知，取消静音 = 全部通知
+                notify_level = CASE
+                    WHEN $4 IS NULL THEN privchat_user_channels.notify_level
+                    WHEN $4 THEN 2
+                    ELSE 0
+                END,
                 updated_at = EXCLUDED.updated_at
             "#,
         )
@@ -415,7 +439,7 @@ impl ChannelService {
             page_size as i64,
             offset as i64,
         )
-        .fetch_all(self.pool())
+        .fetch_all(read_pool(self.pool()))
         .await
         .map_err(|e| ServerError::Database(format!("查询群组列表失败: {}", e)))?;
 
@@ -426,7 +450,7 @@ impl ChannelService {
             WHERE channel_type = 1
             "#
         )
-        .fetch_one(self.pool())
+        .fetch_one(read_pool(self.pool()))
         .await
         .map_err(|e| ServerError::Database(format!("统计群组数失败: {}", e)))?;
 
@@ -1259,6 +1283,11 @@ impl ChannelService {
         tx.commit()
             .await
             .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
+        Self::emit_group_member_webhook(
+            crate::service::webhook_service::WEBHOOK_EVENT_GROUP_MEMBER_LEFT,
+            group_id,
+            user_id,
+        );
 
         info!(
             "✅ 群组成员已移除: group_id={}, user_id={}",
@@ -1309,7 +1338,14 @@ impl ChannelService {
             .map_err(|e| ServerError::Database(format!("提交事务失败: {}", e)))?;
 
         // 同步内存 cache：忽略 join_channel 的 DB 写（upsert 幂等，重写一次代价低且不影响一致性）。
-        let _ = self.join_channel(group_id, user_id, None).await;
+        // join_channel 成功时已发过入群 Webhook；channel 不在 cache 时由这里补发。
+        if !matches!(self.join_channel(group_id, user_id, None).await, Ok(true)) {
+            Self::emit_group_member_webhook(
+                crate::service::webhook_service::WEBHOOK_EVENT_GROUP_MEMBER_JOINED,
+                group_id,
+                user_id,
+            );
+        }
 
         info!(
             "✅ 群组成员已添加: group_id={}, user_id={}",
@@ -1375,10 +1411,14 @@ impl ChannelService {
             ));
         }
 
+        // 角色真正变化时作废旧的定制权限集与头衔：降级后再升回管理员从角色默认重新开始，
+        // 而不是悄悄恢复上一任期的授权。
         sqlx::query(
             r#"
             UPDATE privchat_group_members
-            SET role = $3, updated_at = $4
+            SET role = $3, updated_at = $4,
+                permissions = CASE WHEN role = $3 THEN permissions ELSE '{}'::jsonb END,
+                title = CASE WHEN role = $3 THEN title ELSE NULL END
             WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL
             "#,
         )
@@ -1404,6 +1444,96 @@ impl ChannelService {
         Ok(())
     }
 
+    /// 设置管理员的定制权限集与头衔（群主操作，鉴权由调用方完成）。
+    ///
+    /// `permissions = None` 恢复角色默认（落库为 `'{}'`）；`title = None` 清除头衔。
+    /// 只对当前角色为 Admin 的活跃成员生效，否则 → Validation；不存在 → NotFound。
+    /// 返回实际生效的权限（`can_manage_permissions` 恒为 false）。
+    pub async fn set_admin_permissions(
+        &self,
+        group_id: u64,
+        user_id: u64,
+        permissions: Option<crate::model::channel::MemberPermissions>,
+        title: Option<String>,
+    ) -> Result<crate::model::channel::MemberPermissions> {
+        use crate::model::channel::MemberPermissions;
+
+        let effective = MemberPermissions::effective(MemberRole::Admin, permissions.as_ref());
+        let stored = match permissions {
+            Some(_) => serde_json::to_value(&effective)
+                .map_err(|e| ServerError::Internal(format!("序列化权限失败: {}", e)))?,
+            None => serde_json::Value::Object(serde_json::Map::new()),
+        };
+
+        let current_role: Option<i16> = sqlx::query_scalar(
+            r#"
+            SELECT role FROM privchat_group_members
+            WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL
+            "#,
+        )
+        .bind(group_id as i64)
+        .bind(user_id as i64)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询成员当前角色失败: {}", e)))?;
+
+        match current_role.map(MemberRole::from_i16) {
+            None => {
+                return Err(ServerError::NotFound(format!(
+                    "群组 {} 中不存在活跃成员 {}",
+                    group_id, user_id
+                )))
+            }
+            Some(MemberRole::Admin) => {}
+            Some(_) => {
+                return Err(ServerError::Validation(
+                    "只能为管理员设置权限与头衔".to_string(),
+                ))
+            }
+        }
+
+        // 条件 UPDATE：与并发的降级竞争时，以 role 仍为 Admin 为准
+        let updated = sqlx::query(
+            r#"
+            UPDATE privchat_group_members
+            SET permissions = $3, title = $4, updated_at = $5
+            WHERE group_id = $1 AND user_id = $2 AND left_at IS NULL AND role = $6
+            "#,
+        )
+        .bind(group_id as i64)
+        .bind(user_id as i64)
+        .bind(&stored)
+        .bind(title.as_deref())
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(MemberRole::Admin.to_i16())
+        .execute(self.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("更新管理员权限失败: {}", e)))?;
+
+        if updated.rows_affected() == 0 {
+            return Err(ServerError::Validation(
+                "只能为管理员设置权限与头衔".to_string(),
+            ));
+        }
+
+        // 内存缓存（如果 channel 已 load）
+        let mut channels = self.channels.write().await;
+        if let Some(member) = channels
+            .get_mut(&group_id)
+            .and_then(|channel| channel.members.get_mut(&user_id))
+        {
+            member.permissions = effective.clone();
+            member.title = title.clone();
+        }
+        drop(channels);
+
+        info!(
+            "✅ 管理员权限已更新: group_id={}, user_id={}, title={:?}",
+            group_id, user_id, title
+        );
+        Ok(effective)
+    }
+
     /// 转让群主：**一个事务里**把旧群主降为普通成员、把新群主升为 Owner，
     /// 并同步 `privchat_groups.owner_id`。
     ///
@@ -2159,8 +2289,12 @@ impl ChannelService {
                     // 生产上两个最大的群（807 人 / 622 人）正是这么废掉的。
                     //
                     // 以真源覆盖镜像，drift 在每次 hydrate 时自愈。
-                    match sqlx::query_as::<_, (i64, i16)>(
-                        "SELECT user_id, role FROM privchat_group_members \
+                    // 同一查询带出群主给管理员定制的权限集与头衔（'{}' = 角色默认）。
+                    match sqlx::query_as::<
+                        _,
+                        (i64, i16, Option<serde_json::Value>, Option<String>),
+                    >(
+                        "SELECT user_id, role, permissions, title FROM privchat_group_members \
                          WHERE group_id = $1 AND left_at IS NULL",
                     )
                     .bind(group_id as i64)
@@ -2169,11 +2303,11 @@ impl ChannelService {
                     {
                         Ok(rows) => {
                             let truth = rows
-                                .into_iter()
-                                .map(|(uid, role)| {
+                                .iter()
+                                .map(|(uid, role, _, _)| {
                                     (
-                                        uid as u64,
-                                        crate::model::channel::MemberRole::from_i16(role),
+                                        *uid as u64,
+                                        crate::model::channel::MemberRole::from_i16(*role),
                                     )
                                 })
                                 .collect::<Vec<_>>();
@@ -2187,6 +2321,16 @@ impl ChannelService {
                                     group_id, repaired
                                 );
                             }
+                            let grants = rows
+                                .into_iter()
+                                .map(|(uid, _, permissions, title)| {
+                                    (uid as u64, parse_custom_permissions(permissions), title)
+                                })
+                                .collect::<Vec<_>>();
+                            crate::model::channel::apply_group_admin_grants(
+                                &mut conv_with_members.members,
+                                &grants,
+                            );
                         }
                         Err(e) => {
                             warn!(
@@ -2382,7 +2526,7 @@ impl ChannelService {
         .bind(user_id as i64)
         .bind(since_v as i64)
         .bind(limit + 1)
-        .fetch_all(self.pool())
+        .fetch_all(read_pool(self.pool()))
         .await
         .map_err(|e| ServerError::Database(format!("查询群组同步分页失败: {}", e)))?;
 
@@ -2472,6 +2616,14 @@ impl ChannelService {
         (evicted_channels, evicted_previews)
     }
 
+    /// 丢掉单个频道的内存快照，下次 get_channel_opt 从 DB 回源。
+    ///
+    /// 给绕过 ChannelService 直接改 participants 的路径用（广播频道订阅/退订），
+    /// 否则缓存里的 members 会一直停在旧值，`ensure_channel_visible` 跟着判错。
+    pub async fn forget_channel(&self, channel_id: u64) {
+        self.channels.write().await.remove(&channel_id);
+    }
+
     pub async fn sync_entities_page_for_channels(
         &self,
         user_id: u64,
@@ -2592,7 +2744,7 @@ impl ChannelService {
         .bind(since)
         .bind(after_id)
         .bind(limit as i64 + 1)
-        .fetch_all(self.pool())
+        .fetch_all(read_pool(self.pool()))
         .await
         .map_err(|e| ServerError::Database(format!("查询频道同步分页失败: {}", e)))?;
 
@@ -2770,7 +2922,7 @@ impl ChannelService {
         .bind(group_id as i64)
         .bind(since_v as i64)
         .bind(page_limit + 1)
-        .fetch_all(self.pool())
+        .fetch_all(read_pool(self.pool()))
         .await
         .map_err(|e| ServerError::Database(format!("查询群成员同步分页失败: {}", e)))?;
 
@@ -2879,6 +3031,11 @@ impl ChannelService {
             tx.commit()
                 .await
                 .map_err(|e| ServerError::Database(format!("提交加群事务失败: {}", e)))?;
+            Self::emit_group_member_webhook(
+                crate::service::webhook_service::WEBHOOK_EVENT_GROUP_MEMBER_JOINED,
+                channel_id,
+                user_id,
+            );
         }
 
         self.ensure_user_channel_rows(channel_id, &[user_id])
@@ -2945,6 +3102,11 @@ impl ChannelService {
         tx.commit()
             .await
             .map_err(|e| ServerError::Database(format!("提交审批事务失败: {}", e)))?;
+        Self::emit_group_member_webhook(
+            crate::service::webhook_service::WEBHOOK_EVENT_GROUP_MEMBER_JOINED,
+            group_id,
+            user_id,
+        );
 
         // 提交后同步内存缓存（DB 已是真相；缓存失败不破坏一致性，reconnect/sync 会重建）。
         {
@@ -2969,6 +3131,18 @@ impl ChannelService {
         Ok(true)
     }
 
+    /// 群成员变更的出站 Webhook（只在 DB 事务提交后调用）
+    fn emit_group_member_webhook(event_type: &'static str, group_id: u64, user_id: u64) {
+        crate::service::emit_webhook(
+            event_type,
+            serde_json::json!({
+                "group_id": group_id,
+                "user_id": user_id,
+                "occurred_at": Utc::now().timestamp_millis(),
+            }),
+        );
+    }
+
     /// 离开会话
     pub async fn leave_channel(&self, channel_id: u64, user_id: u64) -> Result<bool> {
         let mut channels = self.channels.write().await;
@@ -3003,6 +3177,13 @@ impl ChannelService {
             tx.commit()
                 .await
                 .map_err(|e| ServerError::Database(format!("提交退群事务失败: {}", e)))?;
+            if actually_left {
+                Self::emit_group_member_webhook(
+                    crate::service::webhook_service::WEBHOOK_EVENT_GROUP_MEMBER_LEFT,
+                    channel_id,
+                    user_id,
+                );
+            }
         }
 
         let mut user_channels = self.user_channels.write().await;
@@ -3424,6 +3605,72 @@ impl ChannelService {
         muted_channels.contains(&key)
     }
 
+    /// 设置会话通知级别（全部 / 仅 @ / 不通知），同步维护 is_muted
+    pub async fn set_channel_notify_level(
+        &self,
+        user_id: u64,
+        channel_id: u64,
+        level: ChannelNotifyLevel,
+    ) -> Result<()> {
+        let muted = level == ChannelNotifyLevel::Nothing;
+
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_user_channels (user_id, channel_id, is_muted, notify_level, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id, channel_id) DO UPDATE SET
+                is_muted = EXCLUDED.is_muted,
+                notify_level = EXCLUDED.notify_level,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(channel_id as i64)
+        .bind(muted)
+        .bind(level.to_i16())
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(self.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("persist notify_level failed: {}", e)))?;
+
+        let key = (user_id, channel_id);
+        let mut muted_channels = self.muted_channels.write().await;
+        if muted {
+            muted_channels.insert(key);
+        } else {
+            muted_channels.remove(&key);
+        }
+
+        info!(
+            "User {} set notify level of channel {} to {}",
+            user_id,
+            channel_id,
+            level.as_str()
+        );
+        Ok(())
+    }
+
+    /// 读取会话通知级别（推送规划用）
+    ///
+    /// 🔴 直接查库而不是读 muted_channels：内存缓存只在用户加载会话列表时填充，
+    /// 离线用户（正是需要推送的人）的缓存往往是空的。
+    pub async fn get_channel_notify_level(
+        &self,
+        user_id: u64,
+        channel_id: u64,
+    ) -> Result<ChannelNotifyLevel> {
+        let level: Option<i16> = sqlx::query_scalar(
+            "SELECT notify_level FROM privchat_user_channels WHERE user_id = $1 AND channel_id = $2",
+        )
+        .bind(user_id as i64)
+        .bind(channel_id as i64)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("load notify_level failed: {}", e)))?;
+
+        Ok(level.map(ChannelNotifyLevel::from_i16).unwrap_or_default())
+    }
+
     /// 隐藏频道（不显示在会话列表中）
     pub async fn hide_channel(&self, user_id: u64, channel_id: u64, hidden: bool) -> Result<()> {
         let key = (user_id, channel_id);
@@ -4081,8 +4328,8 @@ impl ChannelService {
         &self,
         group_id: u64,
     ) -> Result<Option<crate::model::channel::GroupPolicy>> {
-        let row = sqlx::query_as::<_, (bool, i16, bool, bool, bool, bool, bool)>(
-            "SELECT allow_search, join_policy, allow_member_invite, allow_member_add_friend, all_muted, allow_member_post, forbid_forward \
+        let row = sqlx::query_as::<_, (bool, i16, bool, bool, bool, bool, bool, i32)>(
+            "SELECT allow_search, join_policy, allow_member_invite, allow_member_add_friend, all_muted, allow_member_post, forbid_forward, slow_mode_secs \
              FROM privchat_groups WHERE group_id = $1",
         )
         .bind(group_id as i64)
@@ -4099,6 +4346,7 @@ impl ChannelService {
                 all_muted,
                 allow_member_post,
                 forbid_forward,
+                slow_mode_secs,
             )| {
                 crate::model::channel::GroupPolicy {
                     allow_search,
@@ -4108,11 +4356,38 @@ impl ChannelService {
                     all_muted,
                     allow_member_post,
                     forbid_forward,
+                    slow_mode_secs: slow_mode_secs.max(0) as u32,
                 }
             },
         ))
     }
 
+    /// 设置群慢速模式间隔（秒，0 = 关闭），落库到 `privchat_groups.slow_mode_secs`。
+    ///
+    /// 超过 [`MAX_GROUP_SLOW_MODE_SECS`](crate::model::channel::MAX_GROUP_SLOW_MODE_SECS)
+    /// → Validation；群不存在 → NotFound。鉴权由调用方完成。
+    pub async fn set_group_slow_mode(&self, group_id: u64, slow_mode_secs: u32) -> Result<()> {
+        if slow_mode_secs > crate::model::channel::MAX_GROUP_SLOW_MODE_SECS {
+            return Err(ServerError::Validation(format!(
+                "slow_mode_secs 不能超过 {}",
+                crate::model::channel::MAX_GROUP_SLOW_MODE_SECS
+            )));
+        }
+        let result = sqlx::query(
+            "UPDATE privchat_groups SET slow_mode_secs = $2, updated_at = $3 WHERE group_id = $1",
+        )
+        .bind(group_id as i64)
+        .bind(slow_mode_secs as i32)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(self.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("更新群慢速模式失败: {}", e)))?;
+        if result.rows_affected() == 0 {
+            return Err(ServerError::NotFound(format!("群组不存在: {}", group_id)));
+        }
+        Ok(())
+    }
+
     /// 将群业务策略落库到 `privchat_groups`（DB 为真源）。
     ///
     /// 各字段 `None` 表示不修改（`COALESCE` 保留原值）。用运行时 `sqlx::query`，不依赖编译期 DB。
@@ -4262,6 +4537,36 @@ mod tests {
         assert_eq!(MemberRole::Member.to_i16(), 2);
         assert_eq!(db_role_to_wire(MemberRole::Member).to_wire_i32(), 0);
     }
+
+    #[test]
+    fn empty_or_malformed_permissions_fall_back_to_role_defaults() {
+        use crate::model::channel::MemberPermissions;
+
+        assert_eq!(parse_custom_permissions(None), None);
+        assert_eq!(parse_custom_permissions(Some(serde_json::json!({}))), None);
+        assert_eq!(
+            parse_custom_permissions(Some(serde_json::json!({ "can_invite": "yes" }))),
+            None,
+            "坏 JSON 不得让管理员丢权或提权",
+        );
+
+        let custom = MemberPermissions {
+            can_pin_message: false,
+            ..MemberPermissions::admin()
+        };
+        assert_eq!(
+            parse_custom_permissions(Some(serde_json::to_value(&custom).unwrap())),
+            Some(custom),
+        );
+
+        // 早于 can_moderate 保存的定制权限集：缺字段按管理员默认补齐，不丢禁言权。
+        let mut legacy = serde_json::to_value(MemberPermissions::admin()).unwrap();
+        legacy.as_object_mut().unwrap().remove("can_moderate");
+        assert_eq!(
+            parse_custom_permissions(Some(legacy)).map(|p| p.can_moderate),
+            Some(true),
+        );
+    }
     use super::*;
     use crate::model::channel::ChannelType;
     use crate::repository::PgChannelRepository;
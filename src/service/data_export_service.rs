@@ -0,0 +1,606 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 个人数据导出
+//!
+//! 用户通过 `account/export/request` 发起导出，后台任务把服务端保存的该用户数据打成
+//! 一个 `.tar` 归档写入默认存储源：
+//!
+//! ```text
+//! README.txt
+//! profile.json          用户资料（不含密码哈希）
+//! settings.json         用户设置 / 推送偏好
+//! contacts.json         好友关系 / 黑名单
+//! groups.json           群成员身份（含已退出的群）
+//! login_logs.json       登录日志
+//! messages/part-NNNNN.jsonl   自己发送的消息（每行一条）
+//! files/index.json      自己上传的文件元数据
+//! files/<file_id>_<文件名>      文件原始字节
+//! ```
+//!
+//! 归档生成后保留 `export_ttl_secs`，期间经文件服务器
+//! `GET /api/app/account/exports/{export_id}`（`Authorization: Bearer`）下载，过期后删除。
+
+use crate::error::{Result, ServerError};
+use crate::infra::tar_archive;
+use crate::repository::{DataExportRecord, DataExportRepository, EXPORT_STATUS_READY};
+use crate::service::FileService;
+use futures::TryStreamExt;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 后台任务轮询间隔
+pub const DATA_EXPORT_WORKER_INTERVAL: Duration = Duration::from_secs(10);
+/// 生成中任务的租约：超过这么久没有完成视为节点崩溃，允许重新领取
+const EXPORT_LEASE_MS: i64 = 30 * 60 * 1000;
+/// 每个 messages 分片的消息条数
+const MESSAGES_PER_PART: i64 = 5_000;
+/// 每次清理的过期归档数
+const CLEANUP_BATCH_SIZE: i64 = 100;
+/// 状态查询返回的最近任务数
+const EXPORT_HISTORY_LIMIT: i64 = 10;
+
+const README: &str = "PrivChat personal data export\n\
+\n\
+profile.json        account profile (password hash excluded)\n\
+settings.json       user settings and push preferences\n\
+contacts.json       friends and blocked users\n\
+groups.json         group memberships, including groups you have left\n\
+login_logs.json     login history\n\
+messages/*.jsonl    messages you sent, one JSON object per line\n\
+files/index.json    metadata of files you uploaded\n\
+files/*             original bytes of those files\n";
+
+/// 下载地址（文件服务器上的路径）
+pub fn export_download_path(export_id: u64) -> String {
+    format!("/api/app/account/exports/{}", export_id)
+}
+
+/// 导出任务（RPC 响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct DataExportView {
+    pub export_id: u64,
+    /// pending / running / ready / failed / expired
+    pub status: String,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    /// 仅 ready 时有值
+    pub download_path: Option<String>,
+}
+
+impl From<DataExportRecord> for DataExportView {
+    fn from(record: DataExportRecord) -> Self {
+        let download_path =
+            (record.status == EXPORT_STATUS_READY).then(|| export_download_path(record.export_id));
+        Self {
+            export_id: record.export_id,
+            status: record.status,
+            size_bytes: record.size_bytes,
+            error: record.error,
+            created_at: record.created_at,
+            completed_at: record.completed_at,
+            expires_at: record.expires_at,
+            download_path,
+        }
+    }
+}
+
+/// 可下载的归档
+pub struct ExportDownload {
+    pub filename: String,
+    pub size: u64,
+    pub stream: opendal::FuturesBytesStream,
+}
+
+/// 个人数据导出服务
+pub struct DataExportService {
+    repository: Arc<DataExportRepository>,
+    file_service: Arc<FileService>,
+    export_ttl_secs: u64,
+}
+
+impl DataExportService {
+    pub fn new(
+        repository: Arc<DataExportRepository>,
+        file_service: Arc<FileService>,
+        export_ttl_secs: u64,
+    ) -> Self {
+        Self {
+            repository,
+            file_service,
+            export_ttl_secs,
+        }
+    }
+
+    /// 发起导出；已有排队中 / 生成中的任务时直接返回该任务
+    pub async fn request(&self, user_id: u64) -> Result<DataExportView> {
+        if let Some(active) = self.repository.find_active(user_id).await? {
+            return Ok(active.into());
+        }
+        let record = self.repository.create(user_id).await?;
+        info!(
+            "📦 个人数据导出已排队: user_id={}, export_id={}",
+            user_id, record.export_id
+        );
+        Ok(record.into())
+    }
+
+    /// 最近的导出任务（倒序）
+    pub async fn list(&self, user_id: u64) -> Result<Vec<DataExportView>> {
+        Ok(self
+            .repository
+            .list_by_user(user_id, EXPORT_HISTORY_LIMIT)
+            .await?
+            .into_iter()
+            .map(DataExportView::from)
+            .collect())
+    }
+
+    /// 查询单个导出任务；不属于该用户的任务当作不存在
+    pub async fn get(&self, user_id: u64, export_id: u64) -> Result<Option<DataExportView>> {
+        Ok(self
+            .repository
+            .get(export_id)
+            .await?
+            .filter(|r| r.user_id == user_id)
+            .map(DataExportView::from))
+    }
+
+    /// 打开归档下载流；只有任务所属用户可以下载，其他人一律当作不存在
+    pub async fn open_download(&self, user_id: u64, export_id: u64) -> Result<ExportDownload> {
+        let record = self
+            .repository
+            .get(export_id)
+            .await?
+            .filter(|r| r.user_id == user_id)
+            .ok_or_else(|| ServerError::NotFound(format!("导出任务 {} 不存在", export_id)))?;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let (Some(source_id), Some(path)) = (record.storage_source_id, record.object_path) else {
+            return Err(ServerError::Validation(format!(
+                "导出任务 {} 尚未完成: status={}",
+                export_id, record.status
+            )));
+        };
+        if record.status != EXPORT_STATUS_READY || record.expires_at.is_some_and(|t| t <= now_ms) {
+            return Err(ServerError::NotFound(format!(
+                "导出归档 {} 已过期",
+                export_id
+            )));
+        }
+        let (size, stream) = self
+            .file_service
+            .open_object_stream(source_id, &path)
+            .await?;
+        Ok(ExportDownload {
+            filename: format!("privchat-export-{}-{}.tar", user_id, export_id),
+            size,
+            stream,
+        })
+    }
+
+    /// 领取并生成一个导出任务，返回是否处理了任务
+    pub async fn run_next(&self) -> Result<bool> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let Some(record) = self.repository.claim_next(now_ms, EXPORT_LEASE_MS).await? else {
+            return Ok(false);
+        };
+        let path = format!("exports/{}/{}.tar", record.user_id, record.export_id);
+        match self.build_archive(record.user_id, &path).await {
+            Ok((source_id, size)) => {
+                let done_ms = chrono::Utc::now().timestamp_millis();
+                let expires_at = done_ms + (self.export_ttl_secs as i64).saturating_mul(1000);
+                self.repository
+                    .mark_ready(
+                        record.export_id,
+                        source_id,
+                        &path,
+                        size,
+                        done_ms,
+                        expires_at,
+                    )
+                    .await?;
+                info!(
+                    "📦 个人数据导出完成: user_id={}, export_id={}, size={}",
+                    record.user_id, record.export_id, size
+                );
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️ 个人数据导出失败: user_id={}, export_id={}, error={}",
+                    record.user_id, record.export_id, e
+                );
+                self.repository
+                    .mark_failed(
+                        record.export_id,
+                        &e.to_string(),
+                        chrono::Utc::now().timestamp_millis(),
+                    )
+                    .await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// 删除已过期的归档，返回删除数
+    pub async fn cleanup_expired(&self) -> Result<usize> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let expired = self
+            .repository
+            .take_expired(now_ms, CLEANUP_BATCH_SIZE)
+            .await?;
+        let count = expired.len();
+        for record in expired {
+            self.delete_archive(&record).await;
+        }
+        Ok(count)
+    }
+
+    /// 注销时作废用户的全部导出并删除已生成的归档
+    pub async fn purge_user_exports(&self, user_id: u64) -> Result<()> {
+        for record in self.repository.expire_all_for_user(user_id).await? {
+            self.delete_archive(&record).await;
+        }
+        Ok(())
+    }
+
+    /// 启动后台任务：生成排队中的导出、清理过期归档
+    pub fn spawn_worker(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tick.tick().await;
+                loop {
+                    match self.run_next().await {
+                        Ok(true) => continue,
+                        Ok(false) => break,
+                        Err(e) => {
+                            warn!("⚠️ 领取个人数据导出任务失败: {}", e);
+                            break;
+                        }
+                    }
+                }
+                match self.cleanup_expired().await {
+                    Ok(count) if count > 0 => info!("🧹 已删除过期的个人数据导出 {} 个", count),
+                    Ok(_) => {}
+                    Err(e) => warn!("⚠️ 清理过期个人数据导出失败: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn delete_archive(&self, record: &DataExportRecord) {
+        if let (Some(source_id), Some(path)) = (record.storage_source_id, &record.object_path) {
+            if let Err(e) = self.file_service.delete_object(source_id, path).await {
+                warn!(
+                    "⚠️ 删除导出归档失败: export_id={}, path={}, error={}",
+                    record.export_id, path, e
+                );
+            }
+        }
+    }
+
+    /// 生成归档，返回 (存储源 id, 归档大小)；失败时删除写了一半的对象
+    async fn build_archive(&self, user_id: u64, path: &str) -> Result<(u32, u64)> {
+        let (source_id, writer) = self.file_service.open_object_writer(path).await?;
+        let mut archive = ArchiveWriter::new(writer);
+        match self.write_entries(user_id, &mut archive).await {
+            Ok(()) => match archive.finish().await {
+                Ok(size) => Ok((source_id, size)),
+                Err(e) => {
+                    let _ = self.file_service.delete_object(source_id, path).await;
+                    Err(e)
+                }
+            },
+            Err(e) => {
+                archive.abort().await;
+                let _ = self.file_service.delete_object(source_id, path).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_entries(&self, user_id: u64, archive: &mut ArchiveWriter) -> Result<()> {
+        archive
+            .add("README.txt", README.as_bytes().to_vec())
+            .await?;
+        archive
+            .add_json(
+                "profile.json",
+                &self.repository.load_profile(user_id).await?,
+            )
+            .await?;
+        archive
+            .add_json(
+                "settings.json",
+                &self.repository.load_settings(user_id).await?,
+            )
+            .await?;
+        archive
+            .add_json(
+                "contacts.json",
+                &self.repository.load_contacts(user_id).await?,
+            )
+            .await?;
+        archive
+            .add_json(
+                "groups.json",
+                &self.repository.load_group_memberships(user_id).await?,
+            )
+            .await?;
+        archive
+            .add_json(
+                "login_logs.json",
+                &self.repository.load_login_logs(user_id).await?,
+            )
+            .await?;
+
+        // 消息按键集分页写成多个 jsonl 分片，单个条目不需要整体驻留内存
+        let mut after = None;
+        let mut part = 0;
+        loop {
+            let page = self
+                .repository
+                .load_messages_page(user_id, after, MESSAGES_PER_PART)
+                .await?;
+            if page.is_empty() {
+                break;
+            }
+            part += 1;
+            let mut lines = Vec::new();
+            for (created_at, message_id, message) in &page {
+                serde_json::to_writer(&mut lines, message).map_err(|e| {
+                    ServerError::Serialization(format!("序列化导出消息失败: {}", e))
+                })?;
+                lines.push(b'\n');
+                after = Some((*created_at, *message_id));
+            }
+            archive
+                .add(&format!("messages/part-{:05}.jsonl", part), lines)
+                .await?;
+            if (page.len() as i64) < MESSAGES_PER_PART {
+                break;
+            }
+        }
+
+        let mut files = self.repository.load_uploaded_files(user_id).await?;
+        for file in files.iter_mut() {
+            let Some(file_id) = file.get("file_id").and_then(Value::as_u64) else {
+                continue;
+            };
+            let name = file
+                .get("original_filename")
+                .and_then(Value::as_str)
+                .unwrap_or("file");
+            let entry = format!(
+                "files/{}_{}",
+                file_id,
+                tar_archive::sanitize_entry_name(name)
+            );
+            // 按流写入：单个文件可能有几百 MB，整块读进内存会让导出任务吃光节点内存
+            match self.file_service.open_file_stream(file_id).await {
+                Ok((size, stream)) => {
+                    archive.add_stream(&entry, size, stream).await?;
+                    file["archive_path"] = json!(entry);
+                }
+                Err(e) => {
+                    // 物理文件丢失不影响其余数据的导出，在索引里注明
+                    warn!("⚠️ 导出文件读取失败: file_id={}, error={}", file_id, e);
+                    file["archive_error"] = json!(e.to_string());
+                }
+            }
+        }
+        archive.add_json("files/index.json", &json!(files)).await
+    }
+}
+
+/// 把 tar 条目顺序写入存储 writer
+struct ArchiveWriter {
+    writer: opendal::Writer,
+    written: u64,
+    mtime_secs: u64,
+}
+
+impl ArchiveWriter {
+    fn new(writer: opendal::Writer) -> Self {
+        Self {
+            writer,
+            written: 0,
+            mtime_secs: chrono::Utc::now().timestamp().max(0) as u64,
+        }
+    }
+
+    async fn add(&mut self, path: &str, data: Vec<u8>) -> Result<()> {
+        let size = data.len() as u64;
+        self.write_header(path, size).await?;
+        self.write(data).await?;
+        self.write_padding(size).await
+    }
+
+    /// 从字节流写入一个条目，内存里只驻留当前分块。
+    ///
+    /// tar 头先于内容写出，`size` 必须与流的实际长度一致；对不上时归档已无法修补，
+    /// 整个导出按失败处理（对象在读取期间被改写或截断）。
+    async fn add_stream<S>(&mut self, path: &str, size: u64, stream: S) -> Result<()>
+    where
+        S: futures::Stream<Item = std::io::Result<bytes::Bytes>>,
+    {
+        let mut stream = std::pin::pin!(stream);
+        self.write_header(path, size).await?;
+        let mut copied = 0u64;
+        while let Some(chunk) = stream
+            .try_next()
+            .await
+            .map_err(|e| ServerError::Internal(format!("读取导出文件失败: {}", e)))?
+        {
+            copied += chunk.len() as u64;
+            if copied > size {
+                break;
+            }
+            self.written += chunk.len() as u64;
+            self.writer
+                .write(chunk)
+                .await
+                .map_err(|e| ServerError::Internal(format!("写入导出归档失败: {}", e)))?;
+        }
+        if copied != size {
+            return Err(ServerError::Internal(format!(
+                "导出文件 {} 长度不符: 预期 {} 字节，读到 {} 字节",
+                path, size, copied
+            )));
+        }
+        self.write_padding(size).await
+    }
+
+    async fn write_header(&mut self, path: &str, size: u64) -> Result<()> {
+        let header = tar_archive::entry_header(path, size, self.mtime_secs)
+            .map_err(ServerError::Internal)?;
+        self.write(header.to_vec()).await
+    }
+
+    async fn write_padding(&mut self, size: u64) -> Result<()> {
+        let padding = tar_archive::padding_len(size);
+        if padding > 0 {
+            self.write(vec![0u8; padding]).await?;
+        }
+        Ok(())
+    }
+
+    async fn add_json(&mut self, path: &str, value: &Value) -> Result<()> {
+        let data = serde_json::to_vec_pretty(value)
+            .map_err(|e| ServerError::Serialization(format!("序列化导出数据失败: {}", e)))?;
+        self.add(path, data).await
+    }
+
+    async fn write(&mut self, bytes: Vec<u8>) -> Result<()> {
+        self.written += bytes.len() as u64;
+        self.writer
+            .write(bytes)
+            .await
+            .map_err(|e| ServerError::Internal(format!("写入导出归档失败: {}", e)))
+    }
+
+    async fn finish(mut self) -> Result<u64> {
+        self.write(tar_archive::END_OF_ARCHIVE.to_vec()).await?;
+        self.writer
+            .close()
+            .await
+            .map_err(|e| ServerError::Internal(format!("关闭导出归档失败: {}", e)))?;
+        Ok(self.written)
+    }
+
+    async fn abort(mut self) {
+        let _ = self.writer.abort().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: &str) -> DataExportRecord {
+        DataExportRecord {
+            export_id: 7,
+            user_id: 1001,
+            status: status.to_string(),
+            storage_source_id: Some(0),
+            object_path: Some("exports/1001/7.tar".to_string()),
+            size_bytes: Some(4096),
+            error: None,
+            created_at: 1,
+            started_at: Some(2),
+            completed_at: Some(3),
+            expires_at: Some(4),
+        }
+    }
+
+    #[test]
+    fn download_path_only_when_ready() {
+        let ready = DataExportView::from(record(EXPORT_STATUS_READY));
+        assert_eq!(
+            ready.download_path.as_deref(),
+            Some("/api/app/account/exports/7")
+        );
+        for status in ["pending", "running", "failed", "expired"] {
+            assert_eq!(DataExportView::from(record(status)).download_path, None);
+        }
+
+        // 存储路径不出现在 RPC 响应里
+        let json = serde_json::to_value(&ready).unwrap();
+        assert!(json.get("object_path").is_none());
+        assert!(json.get("storage_source_id").is_none());
+    }
+
+    async fn fs_archive(dir: &std::path::Path) -> (opendal::Operator, ArchiveWriter) {
+        let builder = opendal::services::Fs::default().root(&dir.to_string_lossy());
+        let op = opendal::Operator::new(builder)
+            .expect("fs operator")
+            .finish();
+        op.write("files/a.bin", b"hello".to_vec())
+            .await
+            .expect("write");
+        let writer = op.writer("out.tar").await.expect("writer");
+        (op, ArchiveWriter::new(writer))
+    }
+
+    async fn stream_of(op: &opendal::Operator, path: &str) -> opendal::FuturesBytesStream {
+        op.reader(path)
+            .await
+            .expect("reader")
+            .into_bytes_stream(..)
+            .await
+            .expect("stream")
+    }
+
+    #[tokio::test]
+    async fn files_are_streamed_into_padded_tar_entries() {
+        let dir = tempfile::tempdir().expect("tmp");
+        let (op, mut archive) = fs_archive(dir.path()).await;
+        let stream = stream_of(&op, "files/a.bin").await;
+        archive
+            .add_stream("files/1_a.bin", 5, stream)
+            .await
+            .expect("add");
+        let size = archive.finish().await.expect("finish");
+
+        let tar = op.read("out.tar").await.expect("read").to_vec();
+        assert_eq!(size, tar.len() as u64);
+        assert_eq!(tar.len(), tar_archive::BLOCK_SIZE * 4);
+        assert_eq!(
+            &tar[tar_archive::BLOCK_SIZE..tar_archive::BLOCK_SIZE + 5],
+            b"hello"
+        );
+    }
+
+    #[tokio::test]
+    async fn a_stream_shorter_than_its_header_fails_the_export() {
+        let dir = tempfile::tempdir().expect("tmp");
+        let (op, mut archive) = fs_archive(dir.path()).await;
+        let stream = stream_of(&op, "files/a.bin").await;
+        assert!(archive
+            .add_stream("files/1_a.bin", 6, stream)
+            .await
+            .is_err());
+        archive.abort().await;
+    }
+}
@@ -15,7 +15,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use redis::{AsyncCommands, Client as RedisClient, Script};
+use std::sync::Arc;
+
+use redis::{AsyncCommands, Script};
+
+use crate::infra::redis::RedisClient;
 use tracing::{debug, warn};
 
 type UserId = u64;
@@ -29,14 +33,13 @@ type ChannelId = u64;
 /// Redis 数据结构：HASH  key = `dpts:{user_id}`  field = `{channel_id}`  value = pts
 #[derive(Clone)]
 pub struct DeliveryTracker {
-    redis_client: RedisClient,
+    redis_client: Arc<RedisClient>,
 }
 
 impl DeliveryTracker {
-    pub fn new(redis_url: &str) -> Result<Self, crate::error::ServerError> {
-        let redis_client = RedisClient::open(redis_url)
-            .map_err(|e| crate::error::ServerError::Internal(format!("Redis 连接失败: {}", e)))?;
-        Ok(Self { redis_client })
+    /// 复用全局 RedisClient 的连接池（随配置支持单节点 / Sentinel / Cluster）
+    pub fn new(redis_client: Arc<RedisClient>) -> Self {
+        Self { redis_client }
     }
 
     fn hash_key(user_id: UserId) -> String {
@@ -67,7 +70,7 @@ impl DeliveryTracker {
             "#,
         );
 
-        let mut conn = match self.redis_client.get_multiplexed_async_connection().await {
+        let mut conn = match self.redis_client.get_conn().await {
             Ok(c) => c,
             Err(e) => {
                 warn!("DeliveryTracker: Redis 连接失败: {}", e);
@@ -143,7 +146,7 @@ impl DeliveryTracker {
             "#,
         );
 
-        let mut conn = match self.redis_client.get_multiplexed_async_connection().await {
+        let mut conn = match self.redis_client.get_conn().await {
             Ok(c) => c,
             Err(e) => {
                 warn!("DeliveryTracker: Redis 连接失败: {}", e);
@@ -179,7 +182,7 @@ impl DeliveryTracker {
         let key = Self::hash_key(user_id);
         let field = channel_id.to_string();
 
-        let mut conn = match self.redis_client.get_multiplexed_async_connection().await {
+        let mut conn = match self.redis_client.get_conn().await {
             Ok(c) => c,
             Err(e) => {
                 warn!("DeliveryTracker: Redis 连接失败: {}", e);
@@ -202,7 +205,7 @@ impl DeliveryTracker {
         let key = Self::hash_key(user_id);
         let field = channel_id.to_string();
 
-        let mut conn = match self.redis_client.get_multiplexed_async_connection().await {
+        let mut conn = match self.redis_client.get_conn().await {
             Ok(c) => c,
             Err(e) => {
                 warn!("DeliveryTracker: Redis 连接失败: {}", e);
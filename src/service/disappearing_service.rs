@@ -0,0 +1,373 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 阅后即焚 / 定时消失消息
+//!
+//! 计时来源（优先级从高到低）：
+//! 1. 单条消息：发送时 metadata 带 `self_destruct: { ttl_secs, start_on_read }`，
+//!    或发送后由发送方调用 `message/self_destruct/set` 补设；
+//! 2. 会话默认：私聊双方 / 群主、管理员通过 `channel/disappearing/update` 设置，
+//!    之后发出的新消息自动带上计时。
+//!
+//! 新消息的计时由 [`expiry_arm_for_new_message`] 决定，与消息在同一事务里登记。
+//!
+//! `start_on_read = false` 时发送即开始倒计时；`true` 时接收方已读游标越过该消息
+//! （`ReadStateService` 的 read_pts 链路）才开始。[`DisappearingService::spawn_sweeper`]
+//! 周期性硬删除到期消息、commit log 与不再被引用的附件，并为每个会话追加一条
+//! `message.expired` commit，所有设备经 `sync/get_difference` 拉到后删除本地副本。
+
+use crate::error::{Result, ServerError};
+use crate::model::channel::ChannelType;
+use crate::repository::{
+    DisappearingRepository, DisappearingTimer, MessageExpiryArm, NewMessageExpiry,
+};
+use crate::service::sync::get_global_sync_service;
+use crate::service::{FileService, MessageHistoryService};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 消息 metadata 中的单条消息计时字段
+pub const SELF_DESTRUCT_METADATA_KEY: &str = "self_destruct";
+/// 计时下限（秒）
+pub const MIN_DISAPPEARING_TTL_SECS: i64 = 5;
+/// 计时上限（秒）：1 年
+pub const MAX_DISAPPEARING_TTL_SECS: i64 = 365 * 24 * 3600;
+/// 后台清理间隔
+pub const DISAPPEARING_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+/// 每批硬删除的消息数
+const PURGE_BATCH_SIZE: i64 = 500;
+
+/// 会话消失计时器设置（RPC 响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelDisappearing {
+    pub channel_id: u64,
+    /// 会话默认计时（秒），`None` 表示关闭
+    pub ttl_secs: Option<i64>,
+    pub start_on_read: bool,
+}
+
+/// 一轮清理的统计
+#[derive(Debug, Clone, Default)]
+pub struct DisappearingSweepStats {
+    pub channels: u64,
+    pub messages: u64,
+    pub files: u64,
+}
+
+/// 校验计时参数
+pub fn validate_timer(timer: &DisappearingTimer) -> Result<()> {
+    if !(MIN_DISAPPEARING_TTL_SECS..=MAX_DISAPPEARING_TTL_SECS).contains(&timer.ttl_secs) {
+        return Err(ServerError::Validation(format!(
+            "ttl_secs 必须在 {} 到 {} 秒之间",
+            MIN_DISAPPEARING_TTL_SECS, MAX_DISAPPEARING_TTL_SECS
+        )));
+    }
+    Ok(())
+}
+
+/// 从消息 metadata 解析单条消息计时；没有 `self_destruct` 字段时返回 `None`
+pub fn parse_self_destruct(metadata: &Value) -> Result<Option<DisappearingTimer>> {
+    let Some(raw) = metadata.get(SELF_DESTRUCT_METADATA_KEY) else {
+        return Ok(None);
+    };
+    if raw.is_null() {
+        return Ok(None);
+    }
+    let ttl_secs = raw
+        .get("ttl_secs")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| ServerError::Validation("self_destruct.ttl_secs 缺失或无效".to_string()))?;
+    let start_on_read = match raw.get("start_on_read") {
+        None | Some(Value::Null) => false,
+        Some(v) => v.as_bool().ok_or_else(|| {
+            ServerError::Validation("self_destruct.start_on_read 必须是布尔值".to_string())
+        })?,
+    };
+    let timer = DisappearingTimer {
+        ttl_secs,
+        start_on_read,
+    };
+    validate_timer(&timer)?;
+    Ok(Some(timer))
+}
+
+/// 新消息的计时登记方式：Room 不登记；metadata 带 `self_destruct` 时用它，否则按会话默认。
+///
+/// 所有发送路径（客户端发送、`sync/submit` 与转发、服务端 / Bot 发送）都经它得出
+/// [`MessageExpiryArm`]，交给消息事务一并写入。
+pub fn expiry_arm_for_new_message(channel_type: u8, metadata: &Value) -> Result<MessageExpiryArm> {
+    Ok(MessageExpiryArm::for_new_message(
+        channel_type,
+        parse_self_destruct(metadata)?,
+    ))
+}
+
+pub struct DisappearingService {
+    repository: Arc<DisappearingRepository>,
+    message_history_service: Arc<MessageHistoryService>,
+    file_service: Arc<FileService>,
+}
+
+impl DisappearingService {
+    pub fn new(
+        repository: Arc<DisappearingRepository>,
+        message_history_service: Arc<MessageHistoryService>,
+        file_service: Arc<FileService>,
+    ) -> Self {
+        Self {
+            repository,
+            message_history_service,
+            file_service,
+        }
+    }
+
+    pub async fn get_channel_timer(&self, channel_id: u64) -> Result<ChannelDisappearing> {
+        let timer = self.repository.get_channel_timer(channel_id).await?;
+        Ok(Self::describe(channel_id, timer))
+    }
+
+    /// 设置会话默认计时器；`None` 关闭。只影响之后发出的消息
+    pub async fn set_channel_timer(
+        &self,
+        channel_id: u64,
+        operator_id: u64,
+        timer: Option<DisappearingTimer>,
+    ) -> Result<ChannelDisappearing> {
+        match timer {
+            Some(timer) => {
+                validate_timer(&timer)?;
+                self.repository
+                    .upsert_channel_timer(channel_id, timer, operator_id)
+                    .await?;
+            }
+            None => {
+                self.repository.delete_channel_timer(channel_id).await?;
+            }
+        }
+        Ok(Self::describe(channel_id, timer))
+    }
+
+    fn describe(channel_id: u64, timer: Option<DisappearingTimer>) -> ChannelDisappearing {
+        ChannelDisappearing {
+            channel_id,
+            ttl_secs: timer.map(|t| t.ttl_secs),
+            start_on_read: timer.is_some_and(|t| t.start_on_read),
+        }
+    }
+
+    /// 发送方为已发出的消息补设计时；消息已有计时时返回 `Validation`
+    pub async fn arm_sent_message(
+        &self,
+        message_id: u64,
+        message_created_at: i64,
+        channel_id: u64,
+        pts: u64,
+        sender_id: u64,
+        timer: DisappearingTimer,
+    ) -> Result<Option<i64>> {
+        validate_timer(&timer)?;
+        self.repository
+            .insert_message_expiry(
+                &NewMessageExpiry {
+                    message_id,
+                    message_created_at,
+                    channel_id,
+                    pts,
+                    sender_id,
+                    timer,
+                },
+                chrono::Utc::now().timestamp_millis(),
+            )
+            .await?
+            .ok_or_else(|| ServerError::Validation("该消息已设置消失计时".to_string()))
+    }
+
+    /// 已读游标推进：启动 `reader_id` 已读范围内等待已读的倒计时
+    pub async fn on_read_advanced(
+        &self,
+        channel_id: u64,
+        reader_id: u64,
+        read_pts: u64,
+    ) -> Result<u64> {
+        self.repository
+            .start_countdown_on_read(
+                channel_id,
+                reader_id,
+                read_pts,
+                chrono::Utc::now().timestamp_millis(),
+            )
+            .await
+    }
+
+    /// 清理一轮：分批硬删除到期消息，逐会话通知客户端，最后释放不再被引用的附件
+    pub async fn sweep_once(&self) -> Result<DisappearingSweepStats> {
+        let mut stats = DisappearingSweepStats::default();
+        loop {
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let batch = self
+                .repository
+                .purge_expired_batch(now_ms, PURGE_BATCH_SIZE)
+                .await?;
+            let purged = batch.message_count();
+            if purged == 0 {
+                break;
+            }
+            stats.messages += purged as u64;
+
+            for channel in &batch.channels {
+                stats.channels += 1;
+                // 本节点内存中的历史缓存整体丢弃，下次查询从数据库回填
+                let _ = self
+                    .message_history_service
+                    .clear_channel_messages(&channel.channel_id)
+                    .await;
+
+                let wire_type = ChannelType::from_i16(channel.channel_type).to_wire_u8();
+                match get_global_sync_service() {
+                    Some(sync_service) => {
+                        if let Err(e) = sync_service
+                            .append_messages_expired_commit(
+                                channel.channel_id,
+                                wire_type,
+                                &channel.message_ids,
+                                now_ms,
+                            )
+                            .await
+                        {
+                            warn!(
+                                "⚠️ 写入 message.expired commit 失败: channel_id={}, error={}",
+                                channel.channel_id, e
+                            );
+                        }
+                    }
+                    None => warn!("⚠️ SyncService 未初始化，跳过 message.expired commit"),
+                }
+            }
+
+            for file_id in batch.released_file_ids {
+                match self.file_service.release_unreferenced_file(file_id).await {
+                    Ok(true) => stats.files += 1,
+                    Ok(false) => {}
+                    Err(e) => warn!("⚠️ 释放到期消息附件失败: file_id={}, error={}", file_id, e),
+                }
+            }
+
+            if (purged as i64) < PURGE_BATCH_SIZE {
+                break;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 启动后台清理任务
+    pub fn spawn_sweeper(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tick.tick().await;
+                match self.sweep_once().await {
+                    Ok(stats) if stats.messages > 0 => info!(
+                        "🔥 阅后即焚清理：{} 个会话，删除消息 {} 条、附件 {} 个",
+                        stats.channels, stats.messages, stats.files
+                    ),
+                    Ok(_) => {}
+                    Err(e) => warn!("⚠️ 阅后即焚清理失败: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_self_destruct_reads_metadata() {
+        assert_eq!(parse_self_destruct(&json!({})).unwrap(), None);
+        assert_eq!(
+            parse_self_destruct(&json!({ "self_destruct": null })).unwrap(),
+            None
+        );
+        assert_eq!(
+            parse_self_destruct(&json!({ "self_destruct": { "ttl_secs": 30 } })).unwrap(),
+            Some(DisappearingTimer {
+                ttl_secs: 30,
+                start_on_read: false,
+            })
+        );
+        assert_eq!(
+            parse_self_destruct(
+                &json!({ "self_destruct": { "ttl_secs": 60, "start_on_read": true } })
+            )
+            .unwrap(),
+            Some(DisappearingTimer {
+                ttl_secs: 60,
+                start_on_read: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_self_destruct_rejects_bad_timers() {
+        assert!(parse_self_destruct(&json!({ "self_destruct": {} })).is_err());
+        assert!(parse_self_destruct(&json!({ "self_destruct": { "ttl_secs": 1 } })).is_err());
+        assert!(parse_self_destruct(
+            &json!({ "self_destruct": { "ttl_secs": MAX_DISAPPEARING_TTL_SECS + 1 } })
+        )
+        .is_err());
+        assert!(parse_self_destruct(
+            &json!({ "self_destruct": { "ttl_secs": 30, "start_on_read": "yes" } })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn new_messages_use_the_explicit_timer_then_the_channel_default() {
+        let direct = ChannelType::Direct.to_wire_u8();
+        assert_eq!(
+            expiry_arm_for_new_message(direct, &json!({})).unwrap(),
+            MessageExpiryArm::ChannelDefault
+        );
+        assert_eq!(
+            expiry_arm_for_new_message(direct, &json!({ "self_destruct": { "ttl_secs": 30 } }))
+                .unwrap(),
+            MessageExpiryArm::Explicit(DisappearingTimer {
+                ttl_secs: 30,
+                start_on_read: false,
+            })
+        );
+        assert_eq!(
+            expiry_arm_for_new_message(
+                ChannelType::Room.to_wire_u8(),
+                &json!({ "self_destruct": { "ttl_secs": 30 } })
+            )
+            .unwrap(),
+            MessageExpiryArm::Skip,
+            "Room 不支持阅后即焚"
+        );
+        assert!(
+            expiry_arm_for_new_message(direct, &json!({ "self_destruct": { "ttl_secs": 1 } }))
+                .is_err()
+        );
+    }
+}
@@ -1281,6 +1281,134 @@ impl FileService {
         Ok(())
     }
 
+    /// 消息被硬删除（阅后即焚到期）后释放其附件：文件已不被任何消息引用时删除上传记录，
+    /// 同一物理文件没有其他记录指向时连同对象一起删除。返回是否删除了上传记录。
+    ///
+    /// 与 [`Self::delete_file`] 的区别在于这里必须确认「零引用」，而且确认要排在并发转发之后：
+    /// 新引用只能由发送者绑定自己上传的文件产生，绑定语句会 UPDATE 上传记录，
+    /// 所以先 `FOR UPDATE` 锁住这一行——在途的绑定事务提交后才轮到我们，
+    /// 之后的引用计数是新快照，看得到它刚写的引用。
+    pub async fn release_unreferenced_file(&self, file_id: u64) -> Result<bool> {
+        let Some(meta) = self.file_upload_repo.get_by_file_id(file_id).await? else {
+            return Ok(false);
+        };
+        let mut tx = self
+            .file_upload_repo
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启释放事务失败: {e}")))?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(&meta.file_path)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("获取物理文件锁失败: {e}")))?;
+
+        let locked: Option<(i64,)> = sqlx::query_as(
+            "SELECT file_id FROM privchat_file_uploads WHERE file_id = $1 FOR UPDATE",
+        )
+        .bind(file_id as i64)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("锁定上传记录失败: {e}")))?;
+        if locked.is_none() {
+            return Ok(false);
+        }
+
+        let (refs,): (i64,) =
+            sqlx::query_as("SELECT count(*) FROM privchat_message_file_refs WHERE file_id = $1")
+                .bind(file_id as i64)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| ServerError::Database(format!("查询文件引用数失败: {e}")))?;
+        if refs > 0 {
+            return Ok(false);
+        }
+
+        let (others,): (i64,) = sqlx::query_as(
+            "SELECT count(*) FROM privchat_file_uploads \
+             WHERE file_path = $1 AND file_id <> $2",
+        )
+        .bind(&meta.file_path)
+        .bind(file_id as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("统计共享物理文件的记录失败: {e}")))?;
+
+        sqlx::query("DELETE FROM privchat_file_uploads WHERE file_id = $1")
+            .bind(file_id as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("删除上传记录失败: {e}")))?;
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交释放事务失败: {e}")))?;
+
+        if others == 0 {
+            let op = self.operator_for_source(meta.storage_source_id).await?;
+            if let Err(e) = op.delete(&meta.file_path).await {
+                tracing::warn!("删除物理文件失败（留待 GC）path={}: {}", meta.file_path, e);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 在默认存储源上打开一个对象 writer（个人数据导出归档等服务端生成的对象）。
+    /// 返回 (存储源 id, writer)；写失败时调用方负责 [`Self::delete_object`] 清理。
+    pub async fn open_object_writer(&self, path: &str) -> Result<(u32, opendal::Writer)> {
+        let source_id = self.default_storage_source_id;
+        let op = self.operator_for_source(source_id).await?;
+        let writer = op
+            .writer(path)
+            .await
+            .map_err(|e| ServerError::Internal(format!("打开存储 writer 失败: {e}")))?;
+        Ok((source_id, writer))
+    }
+
+    /// 以字节流读取对象（大文件下载不整体进内存）。返回 (对象大小, 字节流)。
+    pub async fn open_object_stream(
+        &self,
+        source_id: u32,
+        path: &str,
+    ) -> Result<(u64, opendal::FuturesBytesStream)> {
+        let op = self.operator_for_source(source_id).await?;
+        let size = op
+            .stat(path)
+            .await
+            .map_err(|e| ServerError::NotFound(format!("存储对象不存在: {e}")))?
+            .content_length();
+        let stream = op
+            .reader(path)
+            .await
+            .map_err(|e| ServerError::Internal(format!("打开存储对象失败: {e}")))?
+            .into_bytes_stream(..)
+            .await
+            .map_err(|e| ServerError::Internal(format!("读取存储对象失败: {e}")))?;
+        Ok((size, stream))
+    }
+
+    /// 以字节流读取文件内容（个人数据导出等大文件场景）。返回 (对象大小, 字节流)。
+    pub async fn open_file_stream(
+        &self,
+        file_id: u64,
+    ) -> Result<(u64, opendal::FuturesBytesStream)> {
+        let metadata = self
+            .get_file_metadata(file_id)
+            .await?
+            .ok_or_else(|| ServerError::NotFound("文件不存在".to_string()))?;
+        self.open_object_stream(metadata.storage_source_id, &metadata.file_path)
+            .await
+    }
+
+    /// 删除对象；对象不存在视为成功。
+    pub async fn delete_object(&self, source_id: u32, path: &str) -> Result<()> {
+        let op = self.operator_for_source(source_id).await?;
+        op.delete(path)
+            .await
+            .map_err(|e| ServerError::Internal(format!("删除存储对象失败: {e}")))
+    }
+
     fn detect_file_type(&self, mime_type: &str) -> Result<FileType> {
         // 注：这里只按 MIME 服务端兜底分类。Voice 消息的分类由 SDK 明确传入 "voice"，
         // 不靠 MIME 推导——否则任何 audio/* 的普通文件会被误分到 Voice。
@@ -0,0 +1,137 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 群慢速模式：普通成员两次发言之间的最小间隔。
+//!
+//! 间隔配置在 `privchat_groups.slow_mode_secs`（随 `GroupPolicy` 读出）；
+//! 每个成员的发言窗口存在 Redis，任一节点发出的消息都占用同一个窗口。
+//! 判定入口只有 `send_authorization::authorize_send_to_channel`；窗口由它返回的
+//! `SendPermit` 在全部校验通过、即将落库时才占用。
+
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::infra::redis::RedisClient;
+
+/// 群慢速模式窗口
+///
+/// Redis 数据结构：STRING  key = `privchat:group:{group_id}:slow:{user_id}`
+/// value = 占用窗口时的毫秒时间戳（Redis 服务器时间），TTL = 当时的间隔。
+#[derive(Clone)]
+pub struct GroupSlowModeService {
+    redis: Arc<RedisClient>,
+}
+
+/// 一次占用结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlowModeAcquire {
+    /// 间隔为 0，没有窗口可占
+    Unlimited,
+    /// 已占用；消息最终没写成时用 [`GroupSlowModeService::release`] 交还
+    Acquired(SlowModeWindow),
+    /// 仍在冷却，还需等待的秒数（至少 1）
+    Cooling { remaining_secs: u64 },
+}
+
+/// 已占用的发言窗口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowModeWindow {
+    pub group_id: u64,
+    pub user_id: u64,
+    started_ms: i64,
+}
+
+impl GroupSlowModeService {
+    pub fn new(redis: Arc<RedisClient>) -> Self {
+        Self { redis }
+    }
+
+    /// 尝试占用一次发言窗口。
+    ///
+    /// 判定与占用在一个 Redis 脚本里完成：同一成员的并发发言只有一条能占到窗口。
+    /// 群主调短间隔后，按旧间隔记下的窗口按新间隔判定，不必等旧 TTL 过期。
+    pub async fn try_acquire(
+        &self,
+        group_id: u64,
+        user_id: u64,
+        interval_secs: u32,
+    ) -> Result<SlowModeAcquire> {
+        if interval_secs == 0 {
+            return Ok(SlowModeAcquire::Unlimited);
+        }
+        let key = slow_mode_key(group_id, user_id);
+        let (acquired, started_ms, now_ms) = self
+            .redis
+            .acquire_interval_window(&key, interval_secs as u64 * 1000)
+            .await?;
+        if acquired {
+            return Ok(SlowModeAcquire::Acquired(SlowModeWindow {
+                group_id,
+                user_id,
+                started_ms,
+            }));
+        }
+        // 脚本已按同一判定拒绝；这里只换算剩余秒数，边界上至少报 1 秒
+        let remaining_secs = remaining_secs(started_ms, interval_secs as u64, now_ms).unwrap_or(1);
+        Ok(SlowModeAcquire::Cooling { remaining_secs })
+    }
+
+    /// 交还窗口：消息在占用之后被拒或落库失败时调用。
+    /// 只删除自己占用的那一份，窗口已过期并被新的发言占用时不动。
+    pub async fn release(&self, window: &SlowModeWindow) -> Result<()> {
+        let key = slow_mode_key(window.group_id, window.user_id);
+        self.redis
+            .compare_and_del(&key, &window.started_ms.to_string())
+            .await?;
+        Ok(())
+    }
+}
+
+fn slow_mode_key(group_id: u64, user_id: u64) -> String {
+    format!("privchat:group:{}:slow:{}", group_id, user_id)
+}
+
+/// 从 `started_ms` 起按 `interval_secs` 计算的剩余冷却秒数（向上取整）；已过期返回 None。
+///
+/// Room 聊天的慢速模式（`RoomChatService::send`）用同一个 Redis 窗口脚本，也用它换算剩余秒数。
+pub(crate) fn remaining_secs(started_ms: i64, interval_secs: u64, now_ms: i64) -> Option<u64> {
+    let ends_ms = started_ms + interval_secs as i64 * 1000;
+    if ends_ms <= now_ms {
+        return None;
+    }
+    Some(((ends_ms - now_ms + 999) / 1000) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::remaining_secs;
+
+    #[test]
+    fn remaining_wait_is_rounded_up() {
+        assert_eq!(remaining_secs(0, 30, 0), Some(30));
+        assert_eq!(remaining_secs(0, 30, 29_001), Some(1));
+        assert_eq!(remaining_secs(0, 30, 10_500), Some(20));
+        assert_eq!(remaining_secs(0, 30, 30_000), None);
+    }
+
+    #[test]
+    fn shortening_the_interval_releases_old_windows() {
+        // 按 60s 记下的窗口，群主改成 10s 后 15s 时应当放行
+        assert_eq!(remaining_secs(0, 10, 15_000), None);
+        assert_eq!(remaining_secs(0, 60, 15_000), Some(45));
+    }
+}
@@ -15,63 +15,112 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::error::Result;
-use crate::model::channel::{ChannelId, MessageId, UserId};
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::error::{Result, ServerError};
+use sqlx::{FromRow, PgPool};
+use std::collections::BTreeSet;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-
-/// @提及记录
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Mention {
-    /// 消息ID
-    pub message_id: MessageId,
-    /// 频道ID
-    pub channel_id: ChannelId,
-    /// 被@的用户ID列表
-    pub mentioned_user_ids: Vec<UserId>,
-    /// 是否@全体成员
-    pub is_mention_all: bool,
-    /// 创建时间
-    pub created_at: DateTime<Utc>,
+
+/// 结构化 @全体成员：客户端在 `mentioned_user_ids` 中放入该值，库内也以此存 @全体成员
+pub const MENTION_ALL_USER_ID: u64 = 0;
+/// 一条消息最多记录的被@用户数，超出部分忽略
+pub const MAX_MENTIONS_PER_MESSAGE: usize = 200;
+/// 未读 @ 查询每个会话最多返回的消息 ID 数（计数不受限）
+pub const MAX_UNREAD_MENTION_IDS: usize = 100;
+
+/// 单个会话的未读 @ 汇总
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChannelUnreadMentions {
+    pub channel_id: u64,
+    /// 未读 @ 消息总数
+    pub unread_count: u64,
+    /// 未读 @ 消息 ID，按 pts 升序（最早的在前，便于客户端依次跳转）
+    pub message_ids: Vec<u64>,
+}
+
+#[derive(FromRow)]
+struct UnreadMentionRow {
+    channel_id: i64,
+    unread_count: i64,
+    message_ids: Vec<i64>,
+}
+
+/// 拆分客户端传来的结构化提及：去重、去掉 0 哨兵并返回是否 @全体成员
+pub fn split_mention_targets(ids: &[u64]) -> (Vec<u64>, bool) {
+    let mut mention_all = false;
+    let mut seen = BTreeSet::new();
+    let mut user_ids = Vec::new();
+    for &id in ids {
+        if id == MENTION_ALL_USER_ID {
+            mention_all = true;
+        } else if seen.insert(id) && user_ids.len() < MAX_MENTIONS_PER_MESSAGE {
+            user_ids.push(id);
+        }
+    }
+    (user_ids, mention_all)
+}
+
+/// 在消息事务内记录 @提及（见 `PgMessageRepository::create_message_and_commit_atomic`），
+/// 重复记录幂等。
+///
+/// `targets` 中的用户须已由调用方过滤为会话成员；含 `MENTION_ALL_USER_ID`
+/// 表示 @全体成员，调用方须已做过 `can_at_all` 权限检查。
+pub(crate) async fn insert_mentions_in_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    message: &crate::model::message::Message,
+    targets: &[u64],
+) -> std::result::Result<(), sqlx::Error> {
+    if targets.is_empty() {
+        return Ok(());
+    }
+    let targets: Vec<i64> = targets.iter().map(|&id| id as i64).collect();
+    sqlx::query(
+        r#"
+        INSERT INTO privchat_message_mentions (
+            message_id, user_id, channel_id, sender_id, pts, message_created_at
+        )
+        SELECT $1, target, $2, $3, $4, $5
+        FROM UNNEST($6::BIGINT[]) AS target
+        ON CONFLICT (message_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(message.message_id as i64)
+    .bind(message.channel_id as i64)
+    .bind(message.sender_id as i64)
+    .bind(message.pts.unwrap_or(0))
+    .bind(message.created_at.timestamp_millis())
+    .bind(&targets)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
 }
 
 /// @提及服务
+///
+/// 提及记录落 Postgres（`privchat_message_mentions`），以客户端在消息里携带的用户 ID 为准，
+/// 不再从正文解析用户名。未读与否由会话已读 pts 决定，已读推进后未读 @ 自然清零。
 pub struct MentionService {
-    /// message_id -> Mention
-    mentions: Arc<RwLock<HashMap<MessageId, Mention>>>,
-    /// user_id -> [message_id]（用户被@的消息列表）
-    user_mentions: Arc<RwLock<HashMap<UserId, Vec<MessageId>>>>,
+    pool: Arc<PgPool>,
 }
 
 impl MentionService {
-    /// 创建新的 @提及服务
-    pub fn new() -> Self {
-        Self {
-            mentions: Arc::new(RwLock::new(HashMap::new())),
-            user_mentions: Arc::new(RwLock::new(HashMap::new())),
-        }
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
     }
 
-    /// 解析消息中的@提及
+    /// 解析消息正文中的 @ 文本
+    ///
+    /// 仅用于推送预览等展示场景，以及兼容不带结构化提及的旧客户端识别 @全体成员；
+    /// 真正的被@用户以 `mentioned_user_ids` 为准。
     ///
     /// 支持格式：
     /// - @username
-    /// - @全体成员（需要权限检查）
+    /// - @全体成员 / @all / @everyone
     pub fn parse_mentions(content: &str) -> (Vec<String>, bool) {
         let mut mentioned_usernames = Vec::new();
         let mut is_mention_all = false;
 
-        // 简单的@解析（实际应该更智能，支持中文用户名等）
-        let words: Vec<&str> = content.split_whitespace().collect();
-
-        for word in words {
-            if word.starts_with("@") {
-                let username = word.strip_prefix("@").unwrap_or("");
-
-                // 检查是否是@全体成员
+        for word in content.split_whitespace() {
+            if let Some(username) = word.strip_prefix('@') {
                 if username == "全体成员" || username == "all" || username == "everyone" {
                     is_mention_all = true;
                 } else if !username.is_empty() {
@@ -83,75 +132,111 @@ impl MentionService {
         (mentioned_usernames, is_mention_all)
     }
 
-    /// 记录@提及
-    pub async fn record_mention(
+    /// 查询用户的未读 @（可限定单个会话），按最近一次 @ 倒序返回各会话汇总
+    ///
+    /// - 直接 @ 本人：pts 大于本人在该会话的已读 pts 即未读
+    /// - @全体成员：另需当前仍在会话中、且入会早于该消息，自己发的不算
+    /// - 已撤回 / 已删除的消息不计入
+    pub async fn get_unread_mentions(
         &self,
-        message_id: u64,
-        channel_id: u64,
-        mentioned_user_ids: Vec<u64>,
-        is_mention_all: bool,
-    ) -> Result<Mention> {
-        let mention = Mention {
-            message_id,
-            channel_id,
-            mentioned_user_ids: mentioned_user_ids.clone(),
-            is_mention_all,
-            created_at: Utc::now(),
-        };
-
-        let mut mentions = self.mentions.write().await;
-        mentions.insert(message_id, mention.clone());
-
-        // 更新用户被@的索引
-        let mut user_mentions = self.user_mentions.write().await;
-        for user_id in mentioned_user_ids {
-            user_mentions
-                .entry(user_id)
-                .or_insert_with(Vec::new)
-                .push(message_id);
-        }
-
-        Ok(mention)
+        user_id: u64,
+        channel_id: Option<u64>,
+        ids_per_channel: usize,
+    ) -> Result<Vec<ChannelUnreadMentions>> {
+        let ids_per_channel = ids_per_channel.clamp(1, MAX_UNREAD_MENTION_IDS);
+        let rows = sqlx::query_as::<_, UnreadMentionRow>(
+            r#"
+            WITH unread AS (
+                SELECT DISTINCT m.channel_id, m.message_id, m.pts
+                FROM privchat_message_mentions m
+                JOIN privchat_messages msg
+                    ON msg.message_id = m.message_id
+                    AND msg.created_at = m.message_created_at
+                LEFT JOIN privchat_channel_read_cursor cur
+                    ON cur.user_id = $1 AND cur.channel_id = m.channel_id
+                WHERE (
+                        m.user_id = $1
+                        OR (
+                            m.user_id = 0
+                            AND m.sender_id <> $1
+                            AND EXISTS (
+                                SELECT 1
+                                FROM privchat_channel_participants p
+                                WHERE p.channel_id = m.channel_id
+                                  AND p.user_id = $1
+                                  AND p.left_at IS NULL
+                                  AND p.joined_at <= m.message_created_at
+                            )
+                        )
+                    )
+                  AND ($2::BIGINT IS NULL OR m.channel_id = $2)
+                  AND m.pts > COALESCE(cur.last_read_pts, 0)
+                  AND NOT COALESCE(msg.revoked, FALSE)
+                  AND NOT COALESCE(msg.deleted, FALSE)
+            )
+            SELECT
+                channel_id,
+                COUNT(*) AS unread_count,
+                (ARRAY_AGG(message_id ORDER BY pts ASC))[1:$3] AS message_ids
+            FROM unread
+            GROUP BY channel_id
+            ORDER BY MAX(pts) DESC
+            "#,
+        )
+        .bind(user_id as i64)
+        .bind(channel_id.map(|id| id as i64))
+        .bind(ids_per_channel as i32)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询未读 @提及失败: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ChannelUnreadMentions {
+                channel_id: row.channel_id as u64,
+                unread_count: row.unread_count.max(0) as u64,
+                message_ids: row.message_ids.into_iter().map(|id| id as u64).collect(),
+            })
+            .collect())
     }
 
-    /// 获取消息的@提及信息
-    pub async fn get_message_mentions(&self, message_id: u64) -> Option<Mention> {
-        let mentions = self.mentions.read().await;
-        mentions.get(&message_id).cloned()
+    /// 获取消息 @ 的用户 ID 列表及是否 @全体成员
+    pub async fn get_message_mentions(&self, message_id: u64) -> Result<(Vec<u64>, bool)> {
+        let user_ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT user_id FROM privchat_message_mentions WHERE message_id = $1 ORDER BY user_id",
+        )
+        .bind(message_id as i64)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询消息 @提及失败: {}", e)))?;
+
+        let ids: Vec<u64> = user_ids.into_iter().map(|id| id as u64).collect();
+        Ok(split_mention_targets(&ids))
     }
+}
 
-    /// 获取用户被@的消息列表
-    pub async fn get_user_mentions(&self, user_id: u64, limit: Option<usize>) -> Vec<MessageId> {
-        let user_mentions = self.user_mentions.read().await;
-
-        if let Some(message_ids) = user_mentions.get(&user_id) {
-            let mut result = message_ids.clone();
-            result.reverse(); // 最新的在前
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            if let Some(limit) = limit {
-                result.truncate(limit);
-            }
+    #[test]
+    fn split_mention_targets_extracts_mention_all_and_dedups() {
+        let (ids, all) = split_mention_targets(&[3, 0, 5, 3]);
+        assert_eq!(ids, vec![3, 5]);
+        assert!(all);
 
-            result
-        } else {
-            Vec::new()
-        }
+        let (ids, all) = split_mention_targets(&[7]);
+        assert_eq!(ids, vec![7]);
+        assert!(!all);
     }
 
-    /// 检查消息是否@了指定用户
-    pub async fn is_user_mentioned(&self, message_id: u64, user_id: u64) -> bool {
-        let mentions = self.mentions.read().await;
-
-        if let Some(mention) = mentions.get(&message_id) {
-            mention.mentioned_user_ids.contains(&user_id)
-        } else {
-            false
-        }
-    }
-}
+    #[test]
+    fn parse_mentions_only_matches_whole_tokens() {
+        let (names, all) = MentionService::parse_mentions("hi @alice mail a@all.com @全体成员");
+        assert_eq!(names, vec!["alice".to_string()]);
+        assert!(all);
 
-impl Default for MentionService {
-    fn default() -> Self {
-        Self::new()
+        let (_, all) = MentionService::parse_mentions("see a@all.com");
+        assert!(!all);
     }
 }
@@ -34,10 +34,11 @@ use serde_json::Value;
 use tracing::{info, warn};
 
 use crate::error::ServerError;
-use crate::model::channel::MemberRole;
 use crate::model::message::Message;
 use crate::model::pts::UserMessageIndex;
-use crate::repository::{AtomicMessageCommitRequest, MessageRepository, PgMessageRepository};
+use crate::repository::{
+    AtomicMessageCommitRequest, BroadcastMessageAttrs, MessageRepository, PgMessageRepository,
+};
 use crate::service::message_history_service::MessageHistoryService;
 use crate::service::sync::get_global_sync_service;
 use crate::service::{ChannelService, OfflineQueueService, UnreadCountService};
@@ -176,6 +177,24 @@ impl MessageService {
     pub async fn send_message(
         &self,
         req: ServerSendMessageRequest,
+    ) -> anyhow::Result<ServerSendMessageResult> {
+        self.send_message_with_attrs(req, None).await
+    }
+
+    /// 广播频道发布：与 [`send_message`](Self::send_message) 同一链路，
+    /// 但广播属性（标题 / 紧急 / 过期）与消息在同一事务内写入。
+    pub async fn send_broadcast_message(
+        &self,
+        req: ServerSendMessageRequest,
+        attrs: BroadcastMessageAttrs,
+    ) -> anyhow::Result<ServerSendMessageResult> {
+        self.send_message_with_attrs(req, Some(attrs)).await
+    }
+
+    async fn send_message_with_attrs(
+        &self,
+        req: ServerSendMessageRequest,
+        broadcast_attrs: Option<BroadcastMessageAttrs>,
     ) -> anyhow::Result<ServerSendMessageResult> {
         let now = Utc::now();
         let message_id = crate::infra::next_message_id();
@@ -198,6 +217,12 @@ impl MessageService {
         let (_, commit_content) = canonical_event
             .to_legacy_commit(req.channel_id, req.channel_type)
             .map_err(|error| anyhow::anyhow!("构造兼容消息投影失败: {error}"))?;
+        // 服务端 / Bot 发出的消息同样遵守会话的阅后即焚计时
+        let expiry = crate::service::disappearing_service::expiry_arm_for_new_message(
+            req.channel_type,
+            &req.metadata,
+        )
+        .map_err(|error| anyhow::anyhow!("阅后即焚参数无效: {error}"))?;
         let message = Message {
             message_id,
             channel_id: req.channel_id,
@@ -226,6 +251,9 @@ impl MessageService {
                 channel_type: i16::from(req.channel_type),
                 event: canonical_event,
                 sender_username: None,
+                expiry,
+                broadcast_attrs,
+                mention_targets: Vec::new(),
             })
             .await
             .map_err(|error| anyhow::anyhow!("事务化写入服务端消息失败: {error}"))?;
@@ -381,7 +409,7 @@ impl MessageService {
 
     /// RPC 入口——带权限与时限校验的撤回（`rpc/message/revoke.rs` 使用）。
     ///
-    /// - 发送者或群主/管理员可撤回
+    /// - 发送者或持有 `can_revoke_any_message` 的成员（群主 / 被授权的管理员）可撤回
     /// - 普通发送者受 [`DEFAULT_REVOKE_TIME_LIMIT_SECS`] 限制
     /// - 权限通过后进入 [`revoke_message_core`] 执行完整副作用编排
     pub async fn revoke_message_with_auth(
@@ -407,8 +435,11 @@ impl MessageService {
             .get_channel(&channel_id)
             .await
             .ok()
-            .and_then(|ch| ch.members.get(&user_id).map(|m| m.role))
-            .map(|role| matches!(role, MemberRole::Owner | MemberRole::Admin))
+            .and_then(|ch| {
+                ch.members
+                    .get(&user_id)
+                    .map(|m| m.permissions.can_revoke_any_message)
+            })
             .unwrap_or(false);
 
         if !is_sender && !is_admin {
@@ -594,6 +625,18 @@ impl MessageService {
             .map(|dt| dt.timestamp_millis())
             .unwrap_or(revoke_ts_ms);
 
+        // 6. 出站 Webhook
+        crate::service::emit_webhook(
+            crate::service::webhook_service::WEBHOOK_EVENT_MESSAGE_REVOKED,
+            serde_json::json!({
+                "message_id": message_id,
+                "channel_id": channel_id,
+                "channel_type": channel_type,
+                "revoker_id": revoker_id,
+                "revoked_at": revoked_at_ms,
+            }),
+        );
+
         Ok(RevokedMessageSummary {
             message_id,
             channel_id,
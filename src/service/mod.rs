@@ -20,6 +20,7 @@ pub mod admin_service;
 pub mod attachment_authorization;
 pub mod file_claim_service;
 pub mod auth_service;
+pub mod broadcast_channel_service;
 pub mod channel_service; // ChannelService 在这里
 pub mod committed_timeline_delivery_service;
 pub mod entity_invalidation_publisher;
@@ -57,8 +58,16 @@ pub mod upload_token_service;
 pub mod sticker_service;
 // 新增黑名单服务
 pub mod blacklist_service;
+// Bot 平台 API（令牌 / 斜杠命令 / 内联按钮回调 / getUpdates）
+pub mod bot_service;
 // 新增二维码服务
 pub mod qrcode_service;
+// 二维码 Key 存储（内存 / Postgres + Redis 缓存）
+pub mod qrcode_store;
+// 内置账号两步验证（TOTP / 恢复码 / 登录 challenge）
+pub mod two_factor_service;
+// 内置账号找回密码（一次性令牌 + 可插拔投递）
+pub mod password_reset_service;
 // Web 扫码登录场景服务（spec QR_API §4）
 pub mod qr_login_service;
 // Web 扫码登录的实时推送通道（spec QR_API §5）
@@ -75,16 +84,34 @@ pub mod reaction_service;
 // 新增 @提及服务
 pub mod mention_service;
 // Room 订阅历史服务（Redis）
+pub mod room_chat_service;
 pub mod room_history_service;
 // 送达水位追踪服务
 pub mod delivery_tracker;
+// 出站 Webhook（订阅 / 签名 / 持久化重试）
+pub mod webhook_service;
+// 消息保留期（会话覆盖 / 超期清理）
+pub mod retention_service;
+// 阅后即焚（单条消息 TTL / 会话计时器 / 到期硬删除）
+pub mod disappearing_service;
+// 个人数据导出（后台生成 tar 归档 / 过期清理）
+pub mod data_export_service;
+// 用户自助注销（冷静期 / 最终注销）
+pub mod account_deletion_service;
+// 群慢速模式（成员发言间隔 / Redis 窗口）
+pub mod group_slow_mode_service;
 // Note: Channel Transfer is *not* a business service (server spec §1.4).
 // See `crate::channel_transfer` for the relay utilities.
 
+pub use account_deletion_service::{AccountDeletionService, AccountDeletionStatus};
 pub use admin_service::AdminService;
 pub use approval_service::{ApprovalService, JoinMethod, JoinRequest, JoinRequestStatus};
 pub use auth_service::AuthService;
 pub use blacklist_service::{BlacklistEntry, BlacklistService};
+pub use bot_service::BotService;
+pub use broadcast_channel_service::{
+    BroadcastChannelService, PublishChannelMessageRequest, PublishedChannelMessage,
+};
 pub use channel_service::{
     ChannelService, ChannelServiceConfig, ChannelServiceStats, EnhancedChannelItem,
     EnhancedChannelListResponse, LastMessagePreview,
@@ -93,12 +120,15 @@ pub use committed_timeline_delivery_service::{
     ClaimedDispatchRecipient, CommittedTimelineDeliveryService, DispatchOutboxStore,
     DispatchOutcome, DispatchRecipientState, RecipientDeliveryOutcome,
 };
+pub use data_export_service::{DataExportService, DataExportView};
 pub use delivery_tracker::DeliveryTracker;
+pub use disappearing_service::{ChannelDisappearing, DisappearingService};
 pub use entity_invalidation_publisher::EntityInvalidationPublisher;
 pub use file_service::{FileMetadata, FileService, FileType, FileUrlResponse};
 pub use friend_service::FriendService;
 pub use group_service::GroupService;
-pub use mention_service::MentionService;
+pub use group_slow_mode_service::{GroupSlowModeService, SlowModeAcquire, SlowModeWindow};
+pub use mention_service::{ChannelUnreadMentions, MentionService};
 pub use message_history_service::{
     ChannelMessageStats, MessageHistoryRecord, MessageHistoryService, MessageQueryParams,
     ReplyMessagePreview,
@@ -108,19 +138,35 @@ pub use message_service::{
 };
 pub use notification_service::NotificationService;
 pub use offline_queue_service::OfflineQueueService;
+pub use password_reset_service::{
+    HttpPasswordResetSender, LocalPasswordResetSender, PasswordResetDelivery, PasswordResetSender,
+    PasswordResetService, ResetChannel, UnconfiguredPasswordResetSender,
+};
 pub use presence_service::PresenceService;
-pub use privacy_service::{PrivacyService, PrivacySettingsUpdate};
+pub use privacy_service::{
+    PresenceVisibility, PrivacyService, PrivacySettingsUpdate, ProfileFieldVisibility,
+};
 pub use push_service::PushService;
 pub use qr_login_publisher::{PushOutcome, QrLoginPublisher};
 pub use qrcode_service::QRCodeService;
-pub use reaction_service::{Reaction, ReactionService, ReactionStats};
+pub use qrcode_store::{MemoryQRKeyStore, PersistentQRKeyStore, QRKeyStore};
+pub use reaction_service::{Reaction, ReactionKey, ReactionService, ReactionStats};
 pub use read_receipt_service::{GroupReadStats, ReadReceipt, ReadReceiptService};
 pub use read_state_service::{ChannelReadCursorRow, ReadPtsUpdateResult, ReadStateService};
+pub use retention_service::{ChannelRetention, RetentionService};
+pub use room_chat_service::{
+    KeywordRoomChatFilter, RoomChatFilter, RoomChatInfo, RoomChatMessage, RoomChatService,
+    RoomChatSettings, RoomChatVerdict,
+};
 pub use room_history_service::RoomHistoryService;
 pub use sticker_service::{Sticker, StickerPackage, StickerService};
+pub use two_factor_service::{
+    PendingLogin, SecondFactor, TwoFactorService, TwoFactorSetup, TwoFactorStatus,
+};
 pub use unread_count_service::UnreadCountService;
 pub use upload_token_service::{UploadToken, UploadTokenService};
 pub use user_service::{
     validate_phone_e164, CreateUserAdminParams, CreateUserOutcome, UpdateUserAdminParams,
     UserService,
 };
+pub use webhook_service::{emit_webhook, WebhookService};
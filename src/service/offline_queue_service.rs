@@ -337,6 +337,7 @@ mod tests {
             connection_timeout_secs: 5,
             command_timeout_ms: 5000,
             idle_timeout_secs: 300,
+            ..Default::default()
         };
         let redis_client = Arc::new(
             RedisClient::new(&redis_config)
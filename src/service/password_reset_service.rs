@@ -0,0 +1,438 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 内置账号找回密码
+//!
+//! - 申请：用户提交用户名 / 邮箱 / 手机号 → 生成一次性令牌（库里只存摘要）→ 经
+//!   [`PasswordResetSender`] 投递到账号绑定的邮箱或手机。账号不存在、未设密码或没有可投递
+//!   的联系方式时同样返回成功，不暴露账号是否存在
+//! - 重置：提交令牌与新密码，令牌核销与改写密码同一事务完成，再由调用方让旧会话失效
+//!
+//! 投递方式由配置选择（[`sender_from_config`]）：配置了网关地址时走
+//! [`HttpPasswordResetSender`]（邮件 / 短信网关）；[`LocalPasswordResetSender`] 只在显式打开
+//! 开发开关时启用；两者都没有时找回密码不可用（申请照常返回成功，令牌不会发出）。
+//! 任何投递方式都不得把令牌明文写进日志——日志里至多出现令牌摘要的前缀。
+
+use crate::config::AccountConfig;
+use crate::error::{Result, ServerError};
+use crate::model::user::User;
+use crate::repository::{hash_reset_token, PasswordResetRepository, UserRepository};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+/// 同一账号两次申请的最小间隔（秒），防止被人反复触发邮件 / 短信
+pub const RESET_REQUEST_COOLDOWN_SECS: i64 = 60;
+
+/// 令牌投递渠道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetChannel {
+    Email,
+    Sms,
+}
+
+impl ResetChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::Sms => "sms",
+        }
+    }
+}
+
+/// 一次令牌投递
+#[derive(Clone, Serialize)]
+pub struct PasswordResetDelivery {
+    pub user_id: u64,
+    pub channel: ResetChannel,
+    /// 邮箱地址或 E.164 手机号
+    pub destination: String,
+    /// 令牌明文（只在投递时出现）
+    pub token: String,
+    pub expires_at: i64,
+}
+
+impl PasswordResetDelivery {
+    /// 令牌摘要前缀，供日志关联使用（不可反推令牌）
+    pub fn token_fingerprint(&self) -> String {
+        hash_reset_token(&self.token)[..12].to_string()
+    }
+}
+
+/// Debug 输出不含令牌明文，避免 `{:?}` 把它带进日志
+impl std::fmt::Debug for PasswordResetDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PasswordResetDelivery")
+            .field("user_id", &self.user_id)
+            .field("channel", &self.channel)
+            .field("destination", &self.destination)
+            .field("token", &self.token_fingerprint())
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// 找回密码令牌投递（邮件 / 短信网关实现此 trait）
+#[async_trait]
+pub trait PasswordResetSender: Send + Sync {
+    async fn send(&self, delivery: &PasswordResetDelivery) -> Result<()>;
+}
+
+/// 网关请求超时
+const GATEWAY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 经 HTTP 网关投递：把 [`PasswordResetDelivery`] 以 JSON POST 到网关，
+/// 由网关按 `channel` 发邮件或短信。非 2xx 视为投递失败。
+pub struct HttpPasswordResetSender {
+    http: reqwest::Client,
+    url: String,
+    bearer_token: Option<String>,
+}
+
+impl HttpPasswordResetSender {
+    pub fn new(url: String, bearer_token: Option<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(GATEWAY_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            http,
+            url,
+            bearer_token,
+        }
+    }
+}
+
+#[async_trait]
+impl PasswordResetSender for HttpPasswordResetSender {
+    async fn send(&self, delivery: &PasswordResetDelivery) -> Result<()> {
+        let mut request = self.http.post(&self.url).json(delivery);
+        if let Some(token) = &self.bearer_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ServerError::Internal(format!("调用找回密码网关失败: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ServerError::Internal(format!(
+                "找回密码网关返回 {}",
+                status
+            )));
+        }
+        tracing::debug!(
+            "📨 找回密码令牌已交给网关: user_id={}, channel={}, token_fp={}",
+            delivery.user_id,
+            delivery.channel.as_str(),
+            delivery.token_fingerprint()
+        );
+        Ok(())
+    }
+}
+
+/// 未配置任何投递方式：一律投递失败（由 `request_reset` 记录并吞掉）
+pub struct UnconfiguredPasswordResetSender;
+
+#[async_trait]
+impl PasswordResetSender for UnconfiguredPasswordResetSender {
+    async fn send(&self, _delivery: &PasswordResetDelivery) -> Result<()> {
+        Err(ServerError::Internal(
+            "未配置找回密码投递网关（account.password_reset_gateway_url）".to_string(),
+        ))
+    }
+}
+
+/// 按 `[account]` 配置选择投递方式：网关优先；本地投递只在开发开关打开时启用。
+pub fn sender_from_config(config: &AccountConfig) -> Arc<dyn PasswordResetSender> {
+    if let Some(url) = &config.password_reset_gateway_url {
+        return Arc::new(HttpPasswordResetSender::new(
+            url.clone(),
+            config.password_reset_gateway_token.clone(),
+        ));
+    }
+    if config.password_reset_dev_local_sender {
+        tracing::warn!(
+            "⚠️ 找回密码使用本地投递（开发模式），令牌明文写入 outbox，生产环境不得开启"
+        );
+        return Arc::new(LocalPasswordResetSender::new(
+            config
+                .password_reset_outbox
+                .as_ref()
+                .map(std::path::PathBuf::from),
+        ));
+    }
+    tracing::warn!("⚠️ 未配置找回密码投递网关，找回密码令牌将不会发出");
+    Arc::new(UnconfiguredPasswordResetSender)
+}
+
+/// 本地投递（仅开发）：日志只记令牌摘要前缀；配置了 outbox 时以 JSON Lines 追加到文件
+///
+/// 🔴 outbox 文件中含令牌明文，只能在显式打开 `password_reset_dev_local_sender` 时使用。
+pub struct LocalPasswordResetSender {
+    outbox: Option<PathBuf>,
+}
+
+impl LocalPasswordResetSender {
+    pub fn new(outbox: Option<PathBuf>) -> Self {
+        Self { outbox }
+    }
+}
+
+#[async_trait]
+impl PasswordResetSender for LocalPasswordResetSender {
+    async fn send(&self, delivery: &PasswordResetDelivery) -> Result<()> {
+        tracing::info!(
+            "📨 找回密码令牌（本地投递）: user_id={}, channel={}, to={}, token_fp={}",
+            delivery.user_id,
+            delivery.channel.as_str(),
+            delivery.destination,
+            delivery.token_fingerprint()
+        );
+
+        let Some(path) = &self.outbox else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| ServerError::Internal(format!("创建 outbox 目录失败: {}", e)))?;
+        }
+        let mut line = serde_json::to_string(delivery)
+            .map_err(|e| ServerError::Serialization(format!("序列化找回密码投递失败: {}", e)))?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .map_err(|e| ServerError::Internal(format!("打开 outbox 文件失败: {}", e)))?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| ServerError::Internal(format!("写入 outbox 文件失败: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// 选择投递目标：按手机号找到的账号发短信，其余优先邮箱
+fn pick_destination(user: &User, matched_by_phone: bool) -> Option<(ResetChannel, String)> {
+    let email = user
+        .email
+        .clone()
+        .filter(|s| !s.is_empty())
+        .map(|s| (ResetChannel::Email, s));
+    let phone = user
+        .phone
+        .clone()
+        .filter(|s| !s.is_empty())
+        .map(|s| (ResetChannel::Sms, s));
+    if matched_by_phone {
+        phone.or(email)
+    } else {
+        email.or(phone)
+    }
+}
+
+/// 找回密码服务
+pub struct PasswordResetService {
+    repo: Arc<PasswordResetRepository>,
+    user_repository: Arc<UserRepository>,
+    sender: Arc<dyn PasswordResetSender>,
+    ttl_secs: u64,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        repo: Arc<PasswordResetRepository>,
+        user_repository: Arc<UserRepository>,
+        sender: Arc<dyn PasswordResetSender>,
+        ttl_secs: u64,
+    ) -> Self {
+        Self {
+            repo,
+            user_repository,
+            sender,
+            ttl_secs,
+        }
+    }
+
+    /// 按用户名 / 邮箱 / 手机号查找账号，返回 `(用户, 是否按手机号命中)`
+    async fn find_account(&self, account: &str) -> Result<Option<(User, bool)>> {
+        if account.starts_with('+') {
+            return Ok(self
+                .user_repository
+                .find_by_phone(account)
+                .await?
+                .map(|u| (u, true)));
+        }
+        let account = account.to_lowercase();
+        if let Some(user) = self.user_repository.find_by_username(&account).await? {
+            return Ok(Some((user, false)));
+        }
+        Ok(self
+            .user_repository
+            .find_by_email(&account)
+            .await?
+            .map(|u| (u, false)))
+    }
+
+    /// 申请重置：能投递时生成令牌并发送；任何情况下对调用方都表现一致
+    pub async fn request_reset(&self, account: &str) -> Result<()> {
+        let Some((user, matched_by_phone)) = self.find_account(account).await? else {
+            tracing::debug!("找回密码: 账号不存在 account={}", account);
+            return Ok(());
+        };
+        if user.password_hash.is_none() {
+            tracing::debug!("找回密码: 账号未设置密码 user_id={}", user.id);
+            return Ok(());
+        }
+        let Some((channel, destination)) = pick_destination(&user, matched_by_phone) else {
+            tracing::warn!("⚠️ 找回密码: 账号没有邮箱或手机号 user_id={}", user.id);
+            return Ok(());
+        };
+
+        let now = chrono::Utc::now().timestamp_millis();
+        if let Some(last) = self.repo.last_created_at(user.id).await? {
+            if now - last < RESET_REQUEST_COOLDOWN_SECS * 1000 {
+                tracing::debug!("找回密码: 申请过于频繁 user_id={}", user.id);
+                return Ok(());
+            }
+        }
+
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let expires_at = now + self.ttl_secs as i64 * 1000;
+        self.repo
+            .create(
+                user.id,
+                &hash_reset_token(&token),
+                channel.as_str(),
+                expires_at,
+            )
+            .await?;
+
+        // 投递失败不能透给调用方：只有真实账号才会走到这里，
+        // 报错等于告诉对方「这个账号存在」。记日志、作废刚生成的令牌，照常返回成功。
+        let delivery = PasswordResetDelivery {
+            user_id: user.id,
+            channel,
+            destination,
+            token,
+            expires_at,
+        };
+        if let Err(e) = self.sender.send(&delivery).await {
+            tracing::error!(
+                "❌ 找回密码令牌投递失败: user_id={}, channel={}, token_fp={}, err={}",
+                user.id,
+                channel.as_str(),
+                delivery.token_fingerprint(),
+                e
+            );
+            if let Err(e) = self.repo.revoke_for_user(user.id).await {
+                tracing::warn!(
+                    "⚠️ 作废未投递的找回密码令牌失败: user_id={}, err={}",
+                    user.id,
+                    e
+                );
+            }
+            return Ok(());
+        }
+        tracing::info!(
+            "✅ 找回密码令牌已投递: user_id={}, channel={}",
+            user.id,
+            channel.as_str()
+        );
+        Ok(())
+    }
+
+    /// 核销令牌并写入新密码摘要（同一事务），返回令牌所属用户；
+    /// 无效、过期或已用过返回 Unauthorized
+    pub async fn reset_password(&self, token: &str, new_password_hash: &str) -> Result<u64> {
+        self.repo
+            .consume_and_set_password(&hash_reset_token(token), new_password_hash)
+            .await?
+            .ok_or_else(|| ServerError::Unauthorized("重置链接无效或已过期".to_string()))
+    }
+
+    /// 密码已通过其它途径修改：作废用户尚未使用的令牌
+    pub async fn revoke_pending(&self, user_id: u64) -> Result<()> {
+        self.repo.revoke_for_user(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_destination() {
+        let mut user = User::new(1, "alice".to_string());
+        assert!(pick_destination(&user, false).is_none());
+
+        user.phone = Some("+8613800000000".to_string());
+        assert_eq!(pick_destination(&user, false).unwrap().0, ResetChannel::Sms);
+
+        user.email = Some("alice@example.com".to_string());
+        assert_eq!(
+            pick_destination(&user, false).unwrap(),
+            (ResetChannel::Email, "alice@example.com".to_string())
+        );
+        assert_eq!(pick_destination(&user, true).unwrap().0, ResetChannel::Sms);
+    }
+
+    #[test]
+    fn debug_output_never_contains_the_token() {
+        let delivery = PasswordResetDelivery {
+            user_id: 1,
+            channel: ResetChannel::Email,
+            destination: "alice@example.com".to_string(),
+            token: "0123456789abcdef0123456789abcdef".to_string(),
+            expires_at: 0,
+        };
+        let debug = format!("{:?}", delivery);
+        assert!(!debug.contains(&delivery.token));
+        assert!(debug.contains(&delivery.token_fingerprint()));
+        assert_eq!(delivery.token_fingerprint().len(), 12);
+    }
+
+    #[tokio::test]
+    async fn unconfigured_sender_fails_and_dev_sender_needs_the_flag() {
+        let delivery = PasswordResetDelivery {
+            user_id: 1,
+            channel: ResetChannel::Sms,
+            destination: "+8613800000000".to_string(),
+            token: "t".to_string(),
+            expires_at: 0,
+        };
+        assert!(UnconfiguredPasswordResetSender
+            .send(&delivery)
+            .await
+            .is_err());
+
+        // 默认配置既没网关也没开发开关：不得退回到本地投递
+        let sender = sender_from_config(&AccountConfig::default());
+        assert!(sender.send(&delivery).await.is_err());
+
+        let dev = AccountConfig {
+            password_reset_dev_local_sender: true,
+            ..AccountConfig::default()
+        };
+        assert!(sender_from_config(&dev).send(&delivery).await.is_ok());
+    }
+}
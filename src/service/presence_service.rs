@@ -18,7 +18,6 @@
 use msgtrans::SessionId;
 #[cfg(test)]
 use sqlx::postgres::PgPoolOptions;
-#[cfg(test)]
 use std::collections::HashMap;
 use std::sync::Arc;
 #[cfg(test)]
@@ -29,7 +28,7 @@ use crate::error::ServerError;
 use crate::infra::{ConnectionManager, PresenceTracker, SubscribeManager};
 #[cfg(test)]
 use crate::repository::PgChannelRepository;
-use crate::service::ChannelService;
+use crate::service::{ChannelService, PresenceVisibility, PrivacyService};
 use privchat_protocol::presence::{
     PresenceBatchStatusResponse, PresenceChangedNotification, PresenceSnapshot,
 };
@@ -46,6 +45,8 @@ pub struct PresenceService {
     channel_service: Arc<ChannelService>,
     subscribe_manager: Arc<SubscribeManager>,
     connection_manager: Arc<ConnectionManager>,
+    /// 用户的「最后上线 / 在线状态」可见性规则；未设置时不做隐私过滤（仅测试）
+    privacy_service: Option<Arc<PrivacyService>>,
     #[cfg(test)]
     test_visibility_overrides: Mutex<HashMap<(u64, u64), bool>>,
     #[cfg(test)]
@@ -66,6 +67,7 @@ impl PresenceService {
             channel_service,
             subscribe_manager,
             connection_manager,
+            privacy_service: None,
             #[cfg(test)]
             test_visibility_overrides: Mutex::new(HashMap::new()),
             #[cfg(test)]
@@ -75,11 +77,18 @@ impl PresenceService {
         }
     }
 
+    pub fn with_privacy_service(mut self, privacy_service: Arc<PrivacyService>) -> Self {
+        self.privacy_service = Some(privacy_service);
+        self
+    }
+
     /// 按 user_ids 获取当前在线状态。
     ///
     /// 权限判断基于关系/会话上下文，但不改变查询主键：
     /// - 自己总是可见
     /// - 与当前 viewer 已存在合法直聊 channel 的用户可见
+    ///
+    /// 可见的用户再按其隐私规则遮蔽在线状态 / 最后上线时间（见 [`mask_snapshot`]）。
     pub async fn batch_get_status(
         &self,
         viewer_user_id: u64,
@@ -117,6 +126,20 @@ impl PresenceService {
             item.device_count = conns.len() as u32;
         }
 
+        let owner_ids: Vec<u64> = items
+            .iter()
+            .map(|item| item.user_id)
+            .filter(|&user_id| user_id != viewer_user_id)
+            .collect();
+        let visibility = self
+            .presence_visibility_for_viewer(viewer_user_id, &owner_ids)
+            .await;
+        for item in items.iter_mut() {
+            if item.user_id != viewer_user_id {
+                mask_snapshot(item, visibility(item.user_id));
+            }
+        }
+
         PresenceBatchStatusResponse {
             items,
             denied_user_ids,
@@ -229,43 +252,66 @@ impl PresenceService {
             }
         }
 
-        let payload_bytes = serde_json::to_vec(&payload).map_err(|e| {
-            ServerError::Internal(format!("Serialize presence_changed payload failed: {}", e))
-        })?;
-
         let transport = self.connection_manager.transport_server.read().await;
         let Some(server) = transport.as_ref() else {
             return Ok(());
         };
 
+        // 订阅会话 → 订阅者 user_id。同一频道里不同订阅者看到的可能不一样
+        // （被拒绝名单挡住的人只能看到离线），所以按会话逐个遮蔽。
+        let mut channel_sessions = Vec::with_capacity(related_channels.len());
+        let mut viewer_of_session = HashMap::new();
         for channel_id in related_channels {
-            let publish_request = PublishRequest {
-                channel_id,
-                topic: Some("presence_changed".to_string()),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0),
-                payload: payload_bytes.clone(),
-                publisher: Some(user_id.to_string()),
-                server_message_id: None,
-            };
-
-            let encoded = privchat_protocol::encode_message(&publish_request).map_err(|e| {
-                ServerError::Protocol(format!("Encode presence_changed publish failed: {}", e))
-            })?;
-
             let sessions = self.subscribe_manager.get_channel_sessions(channel_id);
+            for session_id in &sessions {
+                if !viewer_of_session.contains_key(session_id) {
+                    let viewer = self
+                        .connection_manager
+                        .get_connection_by_session(session_id)
+                        .await
+                        .map(|connection| connection.user_id);
+                    viewer_of_session.insert(session_id.clone(), viewer);
+                }
+            }
+            channel_sessions.push((channel_id, sessions));
+        }
+        let mut viewer_ids: Vec<u64> = viewer_of_session
+            .values()
+            .flatten()
+            .copied()
+            .filter(|&viewer_id| viewer_id != user_id)
+            .collect();
+        viewer_ids.sort_unstable();
+        viewer_ids.dedup();
+        let audience = self.presence_audience(user_id, &viewer_ids).await;
+
+        for (channel_id, sessions) in channel_sessions {
             if sessions.is_empty() {
                 continue;
             }
 
+            // 每种可见程度只编码一次
+            let mut encoded_by_visibility: HashMap<PresenceVisibility, Vec<u8>> = HashMap::new();
             let mut delivered = 0usize;
             for session_id in sessions {
+                let visibility = match viewer_of_session.get(&session_id).copied().flatten() {
+                    Some(viewer_id) if viewer_id == user_id => PresenceVisibility::VISIBLE,
+                    Some(viewer_id) => audience(viewer_id),
+                    // 未认证的会话不该收到任何人的在线信息
+                    None => PresenceVisibility::HIDDEN,
+                };
+                let encoded = match encoded_by_visibility.get(&visibility) {
+                    Some(encoded) => encoded.clone(),
+                    None => {
+                        let encoded = encode_presence_publish(channel_id, &payload, visibility)?;
+                        encoded_by_visibility.insert(visibility, encoded.clone());
+                        encoded
+                    }
+                };
                 let options = msgtrans::SendOptions::new()
                     .biz_type(MessageType::PublishRequest as u8);
                 match server
-                    .send_with_options(session_id.clone(), encoded.clone().into(), options)
+                    .send_with_options(session_id.clone(), encoded.into(), options)
                     .await {
                     Ok(_) => delivered += 1,
                     Err(e) => {
@@ -304,6 +350,23 @@ impl PresenceService {
         let Some(server) = transport.as_ref() else {
             return;
         };
+        // 未认证的会话拿不到 viewer，也就不推任何人的在线信息
+        let Some(viewer_user_id) = self
+            .connection_manager
+            .get_connection_by_session(&session_id)
+            .await
+            .map(|connection| connection.user_id)
+        else {
+            return;
+        };
+        let owner_ids: Vec<u64> = user_ids
+            .iter()
+            .copied()
+            .filter(|&user_id| user_id != viewer_user_id)
+            .collect();
+        let visibility = self
+            .presence_visibility_for_viewer(viewer_user_id, &owner_ids)
+            .await;
         let snapshots = self.presence_tracker.batch_get_snapshots(user_ids).await;
         for mut snapshot in snapshots {
             // 与 batch_get_status / publish 同源：online/device_count 以 ConnectionManager 为权威。
@@ -320,21 +383,12 @@ impl PresenceService {
                 version,
                 snapshot,
             };
-            let Ok(payload_bytes) = serde_json::to_vec(&payload) else {
-                continue;
-            };
-            let publish_request = PublishRequest {
-                channel_id,
-                topic: Some("presence_changed".to_string()),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0),
-                payload: payload_bytes,
-                publisher: Some(user_id.to_string()),
-                server_message_id: None,
+            let user_visibility = if user_id == viewer_user_id {
+                PresenceVisibility::VISIBLE
+            } else {
+                visibility(user_id)
             };
-            let Ok(encoded) = privchat_protocol::encode_message(&publish_request) else {
+            let Ok(encoded) = encode_presence_publish(channel_id, &payload, user_visibility) else {
                 continue;
             };
             let options =
@@ -361,6 +415,68 @@ impl PresenceService {
         Ok(())
     }
 
+    /// viewer 对各 owner 在线信息的可见程度。隐私设置读取失败时全部按不可见处理。
+    async fn presence_visibility_for_viewer(
+        &self,
+        viewer_user_id: u64,
+        owner_ids: &[u64],
+    ) -> impl Fn(u64) -> PresenceVisibility {
+        let resolved = match &self.privacy_service {
+            None => None,
+            Some(_) if owner_ids.is_empty() => Some(HashMap::new()),
+            Some(privacy) => Some(
+                privacy
+                    .presence_visibility_for_viewer(viewer_user_id, owner_ids)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(
+                            "⚠️ PresenceService: 读取隐私设置失败 viewer={}: {}，按不可见处理",
+                            viewer_user_id, e
+                        );
+                        HashMap::new()
+                    }),
+            ),
+        };
+        move |owner_id| match &resolved {
+            None => PresenceVisibility::VISIBLE,
+            Some(map) => map
+                .get(&owner_id)
+                .copied()
+                .unwrap_or(PresenceVisibility::HIDDEN),
+        }
+    }
+
+    /// owner 的在线信息对各 viewer 的可见程度。隐私设置读取失败时全部按不可见处理。
+    async fn presence_audience(
+        &self,
+        owner_user_id: u64,
+        viewer_ids: &[u64],
+    ) -> impl Fn(u64) -> PresenceVisibility {
+        let resolved = match &self.privacy_service {
+            None => None,
+            Some(_) if viewer_ids.is_empty() => Some(HashMap::new()),
+            Some(privacy) => Some(
+                privacy
+                    .presence_audience(owner_user_id, viewer_ids)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(
+                            "⚠️ PresenceService: 读取隐私设置失败 owner={}: {}，按不可见处理",
+                            owner_user_id, e
+                        );
+                        HashMap::new()
+                    }),
+            ),
+        };
+        move |viewer_id| match &resolved {
+            None => PresenceVisibility::VISIBLE,
+            Some(map) => map
+                .get(&viewer_id)
+                .copied()
+                .unwrap_or(PresenceVisibility::HIDDEN),
+        }
+    }
+
     async fn can_view_target(&self, viewer_user_id: u64, target_user_id: u64) -> bool {
         #[cfg(test)]
         if let Some(allowed) = self
@@ -417,6 +533,43 @@ impl PresenceService {
     }
 }
 
+/// 按可见程度遮蔽快照：在线状态不可见时一律显示离线，最后上线时间不可见时置 0。
+fn mask_snapshot(snapshot: &mut PresenceSnapshot, visibility: PresenceVisibility) {
+    if !visibility.online {
+        snapshot.is_online = false;
+        snapshot.device_count = 0;
+    }
+    if !visibility.last_seen {
+        snapshot.last_seen_at = 0;
+    }
+}
+
+fn encode_presence_publish(
+    channel_id: u64,
+    payload: &PresenceChangedNotification,
+    visibility: PresenceVisibility,
+) -> Result<Vec<u8>, ServerError> {
+    let mut payload = payload.clone();
+    mask_snapshot(&mut payload.snapshot, visibility);
+    let payload_bytes = serde_json::to_vec(&payload).map_err(|e| {
+        ServerError::Internal(format!("Serialize presence_changed payload failed: {}", e))
+    })?;
+    let publish_request = PublishRequest {
+        channel_id,
+        topic: Some("presence_changed".to_string()),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        payload: payload_bytes,
+        publisher: Some(payload.user_id.to_string()),
+        server_message_id: None,
+    };
+    privchat_protocol::encode_message(&publish_request).map_err(|e| {
+        ServerError::Protocol(format!("Encode presence_changed publish failed: {}", e))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -17,10 +17,13 @@
 
 use crate::error::{Result, ServerError};
 use crate::infra::CacheManager;
-use crate::model::privacy::{UserDetailSource, UserPrivacySettings};
+use crate::model::privacy::{
+    PrivacyKey, UserDetailSource, UserPrivacySettings, Visibility, VisibilityRule,
+};
 use crate::service::ChannelService;
 use crate::service::FriendService;
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 
@@ -54,6 +57,98 @@ impl DetailAccessVerdict {
     }
 }
 
+/// 某个 viewer 对某个用户在线信息的可见程度（最后上线 / 在线状态两项规则的合成）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PresenceVisibility {
+    pub online: bool,
+    pub last_seen: bool,
+}
+
+impl PresenceVisibility {
+    pub const VISIBLE: Self = Self {
+        online: true,
+        last_seen: true,
+    };
+    pub const HIDDEN: Self = Self {
+        online: false,
+        last_seen: false,
+    };
+
+    pub fn between(
+        owner: &UserPrivacySettings,
+        viewer: &UserPrivacySettings,
+        is_contact: bool,
+    ) -> Self {
+        Self {
+            online: owner.visible_to(PrivacyKey::OnlineStatus, viewer, is_contact),
+            last_seen: owner.visible_to(PrivacyKey::LastSeen, viewer, is_contact),
+        }
+    }
+}
+
+/// viewer 视角下一批用户资料里受可见性规则控制的字段（头像 / 手机号）。
+///
+/// 🔴 凡是把**别人的资料**序列化给客户端的路径都经它投影，不各自判定：
+/// 规则最初只在 `user/detail` 与实体同步里生效，搜索、群成员、已读名单、
+/// 扫码名片照样把头像原样带出去——每多一个返回用户卡片的接口，就多一处漏口。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileFieldVisibility {
+    viewer_id: u64,
+    avatar: HashSet<u64>,
+    phone: HashSet<u64>,
+}
+
+impl ProfileFieldVisibility {
+    /// 由已读出的隐私设置与 viewer 的好友集合计算。查不到设置的 owner 按不可见处理。
+    pub fn between(
+        viewer_id: u64,
+        owner_ids: &[u64],
+        settings: &HashMap<u64, UserPrivacySettings>,
+        contacts: &HashSet<u64>,
+    ) -> Self {
+        let mut visibility = Self::hidden(viewer_id);
+        let Some(viewer) = settings.get(&viewer_id) else {
+            return visibility;
+        };
+        for owner_id in owner_ids {
+            let Some(owner) = settings.get(owner_id) else {
+                continue;
+            };
+            let is_contact = contacts.contains(owner_id);
+            if owner.visible_to(PrivacyKey::Avatar, viewer, is_contact) {
+                visibility.avatar.insert(*owner_id);
+            }
+            if owner.visible_to(PrivacyKey::Phone, viewer, is_contact) {
+                visibility.phone.insert(*owner_id);
+            }
+        }
+        visibility
+    }
+
+    /// 除本人外全部不可见。规则读不出来时的兜底——宁可少给，不能多给。
+    pub fn hidden(viewer_id: u64) -> Self {
+        Self {
+            viewer_id,
+            avatar: HashSet::new(),
+            phone: HashSet::new(),
+        }
+    }
+
+    fn allows(&self, set: &HashSet<u64>, owner_id: u64) -> bool {
+        owner_id == self.viewer_id || set.contains(&owner_id)
+    }
+
+    /// 按规则投影 owner 的头像：不可见时回 `None`
+    pub fn avatar(&self, owner_id: u64, avatar_url: Option<String>) -> Option<String> {
+        avatar_url.filter(|_| self.allows(&self.avatar, owner_id))
+    }
+
+    /// 按规则投影 owner 的手机号：不可见时回 `None`
+    pub fn phone(&self, owner_id: u64, phone: Option<String>) -> Option<String> {
+        phone.filter(|_| self.allows(&self.phone, owner_id))
+    }
+}
+
 /// 隐私和权限验证服务
 /// 维护期停写开关：`PRIVCHAT_PRIVACY_WRITES_FROZEN=1`。
 ///
@@ -467,6 +562,176 @@ impl PrivacyService {
         Ok(settings)
     }
 
+    /// 批量读取隐私设置，同样直读数据库。
+    ///
+    /// 查不到的用户按默认设置；单个用户的设置是脏数据时按「全部不可见」处理——
+    /// 可见性判定宁可多藏，也不能因为一行坏数据把限制放开或拖垮整批。
+    pub async fn get_privacy_settings_batch(
+        &self,
+        user_ids: &[u64],
+    ) -> Result<HashMap<u64, UserPrivacySettings>> {
+        let ids: Vec<i64> = user_ids.iter().map(|&id| id as i64).collect();
+        let rows: Vec<(i64, Option<serde_json::Value>)> = sqlx::query_as(
+            "SELECT user_id, privacy_settings FROM privchat_users WHERE user_id = ANY($1)",
+        )
+        .bind(&ids)
+        .fetch_all(self.channel_service.pool())
+        .await
+        .map_err(|e| ServerError::Database(format!("批量查询隐私设置失败: {e}")))?;
+
+        let mut stored: HashMap<u64, Option<serde_json::Value>> = rows
+            .into_iter()
+            .map(|(user_id, value)| (user_id as u64, value))
+            .collect();
+        Ok(user_ids
+            .iter()
+            .map(|&user_id| {
+                let settings =
+                    Self::settings_from_stored(user_id, stored.remove(&user_id).flatten())
+                        .unwrap_or_else(|e| {
+                            tracing::warn!("⚠️ {e}，按全部不可见处理");
+                            Self::hide_everything(user_id)
+                        });
+                (user_id, settings)
+            })
+            .collect())
+    }
+
+    /// 所有可见性规则都是「所有人不可见」的设置，用于脏数据兜底
+    fn hide_everything(user_id: u64) -> UserPrivacySettings {
+        let nobody = VisibilityRule {
+            visibility: Visibility::Nobody,
+            ..VisibilityRule::default()
+        };
+        let mut settings = UserPrivacySettings::new(user_id);
+        settings.last_seen = nobody.clone();
+        settings.online_status = nobody.clone();
+        settings.avatar = nobody.clone();
+        settings.phone = nobody.clone();
+        settings.read_receipts = nobody;
+        settings
+    }
+
+    /// 读取 anchor 与 others 的隐私设置以及 anchor 的好友集合
+    async fn load_relations(
+        &self,
+        anchor_id: u64,
+        others: &[u64],
+    ) -> Result<(HashMap<u64, UserPrivacySettings>, HashSet<u64>)> {
+        let mut ids: Vec<u64> = others.to_vec();
+        ids.push(anchor_id);
+        ids.sort_unstable();
+        ids.dedup();
+        let settings = self.get_privacy_settings_batch(&ids).await?;
+        let contacts: HashSet<u64> = self
+            .friend_service
+            .get_friends(anchor_id)
+            .await?
+            .into_iter()
+            .collect();
+        Ok((settings, contacts))
+    }
+
+    /// viewer 能否看到 owner 的某一项信息
+    pub async fn can_view(&self, owner_id: u64, viewer_id: u64, key: PrivacyKey) -> Result<bool> {
+        if owner_id == viewer_id {
+            return Ok(true);
+        }
+        let (settings, contacts) = self.load_relations(owner_id, &[viewer_id]).await?;
+        Ok(settings[&owner_id].visible_to(
+            key,
+            &settings[&viewer_id],
+            contacts.contains(&viewer_id),
+        ))
+    }
+
+    /// viewer 视角：owners 中哪些人的某一项信息对 viewer 可见
+    pub async fn visible_owners(
+        &self,
+        viewer_id: u64,
+        owner_ids: &[u64],
+        key: PrivacyKey,
+    ) -> Result<HashSet<u64>> {
+        let (settings, contacts) = self.load_relations(viewer_id, owner_ids).await?;
+        let viewer = &settings[&viewer_id];
+        Ok(owner_ids
+            .iter()
+            .copied()
+            .filter(|owner_id| {
+                settings[owner_id].visible_to(key, viewer, contacts.contains(owner_id))
+            })
+            .collect())
+    }
+
+    /// viewer 视角：各 owner 的在线信息可见程度
+    pub async fn presence_visibility_for_viewer(
+        &self,
+        viewer_id: u64,
+        owner_ids: &[u64],
+    ) -> Result<HashMap<u64, PresenceVisibility>> {
+        let (settings, contacts) = self.load_relations(viewer_id, owner_ids).await?;
+        let viewer = &settings[&viewer_id];
+        Ok(owner_ids
+            .iter()
+            .map(|owner_id| {
+                let visibility = PresenceVisibility::between(
+                    &settings[owner_id],
+                    viewer,
+                    contacts.contains(owner_id),
+                );
+                (*owner_id, visibility)
+            })
+            .collect())
+    }
+
+    /// viewer 视角：一批用户资料的头像 / 手机号可见性
+    pub async fn profile_visibility(
+        &self,
+        viewer_id: u64,
+        owner_ids: &[u64],
+    ) -> Result<ProfileFieldVisibility> {
+        let (settings, contacts) = self.load_relations(viewer_id, owner_ids).await?;
+        Ok(ProfileFieldVisibility::between(
+            viewer_id, owner_ids, &settings, &contacts,
+        ))
+    }
+
+    /// 同 [`Self::profile_visibility`]，规则读不出来时按全部不可见处理：
+    /// 展示类接口宁可少给一张头像，也不因此整个失败。
+    pub async fn profile_visibility_or_hidden(
+        &self,
+        viewer_id: u64,
+        owner_ids: &[u64],
+    ) -> ProfileFieldVisibility {
+        self.profile_visibility(viewer_id, owner_ids)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("⚠️ 读取资料可见性规则失败，按不可见处理: {}", e);
+                ProfileFieldVisibility::hidden(viewer_id)
+            })
+    }
+
+    /// owner 视角：owner 的在线信息对各 viewer 的可见程度（在线状态推送用）
+    pub async fn presence_audience(
+        &self,
+        owner_id: u64,
+        viewer_ids: &[u64],
+    ) -> Result<HashMap<u64, PresenceVisibility>> {
+        let (settings, contacts) = self.load_relations(owner_id, viewer_ids).await?;
+        let owner = &settings[&owner_id];
+        Ok(viewer_ids
+            .iter()
+            .map(|viewer_id| {
+                let visibility = PresenceVisibility::between(
+                    owner,
+                    &settings[viewer_id],
+                    contacts.contains(viewer_id),
+                );
+                (*viewer_id, visibility)
+            })
+            .collect())
+    }
+
     /// 更新隐私设置
     /// 更新隐私设置。**事务内 patch → 解析 → 提交。**
     ///
@@ -491,6 +756,7 @@ impl PrivacyService {
             ));
         }
 
+        let updates = updates.normalized(user_id)?;
         let patch = updates.to_patch_json();
 
         let mut tx = self
@@ -553,6 +819,11 @@ struct StoredPrivacySettings {
     allow_search_by_qrcode: Option<bool>,
     allow_view_by_non_friend: Option<bool>,
     allow_receive_message_from_non_friend: Option<bool>,
+    last_seen: Option<VisibilityRule>,
+    online_status: Option<VisibilityRule>,
+    avatar: Option<VisibilityRule>,
+    phone: Option<VisibilityRule>,
+    read_receipts: Option<VisibilityRule>,
     #[serde(default)]
     user_id: Option<u64>,
     #[serde(default)]
@@ -577,6 +848,11 @@ impl StoredPrivacySettings {
             allow_search_by_qrcode,
             allow_view_by_non_friend,
             allow_receive_message_from_non_friend,
+            last_seen,
+            online_status,
+            avatar,
+            phone,
+            read_receipts,
         );
     }
 }
@@ -592,9 +868,30 @@ pub struct PrivacySettingsUpdate {
     pub allow_search_by_qrcode: Option<bool>,
     pub allow_view_by_non_friend: Option<bool>,
     pub allow_receive_message_from_non_friend: Option<bool>,
+    pub last_seen: Option<VisibilityRule>,
+    pub online_status: Option<VisibilityRule>,
+    pub avatar: Option<VisibilityRule>,
+    pub phone: Option<VisibilityRule>,
+    pub read_receipts: Option<VisibilityRule>,
 }
 
 impl PrivacySettingsUpdate {
+    /// 规范化可见性规则的例外名单（去重、拒绝优先、去掉自己），超长报校验错误
+    fn normalized(mut self, user_id: u64) -> Result<Self> {
+        for rule in [
+            &mut self.last_seen,
+            &mut self.online_status,
+            &mut self.avatar,
+            &mut self.phone,
+            &mut self.read_receipts,
+        ] {
+            if let Some(value) = rule.take() {
+                *rule = Some(value.normalized(user_id).map_err(ServerError::Validation)?);
+            }
+        }
+        Ok(self)
+    }
+
     /// 只把**本次真正要改的字段**变成 JSON patch。
     ///
     /// 没设的字段不出现在 patch 里，因此 `privacy_settings || patch` 不会碰它们——
@@ -618,6 +915,17 @@ impl PrivacySettingsUpdate {
             allow_view_by_non_friend,
             allow_receive_message_from_non_friend,
         );
+        macro_rules! put_rule {
+            ($($field:ident),+ $(,)?) => {
+                $(if let Some(rule) = &self.$field {
+                    patch.insert(
+                        stringify!($field).to_string(),
+                        serde_json::to_value(rule).unwrap_or(serde_json::Value::Null),
+                    );
+                })+
+            };
+        }
+        put_rule!(last_seen, online_status, avatar, phone, read_receipts);
         serde_json::Value::Object(patch)
     }
 }
@@ -953,4 +1261,105 @@ mod tests {
             .await;
         cleanup(&svc, channel_id, &[owner, member, plain]).await;
     }
+
+    fn settings_with(user_id: u64, key: PrivacyKey, rule: VisibilityRule) -> UserPrivacySettings {
+        let mut patch = serde_json::Map::new();
+        patch.insert(
+            key.as_str().to_string(),
+            serde_json::to_value(rule).unwrap(),
+        );
+        PrivacyService::settings_from_stored(user_id, Some(serde_json::Value::Object(patch)))
+            .expect("parse visibility rule")
+    }
+
+    #[test]
+    fn visibility_rule_exceptions_override_base_visibility() {
+        let rule = VisibilityRule {
+            visibility: Visibility::Contacts,
+            allow_user_ids: vec![3],
+            deny_user_ids: vec![2],
+        };
+        assert!(rule.allows(1, true));
+        assert!(!rule.allows(2, true), "拒绝名单优先于好友");
+        assert!(rule.allows(3, false), "允许名单放行非好友");
+        assert!(!rule.allows(4, false));
+
+        let normalized = VisibilityRule {
+            visibility: Visibility::Nobody,
+            allow_user_ids: vec![5, 5, 6, 9],
+            deny_user_ids: vec![6, 0],
+        }
+        .normalized(9)
+        .unwrap();
+        assert_eq!(normalized.allow_user_ids, vec![5]);
+        assert_eq!(normalized.deny_user_ids, vec![6]);
+    }
+
+    #[test]
+    fn last_seen_and_read_receipts_are_reciprocal_but_avatar_is_not() {
+        let hidden = VisibilityRule {
+            visibility: Visibility::Nobody,
+            ..VisibilityRule::default()
+        };
+        let open = UserPrivacySettings::new(1);
+
+        let hides_last_seen = settings_with(2, PrivacyKey::LastSeen, hidden.clone());
+        assert!(!hides_last_seen.visible_to(PrivacyKey::LastSeen, &open, true));
+        assert!(
+            !open.visible_to(PrivacyKey::LastSeen, &hides_last_seen, true),
+            "隐藏自己的最后上线时间后也看不到别人的"
+        );
+
+        let hides_receipts = settings_with(3, PrivacyKey::ReadReceipts, hidden.clone());
+        assert!(!open.visible_to(PrivacyKey::ReadReceipts, &hides_receipts, false));
+
+        let hides_avatar = settings_with(4, PrivacyKey::Avatar, hidden);
+        assert!(!hides_avatar.visible_to(PrivacyKey::Avatar, &open, true));
+        assert!(open.visible_to(PrivacyKey::Avatar, &hides_avatar, true));
+        assert!(hides_avatar.visible_to(PrivacyKey::Avatar, &hides_avatar, false));
+    }
+
+    #[test]
+    fn profile_fields_follow_each_owners_rule_and_default_to_hidden() {
+        let viewer = UserPrivacySettings::new(1);
+        let open = UserPrivacySettings::new(2);
+        let hides_avatar = settings_with(
+            3,
+            PrivacyKey::Avatar,
+            VisibilityRule {
+                visibility: Visibility::Nobody,
+                ..VisibilityRule::default()
+            },
+        );
+        let settings: HashMap<u64, UserPrivacySettings> =
+            [(1, viewer), (2, open), (3, hides_avatar)]
+                .into_iter()
+                .collect();
+        let contacts: HashSet<u64> = [2].into_iter().collect();
+        let visibility = ProfileFieldVisibility::between(1, &[2, 3, 4], &settings, &contacts);
+
+        let url = || Some("https://cdn/a.png".to_string());
+        let phone = || Some("+8613800000000".to_string());
+        assert_eq!(visibility.avatar(2, url()), url());
+        assert_eq!(visibility.phone(2, phone()), phone(), "好友可见手机号");
+        assert_eq!(visibility.avatar(3, url()), None);
+        assert_eq!(visibility.phone(3, phone()), None, "非好友默认看不到手机号");
+        assert_eq!(visibility.avatar(4, url()), None, "没读到设置的按不可见");
+        assert_eq!(visibility.avatar(1, url()), url(), "本人总是可见");
+
+        let hidden = ProfileFieldVisibility::hidden(1);
+        assert_eq!(hidden.avatar(2, url()), None);
+        assert_eq!(hidden.phone(1, phone()), phone());
+    }
+
+    #[test]
+    fn phone_defaults_to_contacts_and_bad_rules_are_rejected() {
+        let settings = PrivacyService::settings_from_stored(7, None).unwrap();
+        let stranger = UserPrivacySettings::new(8);
+        assert!(!settings.visible_to(PrivacyKey::Phone, &stranger, false));
+        assert!(settings.visible_to(PrivacyKey::Phone, &stranger, true));
+
+        let dirty = serde_json::json!({ "last_seen": { "visibility": "friends_of_friends" } });
+        assert!(PrivacyService::settings_from_stored(7, Some(dirty)).is_err());
+    }
 }
@@ -649,6 +649,7 @@ mod tests {
             connection_timeout_secs: 2,
             command_timeout_ms: 2000,
             idle_timeout_secs: 60,
+            ..Default::default()
         };
         let redis = match crate::infra::redis::RedisClient::new(&cfg).await {
             Ok(r) => std::sync::Arc::new(r),
@@ -16,12 +16,18 @@
 // limitations under the License.
 
 use chrono::{Duration, Utc};
-use dashmap::DashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 
 use crate::error::ServerError;
 use crate::model::{QRKeyOptions, QRKeyRecord, QRScanLog, QRType};
+use crate::service::qrcode_store::{MemoryQRKeyStore, QRKeyStore};
+
+/// 过期 Key 在过期后保留的天数（期间扫描仍能给出「已过期」而不是「不存在」）
+const QR_EXPIRED_KEY_RETENTION_DAYS: i64 = 30;
+/// 扫描日志保留天数
+const QR_SCAN_LOG_RETENTION_DAYS: i64 = 180;
+/// 过期 Key / 旧扫描日志清理间隔
+pub const QR_PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
 
 /// QR 码服务
 ///
@@ -33,28 +39,22 @@ use crate::model::{QRKeyOptions, QRKeyRecord, QRScanLog, QRType};
 /// - 扫码登录（Auth）
 /// - 其他功能（Feature）
 ///
-/// 性能优化：使用 DashMap 替代 Arc<RwLock<HashMap>>，减少锁竞争
+/// Key 与扫描日志经 [`QRKeyStore`] 持久化：生产环境为 Postgres + Redis 缓存
+/// （重启不丢、跨节点可解析），`new()` 使用内存存储，仅供测试。
 #[derive(Clone)]
 pub struct QRCodeService {
-    /// QR Key 存储：qr_key -> QRKeyRecord
-    /// TODO: 后续改为 Redis 或 PostgreSQL
-    storage: DashMap<String, QRKeyRecord>,
-
-    /// 反向索引：target_id -> qr_key（用于快速查找和撤销）
-    target_index: DashMap<String, String>,
-
-    /// 扫描日志存储（Vec 需要 RwLock，因为需要追加操作）
-    scan_logs: Arc<RwLock<Vec<QRScanLog>>>,
+    store: Arc<dyn QRKeyStore>,
 }
 
 impl QRCodeService {
-    /// 创建新的 QR 码服务
+    /// 创建新的 QR 码服务（内存存储）
     pub fn new() -> Self {
-        Self {
-            storage: DashMap::new(),
-            target_index: DashMap::new(),
-            scan_logs: Arc::new(RwLock::new(Vec::new())),
-        }
+        Self::with_store(Arc::new(MemoryQRKeyStore::new()))
+    }
+
+    /// 使用指定存储（生产环境传 `PersistentQRKeyStore`）
+    pub fn with_store(store: Arc<dyn QRKeyStore>) -> Self {
+        Self { store }
     }
 
     /// 生成新的 QR Key
@@ -105,11 +105,8 @@ impl QRCodeService {
             metadata: options.metadata,
         };
 
-        // 5. 保存记录（DashMap 无需显式锁）
-        self.storage.insert(qr_key.clone(), record.clone());
-
-        // 6. 更新反向索引
-        self.target_index.insert(target_id.clone(), qr_key.clone());
+        // 5. 保存记录
+        self.store.insert(&record).await?;
 
         tracing::info!(
             "✅ QR Key 生成成功: type={}, target={}, qr_key={}",
@@ -136,10 +133,11 @@ impl QRCodeService {
         scanner_id: u64,
         token: Option<&str>,
     ) -> Result<QRKeyRecord, ServerError> {
-        // 1. 查找记录（DashMap 使用 get_mut 获取可变引用）
-        let mut record = self
-            .storage
-            .get_mut(qr_key)
+        // 1. 查找记录
+        let record = self
+            .store
+            .get(qr_key)
+            .await?
             .ok_or_else(|| ServerError::NotFound("QR Key 不存在或已失效".to_string()))?;
 
         // 2. 验证状态
@@ -176,8 +174,12 @@ impl QRCodeService {
             }
         }
 
-        // 6. 更新使用次数
-        record.used_count += 1;
+        // 6. 原子更新使用次数（并发扫描时以存储层判定为准，避免限次二维码超发）
+        let Some(record) = self.store.consume(qr_key, Utc::now()).await? else {
+            self.log_scan_failure(qr_key, &scanner_id.to_string(), "QR Key 已失效")
+                .await;
+            return Err(ServerError::BadRequest("QR Key 已失效".to_string()));
+        };
 
         // 7. 记录扫描日志（审计）
         self.log_scan_success(qr_key, &scanner_id.to_string()).await;
@@ -189,7 +191,7 @@ impl QRCodeService {
             record.used_count
         );
 
-        Ok(record.clone())
+        Ok(record)
     }
 
     /// 刷新 QR Key（撤销旧的，生成新的）
@@ -238,80 +240,90 @@ impl QRCodeService {
 
     /// 撤销指定的 QR Key
     pub async fn revoke(&self, qr_key: &str) -> Result<(), ServerError> {
-        let mut record = self
-            .storage
-            .get_mut(qr_key)
+        self.store
+            .revoke(qr_key)
+            .await?
             .ok_or_else(|| ServerError::NotFound("QR Key 不存在".to_string()))?;
 
-        record.revoked = true;
-
         tracing::info!("✅ QR Key 已撤销: qr_key={}", qr_key);
 
         Ok(())
     }
 
-    /// 撤销指定目标的所有 QR Key
+    /// 撤销指定目标的当前 QR Key
     pub async fn revoke_by_target(&self, target_id: &str) -> Result<String, ServerError> {
-        // 从反向索引中查找
-        if let Some(qr_key_entry) = self.target_index.get(target_id) {
-            let qr_key = qr_key_entry.value().clone();
-            drop(qr_key_entry);
-            if let Some(mut record) = self.storage.get_mut(&qr_key) {
-                record.revoked = true;
-                drop(record);
-                self.target_index.remove(target_id);
-
-                tracing::info!(
-                    "✅ 撤销目标的 QR Key: target={}, qr_key={}",
-                    target_id,
-                    qr_key
-                );
-
-                return Ok(qr_key);
-            }
-        }
+        let record = self
+            .store
+            .find_active_by_target(target_id)
+            .await?
+            .ok_or_else(|| ServerError::NotFound("未找到该目标的 QR Key".to_string()))?;
+        self.store.revoke(&record.qr_key).await?;
 
-        Err(ServerError::NotFound("未找到该目标的 QR Key".to_string()))
+        tracing::info!(
+            "✅ 撤销目标的 QR Key: target={}, qr_key={}",
+            target_id,
+            record.qr_key
+        );
+
+        Ok(record.qr_key)
     }
 
-    /// 列出指定用户创建的所有 QR Keys
+    /// 列出指定用户创建的所有 QR Keys（新 -> 旧）
     pub async fn list_by_creator(
         &self,
         creator_id: &str,
         include_revoked: bool,
-    ) -> Vec<QRKeyRecord> {
-        self.storage
-            .iter()
-            .filter(|entry| {
-                let record = entry.value();
-                record.creator_id == creator_id && (include_revoked || !record.revoked)
-            })
-            .map(|entry| entry.value().clone())
-            .collect()
+    ) -> Result<Vec<QRKeyRecord>, ServerError> {
+        self.store
+            .list_by_creator(creator_id, include_revoked)
+            .await
     }
 
     /// 获取指定 QR Key 的详细信息
-    pub async fn get(&self, qr_key: &str) -> Option<QRKeyRecord> {
-        self.storage.get(qr_key).map(|entry| entry.value().clone())
+    pub async fn get(&self, qr_key: &str) -> Result<Option<QRKeyRecord>, ServerError> {
+        self.store.get(qr_key).await
     }
 
     /// 获取指定目标的当前 QR Key
-    pub async fn get_by_target(&self, target_id: &str) -> Option<QRKeyRecord> {
-        if let Some(qr_key_entry) = self.target_index.get(target_id) {
-            let qr_key = qr_key_entry.value();
-            return self.storage.get(qr_key).map(|entry| entry.value().clone());
-        }
-        None
+    pub async fn get_by_target(&self, target_id: &str) -> Result<Option<QRKeyRecord>, ServerError> {
+        self.store.find_active_by_target(target_id).await
     }
 
-    /// 获取扫描日志
-    pub async fn get_scan_logs(&self, qr_key: &str) -> Vec<QRScanLog> {
-        let scan_logs = self.scan_logs.read().await;
-        scan_logs
-            .iter()
-            .filter(|log| log.qr_key == qr_key)
-            .cloned()
-            .collect()
+    /// 获取扫描日志（新 -> 旧）
+    pub async fn get_scan_logs(
+        &self,
+        qr_key: &str,
+        limit: usize,
+    ) -> Result<Vec<QRScanLog>, ServerError> {
+        self.store.list_scan_logs(qr_key, limit).await
+    }
+
+    /// 清理过期超过保留期的 Key 与旧扫描日志，返回 (Key 数, 日志条数)
+    pub async fn purge_expired(&self) -> Result<(u64, u64), ServerError> {
+        let now = Utc::now();
+        self.store
+            .purge(
+                now - Duration::days(QR_EXPIRED_KEY_RETENTION_DAYS),
+                now - Duration::days(QR_SCAN_LOG_RETENTION_DAYS),
+            )
+            .await
+    }
+
+    /// 周期性执行 [`Self::purge_expired`]
+    pub fn spawn_purger(self: Arc<Self>, interval: std::time::Duration) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            loop {
+                tick.tick().await;
+                match self.purge_expired().await {
+                    Ok((0, 0)) => {}
+                    Ok((keys, logs)) => {
+                        tracing::info!("🧹 二维码清理：过期 Key {} 个，扫描日志 {} 条", keys, logs)
+                    }
+                    Err(e) => tracing::warn!("⚠️ 二维码清理失败: {}", e),
+                }
+            }
+        });
     }
 
     /// 生成随机 QR Key（12位字母数字）
@@ -338,8 +350,9 @@ impl QRCodeService {
             error: None,
         };
 
-        let mut scan_logs = self.scan_logs.write().await;
-        scan_logs.push(log);
+        if let Err(e) = self.store.append_scan_log(&log).await {
+            tracing::warn!("⚠️ QR 扫描日志写入失败: qr_key={}, error={}", qr_key, e);
+        }
 
         tracing::info!(
             "📱 QR Code Scanned: qr_key={}, scanner={}",
@@ -358,8 +371,9 @@ impl QRCodeService {
             error: Some(error.to_string()),
         };
 
-        let mut scan_logs = self.scan_logs.write().await;
-        scan_logs.push(log);
+        if let Err(e) = self.store.append_scan_log(&log).await {
+            tracing::warn!("⚠️ QR 扫描日志写入失败: qr_key={}, error={}", qr_key, e);
+        }
 
         tracing::warn!(
             "⚠️ QR Code Scan Failed: qr_key={}, scanner={}, error={}",
@@ -463,11 +477,11 @@ mod tests {
         assert_ne!(new_qr_key, old_qr_key);
 
         // 旧的应该被撤销
-        let old_record = service.get(&old_qr_key).await.unwrap();
+        let old_record = service.get(&old_qr_key).await.unwrap().unwrap();
         assert!(old_record.revoked);
 
         // 新的应该有效
-        let new_record = service.get(&new_qr_key).await.unwrap();
+        let new_record = service.get(&new_qr_key).await.unwrap().unwrap();
         assert!(!new_record.revoked);
     }
 
@@ -496,4 +510,91 @@ mod tests {
         let result = service.resolve(&record.qr_key, 1001, None).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_scan_logs_record_success_and_failure() {
+        let service = QRCodeService::new();
+
+        let record = service
+            .generate(
+                QRType::Group,
+                "group_456".to_string(),
+                "alice".to_string(),
+                QRKeyOptions {
+                    max_usage: Some(2),
+                    metadata: json!({ "token": "t0k" }),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        service
+            .resolve(&record.qr_key, 1001, Some("t0k"))
+            .await
+            .unwrap();
+        assert!(service.resolve(&record.qr_key, 1002, None).await.is_err());
+        let second = service
+            .resolve(&record.qr_key, 1003, Some("t0k"))
+            .await
+            .unwrap();
+        assert_eq!(second.used_count, 2);
+        assert!(service
+            .resolve(&record.qr_key, 1004, Some("t0k"))
+            .await
+            .is_err());
+
+        // 新 -> 旧
+        let logs = service.get_scan_logs(&record.qr_key, 10).await.unwrap();
+        let outcomes: Vec<(&str, bool)> = logs
+            .iter()
+            .map(|log| (log.scanner_id.as_str(), log.success))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("1004", false),
+                ("1003", true),
+                ("1002", false),
+                ("1001", true)
+            ]
+        );
+        assert_eq!(
+            service
+                .get_scan_logs(&record.qr_key, 1)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_by_target_only_finds_active_key() {
+        let service = QRCodeService::new();
+
+        let record = service
+            .generate(
+                QRType::User,
+                "bob".to_string(),
+                "bob".to_string(),
+                QRKeyOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get_by_target("bob").await.unwrap().unwrap().qr_key,
+            record.qr_key
+        );
+
+        assert_eq!(
+            service.revoke_by_target("bob").await.unwrap(),
+            record.qr_key
+        );
+        assert!(service.get_by_target("bob").await.unwrap().is_none());
+        assert!(matches!(
+            service.revoke_by_target("bob").await,
+            Err(ServerError::NotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,330 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 二维码 Key 存储
+//!
+//! Key 与扫描日志只放在进程内存里时，重启会让所有已打印的群邀请 / 名片二维码失效，
+//! 在 A 节点生成的 Key 也无法在 B 节点解析。生产环境用 Postgres 为真源、Redis 缓存热点 Key；
+//! 内存实现仅用于测试和单节点开发环境。
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::error::{Result, ServerError};
+use crate::infra::redis::RedisClient;
+use crate::model::{QRKeyRecord, QRScanLog};
+use crate::repository::QRCodeRepository;
+
+/// Redis 缓存单个 Key 的最长时间（秒）；有过期时间的 Key 不会缓存到过期之后
+const QR_KEY_CACHE_TTL_SECS: i64 = 300;
+
+/// 二维码 Key / 扫描日志存储
+#[async_trait]
+pub trait QRKeyStore: Send + Sync {
+    async fn insert(&self, record: &QRKeyRecord) -> Result<()>;
+
+    async fn get(&self, qr_key: &str) -> Result<Option<QRKeyRecord>>;
+
+    /// 目标当前（最新且未撤销）的 Key
+    async fn find_active_by_target(&self, target_id: &str) -> Result<Option<QRKeyRecord>>;
+
+    /// 原子占用一次使用次数：仅在未撤销、未过期、未达上限时 +1 并返回更新后的记录
+    async fn consume(&self, qr_key: &str, now: DateTime<Utc>) -> Result<Option<QRKeyRecord>>;
+
+    /// 撤销；Key 不存在返回 None
+    async fn revoke(&self, qr_key: &str) -> Result<Option<QRKeyRecord>>;
+
+    async fn list_by_creator(
+        &self,
+        creator_id: &str,
+        include_revoked: bool,
+    ) -> Result<Vec<QRKeyRecord>>;
+
+    async fn append_scan_log(&self, log: &QRScanLog) -> Result<()>;
+
+    /// 某个 Key 的扫描日志（新 -> 旧）
+    async fn list_scan_logs(&self, qr_key: &str, limit: usize) -> Result<Vec<QRScanLog>>;
+
+    /// 删除过期早于 `expired_before` 的 Key 及其日志、早于 `scanned_before` 的扫描日志，
+    /// 返回 (Key 数, 日志条数)
+    async fn purge(
+        &self,
+        expired_before: DateTime<Utc>,
+        scanned_before: DateTime<Utc>,
+    ) -> Result<(u64, u64)>;
+}
+
+/// 内存存储
+///
+/// 重启即丢失，也不跨节点共享，仅用于测试和单节点开发环境。
+#[derive(Default)]
+pub struct MemoryQRKeyStore {
+    /// qr_key -> QRKeyRecord
+    storage: DashMap<String, QRKeyRecord>,
+    /// target_id -> 最新生成的 qr_key
+    target_index: DashMap<String, String>,
+    scan_logs: RwLock<Vec<QRScanLog>>,
+}
+
+impl MemoryQRKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl QRKeyStore for MemoryQRKeyStore {
+    async fn insert(&self, record: &QRKeyRecord) -> Result<()> {
+        self.storage.insert(record.qr_key.clone(), record.clone());
+        self.target_index
+            .insert(record.target_id.clone(), record.qr_key.clone());
+        Ok(())
+    }
+
+    async fn get(&self, qr_key: &str) -> Result<Option<QRKeyRecord>> {
+        Ok(self.storage.get(qr_key).map(|entry| entry.value().clone()))
+    }
+
+    async fn find_active_by_target(&self, target_id: &str) -> Result<Option<QRKeyRecord>> {
+        let Some(qr_key) = self.target_index.get(target_id).map(|e| e.value().clone()) else {
+            return Ok(None);
+        };
+        Ok(self
+            .storage
+            .get(&qr_key)
+            .filter(|record| !record.revoked)
+            .map(|record| record.value().clone()))
+    }
+
+    async fn consume(&self, qr_key: &str, now: DateTime<Utc>) -> Result<Option<QRKeyRecord>> {
+        let Some(mut record) = self.storage.get_mut(qr_key) else {
+            return Ok(None);
+        };
+        let expired = record.expire_at.is_some_and(|expire_at| now > expire_at);
+        if record.revoked || expired || record.is_usage_exceeded() {
+            return Ok(None);
+        }
+        record.used_count += 1;
+        Ok(Some(record.clone()))
+    }
+
+    async fn revoke(&self, qr_key: &str) -> Result<Option<QRKeyRecord>> {
+        Ok(self.storage.get_mut(qr_key).map(|mut record| {
+            record.revoked = true;
+            record.clone()
+        }))
+    }
+
+    async fn list_by_creator(
+        &self,
+        creator_id: &str,
+        include_revoked: bool,
+    ) -> Result<Vec<QRKeyRecord>> {
+        let mut records: Vec<QRKeyRecord> = self
+            .storage
+            .iter()
+            .filter(|entry| {
+                let record = entry.value();
+                record.creator_id == creator_id && (include_revoked || !record.revoked)
+            })
+            .map(|entry| entry.value().clone())
+            .collect();
+        records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(records)
+    }
+
+    async fn append_scan_log(&self, log: &QRScanLog) -> Result<()> {
+        self.scan_logs.write().await.push(log.clone());
+        Ok(())
+    }
+
+    async fn list_scan_logs(&self, qr_key: &str, limit: usize) -> Result<Vec<QRScanLog>> {
+        let scan_logs = self.scan_logs.read().await;
+        Ok(scan_logs
+            .iter()
+            .rev()
+            .filter(|log| log.qr_key == qr_key)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn purge(
+        &self,
+        expired_before: DateTime<Utc>,
+        scanned_before: DateTime<Utc>,
+    ) -> Result<(u64, u64)> {
+        let expired: Vec<String> = self
+            .storage
+            .iter()
+            .filter(|entry| entry.expire_at.is_some_and(|t| t < expired_before))
+            .map(|entry| entry.key().clone())
+            .collect();
+        for qr_key in &expired {
+            self.storage.remove(qr_key);
+        }
+        self.target_index
+            .retain(|_, qr_key| self.storage.contains_key(qr_key.as_str()));
+
+        let mut scan_logs = self.scan_logs.write().await;
+        let before = scan_logs.len();
+        scan_logs.retain(|log| {
+            log.scanned_at >= scanned_before && self.storage.contains_key(&log.qr_key)
+        });
+        Ok((expired.len() as u64, (before - scan_logs.len()) as u64))
+    }
+}
+
+/// Postgres 为真源 + Redis 缓存
+///
+/// 读 Key 先查缓存；撤销、占用次数等写操作落库后用返回的最新行回写缓存，
+/// 其他节点随即读到撤销状态。缓存读写失败只告警，回落到数据库。
+/// 使用次数以数据库条件 UPDATE 为准，缓存中的 `used_count` 仅供展示。
+pub struct PersistentQRKeyStore {
+    repo: Arc<QRCodeRepository>,
+    redis: Arc<RedisClient>,
+}
+
+impl PersistentQRKeyStore {
+    pub fn new(repo: Arc<QRCodeRepository>, redis: Arc<RedisClient>) -> Self {
+        Self { repo, redis }
+    }
+
+    fn cache_key(qr_key: &str) -> String {
+        format!("privchat:qrcode:key:{}", qr_key)
+    }
+
+    async fn cache_put(&self, record: &QRKeyRecord) {
+        let mut ttl = QR_KEY_CACHE_TTL_SECS;
+        if let Some(expire_at) = record.expire_at {
+            ttl = ttl.min((expire_at - Utc::now()).num_seconds());
+        }
+        if ttl <= 0 {
+            self.cache_del(&record.qr_key).await;
+            return;
+        }
+        let payload = match serde_json::to_string(record) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️ QR Key 缓存序列化失败 qr_key={}: {}", record.qr_key, e);
+                return;
+            }
+        };
+        if let Err(e) = self
+            .redis
+            .setex(&Self::cache_key(&record.qr_key), ttl as usize, &payload)
+            .await
+        {
+            warn!("⚠️ QR Key 写入缓存失败 qr_key={}: {}", record.qr_key, e);
+        }
+    }
+
+    async fn cache_del(&self, qr_key: &str) {
+        if let Err(e) = self.redis.del(&Self::cache_key(qr_key)).await {
+            warn!("⚠️ QR Key 删除缓存失败 qr_key={}: {}", qr_key, e);
+        }
+    }
+}
+
+#[async_trait]
+impl QRKeyStore for PersistentQRKeyStore {
+    async fn insert(&self, record: &QRKeyRecord) -> Result<()> {
+        self.repo.insert(record).await?;
+        self.cache_put(record).await;
+        Ok(())
+    }
+
+    async fn get(&self, qr_key: &str) -> Result<Option<QRKeyRecord>> {
+        match self.redis.get(&Self::cache_key(qr_key)).await {
+            Ok(Some(raw)) => match serde_json::from_str::<QRKeyRecord>(&raw) {
+                Ok(record) => return Ok(Some(record)),
+                Err(e) => warn!("⚠️ QR Key 缓存反序列化失败 qr_key={}: {}", qr_key, e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("⚠️ QR Key 读取缓存失败 qr_key={}: {}", qr_key, e),
+        }
+
+        let record = self.repo.get(qr_key).await?;
+        if let Some(record) = &record {
+            self.cache_put(record).await;
+        }
+        Ok(record)
+    }
+
+    async fn find_active_by_target(&self, target_id: &str) -> Result<Option<QRKeyRecord>> {
+        self.repo.find_active_by_target(target_id).await
+    }
+
+    async fn consume(&self, qr_key: &str, now: DateTime<Utc>) -> Result<Option<QRKeyRecord>> {
+        let record = self.repo.consume(qr_key, now.timestamp_millis()).await?;
+        if let Some(record) = &record {
+            self.cache_put(record).await;
+        }
+        Ok(record)
+    }
+
+    async fn revoke(&self, qr_key: &str) -> Result<Option<QRKeyRecord>> {
+        let record = self
+            .repo
+            .revoke(qr_key, Utc::now().timestamp_millis())
+            .await?;
+        match &record {
+            Some(record) => self.cache_put(record).await,
+            None => self.cache_del(qr_key).await,
+        }
+        Ok(record)
+    }
+
+    async fn list_by_creator(
+        &self,
+        creator_id: &str,
+        include_revoked: bool,
+    ) -> Result<Vec<QRKeyRecord>> {
+        self.repo.list_by_creator(creator_id, include_revoked).await
+    }
+
+    async fn append_scan_log(&self, log: &QRScanLog) -> Result<()> {
+        self.repo.insert_scan_log(log).await
+    }
+
+    async fn list_scan_logs(&self, qr_key: &str, limit: usize) -> Result<Vec<QRScanLog>> {
+        let limit = i64::try_from(limit)
+            .map_err(|_| ServerError::Validation(format!("limit 过大: {}", limit)))?;
+        self.repo.list_scan_logs(qr_key, limit).await
+    }
+
+    async fn purge(
+        &self,
+        expired_before: DateTime<Utc>,
+        scanned_before: DateTime<Utc>,
+    ) -> Result<(u64, u64)> {
+        // 缓存 TTL 不超过 Key 的过期时间，被删的 Key 早已不在缓存中
+        let keys = self
+            .repo
+            .purge_expired_keys(expired_before.timestamp_millis())
+            .await?;
+        let logs = self
+            .repo
+            .purge_scan_logs(scanned_before.timestamp_millis())
+            .await?;
+        Ok((keys, logs))
+    }
+}
@@ -17,12 +17,22 @@
 
 use crate::error::{Result, ServerError};
 use crate::model::channel::{MessageId, UserId};
+use crate::service::sticker_service::{is_valid_sticker_identifier, Sticker, StickerService};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// 每个用户对一条消息最多的 Reaction 个数
+pub const MAX_REACTIONS_PER_USER: usize = 3;
+/// Unicode emoji 最大字节数（ZWJ 组合、肤色修饰的 emoji 会比较长）
+pub const MAX_EMOJI_BYTES: usize = 32;
+/// 群 Reaction 白名单最多条目数
+pub const MAX_ALLOWED_REACTIONS: usize = 100;
+/// 自定义表情 Reaction 的前缀：`sticker:<sticker_id>`
+pub const STICKER_REACTION_PREFIX: &str = "sticker:";
+
 /// 消息 Reaction（点赞/表情）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Reaction {
@@ -37,6 +47,70 @@ pub struct Reaction {
 pub struct ReactionStats {
     pub reactions: HashMap<String, Vec<UserId>>,
     pub total_count: usize,
+    /// 自定义表情 Reaction 对应的表情（key 为 `sticker:<sticker_id>`），客户端据此渲染图片
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_emoji: HashMap<String, Sticker>,
+}
+
+/// 校验过的 Reaction 标识
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReactionKey {
+    /// Unicode emoji
+    Emoji(String),
+    /// 表情包中的自定义表情（sticker_id）
+    Sticker(String),
+}
+
+impl ReactionKey {
+    /// 解析客户端提交的 Reaction：`sticker:<sticker_id>` 或单个 Unicode emoji
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.trim();
+        if let Some(sticker_id) = raw.strip_prefix(STICKER_REACTION_PREFIX) {
+            if !is_valid_sticker_identifier(sticker_id) {
+                return Err(ServerError::Validation(format!(
+                    "无效的自定义表情: {}",
+                    raw
+                )));
+            }
+            return Ok(Self::Sticker(sticker_id.to_string()));
+        }
+
+        // 纯 ASCII 的文本（如 "lol"）不算 emoji；键帽 emoji（1️⃣）含非 ASCII 的组合字符
+        let valid = !raw.is_empty()
+            && raw.len() <= MAX_EMOJI_BYTES
+            && raw.chars().any(|c| !c.is_ascii())
+            && !raw.chars().any(|c| c.is_whitespace() || c.is_control());
+        if !valid {
+            return Err(ServerError::Validation("无效的 emoji".to_string()));
+        }
+        Ok(Self::Emoji(raw.to_string()))
+    }
+
+    /// 落库 / 下发时的字符串形式
+    pub fn as_stored(&self) -> String {
+        match self {
+            Self::Emoji(emoji) => emoji.clone(),
+            Self::Sticker(sticker_id) => format!("{}{}", STICKER_REACTION_PREFIX, sticker_id),
+        }
+    }
+}
+
+/// 规范化群 Reaction 白名单：逐项校验、去重（保持顺序）、限制条目数
+pub fn normalize_allowed_reactions(items: &[String]) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(items.len());
+    for item in items {
+        let key = ReactionKey::parse(item)?.as_stored();
+        if !normalized.contains(&key) {
+            normalized.push(key);
+        }
+    }
+    if normalized.len() > MAX_ALLOWED_REACTIONS {
+        return Err(ServerError::Validation(format!(
+            "Reaction 白名单最多 {} 项",
+            MAX_ALLOWED_REACTIONS
+        )));
+    }
+    Ok(normalized)
 }
 
 #[derive(Debug, FromRow)]
@@ -54,69 +128,145 @@ struct ReactionStatsRow {
 /// Reaction 服务
 pub struct ReactionService {
     pool: Arc<PgPool>,
+    sticker_service: Arc<StickerService>,
 }
 
 impl ReactionService {
-    pub fn new(pool: Arc<PgPool>) -> Self {
-        Self { pool }
+    pub fn new(pool: Arc<PgPool>, sticker_service: Arc<StickerService>) -> Self {
+        Self {
+            pool,
+            sticker_service,
+        }
+    }
+
+    /// 校验 Reaction 在该会话中是否可用：自定义表情须存在且所属库上架，群白名单须包含
+    async fn ensure_allowed(&self, channel_id: u64, key: &ReactionKey) -> Result<()> {
+        if let ReactionKey::Sticker(sticker_id) = key {
+            self.sticker_service.resolve_sendable(sticker_id).await?;
+        }
+        if let Some(allowed) = self.get_allowed_reactions(channel_id).await? {
+            if allowed.is_empty() {
+                return Err(ServerError::Validation("本群已关闭 Reaction".to_string()));
+            }
+            if !allowed.contains(&key.as_stored()) {
+                return Err(ServerError::Validation("本群不允许该 Reaction".to_string()));
+            }
+        }
+        Ok(())
     }
 
     /// 添加 Reaction
+    ///
+    /// 返回 `true` 表示新增；同一 Reaction 已存在返回 `false`（调用方不必再写 timeline）。
+    /// 超过 [`MAX_REACTIONS_PER_USER`] 返回 Validation。
     pub async fn add_reaction(
         &self,
         message_id: u64,
+        channel_id: u64,
         user_id: u64,
-        emoji: &str,
-    ) -> Result<Reaction> {
-        if emoji.is_empty() || emoji.len() > 10 {
-            return Err(ServerError::Validation("无效的 emoji".to_string()));
+        key: &ReactionKey,
+    ) -> Result<bool> {
+        self.ensure_allowed(channel_id, key).await?;
+        let emoji = key.as_stored();
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ServerError::Database(format!("开启事务失败: {}", e)))?;
+
+        // READ COMMITTED 下「子查询计数 + 插入」挡不住并发：两个请求各自看不到
+        // 对方未提交的行，都数出 < 上限，双双插入。按 (message_id, user_id) 取
+        // advisory 锁把同一用户对同一消息的添加排成序，锁随事务释放。
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(reaction_lock_key(message_id, user_id))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerError::Database(format!("获取 Reaction 锁失败: {}", e)))?;
+
+        let existing: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT emoji FROM privchat_message_reactions
+            WHERE message_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(message_id as i64)
+        .bind(user_id as i64)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Reaction 失败: {}", e)))?;
+        if existing.iter().any(|e| *e == emoji) {
+            return Ok(false);
+        }
+        if existing.len() >= MAX_REACTIONS_PER_USER {
+            return Err(ServerError::Validation(format!(
+                "每条消息最多添加 {} 个 Reaction",
+                MAX_REACTIONS_PER_USER
+            )));
         }
 
-        let created_at_ms = Utc::now().timestamp_millis();
-        let row = sqlx::query_as::<_, ReactionRow>(
+        sqlx::query(
             r#"
             INSERT INTO privchat_message_reactions
                 (message_id, user_id, emoji, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $4)
-            ON CONFLICT (message_id, user_id)
-            DO UPDATE SET
-                emoji = EXCLUDED.emoji,
-                updated_at = EXCLUDED.updated_at
-            RETURNING emoji, created_at
+            ON CONFLICT (message_id, user_id, emoji) DO NOTHING
             "#,
         )
         .bind(message_id as i64)
         .bind(user_id as i64)
-        .bind(emoji)
-        .bind(created_at_ms)
-        .fetch_one(self.pool.as_ref())
+        .bind(&emoji)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&mut *tx)
         .await
         .map_err(|e| ServerError::Database(format!("添加 Reaction 失败: {}", e)))?;
-
-        Ok(Reaction {
-            message_id,
-            user_id,
-            emoji: emoji.to_string(),
-            created_at: DateTime::from_timestamp_millis(row.created_at).unwrap_or_else(Utc::now),
-        })
+        tx.commit()
+            .await
+            .map_err(|e| ServerError::Database(format!("提交 Reaction 失败: {}", e)))?;
+        Ok(true)
     }
 
-    /// 移除 Reaction
-    pub async fn remove_reaction(&self, message_id: u64, user_id: u64) -> Result<Option<Reaction>> {
-        let removed = self.get_user_reaction(message_id, user_id).await;
-        sqlx::query(
+    /// 移除指定 Reaction；原本没有返回 false
+    pub async fn remove_reaction(
+        &self,
+        message_id: u64,
+        user_id: u64,
+        emoji: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
             r#"
             DELETE FROM privchat_message_reactions
-            WHERE message_id = $1 AND user_id = $2
+            WHERE message_id = $1 AND user_id = $2 AND emoji = $3
             "#,
         )
         .bind(message_id as i64)
         .bind(user_id as i64)
+        .bind(emoji.trim())
         .execute(self.pool.as_ref())
         .await
         .map_err(|e| ServerError::Database(format!("移除 Reaction 失败: {}", e)))?;
 
-        Ok(removed)
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 移除用户对消息的全部 Reaction，返回被移除的 emoji
+    pub async fn remove_user_reactions(
+        &self,
+        message_id: u64,
+        user_id: u64,
+    ) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            r#"
+            DELETE FROM privchat_message_reactions
+            WHERE message_id = $1 AND user_id = $2
+            RETURNING emoji
+            "#,
+        )
+        .bind(message_id as i64)
+        .bind(user_id as i64)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("移除 Reaction 失败: {}", e)))
     }
 
     /// 获取消息的所有 Reaction
@@ -143,38 +293,163 @@ impl ReactionService {
         }
         let total_count = reactions.values().map(Vec::len).sum();
 
+        // 自定义表情附上表情详情；表情已被删除的照常计数，只是不带图片
+        let mut custom_emoji = HashMap::new();
+        for key in reactions.keys() {
+            if let Some(sticker_id) = key.strip_prefix(STICKER_REACTION_PREFIX) {
+                if let Some(sticker) = self.sticker_service.get_sticker(sticker_id).await? {
+                    custom_emoji.insert(key.clone(), sticker);
+                }
+            }
+        }
+
         Ok(ReactionStats {
             reactions,
             total_count,
+            custom_emoji,
         })
     }
 
-    /// 检查用户是否对消息有 Reaction
-    pub async fn has_reaction(&self, message_id: u64, user_id: u64) -> Option<Reaction> {
-        self.get_user_reaction(message_id, user_id).await
-    }
-
-    /// 获取用户对消息的 Reaction（如果存在）
-    pub async fn get_user_reaction(&self, message_id: u64, user_id: u64) -> Option<Reaction> {
-        let row = sqlx::query_as::<_, ReactionRow>(
+    /// 获取用户对消息的全部 Reaction（按添加时间）
+    pub async fn get_user_reactions(&self, message_id: u64, user_id: u64) -> Result<Vec<Reaction>> {
+        let rows = sqlx::query_as::<_, ReactionRow>(
             r#"
             SELECT emoji, created_at
             FROM privchat_message_reactions
             WHERE message_id = $1 AND user_id = $2
+            ORDER BY created_at ASC
             "#,
         )
         .bind(message_id as i64)
         .bind(user_id as i64)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("查询 Reaction 失败: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Reaction {
+                message_id,
+                user_id,
+                emoji: row.emoji,
+                created_at: DateTime::from_timestamp_millis(row.created_at)
+                    .unwrap_or_else(Utc::now),
+            })
+            .collect())
+    }
+
+    /// 群 Reaction 白名单；`None` 表示不限制，空列表表示关闭 Reaction
+    pub async fn get_allowed_reactions(&self, channel_id: u64) -> Result<Option<Vec<String>>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT allowed_reactions
+            FROM privchat_channel_reaction_settings
+            WHERE channel_id = $1
+            "#,
+        )
+        .bind(channel_id as i64)
         .fetch_optional(self.pool.as_ref())
         .await
-        .ok()
-        .flatten()?;
-
-        Some(Reaction {
-            message_id,
-            user_id,
-            emoji: row.emoji,
-            created_at: DateTime::from_timestamp_millis(row.created_at).unwrap_or_else(Utc::now),
-        })
+        .map_err(|e| ServerError::Database(format!("查询 Reaction 白名单失败: {}", e)))
+    }
+
+    /// 设置群 Reaction 白名单；`None` 恢复为不限制。返回规范化后的白名单
+    pub async fn set_allowed_reactions(
+        &self,
+        channel_id: u64,
+        operator_id: u64,
+        allowed: Option<&[String]>,
+    ) -> Result<Option<Vec<String>>> {
+        let Some(items) = allowed else {
+            sqlx::query("DELETE FROM privchat_channel_reaction_settings WHERE channel_id = $1")
+                .bind(channel_id as i64)
+                .execute(self.pool.as_ref())
+                .await
+                .map_err(|e| ServerError::Database(format!("清除 Reaction 白名单失败: {}", e)))?;
+            return Ok(None);
+        };
+
+        let normalized = normalize_allowed_reactions(items)?;
+        for key in &normalized {
+            if let Some(sticker_id) = key.strip_prefix(STICKER_REACTION_PREFIX) {
+                self.sticker_service.resolve_sendable(sticker_id).await?;
+            }
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO privchat_channel_reaction_settings
+                (channel_id, allowed_reactions, updated_by, updated_at)
+            VALUES ($1, $2, $3, now_millis())
+            ON CONFLICT (channel_id) DO UPDATE
+            SET allowed_reactions = EXCLUDED.allowed_reactions,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(channel_id as i64)
+        .bind(&normalized)
+        .bind(operator_id as i64)
+        .execute(self.pool.as_ref())
+        .await
+        .map_err(|e| ServerError::Database(format!("保存 Reaction 白名单失败: {}", e)))?;
+        Ok(Some(normalized))
+    }
+}
+
+/// 串行化同一用户对同一消息的 Reaction 添加的 advisory 锁键
+fn reaction_lock_key(message_id: u64, user_id: u64) -> String {
+    format!("reaction:{}:{}", message_id, user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reaction_key() {
+        assert_eq!(
+            ReactionKey::parse("👍").unwrap(),
+            ReactionKey::Emoji("👍".to_string())
+        );
+        // ZWJ 组合 emoji 超过旧的 10 字节上限
+        assert!(ReactionKey::parse("👨‍👩‍👧‍👦").is_ok());
+        assert!(ReactionKey::parse("1️⃣").is_ok());
+        assert_eq!(
+            ReactionKey::parse(" sticker:cat_01 ").unwrap(),
+            ReactionKey::Sticker("cat_01".to_string())
+        );
+        assert_eq!(
+            ReactionKey::parse("sticker:cat_01").unwrap().as_stored(),
+            "sticker:cat_01"
+        );
+
+        assert!(ReactionKey::parse("").is_err());
+        assert!(ReactionKey::parse("lol").is_err());
+        assert!(ReactionKey::parse("👍 👍").is_err());
+        assert!(ReactionKey::parse("sticker:").is_err());
+        assert!(ReactionKey::parse("sticker:Cat/01").is_err());
+        assert!(ReactionKey::parse(&"👍".repeat(9)).is_err());
+    }
+
+    #[test]
+    fn test_reaction_lock_key_is_per_message_and_user() {
+        assert_eq!(reaction_lock_key(1, 2), "reaction:1:2");
+        assert_ne!(reaction_lock_key(1, 2), reaction_lock_key(2, 1));
+    }
+
+    #[test]
+    fn test_normalize_allowed_reactions() {
+        let items = vec![
+            "👍".to_string(),
+            " 👍".to_string(),
+            "sticker:cat_01".to_string(),
+            "❤️".to_string(),
+        ];
+        assert_eq!(
+            normalize_allowed_reactions(&items).unwrap(),
+            vec!["👍", "sticker:cat_01", "❤️"]
+        );
+        assert!(normalize_allowed_reactions(&["abc".to_string()]).is_err());
+        assert!(normalize_allowed_reactions(&[]).unwrap().is_empty());
     }
 }
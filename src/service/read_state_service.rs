@@ -115,6 +115,10 @@ pub struct ReadStateService {
     message_router: Arc<MessageRouter>,
     pool: Arc<PgPool>,
     delivery_tracker: Option<Arc<crate::service::DeliveryTracker>>,
+    /// 已读回执可见性规则（私聊对端 `peer_read_pts` 推送 / 同步时过滤）
+    privacy_service: Option<Arc<crate::service::PrivacyService>>,
+    /// 阅后即焚（已读后开始倒计时的消息在已读游标推进时启动）
+    disappearing_service: Option<Arc<crate::service::DisappearingService>>,
 }
 
 impl ReadStateService {
@@ -130,6 +134,8 @@ impl ReadStateService {
             message_router,
             pool,
             delivery_tracker: None,
+            privacy_service: None,
+            disappearing_service: None,
         }
     }
 
@@ -137,6 +143,40 @@ impl ReadStateService {
         self.delivery_tracker = Some(tracker);
     }
 
+    pub fn set_privacy_service(&mut self, privacy_service: Arc<crate::service::PrivacyService>) {
+        self.privacy_service = Some(privacy_service);
+    }
+
+    pub fn set_disappearing_service(
+        &mut self,
+        disappearing_service: Arc<crate::service::DisappearingService>,
+    ) {
+        self.disappearing_service = Some(disappearing_service);
+    }
+
+    /// viewer 能否看到 reader 的已读回执。读取隐私设置失败时按不可见处理。
+    async fn read_receipt_visible(&self, reader_id: UserId, viewer_id: UserId) -> bool {
+        let Some(privacy) = &self.privacy_service else {
+            return true;
+        };
+        privacy
+            .can_view(
+                reader_id,
+                viewer_id,
+                crate::model::privacy::PrivacyKey::ReadReceipts,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(
+                    "⚠️ ReadStateService: 读取已读回执隐私设置失败 reader={} viewer={}: {}",
+                    reader_id,
+                    viewer_id,
+                    e
+                );
+                false
+            })
+    }
+
     /// 获取指定用户在指定频道的连续送达水位
     pub async fn get_server_delivered_pts(&self, user_id: UserId, channel_id: ChannelId) -> u64 {
         match &self.delivery_tracker {
@@ -252,6 +292,20 @@ impl ReadStateService {
 
         let advanced = upserted.advanced;
         if advanced {
+            if let Some(disappearing) = &self.disappearing_service {
+                // 倒计时启动失败不影响已读本身，下一次已读推进会再次尝试
+                if let Err(e) = disappearing
+                    .on_read_advanced(channel_id, reader_id, upserted.last_read_pts)
+                    .await
+                {
+                    tracing::warn!(
+                        "⚠️ ReadStateService: 启动阅后即焚倒计时失败 user={} channel={}: {}",
+                        reader_id,
+                        channel_id,
+                        e
+                    );
+                }
+            }
             self.broadcast_read_cursor(reader_id, channel_id, upserted.last_read_pts)
                 .await?;
         }
@@ -335,8 +389,29 @@ impl ReadStateService {
             .channels;
         let has_more = db_rows.len() > limit as usize;
         let mut rows: Vec<ChannelReadCursorRow> = Vec::new();
+        let mut peer_visible: std::collections::HashMap<UserId, bool> =
+            std::collections::HashMap::new();
+        let mut skipped_version = since_version;
         for row in db_rows.into_iter().take(limit as usize) {
             let channel_id = row.get::<i64, _>("channel_id") as u64;
+            let reader_id = row.get::<i64, _>("user_id") as u64;
+            let version = row.get::<i64, _>("sync_version") as u64;
+            // 对端游标同样受已读回执隐私规则约束。被过滤的行仍推进 next_version，
+            // 否则客户端会一直从同一位置重新拉到它。
+            if reader_id != user_id {
+                let visible = match peer_visible.get(&reader_id) {
+                    Some(visible) => *visible,
+                    None => {
+                        let visible = self.read_receipt_visible(reader_id, user_id).await;
+                        peer_visible.insert(reader_id, visible);
+                        visible
+                    }
+                };
+                if !visible {
+                    skipped_version = skipped_version.max(version);
+                    continue;
+                }
+            }
             let channel_type = channels
                 .iter()
                 .find(|ch| ch.id == channel_id)
@@ -349,12 +424,16 @@ impl ReadStateService {
             rows.push(ChannelReadCursorRow {
                 channel_id,
                 channel_type,
-                reader_id: row.get::<i64, _>("user_id") as u64,
+                reader_id,
                 last_read_pts: row.get::<i64, _>("last_read_pts") as u64,
-                version: row.get::<i64, _>("sync_version") as u64,
+                version,
             });
         }
-        let next_version = rows.last().map(|r| r.version).unwrap_or(since_version);
+        let next_version = rows
+            .last()
+            .map(|r| r.version)
+            .unwrap_or(since_version)
+            .max(skipped_version);
         Ok((rows, next_version, has_more))
     }
 
@@ -445,16 +524,19 @@ impl ReadStateService {
 
         if matches!(channel_kind, ChannelKind::PrivateChat) {
             // Direct 对端权威识别（direct_user1/2，不靠 members——脏数据/缺行都对）。
+            // 已读回执受双方隐私规则约束（对等）：任一方对另一方关掉，对端都收不到。
             if let Some(peer_id) = channel.direct_peer(reader_id) {
-                self.send_read_cursor_event(
-                    peer_id,
-                    "peer_read_pts_updated",
-                    channel_id,
-                    channel_type,
-                    reader_id,
-                    last_read_pts,
-                )
-                .await?;
+                if self.read_receipt_visible(reader_id, peer_id).await {
+                    self.send_read_cursor_event(
+                        peer_id,
+                        "peer_read_pts_updated",
+                        channel_id,
+                        channel_type,
+                        reader_id,
+                        last_read_pts,
+                    )
+                    .await?;
+                }
             }
         }
         Ok(())
@@ -0,0 +1,284 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 消息保留期
+//!
+//! 平台默认保留期来自 `[message] retention_secs`（0 = 永久），群主 / 私聊双方可以为单个会话
+//! 设置更短的保留期；有效保留期取两者较小值，会话覆盖不能突破平台上限。
+//!
+//! [`RetentionService::spawn_sweeper`] 周期性删除超期消息及其 commit log / 附属行，
+//! 每个被清理的会话在 commit log 追加一条 `channel.history_expired`，客户端经
+//! `sync/get_difference` 拉到后删除本地早于 `expired_before` 的消息。通知与每批删除同事务
+//! 登记，写入 commit log 后才清除，失败的下一轮补发。多节点可同时运行，
+//! 同一会话由 advisory 锁保证同一时刻只有一个节点在删。
+
+use crate::error::{Result, ServerError};
+use crate::model::channel::ChannelType;
+use crate::repository::{ExpiringChannel, PendingExpiryNotice, RetentionRepository};
+use crate::service::sync::{get_global_sync_service, SyncService};
+use crate::service::MessageHistoryService;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 会话级保留期下限（秒）：1 小时
+pub const MIN_CHANNEL_RETENTION_SECS: i64 = 3600;
+/// 每批删除的消息数
+const PURGE_BATCH_SIZE: i64 = 1000;
+
+/// 会话保留期设置（RPC 响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChannelRetention {
+    pub channel_id: u64,
+    /// 会话自己的覆盖（秒），`None` 表示跟随平台默认
+    pub retention_secs: Option<i64>,
+    /// 平台默认（秒），`None` 表示永久保留
+    pub platform_retention_secs: Option<i64>,
+    /// 实际生效的保留期（秒），`None` 表示永久保留
+    pub effective_retention_secs: Option<i64>,
+}
+
+/// 一轮清理的统计
+#[derive(Debug, Clone, Default)]
+pub struct RetentionSweepStats {
+    pub channels: u64,
+    pub messages: u64,
+    pub commits: u64,
+}
+
+/// 有效保留期：平台默认（`0` = 不限）与会话覆盖取较小值，均未设置时为 `None`（永久）
+pub fn effective_retention_secs(platform_secs: i64, channel_secs: Option<i64>) -> Option<i64> {
+    let platform = (platform_secs > 0).then_some(platform_secs);
+    match (platform, channel_secs) {
+        (Some(p), Some(c)) => Some(p.min(c)),
+        (p, c) => p.or(c),
+    }
+}
+
+pub struct RetentionService {
+    repository: Arc<RetentionRepository>,
+    message_history_service: Arc<MessageHistoryService>,
+    /// 平台默认保留期（秒），0 = 永久
+    platform_retention_secs: i64,
+}
+
+impl RetentionService {
+    pub fn new(
+        repository: Arc<RetentionRepository>,
+        message_history_service: Arc<MessageHistoryService>,
+        platform_retention_secs: i64,
+    ) -> Self {
+        Self {
+            repository,
+            message_history_service,
+            platform_retention_secs: platform_retention_secs.max(0),
+        }
+    }
+
+    pub async fn get_channel_retention(&self, channel_id: u64) -> Result<ChannelRetention> {
+        let retention_secs = self.repository.get_channel_retention(channel_id).await?;
+        Ok(self.describe(channel_id, retention_secs))
+    }
+
+    /// 设置会话保留期；`None` 清除覆盖、恢复平台默认
+    pub async fn set_channel_retention(
+        &self,
+        channel_id: u64,
+        operator_id: u64,
+        retention_secs: Option<i64>,
+    ) -> Result<ChannelRetention> {
+        match retention_secs {
+            Some(secs) => {
+                if secs < MIN_CHANNEL_RETENTION_SECS {
+                    return Err(ServerError::Validation(format!(
+                        "保留期不能短于 {} 秒",
+                        MIN_CHANNEL_RETENTION_SECS
+                    )));
+                }
+                if self.platform_retention_secs > 0 && secs > self.platform_retention_secs {
+                    return Err(ServerError::Validation(format!(
+                        "保留期不能超过平台上限 {} 秒",
+                        self.platform_retention_secs
+                    )));
+                }
+                self.repository
+                    .upsert_channel_retention(channel_id, secs, operator_id)
+                    .await?;
+            }
+            None => {
+                self.repository.delete_channel_retention(channel_id).await?;
+            }
+        }
+        Ok(self.describe(channel_id, retention_secs))
+    }
+
+    fn describe(&self, channel_id: u64, retention_secs: Option<i64>) -> ChannelRetention {
+        ChannelRetention {
+            channel_id,
+            retention_secs,
+            platform_retention_secs: (self.platform_retention_secs > 0)
+                .then_some(self.platform_retention_secs),
+            effective_retention_secs: effective_retention_secs(
+                self.platform_retention_secs,
+                retention_secs,
+            ),
+        }
+    }
+
+    /// 清理一轮：逐个会话分批删除超期消息，再为所有已删消息的会话删 commit log 并通知客户端。
+    ///
+    /// 单个会话失败只记日志，不影响其余会话；通知随每批删除登记，没发出去的下一轮补发。
+    pub async fn sweep_once(&self) -> Result<RetentionSweepStats> {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let channels = self
+            .repository
+            .list_expiring_channels(self.platform_retention_secs, now_ms)
+            .await?;
+
+        let mut stats = RetentionSweepStats::default();
+        for channel in channels {
+            match self.purge_channel(&channel, now_ms).await {
+                Ok(0) => {}
+                Ok(purged_messages) => {
+                    stats.channels += 1;
+                    stats.messages += purged_messages;
+                }
+                Err(e) => warn!(
+                    "⚠️ 清理超期消息失败: channel_id={}, error={}",
+                    channel.channel_id, e
+                ),
+            }
+        }
+
+        let notices = self.repository.list_pending_notices().await?;
+        if notices.is_empty() {
+            return Ok(stats);
+        }
+        let Some(sync_service) = get_global_sync_service() else {
+            warn!(
+                "⚠️ SyncService 未初始化，{} 条 history_expired 通知留待下一轮",
+                notices.len()
+            );
+            return Ok(stats);
+        };
+        for notice in notices {
+            match self.send_expiry_notice(&sync_service, &notice).await {
+                Ok(commits) => stats.commits += commits,
+                Err(e) => warn!(
+                    "⚠️ 写入 history_expired commit 失败，下一轮重试: channel_id={}, error={}",
+                    notice.channel_id, e
+                ),
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 分批删除一个会话的超期消息，返回删除条数
+    async fn purge_channel(&self, channel: &ExpiringChannel, now_ms: i64) -> Result<u64> {
+        let cutoff_ms = now_ms - channel.retention_secs * 1000;
+        let mut purged_messages = 0u64;
+        loop {
+            match self
+                .repository
+                .purge_channel_batch(
+                    channel.channel_id,
+                    channel.channel_type,
+                    cutoff_ms,
+                    PURGE_BATCH_SIZE,
+                )
+                .await?
+            {
+                Some(batch) => {
+                    purged_messages += batch.messages;
+                    if (batch.messages as i64) < PURGE_BATCH_SIZE {
+                        break;
+                    }
+                }
+                // 其他节点正在清理这个会话
+                None => break,
+            }
+        }
+        if purged_messages > 0 {
+            // 本节点内存中的历史缓存整体丢弃，下次查询从数据库回填
+            let _ = self
+                .message_history_service
+                .clear_channel_messages(&channel.channel_id)
+                .await;
+        }
+        Ok(purged_messages)
+    }
+
+    /// 删除会话中早于截止时间的 commit log，追加 `channel.history_expired`，成功后清除登记。
+    /// 返回删除的 commit 数。
+    ///
+    /// 清除登记失败时下一轮会重发同一条通知；客户端按 `expired_before` 删本地消息，重复无害。
+    async fn send_expiry_notice(
+        &self,
+        sync_service: &SyncService,
+        notice: &PendingExpiryNotice,
+    ) -> Result<u64> {
+        let commits = self
+            .repository
+            .purge_channel_commits(notice.channel_id, notice.expired_before)
+            .await?;
+        let wire_type = ChannelType::from_i16(notice.channel_type).to_wire_u8();
+        sync_service
+            .append_history_expired_commit(
+                notice.channel_id,
+                wire_type,
+                notice.expired_before,
+                notice.expired_up_to_pts,
+            )
+            .await?;
+        self.repository.clear_pending_notice(notice).await?;
+        Ok(commits)
+    }
+
+    /// 启动后台清理任务
+    pub fn spawn_sweeper(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tick.tick().await;
+                match self.sweep_once().await {
+                    Ok(stats) if stats.messages > 0 => info!(
+                        "🧹 消息保留期清理：{} 个会话，删除消息 {} 条、commit {} 条",
+                        stats.channels, stats.messages, stats.commits
+                    ),
+                    Ok(_) => {}
+                    Err(e) => warn!("⚠️ 消息保留期清理失败: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_retention_takes_the_shorter_period() {
+        assert_eq!(effective_retention_secs(0, None), None);
+        assert_eq!(effective_retention_secs(0, Some(7200)), Some(7200));
+        assert_eq!(effective_retention_secs(86400, None), Some(86400));
+        assert_eq!(effective_retention_secs(86400, Some(7200)), Some(7200));
+        assert_eq!(effective_retention_secs(3600, Some(7200)), Some(3600));
+    }
+}
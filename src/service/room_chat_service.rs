@@ -0,0 +1,776 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Room 订阅者聊天（直播评论）
+//!
+//! Room 默认只接收管理端广播；管理 API 为单个房间开启聊天后，已订阅该房间的会话可以发言：
+//! - 发言经 `SecurityService::check_rpc` 走 `MultiDimensionRateLimiter` 的
+//!   用户 / 用户+房间 / 房间三层令牌桶，另有按房间配置的慢速模式（每人每 N 秒一条，主持人豁免）；
+//! - 文本经 [`RoomChatFilter`] 过滤，默认实现为关键词 + 正则，可替换为外部审核；
+//! - 主持人可删除消息、禁言 / 解禁、设置唯一的置顶消息；
+//! - 聊天与管理事件都以 `PublishRequest` 经 [`RoomFanout`] 广播（按 topic 区分），
+//!   聊天写入 [`RoomHistoryService`] 供后来者回放，置顶槽位在订阅时随历史一起回放。
+//!
+//! 房间设置、禁言、慢速模式计时都存 Redis，集群内共享。
+
+use crate::config::RoomConfig;
+use crate::error::{Result, ServerError};
+use crate::infra::redis::RedisClient;
+use crate::infra::{RoomFanout, SubscribeManager};
+use crate::security::SecurityService;
+use crate::service::group_slow_mode_service::remaining_secs;
+use crate::service::RoomHistoryService;
+use async_trait::async_trait;
+use msgtrans::SessionId;
+use privchat_protocol::protocol::PublishRequest;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 聊天消息
+pub const ROOM_CHAT_TOPIC: &str = "chat";
+/// 主持人删除消息
+pub const ROOM_CHAT_DELETE_TOPIC: &str = "chat.delete";
+/// 置顶消息（同时是置顶槽位的存储格式）
+pub const ROOM_CHAT_PIN_TOPIC: &str = "chat.pin";
+/// 取消置顶
+pub const ROOM_CHAT_UNPIN_TOPIC: &str = "chat.unpin";
+/// 送入 `MultiDimensionRateLimiter` 的方法名（`messages.send` 前缀启用房间维度限流）
+pub const ROOM_CHAT_RATE_LIMIT_METHOD: &str = "messages.send.room";
+
+/// 慢速模式间隔上限（秒）
+const MAX_SLOW_MODE_SECS: u64 = 3600;
+/// 单条消息长度上限（字符）
+const MAX_MESSAGE_LENGTH: usize = 2000;
+/// 限时禁言上限（秒）；0 表示永久
+const MAX_MUTE_SECS: u64 = 30 * 86_400;
+/// 主持人数量上限
+const MAX_MODERATORS: usize = 100;
+
+/// 房间聊天设置（管理 API 维护）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomChatSettings {
+    /// 是否允许订阅者发言
+    #[serde(default)]
+    pub enabled: bool,
+    /// 慢速模式：每人两次发言的最小间隔（秒），0 = 关闭
+    #[serde(default)]
+    pub slow_mode_secs: u64,
+    /// 单条最大字符数；0 = 使用 `[room].chat_max_length`
+    #[serde(default)]
+    pub max_length: usize,
+    /// 主持人：可删除 / 禁言 / 置顶，不受慢速模式限制
+    #[serde(default)]
+    pub moderators: Vec<u64>,
+    /// 本房间追加的违禁关键词
+    #[serde(default)]
+    pub blocked_keywords: Vec<String>,
+}
+
+impl RoomChatSettings {
+    pub fn is_moderator(&self, user_id: u64) -> bool {
+        self.moderators.contains(&user_id)
+    }
+
+    /// 校验并规整（主持人去重、关键词去空白）
+    fn normalized(mut self) -> Result<Self> {
+        if self.slow_mode_secs > MAX_SLOW_MODE_SECS {
+            return Err(ServerError::Validation(format!(
+                "slow_mode_secs 不能超过 {}",
+                MAX_SLOW_MODE_SECS
+            )));
+        }
+        if self.max_length > MAX_MESSAGE_LENGTH {
+            return Err(ServerError::Validation(format!(
+                "max_length 不能超过 {}",
+                MAX_MESSAGE_LENGTH
+            )));
+        }
+        self.moderators.sort_unstable();
+        self.moderators.dedup();
+        if self.moderators.len() > MAX_MODERATORS {
+            return Err(ServerError::Validation(format!(
+                "主持人不能超过 {} 个",
+                MAX_MODERATORS
+            )));
+        }
+        self.blocked_keywords = self
+            .blocked_keywords
+            .into_iter()
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect();
+        Ok(self)
+    }
+}
+
+/// 聊天消息（`topic = chat` 的 PublishRequest payload）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomChatMessage {
+    pub server_message_id: u64,
+    pub channel_id: u64,
+    pub user_id: u64,
+    pub text: String,
+    /// 毫秒时间戳
+    pub sent_at: i64,
+}
+
+/// 客户端进入房间时查询的聊天状态
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomChatInfo {
+    pub channel_id: u64,
+    pub enabled: bool,
+    pub slow_mode_secs: u64,
+    pub max_length: usize,
+    pub is_moderator: bool,
+    pub muted: bool,
+    /// 禁言到期时间（毫秒）；永久禁言或未禁言时为 null
+    pub muted_until: Option<i64>,
+    pub pinned: Option<RoomChatMessage>,
+}
+
+/// 过滤结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomChatVerdict {
+    Allow,
+    /// 以改写后的文本放行（如打码）
+    Replace(String),
+    /// 拒绝，附原因
+    Reject(String),
+}
+
+/// 聊天内容过滤钩子
+#[async_trait]
+pub trait RoomChatFilter: Send + Sync {
+    async fn check(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        text: &str,
+        settings: &RoomChatSettings,
+    ) -> RoomChatVerdict;
+}
+
+/// 默认过滤器：全局关键词 + 房间关键词（大小写不敏感）+ 全局正则
+pub struct KeywordRoomChatFilter {
+    keywords: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl KeywordRoomChatFilter {
+    pub fn new(keywords: &[String], patterns: &[String]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    warn!("⚠️ 忽略非法的 Room 聊天违禁正则 {:?}: {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            keywords: keywords
+                .iter()
+                .map(|keyword| keyword.trim().to_lowercase())
+                .filter(|keyword| !keyword.is_empty())
+                .collect(),
+            patterns,
+        }
+    }
+}
+
+#[async_trait]
+impl RoomChatFilter for KeywordRoomChatFilter {
+    async fn check(
+        &self,
+        _channel_id: u64,
+        _user_id: u64,
+        text: &str,
+        settings: &RoomChatSettings,
+    ) -> RoomChatVerdict {
+        let lowered = text.to_lowercase();
+        let keyword_hit = self.keywords.iter().any(|k| lowered.contains(k.as_str()))
+            || settings
+                .blocked_keywords
+                .iter()
+                .any(|k| lowered.contains(&k.to_lowercase()));
+        if keyword_hit || self.patterns.iter().any(|p| p.is_match(text)) {
+            return RoomChatVerdict::Reject("消息包含违禁内容".to_string());
+        }
+        RoomChatVerdict::Allow
+    }
+}
+
+pub struct RoomChatService {
+    redis: Arc<RedisClient>,
+    room_history_service: Arc<RoomHistoryService>,
+    room_fanout: Arc<RoomFanout>,
+    subscribe_manager: Arc<SubscribeManager>,
+    security_service: Arc<SecurityService>,
+    filter: Arc<dyn RoomChatFilter>,
+    default_max_length: usize,
+}
+
+impl RoomChatService {
+    pub fn new(
+        redis: Arc<RedisClient>,
+        room_history_service: Arc<RoomHistoryService>,
+        room_fanout: Arc<RoomFanout>,
+        subscribe_manager: Arc<SubscribeManager>,
+        security_service: Arc<SecurityService>,
+        config: &RoomConfig,
+    ) -> Self {
+        Self {
+            redis,
+            room_history_service,
+            room_fanout,
+            subscribe_manager,
+            security_service,
+            filter: Arc::new(KeywordRoomChatFilter::new(
+                &config.chat_blocked_keywords,
+                &config.chat_blocked_patterns,
+            )),
+            default_max_length: config.chat_max_length.clamp(1, MAX_MESSAGE_LENGTH),
+        }
+    }
+
+    /// 替换内容过滤器（接入外部审核服务）
+    pub fn with_filter(mut self, filter: Arc<dyn RoomChatFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// 房间聊天设置；未设置过的房间为关闭状态
+    pub async fn settings(&self, channel_id: u64) -> Result<RoomChatSettings> {
+        let Some(raw) = self.redis.get(&settings_key(channel_id)).await? else {
+            return Ok(RoomChatSettings::default());
+        };
+        serde_json::from_str(&raw).map_err(|e| {
+            ServerError::Internal(format!("Room {} 聊天设置解析失败: {}", channel_id, e))
+        })
+    }
+
+    /// 覆盖房间聊天设置（管理 API）
+    pub async fn update_settings(
+        &self,
+        channel_id: u64,
+        settings: RoomChatSettings,
+    ) -> Result<RoomChatSettings> {
+        let settings = settings.normalized()?;
+        let raw = serde_json::to_string(&settings)
+            .map_err(|e| ServerError::Internal(format!("聊天设置序列化失败: {}", e)))?;
+        self.redis.set(&settings_key(channel_id), &raw).await?;
+        info!(
+            "💬 Room {} 聊天设置已更新: enabled={}, slow_mode_secs={}, moderators={}",
+            channel_id,
+            settings.enabled,
+            settings.slow_mode_secs,
+            settings.moderators.len()
+        );
+        Ok(settings)
+    }
+
+    /// 当前用户视角的聊天状态
+    pub async fn info(&self, channel_id: u64, user_id: u64) -> Result<RoomChatInfo> {
+        let settings = self.settings(channel_id).await?;
+        let mute = self.mute_state(channel_id, user_id).await?;
+        let pinned = self
+            .room_history_service
+            .get_pinned(channel_id)
+            .await?
+            .and_then(|frame| pinned_message(&frame));
+        Ok(RoomChatInfo {
+            channel_id,
+            enabled: settings.enabled,
+            slow_mode_secs: settings.slow_mode_secs,
+            max_length: self.max_length(&settings),
+            is_moderator: settings.is_moderator(user_id),
+            muted: mute.is_some(),
+            muted_until: mute.filter(|until| *until > 0),
+            pinned,
+        })
+    }
+
+    /// 订阅者发言
+    pub async fn send(
+        &self,
+        channel_id: u64,
+        user_id: u64,
+        device_id: &str,
+        session_id: SessionId,
+        text: &str,
+    ) -> Result<RoomChatMessage> {
+        let settings = self.settings(channel_id).await?;
+        if !settings.enabled {
+            return Err(ServerError::PermissionDenied(
+                "该 Room 未开启聊天".to_string(),
+            ));
+        }
+        if !self
+            .subscribe_manager
+            .get_session_channels(&session_id)
+            .contains(&channel_id)
+        {
+            return Err(ServerError::PermissionDenied(
+                "请先订阅该 Room 再发言".to_string(),
+            ));
+        }
+
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ServerError::Validation("消息内容不能为空".to_string()));
+        }
+        let max_length = self.max_length(&settings);
+        if text.chars().count() > max_length {
+            return Err(ServerError::Validation(format!(
+                "消息不能超过 {} 个字符",
+                max_length
+            )));
+        }
+
+        if self.mute_state(channel_id, user_id).await?.is_some() {
+            return Err(ServerError::PermissionDenied("你已被禁言".to_string()));
+        }
+
+        let check = self
+            .security_service
+            .check_rpc(
+                user_id,
+                device_id,
+                ROOM_CHAT_RATE_LIMIT_METHOD,
+                Some(channel_id),
+            )
+            .await;
+        if !check.allowed {
+            return Err(ServerError::RateLimit(
+                check.reason.unwrap_or_else(|| "发言过于频繁".to_string()),
+            ));
+        }
+        if let Some(delay) = check.throttle_delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        let text = match self
+            .filter
+            .check(channel_id, user_id, text, &settings)
+            .await
+        {
+            RoomChatVerdict::Allow => text.to_string(),
+            RoomChatVerdict::Replace(replaced) => replaced,
+            RoomChatVerdict::Reject(reason) => return Err(ServerError::Validation(reason)),
+        };
+
+        let message = RoomChatMessage {
+            server_message_id: crate::infra::next_message_id(),
+            channel_id,
+            user_id,
+            text,
+            sent_at: chrono::Utc::now().timestamp_millis(),
+        };
+
+        let frame = publish_frame(
+            channel_id,
+            ROOM_CHAT_TOPIC,
+            user_id,
+            &message,
+            Some(message.server_message_id),
+        )?;
+        // 先占广播名额：房间积压时直接拒绝，不写入历史
+        let permit = if check.should_silent_drop {
+            None
+        } else {
+            Some(self.room_fanout.try_acquire(channel_id)?)
+        };
+
+        // 慢速模式放在内容过滤与积压判断之后：被过滤或因积压被拒的发言不占窗口。
+        // Shadow ban 同样计窗口，否则被静默的人能从发言频率上察觉。
+        // 判定与占用在一个 Redis 脚本里完成（与群慢速模式同一套窗口），值为占用时间。
+        let slow_mode_window = if settings.slow_mode_secs > 0 && !settings.is_moderator(user_id) {
+            let key = slow_mode_key(channel_id, user_id);
+            let (acquired, started_ms, now_ms) = self
+                .redis
+                .acquire_interval_window(&key, settings.slow_mode_secs * 1000)
+                .await?;
+            if !acquired {
+                let remaining =
+                    remaining_secs(started_ms, settings.slow_mode_secs, now_ms).unwrap_or(1);
+                return Err(ServerError::RateLimit(format!(
+                    "慢速模式：请 {} 秒后再发言",
+                    remaining
+                )));
+            }
+            Some((key, started_ms))
+        } else {
+            None
+        };
+
+        // Shadow ban：对发送者表现为成功，但不落历史也不广播
+        let Some(permit) = permit else {
+            return Ok(message);
+        };
+        if let Err(e) = self
+            .room_history_service
+            .append_history(channel_id, &frame)
+            .await
+        {
+            warn!(
+                "⚠️ Room 聊天写入历史失败 channel_id={}, server_msg_id={}: {}",
+                channel_id, message.server_message_id, e
+            );
+        }
+        if let Err(e) = self.room_fanout.publish(permit, frame).await {
+            // 没广播出去的发言不占窗口；只删自己占的那一份，窗口已被新发言占用时不动
+            if let Some((key, started_ms)) = slow_mode_window {
+                if let Err(release_err) = self
+                    .redis
+                    .compare_and_del(&key, &started_ms.to_string())
+                    .await
+                {
+                    warn!(
+                        "⚠️ Room 慢速模式窗口交还失败 channel_id={}, user_id={}: {}",
+                        channel_id, user_id, release_err
+                    );
+                }
+            }
+            return Err(e);
+        }
+        Ok(message)
+    }
+
+    /// 主持人删除消息；返回是否从近期历史中移除
+    ///
+    /// 无论历史里是否还有该消息都会广播删除事件，在线客户端据此移除本地展示。
+    pub async fn delete(
+        &self,
+        channel_id: u64,
+        moderator_id: u64,
+        server_message_id: u64,
+    ) -> Result<bool> {
+        self.ensure_moderator(channel_id, moderator_id).await?;
+
+        let removed = self
+            .room_history_service
+            .remove_history(channel_id, server_message_id)
+            .await?;
+        let pinned = self.room_history_service.get_pinned(channel_id).await?;
+        if pinned.and_then(|frame| frame.server_message_id) == Some(server_message_id) {
+            self.room_history_service
+                .set_pinned(channel_id, None)
+                .await?;
+            self.broadcast_event(
+                channel_id,
+                ROOM_CHAT_UNPIN_TOPIC,
+                moderator_id,
+                &json!({ "unpinned_by": moderator_id }),
+                None,
+            )
+            .await?;
+        }
+
+        self.broadcast_event(
+            channel_id,
+            ROOM_CHAT_DELETE_TOPIC,
+            moderator_id,
+            &json!({ "server_message_id": server_message_id, "deleted_by": moderator_id }),
+            Some(server_message_id),
+        )
+        .await?;
+        info!(
+            "💬 Room {} 消息 {} 被主持人 {} 删除",
+            channel_id, server_message_id, moderator_id
+        );
+        Ok(removed)
+    }
+
+    /// 禁言；`duration_secs = 0` 为永久。返回到期时间（毫秒，永久为 None）
+    pub async fn mute(
+        &self,
+        channel_id: u64,
+        moderator_id: u64,
+        target_user_id: u64,
+        duration_secs: u64,
+    ) -> Result<Option<i64>> {
+        let settings = self.ensure_moderator(channel_id, moderator_id).await?;
+        if settings.is_moderator(target_user_id) {
+            return Err(ServerError::Validation("不能禁言主持人".to_string()));
+        }
+        if duration_secs > MAX_MUTE_SECS {
+            return Err(ServerError::Validation(format!(
+                "禁言时长不能超过 {} 秒",
+                MAX_MUTE_SECS
+            )));
+        }
+
+        let key = mute_key(channel_id, target_user_id);
+        let muted_until = if duration_secs == 0 {
+            self.redis.set(&key, "0").await?;
+            None
+        } else {
+            let until = chrono::Utc::now().timestamp_millis() + duration_secs as i64 * 1000;
+            self.redis
+                .setex(&key, duration_secs as usize, &until.to_string())
+                .await?;
+            Some(until)
+        };
+        info!(
+            "💬 Room {} 用户 {} 被主持人 {} 禁言 duration_secs={}",
+            channel_id, target_user_id, moderator_id, duration_secs
+        );
+        Ok(muted_until)
+    }
+
+    /// 解除禁言
+    pub async fn unmute(
+        &self,
+        channel_id: u64,
+        moderator_id: u64,
+        target_user_id: u64,
+    ) -> Result<()> {
+        self.ensure_moderator(channel_id, moderator_id).await?;
+        self.redis.del(&mute_key(channel_id, target_user_id)).await
+    }
+
+    /// 置顶近期历史中的一条聊天消息（替换已有置顶）
+    pub async fn pin(
+        &self,
+        channel_id: u64,
+        moderator_id: u64,
+        server_message_id: u64,
+    ) -> Result<RoomChatMessage> {
+        self.ensure_moderator(channel_id, moderator_id).await?;
+
+        let message = self
+            .room_history_service
+            .find_history(channel_id, server_message_id)
+            .await?
+            .filter(|frame| frame.topic.as_deref() == Some(ROOM_CHAT_TOPIC))
+            .and_then(|frame| serde_json::from_slice::<RoomChatMessage>(&frame.payload).ok())
+            .ok_or_else(|| {
+                ServerError::NotFound(format!("近期聊天中没有消息 {}", server_message_id))
+            })?;
+
+        let frame = publish_frame(
+            channel_id,
+            ROOM_CHAT_PIN_TOPIC,
+            moderator_id,
+            &json!({ "message": message, "pinned_by": moderator_id }),
+            Some(server_message_id),
+        )?;
+        self.room_history_service
+            .set_pinned(channel_id, Some(&frame))
+            .await?;
+        let permit = self.room_fanout.try_acquire(channel_id)?;
+        self.room_fanout.publish(permit, frame).await?;
+        Ok(message)
+    }
+
+    /// 取消置顶；没有置顶时返回 false
+    pub async fn unpin(&self, channel_id: u64, moderator_id: u64) -> Result<bool> {
+        self.ensure_moderator(channel_id, moderator_id).await?;
+        if self
+            .room_history_service
+            .get_pinned(channel_id)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self.room_history_service
+            .set_pinned(channel_id, None)
+            .await?;
+        self.broadcast_event(
+            channel_id,
+            ROOM_CHAT_UNPIN_TOPIC,
+            moderator_id,
+            &json!({ "unpinned_by": moderator_id }),
+            None,
+        )
+        .await?;
+        Ok(true)
+    }
+
+    async fn ensure_moderator(&self, channel_id: u64, user_id: u64) -> Result<RoomChatSettings> {
+        let settings = self.settings(channel_id).await?;
+        if !settings.is_moderator(user_id) {
+            return Err(ServerError::PermissionDenied(
+                "仅房间主持人可执行该操作".to_string(),
+            ));
+        }
+        Ok(settings)
+    }
+
+    /// 禁言状态：None = 未禁言；Some(0) = 永久；Some(t) = 到期时间（毫秒）
+    async fn mute_state(&self, channel_id: u64, user_id: u64) -> Result<Option<i64>> {
+        Ok(self
+            .redis
+            .get(&mute_key(channel_id, user_id))
+            .await?
+            .map(|raw| raw.parse::<i64>().unwrap_or(0)))
+    }
+
+    fn max_length(&self, settings: &RoomChatSettings) -> usize {
+        if settings.max_length > 0 {
+            settings.max_length
+        } else {
+            self.default_max_length
+        }
+    }
+
+    async fn broadcast_event(
+        &self,
+        channel_id: u64,
+        topic: &str,
+        publisher: u64,
+        payload: &Value,
+        server_message_id: Option<u64>,
+    ) -> Result<()> {
+        let frame = publish_frame(channel_id, topic, publisher, payload, server_message_id)?;
+        let permit = self.room_fanout.try_acquire(channel_id)?;
+        self.room_fanout.publish(permit, frame).await?;
+        Ok(())
+    }
+}
+
+fn publish_frame<T: Serialize>(
+    channel_id: u64,
+    topic: &str,
+    publisher: u64,
+    payload: &T,
+    server_message_id: Option<u64>,
+) -> Result<PublishRequest> {
+    let payload = serde_json::to_vec(payload)
+        .map_err(|e| ServerError::Internal(format!("Room 事件序列化失败: {}", e)))?;
+    Ok(PublishRequest {
+        channel_id,
+        topic: Some(topic.to_string()),
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        payload,
+        publisher: Some(publisher.to_string()),
+        server_message_id,
+    })
+}
+
+fn pinned_message(frame: &PublishRequest) -> Option<RoomChatMessage> {
+    let payload: Value = serde_json::from_slice(&frame.payload).ok()?;
+    serde_json::from_value(payload.get("message")?.clone()).ok()
+}
+
+fn settings_key(channel_id: u64) -> String {
+    format!("room:{}:chat:settings", channel_id)
+}
+
+fn mute_key(channel_id: u64, user_id: u64) -> String {
+    format!("room:{}:chat:mute:{}", channel_id, user_id)
+}
+
+fn slow_mode_key(channel_id: u64, user_id: u64) -> String {
+    format!("room:{}:chat:slow:{}", channel_id, user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keyword_filter_matches_case_insensitively() {
+        let filter = KeywordRoomChatFilter::new(&["Spam".to_string(), "  ".to_string()], &[]);
+        let settings = RoomChatSettings {
+            blocked_keywords: vec!["广告".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filter.check(1, 2, "hello", &settings).await,
+            RoomChatVerdict::Allow
+        );
+        assert!(matches!(
+            filter.check(1, 2, "buy SPAM now", &settings).await,
+            RoomChatVerdict::Reject(_)
+        ));
+        assert!(matches!(
+            filter.check(1, 2, "加我看广告", &settings).await,
+            RoomChatVerdict::Reject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn regex_filter_skips_invalid_patterns() {
+        let filter =
+            KeywordRoomChatFilter::new(&[], &[r"\d{11}".to_string(), "([unclosed".to_string()]);
+        assert_eq!(filter.patterns.len(), 1);
+
+        let settings = RoomChatSettings::default();
+        assert!(matches!(
+            filter.check(1, 2, "call 13800138000", &settings).await,
+            RoomChatVerdict::Reject(_)
+        ));
+        assert_eq!(
+            filter.check(1, 2, "call me", &settings).await,
+            RoomChatVerdict::Allow
+        );
+    }
+
+    #[test]
+    fn settings_are_normalized_and_bounded() {
+        let settings = RoomChatSettings {
+            enabled: true,
+            slow_mode_secs: 10,
+            max_length: 0,
+            moderators: vec![3, 1, 3],
+            blocked_keywords: vec![" foo ".to_string(), "".to_string()],
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(settings.moderators, vec![1, 3]);
+        assert_eq!(settings.blocked_keywords, vec!["foo".to_string()]);
+        assert!(settings.is_moderator(3));
+        assert!(!settings.is_moderator(2));
+
+        let too_slow = RoomChatSettings {
+            slow_mode_secs: MAX_SLOW_MODE_SECS + 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            too_slow.normalized(),
+            Err(ServerError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn pinned_frame_roundtrips_message() {
+        let message = RoomChatMessage {
+            server_message_id: u64::MAX - 1,
+            channel_id: 9,
+            user_id: 42,
+            text: "置顶".to_string(),
+            sent_at: 1,
+        };
+        let frame = publish_frame(
+            9,
+            ROOM_CHAT_PIN_TOPIC,
+            7,
+            &json!({ "message": message, "pinned_by": 7 }),
+            Some(message.server_message_id),
+        )
+        .unwrap();
+        assert_eq!(frame.topic.as_deref(), Some(ROOM_CHAT_PIN_TOPIC));
+        assert_eq!(pinned_message(&frame), Some(message));
+    }
+}
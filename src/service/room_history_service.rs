@@ -111,9 +111,108 @@ impl RoomHistoryService {
         Ok(items)
     }
 
+    /// 从近期历史中移除一条消息（按 server_message_id），返回是否命中
+    pub async fn remove_history(&self, channel_id: u64, server_message_id: u64) -> Result<bool> {
+        let key = Self::history_key(channel_id);
+        let limit = self.subscribe_history_limit();
+        if limit == 0 {
+            return Ok(false);
+        }
+        let raw_items = self
+            .redis
+            .lrange(&key, 0, limit.saturating_sub(1) as isize)
+            .await?;
+
+        let before = raw_items.len();
+        let kept: Vec<String> = raw_items
+            .into_iter()
+            .filter(|raw| {
+                serde_json::from_str::<privchat_protocol::protocol::PublishRequest>(raw)
+                    .map(|item| item.server_message_id != Some(server_message_id))
+                    .unwrap_or(true)
+            })
+            .collect();
+        if kept.len() == before {
+            return Ok(false);
+        }
+
+        // lrange 为新 -> 旧，replace 保持同一顺序写回
+        self.redis
+            .replace_list_ltrim_expire(&key, &kept, limit, self.config.history_ttl_seconds)
+            .await?;
+        debug!(
+            "📚 RoomHistory: removed channel_id={}, server_message_id={}",
+            channel_id, server_message_id
+        );
+        Ok(true)
+    }
+
+    /// 在近期历史中按 server_message_id 查找
+    pub async fn find_history(
+        &self,
+        channel_id: u64,
+        server_message_id: u64,
+    ) -> Result<Option<privchat_protocol::protocol::PublishRequest>> {
+        Ok(self
+            .list_recent_history(channel_id)
+            .await?
+            .into_iter()
+            .find(|item| item.server_message_id == Some(server_message_id)))
+    }
+
+    /// 设置 / 清除置顶消息（每个 room 一个槽位；TTL 与历史一致）
+    pub async fn set_pinned(
+        &self,
+        channel_id: u64,
+        publish_request: Option<&privchat_protocol::protocol::PublishRequest>,
+    ) -> Result<()> {
+        let key = Self::pinned_key(channel_id);
+        let Some(publish_request) = publish_request else {
+            return self.redis.del(&key).await;
+        };
+
+        let payload = serde_json::to_string(publish_request).map_err(|e| {
+            ServerError::Internal(format!(
+                "Room 置顶序列化失败: channel_id={}, error={}",
+                channel_id, e
+            ))
+        })?;
+        if self.config.history_ttl_seconds > 0 {
+            self.redis
+                .setex(&key, self.config.history_ttl_seconds, &payload)
+                .await
+        } else {
+            self.redis.set(&key, &payload).await
+        }
+    }
+
+    /// 当前置顶消息
+    pub async fn get_pinned(
+        &self,
+        channel_id: u64,
+    ) -> Result<Option<privchat_protocol::protocol::PublishRequest>> {
+        let Some(raw) = self.redis.get(&Self::pinned_key(channel_id)).await? else {
+            return Ok(None);
+        };
+        match serde_json::from_str(&raw) {
+            Ok(item) => Ok(Some(item)),
+            Err(e) => {
+                warn!(
+                    "⚠️ RoomHistory: 置顶反序列化失败 channel_id={}, error={}",
+                    channel_id, e
+                );
+                Ok(None)
+            }
+        }
+    }
+
     fn history_key(channel_id: u64) -> String {
         format!("room:{}:recent", channel_id)
     }
+
+    fn pinned_key(channel_id: u64) -> String {
+        format!("room:{}:pinned", channel_id)
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +222,7 @@ mod tests {
     #[test]
     fn history_key_matches_room_channel_spec() {
         assert_eq!(RoomHistoryService::history_key(42), "room:42:recent");
+        assert_eq!(RoomHistoryService::pinned_key(42), "room:42:pinned");
     }
 
     #[test]
@@ -25,7 +25,10 @@
 //! 判定顺序有意与历史行为一致，因为客户端按错误码做不同的提示与重试：
 //!
 //! 1. 是不是会话成员
-//! 2. 群：个人禁言 → 全员禁言（群主/管理员豁免）→ 角色发言权限
+//! 2. 群：个人禁言 → 全员禁言（群主/管理员豁免）→ 角色发言权限；
+//!    慢速模式（群主/管理员豁免）**不在这里占用**：放行返回 [`SendPermit`]，
+//!    调用方在消息自身的校验也全部通过、即将落库时 [`SendPermit::claim`]，
+//!    落库失败再 [`SendClaim::release`]——被拒的消息不消耗窗口
 //! 3. 私聊：**先看双向拉黑**（拉黑不解除好友关系，好友也可能已被拉黑），
 //!    再看好友；非好友才过对方的「仅接收好友消息」
 //!
@@ -36,8 +39,12 @@ use std::sync::Arc;
 
 use privchat_protocol::error_code::ErrorCode;
 
+use crate::error::ServerError;
 use crate::model::channel::{Channel, ChannelType, MemberPermissions, MemberRole};
-use crate::service::{BlacklistService, ChannelService, FriendService, PrivacyService};
+use crate::service::{
+    BlacklistService, ChannelService, FriendService, GroupSlowModeService, PrivacyService,
+    SlowModeAcquire, SlowModeWindow,
+};
 
 /// 拒绝理由。**每一条都带自己的错误码**——统一成 `PermissionDenied` 会让
 /// 客户端无法区分「你被禁言了（等一会儿）」和「对方把你拉黑了（别再发了）」。
@@ -56,6 +63,10 @@ pub enum SendRefusal {
     PeerRejectsNonFriends,
     /// 频道设置不允许该成员发言（如群 `allow_member_post=false`）。
     ChannelForbidsPosting,
+    /// 群慢速模式冷却中，还需等待 `remaining_secs` 秒。
+    SlowMode {
+        remaining_secs: u64,
+    },
     /// 策略判定不出来（数据库故障等）。**不是「有权」也不是「无权」**——
     /// 限制性策略查不到时按拒绝处理，但错误码要能和真正的无权区分开，
     /// 否则客户端会引导用户去申请权限，而真实原因是服务异常。
@@ -73,6 +84,7 @@ impl SendRefusal {
             SendRefusal::PeerInMyBlacklist => ErrorCode::UserInBlacklist,
             SendRefusal::PeerRejectsNonFriends => ErrorCode::PermissionDenied,
             SendRefusal::ChannelForbidsPosting => ErrorCode::PermissionDenied,
+            SendRefusal::SlowMode { .. } => ErrorCode::RateLimitExceeded,
             // 🔴 必须是 ServiceUnavailable(3) 而不是 InternalError(4)：
             // 两端 SDK 的可重试白名单里有 3、没有 4。用 4 的话文案说「稍后重试」，
             // outbox 却按终局失败处理——用户看到的是一条永远发不出去的消息。
@@ -92,11 +104,25 @@ impl SendRefusal {
                 "对方设置了仅接收好友消息，无法发送".to_string()
             }
             SendRefusal::ChannelForbidsPosting => "无权限发送消息".to_string(),
+            SendRefusal::SlowMode { remaining_secs } => {
+                format!("本群已开启慢速模式，请 {} 秒后再发送", remaining_secs)
+            }
             SendRefusal::PolicyUnavailable => "服务暂时不可用，请稍后重试".to_string(),
         }
     }
 }
 
+/// HTTP 写入口（Bot API 等）用：慢速模式 → 429，策略不可用 → 503，其余 → 403。
+impl From<SendRefusal> for ServerError {
+    fn from(refusal: SendRefusal) -> Self {
+        match refusal {
+            SendRefusal::SlowMode { .. } => ServerError::RateLimit(refusal.message()),
+            SendRefusal::PolicyUnavailable => ServerError::ServiceUnavailable(refusal.message()),
+            _ => ServerError::Forbidden(refusal.message()),
+        }
+    }
+}
+
 /// 判定需要的服务。集中成一个结构体，免得每个调用点各传一串参数、
 /// 漏传一个就悄悄少一道校验。
 #[derive(Clone)]
@@ -105,21 +131,89 @@ pub struct SendAuthorizationDeps {
     pub friend_service: Arc<FriendService>,
     pub blacklist_service: Arc<BlacklistService>,
     pub privacy_service: Arc<PrivacyService>,
+    pub group_slow_mode_service: Arc<GroupSlowModeService>,
+}
+
+/// 放行凭证。判定本身不占用慢速模式窗口：判定之后消息还可能因内容、
+/// 附件、落库失败被拒，那时窗口已经没了，成员白等一个间隔。
+#[must_use = "慢速模式窗口要在落库前 claim"]
+#[derive(Default)]
+pub struct SendPermit {
+    slow_mode: Option<SlowModeGate>,
+}
+
+struct SlowModeGate {
+    service: Arc<GroupSlowModeService>,
+    group_id: u64,
+    sender_id: u64,
+    interval_secs: u32,
+}
+
+impl SendPermit {
+    /// 占用慢速模式窗口（若有）。在消息的全部校验之后、落库之前调用。
+    /// Redis 不可用时与其它限制性策略一样按拒绝处理。
+    pub async fn claim(self) -> Result<SendClaim, SendRefusal> {
+        let Some(gate) = self.slow_mode else {
+            return Ok(SendClaim::default());
+        };
+        let acquired = gate
+            .service
+            .try_acquire(gate.group_id, gate.sender_id, gate.interval_secs)
+            .await
+            .map_err(|e| {
+                tracing::error!("占用群 {} 慢速模式窗口失败: {e}", gate.group_id);
+                SendRefusal::PolicyUnavailable
+            })?;
+        match acquired {
+            SlowModeAcquire::Unlimited => Ok(SendClaim::default()),
+            SlowModeAcquire::Acquired(window) => Ok(SendClaim {
+                window: Some((gate.service, window)),
+            }),
+            SlowModeAcquire::Cooling { remaining_secs } => {
+                Err(SendRefusal::SlowMode { remaining_secs })
+            }
+        }
+    }
+}
+
+/// [`SendPermit::claim`] 占到的窗口。消息最终没有新写入（落库失败、幂等命中既有消息）
+/// 时 [`release`](Self::release)，否则这条并未发出的消息也会让成员等满一个间隔。
+#[derive(Default)]
+pub struct SendClaim {
+    window: Option<(Arc<GroupSlowModeService>, SlowModeWindow)>,
+}
+
+impl SendClaim {
+    /// 交还窗口。尽力而为：失败只记日志，窗口按 TTL 自然过期。
+    pub async fn release(self) {
+        if let Some((service, window)) = self.window {
+            if let Err(e) = service.release(&window).await {
+                tracing::warn!(
+                    "交还群 {} 慢速模式窗口失败 user={}: {e}",
+                    window.group_id,
+                    window.user_id
+                );
+            }
+        }
+    }
 }
 
 /// 判定 `sender_id` 能否向 `channel` 写入一条消息。
 ///
-/// `Ok(())` = 放行。副作用只有一个：临时禁言到期时异步懒清理 DB/缓存，
+/// `Ok(permit)` = 放行，慢速模式窗口留给调用方落库前 [`SendPermit::claim`]。
+/// 副作用只有一个：临时禁言到期时异步懒清理 DB/缓存，
 /// 与历史行为一致（此处持成员引用，不能同步取写锁）。
 pub async fn authorize_send_to_channel(
     deps: &SendAuthorizationDeps,
     channel: &Channel,
     sender_id: u64,
-) -> Result<(), SendRefusal> {
+) -> Result<SendPermit, SendRefusal> {
     let member = channel.members.get(&sender_id);
     if member.is_none() {
         return Err(SendRefusal::NotAMember);
     }
+    // 群慢速模式间隔，只对普通成员取值；窗口由调用方落库前占用。
+    let mut slow_mode_secs = 0;
 
     if channel.channel_type == ChannelType::Group {
         let member = member.expect("membership checked above");
@@ -181,6 +275,7 @@ pub async fn authorize_send_to_channel(
             if !policy.map(|p| p.allow_member_post).unwrap_or(true) {
                 return Err(SendRefusal::ChannelForbidsPosting);
             }
+            slow_mode_secs = policy.map(|p| p.slow_mode_secs).unwrap_or(0);
         }
 
         if !MemberPermissions::from_role(member.role).can_send_message {
@@ -202,6 +297,19 @@ pub async fn authorize_send_to_channel(
         return Err(SendRefusal::ChannelForbidsPosting);
     }
 
+    // 慢速模式只记下间隔：判定之后的任何拒绝都不该白白占掉成员的发言窗口。
+    let permit = SendPermit {
+        slow_mode: channel
+            .group_id
+            .filter(|_| slow_mode_secs > 0)
+            .map(|group_id| SlowModeGate {
+                service: deps.group_slow_mode_service.clone(),
+                group_id,
+                sender_id,
+                interval_secs: slow_mode_secs,
+            }),
+    };
+
     if channel.channel_type == ChannelType::Direct {
         let peer_id = channel
             .get_member_ids()
@@ -245,7 +353,7 @@ pub async fn authorize_send_to_channel(
                     SendRefusal::PolicyUnavailable
                 })?;
             if are_friends {
-                return Ok(());
+                return Ok(permit);
             }
 
             // 🔴 隐私设置取不到同样按拒绝：原来的「默认允许」会让「仅接收好友消息」
@@ -264,7 +372,7 @@ pub async fn authorize_send_to_channel(
         }
     }
 
-    Ok(())
+    Ok(permit)
 }
 
 #[cfg(test)]
@@ -310,10 +418,48 @@ mod tests {
         );
     }
 
+    /// 慢速模式是「等一会儿」而不是「无权」：错误码可区分，文案带剩余秒数。
+    #[test]
+    fn a_slow_mode_refusal_reports_the_remaining_wait() {
+        let refusal = SendRefusal::SlowMode { remaining_secs: 12 };
+        assert_eq!(refusal.error_code(), ErrorCode::RateLimitExceeded);
+        assert_ne!(
+            refusal.error_code(),
+            SendRefusal::RoleCannotSend.error_code()
+        );
+        assert!(refusal.message().contains("12 秒"));
+    }
+
     #[test]
     fn a_mute_refusal_keeps_the_generated_text() {
         let refusal = SendRefusal::MemberMuted("你已被禁言至 12:00".into());
         assert_eq!(refusal.message(), "你已被禁言至 12:00");
     }
 
+    #[test]
+    fn http_write_paths_map_refusals_to_distinct_statuses() {
+        assert!(matches!(
+            ServerError::from(SendRefusal::SlowMode { remaining_secs: 3 }),
+            ServerError::RateLimit(_)
+        ));
+        assert!(matches!(
+            ServerError::from(SendRefusal::PolicyUnavailable),
+            ServerError::ServiceUnavailable(_)
+        ));
+        assert!(matches!(
+            ServerError::from(SendRefusal::BlockedByPeer),
+            ServerError::Forbidden(_)
+        ));
+    }
+
+    /// 没有慢速模式的放行不碰 Redis，交还也是空操作。
+    #[tokio::test]
+    async fn a_permit_without_slow_mode_claims_nothing() {
+        let claim = SendPermit::default()
+            .claim()
+            .await
+            .expect("no window to take");
+        assert!(claim.window.is_none());
+        claim.release().await;
+    }
 }
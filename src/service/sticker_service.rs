@@ -18,12 +18,28 @@
 //! 表情包服务
 //!
 //! 提供表情包库管理功能，包括：
-//! - 内置表情包库
-//! - 表情包查询
-//! - 表情包包详情
+//! - 表情包库目录（PostgreSQL 真源，管理 API 维护）
+//! - 表情图片上传（走 FileService 存储源，business_type = `sticker`）
+//! - 用户收藏的表情包库、最近使用的表情
+//! - 发送 sticker 消息时按目录校验引用
 
+use crate::config::SYSTEM_USER_ID;
+use crate::error::{Result, ServerError};
+use crate::model::file_upload::{FileMetadata, FileType};
+use crate::repository::{
+    NewSticker, NewStickerPackage, StickerPackageRecord, StickerPackageUpdate, StickerRecord,
+    StickerRepository,
+};
+use crate::service::FileService;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 表情图片大小上限（字节）
+pub const STICKER_MAX_IMAGE_BYTES: usize = 1024 * 1024;
+/// 每个用户保留的最近使用表情条数
+pub const RECENT_STICKER_LIMIT: usize = 30;
+/// 表情图片在文件表中的业务类型
+pub const STICKER_BUSINESS_TYPE: &str = "sticker";
 
 /// 表情包
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +48,8 @@ pub struct Sticker {
     pub sticker_id: String,
     /// 所属表情包库 ID
     pub package_id: String,
+    /// 图片文件 ID（FileService）
+    pub file_id: u64,
     /// 图片 URL
     pub image_url: String,
     /// 替代文字（纯文本，客户端显示时添加方括号）
@@ -53,380 +71,390 @@ pub struct StickerPackage {
     pub package_id: String,
     /// 表情包库名称
     pub name: String,
-    /// 缩略图 URL
+    /// 缩略图 URL（未设置时为空串）
     pub thumbnail_url: String,
+    /// 缩略图文件 ID
+    pub thumbnail_file_id: Option<u64>,
     /// 作者/来源
     pub author: String,
     /// 描述
     pub description: String,
+    /// 是否上架
+    pub is_enabled: bool,
+    /// 排序（升序）
+    pub sort_order: i32,
     /// 表情包数量
     pub sticker_count: usize,
+    /// 最后更新时间（毫秒），客户端据此判断本地缓存是否过期
+    pub updated_at: i64,
     /// 所有表情包（仅在获取详情时返回）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stickers: Option<Vec<Sticker>>,
 }
 
+/// 表情包库 / 表情 ID 只允许小写字母、数字、`_`、`-`，长度 1..=64
+///
+/// ID 会原样出现在消息 metadata 里，限制字符集免得各端转义不一致。
+pub fn is_valid_sticker_identifier(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-')
+}
+
 /// 表情包服务
 pub struct StickerService {
-    /// 表情包库（package_id -> StickerPackage）
-    packages: HashMap<String, StickerPackage>,
-    /// 表情包（sticker_id -> Sticker）
-    stickers: HashMap<String, Sticker>,
+    repo: Arc<StickerRepository>,
+    file_service: Arc<FileService>,
 }
 
 impl StickerService {
-    /// 创建新的表情包服务（带内置表情包）
-    pub fn new() -> Self {
-        let mut service = Self {
-            packages: HashMap::new(),
-            stickers: HashMap::new(),
-        };
+    pub fn new(repo: Arc<StickerRepository>, file_service: Arc<FileService>) -> Self {
+        Self { repo, file_service }
+    }
+
+    fn access_url(&self, path: Option<&str>, source_id: Option<i32>) -> String {
+        match (path, source_id) {
+            (Some(path), Some(source_id)) => {
+                self.file_service.build_access_url(path, source_id as u32)
+            }
+            _ => String::new(),
+        }
+    }
 
-        // 初始化内置表情包
-        service.init_builtin_stickers();
+    /// 存储路径 → 访问 URL
+    pub fn image_url(&self, file_path: &str, storage_source_id: u32) -> String {
+        self.file_service
+            .build_access_url(file_path, storage_source_id)
+    }
+
+    fn to_sticker(&self, record: StickerRecord) -> Sticker {
+        let image_url = self.access_url(record.file_path.as_deref(), record.storage_source_id);
+        Sticker {
+            sticker_id: record.sticker_id,
+            package_id: record.package_id,
+            file_id: record.file_id as u64,
+            image_url,
+            alt_text: record.alt_text,
+            emoji: record.emoji,
+            width: record.width.max(0) as u32,
+            height: record.height.max(0) as u32,
+            mime_type: record.mime_type,
+        }
+    }
 
-        service
+    fn to_package(&self, record: StickerPackageRecord) -> StickerPackage {
+        let thumbnail_url =
+            self.access_url(record.thumbnail_path.as_deref(), record.thumbnail_source_id);
+        StickerPackage {
+            package_id: record.package_id,
+            name: record.name,
+            thumbnail_url,
+            thumbnail_file_id: record.thumbnail_file_id.map(|id| id as u64),
+            author: record.author,
+            description: record.description,
+            is_enabled: record.is_enabled,
+            sort_order: record.sort_order,
+            sticker_count: record.sticker_count.max(0) as usize,
+            updated_at: record.updated_at,
+            stickers: None,
+        }
+    }
+
+    /// 获取所有上架的表情包库列表（不包含表情包详情）
+    pub async fn list_packages(&self) -> Result<Vec<StickerPackage>> {
+        let records = self.repo.list_packages(false).await?;
+        Ok(records.into_iter().map(|r| self.to_package(r)).collect())
     }
 
-    /// 初始化内置表情包库
-    fn init_builtin_stickers(&mut self) {
-        // 表情包库 1: 经典表情
-        let classic_stickers = vec![
-            Sticker {
-                sticker_id: "classic_001".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/smile.png".to_string(),
-                alt_text: "微笑".to_string(),
-                emoji: Some("😊".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_002".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/laugh.png".to_string(),
-                alt_text: "大笑".to_string(),
-                emoji: Some("😆".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_003".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/cry.png".to_string(),
-                alt_text: "哭泣".to_string(),
-                emoji: Some("😢".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_004".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/love.png".to_string(),
-                alt_text: "爱心".to_string(),
-                emoji: Some("😍".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_005".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/angry.png".to_string(),
-                alt_text: "生气".to_string(),
-                emoji: Some("😠".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_006".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/cool.png".to_string(),
-                alt_text: "酷".to_string(),
-                emoji: Some("😎".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_007".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/sweat.png".to_string(),
-                alt_text: "汗".to_string(),
-                emoji: Some("😅".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_008".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/thinking.png".to_string(),
-                alt_text: "思考".to_string(),
-                emoji: Some("🤔".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_009".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/thumbsup.png".to_string(),
-                alt_text: "点赞".to_string(),
-                emoji: Some("👍".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "classic_010".to_string(),
-                package_id: "classic".to_string(),
-                image_url: "https://cdn.example.com/stickers/classic/ok.png".to_string(),
-                alt_text: "OK".to_string(),
-                emoji: Some("👌".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-        ];
-
-        let classic_package = StickerPackage {
-            package_id: "classic".to_string(),
-            name: "经典表情".to_string(),
-            thumbnail_url: "https://cdn.example.com/stickers/classic/cover.png".to_string(),
-            author: "PrivChat".to_string(),
-            description: "最常用的经典表情包，适合日常聊天使用".to_string(),
-            sticker_count: classic_stickers.len(),
-            stickers: Some(classic_stickers.clone()),
+    /// 管理端：包含已下架的全部表情包库
+    pub async fn list_all_packages(&self) -> Result<Vec<StickerPackage>> {
+        let records = self.repo.list_packages(true).await?;
+        Ok(records.into_iter().map(|r| self.to_package(r)).collect())
+    }
+
+    /// 获取表情包库详情（包含所有表情包）
+    ///
+    /// `include_disabled = false` 时已下架的库按不存在处理。
+    pub async fn get_package_detail(
+        &self,
+        package_id: &str,
+        include_disabled: bool,
+    ) -> Result<Option<StickerPackage>> {
+        let Some(record) = self.repo.get_package(package_id).await? else {
+            return Ok(None);
         };
+        if !include_disabled && !record.is_enabled {
+            return Ok(None);
+        }
+        let mut package = self.to_package(record);
+        package.stickers = Some(self.list_stickers_by_package(package_id).await?);
+        Ok(Some(package))
+    }
 
-        // 存储表情包库
-        self.packages.insert("classic".to_string(), classic_package);
+    /// 获取单个表情包详情
+    pub async fn get_sticker(&self, sticker_id: &str) -> Result<Option<Sticker>> {
+        Ok(self
+            .repo
+            .get_sticker(sticker_id)
+            .await?
+            .map(|r| self.to_sticker(r)))
+    }
 
-        // 存储所有表情包
-        for sticker in classic_stickers {
-            self.stickers.insert(sticker.sticker_id.clone(), sticker);
+    /// 获取表情包库中的所有表情包
+    pub async fn list_stickers_by_package(&self, package_id: &str) -> Result<Vec<Sticker>> {
+        let records = self.repo.list_stickers(package_id).await?;
+        Ok(records.into_iter().map(|r| self.to_sticker(r)).collect())
+    }
+
+    /// 发送 sticker 消息时校验引用：表情必须存在且所属库处于上架状态
+    pub async fn resolve_sendable(&self, sticker_id: &str) -> Result<Sticker> {
+        let record = self
+            .repo
+            .get_sticker(sticker_id)
+            .await?
+            .ok_or_else(|| ServerError::Validation(format!("表情 {} 不存在", sticker_id)))?;
+        if !record.package_enabled {
+            return Err(ServerError::Validation(format!(
+                "表情 {} 所属表情包库已下架",
+                sticker_id
+            )));
         }
+        Ok(self.to_sticker(record))
+    }
 
-        // 表情包库 2: 动物表情
-        let animal_stickers = vec![
-            Sticker {
-                sticker_id: "animal_001".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/cat.png".to_string(),
-                alt_text: "猫咪".to_string(),
-                emoji: Some("🐱".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_002".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/dog.png".to_string(),
-                alt_text: "狗狗".to_string(),
-                emoji: Some("🐶".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_003".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/rabbit.png".to_string(),
-                alt_text: "兔子".to_string(),
-                emoji: Some("🐰".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_004".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/panda.png".to_string(),
-                alt_text: "熊猫".to_string(),
-                emoji: Some("🐼".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_005".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/bear.png".to_string(),
-                alt_text: "小熊".to_string(),
-                emoji: Some("🐻".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_006".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/monkey.png".to_string(),
-                alt_text: "猴子".to_string(),
-                emoji: Some("🐵".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_007".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/pig.png".to_string(),
-                alt_text: "小猪".to_string(),
-                emoji: Some("🐷".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "animal_008".to_string(),
-                package_id: "animals".to_string(),
-                image_url: "https://cdn.example.com/stickers/animals/fox.png".to_string(),
-                alt_text: "狐狸".to_string(),
-                emoji: Some("🦊".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-        ];
-
-        let animal_package = StickerPackage {
-            package_id: "animals".to_string(),
-            name: "可爱动物".to_string(),
-            thumbnail_url: "https://cdn.example.com/stickers/animals/cover.png".to_string(),
-            author: "PrivChat".to_string(),
-            description: "萌萌的动物表情包，让聊天更有趣".to_string(),
-            sticker_count: animal_stickers.len(),
-            stickers: Some(animal_stickers.clone()),
-        };
+    /// 管理端：创建表情包库
+    pub async fn create_package(&self, package: NewStickerPackage) -> Result<StickerPackage> {
+        if !is_valid_sticker_identifier(&package.package_id) {
+            return Err(ServerError::Validation(
+                "package_id 只能包含小写字母、数字、_ 和 -，长度 1~64".to_string(),
+            ));
+        }
+        if package.name.trim().is_empty() {
+            return Err(ServerError::Validation("表情包库名称不能为空".to_string()));
+        }
+        if let Some(file_id) = package.thumbnail_file_id {
+            self.ensure_image_file(file_id).await?;
+        }
+        self.repo.create_package(&package).await?;
+        self.load_package(&package.package_id).await
+    }
 
-        self.packages.insert("animals".to_string(), animal_package);
+    /// 管理端：更新表情包库
+    pub async fn update_package(
+        &self,
+        package_id: &str,
+        update: StickerPackageUpdate,
+    ) -> Result<StickerPackage> {
+        if update.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+            return Err(ServerError::Validation("表情包库名称不能为空".to_string()));
+        }
+        if let Some(file_id) = update.thumbnail_file_id {
+            self.ensure_image_file(file_id).await?;
+        }
+        if !self.repo.update_package(package_id, &update).await? {
+            return Err(ServerError::NotFound(format!(
+                "表情包库 {} 不存在",
+                package_id
+            )));
+        }
+        self.load_package(package_id).await
+    }
 
-        for sticker in animal_stickers {
-            self.stickers.insert(sticker.sticker_id.clone(), sticker);
+    /// 管理端：删除表情包库
+    ///
+    /// 🔴 图片文件保留：已发出的 sticker 消息 metadata 里存的是 image_url，
+    /// 删掉文件会让历史消息裂图。目录行删除后新消息就无法再引用它们。
+    pub async fn delete_package(&self, package_id: &str) -> Result<()> {
+        if !self.repo.delete_package(package_id).await? {
+            return Err(ServerError::NotFound(format!(
+                "表情包库 {} 不存在",
+                package_id
+            )));
         }
+        Ok(())
+    }
 
-        // 表情包库 3: 食物表情
-        let food_stickers = vec![
-            Sticker {
-                sticker_id: "food_001".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/pizza.png".to_string(),
-                alt_text: "披萨".to_string(),
-                emoji: Some("🍕".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "food_002".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/burger.png".to_string(),
-                alt_text: "汉堡".to_string(),
-                emoji: Some("🍔".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "food_003".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/sushi.png".to_string(),
-                alt_text: "寿司".to_string(),
-                emoji: Some("🍣".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "food_004".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/icecream.png".to_string(),
-                alt_text: "冰淇淋".to_string(),
-                emoji: Some("🍦".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "food_005".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/cake.png".to_string(),
-                alt_text: "蛋糕".to_string(),
-                emoji: Some("🍰".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-            Sticker {
-                sticker_id: "food_006".to_string(),
-                package_id: "food".to_string(),
-                image_url: "https://cdn.example.com/stickers/food/coffee.png".to_string(),
-                alt_text: "咖啡".to_string(),
-                emoji: Some("☕".to_string()),
-                width: 128,
-                height: 128,
-                mime_type: "image/png".to_string(),
-            },
-        ];
-
-        let food_package = StickerPackage {
-            package_id: "food".to_string(),
-            name: "美食世界".to_string(),
-            thumbnail_url: "https://cdn.example.com/stickers/food/cover.png".to_string(),
-            author: "PrivChat".to_string(),
-            description: "各种美食表情，吃货必备".to_string(),
-            sticker_count: food_stickers.len(),
-            stickers: Some(food_stickers.clone()),
-        };
+    /// 管理端：把表情图片写入默认存储源，返回文件元数据
+    pub async fn store_image(
+        &self,
+        data: bytes::Bytes,
+        filename: &str,
+        mime_type: &str,
+    ) -> Result<FileMetadata> {
+        if !mime_type.starts_with("image/") {
+            return Err(ServerError::Validation(format!(
+                "表情图片必须是 image/*，收到 {}",
+                mime_type
+            )));
+        }
+        if data.len() > STICKER_MAX_IMAGE_BYTES {
+            return Err(ServerError::Validation(format!(
+                "表情图片大小超过限制: {} > {} bytes",
+                data.len(),
+                STICKER_MAX_IMAGE_BYTES
+            )));
+        }
 
-        self.packages.insert("food".to_string(), food_package);
+        let upload_id = uuid::Uuid::new_v4().to_string();
+        let mut upload = self
+            .file_service
+            .begin_streaming_upload(
+                mime_type,
+                filename,
+                STICKER_MAX_IMAGE_BYTES as i64,
+                None,
+                Some(FileType::Image),
+                SYSTEM_USER_ID,
+                &upload_id,
+            )
+            .await?;
+        if let Err(e) = upload.write_chunk(data).await {
+            upload.abort().await;
+            return Err(e);
+        }
+        self.file_service
+            .commit_streaming_upload(
+                upload,
+                filename.to_string(),
+                mime_type.to_string(),
+                SYSTEM_USER_ID,
+                None,
+                STICKER_BUSINESS_TYPE.to_string(),
+                None,
+                0,
+                None,
+                0,
+                None,
+                None,
+            )
+            .await
+    }
 
-        for sticker in food_stickers {
-            self.stickers.insert(sticker.sticker_id.clone(), sticker);
+    /// 管理端：向表情包库添加一个表情（图片须已通过 [`Self::store_image`] 入库）
+    pub async fn add_sticker(&self, sticker: NewSticker) -> Result<Sticker> {
+        if !is_valid_sticker_identifier(&sticker.sticker_id) {
+            return Err(ServerError::Validation(
+                "sticker_id 只能包含小写字母、数字、_ 和 -，长度 1~64".to_string(),
+            ));
         }
+        if self.repo.get_package(&sticker.package_id).await?.is_none() {
+            return Err(ServerError::NotFound(format!(
+                "表情包库 {} 不存在",
+                sticker.package_id
+            )));
+        }
+        self.ensure_image_file(sticker.file_id).await?;
+        self.file_service
+            .update_business(sticker.file_id, STICKER_BUSINESS_TYPE, &sticker.package_id)
+            .await?;
+        self.repo.insert_sticker(&sticker).await?;
+        self.get_sticker(&sticker.sticker_id)
+            .await?
+            .ok_or_else(|| ServerError::Internal("表情写入后读取失败".to_string()))
     }
 
-    /// 获取所有表情包库列表（不包含表情包详情）
-    pub async fn list_packages(&self) -> Vec<StickerPackage> {
-        self.packages
-            .values()
-            .map(|pkg| {
-                let mut pkg_copy = pkg.clone();
-                pkg_copy.stickers = None; // 列表中不返回具体表情包
-                pkg_copy
-            })
-            .collect()
+    /// 管理端：从表情包库移除一个表情（图片保留，理由同 [`Self::delete_package`]）
+    pub async fn remove_sticker(&self, package_id: &str, sticker_id: &str) -> Result<()> {
+        if !self.repo.delete_sticker(package_id, sticker_id).await? {
+            return Err(ServerError::NotFound(format!(
+                "表情 {}/{} 不存在",
+                package_id, sticker_id
+            )));
+        }
+        Ok(())
     }
 
-    /// 获取表情包库详情（包含所有表情包）
-    pub async fn get_package_detail(&self, package_id: &str) -> Option<StickerPackage> {
-        self.packages.get(package_id).cloned()
+    /// 用户收藏的表情包库
+    pub async fn list_user_packages(&self, user_id: u64) -> Result<Vec<StickerPackage>> {
+        let records = self.repo.list_user_packages(user_id).await?;
+        Ok(records.into_iter().map(|r| self.to_package(r)).collect())
     }
 
-    /// 获取单个表情包详情
-    pub async fn get_sticker(&self, sticker_id: &str) -> Option<Sticker> {
-        self.stickers.get(sticker_id).cloned()
+    /// 收藏表情包库；返回是否新加入（已收藏时为 false）
+    pub async fn add_user_package(&self, user_id: u64, package_id: &str) -> Result<bool> {
+        match self.repo.get_package(package_id).await? {
+            Some(record) if record.is_enabled => {}
+            _ => {
+                return Err(ServerError::NotFound(format!(
+                    "表情包库 {} 不存在",
+                    package_id
+                )))
+            }
+        }
+        self.repo.add_user_package(user_id, package_id).await
     }
 
-    /// 获取表情包库中的所有表情包
-    pub async fn list_stickers_by_package(&self, package_id: &str) -> Vec<Sticker> {
-        self.stickers
-            .values()
-            .filter(|s| s.package_id == package_id)
-            .cloned()
-            .collect()
+    /// 取消收藏；返回是否确实移除
+    pub async fn remove_user_package(&self, user_id: u64, package_id: &str) -> Result<bool> {
+        self.repo.remove_user_package(user_id, package_id).await
+    }
+
+    /// 记录一次表情使用（sticker 消息发送成功后调用）
+    pub async fn record_usage(&self, user_id: u64, sticker_id: &str) -> Result<()> {
+        self.repo
+            .record_recent(user_id, sticker_id, RECENT_STICKER_LIMIT as i64)
+            .await
+    }
+
+    /// 最近使用的表情
+    pub async fn list_recent(&self, user_id: u64, limit: usize) -> Result<Vec<Sticker>> {
+        let limit = limit.clamp(1, RECENT_STICKER_LIMIT) as i64;
+        let records = self.repo.list_recent(user_id, limit).await?;
+        Ok(records.into_iter().map(|r| self.to_sticker(r)).collect())
+    }
+
+    async fn load_package(&self, package_id: &str) -> Result<StickerPackage> {
+        self.repo
+            .get_package(package_id)
+            .await?
+            .map(|r| self.to_package(r))
+            .ok_or_else(|| ServerError::NotFound(format!("表情包库 {} 不存在", package_id)))
+    }
+
+    async fn ensure_image_file(&self, file_id: u64) -> Result<()> {
+        let metadata = self
+            .file_service
+            .get_file_metadata(file_id)
+            .await?
+            .ok_or_else(|| ServerError::Validation(format!("文件 {} 不存在", file_id)))?;
+        if metadata.file_type != FileType::Image {
+            return Err(ServerError::Validation(format!(
+                "文件 {} 不是图片",
+                file_id
+            )));
+        }
+        if metadata.encryption_version != 0 {
+            // 表情对所有人公开，客户端拿不到 CEK
+            return Err(ServerError::Validation(format!(
+                "文件 {} 是加密附件，不能用作表情",
+                file_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// 删除一张尚未被目录引用的表情图片（上传后建表情失败时回收；尽力而为）
+    pub async fn discard_image(&self, file_id: u64) {
+        if let Err(e) = self.file_service.delete_file(file_id, SYSTEM_USER_ID).await {
+            tracing::warn!("⚠️ 清理表情图片失败 file_id={}: {}", file_id, e);
+        }
     }
 }
 
-impl Default for StickerService {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sticker_identifier() {
+        assert!(is_valid_sticker_identifier("classic"));
+        assert!(is_valid_sticker_identifier("classic_001"));
+        assert!(is_valid_sticker_identifier("new-year-2025"));
+        assert!(!is_valid_sticker_identifier(""));
+        assert!(!is_valid_sticker_identifier("Classic"));
+        assert!(!is_valid_sticker_identifier("a/b"));
+        assert!(!is_valid_sticker_identifier(&"a".repeat(65)));
     }
 }
@@ -95,6 +95,23 @@ impl CommitLogDao {
         commit.populate_canonical_event().map_err(|e| {
             crate::error::ServerError::Internal(format!("canonical event mapping failed: {e}"))
         })?;
+        self.allocate_pts_and_insert(commit).await
+    }
+
+    /// 同 [`Self::allocate_pts_and_save_commit`]，但只写 legacy 字段（canonical_event 为 NULL）。
+    ///
+    /// 用于 CanonicalTimelineEvent 尚未覆盖的频道级事件（如 `channel.history_expired`）：
+    /// 这类 commit 只经 get_difference 拉取，不进入 dispatch outbox 的在线推送。
+    pub async fn allocate_pts_and_save_legacy_commit(
+        &self,
+        commit: &mut ServerCommit,
+    ) -> Result<()> {
+        commit.event_schema_version = None;
+        commit.canonical_event = None;
+        self.allocate_pts_and_insert(commit).await
+    }
+
+    async fn allocate_pts_and_insert(&self, commit: &mut ServerCommit) -> Result<()> {
         let now = chrono::Utc::now().timestamp_millis();
         let sender_username = commit.sender_info.as_ref().map(|s| s.username.as_str());
 
@@ -36,6 +36,7 @@ use crate::repository::{
     AtomicMessageCommitRequest, AtomicTimelineEventRequest, ClientRegistryClaim,
     PgMessageRepository,
 };
+use crate::service::send_authorization::SendPermit;
 use crate::service::{ChannelService, CommittedTimelineDeliveryService, UnreadCountService};
 
 // 重新导出协议类型
@@ -50,6 +51,13 @@ const UNREAD_BATCH_QUEUE_CAPACITY: usize = 4_096;
 const UNREAD_BATCH_INTERVAL: Duration = Duration::from_millis(100);
 const POST_COMMIT_CACHE_QUEUE_CAPACITY: usize = 4_096;
 
+/// 保留期清理通知的 commit 类型（legacy-only，content 带 `expired_before` / `expired_up_to_pts`）
+pub const HISTORY_EXPIRED_COMMIT_TYPE: &str = "channel.history_expired";
+/// 阅后即焚到期通知的 commit 类型（legacy-only，content 带 `message_ids` / `expired_at`）
+pub const MESSAGE_EXPIRED_COMMIT_TYPE: &str = "message.expired";
+/// 注销用户消息清空通知的 commit 类型（legacy-only，content 带 `message_ids` / `redacted_at`）
+pub const MESSAGE_REDACTED_COMMIT_TYPE: &str = "message.redacted";
+
 struct UnreadResolveRequest {
     channel_id: u64,
     sender_id: u64,
@@ -309,6 +317,125 @@ impl SyncService {
         Ok(commit)
     }
 
+    /// 保留期清理后追加 `channel.history_expired` commit，通知客户端删除本地早于
+    /// `expired_before`（毫秒）的消息。
+    ///
+    /// 被清理的 commit 同时从 Redis sync cache 驱逐，避免 get_difference 继续返回已删除的消息。
+    pub async fn append_history_expired_commit(
+        &self,
+        channel_id: u64,
+        channel_type: u8,
+        expired_before: i64,
+        expired_up_to_pts: u64,
+    ) -> Result<ServerCommit> {
+        let mut commit = ServerCommit {
+            event_id: None,
+            pts: 0,
+            server_msg_id: self.generate_msg_id().await,
+            local_message_id: None,
+            channel_id,
+            channel_type,
+            message_type: HISTORY_EXPIRED_COMMIT_TYPE.to_string(),
+            content: serde_json::json!({
+                "channel_id": channel_id,
+                "expired_before": expired_before,
+                "expired_up_to_pts": expired_up_to_pts,
+            }),
+            server_timestamp: chrono::Utc::now().timestamp_millis(),
+            sender_id: 0,
+            sender_info: None,
+            event_schema_version: None,
+            canonical_event: None,
+        };
+        self.commit_dao
+            .allocate_pts_and_save_legacy_commit(&mut commit)
+            .await?;
+        if let Err(error) = self.cache.clear_channel_cache(channel_id).await {
+            warn!(channel_id, %error, "evict expired commits from sync cache failed");
+        }
+        self.cache_committed_commit(&commit).await?;
+        Ok(commit)
+    }
+
+    /// 阅后即焚消息硬删除后追加 `message.expired` commit，通知所有设备删除本地的这些消息。
+    ///
+    /// 被删消息的 commit 已从 commit log 删除，这里同样把会话的 Redis sync cache 驱逐掉。
+    pub async fn append_messages_expired_commit(
+        &self,
+        channel_id: u64,
+        channel_type: u8,
+        message_ids: &[u64],
+        expired_at: i64,
+    ) -> Result<ServerCommit> {
+        self.append_message_notice_commit(
+            channel_id,
+            channel_type,
+            MESSAGE_EXPIRED_COMMIT_TYPE,
+            serde_json::json!({
+                "channel_id": channel_id,
+                "message_ids": message_ids,
+                "expired_at": expired_at,
+            }),
+        )
+        .await
+    }
+
+    /// 注销用户的消息被清空后追加 `message.redacted` commit，通知所有设备抹掉本地内容。
+    ///
+    /// 被清空消息的 commit 内容已在 commit log 中改写，Redis sync cache 里仍是原文，一并驱逐。
+    pub async fn append_messages_redacted_commit(
+        &self,
+        channel_id: u64,
+        channel_type: u8,
+        message_ids: &[u64],
+        redacted_at: i64,
+    ) -> Result<ServerCommit> {
+        self.append_message_notice_commit(
+            channel_id,
+            channel_type,
+            MESSAGE_REDACTED_COMMIT_TYPE,
+            serde_json::json!({
+                "channel_id": channel_id,
+                "message_ids": message_ids,
+                "redacted_at": redacted_at,
+            }),
+        )
+        .await
+    }
+
+    /// 追加一条 legacy-only 的消息变更通知 commit，并驱逐会话的 Redis sync cache
+    async fn append_message_notice_commit(
+        &self,
+        channel_id: u64,
+        channel_type: u8,
+        message_type: &str,
+        content: serde_json::Value,
+    ) -> Result<ServerCommit> {
+        let mut commit = ServerCommit {
+            event_id: None,
+            pts: 0,
+            server_msg_id: self.generate_msg_id().await,
+            local_message_id: None,
+            channel_id,
+            channel_type,
+            message_type: message_type.to_string(),
+            content,
+            server_timestamp: chrono::Utc::now().timestamp_millis(),
+            sender_id: 0,
+            sender_info: None,
+            event_schema_version: None,
+            canonical_event: None,
+        };
+        self.commit_dao
+            .allocate_pts_and_save_legacy_commit(&mut commit)
+            .await?;
+        if let Err(error) = self.cache.clear_channel_cache(channel_id).await {
+            warn!(channel_id, message_type, %error, "evict changed messages from sync cache failed");
+        }
+        self.cache_committed_commit(&commit).await?;
+        Ok(commit)
+    }
+
     /// 为既有业务消息分配下一个可用 pts（以数据库为准）
     ///
     /// 用于发送链路先写业务消息、后写同步 commit 的场景，
@@ -420,7 +547,7 @@ impl SyncService {
     ///
     /// 流程：
     /// 1. 幂等性检查（local_message_id 去重）
-    /// 2. 权限验证
+    /// 2. 权限验证（调用方已判定，`send_permit` 的慢速模式窗口在落库前占用）
     /// 3. 获取服务器当前 pts
     /// 4. 检测间隙
     /// 5. 分配新的 pts（数据库原子操作）
@@ -436,6 +563,7 @@ impl SyncService {
         req: ClientSubmitRequest,
         sender_id: u64,  // 从 JWT token 中提取
         device_id: &str, // 从认证会话提取（CODEX-8：幂等命名空间的 device 维度，服务端权威）
+        send_permit: SendPermit,
     ) -> Result<ClientSubmitResponse> {
         debug!(
             "收到客户端提交: local_message_id={}, channel_id={}, channel_type={}",
@@ -493,6 +621,11 @@ impl SyncService {
         let now = chrono::Utc::now();
         let (message, attachment_refs, canonical_event) =
             Self::message_from_submit(&req, sender_id, server_msg_id, now)?;
+        // 转发与普通提交一样按单条 TTL / 会话默认计时登记阅后即焚
+        let expiry = crate::service::disappearing_service::expiry_arm_for_new_message(
+            req.channel_type,
+            &message.metadata,
+        )?;
         let (_, commit_content) = canonical_event
             .to_legacy_commit(req.channel_id, req.channel_type)
             .map_err(|error| {
@@ -500,7 +633,12 @@ impl SyncService {
                     "sync/submit legacy projection failed: {error}"
                 ))
             })?;
-        let tx_result = self
+        // 全部校验通过才占用慢速模式窗口；重复提交已在上面的快路径返回，不占窗口。
+        let send_claim = send_permit
+            .claim()
+            .await
+            .map_err(crate::error::ServerError::from)?;
+        let tx_result = match self
             .message_repository
             .create_message_and_commit_atomic(AtomicMessageCommitRequest {
                 message,
@@ -513,17 +651,25 @@ impl SyncService {
                 channel_type: i16::from(req.channel_type),
                 event: canonical_event,
                 sender_username: None,
+                expiry,
+                broadcast_attrs: None,
+                mention_targets: Vec::new(),
             })
             .await
-            .map_err(|error| {
-                crate::error::ServerError::Database(format!(
+        {
+            Ok(result) => result,
+            Err(error) => {
+                send_claim.release().await;
+                return Err(crate::error::ServerError::Database(format!(
                     "atomic sync/submit commit failed: {error}"
-                ))
-            })?;
+                )));
+            }
+        };
 
         let message = tx_result.message;
         let new_pts = message.pts.unwrap_or(0).max(0) as u64;
         if !tx_result.inserted {
+            send_claim.release().await;
             return Ok(ClientSubmitResponse {
                 decision: ServerDecision::Accepted,
                 pts: Some(new_pts),
@@ -0,0 +1,340 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 内置账号两步验证（TOTP）
+//!
+//! - 绑定：`setup` 生成密钥与 otpauth URI → 用户在验证器 App 中添加 → `enable` 提交首个
+//!   动态码确认，同时一次性返回恢复码明文
+//! - 登录：密码通过且已启用 2FA 时，login 不签 token，只返回 challenge_token（Redis，
+//!   [`CHALLENGE_TTL_SECS`] 内有效，最多 [`CHALLENGE_MAX_ATTEMPTS`] 次尝试），客户端再提交
+//!   动态码或恢复码换取 token
+//! - 重置：用户关闭需验证当前动态码 / 恢复码；管理员可直接清除（用户丢失设备）
+
+use crate::auth::totp;
+use crate::auth::{AMR_OTP, AMR_RECOVERY_CODE};
+use crate::error::{Result, ServerError};
+use crate::infra::redis::RedisClient;
+use crate::repository::TwoFactorRepository;
+use crate::rpc::qrcode::build_otpauth_uri;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 登录 challenge 有效期（秒）
+pub const CHALLENGE_TTL_SECS: u64 = 300;
+/// 单个 challenge 允许的验证失败次数，用完需重新输入密码
+pub const CHALLENGE_MAX_ATTEMPTS: u32 = 5;
+
+const CHALLENGE_KEY_PREFIX: &str = "privchat:2fa:challenge:";
+const CHALLENGE_EXPIRED_MSG: &str = "两步验证已过期，请重新登录";
+const TOO_MANY_ATTEMPTS_MSG: &str = "验证失败次数过多，请重新登录";
+
+/// 密码已通过、等待第二因子的登录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub user_id: u64,
+    pub device_id: String,
+    pub app_id: String,
+    pub device_type: String,
+}
+
+/// 第二因子
+#[derive(Debug, Clone)]
+pub enum SecondFactor {
+    /// 验证器 App 的 6 位动态码
+    Totp(String),
+    /// 一次性恢复码
+    RecoveryCode(String),
+}
+
+impl SecondFactor {
+    /// 写入 token `amr` 的认证方式
+    pub fn amr(&self) -> &'static str {
+        match self {
+            Self::Totp(_) => AMR_OTP,
+            Self::RecoveryCode(_) => AMR_RECOVERY_CODE,
+        }
+    }
+}
+
+/// `setup` 结果：客户端把 otpauth_uri 渲染成二维码，无法扫码时手动输入 secret
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoFactorSetup {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// 两步验证状态
+#[derive(Debug, Clone, Serialize)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    /// 已 setup 但还没确认
+    pub pending: bool,
+    pub confirmed_at: Option<i64>,
+    pub recovery_codes_remaining: u32,
+}
+
+/// 两步验证服务
+pub struct TwoFactorService {
+    repo: Arc<TwoFactorRepository>,
+    redis: Arc<RedisClient>,
+    issuer: String,
+}
+
+impl TwoFactorService {
+    pub fn new(repo: Arc<TwoFactorRepository>, redis: Arc<RedisClient>, issuer: String) -> Self {
+        Self {
+            repo,
+            redis,
+            issuer,
+        }
+    }
+
+    /// challenge 与其尝试计数共享 hash tag，Cluster 下可在同一脚本内访问
+    fn challenge_key(token: &str) -> String {
+        format!("{}{{{}}}", CHALLENGE_KEY_PREFIX, token)
+    }
+
+    fn challenge_attempts_key(token: &str) -> String {
+        format!("{}{{{}}}:attempts", CHALLENGE_KEY_PREFIX, token)
+    }
+
+    fn decode_secret(secret: &str) -> Result<Vec<u8>> {
+        totp::base32_decode(secret)
+            .ok_or_else(|| ServerError::Internal("两步验证密钥损坏".to_string()))
+    }
+
+    fn now_secs() -> u64 {
+        chrono::Utc::now().timestamp().max(0) as u64
+    }
+
+    /// 登录时是否要求第二因子
+    pub async fn is_enabled(&self, user_id: u64) -> Result<bool> {
+        Ok(self
+            .repo
+            .get(user_id)
+            .await?
+            .map(|t| t.enabled)
+            .unwrap_or(false))
+    }
+
+    pub async fn status(&self, user_id: u64) -> Result<TwoFactorStatus> {
+        let record = self.repo.get(user_id).await?;
+        let enabled = record.as_ref().map(|t| t.enabled).unwrap_or(false);
+        let recovery_codes_remaining = if enabled {
+            self.repo.count_remaining_recovery_codes(user_id).await?
+        } else {
+            0
+        };
+        Ok(TwoFactorStatus {
+            enabled,
+            pending: record.as_ref().map(|t| !t.enabled).unwrap_or(false),
+            confirmed_at: record.and_then(|t| t.confirmed_at),
+            recovery_codes_remaining,
+        })
+    }
+
+    /// 生成新密钥（未确认前可重复调用，旧的待确认密钥作废）
+    ///
+    /// `account_label` 为验证器 App 中显示的账号名（用户名 / 邮箱）。
+    pub async fn begin_setup(&self, user_id: u64, account_label: &str) -> Result<TwoFactorSetup> {
+        let secret = totp::generate_secret();
+        if !self.repo.upsert_pending(user_id, &secret).await? {
+            return Err(ServerError::Validation(
+                "两步验证已启用，如需更换设备请先关闭".to_string(),
+            ));
+        }
+        let otpauth_uri = build_otpauth_uri(&self.issuer, account_label, &secret);
+        Ok(TwoFactorSetup {
+            secret,
+            otpauth_uri,
+        })
+    }
+
+    /// 用首个动态码确认绑定，返回恢复码明文（只此一次）
+    pub async fn enable(&self, user_id: u64, code: &str) -> Result<Vec<String>> {
+        let record = self
+            .repo
+            .get(user_id)
+            .await?
+            .ok_or_else(|| ServerError::Validation("请先生成两步验证密钥".to_string()))?;
+        if record.enabled {
+            return Err(ServerError::Validation("两步验证已启用".to_string()));
+        }
+
+        let secret = Self::decode_secret(&record.secret)?;
+        let step = totp::verify_totp(&secret, code, Self::now_secs())
+            .ok_or_else(|| ServerError::Validation("验证码错误".to_string()))?;
+
+        let codes = totp::generate_recovery_codes();
+        let hashes: Vec<String> = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+        if !self.repo.enable(user_id, step, &hashes).await? {
+            return Err(ServerError::Validation("两步验证已启用".to_string()));
+        }
+        Ok(codes)
+    }
+
+    /// 校验第二因子：动态码按时间步防重放，恢复码校验即核销
+    pub async fn verify(&self, user_id: u64, factor: &SecondFactor) -> Result<bool> {
+        match factor {
+            SecondFactor::Totp(code) => {
+                let Some(record) = self.repo.get(user_id).await? else {
+                    return Ok(false);
+                };
+                if !record.enabled {
+                    return Ok(false);
+                }
+                let secret = Self::decode_secret(&record.secret)?;
+                match totp::verify_totp(&secret, code, Self::now_secs()) {
+                    Some(step) => self.repo.consume_step(user_id, step).await,
+                    None => Ok(false),
+                }
+            }
+            SecondFactor::RecoveryCode(code) => {
+                self.repo
+                    .consume_recovery_code(user_id, &totp::hash_recovery_code(code))
+                    .await
+            }
+        }
+    }
+
+    /// 用户主动关闭（需验证当前动态码或恢复码）
+    pub async fn disable(&self, user_id: u64, factor: &SecondFactor) -> Result<()> {
+        if !self.is_enabled(user_id).await? {
+            return Err(ServerError::Validation("两步验证未启用".to_string()));
+        }
+        if !self.verify(user_id, factor).await? {
+            return Err(ServerError::Validation("验证码错误".to_string()));
+        }
+        self.repo.delete(user_id).await?;
+        Ok(())
+    }
+
+    /// 重新生成恢复码（旧码全部作废），需验证当前动态码
+    pub async fn regenerate_recovery_codes(&self, user_id: u64, code: &str) -> Result<Vec<String>> {
+        if !self.is_enabled(user_id).await? {
+            return Err(ServerError::Validation("两步验证未启用".to_string()));
+        }
+        if !self
+            .verify(user_id, &SecondFactor::Totp(code.to_string()))
+            .await?
+        {
+            return Err(ServerError::Validation("验证码错误".to_string()));
+        }
+        let codes = totp::generate_recovery_codes();
+        let hashes: Vec<String> = codes.iter().map(|c| totp::hash_recovery_code(c)).collect();
+        self.repo.replace_recovery_codes(user_id, &hashes).await?;
+        Ok(codes)
+    }
+
+    /// 管理员重置：清除密钥与恢复码，用户下次登录只需密码；原本未配置返回 false
+    pub async fn admin_reset(&self, user_id: u64) -> Result<bool> {
+        self.repo.delete(user_id).await
+    }
+
+    /// 密码通过后登记待验证的登录，返回 `(challenge_token, expires_at_ms)`
+    pub async fn create_challenge(&self, pending: &PendingLogin) -> Result<(String, i64)> {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let value = serde_json::to_string(pending)
+            .map_err(|e| ServerError::Serialization(format!("序列化登录 challenge 失败: {}", e)))?;
+        self.redis
+            .setex(
+                &Self::challenge_key(&token),
+                CHALLENGE_TTL_SECS as usize,
+                &value,
+            )
+            .await?;
+        let expires_at = chrono::Utc::now().timestamp_millis() + CHALLENGE_TTL_SECS as i64 * 1000;
+        Ok((token, expires_at))
+    }
+
+    /// 用第二因子完成 challenge，成功返回待签发 token 的登录信息（challenge 随即作废）
+    pub async fn complete_challenge(
+        &self,
+        token: &str,
+        factor: &SecondFactor,
+    ) -> Result<PendingLogin> {
+        let key = Self::challenge_key(token);
+        let attempts_key = Self::challenge_attempts_key(token);
+
+        // 先原子占用一次尝试机会，再比对动态码：并发的错误猜测各自拿到不同序号，
+        // 超出上限的请求根本不会走到比对，暴力尝试总数被 CHALLENGE_MAX_ATTEMPTS 封顶。
+        let attempt = self
+            .redis
+            .incr_if_exists(&key, &attempts_key)
+            .await?
+            .ok_or_else(|| ServerError::Unauthorized(CHALLENGE_EXPIRED_MSG.to_string()))?;
+        if attempt > CHALLENGE_MAX_ATTEMPTS as i64 {
+            self.discard_challenge(&key, &attempts_key).await;
+            return Err(ServerError::Unauthorized(TOO_MANY_ATTEMPTS_MSG.to_string()));
+        }
+
+        let raw = self
+            .redis
+            .get(&key)
+            .await?
+            .ok_or_else(|| ServerError::Unauthorized(CHALLENGE_EXPIRED_MSG.to_string()))?;
+        let pending: PendingLogin = serde_json::from_str(&raw)
+            .map_err(|e| ServerError::Serialization(format!("解析登录 challenge 失败: {}", e)))?;
+
+        if self.verify(pending.user_id, factor).await? {
+            // 并发提交时只有一个请求能取走 challenge
+            if self.redis.getdel(&key).await?.is_none() {
+                return Err(ServerError::Unauthorized(CHALLENGE_EXPIRED_MSG.to_string()));
+            }
+            let _ = self.redis.del(&attempts_key).await;
+            return Ok(pending);
+        }
+
+        if attempt >= CHALLENGE_MAX_ATTEMPTS as i64 {
+            self.discard_challenge(&key, &attempts_key).await;
+            return Err(ServerError::Unauthorized(TOO_MANY_ATTEMPTS_MSG.to_string()));
+        }
+        Err(ServerError::Unauthorized(format!(
+            "验证码错误，还可尝试 {} 次",
+            CHALLENGE_MAX_ATTEMPTS as i64 - attempt
+        )))
+    }
+
+    async fn discard_challenge(&self, key: &str, attempts_key: &str) {
+        for k in [key, attempts_key] {
+            if let Err(e) = self.redis.del(k).await {
+                tracing::warn!("⚠️ 清理两步验证 challenge 失败: key={}, err={}", k, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::redis_pool::key_slot;
+
+    /// 计数脚本同时访问 challenge 与计数器，Cluster 下两者必须落在同一 slot。
+    #[test]
+    fn challenge_and_attempt_counter_share_a_slot() {
+        for token in ["a", "0123456789abcdef0123456789abcdef"] {
+            assert_eq!(
+                key_slot(&TwoFactorService::challenge_key(token)),
+                key_slot(&TwoFactorService::challenge_attempts_key(token)),
+            );
+        }
+        assert_ne!(
+            TwoFactorService::challenge_key("a"),
+            TwoFactorService::challenge_attempts_key("a")
+        );
+    }
+}
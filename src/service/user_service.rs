@@ -309,6 +309,14 @@ impl UserService {
                     created.username.as_deref().unwrap_or("<none>"),
                     created.phone.as_deref().unwrap_or("<none>")
                 );
+                crate::service::emit_webhook(
+                    crate::service::webhook_service::WEBHOOK_EVENT_USER_REGISTERED,
+                    serde_json::json!({
+                        "user_id": created.id,
+                        "username": created.username,
+                        "source": "admin",
+                    }),
+                );
                 Ok(CreateUserOutcome::Created(created))
             }
             Err(DatabaseError::DuplicateEntry(msg)) => {
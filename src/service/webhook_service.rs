@@ -0,0 +1,593 @@
+// Copyright 2024 Shanghai Boyu Information Technology Co., Ltd.
+// https://privchat.dev
+//
+// Author: zoujiaqing <zoujiaqing@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 出站 Webhook 服务
+//!
+//! 与 [`crate::server_event`] 的区别：server_event 只投一个下游（application_url），
+//! best-effort 不重试；Webhook 面向管理员登记的多个外部端点，按事件类型订阅，
+//! 投递体用端点 secret 做 HMAC-SHA256 签名，投递日志落 PostgreSQL，失败按指数退避重试，
+//! 超过最大次数进入死信，由管理 API 查看 / 重放。
+//!
+//! 业务点通过 [`emit_webhook`] 触发：只入队不发网络请求，不阻塞也不影响业务结果。
+//! 实际投递由 [`WebhookService::spawn_worker`] 启动的后台任务完成，多节点可同时运行。
+//!
+//! 接收方校验：`X-Privchat-Signature: sha256=<hex(HMAC-SHA256(secret, body))>`，
+//! 按 `X-Privchat-Event-Id` 幂等（同一事件重试 / 重放的 event_id 不变）。
+
+use crate::error::{Result, ServerError};
+use crate::repository::{
+    ClaimedWebhookDelivery, NewWebhookEndpoint, WebhookDelivery, WebhookDeliveryFilter,
+    WebhookEndpoint, WebhookEndpointUpdate, WebhookRepository, DELIVERY_STATUS_DEAD,
+    DELIVERY_STATUS_PENDING, DELIVERY_STATUS_SUCCEEDED,
+};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+// ───────────────────────── event_type ─────────────────────────
+//
+// 命名沿用 server_event 的 `<domain>.<past_tense_action>` 约定。
+
+/// 消息已落库（每条消息一次，不按接收者展开）
+pub const WEBHOOK_EVENT_MESSAGE_COMMITTED: &str = "message.committed";
+/// 消息已撤回
+pub const WEBHOOK_EVENT_MESSAGE_REVOKED: &str = "message.revoked";
+/// 用户加入群组（主动加入 / 被邀请 / 审批通过 / 管理员添加）
+pub const WEBHOOK_EVENT_GROUP_MEMBER_JOINED: &str = "group.member_joined";
+/// 用户离开群组（主动退出 / 被移除）
+pub const WEBHOOK_EVENT_GROUP_MEMBER_LEFT: &str = "group.member_left";
+/// 新用户注册（含管理 API 创建）
+pub const WEBHOOK_EVENT_USER_REGISTERED: &str = "user.registered";
+/// 好友申请已通过
+pub const WEBHOOK_EVENT_FRIEND_ACCEPTED: &str = "friend.accepted";
+
+/// 可订阅的全部事件类型；端点 `event_types` 填 `*` 表示全部
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &[
+    WEBHOOK_EVENT_MESSAGE_COMMITTED,
+    WEBHOOK_EVENT_MESSAGE_REVOKED,
+    WEBHOOK_EVENT_GROUP_MEMBER_JOINED,
+    WEBHOOK_EVENT_GROUP_MEMBER_LEFT,
+    WEBHOOK_EVENT_USER_REGISTERED,
+    WEBHOOK_EVENT_FRIEND_ACCEPTED,
+];
+
+/// 订阅全部事件
+pub const WEBHOOK_EVENT_WILDCARD: &str = "*";
+
+// ───────────────────────── 投递参数 ─────────────────────────
+
+/// 签名头：`sha256=<hex>`
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Privchat-Signature";
+pub const WEBHOOK_EVENT_TYPE_HEADER: &str = "X-Privchat-Event";
+pub const WEBHOOK_EVENT_ID_HEADER: &str = "X-Privchat-Event-Id";
+pub const WEBHOOK_DELIVERY_ID_HEADER: &str = "X-Privchat-Delivery-Id";
+pub const WEBHOOK_ATTEMPT_HEADER: &str = "X-Privchat-Attempt";
+
+/// 最大尝试次数；第 N 次失败后等待 `RETRY_BASE_MS * 2^(N-1)`（封顶 `RETRY_MAX_DELAY_MS`）。
+/// 10 次约覆盖 1.5 小时的对端故障。
+pub const WEBHOOK_MAX_ATTEMPTS: i32 = 10;
+const RETRY_BASE_MS: i64 = 10_000;
+const RETRY_MAX_DELAY_MS: i64 = 3600 * 1000;
+
+/// 单次请求超时；租约要比它长，避免请求未结束就被别的节点重复领取
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CLAIM_LEASE_MS: i64 = 60_000;
+const CLAIM_BATCH: u32 = 50;
+
+/// 对端错误响应只保留前若干字符进日志
+const MAX_ERROR_LEN: usize = 512;
+
+/// 成功投递保留 7 天
+const SUCCEEDED_RETENTION_MS: i64 = 7 * 24 * 3600 * 1000;
+
+/// 投递体（body 在入队时定稿，重试 / 重放签名的是同一份字节）
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub event_id: String,
+    pub event_type: String,
+    /// 事件发生时间 Unix ms
+    pub created_at: i64,
+    pub data: Value,
+}
+
+/// `sha256=<hex(HMAC-SHA256(secret, body))>`
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 第 `attempts` 次失败后的等待时间；达到上限返回 None（进入死信）
+pub fn retry_delay_ms(attempts: i32) -> Option<i64> {
+    if attempts >= WEBHOOK_MAX_ATTEMPTS {
+        return None;
+    }
+    let exp = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    Some((RETRY_BASE_MS << exp).min(RETRY_MAX_DELAY_MS))
+}
+
+/// 新端点的签名密钥
+pub fn generate_secret() -> String {
+    format!("whsec_{}", hex::encode(rand::random::<[u8; 24]>()))
+}
+
+/// 校验并归一化订阅的事件类型（去重、保持顺序）
+pub fn normalize_event_types(event_types: &[String]) -> Result<Vec<String>> {
+    let mut out: Vec<String> = Vec::new();
+    for raw in event_types {
+        let t = raw.trim();
+        if t != WEBHOOK_EVENT_WILDCARD && !WEBHOOK_EVENT_TYPES.contains(&t) {
+            return Err(ServerError::Validation(format!(
+                "未知的 Webhook 事件类型: {}",
+                t
+            )));
+        }
+        if !out.iter().any(|e| e == t) {
+            out.push(t.to_string());
+        }
+    }
+    if out.is_empty() {
+        return Err(ServerError::Validation("event_types 不能为空".to_string()));
+    }
+    Ok(out)
+}
+
+fn validate_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| ServerError::Validation(format!("Webhook URL 无效: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(ServerError::Validation(
+            "Webhook URL 只支持 http / https".to_string(),
+        ));
+    }
+    Ok(url.to_string())
+}
+
+fn truncate_error(text: &str) -> String {
+    text.chars().take(MAX_ERROR_LEN).collect()
+}
+
+static GLOBAL_WEBHOOK_SERVICE: OnceLock<Arc<WebhookService>> = OnceLock::new();
+
+pub fn set_global_webhook_service(service: Arc<WebhookService>) {
+    let _ = GLOBAL_WEBHOOK_SERVICE.set(service);
+}
+
+pub fn get_global_webhook_service() -> Option<Arc<WebhookService>> {
+    GLOBAL_WEBHOOK_SERVICE.get().cloned()
+}
+
+/// 业务点触发 Webhook 事件：后台入队，失败只记日志
+pub fn emit_webhook(event_type: &'static str, data: Value) {
+    let Some(service) = get_global_webhook_service() else {
+        return;
+    };
+    if !service.is_subscribed(event_type) {
+        return;
+    }
+    tokio::spawn(async move {
+        if let Err(e) = service.emit(event_type, data).await {
+            warn!("⚠️ Webhook 事件入队失败: event_type={}, {}", event_type, e);
+        }
+    });
+}
+
+/// 出站 Webhook 服务
+pub struct WebhookService {
+    repo: Arc<WebhookRepository>,
+    http: reqwest::Client,
+    /// 启用端点订阅的事件类型缓存；None = 尚未加载（一律走 DB）。
+    /// 端点增删改时本节点立即刷新，其它节点由 worker 定期刷新。
+    subscriptions: RwLock<Option<HashSet<String>>>,
+}
+
+impl WebhookService {
+    pub fn new(repo: Arc<WebhookRepository>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            repo,
+            http,
+            subscriptions: RwLock::new(None),
+        }
+    }
+
+    /// 是否有启用端点订阅了该事件（无订阅时业务点直接跳过，不碰 DB）
+    pub fn is_subscribed(&self, event_type: &str) -> bool {
+        match &*self.subscriptions.read().unwrap_or_else(|e| e.into_inner()) {
+            Some(types) => types.contains(event_type) || types.contains(WEBHOOK_EVENT_WILDCARD),
+            None => true,
+        }
+    }
+
+    pub async fn refresh_subscriptions(&self) -> Result<()> {
+        let types: HashSet<String> = self
+            .repo
+            .subscribed_event_types()
+            .await?
+            .into_iter()
+            .collect();
+        let mut guard = self
+            .subscriptions
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        *guard = Some(types);
+        Ok(())
+    }
+
+    /// 为订阅端点入队一个事件，返回入队的投递数
+    pub async fn emit(&self, event_type: &str, data: Value) -> Result<u64> {
+        let event = WebhookEvent {
+            event_id: format!("whevt_{}", Uuid::new_v4().simple()),
+            event_type: event_type.to_string(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            data,
+        };
+        let body = serde_json::to_string(&event)
+            .map_err(|e| ServerError::Internal(format!("序列化 Webhook 事件失败: {}", e)))?;
+        let queued = self
+            .repo
+            .enqueue(&event.event_id, event_type, &body)
+            .await?;
+        debug!(
+            "📮 Webhook 事件入队: event_type={}, event_id={}, deliveries={}",
+            event_type, event.event_id, queued
+        );
+        Ok(queued)
+    }
+
+    // ───────────────────────── 端点管理 ─────────────────────────
+
+    pub async fn list_endpoints(&self) -> Result<Vec<WebhookEndpoint>> {
+        self.repo.list_endpoints().await
+    }
+
+    pub async fn get_endpoint(&self, endpoint_id: u64) -> Result<WebhookEndpoint> {
+        self.repo
+            .get_endpoint(endpoint_id)
+            .await?
+            .ok_or_else(|| ServerError::NotFound(format!("Webhook 端点 {} 不存在", endpoint_id)))
+    }
+
+    /// 创建端点；未指定 secret 时自动生成
+    pub async fn create_endpoint(
+        &self,
+        url: &str,
+        event_types: &[String],
+        secret: Option<String>,
+        description: String,
+        is_enabled: bool,
+    ) -> Result<WebhookEndpoint> {
+        let secret = match secret.map(|s| s.trim().to_string()) {
+            Some(s) if s.is_empty() => {
+                return Err(ServerError::Validation("secret 不能为空".to_string()))
+            }
+            Some(s) => s,
+            None => generate_secret(),
+        };
+        let endpoint = self
+            .repo
+            .create_endpoint(&NewWebhookEndpoint {
+                url: validate_url(url)?,
+                secret,
+                event_types: normalize_event_types(event_types)?,
+                description,
+                is_enabled,
+            })
+            .await?;
+        self.refresh_subscriptions_logged().await;
+        Ok(endpoint)
+    }
+
+    pub async fn update_endpoint(
+        &self,
+        endpoint_id: u64,
+        mut update: WebhookEndpointUpdate,
+    ) -> Result<WebhookEndpoint> {
+        if let Some(url) = update.url.as_deref() {
+            update.url = Some(validate_url(url)?);
+        }
+        if let Some(types) = update.event_types.as_deref() {
+            update.event_types = Some(normalize_event_types(types)?);
+        }
+        let endpoint = self
+            .repo
+            .update_endpoint(endpoint_id, &update)
+            .await?
+            .ok_or_else(|| ServerError::NotFound(format!("Webhook 端点 {} 不存在", endpoint_id)))?;
+        self.refresh_subscriptions_logged().await;
+        Ok(endpoint)
+    }
+
+    pub async fn delete_endpoint(&self, endpoint_id: u64) -> Result<()> {
+        if !self.repo.delete_endpoint(endpoint_id).await? {
+            return Err(ServerError::NotFound(format!(
+                "Webhook 端点 {} 不存在",
+                endpoint_id
+            )));
+        }
+        self.refresh_subscriptions_logged().await;
+        Ok(())
+    }
+
+    async fn refresh_subscriptions_logged(&self) {
+        if let Err(e) = self.refresh_subscriptions().await {
+            warn!("⚠️ 刷新 Webhook 订阅缓存失败: {}", e);
+        }
+    }
+
+    // ───────────────────────── 投递日志 ─────────────────────────
+
+    pub async fn list_deliveries(
+        &self,
+        filter: &WebhookDeliveryFilter,
+    ) -> Result<Vec<WebhookDelivery>> {
+        if let Some(status) = filter.status.as_deref() {
+            if ![
+                DELIVERY_STATUS_PENDING,
+                DELIVERY_STATUS_SUCCEEDED,
+                DELIVERY_STATUS_DEAD,
+            ]
+            .contains(&status)
+            {
+                return Err(ServerError::Validation(format!(
+                    "未知的投递状态: {}",
+                    status
+                )));
+            }
+        }
+        self.repo.list_deliveries(filter).await
+    }
+
+    pub async fn get_delivery(&self, delivery_id: u64) -> Result<WebhookDelivery> {
+        self.repo
+            .get_delivery(delivery_id)
+            .await?
+            .ok_or_else(|| ServerError::NotFound(format!("Webhook 投递 {} 不存在", delivery_id)))
+    }
+
+    /// 重放单条投递（死信或已成功的都可以）
+    pub async fn replay_delivery(&self, delivery_id: u64) -> Result<()> {
+        if !self.repo.replay_delivery(delivery_id).await? {
+            return Err(ServerError::NotFound(format!(
+                "Webhook 投递 {} 不存在",
+                delivery_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// 批量重放死信，返回条数
+    pub async fn replay_dead_letters(&self, endpoint_id: Option<u64>) -> Result<u64> {
+        self.repo.replay_dead(endpoint_id).await
+    }
+
+    // ───────────────────────── 投递 worker ─────────────────────────
+
+    /// 启动后台投递任务：每 2 秒领取一批到期投递，每 30 秒刷新订阅缓存，每小时清理旧的成功记录
+    pub fn spawn_worker(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(2));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut ticks: u64 = 0;
+            loop {
+                tick.tick().await;
+                if ticks % 15 == 0 {
+                    self.refresh_subscriptions_logged().await;
+                }
+                if ticks % 1800 == 0 {
+                    self.purge_succeeded().await;
+                }
+                ticks = ticks.wrapping_add(1);
+
+                // 一批满了说明还有积压，接着领，不等下一个 tick
+                loop {
+                    match self.deliver_due().await {
+                        Ok(n) if n as u32 >= CLAIM_BATCH => continue,
+                        Ok(_) => break,
+                        Err(e) => {
+                            warn!("⚠️ Webhook 投递轮询失败: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+        info!("✅ Webhook 投递 worker 已启动");
+    }
+
+    /// 领取并投递一批到期记录，返回本批条数
+    pub async fn deliver_due(&self) -> Result<usize> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let claimed = self
+            .repo
+            .claim_due(now, CLAIM_LEASE_MS, CLAIM_BATCH)
+            .await?;
+        let count = claimed.len();
+        futures::future::join_all(claimed.into_iter().map(|d| self.deliver_one(d))).await;
+        Ok(count)
+    }
+
+    async fn deliver_one(&self, delivery: ClaimedWebhookDelivery) {
+        let (status_code, error) = match self.post(&delivery).await {
+            Ok(code) => (Some(code), None),
+            Err((code, error)) => (code, Some(error)),
+        };
+        let succeeded = error.is_none();
+        let now = chrono::Utc::now().timestamp_millis();
+        let retry_at = if succeeded {
+            None
+        } else {
+            retry_delay_ms(delivery.attempts).map(|delay| now + delay)
+        };
+
+        if !succeeded {
+            if retry_at.is_some() {
+                debug!(
+                    "🔁 Webhook 投递失败，稍后重试: delivery_id={}, attempts={}, {}",
+                    delivery.delivery_id,
+                    delivery.attempts,
+                    error.as_deref().unwrap_or_default()
+                );
+            } else {
+                warn!(
+                    "☠️ Webhook 投递进入死信: delivery_id={}, endpoint_id={}, event_type={}, {}",
+                    delivery.delivery_id,
+                    delivery.endpoint_id,
+                    delivery.event_type,
+                    error.as_deref().unwrap_or_default()
+                );
+            }
+        }
+
+        if let Err(e) = self
+            .repo
+            .record_attempt(
+                delivery.delivery_id,
+                delivery.attempts,
+                succeeded,
+                retry_at,
+                status_code,
+                error.as_deref(),
+                now,
+            )
+            .await
+        {
+            warn!(
+                "⚠️ 记录 Webhook 投递结果失败: delivery_id={}, {}",
+                delivery.delivery_id, e
+            );
+        }
+    }
+
+    /// 发送一次，成功返回 HTTP 状态码；失败返回 (HTTP 状态码, 原因)
+    async fn post(
+        &self,
+        delivery: &ClaimedWebhookDelivery,
+    ) -> std::result::Result<i32, (Option<i32>, String)> {
+        let signature = sign_body(&delivery.secret, delivery.body.as_bytes());
+        let response = self
+            .http
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_SIGNATURE_HEADER, signature)
+            .header(WEBHOOK_EVENT_TYPE_HEADER, &delivery.event_type)
+            .header(WEBHOOK_EVENT_ID_HEADER, &delivery.event_id)
+            .header(WEBHOOK_DELIVERY_ID_HEADER, delivery.delivery_id.to_string())
+            .header(WEBHOOK_ATTEMPT_HEADER, delivery.attempts.to_string())
+            .body(delivery.body.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                let reason = if e.is_timeout() {
+                    "timeout".to_string()
+                } else {
+                    e.to_string()
+                };
+                (None, truncate_error(&reason))
+            })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(status.as_u16() as i32);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err((
+            Some(status.as_u16() as i32),
+            truncate_error(&format!("HTTP {}: {}", status.as_u16(), body)),
+        ))
+    }
+
+    async fn purge_succeeded(&self) {
+        const BATCH: i64 = 10_000;
+        let cutoff = chrono::Utc::now().timestamp_millis() - SUCCEEDED_RETENTION_MS;
+        let mut total: u64 = 0;
+        for _ in 0..20 {
+            match self.repo.purge_succeeded(cutoff, BATCH).await {
+                Ok(n) => {
+                    total += n;
+                    if (n as i64) < BATCH {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!("⚠️ Webhook 投递日志清理失败: {}", e);
+                    break;
+                }
+            }
+        }
+        if total > 0 {
+            info!("🧹 Webhook 投递日志清理：删除成功记录 {} 条", total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_matches_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_body("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn retry_delay_backs_off_and_gives_up() {
+        assert_eq!(retry_delay_ms(1), Some(RETRY_BASE_MS));
+        assert_eq!(retry_delay_ms(2), Some(RETRY_BASE_MS * 2));
+        assert_eq!(retry_delay_ms(5), Some(RETRY_BASE_MS * 16));
+        assert_eq!(
+            retry_delay_ms(WEBHOOK_MAX_ATTEMPTS - 1),
+            Some(RETRY_BASE_MS * 256)
+        );
+        assert_eq!(retry_delay_ms(WEBHOOK_MAX_ATTEMPTS), None);
+    }
+
+    #[test]
+    fn event_types_are_validated_and_deduplicated() {
+        let types = normalize_event_types(&[
+            "message.committed".to_string(),
+            " message.committed ".to_string(),
+            "*".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(types, vec!["message.committed", "*"]);
+        assert!(normalize_event_types(&["message.created".to_string()]).is_err());
+        assert!(normalize_event_types(&[]).is_err());
+    }
+
+    #[test]
+    fn only_http_urls_are_accepted() {
+        assert!(validate_url("https://example.com/hook").is_ok());
+        assert!(validate_url("ftp://example.com/hook").is_err());
+        assert!(validate_url("not a url").is_err());
+    }
+}
@@ -77,6 +77,7 @@ async fn rig_at(root: PathBuf, pool: Arc<sqlx::PgPool>, dir: Option<tempfile::Te
             file_service: Arc::new(file_service),
             upload_token_service: Arc::new(UploadTokenService::new()),
             auth: None,
+            data_export_service: None,
         },
         root,
         _dir: dir,
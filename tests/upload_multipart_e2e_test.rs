@@ -131,6 +131,7 @@ async fn rig_at(root: PathBuf, pool: Arc<sqlx::PgPool>, keep: Option<tempfile::T
             ),
             // 整包路径不要求登录态（已发版客户端只带上传 token），这里也就不装验证器。
             auth: None,
+            data_export_service: None,
         },
         root,
         _dir: dir,